@@ -0,0 +1,243 @@
+//! Append-only JSON Lines audit trail of API requests.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::error::{ApiError, Result};
+
+/// Shared handle to an audit log file.
+///
+/// Cloning is cheap; all clones append to the same file.
+#[derive(Debug, Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct AuditLog {
+    /// Path of the log file (for diagnostics).
+    path: PathBuf,
+    /// Open file handle, serialized across clients.
+    file: Arc<Mutex<File>>,
+}
+
+/// One audited API request (a single JSON line).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct AuditEntry {
+    /// Completion time (RFC 3339, UTC).
+    pub ts: String,
+    /// API client name (`syoboi`, `tmdb`).
+    pub client: &'static str,
+    /// API command or path (e.g. `ProgLookup`, `3/tv/1`).
+    pub command: String,
+    /// FNV-1a hash of the query string, to correlate identical requests.
+    pub params_hash: String,
+    /// Last HTTP status received (`None` when no response arrived).
+    pub status: Option<u16>,
+    /// Number of items in the parsed response (`None` on failure).
+    pub items: Option<usize>,
+    /// Total duration including retries, in milliseconds.
+    pub duration_ms: u64,
+    /// Number of retries (network and rate limit).
+    pub retries: u32,
+    /// Error message when the request failed.
+    pub error: Option<String>,
+}
+
+impl AuditLog {
+    /// Opens (or creates) the audit log for appending.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|source| ApiError::Io {
+                message: format!("failed to open audit log: {}", path.display()),
+                source,
+            })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Returns the log file path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an entry. Failures are logged and never interrupt the request.
+    pub fn record(&self, entry: &AuditEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to serialize audit entry");
+                return;
+            }
+        };
+        let Ok(mut file) = self.file.lock() else {
+            tracing::warn!("audit log lock poisoned");
+            return;
+        };
+        if let Err(e) = writeln!(file, "{line}") {
+            tracing::warn!(path = %self.path.display(), error = %e, "failed to write audit log");
+        }
+    }
+}
+
+/// In-flight request state collected by the retry loops.
+#[derive(Debug)]
+pub(crate) struct RequestAudit {
+    /// API client name.
+    client: &'static str,
+    /// API command or path.
+    command: String,
+    /// Start of the first attempt.
+    start: Instant,
+    /// Last HTTP status received.
+    pub(crate) status: Option<u16>,
+    /// Retries performed so far.
+    pub(crate) retries: u32,
+    /// Item count of the parsed response.
+    pub(crate) items: Option<usize>,
+}
+
+impl RequestAudit {
+    /// Starts tracking a request.
+    pub(crate) fn start(client: &'static str, command: &str) -> Self {
+        Self {
+            client,
+            command: command.to_owned(),
+            start: Instant::now(),
+            status: None,
+            retries: 0,
+            items: None,
+        }
+    }
+
+    /// Counts one retry.
+    pub(crate) const fn retry(&mut self) {
+        self.retries = self.retries.saturating_add(1);
+    }
+
+    /// Builds the final entry for a request with the given query string.
+    pub(crate) fn finish(self, query: Option<&str>, error: Option<&ApiError>) -> AuditEntry {
+        AuditEntry {
+            ts: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            client: self.client,
+            command: self.command,
+            params_hash: params_hash(query.unwrap_or_default()),
+            status: self.status,
+            items: if error.is_some() { None } else { self.items },
+            duration_ms: u64::try_from(self.start.elapsed().as_millis()).unwrap_or(u64::MAX),
+            retries: self.retries,
+            error: error.map(error_chain),
+        }
+    }
+}
+
+/// Formats `error` followed by its source chain, separated by `: `.
+fn error_chain(error: &ApiError) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+/// Returns the 64-bit FNV-1a hash of `params` as 16 hex digits.
+///
+/// Stable across runs and Rust versions, unlike `DefaultHasher`.
+#[must_use]
+pub fn params_hash(params: &str) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let hash = params
+        .bytes()
+        .fold(OFFSET, |h, b| (h ^ u64::from(b)).wrapping_mul(PRIME));
+    format!("{hash:016x}")
+}
+
+/// Counts items in a JSON response body: the `results` array for paged
+/// responses, the top-level array length, or 1 for any other object.
+pub(crate) fn json_item_count(body: &str) -> Option<usize> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    match &value {
+        serde_json::Value::Array(items) => Some(items.len()),
+        serde_json::Value::Object(map) => Some(
+            map.get("results")
+                .and_then(serde_json::Value::as_array)
+                .map_or(1, Vec::len),
+        ),
+        _ => Some(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    #[test]
+    fn test_params_hash_is_stable() {
+        // Act & Assert: FNV-1a reference values
+        assert_eq!(params_hash(""), "cbf29ce484222325");
+        assert_eq!(params_hash("a"), "af63dc4c8601ec8c");
+        assert_ne!(params_hash("TID=1"), params_hash("TID=2"));
+    }
+
+    #[test]
+    fn test_json_item_count() {
+        // Act & Assert
+        assert_eq!(json_item_count(r#"{"results":[1,2,3],"page":1}"#), Some(3));
+        assert_eq!(json_item_count("[1,2]"), Some(2));
+        assert_eq!(json_item_count(r#"{"id":1}"#), Some(1));
+        assert_eq!(json_item_count("not json"), None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_record_appends_json_lines() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = AuditLog::open(&path).unwrap();
+        let mut audit = RequestAudit::start("syoboi", "ProgLookup");
+        audit.status = Some(200);
+        audit.items = Some(5);
+        audit.retry();
+
+        // Act: clones share the same file
+        let shared = log.clone();
+        shared.record(&audit.finish(Some("Command=ProgLookup"), None));
+        let failed = RequestAudit::start("tmdb", "3/tv/1");
+        log.record(&failed.finish(None, Some(&ApiError::Invalid(String::from("boom")))));
+
+        // Assert
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["command"], "ProgLookup");
+        assert_eq!(lines[0]["status"], 200);
+        assert_eq!(lines[0]["items"], 5);
+        assert_eq!(lines[0]["retries"], 1);
+        assert_eq!(lines[0]["params_hash"], params_hash("Command=ProgLookup"));
+        assert_eq!(lines[1]["client"], "tmdb");
+        assert!(lines[1]["items"].is_null());
+        assert_eq!(lines[1]["error"], "boom");
+    }
+}
@@ -0,0 +1,228 @@
+//! Circuit breaker that stops sending requests to an API that keeps failing.
+//!
+//! Per-request retries and the caller's chunk backoff assume the API comes
+//! back soon. When it does not (an outage, or a long Cloudflare block), the
+//! breaker opens after `failure_threshold` consecutive failures, or at once
+//! on a `Retry-After` of [`LONG_BLOCK`] or more, and rejects requests with
+//! [`ApiError::CircuitOpen`] until the cooldown has passed. The first
+//! request after the cooldown is a trial: one more failure reopens the
+//! circuit immediately.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ApiError, Result};
+
+/// Default number of consecutive failures that opens the circuit.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// Default time the circuit stays open.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_mins(5);
+
+/// `Retry-After` treated as a long block that opens the circuit at once.
+pub const LONG_BLOCK: Duration = Duration::from_mins(10);
+
+/// Consecutive-failure circuit breaker for one API client.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct CircuitBreaker {
+    /// API client name, for logs and errors.
+    client: &'static str,
+    /// Consecutive failures that open the circuit (`0` disables it).
+    failure_threshold: u32,
+    /// Time the circuit stays open.
+    cooldown: Duration,
+    /// Consecutive failures so far.
+    failures: u32,
+    /// End of the open period, if open.
+    open_until: Option<Instant>,
+    /// Time source.
+    clock: Arc<dyn Clock>,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker that opens after `failure_threshold`
+    /// consecutive failures for `cooldown`. A threshold of `0` disables it.
+    #[must_use]
+    pub fn new(client: &'static str, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            client,
+            failure_threshold,
+            cooldown,
+            failures: 0,
+            open_until: None,
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Uses `clock` instead of the system clock.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns `true` while requests are being rejected.
+    #[must_use]
+    pub fn is_open(&self) -> bool {
+        self.open_until
+            .is_some_and(|until| self.clock.now() < until)
+    }
+
+    /// Checks whether a request may be sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::CircuitOpen`] with the remaining cooldown while
+    /// the circuit is open.
+    pub fn check(&mut self) -> Result<()> {
+        let Some(until) = self.open_until else {
+            return Ok(());
+        };
+        let now = self.clock.now();
+        if now < until {
+            let remaining = until.saturating_duration_since(now);
+            return Err(ApiError::CircuitOpen {
+                message: format!(
+                    "{} API circuit open after repeated failures; retry in {}s",
+                    self.client,
+                    remaining.as_secs()
+                ),
+                retry_after: remaining,
+            });
+        }
+        // Half-open: let a trial request through; one failure reopens.
+        tracing::info!(
+            client = self.client,
+            "Circuit half-open, sending a trial request"
+        );
+        self.open_until = None;
+        self.failures = self.failure_threshold.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Records the outcome of a request that was sent.
+    ///
+    /// Only failures that point at the server (transport errors, rate
+    /// limits, 5xx statuses) count; decode and client errors reset nothing
+    /// and count nothing.
+    pub fn record(&mut self, result: std::result::Result<(), &ApiError>) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        let error = match result {
+            Ok(()) => {
+                self.failures = 0;
+                return;
+            }
+            Err(e) => e,
+        };
+        let long_block = match error {
+            ApiError::Transport { .. } => None,
+            ApiError::RateLimited { retry_after, .. } => retry_after.filter(|d| *d >= LONG_BLOCK),
+            ApiError::HttpStatus { status, .. } if *status >= 500 => None,
+            _ => return,
+        };
+        self.failures = self.failures.saturating_add(1);
+        if let Some(block) = long_block {
+            self.open(block.max(self.cooldown), "server requested a long block");
+        } else if self.failures >= self.failure_threshold {
+            self.open(self.cooldown, "consecutive failures");
+        }
+    }
+
+    /// Opens the circuit for `duration`.
+    fn open(&mut self, duration: Duration, reason: &str) {
+        tracing::warn!(
+            client = self.client,
+            failures = self.failures,
+            cooldown_secs = duration.as_secs(),
+            "Circuit opened ({reason}); rejecting requests until the cooldown ends"
+        );
+        self.open_until = self.clock.now().checked_add(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::clock::FakeClock;
+
+    fn transport() -> ApiError {
+        ApiError::Transport {
+            kind: "connection error",
+            target: String::from("ProgLookup"),
+        }
+    }
+
+    #[test]
+    fn test_opens_after_threshold_and_half_opens_after_cooldown() {
+        // Arrange
+        let clock = Arc::new(FakeClock::new(SystemTime::UNIX_EPOCH));
+        let mut breaker =
+            CircuitBreaker::new("syoboi", 2, Duration::from_mins(1)).with_clock(clock.clone());
+
+        // Act / Assert: one failure keeps it closed, the second opens it
+        breaker.record(Err(&transport()));
+        assert!(breaker.check().is_ok());
+        breaker.record(Err(&transport()));
+        let err = breaker.check().unwrap_err();
+        assert!(err.is_circuit_open());
+        assert_eq!(err.retry_after(), Some(Duration::from_mins(1)));
+
+        // After the cooldown a trial is allowed; its failure reopens at once
+        clock.advance(Duration::from_mins(1));
+        assert!(breaker.check().is_ok());
+        breaker.record(Err(&transport()));
+        assert!(breaker.is_open());
+
+        // A successful trial closes it for good
+        clock.advance(Duration::from_mins(1));
+        assert!(breaker.check().is_ok());
+        breaker.record(Ok(()));
+        breaker.record(Err(&transport()));
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_long_retry_after_opens_immediately() {
+        // Arrange
+        let clock = Arc::new(FakeClock::new(SystemTime::UNIX_EPOCH));
+        let mut breaker =
+            CircuitBreaker::new("syoboi", 5, Duration::from_mins(1)).with_clock(clock.clone());
+        let blocked = ApiError::RateLimited {
+            message: String::from("blocked"),
+            retry_after: Some(Duration::from_hours(1)),
+        };
+
+        // Act
+        breaker.record(Err(&blocked));
+
+        // Assert: open for the requested hour, not the shorter cooldown
+        assert!(breaker.is_open());
+        clock.advance(Duration::from_mins(59));
+        assert!(breaker.check().is_err());
+    }
+
+    #[test]
+    fn test_client_errors_do_not_count() {
+        // Arrange
+        let mut breaker = CircuitBreaker::new("syoboi", 1, Duration::from_mins(1));
+        let not_found = ApiError::HttpStatus {
+            status: 404,
+            message: String::from("not found"),
+        };
+
+        // Act
+        breaker.record(Err(&not_found));
+        breaker.record(Err(&ApiError::Invalid(String::from("bad params"))));
+
+        // Assert
+        assert!(!breaker.is_open());
+    }
+}
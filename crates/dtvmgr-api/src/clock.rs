@@ -0,0 +1,142 @@
+//! Time source for rate limiters, retry backoff, and default time ranges.
+//!
+//! Clients use [`SystemClock`] unless another [`Clock`] is injected through
+//! their builders; [`FakeClock`] lets tests and library consumers drive
+//! time deterministically without real sleeps.
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+
+/// Future returned by [`Clock::sleep`].
+pub type Sleep<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// Source of the current time and of sleeps.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// Returns the current wall-clock time.
+    fn wall_now(&self) -> SystemTime;
+
+    /// Returns the current date and time in `tz`, independent of the
+    /// host timezone.
+    fn now_in(&self, tz: Tz) -> NaiveDateTime {
+        DateTime::<Utc>::from(self.wall_now())
+            .with_timezone(&tz)
+            .naive_local()
+    }
+
+    /// Waits for `duration`.
+    fn sleep(&self, duration: Duration) -> Sleep<'_>;
+}
+
+/// Clock backed by the system time and `tokio` timers.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// Returns the system clock as a shared [`Clock`].
+    #[must_use]
+    pub fn shared() -> Arc<dyn Clock> {
+        Arc::new(Self)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep<'_> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Manually driven clock: time only moves on [`Self::advance`] or
+/// [`Clock::sleep`], which returns immediately after advancing.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct FakeClock {
+    /// Monotonic time at creation.
+    base: Instant,
+    /// Wall-clock time at creation.
+    wall_base: SystemTime,
+    /// Time advanced since creation.
+    elapsed: Mutex<Duration>,
+}
+
+impl FakeClock {
+    /// Creates a clock whose wall-clock time starts at `wall_base`.
+    #[must_use]
+    pub fn new(wall_base: SystemTime) -> Self {
+        Self {
+            base: Instant::now(),
+            wall_base,
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let mut elapsed = self.elapsed.lock().unwrap_or_else(PoisonError::into_inner);
+        *elapsed = elapsed.saturating_add(duration);
+    }
+
+    /// Returns the total time advanced since creation.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.base.checked_add(self.elapsed()).unwrap_or(self.base)
+    }
+
+    fn wall_now(&self) -> SystemTime {
+        self.wall_base
+            .checked_add(self.elapsed())
+            .unwrap_or(self.wall_base)
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep<'_> {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fake_clock_sleep_advances_time() {
+        // Arrange
+        let clock = FakeClock::new(SystemTime::UNIX_EPOCH);
+        let start = clock.now();
+
+        // Act
+        clock.sleep(Duration::from_hours(1)).await;
+        clock.advance(Duration::from_secs(30));
+
+        // Assert
+        assert_eq!(clock.now().duration_since(start), Duration::from_secs(3630));
+        assert_eq!(
+            clock.wall_now(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(3630)
+        );
+        assert_eq!(clock.elapsed(), Duration::from_secs(3630));
+    }
+}
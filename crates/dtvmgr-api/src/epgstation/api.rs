@@ -1,7 +1,7 @@
 //! `EpgStationApi` trait definition.
 #![allow(clippy::future_not_send)]
 
-use anyhow::Result;
+use crate::error::Result;
 
 use super::types::{
     Channel, EncodeInfoResponse, EncodeRequest, EncodeResponse, EpgConfig, RecordedItem,
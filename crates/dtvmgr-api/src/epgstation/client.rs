@@ -3,13 +3,14 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{Context, Result, bail};
 use reqwest::Client;
 use tokio::sync::Mutex;
 use tracing::instrument;
 use url::Url;
 
-use crate::rate_limiter::SimpleRateLimiter;
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ApiError, Result};
+use crate::rate_limiter::{RateLimit, RateLimiter};
 
 use super::api::LocalEpgStationApi;
 use super::types::{
@@ -38,7 +39,9 @@ pub struct EpgStationClient {
     /// Base URL for API requests.
     base_url: Url,
     /// Rate limiter.
-    rate_limiter: Arc<Mutex<SimpleRateLimiter>>,
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    /// Time source for retry backoff.
+    clock: Arc<dyn Clock>,
 }
 
 /// Builder for `EpgStationClient`.
@@ -47,7 +50,9 @@ pub struct EpgStationClient {
 pub struct EpgStationClientBuilder {
     base_url: Option<Url>,
     user_agent: Option<String>,
+    rate_limit: Option<RateLimit>,
     min_interval: Option<Duration>,
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl EpgStationClientBuilder {
@@ -56,7 +61,9 @@ impl EpgStationClientBuilder {
         Self {
             base_url: None,
             user_agent: None,
+            rate_limit: None,
             min_interval: None,
+            clock: None,
         }
     }
 
@@ -74,6 +81,14 @@ impl EpgStationClientBuilder {
         self
     }
 
+    /// Replaces the whole rate limit (default: one request per 50ms).
+    /// `min_interval` still overrides the interval.
+    #[must_use]
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
     /// Sets the minimum request interval (default: 50ms).
     #[must_use]
     pub const fn min_interval(mut self, interval: Duration) -> Self {
@@ -81,6 +96,14 @@ impl EpgStationClientBuilder {
         self
     }
 
+    /// Uses `clock` for rate limiting and retry backoff (default: the
+    /// system clock).
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Builds the client.
     ///
     /// # Errors
@@ -88,31 +111,37 @@ impl EpgStationClientBuilder {
     /// - `user_agent` is not set.
     /// - `reqwest::Client` build fails.
     pub fn build(self) -> Result<EpgStationClient> {
-        let user_agent = self.user_agent.context("user_agent is required")?;
+        let user_agent = self
+            .user_agent
+            .ok_or_else(|| ApiError::Invalid(String::from("user_agent is required")))?;
 
         let base_url = if let Some(url) = self.base_url {
             url
         } else {
-            let result = Url::parse(DEFAULT_BASE_URL);
-            result.context("invalid default base URL")?
+            Url::parse(DEFAULT_BASE_URL)
+                .map_err(|e| ApiError::Invalid(format!("invalid default base URL: {e}")))?
         };
 
-        let rate_limiter = self
-            .min_interval
-            .map_or_else(super::rate_limiter::default_limiter, |interval| {
-                SimpleRateLimiter::new(interval, "epgstation")
-            });
+        let clock = self.clock.unwrap_or_else(SystemClock::shared);
+        let mut limit = self
+            .rate_limit
+            .unwrap_or_else(super::rate_limiter::default_rate_limit);
+        if let Some(interval) = self.min_interval {
+            limit.min_interval = interval;
+        }
+        let rate_limiter = RateLimiter::new("epgstation", limit).with_clock(Arc::clone(&clock));
 
         let http_client = Client::builder()
             .user_agent(&user_agent)
             .gzip(true)
             .build()
-            .context("failed to build HTTP client")?;
+            .map_err(|e| ApiError::Invalid(format!("failed to build HTTP client: {e}")))?;
 
         Ok(EpgStationClient {
             http_client,
             base_url,
             rate_limiter: Arc::new(Mutex::new(rate_limiter)),
+            clock,
         })
     }
 }
@@ -140,7 +169,7 @@ impl EpgStationClient {
         loop {
             let request = build_request()
                 .build()
-                .with_context(|| format!("failed to build request: {path}"))?;
+                .map_err(|_| ApiError::Invalid(format!("failed to build request: {path}")))?;
 
             tracing::Span::current().record("url.full", tracing::field::display(request.url()));
 
@@ -159,12 +188,11 @@ impl EpgStationClient {
                     continue;
                 }
                 Err(e) => {
-                    let kind = crate::classify_reqwest_error(&e);
                     let status_code = e.status().map(|s| i64::from(s.as_u16()));
                     if let Some(code) = status_code {
                         tracing::Span::current().record("http.response.status_code", code);
                     }
-                    bail!("{kind}: {path}");
+                    return Err(ApiError::transport(&e, path));
                 }
             };
 
@@ -178,14 +206,21 @@ impl EpgStationClient {
 
                 rate_limit_retries = rate_limit_retries.saturating_add(1);
                 if rate_limit_retries > MAX_RETRIES {
-                    bail!("EPGStation API rate limit exceeded after {MAX_RETRIES} retries: {path}");
+                    return Err(ApiError::RateLimited {
+                        message: format!(
+                            "EPGStation API rate limit exceeded after {MAX_RETRIES} retries: {path}"
+                        ),
+                        retry_after: crate::error::retry_after(response.headers()),
+                    });
                 }
                 tracing::warn!(
                     retry = rate_limit_retries,
                     max_retries = MAX_RETRIES,
                     "EPGStation API rate limited (429). Retrying..."
                 );
-                tokio::time::sleep(RETRY_BACKOFF.saturating_mul(rate_limit_retries)).await;
+                self.clock
+                    .sleep(RETRY_BACKOFF.saturating_mul(rate_limit_retries))
+                    .await;
                 self.rate_limiter.lock().await.wait().await;
                 continue;
             }
@@ -198,21 +233,28 @@ impl EpgStationClient {
                 });
                 span.record("http.response.body.size", body.len());
                 tracing::debug!(http.response.body = %body, "HTTP response body");
-                bail!("EPGStation API error (HTTP {status}): {body}");
+                return Err(ApiError::HttpStatus {
+                    status: status.as_u16(),
+                    message: format!("EPGStation API error (HTTP {status}): {body}"),
+                });
             }
 
             let body = response
                 .text()
                 .await
-                .with_context(|| format!("failed to read response body: {path}"))?;
+                .map_err(|e| ApiError::transport(&e, path))?;
             span.record("http.response.body.size", body.len());
             tracing::debug!(http.response.body = %body, "HTTP response body");
             // SECURITY: do not include response body in error context — it is
             // already recorded in the tracing span and may contain sensitive data.
-            let parsed: T = serde_json::from_str(&body).with_context(|| {
-                format!(
-                    "failed to decode JSON response: {path} (body_len={} bytes)",
-                    body.len()
+            let parsed: T = serde_json::from_str(&body).map_err(|e| {
+                ApiError::decode(
+                    format!(
+                        "failed to decode JSON response: {path} (body_len={} bytes)",
+                        body.len()
+                    ),
+                    &body,
+                    e,
                 )
             })?;
 
@@ -240,7 +282,7 @@ impl EpgStationClient {
         let url = self
             .base_url
             .join(path)
-            .with_context(|| format!("failed to join URL path: {path}"))?;
+            .map_err(|e| ApiError::Invalid(format!("failed to join URL path: {path}: {e}")))?;
 
         self.request_with_retry(path, "GET", || {
             self.http_client.get(url.clone()).query(query)
@@ -272,7 +314,7 @@ impl EpgStationClient {
         let url = self
             .base_url
             .join(path)
-            .with_context(|| format!("failed to join URL path: {path}"))?;
+            .map_err(|e| ApiError::Invalid(format!("failed to join URL path: {path}: {e}")))?;
 
         self.request_with_retry(path, "POST", || {
             self.http_client.post(url.clone()).json(body)
@@ -742,8 +784,9 @@ mod tests {
         let result: Result<Vec<Channel>> = client.fetch_channels().await;
 
         // Assert
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("rate limit"));
+        let err = result.unwrap_err();
+        assert!(err.is_rate_limited());
+        assert!(err.to_string().contains("rate limit"));
     }
 
     #[cfg_attr(miri, ignore)]
@@ -0,0 +1,203 @@
+//! Structured error type returned by the API clients.
+//!
+//! Callers can branch on [`ApiError`] variants (e.g. wait and retry on
+//! [`ApiError::RateLimited`]) instead of matching error messages.
+
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+/// Result type used throughout `dtvmgr-api`.
+pub type Result<T, E = ApiError> = std::result::Result<T, E>;
+
+/// Maximum number of characters kept in [`ApiError::Decode`] previews.
+const PREVIEW_CHARS: usize = 200;
+
+/// Error returned by the API clients.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+#[allow(clippy::module_name_repetitions)]
+pub enum ApiError {
+    /// The server kept answering HTTP 429 after every retry.
+    #[error("{message}")]
+    RateLimited {
+        /// Human-readable description including the request target.
+        message: String,
+        /// Wait requested by the last `Retry-After` header, if any.
+        retry_after: Option<Duration>,
+    },
+
+    /// The server answered with a non-success HTTP status or API result code.
+    #[error("{message}")]
+    HttpStatus {
+        /// HTTP status (or the Syoboi `<Result><Code>` value).
+        status: u16,
+        /// Human-readable description including the server message.
+        message: String,
+    },
+
+    /// The response body could not be decoded.
+    #[error("{message}")]
+    Decode {
+        /// Human-readable description including the body length.
+        message: String,
+        /// Leading part of the response body.
+        ///
+        /// SECURITY: not included in the `Display` output; the full body is
+        /// already recorded in the tracing span.
+        preview: String,
+        /// Underlying decoder error.
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
+    /// The request could not be sent or its response could not be read.
+    ///
+    /// SECURITY: only the classified kind is kept — the raw
+    /// `reqwest::Error` may carry request context including auth headers.
+    #[error("{kind}: {target}")]
+    Transport {
+        /// Classified error kind (e.g. `timeout`, `connection error`).
+        kind: &'static str,
+        /// API command or path of the failed request.
+        target: String,
+    },
+
+    /// The client's circuit breaker is open after repeated failures; the
+    /// request was not sent.
+    #[error("{message}")]
+    CircuitOpen {
+        /// Human-readable description including the remaining cooldown.
+        message: String,
+        /// Time until the circuit lets a trial request through.
+        retry_after: Duration,
+    },
+
+    /// Invalid arguments or client configuration.
+    #[error("{0}")]
+    Invalid(String),
+
+    /// Local file I/O failed (audit log, rate limit state).
+    #[error("{message}")]
+    Io {
+        /// Human-readable description including the path.
+        message: String,
+        /// Underlying I/O error.
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl ApiError {
+    /// Builds a [`Self::Decode`] error, keeping a short preview of `body`.
+    pub(crate) fn decode(
+        message: String,
+        body: &str,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Decode {
+            message,
+            preview: body.chars().take(PREVIEW_CHARS).collect(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Builds a [`Self::Transport`] error from a `reqwest::Error`.
+    pub(crate) fn transport(e: &reqwest::Error, target: &str) -> Self {
+        Self::Transport {
+            kind: crate::classify_reqwest_error(e),
+            target: target.to_owned(),
+        }
+    }
+
+    /// Returns `true` for [`Self::RateLimited`].
+    #[must_use]
+    pub const fn is_rate_limited(&self) -> bool {
+        matches!(self, Self::RateLimited { .. })
+    }
+
+    /// Returns `true` for [`Self::CircuitOpen`].
+    #[must_use]
+    pub const fn is_circuit_open(&self) -> bool {
+        matches!(self, Self::CircuitOpen { .. })
+    }
+
+    /// Returns the wait requested by the server for [`Self::RateLimited`],
+    /// or the remaining cooldown for [`Self::CircuitOpen`].
+    #[must_use]
+    pub const fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => *retry_after,
+            Self::CircuitOpen { retry_after, .. } => Some(*retry_after),
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP status for [`Self::HttpStatus`].
+    #[must_use]
+    pub const fn status(&self) -> Option<u16> {
+        match self {
+            Self::HttpStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a `Retry-After` header given in seconds.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::panic)]
+
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_keeps_preview_out_of_display() {
+        // Arrange
+        let body = "x".repeat(500);
+        let source = serde_json::from_str::<u32>("oops").unwrap_err();
+
+        // Act
+        let err = ApiError::decode(String::from("failed to decode"), &body, source);
+
+        // Assert
+        assert_eq!(err.to_string(), "failed to decode");
+        let ApiError::Decode { preview, .. } = &err else {
+            panic!("expected Decode, got {err:?}");
+        };
+        assert_eq!(preview.len(), PREVIEW_CHARS);
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_retry_after_accessors() {
+        // Arrange
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        let limited = ApiError::RateLimited {
+            message: String::from("rate limited"),
+            retry_after: retry_after(&headers),
+        };
+        let status = ApiError::HttpStatus {
+            status: 404,
+            message: String::from("not found"),
+        };
+
+        // Act & Assert
+        assert!(limited.is_rate_limited());
+        assert_eq!(limited.retry_after(), Some(Duration::from_secs(30)));
+        assert_eq!(limited.status(), None);
+        assert!(!status.is_rate_limited());
+        assert_eq!(status.status(), Some(404));
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! HTTP transport settings shared by the API clients.
+//!
+//! Without an explicit proxy, `reqwest` picks up the system proxy from the
+//! `HTTP_PROXY` / `HTTPS_PROXY` / `ALL_PROXY` environment variables
+//! (honoring `NO_PROXY`). An explicit [`HttpOptions::proxy`] replaces them
+//! but still honors `NO_PROXY`.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::{Certificate, ClientBuilder, NoProxy, Proxy};
+
+use crate::error::{ApiError, Result};
+
+/// Proxy, timeout, and TLS settings of an API client. Unset values keep
+/// the `reqwest` defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct HttpOptions {
+    /// Proxy URL used for all requests (e.g. `http://proxy.local:3128`).
+    pub proxy: Option<String>,
+    /// Total timeout of one request, including reading the body.
+    pub timeout: Option<Duration>,
+    /// Timeout of the connect phase only.
+    pub connect_timeout: Option<Duration>,
+    /// PEM file with extra root certificates trusted in addition to the
+    /// built-in roots (e.g. the CA of a TLS-inspecting proxy).
+    pub root_ca: Option<PathBuf>,
+}
+
+impl HttpOptions {
+    /// Applies the set values to `builder`.
+    ///
+    /// # Errors
+    ///
+    /// - `proxy` is not a valid URL.
+    /// - `root_ca` cannot be read or contains no PEM certificate.
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder> {
+        if let Some(url) = &self.proxy {
+            // SECURITY: the URL may embed credentials, so it is not echoed.
+            let proxy = Proxy::all(url.as_str())
+                .map_err(|_| ApiError::Invalid(String::from("invalid proxy URL")))?
+                .no_proxy(NoProxy::from_env());
+            builder = builder.proxy(proxy);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(path) = &self.root_ca {
+            let pem = std::fs::read(path).map_err(|e| {
+                ApiError::Invalid(format!(
+                    "failed to read root CA file {}: {e}",
+                    path.display()
+                ))
+            })?;
+            let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+                ApiError::Invalid(format!("invalid root CA file {}: {e}", path.display()))
+            })?;
+            if certs.is_empty() {
+                return Err(ApiError::Invalid(format!(
+                    "no certificate found in root CA file {}",
+                    path.display()
+                )));
+            }
+            builder = builder.tls_certs_merge(certs);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_apply_defaults_builds_client() {
+        // Arrange
+        let options = HttpOptions::default();
+
+        // Act
+        let result = options.apply(reqwest::Client::builder());
+
+        // Assert
+        assert!(result.unwrap().build().is_ok());
+    }
+
+    #[test]
+    fn test_apply_rejects_invalid_proxy_without_echoing_it() {
+        // Arrange
+        let options = HttpOptions {
+            proxy: Some(String::from("user:secret@::not a url")),
+            ..HttpOptions::default()
+        };
+
+        // Act
+        let err = options.apply(reqwest::Client::builder()).unwrap_err();
+
+        // Assert
+        assert!(matches!(err, ApiError::Invalid(_)));
+        assert!(!err.to_string().contains("secret"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_apply_rejects_root_ca_without_certificates() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ca.pem");
+        std::fs::write(&path, "not a certificate\n").unwrap();
+        let options = HttpOptions {
+            root_ca: Some(path),
+            ..HttpOptions::default()
+        };
+
+        // Act
+        let err = options.apply(reqwest::Client::builder()).unwrap_err();
+
+        // Assert
+        assert!(err.to_string().contains("ca.pem"), "{err}");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_apply_missing_root_ca_is_error() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let options = HttpOptions {
+            root_ca: Some(dir.path().join("missing.pem")),
+            ..HttpOptions::default()
+        };
+
+        // Act
+        let err = options.apply(reqwest::Client::builder()).unwrap_err();
+
+        // Assert
+        assert!(err.to_string().starts_with("failed to read root CA file"));
+    }
+}
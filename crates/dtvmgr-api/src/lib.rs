@@ -3,15 +3,36 @@
 //! Provides clients for the Syoboi Calendar API, the TMDB API,
 //! and the `EPGStation` API.
 
+/// JSON Lines audit trail of API requests.
+pub mod audit;
+
+/// Circuit breaker for API clients that keep failing.
+pub mod circuit_breaker;
+
+/// Injectable time source.
+pub mod clock;
+
 /// `EPGStation` API client.
 pub mod epgstation;
 
+/// Structured error type returned by the API clients.
+pub mod error;
+
+/// Proxy, timeout, and TLS settings shared by the HTTP clients.
+pub mod http;
+
 /// OTel metrics instruments for API clients.
 #[cfg(feature = "otel")]
 mod metrics;
 
-/// Simple single-tier rate limiter shared across API clients.
-mod rate_limiter;
+/// Process-wide metrics in the Prometheus text format.
+pub mod prometheus;
+
+/// Token bucket rate limiter shared across API clients.
+pub mod rate_limiter;
+
+/// Offline record/replay of HTTP responses.
+pub mod replay;
 
 /// Syoboi Calendar API client.
 pub mod syoboi;
@@ -0,0 +1,237 @@
+//! Process-wide metrics in the Prometheus text exposition format.
+//!
+//! The API clients count requests and rate-limiter waits here; the CLI adds
+//! sync results, and `dtvmgr-server` renders everything on `/metrics`.
+//! Unlike the OTel instruments this needs no collector: the registry is
+//! always on and costs one mutex lock per update.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+/// Registry shared by the whole process.
+static GLOBAL: LazyLock<Registry> = LazyLock::new(Registry::default);
+
+/// Prometheus metric type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Monotonically increasing value.
+    Counter,
+    /// Value that can go up and down.
+    Gauge,
+}
+
+impl Kind {
+    /// Returns the `# TYPE` keyword.
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+        }
+    }
+}
+
+/// Label set of one sample, sorted by name.
+type Labels = Vec<(&'static str, String)>;
+
+/// All samples of one metric name.
+#[derive(Debug)]
+struct Family {
+    /// `# HELP` text.
+    help: &'static str,
+    /// `# TYPE` of the metric.
+    kind: Kind,
+    /// Current value per label set.
+    samples: BTreeMap<Labels, f64>,
+}
+
+/// Collection of counters and gauges keyed by name and labels.
+#[derive(Debug, Default)]
+pub struct Registry {
+    /// Metric families keyed by name.
+    families: Mutex<BTreeMap<&'static str, Family>>,
+}
+
+impl Registry {
+    /// Adds `value` to the counter `name` with `labels`.
+    pub fn add(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) {
+        self.update(name, help, Kind::Counter, labels, |v| *v += value);
+    }
+
+    /// Sets the gauge `name` with `labels` to `value`.
+    pub fn set(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&'static str, &str)],
+        value: f64,
+    ) {
+        self.update(name, help, Kind::Gauge, labels, |v| *v = value);
+    }
+
+    /// Applies `f` to one sample, creating it at zero.
+    fn update(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        kind: Kind,
+        labels: &[(&'static str, &str)],
+        f: impl FnOnce(&mut f64),
+    ) {
+        let Ok(mut families) = self.families.lock() else {
+            tracing::warn!("metrics registry lock poisoned");
+            return;
+        };
+        let family = families.entry(name).or_insert_with(|| Family {
+            help,
+            kind,
+            samples: BTreeMap::new(),
+        });
+        let mut key: Labels = labels.iter().map(|&(k, v)| (k, v.to_owned())).collect();
+        key.sort_unstable();
+        f(family.samples.entry(key).or_insert(0.0));
+    }
+
+    /// Renders every metric in the Prometheus text format (version 0.0.4).
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let Ok(families) = self.families.lock() else {
+            return out;
+        };
+        for (name, family) in families.iter() {
+            let _ = writeln!(out, "# HELP {name} {}", family.help);
+            let _ = writeln!(out, "# TYPE {name} {}", family.kind.as_str());
+            for (labels, value) in &family.samples {
+                out.push_str(name);
+                if !labels.is_empty() {
+                    out.push('{');
+                    for (i, (key, val)) in labels.iter().enumerate() {
+                        if i > 0 {
+                            out.push(',');
+                        }
+                        let _ = write!(out, "{key}=\"{}\"", escape_label(val));
+                    }
+                    out.push('}');
+                }
+                let _ = writeln!(out, " {value}");
+            }
+        }
+        out
+    }
+}
+
+/// Returns the process-wide registry.
+#[must_use]
+pub fn global() -> &'static Registry {
+    &GLOBAL
+}
+
+/// Counts one finished API request.
+///
+/// Numeric path segments of `endpoint` (TMDB IDs) are collapsed to `{id}`
+/// to keep the label set small.
+pub(crate) fn record_request(client: &'static str, endpoint: &str, status: Option<u16>) {
+    let status = status.map_or_else(|| String::from("none"), |s| s.to_string());
+    global().add(
+        "dtvmgr_api_requests_total",
+        "API requests by client, endpoint, and last HTTP status.",
+        &[
+            ("client", client),
+            ("endpoint", &endpoint_label(endpoint)),
+            ("status", &status),
+        ],
+        1.0,
+    );
+}
+
+/// Counts one rate-limiter wait that had to sleep.
+pub(crate) fn record_rate_limit_wait(client: &'static str, waited: Duration) {
+    if waited.is_zero() {
+        return;
+    }
+    let registry = global();
+    registry.add(
+        "dtvmgr_rate_limit_waits_total",
+        "Requests delayed by the rate limiter.",
+        &[("client", client)],
+        1.0,
+    );
+    registry.add(
+        "dtvmgr_rate_limit_wait_seconds_total",
+        "Time spent waiting for the rate limiter.",
+        &[("client", client)],
+        waited.as_secs_f64(),
+    );
+}
+
+/// Replaces numeric path segments (except a leading API version) with `{id}`.
+fn endpoint_label(endpoint: &str) -> String {
+    endpoint
+        .split('/')
+        .enumerate()
+        .map(|(i, segment)| {
+            if i > 0 && !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Escapes a label value (`\`, `"`, and newlines).
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counters_and_gauges() {
+        // Arrange
+        let registry = Registry::default();
+
+        // Act
+        registry.add("c_total", "A counter.", &[("b", "2"), ("a", "1")], 1.0);
+        registry.add("c_total", "A counter.", &[("a", "1"), ("b", "2")], 2.5);
+        registry.set("g", "A gauge.", &[], 3.0);
+        registry.set("g", "A gauge.", &[], 7.0);
+        let text = registry.render();
+
+        // Assert
+        assert_eq!(
+            text,
+            "# HELP c_total A counter.\n\
+             # TYPE c_total counter\n\
+             c_total{a=\"1\",b=\"2\"} 3.5\n\
+             # HELP g A gauge.\n\
+             # TYPE g gauge\n\
+             g 7\n"
+        );
+    }
+
+    #[test]
+    fn test_endpoint_label_collapses_ids_and_escapes() {
+        // Act / Assert
+        assert_eq!(
+            endpoint_label("3/tv/1234/season/2"),
+            "3/tv/{id}/season/{id}"
+        );
+        assert_eq!(endpoint_label("ProgLookup"), "ProgLookup");
+        assert_eq!(escape_label("a\"b\\c\n"), "a\\\"b\\\\c\\n");
+    }
+}
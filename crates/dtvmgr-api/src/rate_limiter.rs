@@ -1,51 +1,570 @@
-//! Simple single-tier rate limiter shared across API clients.
+//! Rate limiter shared by all API clients.
+//!
+//! A [`RateLimiter`] combines a token bucket (one token per `min_interval`,
+//! up to `burst` tokens), optional sliding-window quotas (e.g. Syoboi's
+//! hourly and daily limits), a server-requested backoff, and random jitter
+//! on throttled requests. Each client passes its own [`RateLimit`], so hosts
+//! are configured independently.
+//!
+//! Request timestamps and any backoff can be persisted to a small JSON state
+//! file so window counters survive across process runs and are shared by
+//! processes running at the same time (e.g. the daemon and a CLI command).
+//! Timestamps are stored as Unix milliseconds and converted back to
+//! [`Instant`]s on load; entries older than the longest window are dropped.
+//! A request is admitted under an exclusive advisory lock on
+//! `<state file>.lock`: the limiter re-reads the file, merges the requests
+//! other processes recorded, re-checks the limits, and writes the retained
+//! window back before releasing the lock.
 
-use std::time::{Duration, Instant};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-/// Single-tier rate limiter enforcing a minimum interval between requests.
-///
-/// Used by `EPGStation` and TMDB clients. `SyoboiRateLimiter` uses a separate
-/// three-tier limiter due to hourly/daily limits.
-#[derive(Debug)]
-pub struct SimpleRateLimiter {
-    /// Minimum interval between requests.
-    min_interval: Duration,
-    /// Last request timestamp.
-    last_request: Option<Instant>,
-    /// Client label for `OTel` metrics (always stored for uniform API).
-    #[cfg_attr(not(feature = "otel"), allow(dead_code))]
-    client: &'static str,
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ApiError, Result};
+
+/// Sliding-window quota: at most `limit` requests per `period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateWindow {
+    /// Window length.
+    pub period: Duration,
+    /// Requests allowed within `period`.
+    pub limit: usize,
+}
+
+/// Rate limit settings of one API host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Minimum average interval between requests.
+    pub min_interval: Duration,
+    /// Requests allowed back-to-back before `min_interval` applies (at least 1).
+    pub burst: u32,
+    /// Maximum random delay added to requests that had to wait.
+    pub jitter: Duration,
+    /// Sliding-window quotas, checked after the token bucket.
+    pub windows: Vec<RateWindow>,
 }
 
-impl SimpleRateLimiter {
-    /// Creates a new rate limiter with the given minimum interval.
-    pub const fn new(min_interval: Duration, client: &'static str) -> Self {
+impl RateLimit {
+    /// Creates a limit of one request per `min_interval` without burst,
+    /// jitter, or windows.
+    #[must_use]
+    pub const fn interval(min_interval: Duration) -> Self {
         Self {
             min_interval,
-            last_request: None,
+            burst: 1,
+            jitter: Duration::ZERO,
+            windows: Vec::new(),
+        }
+    }
+
+    /// Sets the burst size (values below 1 are treated as 1).
+    #[must_use]
+    pub const fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Sets the maximum jitter.
+    #[must_use]
+    pub const fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Adds a sliding-window quota, replacing any window with the same period.
+    #[must_use]
+    pub fn window(mut self, period: Duration, limit: usize) -> Self {
+        self.windows.retain(|w| w.period != period);
+        self.windows.push(RateWindow { period, limit });
+        self
+    }
+
+    /// Returns how long request timestamps must be kept.
+    fn retention(&self) -> Duration {
+        self.windows
+            .iter()
+            .map(|w| w.period)
+            .fold(self.min_interval, Duration::max)
+    }
+}
+
+/// Counters of a [`RateLimiter`], also reported through `tracing`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RateLimiterStats {
+    /// Requests let through.
+    pub requests: u64,
+    /// Requests that had to wait.
+    pub throttled: u64,
+    /// Total time spent waiting.
+    pub waited: Duration,
+}
+
+/// On-disk rate limit state.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct PersistedState {
+    /// Request times within the longest window (Unix milliseconds).
+    #[serde(default)]
+    requests: Vec<u64>,
+    /// Time until which all requests are held back (Unix milliseconds).
+    #[serde(default)]
+    blocked_until: Option<u64>,
+}
+
+/// Token bucket rate limiter with sliding windows, backoff, and jitter.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RateLimiter {
+    /// Client label for logs and metrics (e.g. "syoboi").
+    client: &'static str,
+    /// Limit settings.
+    limit: RateLimit,
+    /// Theoretical arrival time of the next request (GCRA); `None` before
+    /// the first request.
+    next_slot: Option<Instant>,
+    /// Request timestamps within [`RateLimit::retention`].
+    history: VecDeque<Instant>,
+    /// Server-requested backoff shared by all queued requests.
+    blocked_until: Option<Instant>,
+    /// State file for persisting counters across runs.
+    state_file: Option<PathBuf>,
+    /// Time source.
+    clock: Arc<dyn Clock>,
+    /// Request and wait counters.
+    stats: RateLimiterStats,
+    /// Xorshift state for jitter.
+    jitter_seed: u64,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter for `client` with the given limit.
+    #[must_use]
+    pub fn new(client: &'static str, limit: RateLimit) -> Self {
+        let mut limiter = Self {
             client,
+            limit,
+            next_slot: None,
+            history: VecDeque::new(),
+            blocked_until: None,
+            state_file: None,
+            clock: SystemClock::shared(),
+            stats: RateLimiterStats::default(),
+            jitter_seed: 0,
+        };
+        limiter.reseed();
+        limiter
+    }
+
+    /// Uses `clock` instead of the system clock. Call before
+    /// [`Self::with_state_file`] so restored state uses the same clock.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self.reseed();
+        self
+    }
+
+    /// Persists state to `path`, restoring any counters already stored there.
+    ///
+    /// A missing file starts from empty counters; an unreadable one is logged
+    /// and ignored so a corrupt state file never blocks requests.
+    #[must_use]
+    pub fn with_state_file(mut self, path: PathBuf) -> Self {
+        match load_state(&path) {
+            Ok(Some(state)) => self.restore(&state, self.clock.now(), self.clock.wall_now()),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(
+                client = self.client,
+                path = %path.display(),
+                error = %e,
+                "ignoring unreadable rate limit state"
+            ),
+        }
+        self.state_file = Some(path);
+        self
+    }
+
+    /// Returns the limit settings.
+    #[must_use]
+    pub const fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    /// Changes the minimum interval of later requests, e.g. when the server
+    /// reports a smaller remaining quota.
+    pub const fn set_min_interval(&mut self, interval: Duration) {
+        self.limit.min_interval = interval;
+    }
+
+    /// Returns the request and wait counters.
+    #[must_use]
+    pub const fn stats(&self) -> RateLimiterStats {
+        self.stats
+    }
+
+    /// Holds back every queued request for at least `delay`.
+    ///
+    /// Used when the server answers 429 so the backoff applies globally
+    /// instead of only to the request that was rejected.
+    pub fn back_off(&mut self, delay: Duration) {
+        let lock = self.lock_shared_state();
+        let until = self.clock.now().checked_add(delay);
+        if until > self.blocked_until {
+            self.blocked_until = until;
         }
+        self.persist();
+        drop(lock);
     }
 
     /// Waits until the next request is allowed.
-    #[allow(clippy::arithmetic_side_effects)]
+    ///
+    /// Sleeps for any backoff, then until a bucket token is available and
+    /// every window has room, plus jitter if any of these made it wait. With
+    /// a state file, the limits are re-checked against the requests of other
+    /// processes before the request is admitted.
     pub async fn wait(&mut self) {
         #[cfg(feature = "otel")]
         let wait_start = Instant::now();
-        let now = Instant::now();
+        let start = self.clock.now();
+        let mut jittered = false;
 
-        if let Some(last) = self.last_request {
-            let elapsed = now.duration_since(last);
-            if elapsed < self.min_interval {
-                tokio::time::sleep(self.min_interval.saturating_sub(elapsed)).await;
+        loop {
+            // 1. Backoff, token bucket, and sliding windows
+            let now = self.clock.now();
+            self.cleanup_history(now);
+            if let Some(delay) = self.required_delay(now, true) {
+                self.clock.sleep(delay).await;
+                continue;
+            }
+
+            // 2. Jitter on throttled requests
+            if !jittered && self.clock.now() > start {
+                jittered = true;
+                let jitter = self.next_jitter();
+                if !jitter.is_zero() {
+                    self.clock.sleep(jitter).await;
+                    continue;
+                }
+            }
+
+            // 3. Record the request, unless another process took the room
+            if self.try_admit() {
+                break;
             }
         }
 
-        self.last_request = Some(Instant::now());
+        let waited = self.clock.now().saturating_duration_since(start);
+        self.record(waited);
+        crate::prometheus::record_rate_limit_wait(self.client, waited);
 
         #[cfg(feature = "otel")]
         crate::metrics::record_rate_limit_wait(self.client, wait_start);
     }
+
+    /// Returns how long a request at `now` must wait for the backoff, a
+    /// bucket token, and room in every window; `None` if it may go now.
+    /// With `log`, waits for the backoff or a full window are reported.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn required_delay(&self, now: Instant, log: bool) -> Option<Duration> {
+        let mut ready = now;
+
+        if let Some(until) = self.blocked_until
+            && now < until
+        {
+            if log {
+                tracing::warn!(
+                    client = self.client,
+                    remaining_secs = (until - now).as_secs(),
+                    "Backoff in effect. Waiting..."
+                );
+            }
+            ready = ready.max(until);
+        }
+
+        // The next slot may be up to `burst - 1` intervals ahead
+        if let Some(slot) = self.next_slot {
+            let ahead = self
+                .limit
+                .min_interval
+                .saturating_mul(self.limit.burst.max(1) - 1);
+            if let Some(slot_ready) = slot.checked_sub(ahead) {
+                ready = ready.max(slot_ready);
+            }
+        }
+
+        for window in &self.limit.windows {
+            if let Some(window_ready) = self.window_ready(*window, now)
+                && now < window_ready
+            {
+                if log {
+                    tracing::warn!(
+                        client = self.client,
+                        period_secs = window.period.as_secs(),
+                        limit = window.limit,
+                        remaining_secs = (window_ready - now).as_secs(),
+                        "Rate limit window full. Waiting..."
+                    );
+                }
+                ready = ready.max(window_ready);
+            }
+        }
+
+        (now < ready).then(|| ready - now)
+    }
+
+    /// Returns when `window` has room for another request, or `None` if it
+    /// has room already.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn window_ready(&self, window: RateWindow, now: Instant) -> Option<Instant> {
+        let window_start = now.checked_sub(window.period)?;
+        let first = self.history.partition_point(|&t| t < window_start);
+        let count = self.history.len() - first;
+        if window.limit == 0 || count < window.limit {
+            return None;
+        }
+        let oldest = self.history.get(first + count - window.limit)?;
+        oldest.checked_add(window.period)
+    }
+
+    /// Records a request at the current time if the limits, re-checked with
+    /// the requests other processes stored in the state file, still allow
+    /// it. Returns `false` (recording nothing) if they no longer do.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn try_admit(&mut self) -> bool {
+        let lock = self.lock_shared_state();
+        let now = self.clock.now();
+        if self.required_delay(now, false).is_some() {
+            return false;
+        }
+        self.next_slot =
+            Some(self.next_slot.map_or(now, |slot| slot.max(now)) + self.limit.min_interval);
+        self.history.push_back(now);
+        self.persist();
+        drop(lock);
+        true
+    }
+
+    /// Locks the state file, if configured, and merges the requests and
+    /// backoff stored there. The lock is held until the returned file is
+    /// dropped; without a state file, or if locking fails (logged), `None`
+    /// is returned.
+    fn lock_shared_state(&mut self) -> Option<File> {
+        let path = self.state_file.clone()?;
+        let lock = lock_state(&path)
+            .inspect_err(|e| {
+                tracing::warn!(
+                    client = self.client,
+                    path = %path.display(),
+                    error = %e,
+                    "failed to lock rate limit state"
+                );
+            })
+            .ok();
+        match load_state(&path) {
+            Ok(Some(state)) => self.restore(&state, self.clock.now(), self.clock.wall_now()),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(
+                client = self.client,
+                path = %path.display(),
+                error = %e,
+                "ignoring unreadable rate limit state"
+            ),
+        }
+        lock
+    }
+
+    /// Updates the counters and reports throttled requests.
+    fn record(&mut self, waited: Duration) {
+        self.stats.requests = self.stats.requests.saturating_add(1);
+        if waited.is_zero() {
+            return;
+        }
+        self.stats.throttled = self.stats.throttled.saturating_add(1);
+        self.stats.waited = self.stats.waited.saturating_add(waited);
+        tracing::debug!(
+            client = self.client,
+            wait_ms = u64::try_from(waited.as_millis()).unwrap_or(u64::MAX),
+            requests = self.stats.requests,
+            throttled = self.stats.throttled,
+            total_wait_ms = u64::try_from(self.stats.waited.as_millis()).unwrap_or(u64::MAX),
+            "Rate limiter delayed request"
+        );
+    }
+
+    /// Seeds the jitter generator from the clock's wall time.
+    fn reseed(&mut self) {
+        let nanos = self
+            .clock
+            .wall_now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        // Xorshift must not start at zero.
+        self.jitter_seed = u64::from(nanos) | 1;
+    }
+
+    /// Returns a pseudo-random delay in `[0, jitter]`.
+    fn next_jitter(&mut self) -> Duration {
+        let max = u64::try_from(self.limit.jitter.as_micros()).unwrap_or(u64::MAX);
+        if max == 0 {
+            return Duration::ZERO;
+        }
+        let mut x = self.jitter_seed;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.jitter_seed = x;
+        Duration::from_micros(x.checked_rem(max.saturating_add(1)).unwrap_or(0))
+    }
+
+    /// Merges persisted wall-clock state into the history.
+    ///
+    /// The stored requests replace the history, since every admitted
+    /// request is written there; local requests newer than the newest
+    /// stored one (e.g. after a failed write) are kept. The later of the
+    /// two backoffs wins.
+    fn restore(&mut self, state: &PersistedState, now: Instant, wall_now: SystemTime) {
+        let mut requests: Vec<Instant> = state
+            .requests
+            .iter()
+            .filter_map(|&ms| to_instant(ms, now, wall_now))
+            .collect();
+        requests.sort_unstable();
+        let newest = requests.last().copied();
+        requests.extend(
+            self.history
+                .iter()
+                .filter(|&&t| newest.is_none_or(|newest| t > newest)),
+        );
+        let stored_slot = newest.and_then(|last| last.checked_add(self.limit.min_interval));
+        self.next_slot = self.next_slot.max(stored_slot);
+        self.history = requests.into_iter().collect();
+        let stored_block = state
+            .blocked_until
+            .and_then(|ms| to_unix_ms(wall_now).and_then(|w| ms.checked_sub(w)))
+            .and_then(|ahead| now.checked_add(Duration::from_millis(ahead)));
+        self.blocked_until = self.blocked_until.max(stored_block);
+        self.cleanup_history(now);
+    }
+
+    /// Converts the history into wall-clock state.
+    fn snapshot(&self, now: Instant, wall_now: SystemTime) -> PersistedState {
+        let to_ms = |t: &Instant| {
+            wall_now
+                .checked_sub(now.saturating_duration_since(*t))
+                .and_then(to_unix_ms)
+        };
+        PersistedState {
+            requests: self.history.iter().filter_map(to_ms).collect(),
+            blocked_until: self
+                .blocked_until
+                .filter(|&until| until > now)
+                .and_then(|until| wall_now.checked_add(until.saturating_duration_since(now)))
+                .and_then(to_unix_ms),
+        }
+    }
+
+    /// Writes the retained window and backoff to the state file, if
+    /// configured. Callers hold the state file lock.
+    ///
+    /// Failures are logged and never interrupt the request.
+    fn persist(&mut self) {
+        if self.state_file.is_none() {
+            return;
+        }
+        let now = self.clock.now();
+        self.cleanup_history(now);
+        let Some(path) = &self.state_file else {
+            return;
+        };
+        let state = self.snapshot(now, self.clock.wall_now());
+        if let Err(e) = save_state(path, &state) {
+            tracing::warn!(
+                client = self.client,
+                path = %path.display(),
+                error = %e,
+                "failed to save rate limit state"
+            );
+        }
+    }
+
+    /// Removes timestamps older than the longest window.
+    fn cleanup_history(&mut self, now: Instant) {
+        if let Some(cutoff) = now.checked_sub(self.limit.retention()) {
+            while self.history.front().is_some_and(|&t| t < cutoff) {
+                self.history.pop_front();
+            }
+        }
+    }
+}
+
+/// Reads the state file. Returns `None` when it does not exist.
+fn load_state(path: &Path) -> Result<Option<PersistedState>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(io_error("read", path)(e)),
+    };
+    serde_json::from_slice(&bytes).map(Some).map_err(|e| {
+        ApiError::decode(
+            format!("failed to parse {}", path.display()),
+            &String::from_utf8_lossy(&bytes),
+            e,
+        )
+    })
+}
+
+/// Takes an exclusive advisory lock on `<path>.lock`, creating it if
+/// needed. The lock is released when the returned file is dropped.
+fn lock_state(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error("create", parent))?;
+    }
+    let lock_path = path.with_extension("json.lock");
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(io_error("open", &lock_path))?;
+    file.lock().map_err(io_error("lock", &lock_path))?;
+    Ok(file)
+}
+
+/// Writes the state file atomically (temporary file + rename).
+fn save_state(path: &Path, state: &PersistedState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error("create", parent))?;
+    }
+    let json = serde_json::to_vec(state)
+        .map_err(|e| ApiError::Invalid(format!("failed to serialize rate limit state: {e}")))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(io_error("write", &tmp))?;
+    std::fs::rename(&tmp, path).map_err(io_error("replace", path))
+}
+
+/// Returns a mapper turning an I/O error into [`ApiError::Io`] for `path`.
+fn io_error(action: &str, path: &Path) -> impl FnOnce(std::io::Error) -> ApiError {
+    let message = format!("failed to {action} {}", path.display());
+    move |source| ApiError::Io { message, source }
+}
+
+/// Converts a wall-clock time into Unix milliseconds.
+fn to_unix_ms(time: SystemTime) -> Option<u64> {
+    let ms = time.duration_since(UNIX_EPOCH).ok()?.as_millis();
+    u64::try_from(ms).ok()
+}
+
+/// Converts Unix milliseconds into an `Instant` relative to `now`.
+///
+/// Returns `None` for times in the future or before the `Instant` epoch.
+fn to_instant(ms: u64, now: Instant, wall_now: SystemTime) -> Option<Instant> {
+    let age = to_unix_ms(wall_now)?.checked_sub(ms)?;
+    now.checked_sub(Duration::from_millis(age))
 }
 
 #[cfg(test)]
@@ -55,11 +574,26 @@ mod tests {
     use std::time::Duration;
 
     use super::*;
+    use crate::clock::FakeClock;
+
+    /// Limit with Syoboi-style hourly and daily windows.
+    fn windowed(min_interval: Duration, hourly: usize, daily: usize) -> RateLimit {
+        RateLimit::interval(min_interval)
+            .window(Duration::from_hours(1), hourly)
+            .window(Duration::from_hours(24), daily)
+    }
+
+    /// Limiter driven by a fake clock.
+    fn fake(limit: RateLimit) -> (RateLimiter, Arc<FakeClock>) {
+        let clock = Arc::new(FakeClock::new(SystemTime::now()));
+        let limiter = RateLimiter::new("test", limit).with_clock(clock.clone());
+        (limiter, clock)
+    }
 
     #[tokio::test]
     async fn test_first_request_no_wait() {
         // Arrange
-        let mut limiter = SimpleRateLimiter::new(Duration::from_secs(1), "test");
+        let mut limiter = RateLimiter::new("test", RateLimit::interval(Duration::from_secs(1)));
 
         // Act
         let start = Instant::now();
@@ -73,7 +607,7 @@ mod tests {
     #[tokio::test]
     async fn test_min_interval() {
         // Arrange
-        let mut limiter = SimpleRateLimiter::new(Duration::from_millis(50), "test");
+        let mut limiter = RateLimiter::new("test", RateLimit::interval(Duration::from_millis(50)));
 
         // Act
         let start = Instant::now();
@@ -86,14 +620,269 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_records_timestamp() {
+    async fn test_min_interval_with_fake_clock() {
+        // Arrange
+        let (mut limiter, clock) = fake(RateLimit::interval(Duration::from_secs(10)));
+
+        // Act
+        limiter.wait().await;
+        limiter.wait().await;
+
+        // Assert
+        assert_eq!(clock.elapsed(), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_burst_allows_back_to_back_requests() {
+        // Arrange
+        let (mut limiter, clock) = fake(RateLimit::interval(Duration::from_secs(1)).burst(3));
+
+        // Act: three requests fit the bucket, the fourth waits one interval
+        for _ in 0..3 {
+            limiter.wait().await;
+        }
+        let after_burst = clock.elapsed();
+        limiter.wait().await;
+
+        // Assert
+        assert_eq!(after_burst, Duration::ZERO);
+        assert_eq!(clock.elapsed(), Duration::from_secs(1));
+        assert_eq!(limiter.stats().requests, 4);
+        assert_eq!(limiter.stats().throttled, 1);
+        assert_eq!(limiter.stats().waited, Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_burst_refills_over_time() {
+        // Arrange
+        let (mut limiter, clock) = fake(RateLimit::interval(Duration::from_secs(1)).burst(2));
+        limiter.wait().await;
+        limiter.wait().await;
+
+        // Act: after two idle intervals the bucket is full again
+        clock.advance(Duration::from_secs(2));
+        limiter.wait().await;
+        limiter.wait().await;
+
+        // Assert
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+        assert_eq!(limiter.stats().throttled, 0);
+    }
+
+    #[tokio::test]
+    async fn test_jitter_only_on_throttled_requests() {
+        // Arrange
+        let limit = RateLimit::interval(Duration::from_secs(1)).jitter(Duration::from_millis(500));
+        let (mut limiter, clock) = fake(limit);
+
+        // Act
+        limiter.wait().await;
+        let first = clock.elapsed();
+        limiter.wait().await;
+
+        // Assert
+        assert_eq!(first, Duration::ZERO);
+        assert!(clock.elapsed() >= Duration::from_secs(1));
+        assert!(clock.elapsed() <= Duration::from_millis(1500));
+    }
+
+    #[tokio::test]
+    async fn test_records_history() {
+        // Arrange
+        let mut limiter = RateLimiter::new("test", windowed(Duration::ZERO, 500, 10_000));
+
+        // Act
+        for _ in 0..5 {
+            limiter.wait().await;
+        }
+
+        // Assert
+        assert_eq!(limiter.history.len(), 5);
+        assert!(limiter.next_slot.is_some());
+        assert_eq!(limiter.stats().requests, 5);
+    }
+
+    #[tokio::test]
+    async fn test_hourly_limit_waits_on_fake_clock() {
+        // Arrange
+        let (mut limiter, clock) = fake(windowed(Duration::ZERO, 2, 10_000));
+
+        // Act: the third request exceeds the hourly limit
+        limiter.wait().await;
+        limiter.wait().await;
+        limiter.wait().await;
+
+        // Assert
+        assert_eq!(clock.elapsed(), Duration::from_hours(1));
+    }
+
+    #[tokio::test]
+    async fn test_daily_limit_waits_on_fake_clock() {
+        // Arrange
+        let (mut limiter, clock) = fake(windowed(Duration::ZERO, 10_000, 2));
+
+        // Act: the third request exceeds the daily limit
+        limiter.wait().await;
+        limiter.wait().await;
+        limiter.wait().await;
+
+        // Assert
+        assert_eq!(clock.elapsed(), Duration::from_hours(24));
+    }
+
+    #[tokio::test]
+    async fn test_back_off_delays_next_request() {
         // Arrange
-        let mut limiter = SimpleRateLimiter::new(Duration::from_millis(0), "test");
+        let (mut limiter, clock) = fake(RateLimit::interval(Duration::ZERO));
 
         // Act
+        limiter.back_off(Duration::from_secs(30));
         limiter.wait().await;
 
         // Assert
-        assert!(limiter.last_request.is_some());
+        assert_eq!(clock.elapsed(), Duration::from_secs(30));
+        assert_eq!(limiter.stats().throttled, 1);
+    }
+
+    #[test]
+    fn test_window_replaces_same_period() {
+        // Arrange & Act
+        let limit =
+            windowed(Duration::from_secs(1), 500, 10_000).window(Duration::from_hours(1), 100);
+
+        // Assert
+        assert_eq!(limit.windows.len(), 2);
+        assert!(limit.windows.contains(&RateWindow {
+            period: Duration::from_hours(1),
+            limit: 100,
+        }));
+        assert_eq!(limit.retention(), Duration::from_hours(24));
+    }
+
+    #[test]
+    fn test_cleanup_history_keeps_longest_window() {
+        // Arrange: 2 hours ago (daily only), 2 days ago (expired), now
+        let mut limiter = RateLimiter::new("test", windowed(Duration::ZERO, 500, 10_000));
+        let now = Instant::now();
+        limiter
+            .history
+            .push_back(now.checked_sub(Duration::from_hours(48)).unwrap());
+        limiter
+            .history
+            .push_back(now.checked_sub(Duration::from_hours(2)).unwrap());
+        limiter.history.push_back(now);
+
+        // Act
+        limiter.cleanup_history(now);
+
+        // Assert
+        assert_eq!(limiter.history.len(), 2);
+    }
+
+    #[test]
+    fn test_cleanup_history_without_windows() {
+        // Arrange
+        let mut limiter = RateLimiter::new("test", RateLimit::interval(Duration::from_secs(1)));
+        let now = Instant::now();
+        limiter
+            .history
+            .push_back(now.checked_sub(Duration::from_secs(5)).unwrap());
+        limiter.history.push_back(now);
+
+        // Act
+        limiter.cleanup_history(now);
+
+        // Assert
+        assert_eq!(limiter.history.len(), 1);
+    }
+
+    #[test]
+    fn test_state_file_restores_counters_and_backoff() {
+        // Arrange: one run makes a request and is told to back off
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache").join("syoboi_rate_limit.json");
+        let mut first = RateLimiter::new("test", windowed(Duration::ZERO, 500, 10_000))
+            .with_state_file(path.clone());
+        first.history.push_back(Instant::now());
+        first.back_off(Duration::from_mins(1));
+
+        // Act: a new process loads the same file
+        let second =
+            RateLimiter::new("test", windowed(Duration::ZERO, 500, 10_000)).with_state_file(path);
+
+        // Assert
+        assert_eq!(second.history.len(), 1);
+        assert!(second.next_slot.is_some());
+        let remaining = second.blocked_until.unwrap().duration_since(Instant::now());
+        assert!(remaining > Duration::from_secs(55));
+    }
+
+    #[tokio::test]
+    async fn test_state_file_limits_are_shared_across_limiters() {
+        // Arrange: two processes on the same state file, 3 requests per hour
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syoboi_rate_limit.json");
+        let wall_ms = to_unix_ms(SystemTime::now()).unwrap();
+        let wall_base = UNIX_EPOCH
+            .checked_add(Duration::from_millis(wall_ms))
+            .unwrap();
+        let clock_a = Arc::new(FakeClock::new(wall_base));
+        let clock_b = Arc::new(FakeClock::new(wall_base));
+        let mut a = RateLimiter::new("test", windowed(Duration::ZERO, 3, 10_000))
+            .with_clock(clock_a.clone())
+            .with_state_file(path.clone());
+        let mut b = RateLimiter::new("test", windowed(Duration::ZERO, 3, 10_000))
+            .with_clock(clock_b.clone())
+            .with_state_file(path.clone());
+
+        // Act: 2 + 1 requests fill the window, so the fourth has to wait
+        a.wait().await;
+        a.wait().await;
+        b.wait().await;
+        a.wait().await;
+
+        // Assert
+        assert_eq!(clock_b.elapsed(), Duration::ZERO);
+        assert_eq!(clock_a.elapsed(), Duration::from_hours(1));
+        let stored = load_state(&path).unwrap().unwrap();
+        assert_eq!(stored.requests.len(), 4);
+    }
+
+    #[test]
+    #[allow(clippy::arithmetic_side_effects)]
+    fn test_restore_drops_expired_entries() {
+        // Arrange: 2 hours ago (daily only), 2 days ago (expired), 1 minute ago
+        let mut limiter = RateLimiter::new("test", windowed(Duration::ZERO, 500, 10_000));
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+        let wall_ms = to_unix_ms(wall_now).unwrap();
+        let state = PersistedState {
+            requests: vec![wall_ms - 7_200_000, wall_ms - 172_800_000, wall_ms - 60_000],
+            blocked_until: Some(wall_ms - 1_000),
+        };
+
+        // Act
+        limiter.restore(&state, now, wall_now);
+
+        // Assert
+        assert_eq!(limiter.history.len(), 2);
+        assert!(limiter.blocked_until.is_none());
+        assert_eq!(limiter.snapshot(now, wall_now).requests.len(), 2);
+    }
+
+    #[test]
+    fn test_with_state_file_ignores_corrupt_file() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syoboi_rate_limit.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        // Act
+        let limiter =
+            RateLimiter::new("test", windowed(Duration::ZERO, 500, 10_000)).with_state_file(path);
+
+        // Assert
+        assert!(limiter.history.is_empty());
+        assert!(limiter.state_file.is_some());
     }
 }
@@ -0,0 +1,310 @@
+//! Offline record/replay of HTTP responses.
+//!
+//! A [`Cassette`] in record mode saves every response a client receives
+//! under `<dir>/<client>/`; in replay mode the client answers requests from
+//! those files without touching the network, so a `db sync` run can be
+//! reproduced in tests or demos.
+//!
+//! Responses are keyed by HTTP method, path, and query string. The host is
+//! not part of the key, so a recording made against the live API replays
+//! against any base URL. Request headers (tokens, Basic auth) are never
+//! stored.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::params_hash;
+use crate::error::{ApiError, Result};
+
+/// Whether a [`Cassette`] saves live responses or serves saved ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests and save each response.
+    Record,
+    /// Serve saved responses; a request without one fails.
+    Replay,
+}
+
+/// Directory of recorded HTTP responses shared by the API clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cassette {
+    /// Root directory of the recordings.
+    dir: PathBuf,
+    /// Record or replay.
+    mode: CassetteMode,
+}
+
+/// One recorded response (a JSON file).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RecordedResponse {
+    /// HTTP method of the request.
+    method: String,
+    /// Path and query of the request, for reading the file.
+    target: String,
+    /// HTTP status.
+    status: u16,
+    /// `Retry-After` header, kept so replayed 429s behave the same.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_after: Option<String>,
+    /// Response body.
+    body: String,
+}
+
+impl Cassette {
+    /// Creates a cassette that saves responses under `dir`.
+    #[must_use]
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Record,
+        }
+    }
+
+    /// Creates a cassette that serves the responses saved under `dir`.
+    #[must_use]
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Replay,
+        }
+    }
+
+    /// Returns the recordings directory.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the mode.
+    #[must_use]
+    pub const fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Returns `true` in replay mode.
+    #[must_use]
+    pub fn is_replay(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    /// Returns the saved response for `request` in replay mode, or `None`
+    /// in record mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::Io`] if no response was recorded for the request,
+    /// or [`ApiError::Decode`] if the recording is unreadable.
+    fn replay_response(
+        &self,
+        client: &'static str,
+        request: &reqwest::Request,
+    ) -> Result<Option<reqwest::Response>> {
+        if !self.is_replay() {
+            return Ok(None);
+        }
+        let target = request_target(request.url());
+        let path = self.path(client, request.method().as_str(), &target);
+        let json = std::fs::read_to_string(&path).map_err(|source| ApiError::Io {
+            message: format!(
+                "no recorded response for {} {target}: {}",
+                request.method(),
+                path.display()
+            ),
+            source,
+        })?;
+        let recorded: RecordedResponse = serde_json::from_str(&json).map_err(|e| {
+            ApiError::decode(format!("failed to parse {}", path.display()), &json, e)
+        })?;
+        tracing::debug!(client, path = %path.display(), "Replaying recorded response");
+        to_response(recorded).map(Some)
+    }
+
+    /// Saves `response` in record mode and returns an equivalent response;
+    /// returns it unchanged in replay mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::Transport`] if the body cannot be read, or
+    /// [`ApiError::Io`] if the recording cannot be written.
+    async fn record_response(
+        &self,
+        client: &'static str,
+        method: &reqwest::Method,
+        url: &url::Url,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response> {
+        if self.is_replay() {
+            return Ok(response);
+        }
+        let target = request_target(url);
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ApiError::transport(&e, &target))?;
+        let recorded = RecordedResponse {
+            method: method.as_str().to_owned(),
+            target,
+            status,
+            retry_after,
+            body,
+        };
+        let path = self.path(client, &recorded.method, &recorded.target);
+        save(&path, &recorded)?;
+        tracing::debug!(client, path = %path.display(), "Recorded response");
+        to_response(recorded)
+    }
+
+    /// Returns the file of the response to `method` `target`.
+    fn path(&self, client: &str, method: &str, target: &str) -> PathBuf {
+        self.dir
+            .join(client)
+            .join(format!("{method}-{}.json", params_hash(target)))
+    }
+}
+
+/// Sends `request`, or answers it from `cassette` in replay mode.
+///
+/// The outer error is a cassette failure; the inner result is the live
+/// transport outcome, left for the caller's retry loop to classify.
+///
+/// # Errors
+///
+/// Returns an error if a replayed response is missing or a recording cannot
+/// be saved.
+pub(crate) async fn execute(
+    http: &reqwest::Client,
+    cassette: Option<&Cassette>,
+    client: &'static str,
+    request: reqwest::Request,
+) -> Result<std::result::Result<reqwest::Response, reqwest::Error>> {
+    let Some(cassette) = cassette else {
+        return Ok(http.execute(request).await);
+    };
+    if let Some(response) = cassette.replay_response(client, &request)? {
+        return Ok(Ok(response));
+    }
+    let method = request.method().clone();
+    let url = request.url().clone();
+    match http.execute(request).await {
+        Ok(response) => cassette
+            .record_response(client, &method, &url, response)
+            .await
+            .map(Ok),
+        Err(e) => Ok(Err(e)),
+    }
+}
+
+/// Returns the path and query of `url` (the host-independent request key).
+fn request_target(url: &url::Url) -> String {
+    url[url::Position::BeforePath..].to_owned()
+}
+
+/// Writes a recording, creating its directory as needed.
+fn save(path: &Path, recorded: &RecordedResponse) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|source| ApiError::Io {
+            message: format!("failed to create {}", parent.display()),
+            source,
+        })?;
+    }
+    let json = serde_json::to_string_pretty(recorded)
+        .map_err(|e| ApiError::Invalid(format!("failed to serialize recording: {e}")))?;
+    std::fs::write(path, json).map_err(|source| ApiError::Io {
+        message: format!("failed to write {}", path.display()),
+        source,
+    })
+}
+
+/// Rebuilds a `reqwest::Response` from a recording.
+fn to_response(recorded: RecordedResponse) -> Result<reqwest::Response> {
+    let mut builder = http::Response::builder().status(recorded.status);
+    if let Some(retry_after) = &recorded.retry_after {
+        builder = builder.header(reqwest::header::RETRY_AFTER, retry_after);
+    }
+    builder
+        .body(recorded.body)
+        .map(reqwest::Response::from)
+        .map_err(|e| ApiError::Invalid(format!("invalid recorded response: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::panic)]
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_then_replay_roundtrip() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let url =
+            url::Url::parse("https://cal.syoboi.jp/db.php?Command=TitleLookup&TID=1").unwrap();
+        let live = reqwest::Response::from(
+            http::Response::builder()
+                .status(200)
+                .body(String::from("<TitleLookupResponse/>"))
+                .unwrap(),
+        );
+
+        // Act: record against the live host, replay against another one
+        let recorded = Cassette::record(dir.path())
+            .record_response("syoboi", &reqwest::Method::GET, &url, live)
+            .await
+            .unwrap();
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            url::Url::parse("http://127.0.0.1:8080/db.php?Command=TitleLookup&TID=1").unwrap(),
+        );
+        let replayed = Cassette::replay(dir.path())
+            .replay_response("syoboi", &request)
+            .unwrap()
+            .unwrap();
+
+        // Assert
+        assert_eq!(recorded.text().await.unwrap(), "<TitleLookupResponse/>");
+        assert_eq!(replayed.status().as_u16(), 200);
+        assert_eq!(replayed.text().await.unwrap(), "<TitleLookupResponse/>");
+    }
+
+    #[test]
+    fn test_replay_missing_recording_is_io_error() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            url::Url::parse("https://api.themoviedb.org/3/tv/1").unwrap(),
+        );
+
+        // Act
+        let result = Cassette::replay(dir.path()).replay_response("tmdb", &request);
+
+        // Assert
+        let Err(ApiError::Io { message, .. }) = result else {
+            panic!("expected Io error");
+        };
+        assert!(message.contains("GET /3/tv/1"));
+    }
+
+    #[test]
+    fn test_record_mode_does_not_replay() {
+        // Arrange
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            url::Url::parse("https://api.themoviedb.org/3/tv/1").unwrap(),
+        );
+
+        // Act
+        let result = Cassette::record("/nonexistent").replay_response("tmdb", &request);
+
+        // Assert
+        assert!(result.unwrap().is_none());
+    }
+}
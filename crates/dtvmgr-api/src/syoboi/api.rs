@@ -1,9 +1,9 @@
 //! `SyoboiApi` trait definition.
 #![allow(clippy::future_not_send)]
 
-use anyhow::Result;
+use crate::error::Result;
 
-use super::params::ProgLookupParams;
+use super::params::{ProgLookupParams, TitleLookupParams};
 use super::types::{SyoboiChannel, SyoboiChannelGroup, SyoboiProgram, SyoboiTitle};
 
 /// Syoboi Calendar API trait.
@@ -15,18 +15,15 @@ use super::types::{SyoboiChannel, SyoboiChannelGroup, SyoboiProgram, SyoboiTitle
 pub trait LocalSyoboiApi {
     /// Looks up title information.
     ///
-    /// When `fields` is `Some`, only the specified fields are returned
-    /// via the `Fields` query parameter (reduces response size and avoids
-    /// XML parse issues with fields like `Comment`).
+    /// Titles are selected by TID list, range, or `*`, optionally filtered
+    /// by `LastUpdate`. When `params.fields` is `Some`, only the specified
+    /// fields are returned via the `Fields` query parameter (reduces
+    /// response size and avoids XML parse issues with fields like `Comment`).
     ///
     /// # Errors
     ///
     /// Returns an error if the HTTP request or XML parsing fails.
-    async fn lookup_titles(
-        &self,
-        tids: &[u32],
-        fields: Option<&[&str]>,
-    ) -> Result<Vec<SyoboiTitle>>;
+    async fn lookup_titles(&self, params: &TitleLookupParams) -> Result<Vec<SyoboiTitle>>;
 
     /// Looks up program data (5,000-item limit per request).
     ///
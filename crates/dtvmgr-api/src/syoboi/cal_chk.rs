@@ -0,0 +1,124 @@
+//! `cal_chk.php` (personal check list) types and parsing.
+
+use serde::Deserialize;
+
+/// Default number of days covered by a check list request.
+pub const DEFAULT_CAL_CHK_DAYS: u32 = 30;
+
+/// Syoboi account used for user features such as the check list.
+///
+/// Custom `Debug` impl redacts `password` to prevent accidental leakage.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SyoboiCredentials {
+    /// Syoboi user name.
+    pub user: String,
+    /// Password, sent as HTTP Basic auth. Only needed for private lists.
+    pub password: Option<String>,
+}
+
+impl std::fmt::Debug for SyoboiCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let redacted: &str = if self.password.is_some() {
+            "[REDACTED]"
+        } else {
+            "None"
+        };
+        f.debug_struct("SyoboiCredentials")
+            .field("user", &self.user)
+            .field("password", &redacted)
+            .finish()
+    }
+}
+
+/// One program of the user's check list.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CheckedProgram {
+    /// Program ID.
+    #[serde(rename = "@PID")]
+    pub pid: u32,
+    /// Title ID.
+    #[serde(rename = "@TID")]
+    pub tid: u32,
+    /// Channel ID.
+    #[serde(rename = "@ChID", default)]
+    pub ch_id: Option<u32>,
+    /// Start time as returned by the server.
+    #[serde(rename = "@StTime", default)]
+    pub st_time: String,
+    /// Episode number (empty for non-episodic programs).
+    #[serde(
+        rename = "@Count",
+        default,
+        deserialize_with = "super::xml::deserialize_empty_string_as_none_u32"
+    )]
+    pub count: Option<u32>,
+    /// Title name.
+    #[serde(rename = "@Title", default)]
+    pub title: String,
+}
+
+/// `cal_chk.php` response root.
+#[derive(Debug, Deserialize)]
+pub struct CalChkResponse {
+    /// Program items (absent when the list is empty).
+    #[serde(rename = "ProgItems", default)]
+    pub prog_items: Option<CheckedItems>,
+}
+
+/// `ProgItems` container of a `cal_chk.php` response.
+#[derive(Debug, Deserialize)]
+pub struct CheckedItems {
+    #[serde(rename = "ProgItem", default)]
+    pub items: Vec<CheckedProgram>,
+}
+
+/// Returns the distinct TIDs of `programs` in first-seen order.
+#[must_use]
+pub fn checked_tids(programs: &[CheckedProgram]) -> Vec<u32> {
+    let mut seen = std::collections::HashSet::new();
+    programs
+        .iter()
+        .map(|p| p.tid)
+        .filter(|tid| seen.insert(*tid))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    #[test]
+    fn test_parse_cal_chk_and_dedup_tids() {
+        // Arrange
+        let xml = include_str!("../../../../fixtures/syoboi/cal_chk.xml");
+
+        // Act
+        let response: CalChkResponse = quick_xml::de::from_str(xml).unwrap();
+        let programs = response.prog_items.unwrap().items;
+
+        // Assert
+        assert_eq!(programs.len(), 3);
+        assert_eq!(programs[0].pid, 574_823);
+        assert_eq!(programs[0].count, Some(1));
+        assert_eq!(programs[2].count, None);
+        assert_eq!(checked_tids(&programs), vec![6309, 6530]);
+    }
+
+    #[test]
+    fn test_credentials_debug_redacts_password() {
+        // Arrange
+        let creds = SyoboiCredentials {
+            user: String::from("alice"),
+            password: Some(String::from("secret")),
+        };
+
+        // Act
+        let debug = format!("{creds:?}");
+
+        // Assert
+        assert!(debug.contains("alice"));
+        assert!(!debug.contains("secret"));
+    }
+}
@@ -0,0 +1,180 @@
+//! Syoboi title category (`Cat`) decoding.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::error::ApiError;
+
+/// Decoded Syoboi title category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TitleCategory {
+    /// Other (`Cat=0`).
+    Other,
+    /// Anime currently airing (`Cat=1`).
+    Anime,
+    /// Radio (`Cat=2`).
+    Radio,
+    /// Non-anime TV (`Cat=3`).
+    Tv,
+    /// Tokusatsu (`Cat=4`).
+    Tokusatsu,
+    /// Anime-related program (`Cat=5`).
+    AnimeRelated,
+    /// Memo (`Cat=6`).
+    Memo,
+    /// OVA (`Cat=7`).
+    Ova,
+    /// Movie (`Cat=8`).
+    Movie,
+    /// Anime that finished airing or is rerunning (`Cat=10`).
+    AnimeEnded,
+    /// Code not known to this version.
+    Unknown(u32),
+}
+
+impl TitleCategory {
+    /// Every category with a known code, in code order.
+    pub const KNOWN: [Self; 10] = [
+        Self::Other,
+        Self::Anime,
+        Self::Radio,
+        Self::Tv,
+        Self::Tokusatsu,
+        Self::AnimeRelated,
+        Self::Memo,
+        Self::Ova,
+        Self::Movie,
+        Self::AnimeEnded,
+    ];
+
+    /// Decodes a `Cat` code. A missing code is treated as [`Self::Other`].
+    #[must_use]
+    pub const fn from_cat(cat: Option<u32>) -> Self {
+        match cat {
+            None | Some(0) => Self::Other,
+            Some(1) => Self::Anime,
+            Some(2) => Self::Radio,
+            Some(3) => Self::Tv,
+            Some(4) => Self::Tokusatsu,
+            Some(5) => Self::AnimeRelated,
+            Some(6) => Self::Memo,
+            Some(7) => Self::Ova,
+            Some(8) => Self::Movie,
+            Some(10) => Self::AnimeEnded,
+            Some(code) => Self::Unknown(code),
+        }
+    }
+
+    /// Returns the Syoboi `Cat` code.
+    #[must_use]
+    pub const fn code(self) -> u32 {
+        match self {
+            Self::Other => 0,
+            Self::Anime => 1,
+            Self::Radio => 2,
+            Self::Tv => 3,
+            Self::Tokusatsu => 4,
+            Self::AnimeRelated => 5,
+            Self::Memo => 6,
+            Self::Ova => 7,
+            Self::Movie => 8,
+            Self::AnimeEnded => 10,
+            Self::Unknown(code) => code,
+        }
+    }
+
+    /// Returns a short display label (e.g. `"anime"`, `"ova"`).
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Other => "other",
+            Self::Anime => "anime",
+            Self::Radio => "radio",
+            Self::Tv => "tv",
+            Self::Tokusatsu => "tokusatsu",
+            Self::AnimeRelated => "anime-rel",
+            Self::Memo => "memo",
+            Self::Ova => "ova",
+            Self::Movie => "movie",
+            Self::AnimeEnded => "anime-end",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for TitleCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(code) => write!(f, "unknown({code})"),
+            _ => f.write_str(self.label()),
+        }
+    }
+}
+
+impl FromStr for TitleCategory {
+    type Err = ApiError;
+
+    /// Parses a label (`"anime"`, `"ova"`, ...) or a numeric `Cat` code.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(code) = s.parse::<u32>() {
+            return Ok(Self::from_cat(Some(code)));
+        }
+        Self::KNOWN
+            .into_iter()
+            .find(|c| c.label().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let labels: Vec<&str> = Self::KNOWN.iter().map(|c| c.label()).collect();
+                ApiError::Invalid(format!(
+                    "unknown category {s:?} (expected a Cat code or one of: {})",
+                    labels.join(", ")
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_from_cat_roundtrips_known_codes() {
+        // Arrange
+        let codes = [0, 1, 2, 3, 4, 5, 6, 7, 8, 10];
+
+        // Act & Assert
+        for code in codes {
+            let cat = TitleCategory::from_cat(Some(code));
+            assert_eq!(cat.code(), code);
+            assert!(!matches!(cat, TitleCategory::Unknown(_)));
+        }
+    }
+
+    #[test]
+    fn test_from_cat_missing_and_unknown() {
+        // Act & Assert
+        assert_eq!(TitleCategory::from_cat(None), TitleCategory::Other);
+        assert_eq!(TitleCategory::from_cat(Some(9)), TitleCategory::Unknown(9));
+        assert_eq!(TitleCategory::Unknown(9).to_string(), "unknown(9)");
+        assert_eq!(TitleCategory::Ova.to_string(), "ova");
+    }
+
+    #[test]
+    fn test_from_str_accepts_labels_and_codes() {
+        // Act & Assert
+        assert_eq!("ova".parse::<TitleCategory>().unwrap(), TitleCategory::Ova);
+        assert_eq!(
+            " Anime-End ".parse::<TitleCategory>().unwrap(),
+            TitleCategory::AnimeEnded
+        );
+        assert_eq!("8".parse::<TitleCategory>().unwrap(), TitleCategory::Movie);
+        assert_eq!(
+            "9".parse::<TitleCategory>().unwrap(),
+            TitleCategory::Unknown(9)
+        );
+        let err = "drama".parse::<TitleCategory>().unwrap_err();
+        assert!(err.to_string().contains("anime-rel"));
+    }
+}
@@ -1,21 +1,32 @@
 //! `SyoboiClient` - Syoboi Calendar API client implementation.
 
+use std::borrow::Cow;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{Context, Result, bail};
 use reqwest::Client;
 use tokio::sync::Mutex;
 use tracing::instrument;
 use url::Url;
 
 use super::api::LocalSyoboiApi;
-use super::params::ProgLookupParams;
-use super::rate_limiter::SyoboiRateLimiter;
+use super::cal_chk::{CalChkResponse, CheckedProgram, SyoboiCredentials};
+use super::json::{self, ProgramByDateResponse, TitleFullResponse};
+use super::params::{CountRange, ProgLookupParams, TidSelector, TitleLookupParams};
+use super::rate_limiter::{DAY, HOUR};
 use super::types::{SyoboiChannel, SyoboiChannelGroup, SyoboiProgram, SyoboiTitle};
 use super::xml::{
-    ApiResult, ChGroupLookupResponse, ChLookupResponse, ProgLookupResponse, TitleLookupResponse,
+    self, ApiResult, ChGroupLookupResponse, ChLookupResponse, ProgLookupResponse,
+    TitleLookupResponse,
 };
+use crate::audit::{AuditLog, RequestAudit};
+use crate::circuit_breaker::{CircuitBreaker, DEFAULT_COOLDOWN, DEFAULT_FAILURE_THRESHOLD};
+use crate::clock::Clock;
+use crate::error::{ApiError, Result};
+use crate::http::HttpOptions;
+use crate::rate_limiter::{RateLimit, RateLimiter};
+use crate::replay::Cassette;
 
 /// Base URL for the Syoboi Calendar website.
 pub const SYOBOI_BASE_URL: &str = "https://cal.syoboi.jp";
@@ -32,6 +43,21 @@ const MAX_NETWORK_RETRIES: u32 = 1;
 /// Delay between retries.
 const RETRY_DELAY: Duration = Duration::from_secs(2);
 
+/// Response format (endpoint) used for `TitleLookup` and `ProgLookup`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub enum SyoboiFormat {
+    /// `db.php` XML (default).
+    #[default]
+    Xml,
+    /// `json.php` JSON. Immune to unescaped entities in free-text fields.
+    ///
+    /// Queries `json.php` cannot express (TID ranges or `*`, `LastUpdate`,
+    /// `ProgLookup` without TIDs or `Range`) and channel lookups still use
+    /// `db.php`.
+    Json,
+}
+
 /// Syoboi Calendar API client.
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -40,8 +66,24 @@ pub struct SyoboiClient {
     http_client: Client,
     /// Base URL.
     base_url: Url,
+    /// `json.php` URL (sibling of `base_url`).
+    json_url: Url,
+    /// `cal_chk.php` URL (sibling of `base_url`).
+    cal_chk_url: Url,
+    /// Account for user features (check list).
+    credentials: Option<SyoboiCredentials>,
+    /// Response format for title/program lookups.
+    format: SyoboiFormat,
+    /// Whether to repair malformed XML before parsing.
+    sanitize_xml: bool,
     /// Rate limiter.
-    rate_limiter: Arc<Mutex<SyoboiRateLimiter>>,
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    /// Circuit breaker shared by all requests.
+    circuit_breaker: std::sync::Mutex<CircuitBreaker>,
+    /// Optional audit trail of requests.
+    audit_log: Option<AuditLog>,
+    /// Optional response recording or replay.
+    cassette: Option<Cassette>,
 }
 
 /// Builder for `SyoboiClient`.
@@ -50,9 +92,20 @@ pub struct SyoboiClient {
 pub struct SyoboiClientBuilder {
     base_url: Option<Url>,
     user_agent: Option<String>,
+    rate_limit: Option<RateLimit>,
     min_interval: Option<Duration>,
     hourly_limit: Option<u32>,
     daily_limit: Option<u32>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    audit_log: Option<AuditLog>,
+    cassette: Option<Cassette>,
+    state_file: Option<PathBuf>,
+    format: SyoboiFormat,
+    sanitize_xml: bool,
+    clock: Option<Arc<dyn Clock>>,
+    credentials: Option<SyoboiCredentials>,
+    http_options: HttpOptions,
 }
 
 impl SyoboiClientBuilder {
@@ -61,9 +114,25 @@ impl SyoboiClientBuilder {
         Self {
             base_url: None,
             user_agent: None,
+            rate_limit: None,
             min_interval: None,
             hourly_limit: None,
             daily_limit: None,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+            audit_log: None,
+            cassette: None,
+            state_file: None,
+            format: SyoboiFormat::Xml,
+            sanitize_xml: false,
+            clock: None,
+            credentials: None,
+            http_options: HttpOptions {
+                proxy: None,
+                timeout: None,
+                connect_timeout: None,
+                root_ca: None,
+            },
         }
     }
 
@@ -81,6 +150,15 @@ impl SyoboiClientBuilder {
         self
     }
 
+    /// Replaces the whole rate limit (default: 1s interval, 500/hour,
+    /// 10,000/day). `min_interval`, `hourly_limit`, and `daily_limit`
+    /// still override the matching parts.
+    #[must_use]
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
     /// Sets the minimum request interval (default: 1s).
     #[must_use]
     pub const fn min_interval(mut self, interval: Duration) -> Self {
@@ -102,42 +180,155 @@ impl SyoboiClientBuilder {
         self
     }
 
+    /// Opens the circuit breaker for `cooldown` after `failure_threshold`
+    /// consecutive server-side failures (default: 5 failures, 5 minutes).
+    /// A threshold of `0` disables the breaker.
+    #[must_use]
+    pub const fn circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.failure_threshold = failure_threshold;
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Appends one JSON line per request to the given audit log.
+    #[must_use]
+    pub fn audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = Some(log);
+        self
+    }
+
+    /// Records responses to, or replays them from, `cassette`.
+    ///
+    /// Replayed requests skip rate limiting since they never reach the API.
+    #[must_use]
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Persists rate limit counters and backoff to `path` so they survive
+    /// across process runs.
+    #[must_use]
+    pub fn state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Selects the response format for title/program lookups
+    /// (default: [`SyoboiFormat::Xml`]).
+    #[must_use]
+    pub const fn format(mut self, format: SyoboiFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Repairs malformed `db.php` XML (bare `&`, forbidden control
+    /// characters) before parsing (default: `false`).
+    ///
+    /// Enables requesting free-text fields such as `Comment`, which
+    /// otherwise fail to parse when they contain unescaped URLs.
+    #[must_use]
+    pub const fn sanitize_xml(mut self, enabled: bool) -> Self {
+        self.sanitize_xml = enabled;
+        self
+    }
+
+    /// Uses `clock` for rate limiting and backoff (default: the system
+    /// clock).
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Sets the Syoboi account used by [`SyoboiClient::lookup_checked_programs`].
+    #[must_use]
+    pub fn credentials(mut self, credentials: SyoboiCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Sets the proxy, timeouts, and extra root certificates (default:
+    /// system proxy from the environment, no timeout, built-in roots).
+    #[must_use]
+    pub fn http_options(mut self, options: HttpOptions) -> Self {
+        self.http_options = options;
+        self
+    }
+
     /// Builds the client.
     ///
     /// # Errors
     ///
     /// - `user_agent` is not set.
+    /// - The HTTP options are invalid (see [`HttpOptions`]).
     /// - `reqwest::Client` build fails.
     pub fn build(self) -> Result<SyoboiClient> {
-        let user_agent = self.user_agent.context("user_agent is required")?;
+        let user_agent = self
+            .user_agent
+            .ok_or_else(|| ApiError::Invalid(String::from("user_agent is required")))?;
 
         let base_url = if let Some(url) = self.base_url {
             url
         } else {
-            let result = Url::parse(DEFAULT_BASE_URL);
-            result.context("invalid default base URL")?
+            Url::parse(DEFAULT_BASE_URL)
+                .map_err(|e| ApiError::Invalid(format!("invalid default base URL: {e}")))?
         };
 
-        let min_interval = self.min_interval.unwrap_or(Duration::from_secs(1));
-        let hourly_limit = self.hourly_limit.unwrap_or(500);
-        let daily_limit = self.daily_limit.unwrap_or(10_000);
+        let json_url = base_url
+            .join("json.php")
+            .map_err(|e| ApiError::Invalid(format!("failed to derive json.php URL: {e}")))?;
+        let cal_chk_url = base_url
+            .join("cal_chk.php")
+            .map_err(|e| ApiError::Invalid(format!("failed to derive cal_chk.php URL: {e}")))?;
+
+        let mut limit = self
+            .rate_limit
+            .unwrap_or_else(super::rate_limiter::default_rate_limit);
+        if let Some(interval) = self.min_interval {
+            limit.min_interval = interval;
+        }
+        if let Some(hourly_limit) = self.hourly_limit {
+            let hourly_limit = usize::try_from(hourly_limit)
+                .map_err(|_| ApiError::Invalid(String::from("failed to convert hourly_limit")))?;
+            limit = limit.window(HOUR, hourly_limit);
+        }
+        if let Some(daily_limit) = self.daily_limit {
+            let daily_limit = usize::try_from(daily_limit)
+                .map_err(|_| ApiError::Invalid(String::from("failed to convert daily_limit")))?;
+            limit = limit.window(DAY, daily_limit);
+        }
 
-        let http_client = Client::builder()
-            .user_agent(&user_agent)
-            .gzip(true)
+        let http_client = self
+            .http_options
+            .apply(Client::builder().user_agent(&user_agent).gzip(true))?
             .build()
-            .context("failed to build HTTP client")?;
-
-        let rate_limiter = Arc::new(Mutex::new(SyoboiRateLimiter::new(
-            min_interval,
-            usize::try_from(hourly_limit).context("failed to convert hourly_limit")?,
-            usize::try_from(daily_limit).context("failed to convert daily_limit")?,
-        )));
+            .map_err(|e| ApiError::Invalid(format!("failed to build HTTP client: {e}")))?;
+
+        let mut rate_limiter = RateLimiter::new("syoboi", limit);
+        let mut circuit_breaker =
+            CircuitBreaker::new("syoboi", self.failure_threshold, self.cooldown);
+        if let Some(clock) = self.clock {
+            circuit_breaker = circuit_breaker.with_clock(Arc::clone(&clock));
+            rate_limiter = rate_limiter.with_clock(clock);
+        }
+        if let Some(path) = self.state_file {
+            rate_limiter = rate_limiter.with_state_file(path);
+        }
+        let rate_limiter = Arc::new(Mutex::new(rate_limiter));
 
         Ok(SyoboiClient {
             http_client,
             base_url,
+            json_url,
+            cal_chk_url,
+            credentials: self.credentials,
+            format: self.format,
+            sanitize_xml: self.sanitize_xml,
             rate_limiter,
+            circuit_breaker: std::sync::Mutex::new(circuit_breaker),
+            audit_log: self.audit_log,
+            cassette: self.cassette,
         })
     }
 }
@@ -154,16 +345,26 @@ impl SyoboiClient {
         if let Some(r) = result
             && r.code != 200
         {
-            bail!(
-                "{} API error: code={}, message={:?}",
-                command,
-                r.code,
-                r.message
-            );
+            return Err(ApiError::HttpStatus {
+                status: u16::try_from(r.code).unwrap_or(u16::MAX),
+                message: format!(
+                    "{} API error: code={}, message={:?}",
+                    command, r.code, r.message
+                ),
+            });
         }
         Ok(())
     }
 
+    /// Returns the XML body to parse, repaired when `sanitize_xml` is set.
+    fn xml_body<'a>(&self, body: &'a str) -> Cow<'a, str> {
+        if self.sanitize_xml {
+            xml::sanitize_xml(body)
+        } else {
+            Cow::Borrowed(body)
+        }
+    }
+
     /// Builds an XML decode error message.
     ///
     /// SECURITY: do not include response body preview — the body is already
@@ -175,29 +376,72 @@ impl SyoboiClient {
     /// Parses a `TitleLookup` XML response.
     pub(crate) fn parse_title_response(xml: &str) -> Result<Vec<SyoboiTitle>> {
         let raw_result: std::result::Result<TitleLookupResponse, _> = quick_xml::de::from_str(xml);
-        let response =
-            raw_result.with_context(|| Self::xml_decode_error("TitleLookup", xml.len()))?;
+        let response = raw_result.map_err(|e| {
+            ApiError::decode(Self::xml_decode_error("TitleLookup", xml.len()), xml, e)
+        })?;
         Self::check_api_result(response.result.as_ref(), "TitleLookup")?;
         Ok(response
             .title_items
             .map_or_else(Vec::new, |items| items.items))
     }
 
+    /// Parses a `TitleFull` JSON response.
+    pub(crate) fn parse_title_json(body: &str) -> Result<Vec<SyoboiTitle>> {
+        let response: TitleFullResponse = serde_json::from_str(body).map_err(|e| {
+            ApiError::decode(
+                format!(
+                    "TitleFull JSON decoding failed (body_len={} bytes)",
+                    body.len()
+                ),
+                body,
+                e,
+            )
+        })?;
+        json::decode_titles(response)
+    }
+
+    /// Parses a `ProgramByDate` JSON response.
+    pub(crate) fn parse_prog_json(body: &str) -> Result<Vec<SyoboiProgram>> {
+        let response: ProgramByDateResponse = serde_json::from_str(body).map_err(|e| {
+            ApiError::decode(
+                format!(
+                    "ProgramByDate JSON decoding failed (body_len={} bytes)",
+                    body.len()
+                ),
+                body,
+                e,
+            )
+        })?;
+        json::decode_programs(response)
+    }
+
     /// Parses a `ProgLookup` XML response.
     pub(crate) fn parse_prog_response(xml: &str) -> Result<Vec<SyoboiProgram>> {
         let raw_result: std::result::Result<ProgLookupResponse, _> = quick_xml::de::from_str(xml);
-        let response =
-            raw_result.with_context(|| Self::xml_decode_error("ProgLookup", xml.len()))?;
+        let response = raw_result.map_err(|e| {
+            ApiError::decode(Self::xml_decode_error("ProgLookup", xml.len()), xml, e)
+        })?;
         Self::check_api_result(response.result.as_ref(), "ProgLookup")?;
         Ok(response
             .prog_items
             .map_or_else(Vec::new, |items| items.items))
     }
 
+    /// Parses a `cal_chk.php` XML response.
+    pub(crate) fn parse_cal_chk_response(xml: &str) -> Result<Vec<CheckedProgram>> {
+        let raw_result: std::result::Result<CalChkResponse, _> = quick_xml::de::from_str(xml);
+        let response = raw_result
+            .map_err(|e| ApiError::decode(Self::xml_decode_error("CalChk", xml.len()), xml, e))?;
+        Ok(response
+            .prog_items
+            .map_or_else(Vec::new, |items| items.items))
+    }
+
     /// Parses a `ChLookup` XML response.
     pub(crate) fn parse_ch_response(xml: &str) -> Result<Vec<SyoboiChannel>> {
         let raw_result: std::result::Result<ChLookupResponse, _> = quick_xml::de::from_str(xml);
-        let response = raw_result.with_context(|| Self::xml_decode_error("ChLookup", xml.len()))?;
+        let response = raw_result
+            .map_err(|e| ApiError::decode(Self::xml_decode_error("ChLookup", xml.len()), xml, e))?;
         Self::check_api_result(response.result.as_ref(), "ChLookup")?;
         Ok(response.ch_items.map_or_else(Vec::new, |items| items.items))
     }
@@ -206,8 +450,9 @@ impl SyoboiClient {
     pub(crate) fn parse_ch_group_response(xml: &str) -> Result<Vec<SyoboiChannelGroup>> {
         let raw_result: std::result::Result<ChGroupLookupResponse, _> =
             quick_xml::de::from_str(xml);
-        let response =
-            raw_result.with_context(|| Self::xml_decode_error("ChGroupLookup", xml.len()))?;
+        let response = raw_result.map_err(|e| {
+            ApiError::decode(Self::xml_decode_error("ChGroupLookup", xml.len()), xml, e)
+        })?;
         Self::check_api_result(response.result.as_ref(), "ChGroupLookup")?;
         Ok(response
             .ch_group_items
@@ -216,11 +461,59 @@ impl SyoboiClient {
 }
 
 impl SyoboiClient {
+    /// Returns `true` when responses come from a replay cassette.
+    fn replaying(&self) -> bool {
+        self.cassette.as_ref().is_some_and(Cassette::is_replay)
+    }
+
+    /// Sends a GET request with retry logic, recording it to the audit log
+    /// when one is configured.
+    ///
+    /// Requests are rejected with [`ApiError::CircuitOpen`] while the
+    /// circuit breaker is open; every sent request reports its outcome to
+    /// the breaker.
+    ///
+    /// Returns the HTTP status code alongside the parsed result.
+    async fn request_with_retry<T, F>(
+        &self,
+        command: &str,
+        build_request: impl Fn() -> reqwest::RequestBuilder + Sync,
+        parse: F,
+    ) -> Result<(u16, Vec<T>)>
+    where
+        F: Fn(&str) -> Result<Vec<T>>,
+    {
+        self.breaker().check()?;
+        let mut audit = RequestAudit::start("syoboi", command);
+        let result = self
+            .send_with_retry(command, &build_request, parse, &mut audit)
+            .await;
+        self.breaker().record(result.as_ref().map(|_| ()));
+        crate::prometheus::record_request("syoboi", command, audit.status);
+        if let Some(log) = &self.audit_log {
+            let request = build_request().build().ok();
+            log.record(&audit.finish(
+                request.as_ref().and_then(|r| r.url().query()),
+                result.as_ref().err(),
+            ));
+        }
+        result
+    }
+
+    /// Locks the circuit breaker.
+    fn breaker(&self) -> std::sync::MutexGuard<'_, CircuitBreaker> {
+        self.circuit_breaker
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
     /// Sends a GET request with retry logic.
     ///
     /// Retries up to `MAX_RETRIES` times on failure, waiting the rate limiter
     /// interval before each attempt. Logs warnings on each retry.
-    /// Returns the HTTP status code alongside the parsed result.
+    ///
+    /// Requests queue on the shared rate limiter; a 429 backoff is recorded
+    /// there so it holds back every queued request, not just this one.
     #[instrument(skip_all, fields(
         otel.kind = "Client",
         http.request.method = "GET",
@@ -229,14 +522,16 @@ impl SyoboiClient {
         http.response.status_code = tracing::field::Empty,
         http.response.body.size = tracing::field::Empty,
     ), err(level = "warn"))]
-    async fn request_with_retry<T, F>(
+    #[allow(clippy::too_many_lines)]
+    async fn send_with_retry<T, F>(
         &self,
         command: &str,
         build_request: impl Fn() -> reqwest::RequestBuilder,
         parse: F,
-    ) -> Result<(u16, T)>
+        audit: &mut RequestAudit,
+    ) -> Result<(u16, Vec<T>)>
     where
-        F: Fn(&str) -> Result<T>,
+        F: Fn(&str) -> Result<Vec<T>>,
     {
         #[cfg(feature = "otel")]
         let request_start = std::time::Instant::now();
@@ -244,12 +539,26 @@ impl SyoboiClient {
         let mut rate_limit_retries = 0u32;
 
         loop {
-            self.rate_limiter.lock().await.wait().await;
+            if !self.replaying() {
+                self.rate_limiter.lock().await.wait().await;
+            }
 
-            let response = match build_request().send().await {
+            let request = build_request()
+                .build()
+                .map_err(|_| ApiError::Invalid(format!("failed to build request: {command}")))?;
+            tracing::Span::current().record("url.full", tracing::field::display(request.url()));
+            let sent = crate::replay::execute(
+                &self.http_client,
+                self.cassette.as_ref(),
+                "syoboi",
+                request,
+            )
+            .await?;
+            let response = match sent {
                 Ok(r) => r,
                 Err(e) if !e.is_timeout() && network_retries < MAX_NETWORK_RETRIES => {
                     network_retries = network_retries.saturating_add(1);
+                    audit.retry();
                     // SECURITY: log classified kind only — reqwest::Error from
                     // send() may carry request context; never format it.
                     let kind = crate::classify_reqwest_error(&e);
@@ -261,19 +570,18 @@ impl SyoboiClient {
                     continue;
                 }
                 Err(e) => {
-                    let kind = crate::classify_reqwest_error(&e);
                     let status_code = e.status().map(|s| i64::from(s.as_u16()));
                     if let Some(code) = status_code {
                         tracing::Span::current().record("http.response.status_code", code);
                     }
-                    bail!("{kind}: {command}");
+                    return Err(ApiError::transport(&e, command));
                 }
             };
 
             let span = tracing::Span::current();
-            span.record("url.full", tracing::field::display(response.url()));
             let status = response.status();
             span.record("http.response.status_code", i64::from(status.as_u16()));
+            audit.status = Some(status.as_u16());
             let headers = response.headers().clone();
             // SECURITY: log only header names, not values — response headers
             // may contain session tokens or other sensitive data.
@@ -296,17 +604,18 @@ impl SyoboiClient {
                 crate::metrics::record_rate_limit_hit("syoboi");
 
                 rate_limit_retries = rate_limit_retries.saturating_add(1);
+                let requested = crate::error::retry_after(&headers);
                 if rate_limit_retries > MAX_RETRIES {
-                    bail!("Syoboi API rate limited after {MAX_RETRIES} retries: {command}");
+                    return Err(ApiError::RateLimited {
+                        message: format!(
+                            "Syoboi API rate limited after {MAX_RETRIES} retries: {command}"
+                        ),
+                        retry_after: requested,
+                    });
                 }
 
-                let retry_after = headers
-                    .get(reqwest::header::RETRY_AFTER)
-                    .and_then(|v| v.to_str().ok())
-                    .and_then(|s| s.parse::<u64>().ok())
-                    .map_or(RETRY_DELAY, |secs| {
-                        Duration::from_secs(secs.saturating_add(1))
-                    });
+                let retry_after =
+                    requested.map_or(RETRY_DELAY, |d| d.saturating_add(Duration::from_secs(1)));
 
                 tracing::warn!(
                     %command,
@@ -315,20 +624,39 @@ impl SyoboiClient {
                     retry_after_secs = retry_after.as_secs(),
                     "Rate limited, waiting before retry"
                 );
-                tokio::time::sleep(retry_after).await;
+                self.rate_limiter.lock().await.back_off(retry_after);
+                audit.retry();
                 continue;
             }
 
+            // Cloudflare and origin errors answer 5xx with an HTML page.
+            if status.is_server_error() {
+                return Err(ApiError::HttpStatus {
+                    status: status.as_u16(),
+                    message: format!("Syoboi {command} failed with a server error ({status})"),
+                });
+            }
+
+            // User features answer 401/403 with an HTML page; fail before parsing.
+            if status == reqwest::StatusCode::UNAUTHORIZED
+                || status == reqwest::StatusCode::FORBIDDEN
+            {
+                return Err(ApiError::HttpStatus {
+                    status: status.as_u16(),
+                    message: format!("Syoboi {command} rejected the credentials ({status})"),
+                });
+            }
+
             let xml = response
                 .text()
                 .await
-                .with_context(|| format!("failed to read {command} response body"))?;
+                .map_err(|e| ApiError::transport(&e, command))?;
 
             span.record("http.response.body.size", xml.len());
             tracing::debug!(http.response.body = %xml, "HTTP response body");
 
-            let result =
-                parse(&xml).with_context(|| format!("failed to parse {command} response"))?;
+            let result = parse(&xml)?;
+            audit.items = Some(result.len());
 
             #[cfg(feature = "otel")]
             crate::metrics::record_request_duration("syoboi", "GET", request_start);
@@ -350,58 +678,137 @@ impl SyoboiClient {
     /// Returns an error if the HTTP request or XML parsing fails.
     pub async fn lookup_titles_with_status(
         &self,
-        tids: &[u32],
-        fields: Option<&[&str]>,
+        params: &TitleLookupParams,
     ) -> Result<(u16, Vec<SyoboiTitle>)> {
-        let tid_str = tids
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<_>>()
-            .join(",");
+        if self.format == SyoboiFormat::Json {
+            if let Some(query) = Self::build_title_json_query(params) {
+                return self
+                    .request_with_retry(
+                        "TitleFull",
+                        || self.http_client.get(self.json_url.clone()).query(&query),
+                        Self::parse_title_json,
+                    )
+                    .await;
+            }
+            tracing::debug!("TitleLookup not expressible in json.php, using db.php");
+        }
 
-        let fields_str = fields.map(|f| f.join(","));
+        let query = Self::build_title_query(params);
 
         self.request_with_retry(
             "TitleLookup",
+            || self.http_client.get(self.base_url.clone()).query(&query),
+            |body| Self::parse_title_response(&self.xml_body(body)),
+        )
+        .await
+    }
+
+    /// Fetches the programs of the configured user's check list
+    /// (`cal_chk.php`) for the next `days` days.
+    ///
+    /// The password, when set, is sent as HTTP Basic auth; public lists
+    /// only need the user name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no credentials are configured, the server
+    /// rejects them, or the HTTP request or XML parsing fails.
+    #[instrument(skip_all, fields(otel.kind = "Client"), err(level = "error"))]
+    pub async fn lookup_checked_programs(&self, days: u32) -> Result<Vec<CheckedProgram>> {
+        let credentials = self.credentials.as_ref().ok_or_else(|| {
+            ApiError::Invalid(String::from("Syoboi user is required for the check list"))
+        })?;
+        let query: Vec<(&str, String)> = vec![
+            ("usr", credentials.user.clone()),
+            ("days", days.to_string()),
+        ];
+
+        self.request_with_retry(
+            "CalChk",
             || {
-                let mut req = self
-                    .http_client
-                    .get(self.base_url.clone())
-                    .query(&[("Command", "TitleLookup"), ("TID", &*tid_str)]);
-                if let Some(ref f) = fields_str {
-                    req = req.query(&[("Fields", f.as_str())]);
+                let request = self.http_client.get(self.cal_chk_url.clone()).query(&query);
+                match &credentials.password {
+                    Some(password) => request.basic_auth(&credentials.user, Some(password)),
+                    None => request,
                 }
-                req
             },
-            Self::parse_title_response,
+            |body| Self::parse_cal_chk_response(&self.xml_body(body)),
         )
         .await
+        .map(|(_, programs)| programs)
+    }
+
+    /// Builds query parameters for `TitleLookup`.
+    pub(crate) fn build_title_query(params: &TitleLookupParams) -> Vec<(&'static str, String)> {
+        let mut query: Vec<(&str, String)> = vec![
+            ("Command", String::from("TitleLookup")),
+            ("TID", params.tids.to_syoboi_format()),
+        ];
+
+        if let Some(ref last_update) = params.last_update {
+            query.push(("LastUpdate", last_update.clone()));
+        }
+
+        if let Some(ref fields) = params.fields {
+            query.push(("Fields", fields.join(",")));
+        }
+
+        query
     }
 }
 
 impl LocalSyoboiApi for SyoboiClient {
     #[instrument(skip_all, fields(otel.kind = "Client"), err(level = "error"))]
-    async fn lookup_titles(
-        &self,
-        tids: &[u32],
-        fields: Option<&[&str]>,
-    ) -> Result<Vec<SyoboiTitle>> {
-        self.lookup_titles_with_status(tids, fields)
+    async fn lookup_titles(&self, params: &TitleLookupParams) -> Result<Vec<SyoboiTitle>> {
+        self.lookup_titles_with_status(params)
             .await
             .map(|(_, titles)| titles)
     }
 
     #[instrument(skip_all, fields(otel.kind = "Client"), err(level = "error"))]
     async fn lookup_programs(&self, params: &ProgLookupParams) -> Result<Vec<SyoboiProgram>> {
+        params.validate()?;
+        if self.format == SyoboiFormat::Json {
+            if let Some(query) = Self::build_prog_json_query(params) {
+                let ch_ids = params.ch_ids.as_deref();
+                let range = params.range.as_ref().map(|r| {
+                    (
+                        r.start.format("%Y-%m-%d %H:%M:%S").to_string(),
+                        r.end.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    )
+                });
+                return self
+                    .request_with_retry(
+                        "ProgramByDate",
+                        || self.http_client.get(self.json_url.clone()).query(&query),
+                        Self::parse_prog_json,
+                    )
+                    .await
+                    .map(|(_, data)| {
+                        data.into_iter()
+                            .filter(|p| ch_ids.is_none_or(|ids| ids.contains(&p.ch_id)))
+                            .filter(|p| params.matches(p))
+                            .filter(|p| {
+                                range.as_ref().is_none_or(|(start, end)| {
+                                    p.st_time.as_str() < end.as_str()
+                                        && p.ed_time.as_str() > start.as_str()
+                                })
+                            })
+                            .collect()
+                    });
+            }
+            tracing::debug!("ProgLookup not expressible in json.php, using db.php");
+        }
+
         let query = Self::build_prog_query(params);
 
         self.request_with_retry(
             "ProgLookup",
             || self.http_client.get(self.base_url.clone()).query(&query),
-            Self::parse_prog_response,
+            |body| Self::parse_prog_response(&self.xml_body(body)),
         )
         .await
-        .map(|(_, data)| data)
+        .map(|(_, data)| data.into_iter().filter(|p| params.matches(p)).collect())
     }
 
     #[instrument(skip_all, fields(otel.kind = "Client"), err(level = "error"))]
@@ -419,7 +826,7 @@ impl LocalSyoboiApi for SyoboiClient {
         self.request_with_retry(
             "ChLookup",
             || self.http_client.get(self.base_url.clone()).query(&query),
-            Self::parse_ch_response,
+            |body| Self::parse_ch_response(&self.xml_body(body)),
         )
         .await
         .map(|(_, data)| data)
@@ -445,7 +852,7 @@ impl LocalSyoboiApi for SyoboiClient {
         self.request_with_retry(
             "ChGroupLookup",
             || self.http_client.get(self.base_url.clone()).query(&query),
-            Self::parse_ch_group_response,
+            |body| Self::parse_ch_group_response(&self.xml_body(body)),
         )
         .await
         .map(|(_, data)| data)
@@ -453,6 +860,55 @@ impl LocalSyoboiApi for SyoboiClient {
 }
 
 impl SyoboiClient {
+    /// Builds `json.php` query parameters for a `TitleLookup`.
+    ///
+    /// Returns `None` when the lookup needs `db.php` (TID `*`/ranges or
+    /// `LastUpdate`). `Fields` is ignored: `TitleFull` always returns every
+    /// column.
+    pub(crate) fn build_title_json_query(
+        params: &TitleLookupParams,
+    ) -> Option<Vec<(&'static str, String)>> {
+        let TidSelector::List(ref tids) = params.tids else {
+            return None;
+        };
+        if params.last_update.is_some() || tids.is_empty() {
+            return None;
+        }
+        Some(vec![
+            ("Req", String::from("TitleFull")),
+            ("TID", params.tids.to_syoboi_format()),
+        ])
+    }
+
+    /// Builds `json.php` query parameters for a `ProgLookup`.
+    ///
+    /// `ProgramByDate` needs TIDs and whole days, so this returns `None`
+    /// unless both `tids` and `range` are set and `StTime`/`LastUpdate` are
+    /// not. Channel filtering is applied to the response.
+    pub(crate) fn build_prog_json_query(
+        params: &ProgLookupParams,
+    ) -> Option<Vec<(&'static str, String)>> {
+        let tids = params.tids.as_ref().filter(|t| !t.is_empty())?;
+        let range = params.range.as_ref()?;
+        if params.st_time.is_some() || params.last_update.is_some() || params.pids.is_some() {
+            return None;
+        }
+        let start = range.start.date();
+        let days = range
+            .end
+            .date()
+            .signed_duration_since(start)
+            .num_days()
+            .saturating_add(1)
+            .max(1);
+        Some(vec![
+            ("Req", String::from("ProgramByDate")),
+            ("TID", TidSelector::List(tids.clone()).to_syoboi_format()),
+            ("Start", start.format("%Y-%m-%d").to_string()),
+            ("Days", days.to_string()),
+        ])
+    }
+
     /// Builds query parameters for `ProgLookup`.
     fn build_prog_query(params: &ProgLookupParams) -> Vec<(&'static str, String)> {
         let mut query: Vec<(&str, String)> = vec![("Command", String::from("ProgLookup"))];
@@ -475,6 +931,19 @@ impl SyoboiClient {
             query.push(("ChID", ch_id_str));
         }
 
+        if let Some(ref pids) = params.pids {
+            let pid_str = pids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            query.push(("PID", pid_str));
+        }
+
+        if let Some(count) = params.count.as_ref().and_then(CountRange::to_syoboi_format) {
+            query.push(("Count", count));
+        }
+
         if let Some(ref range) = params.range {
             query.push(("Range", range.to_syoboi_format()));
         }
@@ -507,6 +976,7 @@ mod tests {
     use chrono::NaiveDate;
 
     use super::*;
+    use crate::syoboi::params::TimeRange;
 
     #[test]
     fn test_builder_requires_user_agent() {
@@ -717,7 +1187,10 @@ mod tests {
             .unwrap();
 
         // Act
-        let titles = client.lookup_titles(&[6309], None).await.unwrap();
+        let titles = client
+            .lookup_titles(&TitleLookupParams::from_tids(&[6309]))
+            .await
+            .unwrap();
 
         // Assert
         assert_eq!(titles.len(), 1);
@@ -725,6 +1198,148 @@ mod tests {
         assert_eq!(titles[0].tid, 6309);
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_lookup_checked_programs_with_basic_auth() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let xml_body = include_str!("../../../../fixtures/syoboi/cal_chk.xml");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/cal_chk.php"))
+            .and(wiremock::matchers::query_param("usr", "alice"))
+            .and(wiremock::matchers::query_param("days", "30"))
+            .and(wiremock::matchers::header(
+                "authorization",
+                "Basic YWxpY2U6c2VjcmV0",
+            ))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(xml_body))
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/cal_chk.php"))
+            .respond_with(wiremock::ResponseTemplate::new(401).set_body_string("<html/>"))
+            .mount(&mock_server)
+            .await;
+
+        let base_url: Url = format!("{}/db.php", mock_server.uri()).parse().unwrap();
+        let client_for = |password: &str| {
+            SyoboiClient::builder()
+                .base_url(base_url.clone())
+                .user_agent("test/0.0.0")
+                .min_interval(Duration::from_millis(0))
+                .credentials(SyoboiCredentials {
+                    user: String::from("alice"),
+                    password: Some(password.to_owned()),
+                })
+                .build()
+                .unwrap()
+        };
+
+        // Act
+        let programs = client_for("secret")
+            .lookup_checked_programs(30)
+            .await
+            .unwrap();
+        let rejected = client_for("wrong").lookup_checked_programs(30).await;
+
+        // Assert
+        assert_eq!(programs.len(), 3);
+        assert_eq!(programs[0].tid, 6309);
+        assert!(matches!(
+            rejected,
+            Err(ApiError::HttpStatus { status: 401, .. })
+        ));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_title_lookup_writes_audit_log() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let xml_body = include_str!("../../../../fixtures/syoboi/title_lookup_6309.xml");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/db.php"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(xml_body))
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("audit.jsonl");
+        let base_url = format!("{}/db.php", mock_server.uri());
+        let client = SyoboiClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .audit_log(AuditLog::open(&log_path).unwrap())
+            .build()
+            .unwrap();
+
+        // Act
+        client
+            .lookup_titles(&TitleLookupParams::from_tids(&[6309]))
+            .await
+            .unwrap();
+
+        // Assert
+        let content = std::fs::read_to_string(&log_path).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(entry["client"], "syoboi");
+        assert_eq!(entry["command"], "TitleLookup");
+        assert_eq!(entry["status"], 200);
+        assert_eq!(entry["items"], 1);
+        assert_eq!(entry["retries"], 0);
+        assert_eq!(
+            entry["params_hash"],
+            crate::audit::params_hash("Command=TitleLookup&TID=6309")
+        );
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_title_lookup_record_then_replay_offline() {
+        // Arrange: record one lookup against the mock server
+        let mock_server = wiremock::MockServer::start().await;
+        let xml_body = include_str!("../../../../fixtures/syoboi/title_lookup_6309.xml");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/db.php"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(xml_body))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let params = TitleLookupParams::from_tids(&[6309]);
+        let record_client = SyoboiClient::builder()
+            .base_url(format!("{}/db.php", mock_server.uri()).parse().unwrap())
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .cassette(Cassette::record(dir.path()))
+            .build()
+            .unwrap();
+        let recorded = record_client.lookup_titles(&params).await.unwrap();
+
+        // Act: replay against an unreachable host with the default limits
+        let replay_client = SyoboiClient::builder()
+            .base_url("http://127.0.0.1:1/db.php".parse().unwrap())
+            .user_agent("test/0.0.0")
+            .cassette(Cassette::replay(dir.path()))
+            .build()
+            .unwrap();
+        let replayed = replay_client.lookup_titles(&params).await.unwrap();
+        let missing = replay_client
+            .lookup_titles(&TitleLookupParams::from_tids(&[1]))
+            .await;
+
+        // Assert
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].tid, recorded[0].tid);
+        assert_eq!(replayed[0].title, recorded[0].title);
+        assert!(matches!(missing, Err(ApiError::Io { .. })));
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test]
     async fn test_prog_lookup_via_http() {
@@ -839,6 +1454,43 @@ mod tests {
         assert_eq!(groups[0].ch_group_name, "テレビ 関東");
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_circuit_opens_after_server_errors() {
+        // Arrange: every request fails with 503; the third is never sent
+        let mock_server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .respond_with(wiremock::ResponseTemplate::new(503).set_body_string("<html/>"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/db.php", mock_server.uri());
+        let client = SyoboiClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .circuit_breaker(2, Duration::from_mins(5))
+            .build()
+            .unwrap();
+        let params = TitleLookupParams::from_tids(&[1]);
+
+        // Act
+        let first = client.lookup_titles(&params).await.unwrap_err();
+        let second = client.lookup_titles(&params).await.unwrap_err();
+        let third = client.lookup_titles(&params).await.unwrap_err();
+
+        // Assert
+        assert_eq!(first.status(), Some(503));
+        assert_eq!(second.status(), Some(503));
+        assert!(third.is_circuit_open());
+        assert!(
+            third
+                .retry_after()
+                .is_some_and(|d| d <= Duration::from_mins(5))
+        );
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test]
     async fn test_rate_limiter_enforces_interval() {
@@ -864,8 +1516,14 @@ mod tests {
 
         // Act
         let start = std::time::Instant::now();
-        client.lookup_titles(&[1], None).await.unwrap();
-        client.lookup_titles(&[2], None).await.unwrap();
+        client
+            .lookup_titles(&TitleLookupParams::from_tids(&[1]))
+            .await
+            .unwrap();
+        client
+            .lookup_titles(&TitleLookupParams::from_tids(&[2]))
+            .await
+            .unwrap();
         let elapsed = start.elapsed();
 
         // Assert: at least 100ms interval between two requests
@@ -897,7 +1555,10 @@ mod tests {
             .unwrap();
 
         // Act & Assert (mock expect(1) verifies User-Agent header)
-        client.lookup_titles(&[1], None).await.unwrap();
+        client
+            .lookup_titles(&TitleLookupParams::from_tids(&[1]))
+            .await
+            .unwrap();
     }
 
     #[cfg_attr(miri, ignore)]
@@ -927,7 +1588,7 @@ mod tests {
 
         // Act
         let titles = client
-            .lookup_titles(&[6309], Some(&["TID", "Title", "Cat"]))
+            .lookup_titles(&TitleLookupParams::from_tids(&[6309]).fields(&["TID", "Title", "Cat"]))
             .await
             .unwrap();
 
@@ -1106,6 +1767,7 @@ mod tests {
             last_update: Some(String::from("2024-01-01")),
             join_sub_titles: true,
             fields: Some(vec![String::from("PID"), String::from("TID")]),
+            ..ProgLookupParams::default()
         };
 
         // Act
@@ -1123,6 +1785,36 @@ mod tests {
         assert!(keys.contains(&"Fields"));
     }
 
+    #[test]
+    fn test_build_prog_query_pid_count_and_no_join() {
+        // Arrange
+        let params = ProgLookupParams::default()
+            .pids(&[501, 502])
+            .count(CountRange::single(12))
+            .join_sub_titles(false);
+
+        // Act
+        let query = SyoboiClient::build_prog_query(&params);
+
+        // Assert
+        assert!(query.contains(&("PID", String::from("501,502"))));
+        assert!(query.contains(&("Count", String::from("12"))));
+        assert!(!query.iter().any(|(k, _)| *k == "JOIN"));
+        assert!(SyoboiClient::build_prog_json_query(&params.tids(&[1])).is_none());
+    }
+
+    #[test]
+    fn test_build_prog_query_omits_open_count_range() {
+        // Arrange
+        let params = ProgLookupParams::default().count("13-".parse().unwrap());
+
+        // Act
+        let query = SyoboiClient::build_prog_query(&params);
+
+        // Assert: the open range is applied to the response instead
+        assert!(!query.iter().any(|(k, _)| *k == "Count"));
+    }
+
     #[test]
     fn test_build_prog_query_minimal() {
         // Arrange
@@ -1139,6 +1831,124 @@ mod tests {
         assert_eq!(query[1].1, "SubTitles");
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_json_format_uses_json_php_and_falls_back_to_db_php() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = r#"{"Titles": {"6309": {"TID": "6309",
+            "LastUpdate": "2022-06-30 01:56:20", "Title": "A & <B>"}}}"#;
+        let xml_body = include_str!("../../../../fixtures/syoboi/title_lookup_6309.xml");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/json.php"))
+            .and(wiremock::matchers::query_param("Req", "TitleFull"))
+            .and(wiremock::matchers::query_param("TID", "6309"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/db.php"))
+            .and(wiremock::matchers::query_param("TID", "6000-"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(xml_body))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/db.php", mock_server.uri());
+        let client = SyoboiClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .format(SyoboiFormat::Json)
+            .build()
+            .unwrap();
+
+        // Act
+        let from_json = client
+            .lookup_titles(&TitleLookupParams::from_tids(&[6309]))
+            .await
+            .unwrap();
+        let from_xml = client
+            .lookup_titles(&TitleLookupParams {
+                tids: "6000-".parse().unwrap(),
+                ..TitleLookupParams::default()
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(from_json.len(), 1);
+        assert_eq!(from_json[0].title, "A & <B>");
+        assert_eq!(from_xml.len(), 1);
+    }
+
+    #[test]
+    fn test_build_prog_json_query_requires_tids_and_range() {
+        // Arrange
+        let range = TimeRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(6, 0, 0)
+                .unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 3)
+                .unwrap()
+                .and_hms_opt(5, 0, 0)
+                .unwrap(),
+        );
+        let params = ProgLookupParams {
+            tids: Some(vec![6309, 7667]),
+            range: Some(range.clone()),
+            ..ProgLookupParams::default()
+        };
+        let without_tids = ProgLookupParams {
+            range: Some(range),
+            ..ProgLookupParams::default()
+        };
+
+        // Act
+        let query = SyoboiClient::build_prog_json_query(&params).unwrap();
+
+        // Assert
+        assert_eq!(
+            query,
+            vec![
+                ("Req", String::from("ProgramByDate")),
+                ("TID", String::from("6309,7667")),
+                ("Start", String::from("2024-01-01")),
+                ("Days", String::from("3")),
+            ]
+        );
+        assert!(SyoboiClient::build_prog_json_query(&without_tids).is_none());
+    }
+
+    #[test]
+    fn test_build_title_query_range_with_last_update() {
+        // Arrange
+        let params = TitleLookupParams {
+            tids: TidSelector::Range {
+                start: 6000,
+                end: None,
+            },
+            last_update: Some(String::from("20240401_000000-")),
+            fields: None,
+        };
+
+        // Act
+        let query = SyoboiClient::build_title_query(&params);
+
+        // Assert
+        assert_eq!(
+            query,
+            vec![
+                ("Command", String::from("TitleLookup")),
+                ("TID", String::from("6000-")),
+                ("LastUpdate", String::from("20240401_000000-")),
+            ]
+        );
+    }
+
     #[cfg_attr(miri, ignore)]
     #[test]
     fn test_builder_with_custom_limits() {
@@ -1241,6 +2051,9 @@ mod tests {
             .unwrap();
 
         // Act & Assert (mock expect(1) + query_param_is_missing verifies no Fields param)
-        client.lookup_titles(&[1], None).await.unwrap();
+        client
+            .lookup_titles(&TitleLookupParams::from_tids(&[1]))
+            .await
+            .unwrap();
     }
 }
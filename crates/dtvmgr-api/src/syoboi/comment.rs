@@ -0,0 +1,222 @@
+//! Syoboi title `Comment` parsing.
+
+/// Section of a title comment an entry belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// `*スタッフ`.
+    Staff,
+    /// `*キャスト`.
+    Cast,
+    /// `*リンク`.
+    Links,
+}
+
+impl Section {
+    /// Maps a top-level heading to its section (`None` for others, e.g.
+    /// `*メモ` or theme songs).
+    fn from_heading(heading: &str) -> Option<Self> {
+        if heading.starts_with("スタッフ") {
+            Some(Self::Staff)
+        } else if heading.starts_with("キャスト") {
+            Some(Self::Cast)
+        } else if heading.starts_with("リンク") {
+            Some(Self::Links)
+        } else {
+            None
+        }
+    }
+}
+
+/// A `:role:name` entry of the staff or cast section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentCredit {
+    /// Role (staff position or character name; may be empty).
+    pub role: String,
+    /// Person or company name.
+    pub name: String,
+}
+
+/// A `-[[label url]]` entry of the links section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentLink {
+    /// Link label (e.g. "公式"; empty when the entry has none).
+    pub label: String,
+    /// Link URL.
+    pub url: String,
+}
+
+/// Structured staff, cast, and links extracted from a title comment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TitleComment {
+    /// Entries of the `*スタッフ` section, in comment order.
+    pub staff: Vec<CommentCredit>,
+    /// Entries of the `*キャスト` section, in comment order.
+    pub cast: Vec<CommentCredit>,
+    /// Entries of the `*リンク` section, in comment order.
+    pub links: Vec<CommentLink>,
+}
+
+impl TitleComment {
+    /// Returns whether no staff, cast, or links were found.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.staff.is_empty() && self.cast.is_empty() && self.links.is_empty()
+    }
+}
+
+/// Extracts the staff, cast, and links sections from a wiki-formatted
+/// `Comment`. Other sections (memo, theme songs, ...) and malformed lines
+/// are skipped.
+///
+/// # Input format
+///
+/// ```text
+/// *リンク
+/// -[[公式 https://example.com/]]
+/// *スタッフ
+/// :原作:遠藤達哉
+/// *キャスト
+/// :ロイド・フォージャー:江口拓也
+/// ```
+///
+/// Sub-headings (`**...`) stay in the enclosing section.
+#[must_use]
+pub fn parse_comment(raw: &str) -> TitleComment {
+    let mut comment = TitleComment::default();
+    let mut section = None;
+    for line in raw.lines().map(str::trim) {
+        if let Some(heading) = line.strip_prefix('*') {
+            if !heading.starts_with('*') {
+                section = Section::from_heading(heading.trim());
+            }
+            continue;
+        }
+        match section {
+            Some(Section::Staff) => comment.staff.extend(parse_credit(line)),
+            Some(Section::Cast) => comment.cast.extend(parse_credit(line)),
+            Some(Section::Links) => comment.links.extend(parse_link(line)),
+            None => {}
+        }
+    }
+    comment
+}
+
+/// Parses `:role:name` (or `:name` without a role).
+fn parse_credit(line: &str) -> Option<CommentCredit> {
+    let rest = line.strip_prefix(':')?;
+    let (role, name) = rest.split_once(':').unwrap_or(("", rest));
+    let name = strip_wiki_link(name.trim());
+    if name.is_empty() {
+        return None;
+    }
+    Some(CommentCredit {
+        role: role.trim().to_owned(),
+        name: name.to_owned(),
+    })
+}
+
+/// Parses `-[[label url]]` (or `-[[url]]`).
+fn parse_link(line: &str) -> Option<CommentLink> {
+    let inner = line
+        .strip_prefix('-')?
+        .trim()
+        .strip_prefix("[[")?
+        .strip_suffix("]]")?
+        .trim();
+    let (label, url) = inner
+        .rsplit_once(char::is_whitespace)
+        .unwrap_or(("", inner));
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return None;
+    }
+    Some(CommentLink {
+        label: label.trim().to_owned(),
+        url: url.to_owned(),
+    })
+}
+
+/// Removes `[[...]]` around a name linked to another wiki page.
+fn strip_wiki_link(name: &str) -> &str {
+    name.strip_prefix("[[")
+        .and_then(|n| n.strip_suffix("]]"))
+        .map_or(name, str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credit(role: &str, name: &str) -> CommentCredit {
+        CommentCredit {
+            role: role.to_owned(),
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_parse_comment_extracts_sections() {
+        // Arrange
+        let raw = "*リンク\n\
+                   -[[公式 https://spy-family.net/]]\n\
+                   -[[https://x.com/spyfamily_anime]]\n\
+                   -[[壊れたリンク]]\n\
+                   *メモ\n\
+                   :ignored:memo\n\
+                   *スタッフ\n\
+                   :原作:遠藤達哉(集英社)\n\
+                   :アニメーション制作:[[WIT STUDIO]]\n\
+                   **第2クール\n\
+                   :監督:古橋一浩\n\
+                   :空欄:\n\
+                   *オープニングテーマ「ミックスナッツ」\n\
+                   :歌:Official髭男dism\n\
+                   *キャスト\n\
+                   :ロイド・フォージャー:江口拓也\n\
+                   :種﨑敦美\n";
+
+        // Act
+        let comment = parse_comment(raw);
+
+        // Assert
+        assert_eq!(
+            comment.staff,
+            vec![
+                credit("原作", "遠藤達哉(集英社)"),
+                credit("アニメーション制作", "WIT STUDIO"),
+                credit("監督", "古橋一浩"),
+            ]
+        );
+        assert_eq!(
+            comment.cast,
+            vec![
+                credit("ロイド・フォージャー", "江口拓也"),
+                credit("", "種﨑敦美")
+            ]
+        );
+        assert_eq!(
+            comment.links,
+            vec![
+                CommentLink {
+                    label: String::from("公式"),
+                    url: String::from("https://spy-family.net/"),
+                },
+                CommentLink {
+                    label: String::new(),
+                    url: String::from("https://x.com/spyfamily_anime"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_comment_without_sections_is_empty() {
+        // Arrange
+        let raw = "放送開始日未定\n:原作:誰か";
+
+        // Act
+        let comment = parse_comment(raw);
+
+        // Assert
+        assert!(comment.is_empty());
+    }
+}
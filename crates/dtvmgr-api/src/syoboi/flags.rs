@@ -0,0 +1,82 @@
+//! Syoboi program flag (`Flag`) bitmask.
+
+bitflags::bitflags! {
+    /// Decoded Syoboi program `Flag` bitmask.
+    ///
+    /// Unknown bits are retained so a value round-trips through
+    /// [`Self::from_flag`] and [`Self::bits`] unchanged.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct ProgramFlags: u32 {
+        /// Caution: schedule subject to change (注, bit 1).
+        const CAUTION = 1;
+        /// New series or season (新, bit 2).
+        const NEW = 2;
+        /// Final episode (終, bit 4).
+        const FINAL = 4;
+        /// Rerun (再, bit 8).
+        const RERUN = 8;
+    }
+}
+
+impl ProgramFlags {
+    /// Decodes a `Flag` value. A missing value has no flags set.
+    #[must_use]
+    pub const fn from_flag(flag: Option<u32>) -> Self {
+        match flag {
+            Some(bits) => Self::from_bits_retain(bits),
+            None => Self::empty(),
+        }
+    }
+
+    /// Returns whether the caution flag (注) is set.
+    #[must_use]
+    pub const fn is_caution(self) -> bool {
+        self.contains(Self::CAUTION)
+    }
+
+    /// Returns whether the new series flag (新) is set.
+    #[must_use]
+    pub const fn is_new(self) -> bool {
+        self.contains(Self::NEW)
+    }
+
+    /// Returns whether the final episode flag (終) is set.
+    #[must_use]
+    pub const fn is_final(self) -> bool {
+        self.contains(Self::FINAL)
+    }
+
+    /// Returns whether the rerun flag (再) is set.
+    #[must_use]
+    pub const fn is_rerun(self) -> bool {
+        self.contains(Self::RERUN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_flag_decodes_bits() {
+        // Act
+        let flags = ProgramFlags::from_flag(Some(13));
+
+        // Assert
+        assert!(flags.is_caution());
+        assert!(!flags.is_new());
+        assert!(flags.is_final());
+        assert!(flags.is_rerun());
+        assert_eq!(ProgramFlags::from_flag(None), ProgramFlags::empty());
+    }
+
+    #[test]
+    fn test_from_flag_retains_unknown_bits() {
+        // Act
+        let flags = ProgramFlags::from_flag(Some(16 | 2));
+
+        // Assert
+        assert!(flags.is_new());
+        assert_eq!(flags.bits(), 18);
+    }
+}
@@ -0,0 +1,206 @@
+//! `json.php` response types and normalization.
+//!
+//! `json.php` returns every column as a JSON string (or `null`) keyed by ID,
+//! e.g. `{"Titles": {"6309": {"TID": "6309", ...}}}`. Items are normalized
+//! into the shape the shared [`SyoboiTitle`] / [`SyoboiProgram`] deserializers
+//! expect before decoding.
+
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::params::SYOBOI_TIMEZONE;
+use super::types::{SyoboiProgram, SyoboiTitle};
+use crate::error::{ApiError, Result};
+
+/// Keys decoded as plain integers by the shared types.
+const NUMERIC_KEYS: &[&str] = &["TID", "PID", "ChID"];
+
+/// Keys holding Unix timestamps in `ProgramByDate` responses.
+const TIMESTAMP_KEYS: &[&str] = &["StTime", "EdTime"];
+
+/// `Req=TitleFull` response.
+#[derive(Debug, Deserialize)]
+pub struct TitleFullResponse {
+    /// Titles keyed by TID (`[]` when none match).
+    #[serde(rename = "Titles", default)]
+    pub titles: Value,
+}
+
+/// `Req=ProgramByDate` response.
+#[derive(Debug, Deserialize)]
+pub struct ProgramByDateResponse {
+    /// Programs keyed by PID (`[]` when none match).
+    #[serde(rename = "Programs", default)]
+    pub programs: Value,
+}
+
+/// Decodes the titles of a `TitleFull` response.
+///
+/// # Errors
+///
+/// Returns an error if an item cannot be decoded.
+pub fn decode_titles(response: TitleFullResponse) -> Result<Vec<SyoboiTitle>> {
+    let mut titles = items(response.titles)
+        .map(|item| {
+            SyoboiTitle::deserialize(normalize(item)?)
+                .map_err(|e| invalid_item("failed to decode title item", "", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    titles.sort_by_key(|t| t.tid);
+    Ok(titles)
+}
+
+/// Decodes the programs of a `ProgramByDate` response.
+///
+/// # Errors
+///
+/// Returns an error if an item cannot be decoded.
+pub fn decode_programs(response: ProgramByDateResponse) -> Result<Vec<SyoboiProgram>> {
+    let mut programs = items(response.programs)
+        .map(|item| {
+            SyoboiProgram::deserialize(normalize(item)?)
+                .map_err(|e| invalid_item("failed to decode program item", "", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    programs.sort_by(|a, b| a.st_time.cmp(&b.st_time).then(a.pid.cmp(&b.pid)));
+    Ok(programs)
+}
+
+/// Builds a decode error for an item, with `text` as the preview.
+fn invalid_item(
+    message: &str,
+    text: &str,
+    source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> ApiError {
+    ApiError::decode(message.to_owned(), text, source)
+}
+
+/// Iterates the items of an ID-keyed object (or an empty array).
+fn items(value: Value) -> impl Iterator<Item = Value> {
+    let items: Vec<Value> = match value {
+        Value::Object(map) => map.into_iter().map(|(_, v)| v).collect(),
+        Value::Array(list) => list,
+        _ => Vec::new(),
+    };
+    items.into_iter()
+}
+
+/// Converts an item to the string-typed shape of `db.php` XML.
+///
+/// `null` columns are dropped, numbers become strings, Unix timestamps
+/// become `YYYY-MM-DD HH:MM:SS` (JST), and ID columns become integers.
+fn normalize(item: Value) -> Result<Value> {
+    let Value::Object(map) = item else {
+        return Err(ApiError::Decode {
+            message: String::from("item is not a JSON object"),
+            preview: item.to_string(),
+            source: None,
+        });
+    };
+    let mut out = Map::with_capacity(map.len());
+    for (key, value) in map {
+        let text = match value {
+            Value::Null => continue,
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        let normalized = if NUMERIC_KEYS.contains(&key.as_str()) {
+            let id: u64 = text
+                .parse()
+                .map_err(|e| invalid_item(&format!("invalid {key}: {text}"), &text, e))?;
+            Value::from(id)
+        } else if TIMESTAMP_KEYS.contains(&key.as_str()) {
+            Value::String(format_timestamp(&text)?)
+        } else {
+            Value::String(text)
+        };
+        out.insert(key, normalized);
+    }
+    Ok(Value::Object(out))
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM:SS` in JST.
+///
+/// Values that are already formatted are passed through.
+fn format_timestamp(text: &str) -> Result<String> {
+    let Ok(secs) = text.parse::<i64>() else {
+        return Ok(text.to_owned());
+    };
+    let dt = DateTime::from_timestamp(secs, 0).ok_or_else(|| ApiError::Decode {
+        message: format!("timestamp out of range: {secs}"),
+        preview: text.to_owned(),
+        source: None,
+    })?;
+    Ok(dt
+        .with_timezone(&SYOBOI_TIMEZONE)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    #[test]
+    fn test_decode_titles_from_string_columns() {
+        // Arrange: unescaped `&` and `<` are fine in JSON
+        let json = r#"{"Titles": {"6309": {
+            "TID": "6309", "LastUpdate": "2022-06-30 01:56:20",
+            "Title": "SPY×FAMILY", "ShortTitle": "", "Cat": "1",
+            "FirstYear": "2022", "UserPoint": null,
+            "Comment": "*スタッフ\n:原作:遠藤達哉 & <集英社>"
+        }}}"#;
+
+        // Act
+        let response: TitleFullResponse = serde_json::from_str(json).unwrap();
+        let titles = decode_titles(response).unwrap();
+
+        // Assert
+        assert_eq!(titles.len(), 1);
+        assert_eq!(titles[0].tid, 6309);
+        assert_eq!(titles[0].title, "SPY×FAMILY");
+        assert_eq!(titles[0].short_title, None);
+        assert_eq!(titles[0].cat, Some(1));
+        assert_eq!(titles[0].first_year, Some(2022));
+        assert_eq!(titles[0].user_point, None);
+        assert!(titles[0].comment.as_deref().unwrap().contains("& <集英社>"));
+    }
+
+    #[test]
+    fn test_decode_programs_converts_unix_timestamps_to_jst() {
+        // Arrange
+        let json = r#"{"Programs": {"560001": {
+            "PID": "560001", "TID": "6309", "ChID": "7",
+            "StTime": "1649512800", "EdTime": "1649514600",
+            "Count": "1", "SubTitle": "オペレーション〈梟〉", "Flag": "2"
+        }}}"#;
+
+        // Act
+        let response: ProgramByDateResponse = serde_json::from_str(json).unwrap();
+        let programs = decode_programs(response).unwrap();
+
+        // Assert
+        assert_eq!(programs.len(), 1);
+        assert_eq!(programs[0].pid, 560_001);
+        assert_eq!(programs[0].ch_id, 7);
+        assert_eq!(programs[0].st_time, "2022-04-09 23:00:00");
+        assert_eq!(programs[0].ed_time, "2022-04-09 23:30:00");
+        assert_eq!(programs[0].count, Some(1));
+        assert_eq!(programs[0].flag, Some(2));
+    }
+
+    #[test]
+    fn test_decode_empty_array_and_invalid_item() {
+        // Arrange
+        let empty: TitleFullResponse = serde_json::from_str(r#"{"Titles": []}"#).unwrap();
+        let invalid: TitleFullResponse =
+            serde_json::from_str(r#"{"Titles": {"1": {"TID": "abc"}}}"#).unwrap();
+
+        // Act & Assert
+        assert!(decode_titles(empty).unwrap().is_empty());
+        assert!(decode_titles(invalid).is_err());
+    }
+}
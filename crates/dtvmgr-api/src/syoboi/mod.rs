@@ -1,11 +1,17 @@
 //! Syoboi Calendar API client module.
 //!
-//! Handles HTTP requests to the Syoboi Calendar `db.php` endpoint
-//! and retrieves title, program, and channel data.
+//! Handles HTTP requests to the Syoboi Calendar `db.php` (XML) and
+//! `json.php` (JSON) endpoints and retrieves title, program, and channel data.
 
 mod api;
+mod cal_chk;
+mod category;
 mod client;
+mod comment;
+mod flags;
+mod json;
 mod params;
+mod progress;
 mod rate_limiter;
 mod types;
 mod util;
@@ -13,10 +19,27 @@ pub(crate) mod xml;
 
 #[allow(clippy::module_name_repetitions)]
 pub use api::{LocalSyoboiApi, SyoboiApi};
+#[allow(clippy::module_name_repetitions)]
+pub use cal_chk::{CheckedProgram, DEFAULT_CAL_CHK_DAYS, SyoboiCredentials, checked_tids};
+pub use category::TitleCategory;
 pub use client::SYOBOI_BASE_URL;
 #[allow(clippy::module_name_repetitions)]
-pub use client::{SyoboiClient, SyoboiClientBuilder};
-pub use params::{ProgLookupParams, TimeRange, resolve_time_range};
+pub use client::{SyoboiClient, SyoboiClientBuilder, SyoboiFormat};
+pub use comment::{CommentCredit, CommentLink, TitleComment, parse_comment};
+pub use flags::ProgramFlags;
+pub use params::{
+    AnimeSeason, BROADCAST_DAY_START_HOUR, CountRange, DEFAULT_RANGE_DAYS, ProgLookupParams,
+    SYOBOI_TIMEZONE, TidSelector, TimeRange, TitleLookupParams, month_ranges, resolve_season_range,
+    resolve_time_range, resolve_time_range_with, to_naive_datetime_since, to_naive_datetime_until,
+};
+pub use progress::{NoProgress, SyncProgress, SyncStage};
+pub use rate_limiter::default_rate_limit;
 #[allow(clippy::module_name_repetitions)]
-pub use types::{SyoboiChannel, SyoboiChannelGroup, SyoboiProgram, SyoboiTitle};
-pub use util::{lookup_all_programs, parse_sub_titles};
+pub use types::{
+    PROGRAM_FIELDS, REQUIRED_PROGRAM_FIELDS, REQUIRED_TITLE_FIELDS, SyoboiChannel,
+    SyoboiChannelGroup, SyoboiProgram, SyoboiTitle, TITLE_FIELDS,
+};
+pub use util::{
+    lookup_all_programs, lookup_all_programs_with_progress, lookup_updated_programs,
+    lookup_updated_programs_with_progress, parse_sub_titles,
+};
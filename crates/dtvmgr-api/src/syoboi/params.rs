@@ -1,7 +1,17 @@
 //! Syoboi Calendar API request parameter types.
 
-use anyhow::{Context, Result, bail};
-use chrono::{Duration, Local, NaiveDateTime};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ApiError, Result};
+
+/// Timezone of every Syoboi Calendar timestamp and `Range` parameter
+/// (JST, no daylight saving time).
+pub const SYOBOI_TIMEZONE: Tz = chrono_tz::Asia::Tokyo;
+
+/// Days before and after now covered by a default time range.
+pub const DEFAULT_RANGE_DAYS: u32 = 1;
 
 /// `Range` parameter for `ProgLookup`.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,13 +42,96 @@ impl TimeRange {
     }
 }
 
+/// Longest closed [`CountRange`] sent as a `Count` list; longer and
+/// open-ended ranges are applied to the response instead.
+const MAX_COUNT_LIST: u32 = 100;
+
+/// `Count` (episode number) filter for `ProgLookup`.
+///
+/// Syoboi takes a comma-separated list of episode numbers, so a closed
+/// range is expanded; open-ended ranges are applied to the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountRange {
+    /// First episode number.
+    pub start: u32,
+    /// Last episode number (inclusive); open-ended when `None`.
+    pub end: Option<u32>,
+}
+
+impl CountRange {
+    /// Matches exactly one episode number.
+    #[must_use]
+    pub const fn single(count: u32) -> Self {
+        Self {
+            start: count,
+            end: Some(count),
+        }
+    }
+
+    /// Returns `true` if `count` is within the range.
+    #[must_use]
+    pub fn contains(&self, count: u32) -> bool {
+        count >= self.start && self.end.is_none_or(|end| count <= end)
+    }
+
+    /// Formats as Syoboi `Count` string (`1,2,3`), or `None` for an
+    /// open-ended or overly long range.
+    #[must_use]
+    pub fn to_syoboi_format(&self) -> Option<String> {
+        let end = self.end?;
+        if end.checked_sub(self.start)? >= MAX_COUNT_LIST {
+            return None;
+        }
+        Some(
+            (self.start..=end)
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+}
+
+impl std::str::FromStr for CountRange {
+    type Err = ApiError;
+
+    /// Parses `N`, `START-END`, or `START-`.
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<u32>()
+                .map_err(|e| ApiError::Invalid(format!("invalid episode count: {s}: {e}")))
+        };
+        let Some((start, end)) = s.split_once('-') else {
+            return parse(s).map(Self::single);
+        };
+        let start = parse(start)?;
+        let end = match end.trim() {
+            "" => None,
+            e => Some(parse(e)?),
+        };
+        if end.is_some_and(|e| e < start) {
+            return Err(ApiError::Invalid(format!(
+                "episode count range end is before start: {s}"
+            )));
+        }
+        Ok(Self { start, end })
+    }
+}
+
 /// Request parameters for `ProgLookup`.
+///
+/// Fields can be set directly or through the chainable setters; call
+/// [`validate`](Self::validate) (the client does) before sending.
 #[derive(Debug, Clone)]
 pub struct ProgLookupParams {
     /// Title ID filter (`None` = all titles).
     pub tids: Option<Vec<u32>>,
     /// Channel ID filter (`None` = all channels).
     pub ch_ids: Option<Vec<u32>>,
+    /// Program ID filter (`PID` parameter, `None` = all programs).
+    pub pids: Option<Vec<u32>>,
+    /// Episode number filter (`Count` parameter).
+    pub count: Option<CountRange>,
     /// Time range (`Range` parameter).
     pub range: Option<TimeRange>,
     /// Start time filter (`StTime` parameter).
@@ -56,6 +149,8 @@ impl Default for ProgLookupParams {
         Self {
             tids: None,
             ch_ids: None,
+            pids: None,
+            count: None,
             range: None,
             st_time: None,
             last_update: None,
@@ -65,6 +160,235 @@ impl Default for ProgLookupParams {
     }
 }
 
+impl ProgLookupParams {
+    /// Restricts the lookup to the given titles (`TID` parameter).
+    #[must_use]
+    pub fn tids(mut self, tids: &[u32]) -> Self {
+        self.tids = Some(tids.to_vec());
+        self
+    }
+
+    /// Restricts the lookup to the given channels (`ChID` parameter).
+    #[must_use]
+    pub fn ch_ids(mut self, ch_ids: &[u32]) -> Self {
+        self.ch_ids = Some(ch_ids.to_vec());
+        self
+    }
+
+    /// Restricts the lookup to the given programs (`PID` parameter).
+    #[must_use]
+    pub fn pids(mut self, pids: &[u32]) -> Self {
+        self.pids = Some(pids.to_vec());
+        self
+    }
+
+    /// Restricts the lookup to the given episode numbers (`Count` parameter).
+    #[must_use]
+    pub const fn count(mut self, count: CountRange) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Sets the time range (`Range` parameter).
+    #[must_use]
+    pub const fn range(mut self, range: TimeRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Restricts the lookup to programs updated at or after `since`.
+    #[must_use]
+    pub fn last_update_since(mut self, since: NaiveDateTime) -> Self {
+        self.last_update = Some(format!("{}-", since.format("%Y%m%d_%H%M%S")));
+        self
+    }
+
+    /// Sets whether to join the `SubTitles` table (`JOIN` parameter).
+    #[must_use]
+    pub const fn join_sub_titles(mut self, join: bool) -> Self {
+        self.join_sub_titles = join;
+        self
+    }
+
+    /// Restricts the returned fields (`Fields` parameter).
+    #[must_use]
+    pub fn fields(mut self, fields: &[&str]) -> Self {
+        self.fields = Some(fields.iter().map(|f| (*f).to_owned()).collect());
+        self
+    }
+
+    /// Returns `true` if `program` passes the filters the API cannot apply
+    /// itself (an episode count range).
+    #[must_use]
+    pub fn matches(&self, program: &super::types::SyoboiProgram) -> bool {
+        self.count
+            .is_none_or(|c| program.count.is_some_and(|n| c.contains(n)))
+    }
+
+    /// Checks that the parameters form a valid request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::Invalid`] if an ID or field list is empty, the
+    /// time range ends before it starts, or the episode count range is
+    /// reversed.
+    pub fn validate(&self) -> Result<()> {
+        for (name, ids) in [
+            ("TID", &self.tids),
+            ("ChID", &self.ch_ids),
+            ("PID", &self.pids),
+        ] {
+            if ids.as_ref().is_some_and(Vec::is_empty) {
+                return Err(ApiError::Invalid(format!(
+                    "ProgLookup {name} filter must not be empty"
+                )));
+            }
+        }
+        if self.fields.as_ref().is_some_and(Vec::is_empty) {
+            return Err(ApiError::Invalid(String::from(
+                "ProgLookup Fields must not be empty",
+            )));
+        }
+        if let Some(range) = &self.range
+            && range.end < range.start
+        {
+            return Err(ApiError::Invalid(format!(
+                "ProgLookup Range ends before it starts: {}",
+                range.to_syoboi_format()
+            )));
+        }
+        if let Some(count) = self.count
+            && count.end.is_some_and(|end| end < count.start)
+        {
+            return Err(ApiError::Invalid(format!(
+                "ProgLookup Count range is reversed: {}-{:?}",
+                count.start, count.end
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// `TID` parameter for `TitleLookup`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TidSelector {
+    /// Every title (`TID=*`).
+    #[default]
+    All,
+    /// Explicit title IDs (`TID=6309,7667`).
+    List(Vec<u32>),
+    /// Inclusive range (`TID=6000-6999`); open-ended when `end` is `None`
+    /// (`TID=6000-`).
+    Range {
+        /// First TID.
+        start: u32,
+        /// Last TID (inclusive).
+        end: Option<u32>,
+    },
+}
+
+impl TidSelector {
+    /// Formats as Syoboi `TID` string.
+    #[must_use]
+    pub fn to_syoboi_format(&self) -> String {
+        match self {
+            Self::All => String::from("*"),
+            Self::List(tids) => tids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            Self::Range { start, end } => {
+                format!(
+                    "{start}-{}",
+                    end.map_or_else(String::new, |e| e.to_string())
+                )
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for TidSelector {
+    type Err = ApiError;
+
+    /// Parses `*`, `START-[END]`, or a comma-separated TID list.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s == "*" {
+            return Ok(Self::All);
+        }
+        if let Some((start, end)) = s.split_once('-') {
+            let start: u32 = start
+                .trim()
+                .parse()
+                .map_err(|e| ApiError::Invalid(format!("invalid TID range start: {s}: {e}")))?;
+            let end =
+                match end.trim() {
+                    "" => None,
+                    e => Some(e.parse::<u32>().map_err(|e| {
+                        ApiError::Invalid(format!("invalid TID range end: {s}: {e}"))
+                    })?),
+                };
+            if end.is_some_and(|e| e < start) {
+                return Err(ApiError::Invalid(format!(
+                    "TID range end is before start: {s}"
+                )));
+            }
+            return Ok(Self::Range { start, end });
+        }
+        s.split(',')
+            .map(|t| {
+                t.trim()
+                    .parse::<u32>()
+                    .map_err(|e| ApiError::Invalid(format!("invalid TID: {t}: {e}")))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Self::List)
+    }
+}
+
+/// Request parameters for `TitleLookup`.
+#[derive(Debug, Clone, Default)]
+pub struct TitleLookupParams {
+    /// Title selector (`TID` parameter, default `*`).
+    pub tids: TidSelector,
+    /// Last update filter (`LastUpdate` parameter, e.g. `20240101_000000-`).
+    pub last_update: Option<String>,
+    /// Restrict output fields.
+    pub fields: Option<Vec<String>>,
+}
+
+impl TitleLookupParams {
+    /// Looks up the given TIDs.
+    #[must_use]
+    pub fn from_tids(tids: &[u32]) -> Self {
+        Self {
+            tids: TidSelector::List(tids.to_vec()),
+            ..Self::default()
+        }
+    }
+
+    /// Looks up every title updated at or after `since`.
+    #[must_use]
+    pub fn updated_since(since: NaiveDateTime) -> Self {
+        Self::default().last_update_since(since)
+    }
+
+    /// Restricts the lookup to titles updated at or after `since`.
+    #[must_use]
+    pub fn last_update_since(mut self, since: NaiveDateTime) -> Self {
+        self.last_update = Some(format!("{}-", since.format("%Y%m%d_%H%M%S")));
+        self
+    }
+
+    /// Restricts the returned fields (`Fields` parameter).
+    #[must_use]
+    pub fn fields(mut self, fields: &[&str]) -> Self {
+        self.fields = Some(fields.iter().map(|f| (*f).to_owned()).collect());
+        self
+    }
+}
+
 /// Tries full datetime formats, returns `None` if both fail.
 fn try_full_datetime(s: &str) -> Option<NaiveDateTime> {
     NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
@@ -84,7 +408,7 @@ pub fn to_naive_datetime_since(s: &str) -> Result<NaiveDateTime> {
         return Ok(dt);
     }
     NaiveDateTime::parse_from_str(&format!("{s}T00:00:00"), "%Y-%m-%dT%H:%M:%S")
-        .with_context(|| format!("invalid datetime format: {s}"))
+        .map_err(|e| ApiError::Invalid(format!("invalid datetime format: {s}: {e}")))
 }
 
 /// Converts a datetime string for `--time-until` (date-only defaults to `23:59:59`).
@@ -99,7 +423,7 @@ pub fn to_naive_datetime_until(s: &str) -> Result<NaiveDateTime> {
         return Ok(dt);
     }
     NaiveDateTime::parse_from_str(&format!("{s}T23:59:59"), "%Y-%m-%dT%H:%M:%S")
-        .with_context(|| format!("invalid datetime format: {s}"))
+        .map_err(|e| ApiError::Invalid(format!("invalid datetime format: {s}: {e}")))
 }
 
 /// Resolves time range from optional since/until strings using local timezone.
@@ -111,15 +435,32 @@ pub fn to_naive_datetime_until(s: &str) -> Result<NaiveDateTime> {
 /// Returns an error if only one of since/until is specified,
 /// or if datetime parsing fails.
 pub fn resolve_time_range(time_since: Option<&str>, time_until: Option<&str>) -> Result<TimeRange> {
+    resolve_time_range_with(time_since, time_until, &SystemClock, DEFAULT_RANGE_DAYS)
+}
+
+/// Like [`resolve_time_range`], but reads "now" from `clock` and defaults
+/// to `[now - range_days, now + range_days]`.
+///
+/// # Errors
+///
+/// Returns an error if only one of since/until is specified,
+/// or if datetime parsing fails.
+pub fn resolve_time_range_with(
+    time_since: Option<&str>,
+    time_until: Option<&str>,
+    clock: &dyn Clock,
+    range_days: u32,
+) -> Result<TimeRange> {
     match (time_since, time_until) {
         (None, None) => {
-            let now = Local::now().naive_local();
+            let now = clock.now_in(SYOBOI_TIMEZONE);
+            let days = Duration::days(i64::from(range_days));
             let start = now
-                .checked_sub_signed(Duration::days(1))
-                .context("failed to compute start time")?;
+                .checked_sub_signed(days)
+                .ok_or_else(|| ApiError::Invalid(String::from("failed to compute start time")))?;
             let end = now
-                .checked_add_signed(Duration::days(1))
-                .context("failed to compute end time")?;
+                .checked_add_signed(days)
+                .ok_or_else(|| ApiError::Invalid(String::from("failed to compute end time")))?;
             Ok(TimeRange::new(start, end))
         }
         (Some(since), Some(until)) => {
@@ -127,12 +468,150 @@ pub fn resolve_time_range(time_since: Option<&str>, time_until: Option<&str>) ->
             let end = to_naive_datetime_until(until)?;
             Ok(TimeRange::new(start, end))
         }
-        _ => {
-            bail!("both --time-since and --time-until must be specified together");
+        _ => Err(ApiError::Invalid(String::from(
+            "both --time-since and --time-until must be specified together",
+        ))),
+    }
+}
+
+/// Hour (JST) a broadcast day starts at. Late-night programs before it
+/// (`25:30` = 01:30 the next day) belong to the previous day.
+pub const BROADCAST_DAY_START_HOUR: u32 = 5;
+
+/// A Japanese anime broadcast season (cour): `q1` winter (January-March),
+/// `q2` spring (April-June), `q3` summer (July-September), `q4` autumn
+/// (October-December).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimeSeason {
+    /// Calendar year.
+    pub year: i32,
+    /// Quarter, 1-4.
+    pub quarter: u32,
+}
+
+impl AnimeSeason {
+    /// Returns the season whose broadcast days include `at` (JST), so
+    /// `2024-07-01 03:00` still belongs to `2024q2`.
+    #[must_use]
+    pub fn containing(at: NaiveDateTime) -> Self {
+        let day = at
+            .checked_sub_signed(Duration::hours(i64::from(BROADCAST_DAY_START_HOUR)))
+            .unwrap_or(at)
+            .date();
+        Self {
+            year: day.year(),
+            quarter: day.quarter(),
+        }
+    }
+
+    /// Parses `YYYYqN` (e.g. `2024q2`) or `now`, the current season on
+    /// `clock`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for any other input or a quarter outside 1-4.
+    pub fn parse_with(s: &str, clock: &dyn Clock) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("now") {
+            return Ok(Self::containing(clock.now_in(SYOBOI_TIMEZONE)));
+        }
+        let invalid = || ApiError::Invalid(format!("invalid season (expected YYYYqN or now): {s}"));
+        let (year, quarter) = s.split_once(['q', 'Q']).ok_or_else(invalid)?;
+        let year: i32 = year.parse().map_err(|_| invalid())?;
+        let quarter: u32 = quarter.parse().map_err(|_| invalid())?;
+        if !(1..=4).contains(&quarter) {
+            return Err(invalid());
         }
+        Ok(Self { year, quarter })
+    }
+
+    /// Returns the first calendar month of the season.
+    const fn first_month(self) -> u32 {
+        self.quarter
+            .saturating_sub(1)
+            .saturating_mul(3)
+            .saturating_add(1)
+    }
+
+    /// Returns the season's broadcast window: from the first day's
+    /// [`BROADCAST_DAY_START_HOUR`] to just before that hour on the first
+    /// day of the next season, so late-night programs of the last day are
+    /// included.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the year is out of range.
+    pub fn time_range(&self) -> Result<TimeRange> {
+        let out_of_range = || ApiError::Invalid(format!("season out of range: {self}"));
+        let first =
+            NaiveDate::from_ymd_opt(self.year, self.first_month(), 1).ok_or_else(out_of_range)?;
+        let next = first
+            .checked_add_months(Months::new(3))
+            .ok_or_else(out_of_range)?;
+        let day_start =
+            NaiveTime::from_hms_opt(BROADCAST_DAY_START_HOUR, 0, 0).ok_or_else(out_of_range)?;
+        let end = next
+            .and_time(day_start)
+            .checked_sub_signed(Duration::seconds(1))
+            .ok_or_else(out_of_range)?;
+        Ok(TimeRange::new(first.and_time(day_start), end))
     }
 }
 
+impl core::fmt::Display for AnimeSeason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}q{}", self.year, self.quarter)
+    }
+}
+
+/// Resolves a `--season` value (`YYYYqN` or `now`) into its broadcast
+/// window (see [`AnimeSeason::time_range`]).
+///
+/// # Errors
+///
+/// Returns an error if the season cannot be parsed.
+pub fn resolve_season_range(season: &str, clock: &dyn Clock) -> Result<TimeRange> {
+    AnimeSeason::parse_with(season, clock)?.time_range()
+}
+
+/// Parses a `YYYY-MM` month into its first day.
+fn parse_month(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d").map_err(|e| {
+        ApiError::Invalid(format!("invalid month format (expected YYYY-MM): {s}: {e}"))
+    })
+}
+
+/// Splits the months `from..=to` (`YYYY-MM`) into one range per month, from
+/// the first day `00:00:00` to the last day `23:59:59`.
+///
+/// # Errors
+///
+/// Returns an error if a month cannot be parsed or `from` is after `to`.
+pub fn month_ranges(from: &str, to: &str) -> Result<Vec<TimeRange>> {
+    let first = parse_month(from)?;
+    let last = parse_month(to)?;
+    if first > last {
+        return Err(ApiError::Invalid(format!(
+            "--from {from} is after --to {to}"
+        )));
+    }
+    let mut ranges = Vec::new();
+    let mut month = first;
+    while month <= last {
+        let next = month
+            .checked_add_months(Months::new(1))
+            .ok_or_else(|| ApiError::Invalid(String::from("month out of range")))?;
+        let start = month.and_time(NaiveTime::MIN);
+        let end = next
+            .and_time(NaiveTime::MIN)
+            .checked_sub_signed(Duration::seconds(1))
+            .ok_or_else(|| ApiError::Invalid(String::from("failed to compute end of month")))?;
+        ranges.push(TimeRange::new(start, end));
+        month = next;
+    }
+    Ok(ranges)
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -141,6 +620,64 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_month_ranges_spans_year_boundary() {
+        // Arrange & Act
+        let ranges = month_ranges("2019-11", "2020-02").unwrap();
+
+        // Assert
+        let formatted: Vec<String> = ranges.iter().map(TimeRange::to_syoboi_format).collect();
+        assert_eq!(
+            formatted,
+            vec![
+                "20191101_000000-20191130_235959",
+                "20191201_000000-20191231_235959",
+                "20200101_000000-20200131_235959",
+                "20200201_000000-20200229_235959",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_season_range_includes_late_night_spillover() {
+        // Arrange & Act
+        let spring = resolve_season_range("2024q2", &SystemClock).unwrap();
+        let autumn = resolve_season_range("2023Q4", &SystemClock).unwrap();
+
+        // Assert
+        assert_eq!(spring.to_syoboi_format(), "20240401_050000-20240701_045959");
+        assert_eq!(autumn.to_syoboi_format(), "20231001_050000-20240101_045959");
+    }
+
+    #[test]
+    fn test_season_parse_now_and_invalid_input() {
+        // Arrange: the Unix epoch is 1970-01-01 09:00 JST
+        let clock = crate::clock::FakeClock::new(std::time::SystemTime::UNIX_EPOCH);
+        let late_night = NaiveDate::from_ymd_opt(2024, 7, 1)
+            .unwrap()
+            .and_hms_opt(3, 0, 0)
+            .unwrap();
+
+        // Act & Assert
+        assert_eq!(
+            AnimeSeason::parse_with("now", &clock).unwrap().to_string(),
+            "1970q1"
+        );
+        assert_eq!(AnimeSeason::containing(late_night).to_string(), "2024q2");
+        assert!(AnimeSeason::parse_with("2024q5", &clock).is_err());
+        assert!(AnimeSeason::parse_with("2024q0", &clock).is_err());
+        assert!(AnimeSeason::parse_with("spring", &clock).is_err());
+    }
+
+    #[test]
+    fn test_month_ranges_rejects_invalid_input() {
+        // Arrange & Act & Assert
+        assert!(month_ranges("2020-05", "2020-01").is_err());
+        assert!(month_ranges("2020-13", "2021-01").is_err());
+        assert!(month_ranges("2020/01", "2020-02").is_err());
+        assert_eq!(month_ranges("2020-01", "2020-01").unwrap().len(), 1);
+    }
+
     #[test]
     fn test_time_range_format() {
         // Arrange
@@ -160,6 +697,42 @@ mod tests {
         assert_eq!(range.to_syoboi_format(), "20240101_000000-20240201_000000");
     }
 
+    #[test]
+    fn test_tid_selector_parse_and_format() {
+        // Arrange
+        let cases = [
+            ("*", "*"),
+            ("6309,7667", "6309,7667"),
+            ("6000-6999", "6000-6999"),
+            ("6000-", "6000-"),
+        ];
+
+        // Act & Assert
+        for (input, expected) in cases {
+            let selector: TidSelector = input.parse().unwrap();
+            assert_eq!(selector.to_syoboi_format(), expected, "input: {input}");
+        }
+        assert!("7000-6000".parse::<TidSelector>().is_err());
+        assert!("abc".parse::<TidSelector>().is_err());
+    }
+
+    #[test]
+    fn test_title_lookup_params_updated_since() {
+        // Arrange
+        let since = NaiveDate::from_ymd_opt(2024, 4, 1)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap();
+
+        // Act
+        let params = TitleLookupParams::updated_since(since).fields(&["TID", "Title"]);
+
+        // Assert
+        assert_eq!(params.tids, TidSelector::All);
+        assert_eq!(params.last_update.as_deref(), Some("20240401_123000-"));
+        assert_eq!(params.fields.unwrap(), vec!["TID", "Title"]);
+    }
+
     #[test]
     fn test_prog_lookup_params_default() {
         // Arrange & Act
@@ -169,10 +742,85 @@ mod tests {
         assert!(params.tids.is_none());
         assert!(params.ch_ids.is_none());
         assert!(params.range.is_none());
+        assert!(params.pids.is_none());
+        assert!(params.count.is_none());
         assert!(params.join_sub_titles);
         assert!(params.fields.is_none());
     }
 
+    #[test]
+    fn test_count_range_parse_and_contains() {
+        // Arrange & Act
+        let single: CountRange = "12".parse().unwrap();
+        let closed: CountRange = "1-12".parse().unwrap();
+        let open: CountRange = "13-".parse().unwrap();
+
+        // Assert
+        assert_eq!(single, CountRange::single(12));
+        assert_eq!(single.to_syoboi_format().as_deref(), Some("12"));
+        assert!(closed.contains(1) && closed.contains(12) && !closed.contains(13));
+        assert_eq!(
+            "1-3"
+                .parse::<CountRange>()
+                .unwrap()
+                .to_syoboi_format()
+                .as_deref(),
+            Some("1,2,3")
+        );
+        assert!(
+            "1-1000"
+                .parse::<CountRange>()
+                .unwrap()
+                .to_syoboi_format()
+                .is_none()
+        );
+        assert!(open.contains(13) && open.contains(100) && !open.contains(12));
+        assert!(open.to_syoboi_format().is_none());
+        assert!("12-1".parse::<CountRange>().is_err());
+        assert!("x".parse::<CountRange>().is_err());
+    }
+
+    #[test]
+    fn test_prog_lookup_params_validate() {
+        // Arrange
+        let start = NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        // Act & Assert
+        assert!(
+            ProgLookupParams::default()
+                .tids(&[1])
+                .pids(&[2])
+                .validate()
+                .is_ok()
+        );
+        assert!(ProgLookupParams::default().ch_ids(&[]).validate().is_err());
+        assert!(ProgLookupParams::default().pids(&[]).validate().is_err());
+        assert!(ProgLookupParams::default().fields(&[]).validate().is_err());
+        assert!(
+            ProgLookupParams::default()
+                .range(TimeRange::new(start, end))
+                .validate()
+                .is_err()
+        );
+        let reversed = CountRange {
+            start: 5,
+            end: Some(1),
+        };
+        assert!(
+            ProgLookupParams::default()
+                .count(reversed)
+                .validate()
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_to_naive_datetime_since_iso_format() {
         // Arrange & Act
@@ -245,6 +893,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_resolve_time_range_with_fake_clock_uses_jst() {
+        // Arrange: the Unix epoch is 09:00 in JST whatever the host timezone
+        let clock = crate::clock::FakeClock::new(std::time::SystemTime::UNIX_EPOCH);
+        let now = NaiveDate::from_ymd_opt(1970, 1, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+
+        // Act
+        let range = resolve_time_range_with(None, None, &clock, 7).unwrap();
+
+        // Assert
+        assert_eq!(range.start, now - Duration::days(7));
+        assert_eq!(range.end, now + Duration::days(7));
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_resolve_time_range_both_none() {
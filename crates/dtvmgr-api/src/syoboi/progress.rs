@@ -0,0 +1,58 @@
+//! Progress reporting for long-running syncs.
+//!
+//! Paginated lookups and the CLI sync report their progress through
+//! [`SyncProgress`], so a front end can render progress bars while library
+//! callers use [`NoProgress`].
+
+/// Phase of a sync that reports progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStage {
+    /// `ProgLookup` pages (total unknown up front).
+    Programs,
+    /// `TitleLookup` chunks.
+    Titles,
+}
+
+impl SyncStage {
+    /// Returns a short label for display.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Programs => "programs",
+            Self::Titles => "titles",
+        }
+    }
+
+    /// Returns the unit counted by [`SyncProgress::advance`].
+    #[must_use]
+    pub const fn unit(self) -> &'static str {
+        match self {
+            Self::Programs => "pages",
+            Self::Titles => "chunks",
+        }
+    }
+}
+
+/// Receiver of sync progress events.
+///
+/// Every method has a no-op default, so implementors only override what they
+/// display. Stages are reported as `begin`, any number of `advance` calls,
+/// then `finish`.
+pub trait SyncProgress: Send + Sync {
+    /// A stage started; `total` is `None` when the amount of work is unknown
+    /// (e.g. pagination).
+    fn begin(&self, _stage: SyncStage, _total: Option<u64>) {}
+
+    /// `delta` units of the stage completed; `items` is the number of
+    /// records fetched by them.
+    fn advance(&self, _stage: SyncStage, _delta: u64, _items: usize) {}
+
+    /// The stage completed (or was abandoned).
+    fn finish(&self, _stage: SyncStage) {}
+}
+
+/// [`SyncProgress`] that discards every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl SyncProgress for NoProgress {}
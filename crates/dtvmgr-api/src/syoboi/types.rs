@@ -1,126 +1,177 @@
 //! Syoboi Calendar API response types.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use super::flags::ProgramFlags;
 
 use super::xml::{
     deserialize_empty_string_as_none, deserialize_empty_string_as_none_i32,
     deserialize_empty_string_as_none_u32,
 };
 
+/// `TitleLookup` field names parsed into [`SyoboiTitle`], usable in the
+/// `Fields` parameter.
+pub const TITLE_FIELDS: &[&str] = &[
+    "TID",
+    "LastUpdate",
+    "Title",
+    "ShortTitle",
+    "TitleYomi",
+    "TitleEN",
+    "Comment",
+    "Cat",
+    "TitleFlag",
+    "FirstYear",
+    "FirstMonth",
+    "FirstEndYear",
+    "FirstEndMonth",
+    "FirstCh",
+    "Keywords",
+    "UserPoint",
+    "UserPointRank",
+    "SubTitles",
+];
+
+/// `TitleLookup` fields a [`SyoboiTitle`] cannot be parsed without.
+pub const REQUIRED_TITLE_FIELDS: &[&str] = &["TID", "LastUpdate", "Title"];
+
+/// `ProgLookup` field names parsed into [`SyoboiProgram`], usable in the
+/// `Fields` parameter.
+pub const PROGRAM_FIELDS: &[&str] = &[
+    "PID",
+    "TID",
+    "StTime",
+    "StOffset",
+    "EdTime",
+    "Count",
+    "SubTitle",
+    "ProgComment",
+    "Flag",
+    "Deleted",
+    "Warn",
+    "ChID",
+    "Revision",
+    "LastUpdate",
+    "STSubTitle",
+];
+
+/// `ProgLookup` fields a [`SyoboiProgram`] cannot be parsed without.
+pub const REQUIRED_PROGRAM_FIELDS: &[&str] = &["PID", "TID", "StTime", "EdTime", "ChID"];
+
 /// A single title from `TitleLookup` response.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(clippy::module_name_repetitions)]
 pub struct SyoboiTitle {
     /// Title ID.
-    #[serde(rename = "TID")]
+    #[serde(rename(deserialize = "TID"))]
     pub tid: u32,
     /// Last update timestamp (e.g. "2022-06-30 01:56:20").
-    #[serde(rename = "LastUpdate")]
+    #[serde(rename(deserialize = "LastUpdate"))]
     pub last_update: String,
     /// Title name.
-    #[serde(rename = "Title")]
+    #[serde(rename(deserialize = "Title"))]
     pub title: String,
     /// Short title (may be empty).
     #[serde(
-        rename = "ShortTitle",
+        rename(deserialize = "ShortTitle"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub short_title: Option<String>,
     /// Title reading (hiragana).
     #[serde(
-        rename = "TitleYomi",
+        rename(deserialize = "TitleYomi"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub title_yomi: Option<String>,
     /// English title (may be empty).
     #[serde(
-        rename = "TitleEN",
+        rename(deserialize = "TitleEN"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub title_en: Option<String>,
     /// Free-form comment (staff, cast, etc.).
     #[serde(
-        rename = "Comment",
+        rename(deserialize = "Comment"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub comment: Option<String>,
     /// Category (10=anime, etc.).
     #[serde(
-        rename = "Cat",
+        rename(deserialize = "Cat"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub cat: Option<u32>,
     /// Title flag.
     #[serde(
-        rename = "TitleFlag",
+        rename(deserialize = "TitleFlag"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub title_flag: Option<u32>,
     /// First broadcast year.
     #[serde(
-        rename = "FirstYear",
+        rename(deserialize = "FirstYear"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub first_year: Option<u32>,
     /// First broadcast month.
     #[serde(
-        rename = "FirstMonth",
+        rename(deserialize = "FirstMonth"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub first_month: Option<u32>,
     /// Last broadcast year.
     #[serde(
-        rename = "FirstEndYear",
+        rename(deserialize = "FirstEndYear"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub first_end_year: Option<u32>,
     /// Last broadcast month.
     #[serde(
-        rename = "FirstEndMonth",
+        rename(deserialize = "FirstEndMonth"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub first_end_month: Option<u32>,
     /// Earliest broadcast channel.
     #[serde(
-        rename = "FirstCh",
+        rename(deserialize = "FirstCh"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub first_ch: Option<String>,
     /// Keywords.
     #[serde(
-        rename = "Keywords",
+        rename(deserialize = "Keywords"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub keywords: Option<String>,
     /// User point score.
     #[serde(
-        rename = "UserPoint",
+        rename(deserialize = "UserPoint"),
         deserialize_with = "deserialize_empty_string_as_none_i32",
         default
     )]
     pub user_point: Option<i32>,
     /// User point rank.
     #[serde(
-        rename = "UserPointRank",
+        rename(deserialize = "UserPointRank"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub user_point_rank: Option<u32>,
     /// Raw subtitle text ("*01*Subtitle\n*02*Subtitle" format).
     #[serde(
-        rename = "SubTitles",
+        rename(deserialize = "SubTitles"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
@@ -128,166 +179,174 @@ pub struct SyoboiTitle {
 }
 
 /// A single program from `ProgLookup` response.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(clippy::module_name_repetitions)]
 pub struct SyoboiProgram {
     /// Program ID.
-    #[serde(rename = "PID")]
+    #[serde(rename(deserialize = "PID"))]
     pub pid: u32,
     /// Title ID.
-    #[serde(rename = "TID")]
+    #[serde(rename(deserialize = "TID"))]
     pub tid: u32,
     /// Broadcast start time (e.g. "2022-04-09 23:00:00").
-    #[serde(rename = "StTime")]
+    #[serde(rename(deserialize = "StTime"))]
     pub st_time: String,
     /// Start offset in seconds.
     #[serde(
-        rename = "StOffset",
+        rename(deserialize = "StOffset"),
         deserialize_with = "deserialize_empty_string_as_none_i32",
         default
     )]
     pub st_offset: Option<i32>,
     /// Broadcast end time.
-    #[serde(rename = "EdTime")]
+    #[serde(rename(deserialize = "EdTime"))]
     pub ed_time: String,
     /// Episode number (0 = special/unset).
     #[serde(
-        rename = "Count",
+        rename(deserialize = "Count"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub count: Option<u32>,
     /// Subtitle (may be empty; prefer `st_sub_title`).
     #[serde(
-        rename = "SubTitle",
+        rename(deserialize = "SubTitle"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub sub_title: Option<String>,
     /// Program comment.
     #[serde(
-        rename = "ProgComment",
+        rename(deserialize = "ProgComment"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub prog_comment: Option<String>,
-    /// Flag bitmask (2=first episode, etc.).
+    /// Flag bitmask (see [`ProgramFlags`]).
     #[serde(
-        rename = "Flag",
+        rename(deserialize = "Flag"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub flag: Option<u32>,
     /// Deleted flag.
     #[serde(
-        rename = "Deleted",
+        rename(deserialize = "Deleted"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub deleted: Option<u32>,
     /// Warning flag.
     #[serde(
-        rename = "Warn",
+        rename(deserialize = "Warn"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub warn: Option<u32>,
     /// Channel ID.
-    #[serde(rename = "ChID")]
+    #[serde(rename(deserialize = "ChID"))]
     pub ch_id: u32,
     /// Revision number.
     #[serde(
-        rename = "Revision",
+        rename(deserialize = "Revision"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub revision: Option<u32>,
     /// Last update timestamp.
     #[serde(
-        rename = "LastUpdate",
+        rename(deserialize = "LastUpdate"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub last_update: Option<String>,
     /// Subtitle from `SubTitles` table join (only with `JOIN=SubTitles`).
     #[serde(
-        rename = "STSubTitle",
+        rename(deserialize = "STSubTitle"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub st_sub_title: Option<String>,
 }
 
+impl SyoboiProgram {
+    /// Returns the decoded `Flag` bitmask.
+    #[must_use]
+    pub const fn flags(&self) -> ProgramFlags {
+        ProgramFlags::from_flag(self.flag)
+    }
+}
+
 /// A single channel group from `ChGroupLookup` response.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(clippy::module_name_repetitions)]
 pub struct SyoboiChannelGroup {
     /// Channel group ID.
-    #[serde(rename = "ChGID")]
+    #[serde(rename(deserialize = "ChGID"))]
     pub ch_gid: u32,
     /// Channel group name (e.g. "テレビ 関東", "BSデジタル").
-    #[serde(rename = "ChGroupName")]
+    #[serde(rename(deserialize = "ChGroupName"))]
     pub ch_group_name: String,
     /// Display order for sorting.
-    #[serde(rename = "ChGroupOrder")]
+    #[serde(rename(deserialize = "ChGroupOrder"))]
     pub ch_group_order: u32,
 }
 
 /// A single channel from `ChLookup` response.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(clippy::module_name_repetitions)]
 pub struct SyoboiChannel {
     /// Channel ID.
-    #[serde(rename = "ChID")]
+    #[serde(rename(deserialize = "ChID"))]
     pub ch_id: u32,
     /// Channel group ID.
     #[serde(
-        rename = "ChGID",
+        rename(deserialize = "ChGID"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
     pub ch_gid: Option<u32>,
     /// Channel name.
-    #[serde(rename = "ChName")]
+    #[serde(rename(deserialize = "ChName"))]
     pub ch_name: String,
     /// Channel comment.
     #[serde(
-        rename = "ChComment",
+        rename(deserialize = "ChComment"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub ch_comment: Option<String>,
     /// Channel URL.
     #[serde(
-        rename = "ChURL",
+        rename(deserialize = "ChURL"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub ch_url: Option<String>,
     /// Last update timestamp.
     #[serde(
-        rename = "LastUpdate",
+        rename(deserialize = "LastUpdate"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub last_update: Option<String>,
     /// EPG channel name.
     #[serde(
-        rename = "ChiEPGName",
+        rename(deserialize = "ChiEPGName"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub ch_iepg_name: Option<String>,
     /// EPG URL.
     #[serde(
-        rename = "ChEPGURL",
+        rename(deserialize = "ChEPGURL"),
         deserialize_with = "deserialize_empty_string_as_none",
         default
     )]
     pub ch_epg_url: Option<String>,
     /// Channel number.
     #[serde(
-        rename = "ChNumber",
+        rename(deserialize = "ChNumber"),
         deserialize_with = "deserialize_empty_string_as_none_u32",
         default
     )]
@@ -3,14 +3,15 @@
 use std::collections::HashSet;
 use std::sync::LazyLock;
 
-use anyhow::{Context, Result};
 use chrono::NaiveDateTime;
 use regex::Regex;
 use tracing::instrument;
 
 use super::api::LocalSyoboiApi;
 use super::params::{ProgLookupParams, TimeRange};
+use super::progress::{NoProgress, SyncProgress, SyncStage};
 use super::types::SyoboiProgram;
+use crate::error::{ApiError, Result};
 
 /// Maximum number of programs returned per `ProgLookup` request.
 const PROG_LOOKUP_LIMIT: usize = 5_000;
@@ -54,24 +55,42 @@ pub fn parse_sub_titles(raw: &str) -> Vec<(u32, String)> {
 ///
 /// Returns an error if `params.range` is `None`, any underlying API request
 /// fails, or timestamp conversion fails.
-#[instrument(skip_all, err(level = "error"))]
 pub async fn lookup_all_programs(
     api: &(impl LocalSyoboiApi + Sync),
     params: &ProgLookupParams,
 ) -> Result<Vec<SyoboiProgram>> {
-    let original_range = params
-        .range
-        .as_ref()
-        .context("ProgLookupParams.range is required for pagination")?;
+    lookup_all_programs_with_progress(api, params, &NoProgress).await
+}
+
+/// [`lookup_all_programs`] reporting each fetched page to `progress` as
+/// [`SyncStage::Programs`].
+///
+/// # Errors
+///
+/// Same as [`lookup_all_programs`].
+#[instrument(skip_all, err(level = "error"))]
+pub async fn lookup_all_programs_with_progress(
+    api: &(impl LocalSyoboiApi + Sync),
+    params: &ProgLookupParams,
+    progress: &dyn SyncProgress,
+) -> Result<Vec<SyoboiProgram>> {
+    let original_range = params.range.as_ref().ok_or_else(|| {
+        ApiError::Invalid(String::from(
+            "ProgLookupParams.range is required for pagination",
+        ))
+    })?;
 
     let end = original_range.end;
     let mut current_start = original_range.start;
     let mut all_programs: Vec<SyoboiProgram> = Vec::new();
     let mut seen_pids: HashSet<u32> = HashSet::new();
     let mut page: u32 = 0;
+    progress.begin(SyncStage::Programs, None);
 
     loop {
-        page = page.checked_add(1).context("page counter overflow")?;
+        page = page
+            .checked_add(1)
+            .ok_or_else(|| ApiError::Invalid(String::from("page counter overflow")))?;
 
         let page_range = TimeRange::new(current_start, end);
         let page_params = ProgLookupParams {
@@ -87,16 +106,19 @@ pub async fn lookup_all_programs(
             "ProgLookup request"
         );
 
-        let programs = api.lookup_programs(&page_params).await.with_context(|| {
-            format!(
-                "ProgLookup failed on page {page} (range: {})",
-                page_range.to_syoboi_format()
-            )
+        let programs = api.lookup_programs(&page_params).await.inspect_err(|e| {
+            tracing::warn!(
+                page = page,
+                range = %page_range.to_syoboi_format(),
+                error = %e,
+                "ProgLookup failed"
+            );
         })?;
 
         let fetched_count = programs.len();
+        progress.advance(SyncStage::Programs, 1, fetched_count);
 
-        tracing::info!(
+        tracing::debug!(
             page = page,
             fetched = fetched_count,
             range = %page_range.to_syoboi_format(),
@@ -136,12 +158,13 @@ pub async fn lookup_all_programs(
         }
 
         // Use the max st_time from this page as cursor for the next page
-        let max_st_time = max_st_time.context("unexpected empty program list after limit check")?;
+        let max_st_time = max_st_time.ok_or_else(|| {
+            ApiError::Invalid(String::from(
+                "unexpected empty program list after limit check",
+            ))
+        })?;
 
-        let raw_result: std::result::Result<NaiveDateTime, _> =
-            NaiveDateTime::parse_from_str(&max_st_time, "%Y-%m-%d %H:%M:%S");
-        let next_start =
-            raw_result.with_context(|| format!("invalid StTime for cursor: {max_st_time}"))?;
+        let next_start = parse_cursor("StTime", &max_st_time)?;
 
         // Guard against infinite loop: if cursor doesn't advance, stop
         if next_start <= current_start {
@@ -162,6 +185,7 @@ pub async fn lookup_all_programs(
         current_start = next_start;
     }
 
+    progress.finish(SyncStage::Programs);
     tracing::info!(
         total = all_programs.len(),
         pages = page,
@@ -171,6 +195,114 @@ pub async fn lookup_all_programs(
     Ok(all_programs)
 }
 
+/// Fetches all programs updated at or after `since` (`LastUpdate`
+/// parameter), paginating on the maximum `LastUpdate` of each full page.
+///
+/// `params.range` is ignored so edits to past and future broadcasts are
+/// both returned. Results are deduplicated by `PID`.
+///
+/// # Errors
+///
+/// Returns an error if any underlying API request fails or a `LastUpdate`
+/// cursor cannot be parsed.
+pub async fn lookup_updated_programs(
+    api: &(impl LocalSyoboiApi + Sync),
+    params: &ProgLookupParams,
+    since: NaiveDateTime,
+) -> Result<Vec<SyoboiProgram>> {
+    lookup_updated_programs_with_progress(api, params, since, &NoProgress).await
+}
+
+/// [`lookup_updated_programs`] reporting each fetched page to `progress`
+/// as [`SyncStage::Programs`].
+///
+/// # Errors
+///
+/// Same as [`lookup_updated_programs`].
+#[instrument(skip_all, err(level = "error"))]
+pub async fn lookup_updated_programs_with_progress(
+    api: &(impl LocalSyoboiApi + Sync),
+    params: &ProgLookupParams,
+    since: NaiveDateTime,
+    progress: &dyn SyncProgress,
+) -> Result<Vec<SyoboiProgram>> {
+    let mut cursor = since;
+    let mut all_programs: Vec<SyoboiProgram> = Vec::new();
+    let mut seen_pids: HashSet<u32> = HashSet::new();
+    let mut page: u32 = 0;
+    progress.begin(SyncStage::Programs, None);
+
+    loop {
+        page = page
+            .checked_add(1)
+            .ok_or_else(|| ApiError::Invalid(String::from("page counter overflow")))?;
+
+        let last_update = format!("{}-", cursor.format("%Y%m%d_%H%M%S"));
+        let page_params = ProgLookupParams {
+            range: None,
+            last_update: Some(last_update.clone()),
+            ..params.clone()
+        };
+        let programs = api.lookup_programs(&page_params).await.inspect_err(|e| {
+            tracing::warn!(
+                page = page,
+                last_update = %last_update,
+                error = %e,
+                "ProgLookup failed"
+            );
+        })?;
+
+        let fetched_count = programs.len();
+        progress.advance(SyncStage::Programs, 1, fetched_count);
+        tracing::debug!(
+            page = page,
+            fetched = fetched_count,
+            last_update = %last_update,
+            "ProgLookup page completed"
+        );
+
+        let max_last_update = programs.iter().filter_map(|p| p.last_update.clone()).max();
+        for prog in programs {
+            if seen_pids.insert(prog.pid) {
+                all_programs.push(prog);
+            }
+        }
+
+        if fetched_count < PROG_LOOKUP_LIMIT {
+            break;
+        }
+
+        let Some(max_last_update) = max_last_update else {
+            tracing::warn!("full page without LastUpdate values, stopping pagination");
+            break;
+        };
+        let next = parse_cursor("LastUpdate", &max_last_update)?;
+        if next <= cursor {
+            tracing::warn!(
+                cursor = %max_last_update,
+                "LastUpdate cursor did not advance, stopping pagination"
+            );
+            break;
+        }
+        cursor = next;
+    }
+
+    progress.finish(SyncStage::Programs);
+    tracing::info!(
+        total = all_programs.len(),
+        pages = page,
+        "ProgLookup LastUpdate pagination completed"
+    );
+
+    Ok(all_programs)
+}
+
+/// Parses a `YYYY-MM-DD HH:MM:SS` pagination cursor taken from `field`.
+fn parse_cursor(field: &str, value: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .map_err(|e| ApiError::decode(format!("invalid {field} for cursor: {value}"), value, e))
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -179,11 +311,11 @@ mod tests {
     use std::collections::HashSet;
     use std::sync::atomic::{AtomicU32, Ordering};
 
-    use anyhow::Result;
     use chrono::NaiveDate;
 
     use super::*;
     use crate::syoboi::api::LocalSyoboiApi;
+    use crate::syoboi::params::TitleLookupParams;
     use crate::syoboi::types::{SyoboiChannel, SyoboiChannelGroup, SyoboiTitle};
 
     /// Mock API that returns pre-configured batches in order.
@@ -202,11 +334,7 @@ mod tests {
     }
 
     impl LocalSyoboiApi for MockSyoboiApi {
-        async fn lookup_titles(
-            &self,
-            _tids: &[u32],
-            _fields: Option<&[&str]>,
-        ) -> Result<Vec<SyoboiTitle>> {
+        async fn lookup_titles(&self, _params: &TitleLookupParams) -> Result<Vec<SyoboiTitle>> {
             Ok(vec![])
         }
 
@@ -316,6 +444,36 @@ mod tests {
         assert_eq!(mock.call_count.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn test_lookup_updated_programs_pages_on_last_update() {
+        // Arrange: a full page whose newest LastUpdate is in the middle
+        let mut batch1: Vec<SyoboiProgram> = (1..=5000)
+            .map(|i| {
+                let mut p = make_program(i, "2024-01-15 12:00:00");
+                p.last_update = Some(String::from("2024-03-01 10:00:00"));
+                p
+            })
+            .collect();
+        batch1[1200].last_update = Some(String::from("2024-03-02 08:00:00"));
+        let mut overlap = make_program(1201, "2024-01-15 12:00:00");
+        overlap.last_update = Some(String::from("2024-03-02 08:00:00"));
+        let batch2 = vec![overlap, make_program(5001, "2024-01-20 01:00:00")];
+        let mock = MockSyoboiApi::new(vec![batch1, batch2]);
+        let since = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        // Act
+        let result = lookup_updated_programs(&mock, &ProgLookupParams::default(), since)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(result.len(), 5001);
+        assert_eq!(mock.call_count.load(Ordering::SeqCst), 2);
+    }
+
     #[tokio::test]
     async fn test_lookup_all_programs_two_pages() {
         // Arrange: first batch = 5000 items, second batch < 5000
@@ -343,6 +501,59 @@ mod tests {
         assert_eq!(mock.call_count.load(Ordering::SeqCst), 2);
     }
 
+    /// Records progress events as `(stage, delta, items)`; `begin`/`finish`
+    /// are recorded with `u64::MAX` / `0` deltas.
+    #[derive(Default)]
+    struct RecordingProgress {
+        events: std::sync::Mutex<Vec<(SyncStage, u64, usize)>>,
+    }
+
+    impl SyncProgress for RecordingProgress {
+        fn begin(&self, stage: SyncStage, _total: Option<u64>) {
+            self.events.lock().unwrap().push((stage, u64::MAX, 0));
+        }
+
+        fn advance(&self, stage: SyncStage, delta: u64, items: usize) {
+            self.events.lock().unwrap().push((stage, delta, items));
+        }
+
+        fn finish(&self, stage: SyncStage) {
+            self.events.lock().unwrap().push((stage, 0, 0));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lookup_all_programs_reports_each_page() {
+        // Arrange
+        let batch1: Vec<SyoboiProgram> = (1..=5000)
+            .map(|i| make_program(i, "2024-01-15 12:00:00"))
+            .collect();
+        let batch2 = vec![make_program(5001, "2024-01-20 01:00:00")];
+        let mock = MockSyoboiApi::new(vec![batch1, batch2]);
+        let params = ProgLookupParams {
+            range: Some(make_range((2024, 1, 1), (2024, 2, 1))),
+            ..ProgLookupParams::default()
+        };
+        let progress = RecordingProgress::default();
+
+        // Act
+        lookup_all_programs_with_progress(&mock, &params, &progress)
+            .await
+            .unwrap();
+
+        // Assert
+        let events = progress.events.into_inner().unwrap();
+        assert_eq!(
+            events,
+            vec![
+                (SyncStage::Programs, u64::MAX, 0),
+                (SyncStage::Programs, 1, 5000),
+                (SyncStage::Programs, 1, 1),
+                (SyncStage::Programs, 0, 0),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_lookup_all_programs_deduplication() {
         // Arrange: second batch contains boundary duplicates
@@ -533,16 +744,15 @@ mod tests {
     struct ErrorMockSyoboiApi;
 
     impl LocalSyoboiApi for ErrorMockSyoboiApi {
-        async fn lookup_titles(
-            &self,
-            _tids: &[u32],
-            _fields: Option<&[&str]>,
-        ) -> Result<Vec<SyoboiTitle>> {
+        async fn lookup_titles(&self, _params: &TitleLookupParams) -> Result<Vec<SyoboiTitle>> {
             Ok(vec![])
         }
 
         async fn lookup_programs(&self, _params: &ProgLookupParams) -> Result<Vec<SyoboiProgram>> {
-            anyhow::bail!("simulated API error")
+            Err(ApiError::HttpStatus {
+                status: 500,
+                message: String::from("simulated API error"),
+            })
         }
 
         async fn lookup_channels(&self, _ch_ids: Option<&[u32]>) -> Result<Vec<SyoboiChannel>> {
@@ -569,13 +779,10 @@ mod tests {
         // Act
         let result = lookup_all_programs(&mock, &params).await;
 
-        // Assert
-        assert!(result.is_err());
-        let err = format!("{:#}", result.unwrap_err());
-        assert!(
-            err.contains("ProgLookup failed"),
-            "expected 'ProgLookup failed' in: {err}"
-        );
+        // Assert: the API error is propagated with its kind intact
+        let err = result.unwrap_err();
+        assert_eq!(err.status(), Some(500));
+        assert!(err.to_string().contains("simulated API error"));
     }
 
     #[tokio::test]
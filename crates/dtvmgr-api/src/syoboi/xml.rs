@@ -1,5 +1,7 @@
 //! XML response wrapper types and custom deserializers.
 
+use std::borrow::Cow;
+
 use serde::de::Error;
 use serde::{Deserialize, Deserializer};
 
@@ -51,6 +53,65 @@ where
     }
 }
 
+/// Repairs common defects of `db.php` responses before parsing.
+///
+/// Escapes `&` that does not start a character or predefined entity
+/// reference (e.g. bare `&` in URLs inside `Comment`) and strips control
+/// characters that XML 1.0 forbids. Returns the input unchanged when it
+/// needs no repair.
+#[must_use]
+pub fn sanitize_xml(xml: &str) -> Cow<'_, str> {
+    let needs_repair = xml.char_indices().any(|(i, c)| {
+        is_forbidden_char(c) || (c == '&' && !xml.get(i..).is_some_and(starts_with_reference))
+    });
+    if !needs_repair {
+        return Cow::Borrowed(xml);
+    }
+
+    let mut out = String::with_capacity(xml.len().saturating_add(32));
+    for (i, c) in xml.char_indices() {
+        if is_forbidden_char(c) {
+            continue;
+        }
+        if c == '&' && !xml.get(i..).is_some_and(starts_with_reference) {
+            out.push_str("&amp;");
+            continue;
+        }
+        out.push(c);
+    }
+    Cow::Owned(out)
+}
+
+/// Returns whether `c` is not allowed in an XML 1.0 document.
+const fn is_forbidden_char(c: char) -> bool {
+    matches!(c, '\u{0}'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}')
+}
+
+/// Longest reference name checked by `starts_with_reference` (`#x10FFFF`).
+const MAX_REFERENCE_LEN: usize = 9;
+
+/// Returns whether `s` (starting at `&`) begins with an entity or
+/// character reference.
+fn starts_with_reference(s: &str) -> bool {
+    let Some((end, _)) = s.get(1..).and_then(|rest| {
+        rest.char_indices()
+            .take(MAX_REFERENCE_LEN)
+            .find(|(_, c)| *c == ';')
+    }) else {
+        return false;
+    };
+    let Some(name) = s.get(1..end.saturating_add(1)) else {
+        return false;
+    };
+    if let Some(hex) = name.strip_prefix("#x") {
+        return !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    if let Some(dec) = name.strip_prefix('#') {
+        return !dec.is_empty() && dec.chars().all(|c| c.is_ascii_digit());
+    }
+    matches!(name, "amp" | "lt" | "gt" | "quot" | "apos")
+}
+
 /// API result status.
 #[derive(Debug, Deserialize)]
 pub struct ApiResult {
@@ -331,4 +392,39 @@ mod tests {
         assert_eq!(items.items.len(), 1);
         assert_eq!(items.items[0].tid, 100);
     }
+
+    #[test]
+    fn test_sanitize_xml_escapes_bare_ampersands_and_strips_controls() {
+        // Arrange
+        let xml =
+            "<Comment>https://example.com/?a=1&b=2 &amp; &#38; &#x26; &lt;\u{1}ok\t</Comment>";
+
+        // Act
+        let sanitized = sanitize_xml(xml);
+
+        // Assert
+        assert_eq!(
+            sanitized,
+            "<Comment>https://example.com/?a=1&amp;b=2 &amp; &#38; &#x26; &lt;ok\t</Comment>"
+        );
+        assert!(matches!(sanitize_xml("<a>&amp;</a>"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_sanitize_xml_repairs_title_comment() {
+        // Arrange
+        let xml = "<TitleLookupResponse><TitleItems><TitleItem>\
+            <TID>1</TID><LastUpdate>2024-01-01 00:00:00</LastUpdate>\
+            <Title>A</Title><Comment>*リンク\n-[[公式 https://a.example/?x=1&y=2]]</Comment>\
+            </TitleItem></TitleItems></TitleLookupResponse>";
+        let raw: Result<TitleLookupResponse, _> = quick_xml::de::from_str(xml);
+
+        // Act
+        let repaired: TitleLookupResponse = quick_xml::de::from_str(&sanitize_xml(xml)).unwrap();
+
+        // Assert
+        assert!(raw.is_err());
+        let items = repaired.title_items.unwrap().items;
+        assert!(items[0].comment.as_deref().unwrap().contains("?x=1&y=2"));
+    }
 }
@@ -1,11 +1,13 @@
 //! `TmdbApi` trait definition.
 #![allow(clippy::future_not_send)]
 
-use anyhow::Result;
+use crate::error::Result;
 
 use super::types::{
-    SearchMultiParams, TmdbAlternativeTitlesResponse, TmdbGenreListResponse, TmdbMediaType,
-    TmdbSearchMultiResponse, TmdbTvDetails, TmdbTvSeason,
+    SearchMultiParams, TmdbAlternativeTitlesResponse, TmdbConfiguration, TmdbEpisodeDetails,
+    TmdbEpisodeGroup, TmdbEpisodeGroupsResponse, TmdbExternalSource, TmdbFindResponse,
+    TmdbGenreListResponse, TmdbImagesResponse, TmdbMediaType, TmdbMovieDetails,
+    TmdbSearchMultiResponse, TmdbTvDetails, TmdbTvSeason, TmdbWatchProvidersResponse,
 };
 
 /// TMDB API trait.
@@ -29,6 +31,13 @@ pub trait LocalTmdbApi {
     /// Returns an error if the HTTP request or JSON parsing fails.
     async fn tv_details(&self, series_id: u64, language: &str) -> Result<TmdbTvDetails>;
 
+    /// Fetches movie details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn movie_details(&self, movie_id: u64, language: &str) -> Result<TmdbMovieDetails>;
+
     /// Fetches TV season details including episode list.
     ///
     /// # Errors
@@ -41,6 +50,40 @@ pub trait LocalTmdbApi {
         language: &str,
     ) -> Result<TmdbTvSeason>;
 
+    /// Fetches a single episode including guest stars and still image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn tv_episode(
+        &self,
+        series_id: u64,
+        season_number: u32,
+        episode_number: u32,
+        language: &str,
+    ) -> Result<TmdbEpisodeDetails>;
+
+    /// Lists the episode groups (alternate orders) of a TV series.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn tv_episode_groups(&self, series_id: u64) -> Result<TmdbEpisodeGroupsResponse>;
+
+    /// Fetches an episode group with its parts and episodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn episode_group(&self, group_id: &str, language: &str) -> Result<TmdbEpisodeGroup>;
+
+    /// Fetches the API configuration (image CDN base URLs and sizes).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn configuration(&self) -> Result<TmdbConfiguration>;
+
     /// Fetches the TV genre list.
     ///
     /// # Errors
@@ -65,4 +108,33 @@ pub trait LocalTmdbApi {
         media_type: TmdbMediaType,
         id: u64,
     ) -> Result<TmdbAlternativeTitlesResponse>;
+
+    /// Fetches streaming availability for a TV series, grouped by region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn tv_watch_providers(&self, series_id: u64) -> Result<TmdbWatchProvidersResponse>;
+
+    /// Finds TV series and movies by an external (`IMDb` / `TheTVDB`) ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn find_by_external_id(
+        &self,
+        external_id: &str,
+        source: TmdbExternalSource,
+        language: &str,
+    ) -> Result<TmdbFindResponse>;
+
+    /// Fetches posters, backdrops, and logos for a TV series.
+    ///
+    /// `languages` is passed as `include_image_language`
+    /// (e.g. "ja,null" keeps Japanese and text-less images).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP request or JSON parsing fails.
+    async fn tv_images(&self, series_id: u64, languages: &str) -> Result<TmdbImagesResponse>;
 }
@@ -1,22 +1,30 @@
 //! `TmdbClient` - TMDB API client implementation.
 
 use std::fmt;
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError};
 use std::time::Duration;
 
-use anyhow::{Context, Result, bail};
 use reqwest::Client;
 use reqwest::header::{AUTHORIZATION, HeaderValue};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OnceCell};
 use tracing::instrument;
 use url::Url;
 
-use crate::rate_limiter::SimpleRateLimiter;
+use crate::audit::{AuditLog, RequestAudit, json_item_count};
+use crate::clock::{Clock, SystemClock};
+use crate::error::{ApiError, Result};
+use crate::http::HttpOptions;
+use crate::rate_limiter::{RateLimit, RateLimiter};
+use crate::replay::Cassette;
 
 use super::api::LocalTmdbApi;
+use super::rate_limiter::TmdbLimits;
 use super::types::{
-    SearchMultiParams, TmdbAlternativeTitlesResponse, TmdbErrorResponse, TmdbGenreListResponse,
-    TmdbMediaType, TmdbSearchMultiResponse, TmdbTvDetails, TmdbTvSeason,
+    SearchMultiParams, TmdbAlternativeTitlesResponse, TmdbConfiguration, TmdbEpisodeDetails,
+    TmdbEpisodeGroup, TmdbEpisodeGroupsResponse, TmdbErrorResponse, TmdbExternalSource,
+    TmdbFindResponse, TmdbGenreListResponse, TmdbImageConfiguration, TmdbImagesResponse,
+    TmdbMediaType, TmdbMovieDetails, TmdbSearchMultiResponse, TmdbTvDetails, TmdbTvSeason,
+    TmdbWatchProvidersResponse,
 };
 
 /// Default base URL for TMDB API v3.
@@ -60,7 +68,20 @@ pub struct TmdbClient {
     /// Bearer API token.
     api_token: Secret,
     /// Rate limiter.
-    rate_limiter: Arc<Mutex<SimpleRateLimiter>>,
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    /// Configured request interval, the fastest the header hints allow.
+    base_interval: Duration,
+    /// Latest rate limit hints from response headers.
+    limits: std::sync::Mutex<TmdbLimits>,
+    /// Optional audit trail of requests.
+    audit_log: Option<AuditLog>,
+    /// Optional response recording or replay.
+    cassette: Option<Cassette>,
+    /// Time source for retry backoff.
+    clock: Arc<dyn Clock>,
+    /// Image CDN settings, fetched from `configuration` on first use
+    /// (boxed to keep the client small).
+    image_config: OnceCell<Box<TmdbImageConfiguration>>,
 }
 
 /// Builder for `TmdbClient`.
@@ -70,7 +91,12 @@ pub struct TmdbClientBuilder {
     base_url: Option<Url>,
     api_token: Option<String>,
     user_agent: Option<String>,
+    rate_limit: Option<RateLimit>,
     min_interval: Option<Duration>,
+    audit_log: Option<AuditLog>,
+    cassette: Option<Cassette>,
+    clock: Option<Arc<dyn Clock>>,
+    http_options: HttpOptions,
 }
 
 impl TmdbClientBuilder {
@@ -80,7 +106,17 @@ impl TmdbClientBuilder {
             base_url: None,
             api_token: None,
             user_agent: None,
+            rate_limit: None,
             min_interval: None,
+            audit_log: None,
+            cassette: None,
+            clock: None,
+            http_options: HttpOptions {
+                proxy: None,
+                timeout: None,
+                connect_timeout: None,
+                root_ca: None,
+            },
         }
     }
 
@@ -105,6 +141,14 @@ impl TmdbClientBuilder {
         self
     }
 
+    /// Replaces the whole rate limit (default: one request per 25ms).
+    /// `min_interval` still overrides the interval.
+    #[must_use]
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
     /// Sets the minimum request interval (default: 25ms).
     #[must_use]
     pub const fn min_interval(mut self, interval: Duration) -> Self {
@@ -112,41 +156,88 @@ impl TmdbClientBuilder {
         self
     }
 
+    /// Appends one JSON line per request to the given audit log.
+    #[must_use]
+    pub fn audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = Some(log);
+        self
+    }
+
+    /// Records responses to, or replays them from, `cassette`.
+    ///
+    /// Replayed requests skip rate limiting since they never reach the API.
+    #[must_use]
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Uses `clock` for rate limiting and retry backoff (default: the
+    /// system clock).
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Sets the proxy, timeouts, and extra root certificates (default:
+    /// system proxy from the environment, no timeout, built-in roots).
+    #[must_use]
+    pub fn http_options(mut self, options: HttpOptions) -> Self {
+        self.http_options = options;
+        self
+    }
+
     /// Builds the client.
     ///
     /// # Errors
     ///
     /// - `api_token` is not set.
     /// - `user_agent` is not set.
+    /// - The HTTP options are invalid (see [`HttpOptions`]).
     /// - `reqwest::Client` build fails.
     pub fn build(self) -> Result<TmdbClient> {
-        let api_token = self.api_token.context("api_token is required")?;
-        let user_agent = self.user_agent.context("user_agent is required")?;
+        let api_token = self
+            .api_token
+            .ok_or_else(|| ApiError::Invalid(String::from("api_token is required")))?;
+        let user_agent = self
+            .user_agent
+            .ok_or_else(|| ApiError::Invalid(String::from("user_agent is required")))?;
 
         let base_url = if let Some(url) = self.base_url {
             url
         } else {
-            let result = Url::parse(DEFAULT_BASE_URL);
-            result.context("invalid default base URL")?
+            Url::parse(DEFAULT_BASE_URL)
+                .map_err(|e| ApiError::Invalid(format!("invalid default base URL: {e}")))?
         };
 
-        let rate_limiter = self
-            .min_interval
-            .map_or_else(super::rate_limiter::default_limiter, |interval| {
-                SimpleRateLimiter::new(interval, "tmdb")
-            });
+        let clock = self.clock.unwrap_or_else(SystemClock::shared);
+        let mut limit = self
+            .rate_limit
+            .unwrap_or_else(super::rate_limiter::default_rate_limit);
+        if let Some(interval) = self.min_interval {
+            limit.min_interval = interval;
+        }
+        let base_interval = limit.min_interval;
+        let rate_limiter = RateLimiter::new("tmdb", limit).with_clock(Arc::clone(&clock));
 
-        let http_client = Client::builder()
-            .user_agent(&user_agent)
-            .gzip(true)
+        let http_client = self
+            .http_options
+            .apply(Client::builder().user_agent(&user_agent).gzip(true))?
             .build()
-            .context("failed to build HTTP client")?;
+            .map_err(|e| ApiError::Invalid(format!("failed to build HTTP client: {e}")))?;
 
         Ok(TmdbClient {
             http_client,
             base_url,
             api_token: Secret(api_token),
             rate_limiter: Arc::new(Mutex::new(rate_limiter)),
+            base_interval,
+            limits: std::sync::Mutex::new(TmdbLimits::new(base_interval)),
+            audit_log: self.audit_log,
+            cassette: self.cassette,
+            clock,
+            image_config: OnceCell::new(),
         })
     }
 }
@@ -158,41 +249,163 @@ impl TmdbClient {
         TmdbClientBuilder::new()
     }
 
+    /// Returns the latest rate limit hints (remaining quota, reset time,
+    /// last `Retry-After`) and the request interval currently in effect.
+    #[must_use]
+    pub fn limits(&self) -> TmdbLimits {
+        *self.limits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records the rate limit headers of a response and adapts the request
+    /// interval to them (see [`TmdbLimits`]).
+    async fn observe_limits(&self, headers: &reqwest::header::HeaderMap) {
+        let now = self.clock.wall_now();
+        let interval = {
+            let mut limits = self.limits.lock().unwrap_or_else(PoisonError::into_inner);
+            limits.observe(headers, now);
+            let interval = limits.adapted_interval(self.base_interval, now);
+            if interval == limits.interval {
+                return;
+            }
+            limits.interval = interval;
+            interval
+        };
+        tracing::debug!(
+            interval_ms = u64::try_from(interval.as_millis()).unwrap_or(u64::MAX),
+            "TMDB request interval adapted to rate limit headers"
+        );
+        self.rate_limiter.lock().await.set_min_interval(interval);
+    }
+
+    /// Backs off before retrying a 429. `Retry-After` holds back every
+    /// queued request; without it, only this request sleeps.
+    async fn back_off_rate_limited(&self, retry_after: Option<Duration>, retry: u32) {
+        match retry_after {
+            Some(delay) => self.rate_limiter.lock().await.back_off(delay),
+            None => self.clock.sleep(RETRY_BACKOFF.saturating_mul(retry)).await,
+        }
+    }
+
+    /// Returns the image CDN settings, fetching `configuration` once and
+    /// reusing the result for the lifetime of the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `configuration` request fails.
+    pub async fn image_configuration(&self) -> Result<&TmdbImageConfiguration> {
+        self.image_config
+            .get_or_try_init(|| async { self.configuration().await.map(|c| Box::new(c.images)) })
+            .await
+            .map(AsRef::as_ref)
+    }
+
+    /// Returns the download URL of an image `path` (e.g. `poster_path`)
+    /// at `size` (e.g. "w780", "original").
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `configuration` request fails or no image
+    /// type offers `size`.
+    pub async fn image_url(&self, path: &str, size: &str) -> Result<String> {
+        self.image_configuration().await?.url(path, size)
+    }
+
+    /// Sends a request with retries, recording it to the audit log when one
+    /// is configured.
+    async fn request_with_retry<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        method: &'static str,
+        build_request: impl Fn() -> reqwest::RequestBuilder + Sync,
+    ) -> Result<T> {
+        let mut audit = RequestAudit::start("tmdb", path);
+        let result = self
+            .send_with_retry(path, method, &build_request, &mut audit)
+            .await;
+        crate::prometheus::record_request("tmdb", path, audit.status);
+        if let Some(log) = &self.audit_log {
+            let request = build_request().build().ok();
+            log.record(&audit.finish(
+                request.as_ref().and_then(|r| r.url().query()),
+                result.as_ref().err(),
+            ));
+        }
+        result
+    }
+
+    /// Builds a request and injects the bearer token.
+    ///
+    /// Auth is injected after build so build errors cannot leak the token.
+    fn authorized_request(
+        &self,
+        path: &str,
+        build_request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Request> {
+        let mut request = build_request()
+            .build()
+            .map_err(|_| ApiError::Invalid(format!("failed to build request: {path}")))?;
+
+        tracing::Span::current().record("url.full", tracing::field::display(request.url()));
+
+        // SECURITY: discard InvalidHeaderValue — its Display may echo the token.
+        let Ok(auth_value) = HeaderValue::from_str(&format!("Bearer {}", self.api_token.expose()))
+        else {
+            return Err(ApiError::Invalid(String::from(
+                "failed to set authorization header",
+            )));
+        };
+        request.headers_mut().insert(AUTHORIZATION, auth_value);
+        Ok(request)
+    }
+
+    /// Builds the error for a non-success response, using the structured
+    /// `TmdbErrorResponse` message when the body contains one.
+    fn http_error(status: reqwest::StatusCode, body: &str) -> ApiError {
+        let message = serde_json::from_str::<TmdbErrorResponse>(body).map_or_else(
+            |_| format!("TMDB API error (HTTP {status})"),
+            |error_response| {
+                format!(
+                    "TMDB API error (HTTP {}): code={}, message={}",
+                    status, error_response.status_code, error_response.status_message,
+                )
+            },
+        );
+        ApiError::HttpStatus {
+            status: status.as_u16(),
+            message,
+        }
+    }
+
     /// Sends a request with rate limiting, retry on 429, and JSON parsing.
     ///
     /// TMDB-specific: parses `TmdbErrorResponse` for structured error messages.
-    async fn request_with_retry<T: serde::de::DeserializeOwned>(
+    async fn send_with_retry<T: serde::de::DeserializeOwned>(
         &self,
         path: &str,
         #[cfg_attr(not(feature = "otel"), allow(unused_variables))] method: &'static str,
         build_request: impl Fn() -> reqwest::RequestBuilder,
+        audit: &mut RequestAudit,
     ) -> Result<T> {
-        self.rate_limiter.lock().await.wait().await;
+        let replaying = self.cassette.as_ref().is_some_and(Cassette::is_replay);
+        if !replaying {
+            self.rate_limiter.lock().await.wait().await;
+        }
 
         #[cfg(feature = "otel")]
         let request_start = std::time::Instant::now();
         let mut network_retries = 0u32;
         let mut rate_limit_retries = 0u32;
         loop {
-            // Auth is injected after build so build errors cannot leak the token.
-            let mut request = build_request()
-                .build()
-                .with_context(|| format!("failed to build request: {path}"))?;
-
-            tracing::Span::current().record("url.full", tracing::field::display(request.url()));
-
-            // SECURITY: discard InvalidHeaderValue — its Display may echo the token.
-            let Ok(auth_value) =
-                HeaderValue::from_str(&format!("Bearer {}", self.api_token.expose()))
-            else {
-                bail!("failed to set authorization header");
-            };
-            request.headers_mut().insert(AUTHORIZATION, auth_value);
+            let request = self.authorized_request(path, &build_request)?;
 
-            let response = match self.http_client.execute(request).await {
+            let sent =
+                crate::replay::execute(&self.http_client, self.cassette.as_ref(), "tmdb", request)
+                    .await?;
+            let response = match sent {
                 Ok(resp) => resp,
                 Err(e) if !e.is_timeout() && network_retries < MAX_NETWORK_RETRIES => {
                     network_retries = network_retries.saturating_add(1);
+                    audit.retry();
                     let kind = crate::classify_reqwest_error(&e);
                     tracing::debug!(
                         retry = network_retries,
@@ -202,36 +415,47 @@ impl TmdbClient {
                     continue;
                 }
                 Err(e) => {
-                    let kind = crate::classify_reqwest_error(&e);
                     let status_code = e.status().map(|s| i64::from(s.as_u16()));
                     if let Some(code) = status_code {
                         tracing::Span::current().record("http.response.status_code", code);
                     }
                     // SECURITY: do not format the raw reqwest::Error — it
                     // may carry request context including auth headers.
-                    bail!("{kind}: {path}");
+                    return Err(ApiError::transport(&e, path));
                 }
             };
 
             let span = tracing::Span::current();
             let status = response.status();
             span.record("http.response.status_code", i64::from(status.as_u16()));
+            audit.status = Some(status.as_u16());
+            self.observe_limits(response.headers()).await;
 
             if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
                 #[cfg(feature = "otel")]
                 crate::metrics::record_rate_limit_hit("tmdb");
 
                 rate_limit_retries = rate_limit_retries.saturating_add(1);
+                let requested = crate::error::retry_after(response.headers());
                 if rate_limit_retries > MAX_RETRIES {
-                    bail!("TMDB API rate limit exceeded after {MAX_RETRIES} retries: {path}");
+                    return Err(ApiError::RateLimited {
+                        message: format!(
+                            "TMDB API rate limit exceeded after {MAX_RETRIES} retries: {path}"
+                        ),
+                        retry_after: requested,
+                    });
                 }
                 tracing::warn!(
                     retry = rate_limit_retries,
                     max_retries = MAX_RETRIES,
                     "TMDB API rate limited (429). Retrying..."
                 );
-                tokio::time::sleep(RETRY_BACKOFF.saturating_mul(rate_limit_retries)).await;
-                self.rate_limiter.lock().await.wait().await;
+                self.back_off_rate_limited(requested, rate_limit_retries)
+                    .await;
+                if !replaying {
+                    self.rate_limiter.lock().await.wait().await;
+                }
+                audit.retry();
                 continue;
             }
 
@@ -243,29 +467,28 @@ impl TmdbClient {
                 });
                 span.record("http.response.body.size", body.len());
                 tracing::debug!(http.response.body = %body, "HTTP response body");
-                if let Ok(error_response) = serde_json::from_str::<TmdbErrorResponse>(&body) {
-                    bail!(
-                        "TMDB API error (HTTP {}): code={}, message={}",
-                        status,
-                        error_response.status_code,
-                        error_response.status_message,
-                    );
-                }
-                bail!("TMDB API error (HTTP {status})");
+                return Err(Self::http_error(status, &body));
             }
 
             let body = response
                 .text()
                 .await
-                .with_context(|| format!("failed to read response body: {path}"))?;
+                .map_err(|e| ApiError::transport(&e, path))?;
             span.record("http.response.body.size", body.len());
             tracing::debug!(http.response.body = %body, "HTTP response body");
-            let parsed: T = serde_json::from_str(&body).with_context(|| {
-                format!(
-                    "failed to decode JSON response: {path} (body_len={} bytes)",
-                    body.len()
+            let parsed: T = serde_json::from_str(&body).map_err(|e| {
+                ApiError::decode(
+                    format!(
+                        "failed to decode JSON response: {path} (body_len={} bytes)",
+                        body.len()
+                    ),
+                    &body,
+                    e,
                 )
             })?;
+            if self.audit_log.is_some() {
+                audit.items = json_item_count(&body);
+            }
 
             #[cfg(feature = "otel")]
             crate::metrics::record_request_duration("tmdb", method, request_start);
@@ -291,7 +514,7 @@ impl TmdbClient {
         let url = self
             .base_url
             .join(path)
-            .with_context(|| format!("failed to join URL path: {path}"))?;
+            .map_err(|e| ApiError::Invalid(format!("failed to join URL path: {path}: {e}")))?;
 
         self.request_with_retry(path, "GET", || {
             self.http_client.get(url.clone()).query(query)
@@ -308,6 +531,13 @@ impl LocalTmdbApi for TmdbClient {
         self.get_json(&path, &query).await
     }
 
+    #[instrument(skip_all, err(level = "error"))]
+    async fn movie_details(&self, movie_id: u64, language: &str) -> Result<TmdbMovieDetails> {
+        let path = format!("movie/{movie_id}");
+        let query = [("language", String::from(language))];
+        self.get_json(&path, &query).await
+    }
+
     #[instrument(skip_all, err(level = "error"))]
     async fn tv_season(
         &self,
@@ -320,6 +550,37 @@ impl LocalTmdbApi for TmdbClient {
         self.get_json(&path, &query).await
     }
 
+    #[instrument(skip_all, err(level = "error"))]
+    async fn tv_episode(
+        &self,
+        series_id: u64,
+        season_number: u32,
+        episode_number: u32,
+        language: &str,
+    ) -> Result<TmdbEpisodeDetails> {
+        let path = format!("tv/{series_id}/season/{season_number}/episode/{episode_number}");
+        let query = [("language", String::from(language))];
+        self.get_json(&path, &query).await
+    }
+
+    #[instrument(skip_all, err(level = "error"))]
+    async fn tv_episode_groups(&self, series_id: u64) -> Result<TmdbEpisodeGroupsResponse> {
+        let path = format!("tv/{series_id}/episode_groups");
+        self.get_json(&path, &[]).await
+    }
+
+    #[instrument(skip_all, err(level = "error"))]
+    async fn episode_group(&self, group_id: &str, language: &str) -> Result<TmdbEpisodeGroup> {
+        let path = format!("tv/episode_group/{group_id}");
+        let query = [("language", String::from(language))];
+        self.get_json(&path, &query).await
+    }
+
+    #[instrument(skip_all, err(level = "error"))]
+    async fn configuration(&self) -> Result<TmdbConfiguration> {
+        self.get_json("configuration", &[]).await
+    }
+
     #[instrument(skip_all, err(level = "error"))]
     async fn genre_tv_list(&self, language: &str) -> Result<TmdbGenreListResponse> {
         self.get_json("genre/tv/list", &[("language", String::from(language))])
@@ -352,6 +613,34 @@ impl LocalTmdbApi for TmdbClient {
         let path = format!("{}/{id}/alternative_titles", media_type.as_str());
         self.get_json(&path, &[]).await
     }
+
+    #[instrument(skip_all, err(level = "error"))]
+    async fn tv_watch_providers(&self, series_id: u64) -> Result<TmdbWatchProvidersResponse> {
+        let path = format!("tv/{series_id}/watch/providers");
+        self.get_json(&path, &[]).await
+    }
+
+    #[instrument(skip_all, err(level = "error"))]
+    async fn find_by_external_id(
+        &self,
+        external_id: &str,
+        source: TmdbExternalSource,
+        language: &str,
+    ) -> Result<TmdbFindResponse> {
+        let path = format!("find/{external_id}");
+        let query = [
+            ("external_source", String::from(source.as_str())),
+            ("language", String::from(language)),
+        ];
+        self.get_json(&path, &query).await
+    }
+
+    #[instrument(skip_all, err(level = "error"))]
+    async fn tv_images(&self, series_id: u64, languages: &str) -> Result<TmdbImagesResponse> {
+        let path = format!("tv/{series_id}/images");
+        let query = [("include_image_language", String::from(languages))];
+        self.get_json(&path, &query).await
+    }
 }
 
 #[cfg(test)]
@@ -500,6 +789,157 @@ mod tests {
         assert_eq!(details.name, "SPY×FAMILY");
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_image_url_fetches_configuration_once() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/configuration.json");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/configuration"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/3/", mock_server.uri());
+        let client = TmdbClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+
+        // Act
+        let poster = client.image_url("/poster.jpg", "w780").await.unwrap();
+        let still = client.image_url("/still.jpg", "original").await.unwrap();
+        let err = client.image_url("/poster.jpg", "w9999").await.unwrap_err();
+
+        // Assert
+        assert_eq!(poster, "https://image.tmdb.org/t/p/w780/poster.jpg");
+        assert_eq!(still, "https://image.tmdb.org/t/p/original/still.jpg");
+        assert!(matches!(err, ApiError::Invalid(_)));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_requests_go_through_configured_proxy() {
+        // Arrange: the mock server acts as the HTTP proxy of an unreachable host
+        let proxy = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/tv_details_120089.json");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/tv/120089"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+
+        let client = TmdbClient::builder()
+            .base_url(Url::parse("http://api.tmdb.invalid/3/").unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .http_options(HttpOptions {
+                proxy: Some(proxy.uri()),
+                timeout: Some(Duration::from_secs(10)),
+                connect_timeout: Some(Duration::from_secs(5)),
+                root_ca: None,
+            })
+            .build()
+            .unwrap();
+
+        // Act
+        let details = client.tv_details(120_089, "ja-JP").await.unwrap();
+
+        // Assert
+        assert_eq!(details.id, 120_089);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_movie_details_via_http() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/movie_details_916224.json");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/movie/916224"))
+            .and(wiremock::matchers::query_param("language", "ja-JP"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/3/", mock_server.uri());
+        let client = TmdbClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+
+        // Act
+        let details = client.movie_details(916_224, "ja-JP").await.unwrap();
+
+        // Assert
+        assert_eq!(details.id, 916_224);
+        assert_eq!(details.original_title, "すずめの戸締まり");
+        assert_eq!(details.runtime, Some(122));
+        assert_eq!(details.release_date.as_deref(), Some("2022-11-11"));
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_episode_groups_via_http() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let list_body = include_str!("../../../../fixtures/tmdb/tv_episode_groups_120089.json");
+        let group_body = include_str!(
+            "../../../../fixtures/tmdb/tv_episode_group_6310c3c1a4a9b9007e1f4f21.json"
+        );
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/tv/120089/episode_groups"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(list_body))
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path(
+                "/3/tv/episode_group/6310c3c1a4a9b9007e1f4f21",
+            ))
+            .and(wiremock::matchers::query_param("language", "ja-JP"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(group_body))
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/3/", mock_server.uri());
+        let client = TmdbClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+
+        // Act
+        let groups = client.tv_episode_groups(120_089).await.unwrap();
+        let group = client
+            .episode_group(&groups.results[0].id, "ja-JP")
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(groups.results.len(), 2);
+        assert_eq!(groups.results[0].type_name(), "absolute");
+        assert_eq!(groups.results[1].type_name(), "dvd");
+        assert_eq!(group.name, "Absolute Order");
+        assert_eq!(group.groups.len(), 2);
+        assert_eq!(group.absolute_episodes().len(), 3);
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test]
     async fn test_tv_season_via_http() {
@@ -530,6 +970,46 @@ mod tests {
         assert!(!season.episodes.is_empty());
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_tv_episode_via_http() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/tv_episode_120089_1_1.json");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/tv/120089/season/1/episode/1"))
+            .and(wiremock::matchers::query_param("language", "ja-JP"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/3/", mock_server.uri());
+        let client = TmdbClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+
+        // Act
+        let episode = client.tv_episode(120_089, 1, 1, "ja-JP").await.unwrap();
+
+        // Assert
+        assert_eq!(episode.id, 3_636_093);
+        assert_eq!(episode.runtime, Some(37));
+        assert_eq!(
+            episode.still_path.as_deref(),
+            Some("/5Wm9kCwAEZpjcxXgv6BgVAHnSaB.jpg")
+        );
+        assert_eq!(episode.guest_stars.len(), 2);
+        assert_eq!(
+            episode.guest_stars[0].original_name.as_deref(),
+            Some("吉野裕行")
+        );
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test]
     async fn test_bearer_token_is_sent() {
@@ -591,8 +1071,9 @@ mod tests {
         let result = client.search_multi(&params).await;
 
         // Assert
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
+        let err = result.unwrap_err();
+        assert_eq!(err.status(), Some(401));
+        let err = err.to_string();
         assert!(err.contains("TMDB API error"));
         assert!(err.contains("code=7"));
         assert!(err.contains("message=Invalid API key"));
@@ -628,8 +1109,9 @@ mod tests {
         let result = client.search_multi(&params).await;
 
         // Assert
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("rate limit"));
+        let err = result.unwrap_err();
+        assert!(err.is_rate_limited());
+        assert!(err.to_string().contains("rate limit"));
     }
 
     #[test]
@@ -1012,6 +1494,167 @@ mod tests {
         assert!(!response.genres.is_empty());
     }
 
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_tv_watch_providers_via_http() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/tv_watch_providers_120089.json");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/tv/120089/watch/providers"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/3/", mock_server.uri());
+        let client = TmdbClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+
+        // Act
+        let response = client.tv_watch_providers(120_089).await.unwrap();
+
+        // Assert
+        assert_eq!(response.id, 120_089);
+        let jp = response.region("JP").unwrap();
+        assert_eq!(jp.flatrate.len(), 2);
+        assert_eq!(jp.flatrate[0].provider_name, "Netflix");
+        assert_eq!(jp.buy.len(), 1);
+        assert!(response.region("US").is_some());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_tv_images_via_http() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/tv_images_120089.json");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/tv/120089/images"))
+            .and(wiremock::matchers::query_param(
+                "include_image_language",
+                "ja,null",
+            ))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/3/", mock_server.uri());
+        let client = TmdbClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+
+        // Act
+        let response = client.tv_images(120_089, "ja,null").await.unwrap();
+
+        // Assert
+        assert_eq!(response.id, 120_089);
+        assert_eq!(response.posters.len(), 2);
+        assert_eq!(response.posters[0].iso_639_1.as_deref(), Some("ja"));
+        assert_eq!(response.backdrops.len(), 1);
+        assert!(response.backdrops[0].iso_639_1.is_none());
+        assert!(response.logos.is_empty());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_find_by_external_id_via_http() {
+        // Arrange
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/find_tt13706018.json");
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/find/tt13706018"))
+            .and(wiremock::matchers::query_param(
+                "external_source",
+                "imdb_id",
+            ))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(json_body))
+            .mount(&mock_server)
+            .await;
+
+        let base_url = format!("{}/3/", mock_server.uri());
+        let client = TmdbClient::builder()
+            .base_url(base_url.parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+
+        // Act
+        let response = client
+            .find_by_external_id("tt13706018", TmdbExternalSource::Imdb, "ja-JP")
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(response.tv_results.len(), 1);
+        assert_eq!(response.tv_results[0].id, 120_089);
+        assert!(response.movie_results.is_empty());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_rate_limit_headers_adapt_interval_and_retry_after() {
+        // Arrange: a 429 with Retry-After, then a success reporting 2
+        // requests left for the next 30 seconds
+        let mock_server = wiremock::MockServer::start().await;
+        let json_body = include_str!("../../../../fixtures/tmdb/search_multi_empty.json");
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/search/multi"))
+            .respond_with(wiremock::ResponseTemplate::new(429).insert_header("retry-after", "7"))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/search/multi"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(200)
+                    .insert_header("x-ratelimit-limit", "40")
+                    .insert_header("x-ratelimit-remaining", "2")
+                    .insert_header("x-ratelimit-reset", "30")
+                    .set_body_string(json_body),
+            )
+            .mount(&mock_server)
+            .await;
+        let clock = Arc::new(crate::clock::FakeClock::new(
+            std::time::SystemTime::UNIX_EPOCH,
+        ));
+        let client = TmdbClient::builder()
+            .base_url(format!("{}/3/", mock_server.uri()).parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .clock(clock.clone())
+            .build()
+            .unwrap();
+
+        // Act
+        client
+            .search_multi(&SearchMultiParams::new("test"))
+            .await
+            .unwrap();
+
+        // Assert: the retry waited for Retry-After, and the interval now
+        // spreads the 2 remaining requests over the 30 seconds left
+        assert!(clock.elapsed() >= Duration::from_secs(7));
+        let limits = client.limits();
+        assert_eq!(limits.limit, Some(40));
+        assert_eq!(limits.remaining, Some(2));
+        assert_eq!(limits.retry_after, Some(Duration::from_secs(7)));
+        assert_eq!(limits.interval, Duration::from_secs(10));
+    }
+
     #[cfg_attr(miri, ignore)]
     #[tokio::test]
     async fn test_rate_limiter_enforces_interval() {
@@ -7,13 +7,22 @@ mod api;
 mod client;
 mod rate_limiter;
 mod types;
+mod util;
 
 #[allow(clippy::module_name_repetitions)]
 pub use api::{LocalTmdbApi, TmdbApi};
 #[allow(clippy::module_name_repetitions)]
 pub use client::{TmdbClient, TmdbClientBuilder};
 #[allow(clippy::module_name_repetitions)]
+pub use rate_limiter::{TmdbLimits, default_rate_limit};
+#[allow(clippy::module_name_repetitions)]
 pub use types::{
-    SearchMultiParams, TmdbAlternativeTitle, TmdbAlternativeTitlesResponse, TmdbGenreListResponse,
-    TmdbMediaType, TmdbMultiSearchResult, TmdbSearchMultiResponse, TmdbTvDetails, TmdbTvSeason,
+    SearchMultiParams, TmdbAlternativeTitle, TmdbAlternativeTitlesResponse, TmdbConfiguration,
+    TmdbEpisode, TmdbEpisodeDetails, TmdbEpisodeGroup, TmdbEpisodeGroupEpisode,
+    TmdbEpisodeGroupPart, TmdbEpisodeGroupSummary, TmdbEpisodeGroupsResponse, TmdbExternalSource,
+    TmdbFindResponse, TmdbGenreListResponse, TmdbGuestStar, TmdbImage, TmdbImageConfiguration,
+    TmdbImagesResponse, TmdbMediaType, TmdbMovieDetails, TmdbMovieSearchResult,
+    TmdbMultiSearchResult, TmdbSearchMultiResponse, TmdbTvDetails, TmdbTvSearchResult,
+    TmdbTvSeason, TmdbWatchProvider, TmdbWatchProviderRegion, TmdbWatchProvidersResponse,
 };
+pub use util::{search_movie_all, search_multi_all, search_tv_all};
@@ -1,37 +1,194 @@
-//! TMDB API rate limiter.
+//! TMDB API rate limit defaults and response header hints.
+//!
+//! TMDB may report its quota in `X-RateLimit-Limit` / `X-RateLimit-Remaining`
+//! / `X-RateLimit-Reset` headers and answers 429 with `Retry-After`.
+//! [`TmdbLimits`] keeps the latest hints and derives the request interval
+//! from them: the remaining requests are spread until the reset, but never
+//! faster than the configured interval.
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::rate_limiter::SimpleRateLimiter;
+use reqwest::header::HeaderMap;
+
+use crate::rate_limiter::RateLimit;
 
 /// Default minimum interval between requests (~40 req/s).
 const DEFAULT_MIN_INTERVAL: Duration = Duration::from_millis(25);
 
-/// Creates a TMDB rate limiter with the default interval.
-pub const fn default_limiter() -> SimpleRateLimiter {
-    SimpleRateLimiter::new(DEFAULT_MIN_INTERVAL, "tmdb")
+/// Longest interval the header hints can slow requests down to.
+const MAX_ADAPTIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// `X-RateLimit-Reset` values at or above this are Unix timestamps; smaller
+/// values are seconds until the reset.
+const RESET_EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+/// Rate limit state reported by TMDB, as returned by
+/// [`TmdbClient::limits`](super::TmdbClient::limits).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TmdbLimits {
+    /// Requests allowed per window (`X-RateLimit-Limit`).
+    pub limit: Option<u32>,
+    /// Requests left in the current window (`X-RateLimit-Remaining`).
+    pub remaining: Option<u32>,
+    /// When the current window resets (`X-RateLimit-Reset`).
+    pub reset_at: Option<SystemTime>,
+    /// `Retry-After` of the last 429 response.
+    pub retry_after: Option<Duration>,
+    /// Request interval currently in effect.
+    pub interval: Duration,
+}
+
+impl TmdbLimits {
+    /// Creates the state for a client configured with `interval`.
+    #[must_use]
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            limit: None,
+            remaining: None,
+            reset_at: None,
+            retry_after: None,
+            interval,
+        }
+    }
+
+    /// Records the hints of a response received at `now`. Headers that are
+    /// missing or malformed keep their previous value.
+    pub(crate) fn observe(&mut self, headers: &HeaderMap, now: SystemTime) {
+        let number = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.trim().parse::<u64>().ok())
+        };
+        if let Some(limit) = number("x-ratelimit-limit") {
+            self.limit = Some(u32::try_from(limit).unwrap_or(u32::MAX));
+        }
+        if let Some(remaining) = number("x-ratelimit-remaining") {
+            self.remaining = Some(u32::try_from(remaining).unwrap_or(u32::MAX));
+        }
+        if let Some(reset) = number("x-ratelimit-reset") {
+            self.reset_at = if reset >= RESET_EPOCH_THRESHOLD {
+                UNIX_EPOCH.checked_add(Duration::from_secs(reset))
+            } else {
+                now.checked_add(Duration::from_secs(reset))
+            };
+        }
+        if let Some(retry_after) = crate::error::retry_after(headers) {
+            self.retry_after = Some(retry_after);
+        }
+    }
+
+    /// Returns the interval that spreads the remaining requests until the
+    /// reset, clamped to `base..=MAX_ADAPTIVE_INTERVAL`. Without both hints,
+    /// or once the window has reset, returns `base`.
+    #[must_use]
+    pub(crate) fn adapted_interval(&self, base: Duration, now: SystemTime) -> Duration {
+        let (Some(remaining), Some(reset_at)) = (self.remaining, self.reset_at) else {
+            return base;
+        };
+        let Ok(until_reset) = reset_at.duration_since(now) else {
+            return base;
+        };
+        until_reset
+            .checked_div(remaining.saturating_add(1))
+            .unwrap_or(base)
+            .clamp(base, MAX_ADAPTIVE_INTERVAL.max(base))
+    }
+}
+
+/// Returns the default TMDB rate limit.
+#[must_use]
+pub const fn default_rate_limit() -> RateLimit {
+    RateLimit::interval(DEFAULT_MIN_INTERVAL)
 }
 
 #[cfg(test)]
 mod tests {
+    #![allow(clippy::unwrap_used)]
+
     use std::time::Duration;
 
+    use reqwest::header::HeaderValue;
+
     use super::*;
 
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
     #[test]
-    fn test_default_interval_is_25ms() {
-        // Arrange & Act & Assert
-        assert_eq!(DEFAULT_MIN_INTERVAL, Duration::from_millis(25));
+    fn test_limits_spread_remaining_requests_until_reset() {
+        // Arrange
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let base = Duration::from_millis(25);
+        let mut limits = TmdbLimits::new(base);
+
+        // Act: 3 requests left for the next 8 seconds
+        limits.observe(
+            &headers(&[
+                ("x-ratelimit-limit", "40"),
+                ("x-ratelimit-remaining", "3"),
+                ("x-ratelimit-reset", "1700000008"),
+            ]),
+            now,
+        );
+
+        // Assert
+        assert_eq!(limits.limit, Some(40));
+        assert_eq!(limits.remaining, Some(3));
+        assert_eq!(limits.adapted_interval(base, now), Duration::from_secs(2));
+        // After the reset the configured interval applies again.
+        assert_eq!(
+            limits.adapted_interval(base, now + Duration::from_secs(9)),
+            base
+        );
     }
 
-    #[tokio::test]
-    async fn test_default_limiter_works() {
+    #[test]
+    fn test_limits_never_go_below_base_or_above_cap() {
         // Arrange
-        let mut limiter = default_limiter();
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let base = Duration::from_millis(25);
+        let mut plenty = TmdbLimits::new(base);
+        let mut exhausted = TmdbLimits::new(base);
+
+        // Act: relative reset values are seconds from now
+        plenty.observe(
+            &headers(&[
+                ("x-ratelimit-remaining", "1000"),
+                ("x-ratelimit-reset", "1"),
+            ]),
+            now,
+        );
+        exhausted.observe(
+            &headers(&[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", "60"),
+                ("retry-after", "30"),
+            ]),
+            now,
+        );
+
+        // Assert
+        assert_eq!(plenty.adapted_interval(base, now), base);
+        assert_eq!(exhausted.adapted_interval(base, now), MAX_ADAPTIVE_INTERVAL);
+        assert_eq!(exhausted.retry_after, Some(Duration::from_secs(30)));
+        assert_eq!(TmdbLimits::new(base).adapted_interval(base, now), base);
+    }
 
-        // Act
-        limiter.wait().await;
+    #[test]
+    fn test_default_interval_is_25ms() {
+        // Arrange & Act
+        let limit = default_rate_limit();
 
-        // Assert — no panic, limiter functions correctly
+        // Assert
+        assert_eq!(limit.min_interval, Duration::from_millis(25));
+        assert_eq!(limit.burst, 1);
+        assert!(limit.windows.is_empty());
     }
 }
@@ -1,11 +1,14 @@
 //! TMDB API response types and search parameters.
 
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
 // --- Search TV Result ---
 
 /// A single TV series search result.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TmdbTvSearchResult {
     /// TMDB series ID.
     pub id: u64,
@@ -40,7 +43,7 @@ pub struct TmdbTvSearchResult {
 // --- Search Movie Result ---
 
 /// A single movie search result.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TmdbMovieSearchResult {
     /// TMDB movie ID.
     pub id: u64,
@@ -75,7 +78,7 @@ pub struct TmdbMovieSearchResult {
 // --- TV Details ---
 
 /// Response from `tv/{series_id}` endpoint.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TmdbTvDetails {
     /// TMDB series ID.
     pub id: u64,
@@ -113,8 +116,41 @@ pub struct TmdbTvDetails {
     pub poster_path: Option<String>,
 }
 
+// --- Movie Details ---
+
+/// Response from `movie/{movie_id}` endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbMovieDetails {
+    /// TMDB movie ID.
+    pub id: u64,
+    /// Localized title.
+    pub title: String,
+    /// Original title.
+    pub original_title: String,
+    /// Original language (ISO 639-1).
+    pub original_language: String,
+    /// Release date (YYYY-MM-DD or null).
+    pub release_date: Option<String>,
+    /// Runtime in minutes.
+    pub runtime: Option<u32>,
+    /// Status (e.g., "Released").
+    pub status: Option<String>,
+    /// Overview text.
+    pub overview: Option<String>,
+    /// `IMDb` ID.
+    pub imdb_id: Option<String>,
+    /// Popularity score.
+    pub popularity: f64,
+    /// Vote average.
+    pub vote_average: f64,
+    /// Genres.
+    pub genres: Vec<TmdbGenre>,
+    /// Poster image path.
+    pub poster_path: Option<String>,
+}
+
 /// Season summary within TV details.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TmdbSeasonSummary {
     /// TMDB season ID.
     pub id: u64,
@@ -133,7 +169,7 @@ pub struct TmdbSeasonSummary {
 }
 
 /// Genre entry.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TmdbGenre {
     /// Genre ID.
     pub id: u32,
@@ -271,6 +307,233 @@ pub struct TmdbAlternativeTitle {
     pub title_type: String,
 }
 
+// --- Watch Providers ---
+
+/// Response from `tv/{series_id}/watch/providers` endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TmdbWatchProvidersResponse {
+    /// TMDB series ID.
+    pub id: u64,
+    /// Availability keyed by region code (ISO 3166-1).
+    #[serde(default)]
+    pub results: BTreeMap<String, TmdbWatchProviderRegion>,
+}
+
+impl TmdbWatchProvidersResponse {
+    /// Returns the availability for the given region (e.g. "JP").
+    #[must_use]
+    pub fn region(&self, region: &str) -> Option<&TmdbWatchProviderRegion> {
+        self.results.get(region)
+    }
+}
+
+/// Streaming availability within a single region.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TmdbWatchProviderRegion {
+    /// TMDB watch page URL for the title in this region.
+    pub link: Option<String>,
+    /// Subscription (flat-rate) providers.
+    #[serde(default)]
+    pub flatrate: Vec<TmdbWatchProvider>,
+    /// Free-with-ads providers.
+    #[serde(default)]
+    pub ads: Vec<TmdbWatchProvider>,
+    /// Free providers.
+    #[serde(default)]
+    pub free: Vec<TmdbWatchProvider>,
+    /// Rental providers.
+    #[serde(default)]
+    pub rent: Vec<TmdbWatchProvider>,
+    /// Purchase providers.
+    #[serde(default)]
+    pub buy: Vec<TmdbWatchProvider>,
+}
+
+/// A single watch provider entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TmdbWatchProvider {
+    /// TMDB provider ID.
+    pub provider_id: u64,
+    /// Provider name (e.g. "Netflix").
+    pub provider_name: String,
+    /// Logo image path.
+    pub logo_path: Option<String>,
+    /// Display priority (lower is more prominent).
+    #[serde(default)]
+    pub display_priority: u32,
+}
+
+// --- Find by External ID ---
+
+/// External ID namespace accepted by the `find/{external_id}` endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TmdbExternalSource {
+    /// `IMDb` ID (e.g. "tt13706018").
+    Imdb,
+    /// `TheTVDB` ID (e.g. "405920").
+    Tvdb,
+}
+
+impl TmdbExternalSource {
+    /// Returns the `external_source` query value (e.g. `imdb_id`).
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Imdb => "imdb_id",
+            Self::Tvdb => "tvdb_id",
+        }
+    }
+
+    /// Guesses the source from the ID format: `tt` prefix is `IMDb`, digits
+    /// only is `TheTVDB`.
+    #[must_use]
+    pub fn infer(external_id: &str) -> Option<Self> {
+        if external_id.starts_with("tt") {
+            Some(Self::Imdb)
+        } else if !external_id.is_empty() && external_id.bytes().all(|b| b.is_ascii_digit()) {
+            Some(Self::Tvdb)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for TmdbExternalSource {
+    type Err = crate::error::ApiError;
+
+    /// Parses `imdb` / `imdb_id` or `tvdb` / `tvdb_id`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "imdb" | "imdb_id" => Ok(Self::Imdb),
+            "tvdb" | "tvdb_id" => Ok(Self::Tvdb),
+            other => Err(crate::error::ApiError::Invalid(format!(
+                "unknown external source: {other} (expected imdb or tvdb)"
+            ))),
+        }
+    }
+}
+
+/// Response from `find/{external_id}` endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbFindResponse {
+    /// Matching TV series.
+    #[serde(default)]
+    pub tv_results: Vec<TmdbTvSearchResult>,
+    /// Matching movies.
+    #[serde(default)]
+    pub movie_results: Vec<TmdbMovieSearchResult>,
+}
+
+// --- Configuration ---
+
+/// Response from `configuration` endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbConfiguration {
+    /// Image CDN settings.
+    pub images: TmdbImageConfiguration,
+    /// Keys reported by the change endpoints.
+    #[serde(default)]
+    pub change_keys: Vec<String>,
+}
+
+/// Image CDN base URLs and the sizes available per image type.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbImageConfiguration {
+    /// HTTP base URL (e.g. `http://image.tmdb.org/t/p/`).
+    pub base_url: String,
+    /// HTTPS base URL (e.g. `https://image.tmdb.org/t/p/`).
+    pub secure_base_url: String,
+    /// Backdrop sizes (e.g. "w780", "original").
+    #[serde(default)]
+    pub backdrop_sizes: Vec<String>,
+    /// Logo sizes.
+    #[serde(default)]
+    pub logo_sizes: Vec<String>,
+    /// Poster sizes.
+    #[serde(default)]
+    pub poster_sizes: Vec<String>,
+    /// Person profile sizes.
+    #[serde(default)]
+    pub profile_sizes: Vec<String>,
+    /// Episode still sizes.
+    #[serde(default)]
+    pub still_sizes: Vec<String>,
+}
+
+impl TmdbImageConfiguration {
+    /// Returns whether any image type offers `size`.
+    #[must_use]
+    pub fn has_size(&self, size: &str) -> bool {
+        [
+            &self.backdrop_sizes,
+            &self.logo_sizes,
+            &self.poster_sizes,
+            &self.profile_sizes,
+            &self.still_sizes,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|s| s == size)
+    }
+
+    /// Returns the HTTPS download URL of `path` (e.g. "/abc.jpg") at `size`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::Invalid`](crate::error::ApiError::Invalid) if no
+    /// image type offers `size`.
+    pub fn url(&self, path: &str, size: &str) -> crate::error::Result<String> {
+        if !self.has_size(size) {
+            return Err(crate::error::ApiError::Invalid(format!(
+                "unknown TMDB image size: {size} (e.g. {})",
+                self.poster_sizes.join(", ")
+            )));
+        }
+        Ok(format!(
+            "{}/{size}/{}",
+            self.secure_base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        ))
+    }
+}
+
+// --- Images ---
+
+/// Response from `tv/{series_id}/images` endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbImagesResponse {
+    /// TMDB series ID.
+    pub id: u64,
+    /// Poster images.
+    #[serde(default)]
+    pub posters: Vec<TmdbImage>,
+    /// Backdrop (fanart) images.
+    #[serde(default)]
+    pub backdrops: Vec<TmdbImage>,
+    /// Logo images.
+    #[serde(default)]
+    pub logos: Vec<TmdbImage>,
+}
+
+/// A single image entry.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbImage {
+    /// Image path relative to the CDN size directory (e.g. "/abc.jpg").
+    pub file_path: String,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Language code (ISO 639-1); `None` for text-less images.
+    pub iso_639_1: Option<String>,
+    /// Aspect ratio (width / height).
+    #[serde(default)]
+    pub aspect_ratio: f64,
+    /// Vote average.
+    #[serde(default)]
+    pub vote_average: f64,
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -279,6 +542,50 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_external_source_parse_and_infer() {
+        // Arrange & Act & Assert
+        assert_eq!(
+            "imdb".parse::<TmdbExternalSource>().unwrap(),
+            TmdbExternalSource::Imdb
+        );
+        assert_eq!(TmdbExternalSource::Tvdb.as_str(), "tvdb_id");
+        assert_eq!(
+            TmdbExternalSource::infer("tt13706018"),
+            Some(TmdbExternalSource::Imdb)
+        );
+        assert_eq!(
+            TmdbExternalSource::infer("405920"),
+            Some(TmdbExternalSource::Tvdb)
+        );
+        assert_eq!(TmdbExternalSource::infer("abc"), None);
+        assert!("tmdb".parse::<TmdbExternalSource>().is_err());
+    }
+
+    #[test]
+    fn test_episode_group_absolute_episodes_number_across_parts() {
+        // Arrange: parts listed out of order
+        let json = include_str!(
+            "../../../../fixtures/tmdb/tv_episode_group_6310c3c1a4a9b9007e1f4f21.json"
+        );
+        let mut group: TmdbEpisodeGroup = serde_json::from_str(json).unwrap();
+        group.groups.reverse();
+
+        // Act
+        let episodes = group.absolute_episodes();
+
+        // Assert
+        assert_eq!(group.type_name(), "absolute");
+        let numbered: Vec<(u32, u32, u64)> = episodes
+            .iter()
+            .map(|e| (e.episode_number, e.season_number, e.id))
+            .collect();
+        assert_eq!(
+            numbered,
+            vec![(1, 1, 3_636_093), (2, 1, 3_636_094), (3, 2, 3_875_331)]
+        );
+    }
+
     #[test]
     fn media_type_as_str_tv() {
         // Arrange & Act & Assert
@@ -458,12 +765,76 @@ mod tests {
         assert_eq!(resp.results.len(), 1);
         assert_eq!(resp.results[0].iso_3166_1, "US");
     }
+
+    #[test]
+    fn image_configuration_url_joins_base_size_and_path() {
+        // Arrange
+        let json = include_str!("../../../../fixtures/tmdb/configuration.json");
+        let config: TmdbConfiguration = serde_json::from_str(json).unwrap();
+
+        // Act
+        let original = config.images.url("/p.jpg", "original").unwrap();
+        let profile = config.images.url("/p.jpg", "h632").unwrap();
+        let err = config.images.url("/p.jpg", "w9999").unwrap_err();
+
+        // Assert
+        assert_eq!(original, "https://image.tmdb.org/t/p/original/p.jpg");
+        assert_eq!(profile, "https://image.tmdb.org/t/p/h632/p.jpg");
+        assert!(
+            err.to_string()
+                .starts_with("unknown TMDB image size: w9999")
+        );
+        assert!(config.change_keys.contains(&String::from("images")));
+    }
+
+    #[test]
+    fn deserialize_images_without_language() {
+        // Arrange
+        let json = r#"{"id": 1, "posters": [{"file_path": "/p.jpg", "width": 500, "height": 750, "iso_639_1": null}]}"#;
+
+        // Act
+        let resp: TmdbImagesResponse = serde_json::from_str(json).unwrap();
+
+        // Assert
+        assert!(resp.backdrops.is_empty());
+        assert!(resp.posters[0].iso_639_1.is_none());
+    }
+
+    #[test]
+    fn deserialize_watch_providers_region_lookup() {
+        // Arrange
+        let json = r#"{
+            "id": 300,
+            "results": {
+                "JP": {
+                    "link": "https://www.themoviedb.org/tv/300/watch?locale=JP",
+                    "flatrate": [
+                        {"provider_id": 8, "provider_name": "Netflix", "logo_path": "/n.jpg", "display_priority": 1}
+                    ]
+                },
+                "US": {"buy": [{"provider_id": 2, "provider_name": "Apple TV"}]}
+            }
+        }"#;
+
+        // Act
+        let resp: TmdbWatchProvidersResponse = serde_json::from_str(json).unwrap();
+
+        // Assert
+        let jp = resp.region("JP").unwrap();
+        assert_eq!(jp.flatrate.len(), 1);
+        assert_eq!(jp.flatrate[0].provider_name, "Netflix");
+        assert!(jp.rent.is_empty());
+        let us = resp.region("US").unwrap();
+        assert!(us.link.is_none());
+        assert_eq!(us.buy[0].display_priority, 0);
+        assert!(resp.region("KR").is_none());
+    }
 }
 
 // --- TV Season Details ---
 
 /// Response from `tv/{series_id}/season/{season_number}` endpoint.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TmdbTvSeason {
     /// Internal `MongoDB` ID.
     #[serde(rename = "_id", default)]
@@ -485,7 +856,7 @@ pub struct TmdbTvSeason {
 }
 
 /// A single episode within a season.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TmdbEpisode {
     /// TMDB episode ID.
     pub id: u64,
@@ -509,6 +880,177 @@ pub struct TmdbEpisode {
     pub episode_type: Option<String>,
 }
 
+/// Response from `tv/{series_id}/season/{season_number}/episode/{episode_number}`
+/// endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbEpisodeDetails {
+    /// TMDB episode ID.
+    pub id: u64,
+    /// Episode number within the season.
+    pub episode_number: u32,
+    /// Season number.
+    pub season_number: u32,
+    /// Episode name.
+    pub name: String,
+    /// Episode overview.
+    pub overview: Option<String>,
+    /// Air date.
+    pub air_date: Option<String>,
+    /// Runtime in minutes.
+    pub runtime: Option<u32>,
+    /// Still image path (relative to [`TMDB_IMAGE_BASE_URL`]).
+    pub still_path: Option<String>,
+    /// Vote average.
+    #[serde(default)]
+    pub vote_average: f64,
+    /// Episode type (e.g., "standard", "finale").
+    pub episode_type: Option<String>,
+    /// Guest cast of this episode.
+    #[serde(default)]
+    pub guest_stars: Vec<TmdbGuestStar>,
+}
+
+/// A guest cast member of an episode.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbGuestStar {
+    /// TMDB person ID.
+    pub id: u64,
+    /// Person name.
+    pub name: String,
+    /// Name in the original language.
+    pub original_name: Option<String>,
+    /// Character played.
+    pub character: Option<String>,
+    /// Profile image path.
+    pub profile_path: Option<String>,
+    /// Billing order.
+    pub order: Option<u32>,
+}
+
+// --- Episode Groups ---
+
+/// Returns the name of a TMDB episode group `type` code.
+const fn episode_group_type_name(code: u32) -> &'static str {
+    match code {
+        1 => "original air date",
+        2 => "absolute",
+        3 => "dvd",
+        4 => "digital",
+        5 => "story arc",
+        6 => "production",
+        7 => "tv",
+        _ => "unknown",
+    }
+}
+
+/// Response from `tv/{series_id}/episode_groups` endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbEpisodeGroupsResponse {
+    /// TMDB series ID.
+    pub id: u64,
+    /// Episode groups of the series.
+    pub results: Vec<TmdbEpisodeGroupSummary>,
+}
+
+/// An episode group listed for a series.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbEpisodeGroupSummary {
+    /// Episode group ID.
+    pub id: String,
+    /// Group name.
+    pub name: String,
+    /// Group description.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Number of episodes in the group.
+    pub episode_count: u32,
+    /// Number of parts in the group.
+    pub group_count: u32,
+    /// Group type code (1: original air date, 2: absolute, 3: DVD,
+    /// 4: digital, 5: story arc, 6: production, 7: TV).
+    #[serde(rename = "type")]
+    pub group_type: u32,
+}
+
+impl TmdbEpisodeGroupSummary {
+    /// Returns the name of the group type.
+    #[must_use]
+    pub const fn type_name(&self) -> &'static str {
+        episode_group_type_name(self.group_type)
+    }
+}
+
+/// Response from `tv/episode_group/{group_id}` endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbEpisodeGroup {
+    /// Episode group ID.
+    pub id: String,
+    /// Group name.
+    pub name: String,
+    /// Group description.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Number of episodes in the group.
+    pub episode_count: u32,
+    /// Group type code (see [`TmdbEpisodeGroupSummary::group_type`]).
+    #[serde(rename = "type")]
+    pub group_type: u32,
+    /// Parts of the group (e.g. cours), in any order.
+    pub groups: Vec<TmdbEpisodeGroupPart>,
+}
+
+impl TmdbEpisodeGroup {
+    /// Returns the name of the group type.
+    #[must_use]
+    pub const fn type_name(&self) -> &'static str {
+        episode_group_type_name(self.group_type)
+    }
+
+    /// Returns the episodes of all parts in group order, renumbered from 1
+    /// across parts so `episode_number` is the absolute position.
+    #[must_use]
+    pub fn absolute_episodes(&self) -> Vec<TmdbEpisode> {
+        let mut parts: Vec<&TmdbEpisodeGroupPart> = self.groups.iter().collect();
+        parts.sort_by_key(|part| part.order);
+        parts
+            .into_iter()
+            .flat_map(|part| {
+                let mut episodes: Vec<&TmdbEpisodeGroupEpisode> = part.episodes.iter().collect();
+                episodes.sort_by_key(|e| e.order);
+                episodes
+            })
+            .zip(1_u32..)
+            .map(|(e, number)| TmdbEpisode {
+                episode_number: number,
+                ..e.episode.clone()
+            })
+            .collect()
+    }
+}
+
+/// One part of an episode group.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbEpisodeGroupPart {
+    /// Part ID.
+    pub id: String,
+    /// Part name.
+    pub name: String,
+    /// Position of the part within the group.
+    pub order: u32,
+    /// Episodes of the part.
+    pub episodes: Vec<TmdbEpisodeGroupEpisode>,
+}
+
+/// An episode within an episode group part.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbEpisodeGroupEpisode {
+    /// Position of the episode within its part.
+    pub order: u32,
+    /// Episode with its original season and episode number.
+    #[serde(flatten)]
+    pub episode: TmdbEpisode,
+}
+
 // --- Error Response ---
 
 /// TMDB API error response body.
@@ -0,0 +1,152 @@
+//! TMDB API utility functions.
+
+use tracing::instrument;
+
+use super::api::LocalTmdbApi;
+use super::types::{
+    SearchMultiParams, TmdbMovieSearchResult, TmdbMultiSearchResult, TmdbTvSearchResult,
+};
+use crate::error::Result;
+
+/// Highest result page TMDB serves for a search.
+const SEARCH_PAGE_LIMIT: u32 = 500;
+
+/// Fetches every result page of a `search/multi` query, starting at
+/// `params.page`, and returns the results of all pages in order.
+///
+/// Pages are requested one at a time through `api`, so the client's rate
+/// limiter spaces them out. Stops after `total_pages` (capped at the
+/// 500 pages TMDB serves).
+///
+/// # Errors
+///
+/// Returns an error if any page request fails.
+#[instrument(skip_all, err(level = "error"))]
+pub async fn search_multi_all(
+    api: &(impl LocalTmdbApi + Sync),
+    params: &SearchMultiParams,
+) -> Result<Vec<TmdbMultiSearchResult>> {
+    let mut page = params.page.max(1);
+    let mut results: Vec<TmdbMultiSearchResult> = Vec::new();
+
+    loop {
+        let page_params = params.clone().page(page);
+        let response = api.search_multi(&page_params).await.inspect_err(|e| {
+            tracing::warn!(page = page, error = %e, "search/multi failed");
+        })?;
+        tracing::debug!(
+            page = page,
+            total_pages = response.total_pages,
+            fetched = response.results.len(),
+            "search/multi page completed"
+        );
+        results.extend(response.results);
+
+        if page >= response.total_pages.min(SEARCH_PAGE_LIMIT) {
+            break;
+        }
+        page = page.saturating_add(1);
+    }
+
+    Ok(results)
+}
+
+/// Fetches every result page of a `search/multi` query and returns its TV
+/// series results.
+///
+/// # Errors
+///
+/// Returns an error if any page request fails.
+pub async fn search_tv_all(
+    api: &(impl LocalTmdbApi + Sync),
+    params: &SearchMultiParams,
+) -> Result<Vec<TmdbTvSearchResult>> {
+    Ok(search_multi_all(api, params)
+        .await?
+        .into_iter()
+        .filter_map(|r| match r {
+            TmdbMultiSearchResult::Tv(tv) => Some(tv),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Fetches every result page of a `search/multi` query and returns its
+/// movie results.
+///
+/// # Errors
+///
+/// Returns an error if any page request fails.
+pub async fn search_movie_all(
+    api: &(impl LocalTmdbApi + Sync),
+    params: &SearchMultiParams,
+) -> Result<Vec<TmdbMovieSearchResult>> {
+    Ok(search_multi_all(api, params)
+        .await?
+        .into_iter()
+        .filter_map(|r| match r {
+            TmdbMultiSearchResult::Movie(movie) => Some(movie),
+            _ => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::time::Duration;
+
+    use super::*;
+    use crate::tmdb::TmdbClient;
+
+    /// Mounts a `search/multi` page holding `results` out of `total_pages`.
+    async fn mount_page(server: &wiremock::MockServer, page: u32, total_pages: u32, results: &str) {
+        let body = format!(
+            r#"{{"page":{page},"results":[{results}],"total_pages":{total_pages},"total_results":3}}"#
+        );
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/3/search/multi"))
+            .and(wiremock::matchers::query_param("page", page.to_string()))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(body))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    fn tv(id: u64) -> String {
+        format!(
+            r#"{{"media_type":"tv","id":{id},"name":"TV {id}","original_name":"TV {id}","original_language":"ja","origin_country":["JP"],"popularity":1.0,"vote_average":0.0,"vote_count":0,"genre_ids":[16],"adult":false}}"#
+        )
+    }
+
+    fn movie(id: u64) -> String {
+        format!(
+            r#"{{"media_type":"movie","id":{id},"title":"Movie {id}","original_title":"Movie {id}","original_language":"ja","popularity":1.0,"vote_average":0.0,"vote_count":0,"genre_ids":[16],"adult":false,"video":false}}"#
+        )
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[tokio::test]
+    async fn test_search_all_fetches_every_page() {
+        // Arrange
+        let server = wiremock::MockServer::start().await;
+        mount_page(&server, 1, 2, &format!("{},{}", tv(1), movie(10))).await;
+        mount_page(&server, 2, 2, &tv(2)).await;
+        let client = TmdbClient::builder()
+            .base_url(format!("{}/3/", server.uri()).parse().unwrap())
+            .api_token("test-token")
+            .user_agent("test/0.0.0")
+            .min_interval(Duration::from_millis(0))
+            .build()
+            .unwrap();
+        let params = SearchMultiParams::new("test");
+
+        // Act
+        let tv_results = search_tv_all(&client, &params).await.unwrap();
+
+        // Assert
+        let ids: Vec<u64> = tv_results.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+}
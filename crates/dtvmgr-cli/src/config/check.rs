@@ -0,0 +1,282 @@
+//! Config validation for `config check`.
+
+use std::collections::HashSet;
+
+use dtvmgr_core::sync::{validate_program_fields, validate_title_fields};
+
+use super::AppConfig;
+
+/// Severity of a config issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The setting is likely unintended but the config still works.
+    Warning,
+    /// The setting cannot work as written.
+    Error,
+}
+
+/// A problem found in the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// How serious the issue is.
+    pub severity: Severity,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl ConfigIssue {
+    /// Creates a warning.
+    const fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+
+    /// Creates an error.
+    const fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+}
+
+/// Environment facts the config is checked against.
+#[derive(Debug, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct CheckContext {
+    /// Channel IDs in the channel cache (`None` when the cache is empty).
+    pub cached_channels: Option<HashSet<u32>>,
+    /// Whether `TMDB_API_TOKEN` is set.
+    pub tmdb_token_env: bool,
+    /// Whether a local `dtvmgr.mapping.toml` exists.
+    pub mapping_file: bool,
+}
+
+/// Validates `config` and returns the issues found, errors first.
+///
+/// `unknown_keys` are the keys reported by [`AppConfig::parse`].
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub fn check_config(
+    config: &AppConfig,
+    unknown_keys: &[String],
+    ctx: &CheckContext,
+) -> Vec<ConfigIssue> {
+    let mut issues: Vec<ConfigIssue> = unknown_keys
+        .iter()
+        .map(|key| ConfigIssue::warning(format!("unknown key `{key}` is ignored")))
+        .collect();
+
+    check_channels(config, ctx, &mut issues);
+
+    for pattern in &config.normalize.regex_titles {
+        if let Err(e) = regex::Regex::new(pattern) {
+            issues.push(ConfigIssue::error(format!(
+                "normalize.regex_titles: invalid pattern `{pattern}`: {e}"
+            )));
+        }
+    }
+
+    let sync = &config.syoboi.sync;
+    if let Some(Err(e)) = sync.title_fields.as_deref().map(validate_title_fields) {
+        issues.push(ConfigIssue::error(format!("syoboi.sync.{e}")));
+    }
+    if let Some(Err(e)) = sync.program_fields.as_deref().map(validate_program_fields) {
+        issues.push(ConfigIssue::error(format!("syoboi.sync.{e}")));
+    }
+
+    if let Err(e) = config.timezone() {
+        issues.push(ConfigIssue::error(format!("timezone: {e}")));
+    }
+    if let Some(path) = &config.http.ca_cert
+        && !path.is_file()
+    {
+        issues.push(ConfigIssue::error(format!(
+            "http.ca_cert: file {} does not exist",
+            path.display()
+        )));
+    }
+    // SECURITY: the proxy URL may embed credentials, so it is not echoed.
+    if let Some(proxy) = &config.http.proxy
+        && reqwest::Proxy::all(proxy.as_str()).is_err()
+    {
+        issues.push(ConfigIssue::error(String::from(
+            "http.proxy: invalid proxy URL",
+        )));
+    }
+
+    let has_token = ctx.tmdb_token_env
+        || config.tmdb.api_key.is_some()
+        || config.profile.values().any(|p| p.tmdb.api_key.is_some());
+    if !has_token && ctx.mapping_file {
+        issues.push(ConfigIssue::warning(String::from(
+            "dtvmgr.mapping.toml is present but no TMDB token is configured \
+             (set TMDB_API_TOKEN or tmdb.api_key)",
+        )));
+    }
+
+    issues.sort_by_key(|i| i.severity != Severity::Error);
+    issues
+}
+
+/// Checks selected channel IDs (top level and profiles) against the cache.
+fn check_channels(config: &AppConfig, ctx: &CheckContext, issues: &mut Vec<ConfigIssue>) {
+    let selections = std::iter::once((
+        String::from("syoboi.channels.selected"),
+        &config.syoboi.channels.selected,
+    ))
+    .chain(config.profile.iter().filter_map(|(name, p)| {
+        p.channels
+            .as_ref()
+            .map(|ids| (format!("profile.{name}.channels"), ids))
+    }));
+
+    let Some(cached) = &ctx.cached_channels else {
+        if selections.into_iter().any(|(_, ids)| !ids.is_empty()) {
+            issues.push(ConfigIssue::warning(String::from(
+                "channel cache is empty; run `syoboi channels select` to validate channel IDs",
+            )));
+        }
+        return;
+    };
+
+    for (key, ids) in selections {
+        let unknown: Vec<String> = ids
+            .iter()
+            .filter(|id| !cached.contains(id))
+            .map(ToString::to_string)
+            .collect();
+        if !unknown.is_empty() {
+            issues.push(ConfigIssue::error(format!(
+                "{key}: unknown channel IDs {}",
+                unknown.join(", ")
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    #[test]
+    fn test_check_config_reports_unknown_channels_and_keys() {
+        // Arrange
+        let (config, unknown) = AppConfig::parse(
+            "[syoboi.channels]\nselected = [1, 99]\n\n[syoboi.chanels]\nselected = [2]\n\n\
+             [profile.home]\nchannels = [7]\n",
+        )
+        .unwrap();
+        let ctx = CheckContext {
+            cached_channels: Some(HashSet::from([1, 2])),
+            ..CheckContext::default()
+        };
+
+        // Act
+        let issues = check_config(&config, &unknown, &ctx);
+
+        // Assert
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "syoboi.channels.selected: unknown channel IDs 99",
+                "profile.home.channels: unknown channel IDs 7",
+                "unknown key `syoboi.chanels` is ignored",
+            ]
+        );
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[2].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_check_config_without_channel_cache_or_token() {
+        // Arrange
+        let (config, unknown) = AppConfig::parse(
+            "[syoboi.channels]\nselected = [1]\n\n[normalize]\nregex_titles = [\"(\"]\n",
+        )
+        .unwrap();
+        let ctx = CheckContext {
+            mapping_file: true,
+            ..CheckContext::default()
+        };
+
+        // Act
+        let issues = check_config(&config, &unknown, &ctx);
+
+        // Assert
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert!(issues[0].message.starts_with("normalize.regex_titles"));
+        assert!(issues[1].message.starts_with("channel cache is empty"));
+        assert!(issues[2].message.contains("no TMDB token"));
+    }
+
+    #[test]
+    fn test_check_config_reports_invalid_timezone_and_http_settings() {
+        // Arrange
+        let (config, unknown) = AppConfig::parse(
+            "timezone = \"Mars/Olympus\"\n[http]\nproxy = \"user:secret@::not a url\"\nca_cert = \"/nonexistent/ca.pem\"\n",
+        )
+        .unwrap();
+
+        // Act
+        let issues = check_config(&config, &unknown, &CheckContext::default());
+
+        // Assert
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "timezone: unknown timezone \"Mars/Olympus\"",
+                "http.ca_cert: file /nonexistent/ca.pem does not exist",
+                "http.proxy: invalid proxy URL",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_config_reports_invalid_sync_fields() {
+        // Arrange
+        let (config, unknown) = AppConfig::parse(
+            "[syoboi.sync]\ntitle_fields = [\"TID\", \"Title\", \"Points\"]\n\
+             program_fields = [\"PID\", \"TID\", \"StTime\", \"EdTime\", \"ChID\", \"Count\"]\n",
+        )
+        .unwrap();
+
+        // Act
+        let issues = check_config(&config, &unknown, &CheckContext::default());
+
+        // Assert
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert!(
+            issues[0]
+                .message
+                .starts_with("syoboi.sync.title_fields: unknown field(s) Points (known: TID, "),
+            "{}",
+            issues[0].message
+        );
+    }
+
+    #[test]
+    fn test_check_config_default_template_is_clean() {
+        // Arrange
+        let (config, unknown) =
+            AppConfig::parse(&AppConfig::default().to_commented_toml()).unwrap();
+        let ctx = CheckContext {
+            tmdb_token_env: true,
+            ..CheckContext::default()
+        };
+
+        // Act
+        let issues = check_config(&config, &unknown, &ctx);
+
+        // Assert
+        assert!(issues.is_empty(), "{issues:?}");
+    }
+}
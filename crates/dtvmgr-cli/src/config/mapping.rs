@@ -9,12 +9,14 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use super::config::read_toml_text;
+
 /// GitHub raw URL for the shared mapping file.
 const MAPPING_GITHUB_URL: &str =
     "https://raw.githubusercontent.com/naa0yama/dtvmgr/main/dtvmgr.mapping.toml";
 
 /// Filename for the local mapping file.
-const MAPPING_FILENAME: &str = "dtvmgr.mapping.toml";
+pub const MAPPING_FILENAME: &str = "dtvmgr.mapping.toml";
 
 /// A single manual tid-to-TMDB mapping entry.
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
@@ -32,6 +34,14 @@ pub struct MappingEntry {
     /// TMDB season ID. Use 0 as placeholder for unfilled entries.
     #[serde(default)]
     pub tmdb_season_id: u64,
+    /// TMDB movie ID for movie titles. Takes precedence over the series
+    /// mapping when set to a non-zero value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb_movie_id: Option<u64>,
+    /// TMDB episode group ID (alternate order) matched instead of the
+    /// season, for series whose absolute numbering follows Syoboi counts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb_episode_group_id: Option<String>,
 }
 
 /// Top-level mapping file structure.
@@ -53,8 +63,8 @@ impl MappingFile {
                 mappings: Vec::new(),
             });
         }
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("failed to read {}", path.display()))?;
+        let content =
+            read_toml_text(path).with_context(|| format!("failed to read {}", path.display()))?;
         toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))
     }
 
@@ -83,6 +93,8 @@ impl MappingFile {
                     tmdb_series_id: 0,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 });
             }
         }
@@ -232,6 +244,46 @@ tmdb_series_id = 67890
         // Assert
         assert_eq!(result.mappings.len(), 1);
         assert_eq!(result.mappings[0].tmdb_season_number, None);
+        assert_eq!(result.mappings[0].tmdb_movie_id, None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_load_movie_mapping() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[mappings]]
+tid = 6500
+name = "すずめの戸締まり"
+tmdb_series_id = 0
+tmdb_movie_id = 916224
+
+[[mappings]]
+tid = 6309
+name = "SPY×FAMILY"
+tmdb_series_id = 120089
+tmdb_episode_group_id = "6310c3c1a4a9b9007e1f4f21"
+"#,
+        )
+        .unwrap();
+
+        // Act
+        let result = MappingFile::load(&path).unwrap();
+        result.save(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+
+        // Assert
+        assert_eq!(result.mappings[0].tmdb_movie_id, Some(916_224));
+        assert!(saved.contains("tmdb_movie_id = 916224"));
+        assert_eq!(
+            result.mappings[1].tmdb_episode_group_id.as_deref(),
+            Some("6310c3c1a4a9b9007e1f4f21")
+        );
+        assert!(saved.contains("tmdb_episode_group_id = \"6310c3c1a4a9b9007e1f4f21\""));
     }
 
     #[test]
@@ -245,6 +297,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 200,
                     tmdb_season_number: Some(1),
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
                 MappingEntry {
                     tid: 300,
@@ -252,6 +306,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 400,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
             ],
         };
@@ -279,6 +335,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 200,
                     tmdb_season_number: Some(1),
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
                 MappingEntry {
                     tid: 300,
@@ -286,6 +344,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 0,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
             ],
         };
@@ -308,6 +368,8 @@ tmdb_series_id = 67890
                 tmdb_series_id: 999,
                 tmdb_season_number: None,
                 tmdb_season_id: 0,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
             }],
         };
 
@@ -333,6 +395,8 @@ tmdb_series_id = 67890
                 tmdb_series_id: 999,
                 tmdb_season_number: Some(2),
                 tmdb_season_id: 0,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
             }],
         };
 
@@ -356,6 +420,8 @@ tmdb_series_id = 67890
                 tmdb_series_id: 999,
                 tmdb_season_number: None,
                 tmdb_season_id: 0,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
             }],
         };
 
@@ -395,6 +461,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 1,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
                 MappingEntry {
                     tid: 200,
@@ -402,6 +470,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 2,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
                 MappingEntry {
                     tid: 300,
@@ -409,6 +479,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 3,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
             ],
         };
@@ -447,6 +519,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 1,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
                 MappingEntry {
                     tid: 200,
@@ -454,6 +528,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 2,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
             ],
         };
@@ -494,6 +570,8 @@ tmdb_series_id = 67890
                 tmdb_series_id: 1,
                 tmdb_season_number: None,
                 tmdb_season_id: 0,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
             }],
         };
 
@@ -517,6 +595,8 @@ tmdb_series_id = 67890
                 tmdb_series_id: 200,
                 tmdb_season_number: None,
                 tmdb_season_id: 0,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
             }],
         };
 
@@ -558,6 +638,8 @@ tmdb_series_id = 67890
                 tmdb_series_id: 999,
                 tmdb_season_number: Some(1),
                 tmdb_season_id: 42,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
             }],
         };
 
@@ -581,6 +663,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 1,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
                 MappingEntry {
                     tid: 100,
@@ -588,6 +672,8 @@ tmdb_series_id = 67890
                     tmdb_series_id: 2,
                     tmdb_season_number: None,
                     tmdb_season_id: 0,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
                 },
             ],
         };
@@ -610,6 +696,8 @@ tmdb_series_id = 67890
                 tmdb_series_id: 1,
                 tmdb_season_number: None,
                 tmdb_season_id: 0,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
             }],
         };
 
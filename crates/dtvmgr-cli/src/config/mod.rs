@@ -3,12 +3,21 @@
 //! Manages TOML-based config files for user settings such as
 //! selected channel IDs.
 
+pub mod check;
 #[allow(clippy::module_inception)]
 mod config;
 pub mod mapping;
 mod paths;
+mod profile;
+pub mod selection;
 
+pub use config::read_toml_text;
 #[allow(clippy::module_name_repetitions)]
-pub use config::AppConfig;
+pub use config::{
+    AppConfig, ChannelsConfig, DEFAULT_TMDB_LANGUAGE, DEFAULT_TMDB_REGION, EmailConfig,
+    NotifyConfig, RateLimitConfig,
+};
 pub use mapping::load_or_fetch;
-pub use paths::{resolve_config_path, resolve_data_dir};
+pub use paths::{
+    Paths, resolve_cache_dir, resolve_config_path, resolve_data_dir, set_path_overrides,
+};
@@ -1,87 +1,441 @@
-//! Config directory resolution.
-
-use std::path::PathBuf;
+//! Config, data, and cache directory resolution.
+//!
+//! Defaults follow platform-native locations ([`PlatformDirs`]: XDG base
+//! directories on Linux, `~/Library` on macOS, `%APPDATA%` on Windows). A
+//! global `--dir` override places every file under a single directory
+//! instead, and `--data-dir` moves only the data directory (e.g. next to a
+//! read-only mounted `--config` file). A `--profile` with a `data_dir` acts
+//! like `--data-dir`.
+//!
+//! Config and data are kept apart: a `--config` inside the platform config
+//! directory still stores the database in the platform data directory.
+//! [`Paths::migrate_legacy_db`] moves a database left next to that config
+//! into the platform data directory. It and [`Paths::migrate_legacy_config`]
+//! also move the database and config of the layout earlier versions used on
+//! every platform ([`PlatformDirs::legacy`]).
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use anyhow::{Context, Result};
+use dtvmgr_db::platform::PlatformDirs;
+use dtvmgr_db::{Connection, DB_FILE_NAME, DbOptions, detach_wal, open_db_with_options};
+
+use super::config::read_toml_text;
+use super::profile::{profile_data_dir, set_active_profile};
 
 /// Config file name.
 const CONFIG_FILE_NAME: &str = "dtvmgr.toml";
 
-/// Resolves the data directory for database and other files.
+/// Cache subdirectory name under the `--dir` override.
+const CACHE_DIR_NAME: &str = "cache";
+
+/// Directory overrides from global CLI flags (or their environment variables).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathOverrides {
+    /// `--dir` / `DTVMGR_DIR`: base for config, data, and cache.
+    pub dir: Option<PathBuf>,
+    /// `--data-dir` / `DTVMGR_DATA_DIR`: data directory only.
+    pub data_dir: Option<PathBuf>,
+}
+
+/// Process-wide overrides, set once at startup.
+static OVERRIDES: OnceLock<PathOverrides> = OnceLock::new();
+
+/// Inputs of path resolution: overrides, platform base directories, and
+/// the working directory.
+///
+/// The process uses [`Paths::current`]; tests build one with their own
+/// directories instead of touching `$HOME` or changing the CWD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    /// Directory overrides from global CLI flags.
+    pub overrides: PathOverrides,
+    /// Platform config directory (see [`PlatformDirs`]).
+    pub config_home: Option<PathBuf>,
+    /// Platform data directory (see [`PlatformDirs`]).
+    pub data_home: Option<PathBuf>,
+    /// Platform cache directory (see [`PlatformDirs`]).
+    pub cache_home: Option<PathBuf>,
+    /// Config directory of earlier versions (see [`PlatformDirs::legacy`]).
+    pub legacy_config_home: Option<PathBuf>,
+    /// Data directory of earlier versions (see [`PlatformDirs::legacy`]).
+    pub legacy_data_home: Option<PathBuf>,
+    /// Working directory for `./dtvmgr.toml` detection and relative overrides.
+    pub cwd: PathBuf,
+}
+
+impl Paths {
+    /// Returns the process-wide overrides with the platform directories
+    /// and the current working directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current directory cannot be determined.
+    pub fn current() -> Result<Self> {
+        Self::platform(overrides().clone())
+    }
+
+    /// Returns `overrides` with the platform directories and the current
+    /// working directory.
+    fn platform(overrides: PathOverrides) -> Result<Self> {
+        let dirs = PlatformDirs::detect();
+        let legacy = PlatformDirs::legacy(|key| std::env::var_os(key));
+        Ok(Self {
+            overrides,
+            config_home: dirs.config,
+            data_home: dirs.data,
+            cache_home: dirs.cache,
+            legacy_config_home: legacy.config,
+            legacy_data_home: legacy.data,
+            cwd: std::env::current_dir().context("failed to get current directory")?,
+        })
+    }
+
+    /// Returns `--dir`, resolved against the working directory.
+    fn dir(&self) -> Option<PathBuf> {
+        self.overrides.dir.as_ref().map(|d| self.cwd.join(d))
+    }
+
+    /// Resolves the data directory for database and other files.
+    ///
+    /// Priority:
+    /// 1. `--data-dir` / `DTVMGR_DATA_DIR` → that directory
+    /// 2. `--dir` / `DTVMGR_DIR` → that directory
+    /// 3. `--config` / `DTVMGR_CONFIG` specified → parent directory of the
+    ///    config file, unless it is the platform config directory
+    /// 4. CWD `./dtvmgr.toml` exists with marker keys → CWD
+    /// 5. Platform data directory ([`PlatformDirs`])
+    /// 6. `None` when the platform data directory cannot be determined
+    ///
+    /// Relative overrides are resolved against the working directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file cannot be canonicalized or CWD
+    /// detection fails.
+    pub fn data_dir(&self, config: Option<&PathBuf>) -> Result<Option<PathBuf>> {
+        if let Some(d) = &self.overrides.data_dir {
+            return Ok(Some(self.cwd.join(d)));
+        }
+        if let Some(d) = self.dir() {
+            return Ok(Some(d));
+        }
+
+        if let Some(c) = config {
+            let abs = std::fs::canonicalize(self.cwd.join(c))
+                .with_context(|| format!("failed to canonicalize config path: {}", c.display()))?;
+            let parent = abs.parent().map(PathBuf::from);
+            // The platform config directory holds only the config; the
+            // database belongs in the platform data directory.
+            if parent.is_some() && parent == self.canonical_config_home() {
+                return Ok(self.data_home.clone());
+            }
+            return Ok(parent);
+        }
+
+        if let Some(cwd_config) = detect_config_in_dir(&self.cwd)? {
+            // Parent of `{cwd}/dtvmgr.toml` is the CWD itself.
+            return Ok(cwd_config.parent().map(PathBuf::from));
+        }
+
+        Ok(self.data_home.clone())
+    }
+
+    /// Resolves the config file path.
+    ///
+    /// Priority:
+    /// 1. `--config` / `DTVMGR_CONFIG` specified → that path directly (canonicalized)
+    /// 2. `--dir` / `DTVMGR_DIR` → `{dir}/dtvmgr.toml`
+    /// 3. CWD `./dtvmgr.toml` exists with `syoboi` or `tmdb` top-level key → CWD path
+    /// 4. Platform config directory ([`PlatformDirs`]) → `{config_home}/dtvmgr.toml`
+    ///
+    /// `--data-dir` never affects the config path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined (when
+    /// `config` is `None`) or CWD detection fails.
+    pub fn config_path(&self, config: Option<&PathBuf>) -> Result<PathBuf> {
+        if let Some(c) = config {
+            // Return the path as-is if it doesn't exist yet (e.g. for init),
+            // otherwise canonicalize to resolve relative paths.
+            let joined = self.cwd.join(c);
+            return if joined.exists() {
+                std::fs::canonicalize(&joined)
+                    .with_context(|| format!("failed to canonicalize config path: {}", c.display()))
+            } else {
+                Ok(c.clone())
+            };
+        }
+
+        if let Some(d) = self.dir() {
+            return Ok(d.join(CONFIG_FILE_NAME));
+        }
+
+        // Try CWD auto-detection
+        if let Some(cwd_path) = detect_config_in_dir(&self.cwd)? {
+            return Ok(cwd_path);
+        }
+
+        let home = self
+            .config_home
+            .as_ref()
+            .context("failed to determine the home directory")?;
+        Ok(home.join(CONFIG_FILE_NAME))
+    }
+
+    /// Resolves the cache directory for downloaded media (logos, images).
+    ///
+    /// Priority:
+    /// 1. `--dir` / `DTVMGR_DIR` → `{dir}/cache`
+    /// 2. Platform cache directory ([`PlatformDirs`])
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined.
+    pub fn cache_dir(&self) -> Result<PathBuf> {
+        if let Some(d) = self.dir() {
+            return Ok(d.join(CACHE_DIR_NAME));
+        }
+        self.cache_home
+            .clone()
+            .context("failed to determine the home directory")
+    }
+
+    /// Resolves the database file path (`dtvmgr.db` in [`Paths::data_dir`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined.
+    pub fn db_path(&self, config: Option<&PathBuf>) -> Result<PathBuf> {
+        let dir = self
+            .data_dir(config)?
+            .context("failed to determine the data directory")?;
+        Ok(dir.join(DB_FILE_NAME))
+    }
+
+    /// Opens the database in [`Paths::data_dir`] with `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined or the
+    /// database cannot be opened or migrated.
+    pub fn open_db(&self, config: Option<&PathBuf>, options: &DbOptions) -> Result<Connection> {
+        let dir = self
+            .data_dir(config)?
+            .context("failed to determine the data directory")?;
+        open_db_with_options(Some(&dir), options).context("failed to open database")
+    }
+
+    /// Moves the config file from the legacy config directory
+    /// ([`PlatformDirs::legacy`]) into the platform config directory.
+    ///
+    /// Only applies when no `--config` is given, the config path resolves
+    /// to the platform config directory, and no config exists there yet.
+    /// Returns the new config path, or `None` when nothing was moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file cannot be moved.
+    pub fn migrate_legacy_config(&self, config: Option<&PathBuf>) -> Result<Option<PathBuf>> {
+        let (Some(legacy_home), Some(config_home)) = (&self.legacy_config_home, &self.config_home)
+        else {
+            return Ok(None);
+        };
+        let from = legacy_home.join(CONFIG_FILE_NAME);
+        let to = config_home.join(CONFIG_FILE_NAME);
+        if config.is_some() || legacy_home == config_home || !from.exists() || to.exists() {
+            return Ok(None);
+        }
+        if self.config_path(None).ok().as_ref() != Some(&to) {
+            return Ok(None);
+        }
+        std::fs::create_dir_all(config_home)
+            .with_context(|| format!("failed to create directory {}", config_home.display()))?;
+        move_file(&from, &to)?;
+        Ok(Some(to))
+    }
+
+    /// Moves a database left in the platform config directory (the former
+    /// combined layout) or in the legacy config or data directory
+    /// ([`PlatformDirs::legacy`]) into the platform data directory.
+    ///
+    /// Only applies when the data directory resolves to the platform data
+    /// directory and holds no database yet; the first legacy database found
+    /// is moved. The write-ahead log is first checkpointed into `dtvmgr.db`
+    /// ([`detach_wal`]), which also refuses a database another process has
+    /// open, so only that one file is moved.
+    /// Returns the new database path, or `None` when nothing was moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the legacy database is in use or cannot be
+    /// checkpointed or moved. The legacy database stays in place then.
+    pub fn migrate_legacy_db(&self, config: Option<&PathBuf>) -> Result<Option<PathBuf>> {
+        let Some(data_home) = &self.data_home else {
+            return Ok(None);
+        };
+        let to = data_home.join(DB_FILE_NAME);
+        let from = [
+            &self.config_home,
+            &self.legacy_config_home,
+            &self.legacy_data_home,
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|dir| *dir != data_home)
+        .map(|dir| dir.join(DB_FILE_NAME))
+        .find(|path| path.exists());
+        let Some(from) = from else {
+            return Ok(None);
+        };
+        if to.exists() {
+            return Ok(None);
+        }
+        // An unresolvable data directory (e.g. `--config` for a file `init`
+        // has yet to create) is reported by the command that needs it.
+        if self.data_dir(config).ok().flatten().as_ref() != Some(data_home) {
+            return Ok(None);
+        }
+        detach_wal(&from)?;
+        std::fs::create_dir_all(data_home)
+            .with_context(|| format!("failed to create directory {}", data_home.display()))?;
+        move_file(&from, &to)?;
+        Ok(Some(to))
+    }
+
+    /// Returns the canonical platform config directory, if it exists.
+    fn canonical_config_home(&self) -> Option<PathBuf> {
+        self.config_home
+            .as_ref()
+            .and_then(|d| std::fs::canonicalize(d).ok())
+    }
+}
+
+/// Sets the overrides used by all path resolvers and selects `profile`.
+///
+/// Without `data_dir`, the `data_dir` of the profile (if any) is used.
+/// The given directories are created if missing and stored as absolute
+/// paths. Later calls are ignored.
 ///
-/// Priority:
-/// 1. `--config` specified → parent directory of the config file
-/// 2. CWD `./dtvmgr.toml` exists with marker keys → CWD
-/// 3. `None` (falls back to `dtvmgr-db` default `~/.local/share/dtvmgr/`)
+/// A config file left in the legacy config directory is moved first
+/// ([`Paths::migrate_legacy_config`]), before anything reads the config;
+/// its new path is returned.
 ///
 /// # Errors
 ///
-/// Returns an error if CWD detection fails.
-pub fn resolve_data_dir(config: Option<&PathBuf>) -> Result<Option<PathBuf>> {
-    if let Some(c) = config {
-        let abs = std::fs::canonicalize(c)
-            .with_context(|| format!("failed to canonicalize config path: {}", c.display()))?;
-        return Ok(abs.parent().map(PathBuf::from));
+/// Returns an error if a directory cannot be created or canonicalized, the
+/// legacy config file cannot be moved, or the profile does not exist in the
+/// config file.
+pub fn set_path_overrides(
+    dir: Option<&Path>,
+    data_dir: Option<&Path>,
+    profile: Option<&str>,
+    config: Option<&PathBuf>,
+) -> Result<Option<PathBuf>> {
+    let mut paths = Paths::platform(PathOverrides {
+        dir: dir.map(ensure_dir).transpose()?,
+        data_dir: data_dir.map(ensure_dir).transpose()?,
+    })?;
+    let moved_config = paths
+        .migrate_legacy_config(config)
+        .context("failed to move the config file out of the legacy config directory")?;
+    if let Some(name) = profile {
+        let config_path = paths.config_path(config)?;
+        let profile_dir = profile_data_dir(&config_path, name)?;
+        if paths.overrides.data_dir.is_none() {
+            paths.overrides.data_dir = profile_dir.as_deref().map(ensure_dir).transpose()?;
+        }
+        set_active_profile(name);
     }
+    let _ = OVERRIDES.set(paths.overrides);
+    Ok(moved_config)
+}
+
+/// Creates `dir` if missing and returns its canonical path.
+fn ensure_dir(dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create directory {}", dir.display()))?;
+    std::fs::canonicalize(dir)
+        .with_context(|| format!("failed to canonicalize directory: {}", dir.display()))
+}
 
-    if let Some(cwd_config) = detect_cwd_config()? {
-        // Parent of `{cwd}/dtvmgr.toml` is the CWD itself.
-        return Ok(cwd_config.parent().map(PathBuf::from));
+/// Moves `from` to `to`, copying when they are on different file systems.
+///
+/// A copy goes to a temporary name next to `to` and is renamed into place,
+/// so `to` never holds a partial file. If `from` cannot be removed
+/// afterwards, `to` is removed again so only one copy remains.
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let mut tmp = to.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let copied = std::fs::copy(from, &tmp)
+        .and_then(|_| std::fs::File::open(&tmp)?.sync_all())
+        .and_then(|()| std::fs::rename(&tmp, to));
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e)
+            .with_context(|| format!("failed to copy {} to {}", from.display(), to.display()));
+    }
+    if let Err(e) = std::fs::remove_file(from) {
+        let _ = std::fs::remove_file(to);
+        return Err(e).with_context(|| format!("failed to remove {}", from.display()));
     }
+    Ok(())
+}
 
-    Ok(None)
+/// Returns the configured overrides (empty when unset).
+fn overrides() -> &'static PathOverrides {
+    OVERRIDES.get_or_init(PathOverrides::default)
+}
+
+/// Resolves the data directory for database and other files.
+///
+/// See [`Paths::data_dir`] for the priority order.
+///
+/// # Errors
+///
+/// Returns an error if CWD detection fails.
+pub fn resolve_data_dir(config: Option<&PathBuf>) -> Result<Option<PathBuf>> {
+    Paths::current()?.data_dir(config)
 }
 
 /// Resolves the config file path.
 ///
-/// Priority:
-/// 1. `--config` specified → that path directly (canonicalized)
-/// 2. CWD `./dtvmgr.toml` exists with `syoboi` or `tmdb` top-level key → CWD path
-/// 3. `~/.config/dtvmgr/dtvmgr.toml`
+/// See [`Paths::config_path`] for the priority order.
 ///
 /// # Errors
 ///
 /// Returns an error if the home directory cannot be determined (when `config` is `None`)
 /// or CWD detection fails.
 pub fn resolve_config_path(config: Option<&PathBuf>) -> Result<PathBuf> {
-    if let Some(c) = config {
-        // Return the path as-is if it doesn't exist yet (e.g. for init),
-        // otherwise canonicalize to resolve relative paths.
-        return if c.exists() {
-            std::fs::canonicalize(c)
-                .with_context(|| format!("failed to canonicalize config path: {}", c.display()))
-        } else {
-            Ok(c.clone())
-        };
-    }
-
-    // Try CWD auto-detection
-    if let Some(cwd_path) = detect_cwd_config()? {
-        return Ok(cwd_path);
-    }
-
-    let home = std::env::var("HOME").context("HOME environment variable is not set")?;
-    Ok(PathBuf::from(home)
-        .join(".config")
-        .join("dtvmgr")
-        .join(CONFIG_FILE_NAME))
+    Paths::current()?.config_path(config)
 }
 
-/// Checks if `./dtvmgr.toml` exists in CWD and contains marker keys.
-fn detect_cwd_config() -> Result<Option<PathBuf>> {
-    let cwd = std::env::current_dir().context("failed to get current directory")?;
-    detect_config_in_dir(&cwd)
+/// Resolves the cache directory for downloaded media (logos, images).
+///
+/// See [`Paths::cache_dir`] for the priority order.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn resolve_cache_dir() -> Result<PathBuf> {
+    Paths::current()?.cache_dir()
 }
 
 /// Checks if `dtvmgr.toml` exists in `dir` and contains marker keys.
-fn detect_config_in_dir(dir: &std::path::Path) -> Result<Option<PathBuf>> {
+fn detect_config_in_dir(dir: &Path) -> Result<Option<PathBuf>> {
     let path = dir.join(CONFIG_FILE_NAME);
     if !path.exists() {
         return Ok(None);
     }
 
-    let content = std::fs::read_to_string(&path)
-        .with_context(|| format!("failed to read {}", path.display()))?;
+    let content =
+        read_toml_text(&path).with_context(|| format!("failed to read {}", path.display()))?;
     let value: toml::Value =
         toml::from_str(&content).with_context(|| format!("failed to parse {}", path.display()))?;
 
@@ -100,8 +454,23 @@ fn detect_config_in_dir(dir: &std::path::Path) -> Result<Option<PathBuf>> {
 mod tests {
     #![allow(clippy::unwrap_used)]
 
+    use dtvmgr_db::platform::Platform;
+
     use super::*;
 
+    /// Returns paths with platform directories under `root` and `root` as CWD.
+    fn paths_in(root: &Path, overrides: PathOverrides) -> Paths {
+        Paths {
+            overrides,
+            config_home: Some(root.join("config")),
+            data_home: Some(root.join("data")),
+            cache_home: Some(root.join("cache")),
+            legacy_config_home: None,
+            legacy_data_home: None,
+            cwd: root.to_path_buf(),
+        }
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_resolve_with_config_file() {
@@ -331,4 +700,249 @@ mod tests {
         // Assert: no marker keys → None
         assert!(result.unwrap().is_none());
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_dir_override_takes_precedence() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("other.toml");
+        std::fs::write(&config_file, "").unwrap();
+        let over = PathOverrides {
+            dir: Some(PathBuf::from("/srv/dtvmgr")),
+            data_dir: None,
+        };
+        let paths = paths_in(dir.path(), over);
+
+        // Act
+        let data = paths.data_dir(Some(&config_file)).unwrap();
+        let config = paths.config_path(None).unwrap();
+        let explicit = paths.config_path(Some(&config_file)).unwrap();
+        let cache = paths.cache_dir().unwrap();
+
+        // Assert: --dir wins for data and cache; --config still names the file
+        assert_eq!(data, Some(PathBuf::from("/srv/dtvmgr")));
+        assert_eq!(config, PathBuf::from("/srv/dtvmgr/dtvmgr.toml"));
+        assert_eq!(explicit, std::fs::canonicalize(&config_file).unwrap());
+        assert_eq!(cache, PathBuf::from("/srv/dtvmgr/cache"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_data_dir_override_is_independent_of_config() {
+        // Arrange: read-only style config elsewhere, data in its own directory
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("dtvmgr.toml");
+        std::fs::write(&config_file, "").unwrap();
+        let over = PathOverrides {
+            dir: Some(PathBuf::from("/srv/base")),
+            data_dir: Some(PathBuf::from("/var/lib/dtvmgr")),
+        };
+        let paths = paths_in(dir.path(), over);
+
+        // Act
+        let data = paths.data_dir(Some(&config_file)).unwrap();
+        let config = paths.config_path(Some(&config_file)).unwrap();
+        let default_config = paths.config_path(None).unwrap();
+        let cache = paths.cache_dir().unwrap();
+
+        // Assert: --data-dir only moves the data directory
+        assert_eq!(data, Some(PathBuf::from("/var/lib/dtvmgr")));
+        assert_eq!(config, std::fs::canonicalize(&config_file).unwrap());
+        assert_eq!(default_config, PathBuf::from("/srv/base/dtvmgr.toml"));
+        assert_eq!(cache, PathBuf::from("/srv/base/cache"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_resolve_cache_dir_default_is_absolute() {
+        // Arrange & Act
+        let path = Paths::current().unwrap().cache_dir().unwrap();
+
+        // Assert
+        assert!(path.is_absolute());
+        assert!(path.to_string_lossy().contains("dtvmgr"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_relative_dir_override_resolves_against_cwd() {
+        // Arrange
+        let root = tempfile::tempdir().unwrap();
+        let paths = paths_in(
+            root.path(),
+            PathOverrides {
+                dir: Some(PathBuf::from("state")),
+                data_dir: None,
+            },
+        );
+
+        // Act & Assert: config, data, and cache all live under {cwd}/state
+        let base = root.path().join("state");
+        assert_eq!(paths.config_path(None).unwrap(), base.join("dtvmgr.toml"));
+        assert_eq!(paths.data_dir(None).unwrap(), Some(base.clone()));
+        assert_eq!(paths.cache_dir().unwrap(), base.join("cache"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_config_in_config_home_keeps_data_in_data_home() {
+        // Arrange
+        let root = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(root.path()).unwrap();
+        let paths = paths_in(&root, PathOverrides::default());
+        std::fs::create_dir_all(root.join("config")).unwrap();
+        let config_file = root.join("config").join("dtvmgr.toml");
+        std::fs::write(&config_file, "").unwrap();
+
+        // Act
+        let data = paths.data_dir(Some(&config_file)).unwrap();
+        let default = paths.data_dir(None).unwrap();
+
+        // Assert
+        assert_eq!(data, Some(root.join("data")));
+        assert_eq!(default, Some(root.join("data")));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_migrate_legacy_db_moves_db_from_config_home() {
+        // Arrange: a database left next to the config
+        let root = tempfile::tempdir().unwrap();
+        let paths = paths_in(root.path(), PathOverrides::default());
+        let config_home = root.path().join("config");
+        let conn = dtvmgr_db::open_db(Some(&config_home)).unwrap();
+        conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (1, 'A');")
+            .unwrap();
+        drop(conn);
+
+        // Act
+        let moved = paths.migrate_legacy_db(None).unwrap();
+        let again = paths.migrate_legacy_db(None).unwrap();
+
+        // Assert: committed rows moved with the single database file
+        let db_path = root.path().join("data").join("dtvmgr.db");
+        assert_eq!(moved, Some(db_path.clone()));
+        assert_eq!(paths.db_path(None).unwrap(), db_path);
+        assert!(!config_home.join("dtvmgr.db").exists());
+        assert!(!config_home.join("dtvmgr.db-wal").exists());
+        let conn = paths.open_db(None, &DbOptions::default()).unwrap();
+        assert_eq!(dtvmgr_db::load_channels(&conn).unwrap().len(), 1);
+        assert!(again.is_none());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_migrate_legacy_db_refuses_database_in_use() {
+        // Arrange: another connection keeps the legacy database open
+        let root = tempfile::tempdir().unwrap();
+        let paths = paths_in(root.path(), PathOverrides::default());
+        let config_home = root.path().join("config");
+        let conn = dtvmgr_db::open_db(Some(&config_home)).unwrap();
+        conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (1, 'A');")
+            .unwrap();
+
+        // Act
+        let result = paths.migrate_legacy_db(None);
+
+        // Assert: nothing moved
+        assert!(result.unwrap_err().to_string().contains("in use"));
+        assert!(config_home.join("dtvmgr.db").exists());
+        assert!(!root.path().join("data").join("dtvmgr.db").exists());
+        assert_eq!(dtvmgr_db::load_channels(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_migrate_legacy_db_skips_when_dir_override_is_set() {
+        // Arrange
+        let root = tempfile::tempdir().unwrap();
+        let paths = paths_in(
+            root.path(),
+            PathOverrides {
+                dir: Some(PathBuf::from("state")),
+                data_dir: None,
+            },
+        );
+        let config_home = root.path().join("config");
+        std::fs::create_dir_all(&config_home).unwrap();
+        std::fs::write(config_home.join("dtvmgr.db"), "db").unwrap();
+
+        // Act
+        let moved = paths.migrate_legacy_db(None).unwrap();
+
+        // Assert
+        assert!(moved.is_none());
+        assert!(config_home.join("dtvmgr.db").exists());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_migrate_legacy_moves_config_and_db_on_macos() {
+        // Arrange: config and database where earlier versions kept them
+        let root = tempfile::tempdir().unwrap();
+        let home = root.path().as_os_str().to_owned();
+        let get = |key: &str| (key == "HOME").then(|| home.clone());
+        let dirs = PlatformDirs::from_env(Platform::MacOs, get);
+        let legacy = PlatformDirs::legacy(get);
+        let paths = Paths {
+            overrides: PathOverrides::default(),
+            config_home: dirs.config,
+            data_home: dirs.data,
+            cache_home: dirs.cache,
+            legacy_config_home: legacy.config.clone(),
+            legacy_data_home: legacy.data.clone(),
+            cwd: root.path().to_path_buf(),
+        };
+        let legacy_config = legacy.config.unwrap().join("dtvmgr.toml");
+        std::fs::create_dir_all(legacy_config.parent().unwrap()).unwrap();
+        std::fs::write(&legacy_config, "[syoboi]\n").unwrap();
+        let legacy_data = legacy.data.unwrap();
+        let conn = dtvmgr_db::open_db(Some(&legacy_data)).unwrap();
+        conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (1, 'A');")
+            .unwrap();
+        drop(conn);
+
+        // Act
+        let moved_config = paths.migrate_legacy_config(None).unwrap();
+        let moved_db = paths.migrate_legacy_db(None).unwrap();
+        let again = (
+            paths.migrate_legacy_config(None).unwrap(),
+            paths.migrate_legacy_db(None).unwrap(),
+        );
+
+        // Assert: both files now live in ~/Library/Application Support
+        let support = root.path().join("Library/Application Support/dtvmgr");
+        let config_path = support.join("dtvmgr.toml");
+        assert_eq!(moved_config, Some(config_path.clone()));
+        assert_eq!(moved_db, Some(support.join("dtvmgr.db")));
+        assert_eq!(paths.config_path(None).unwrap(), config_path);
+        assert_eq!(std::fs::read_to_string(&config_path).unwrap(), "[syoboi]\n");
+        assert!(!legacy_config.exists());
+        assert!(!legacy_data.join("dtvmgr.db").exists());
+        let conn = paths.open_db(None, &DbOptions::default()).unwrap();
+        assert_eq!(dtvmgr_db::load_channels(&conn).unwrap().len(), 1);
+        assert_eq!(again, (None, None));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_migrate_legacy_config_skips_explicit_config() {
+        // Arrange
+        let root = tempfile::tempdir().unwrap();
+        let paths = Paths {
+            legacy_config_home: Some(root.path().join("legacy")),
+            ..paths_in(root.path(), PathOverrides::default())
+        };
+        let legacy_config = root.path().join("legacy").join("dtvmgr.toml");
+        std::fs::create_dir_all(legacy_config.parent().unwrap()).unwrap();
+        std::fs::write(&legacy_config, "").unwrap();
+
+        // Act
+        let moved = paths.migrate_legacy_config(Some(&legacy_config)).unwrap();
+
+        // Assert
+        assert!(moved.is_none());
+        assert!(legacy_config.exists());
+    }
 }
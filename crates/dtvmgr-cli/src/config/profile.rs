@@ -0,0 +1,143 @@
+//! Named configuration profiles (`[profile.<name>]`).
+//!
+//! A profile overrides the channel selection, data directory, and API
+//! options of the top-level config, so one `dtvmgr.toml` can serve several
+//! machines or tuner setups. The profile is chosen with the global
+//! `--profile` flag (or `DTVMGR_PROFILE`).
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::config::{AppConfig, TmdbConfig, read_toml_text};
+use crate::exit::ConfigError;
+
+/// Settings of one named profile. Unset values keep the top-level setting.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProfileConfig {
+    /// Data directory (database). Relative paths are resolved against the
+    /// directory of the config file.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    /// Selected channel IDs, replacing `[syoboi.channels] selected`.
+    #[serde(default)]
+    pub channels: Option<Vec<u32>>,
+    /// TMDB settings; set values replace those of `[tmdb]`.
+    #[serde(default)]
+    pub tmdb: TmdbConfig,
+    /// `EPGStation` base URL, replacing `[epgstation] base_url`.
+    #[serde(default)]
+    pub epgstation_base_url: Option<String>,
+}
+
+/// Profile selected at startup.
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Selects the profile applied by [`AppConfig::load`]. Later calls are
+/// ignored.
+pub fn set_active_profile(name: &str) {
+    let _ = ACTIVE_PROFILE.set(name.to_owned());
+}
+
+/// Returns the profile selected at startup.
+pub fn active_profile() -> Option<&'static str> {
+    ACTIVE_PROFILE.get().map(String::as_str)
+}
+
+/// Returns the data directory of profile `name` in the config at
+/// `config_path`, resolved against the config file directory.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be read or parsed, or has no
+/// profile `name`.
+pub fn profile_data_dir(config_path: &Path, name: &str) -> Result<Option<PathBuf>> {
+    let content = read_toml_text(config_path)
+        .with_context(|| format!("failed to read {}", config_path.display()))?;
+    let config: AppConfig = toml::from_str(&content)
+        .with_context(|| ConfigError(format!("failed to parse {}", config_path.display())))?;
+    let Some(profile) = config.profile.get(name) else {
+        return Err(ConfigError(format!(
+            "unknown profile {name} in {}",
+            config_path.display()
+        ))
+        .into());
+    };
+    Ok(profile.data_dir.as_ref().map(|dir| {
+        config_path
+            .parent()
+            .map_or_else(|| dir.clone(), |parent| parent.join(dir))
+    }))
+}
+
+/// Moves `current` back to `base`, recording it in the profile when the
+/// profile already sets it or it differs from `base`.
+pub(super) fn store_override<T: Clone + PartialEq>(
+    profile: &mut Option<T>,
+    current: &mut Option<T>,
+    base: Option<T>,
+) {
+    if profile.is_some() || *current != base {
+        profile.clone_from(current);
+    }
+    *current = base;
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_profile_data_dir_resolves_relative_to_config() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dtvmgr.toml");
+        std::fs::write(
+            &path,
+            "[profile.home]\ndata_dir = \"home\"\n\n[profile.vps]\nchannels = [1]\n",
+        )
+        .unwrap();
+
+        // Act
+        let home = profile_data_dir(&path, "home").unwrap();
+        let vps = profile_data_dir(&path, "vps").unwrap();
+        let unknown = profile_data_dir(&path, "other");
+
+        // Assert
+        assert_eq!(home, Some(dir.path().join("home")));
+        assert_eq!(vps, None);
+        assert!(unknown.unwrap_err().to_string().contains("unknown profile"));
+    }
+
+    #[test]
+    fn test_store_override_records_changed_values_only() {
+        // Arrange
+        let mut untouched_profile: Option<String> = None;
+        let mut untouched = Some(String::from("ja-JP"));
+        let mut changed_profile: Option<String> = None;
+        let mut changed = Some(String::from("en-US"));
+
+        // Act
+        store_override(
+            &mut untouched_profile,
+            &mut untouched,
+            Some(String::from("ja-JP")),
+        );
+        store_override(
+            &mut changed_profile,
+            &mut changed,
+            Some(String::from("ja-JP")),
+        );
+
+        // Assert
+        assert_eq!(untouched_profile, None);
+        assert_eq!(changed_profile.as_deref(), Some("en-US"));
+        assert_eq!(changed.as_deref(), Some("ja-JP"));
+    }
+}
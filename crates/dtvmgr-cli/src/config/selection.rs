@@ -0,0 +1,371 @@
+//! Export and import of the channel selection.
+//!
+//! The exported document lists the selected channels with their names and
+//! groups so it can be reviewed and shared between machines. Importing
+//! validates the channel IDs against the channel list of the target
+//! machine before they are merged into (or replace) its selection.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use dtvmgr_db::channels::{CachedChannel, CachedChannelGroup};
+use serde::{Deserialize, Serialize};
+
+/// Current version of the channel selection document.
+pub const SELECTION_FORMAT_VERSION: u32 = 1;
+
+/// Exported channel selection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SelectionDocument {
+    /// Format version ([`SELECTION_FORMAT_VERSION`]).
+    pub version: u32,
+    /// Selected channels ordered by channel ID.
+    #[serde(default)]
+    pub channels: Vec<SelectedChannel>,
+}
+
+/// One selected channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::struct_field_names)]
+pub struct SelectedChannel {
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Channel name, checked against the channel list on import.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ch_name: String,
+    /// Channel group ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ch_gid: Option<u32>,
+    /// Channel group name, for readability only.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ch_group_name: String,
+}
+
+/// File format of a selection document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum SelectionFormat {
+    /// TOML (`[[channels]]` tables).
+    Toml,
+    /// Pretty-printed JSON.
+    Json,
+}
+
+impl SelectionFormat {
+    /// Picks the format from the file extension: `.json` is JSON,
+    /// anything else TOML.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        {
+            Self::Json
+        } else {
+            Self::Toml
+        }
+    }
+}
+
+impl SelectionDocument {
+    /// Builds the document for `selected`, taking names and groups from the
+    /// cached channel list. Channels missing from the cache are exported
+    /// with their ID only.
+    #[must_use]
+    pub fn build(
+        selected: &[u32],
+        channels: &[CachedChannel],
+        groups: &[CachedChannelGroup],
+    ) -> Self {
+        let channels: HashMap<u32, &CachedChannel> =
+            channels.iter().map(|c| (c.ch_id, c)).collect();
+        let groups: HashMap<u32, &str> = groups
+            .iter()
+            .map(|g| (g.ch_gid, g.ch_group_name.as_str()))
+            .collect();
+        let ids: BTreeSet<u32> = selected.iter().copied().collect();
+        let channels = ids
+            .into_iter()
+            .map(|ch_id| {
+                let cached = channels.get(&ch_id);
+                let ch_gid = cached.and_then(|c| c.ch_gid);
+                SelectedChannel {
+                    ch_id,
+                    ch_name: cached.map(|c| c.ch_name.clone()).unwrap_or_default(),
+                    ch_gid,
+                    ch_group_name: ch_gid
+                        .and_then(|gid| groups.get(&gid))
+                        .map(|name| (*name).to_owned())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        Self {
+            version: SELECTION_FORMAT_VERSION,
+            channels,
+        }
+    }
+
+    /// Serializes the document in `format`, ending with a newline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_string(&self, format: SelectionFormat) -> Result<String> {
+        match format {
+            SelectionFormat::Toml => {
+                toml::to_string_pretty(self).context("failed to serialize channel selection")
+            }
+            SelectionFormat::Json => {
+                let mut json = serde_json::to_string_pretty(self)
+                    .context("failed to serialize channel selection")?;
+                json.push('\n');
+                Ok(json)
+            }
+        }
+    }
+
+    /// Parses a document in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content is malformed or has an unsupported
+    /// format version.
+    pub fn parse(content: &str, format: SelectionFormat) -> Result<Self> {
+        let doc: Self = match format {
+            SelectionFormat::Toml => toml::from_str(content)?,
+            SelectionFormat::Json => serde_json::from_str(content)?,
+        };
+        if doc.version != SELECTION_FORMAT_VERSION {
+            bail!(
+                "unsupported channel selection format version {} (expected {SELECTION_FORMAT_VERSION})",
+                doc.version
+            );
+        }
+        Ok(doc)
+    }
+}
+
+/// How imported channels are combined with the current selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Add the imported channels to the current selection.
+    Merge,
+    /// Use only the imported channels.
+    Replace,
+}
+
+/// Result of validating a document against the channel list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SelectionImport {
+    /// New selection, ordered by channel ID.
+    pub selected: Vec<u32>,
+    /// Imported channel IDs not in the current selection.
+    pub added: Vec<u32>,
+    /// Current channel IDs dropped by [`ImportMode::Replace`].
+    pub removed: Vec<u32>,
+    /// Imported channel IDs missing from the channel list (skipped).
+    pub unknown: Vec<u32>,
+    /// Imported channels whose name differs from the channel list, as
+    /// `(ch_id, name in the file, name in the channel list)`.
+    pub renamed: Vec<(u32, String, String)>,
+}
+
+impl SelectionImport {
+    /// Whether the new selection differs from the current one.
+    #[must_use]
+    pub const fn changed(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+/// Validates `doc` against `channels` and computes the new selection.
+///
+/// Channels missing from `channels` are skipped; channels whose name
+/// changed are kept and reported.
+#[must_use]
+pub fn plan_import(
+    doc: &SelectionDocument,
+    current: &[u32],
+    channels: &[CachedChannel],
+    mode: ImportMode,
+) -> SelectionImport {
+    let known: HashMap<u32, &str> = channels
+        .iter()
+        .map(|c| (c.ch_id, c.ch_name.as_str()))
+        .collect();
+    let current: BTreeSet<u32> = current.iter().copied().collect();
+    let mut imported = BTreeSet::new();
+    let mut plan = SelectionImport::default();
+    for channel in &doc.channels {
+        let Some(name) = known.get(&channel.ch_id) else {
+            plan.unknown.push(channel.ch_id);
+            continue;
+        };
+        if !channel.ch_name.is_empty() && channel.ch_name != *name {
+            plan.renamed
+                .push((channel.ch_id, channel.ch_name.clone(), (*name).to_owned()));
+        }
+        imported.insert(channel.ch_id);
+    }
+
+    let selected: BTreeSet<u32> = match mode {
+        ImportMode::Merge => current.union(&imported).copied().collect(),
+        ImportMode::Replace => imported,
+    };
+    plan.added = selected.difference(&current).copied().collect();
+    plan.removed = current.difference(&selected).copied().collect();
+    plan.selected = selected.into_iter().collect();
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn channel(id: u32, gid: Option<u32>, name: &str) -> CachedChannel {
+        CachedChannel {
+            ch_id: id,
+            ch_gid: gid,
+            ch_name: name.to_owned(),
+            ch_url: None,
+            ch_iepg_name: None,
+            ch_comment: None,
+            logo_url: None,
+        }
+    }
+
+    fn doc(channels: &[(u32, &str)]) -> SelectionDocument {
+        SelectionDocument {
+            version: SELECTION_FORMAT_VERSION,
+            channels: channels
+                .iter()
+                .map(|(ch_id, name)| SelectedChannel {
+                    ch_id: *ch_id,
+                    ch_name: (*name).to_owned(),
+                    ch_gid: None,
+                    ch_group_name: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_build_adds_names_and_groups() {
+        // Arrange
+        let channels = [
+            channel(3, Some(1), "フジテレビ"),
+            channel(1, None, "NHK総合"),
+        ];
+        let groups = [CachedChannelGroup {
+            ch_gid: 1,
+            ch_group_name: String::from("テレビ 関東"),
+            ch_group_order: 100,
+        }];
+
+        // Act
+        let doc = SelectionDocument::build(&[3, 1, 99], &channels, &groups);
+
+        // Assert
+        let ids: Vec<u32> = doc.channels.iter().map(|c| c.ch_id).collect();
+        assert_eq!(ids, vec![1, 3, 99]);
+        assert_eq!(doc.channels[1].ch_name, "フジテレビ");
+        assert_eq!(doc.channels[1].ch_group_name, "テレビ 関東");
+        assert!(doc.channels[2].ch_name.is_empty());
+    }
+
+    #[test]
+    fn test_document_round_trips_in_both_formats() {
+        // Arrange
+        let doc = SelectionDocument::build(
+            &[1, 3],
+            &[
+                channel(1, None, "NHK総合"),
+                channel(3, Some(1), "フジテレビ"),
+            ],
+            &[],
+        );
+
+        for format in [SelectionFormat::Toml, SelectionFormat::Json] {
+            // Act
+            let content = doc.to_string(format).unwrap();
+            let parsed = SelectionDocument::parse(&content, format).unwrap();
+
+            // Assert
+            assert_eq!(parsed, doc);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_version() {
+        // Arrange & Act
+        let result = SelectionDocument::parse("version = 2\n", SelectionFormat::Toml);
+
+        // Assert
+        assert!(result.unwrap_err().to_string().contains("version 2"));
+    }
+
+    #[test]
+    fn test_plan_import_merge_skips_unknown_and_reports_renames() {
+        // Arrange
+        let channels = [
+            channel(1, None, "NHK総合"),
+            channel(3, None, "フジテレビ"),
+            channel(4, None, "日本テレビ"),
+        ];
+        let doc = doc(&[(3, "フジ"), (4, "日本テレビ"), (99, "Gone")]);
+
+        // Act
+        let plan = plan_import(&doc, &[1, 3], &channels, ImportMode::Merge);
+
+        // Assert
+        assert_eq!(plan.selected, vec![1, 3, 4]);
+        assert_eq!(plan.added, vec![4]);
+        assert!(plan.removed.is_empty());
+        assert_eq!(plan.unknown, vec![99]);
+        assert_eq!(
+            plan.renamed,
+            vec![(3, String::from("フジ"), String::from("フジテレビ"))]
+        );
+        assert!(plan.changed());
+    }
+
+    #[test]
+    fn test_plan_import_replace_drops_other_channels() {
+        // Arrange
+        let channels = [channel(1, None, "NHK総合"), channel(3, None, "フジテレビ")];
+        let doc = doc(&[(3, "")]);
+
+        // Act
+        let plan = plan_import(&doc, &[1, 3], &channels, ImportMode::Replace);
+
+        // Assert
+        assert_eq!(plan.selected, vec![3]);
+        assert!(plan.added.is_empty());
+        assert_eq!(plan.removed, vec![1]);
+        assert!(plan.renamed.is_empty());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        // Arrange & Act & Assert
+        assert_eq!(
+            SelectionFormat::from_path(Path::new("channels.JSON")),
+            SelectionFormat::Json
+        );
+        assert_eq!(
+            SelectionFormat::from_path(Path::new("channels.toml")),
+            SelectionFormat::Toml
+        );
+        assert_eq!(
+            SelectionFormat::from_path(Path::new("channels")),
+            SelectionFormat::Toml
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Exit codes and the `--quiet` summary line.
+//!
+//! Cron wrappers tell failure modes apart by the process exit code instead
+//! of parsing log output:
+//!
+//! | Code | Status          | Meaning                                            |
+//! |------|-----------------|----------------------------------------------------|
+//! | 0    | `success`       | The command finished                               |
+//! | 1    | `failure`       | Any other error                                    |
+//! | 2    | —               | Invalid arguments (reported by clap)               |
+//! | 3    | `config_error`  | The config file is invalid or a setting is missing |
+//! | 4    | `network_error` | An API request could not be completed              |
+//! | 5    | `rate_limited`  | An API kept answering HTTP 429                     |
+//! | 6    | `partial_sync`  | A sync stopped early; the rest was queued as a job |
+//! | 7    | `nothing_to_do` | There was nothing to sync or look up               |
+//!
+//! Errors are classified from the `anyhow` chain; `partial_sync` and
+//! `nothing_to_do` are not errors and are recorded with [`mark`] by the
+//! command that detects them. Marks belong to the enclosing [`scoped`]
+//! future, so a long-running command can run each unit of work in its own
+//! scope and keep their outcomes out of its exit status.
+
+use core::cell::RefCell;
+use std::io::Write as _;
+use std::process::ExitCode;
+
+use dtvmgr_api::error::ApiError;
+use serde::Serialize;
+
+/// Outcome of a command, mapped to the process exit code.
+///
+/// Variants are ordered by precedence: when several outcomes are marked,
+/// the greatest one is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitStatus {
+    /// The command finished.
+    Success,
+    /// There was nothing to sync or look up.
+    NothingToDo,
+    /// A sync stopped early and queued the remainder as a job.
+    PartialSync,
+    /// Any other error.
+    Failure,
+    /// An API request could not be completed (transport error, 5xx, open
+    /// circuit breaker).
+    NetworkError,
+    /// An API kept answering HTTP 429.
+    RateLimited,
+    /// The config file is invalid or a required setting is missing.
+    ConfigError,
+}
+
+impl ExitStatus {
+    /// Returns the process exit code of this status.
+    #[must_use]
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Success => 0,
+            Self::Failure => 1,
+            Self::ConfigError => 3,
+            Self::NetworkError => 4,
+            Self::RateLimited => 5,
+            Self::PartialSync => 6,
+            Self::NothingToDo => 7,
+        }
+    }
+
+    /// Classifies a command error by the causes in its chain.
+    #[must_use]
+    pub fn classify(err: &anyhow::Error) -> Self {
+        if err.downcast_ref::<ConfigError>().is_some() {
+            return Self::ConfigError;
+        }
+        err.chain()
+            .filter_map(|cause| cause.downcast_ref::<ApiError>())
+            .find_map(|api| match api {
+                ApiError::RateLimited { .. } => Some(Self::RateLimited),
+                ApiError::Transport { .. } | ApiError::CircuitOpen { .. } => {
+                    Some(Self::NetworkError)
+                }
+                ApiError::HttpStatus { status, .. } if *status >= 500 => Some(Self::NetworkError),
+                _ => None,
+            })
+            .unwrap_or(Self::Failure)
+    }
+}
+
+/// Context marking an error as a configuration problem (exit code 3).
+///
+/// Attach it with `.context(ConfigError(..))` where the config is parsed
+/// or validated; its message is shown like any other context.
+#[derive(Debug)]
+pub struct ConfigError(pub String);
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl core::error::Error for ConfigError {}
+
+/// Non-error outcome recorded with [`mark`], with its message.
+pub type Marked = Option<(ExitStatus, String)>;
+
+tokio::task_local! {
+    /// Outcome marked inside the innermost [`scoped`] future.
+    static MARKED: RefCell<Marked>;
+}
+
+/// Records a non-error outcome (`PartialSync`, `NothingToDo`) in the
+/// innermost [`scoped`] future. A later mark replaces an earlier one only
+/// if it takes precedence; outside a scope the mark is dropped.
+pub fn mark(status: ExitStatus, message: impl Into<String>) {
+    let _ = MARKED.try_with(|marked| {
+        let mut marked = marked.borrow_mut();
+        if marked.as_ref().is_none_or(|(current, _)| status > *current) {
+            *marked = Some((status, message.into()));
+        }
+    });
+}
+
+/// Runs `fut` in its own mark scope and returns its output with the
+/// outcome marked inside it. Marks in a nested scope stay there.
+pub async fn scoped<F: Future>(fut: F) -> (F::Output, Marked) {
+    MARKED
+        .scope(RefCell::new(None), async {
+            let output = fut.await;
+            (output, MARKED.with(RefCell::take))
+        })
+        .await
+}
+
+/// Final line printed by `--quiet`.
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    status: ExitStatus,
+    exit_code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+}
+
+/// Turns the command result and its [`scoped`] mark into the process exit
+/// code.
+///
+/// Errors are printed to stderr as `Error: ...`, the same way `main`
+/// returning `Err` would. With `quiet`, a single JSON summary line
+/// (`{"status":..,"exit_code":..,"message":..}`) is written to stdout
+/// instead.
+pub fn finish(result: &anyhow::Result<()>, marked: Marked, quiet: bool) -> ExitCode {
+    let (status, message) = match result {
+        Ok(()) => marked.map_or((ExitStatus::Success, None), |(status, message)| {
+            (status, Some(message))
+        }),
+        Err(e) => (ExitStatus::classify(e), Some(format!("{e:#}"))),
+    };
+    if quiet {
+        let summary = Summary {
+            status,
+            exit_code: status.code(),
+            message: message.as_deref(),
+        };
+        if let Ok(line) = serde_json::to_string(&summary) {
+            let _ = writeln!(std::io::stdout().lock(), "{line}");
+        }
+    } else if let Err(e) = result {
+        let _ = writeln!(std::io::stderr().lock(), "Error: {e:?}");
+    }
+    ExitCode::from(status.code())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context as _;
+
+    use super::*;
+
+    #[test]
+    fn test_classify_maps_error_causes_to_statuses() {
+        // Arrange
+        let config: anyhow::Error = anyhow::anyhow!("expected `=`")
+            .context(ConfigError(String::from("failed to parse dtvmgr.toml")))
+            .context("failed to load config");
+        let rate_limited = Err::<(), _>(ApiError::RateLimited {
+            message: String::from("429"),
+            retry_after: None,
+        })
+        .context("failed to fetch programs")
+        .unwrap_err();
+        let network = anyhow::Error::new(ApiError::Transport {
+            kind: "timeout",
+            target: String::from("ProgLookup"),
+        });
+        let other = anyhow::anyhow!("disk full");
+
+        // Act & Assert
+        assert_eq!(ExitStatus::classify(&config), ExitStatus::ConfigError);
+        assert_eq!(ExitStatus::classify(&rate_limited), ExitStatus::RateLimited);
+        assert_eq!(ExitStatus::classify(&network), ExitStatus::NetworkError);
+        assert_eq!(ExitStatus::classify(&other), ExitStatus::Failure);
+    }
+
+    #[test]
+    fn test_exit_status_codes_are_distinct() {
+        // Arrange
+        let statuses = [
+            ExitStatus::Success,
+            ExitStatus::NothingToDo,
+            ExitStatus::PartialSync,
+            ExitStatus::Failure,
+            ExitStatus::NetworkError,
+            ExitStatus::RateLimited,
+            ExitStatus::ConfigError,
+        ];
+
+        // Act
+        let codes: std::collections::BTreeSet<u8> = statuses.iter().map(|s| s.code()).collect();
+
+        // Assert
+        assert_eq!(codes.len(), statuses.len());
+        assert!(!codes.contains(&2), "2 is reserved for clap usage errors");
+    }
+
+    #[tokio::test]
+    async fn test_scoped_keeps_marks_of_nested_scopes() {
+        // Arrange & Act
+        let (((), inner), outer) = scoped(async {
+            let inner = scoped(async { mark(ExitStatus::NothingToDo, "inner") }).await;
+            mark(ExitStatus::PartialSync, "outer");
+            mark(ExitStatus::NothingToDo, "lower precedence");
+            inner
+        })
+        .await;
+
+        // Assert
+        assert_eq!(
+            inner,
+            Some((ExitStatus::NothingToDo, String::from("inner")))
+        );
+        assert_eq!(
+            outer,
+            Some((ExitStatus::PartialSync, String::from("outer")))
+        );
+    }
+}
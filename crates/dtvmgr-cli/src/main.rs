@@ -72,48 +72,124 @@ mod cli_metrics {
 
 /// Application configuration (TOML).
 mod config;
+/// Exit codes and the `--quiet` summary line.
+mod exit;
+/// Output format selection for query commands.
+mod output;
+/// Terminal progress bars for long syncs.
+mod progress;
 
 use std::cell::Cell;
 use std::collections::{BTreeSet, HashSet};
 use std::io::BufRead;
 use std::mem;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, OnceLock};
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use chrono::Utc;
+use chrono_tz::Tz;
 use clap::{CommandFactory, Parser, Subcommand};
 use tracing::{Instrument as _, instrument};
 use tracing_subscriber::filter::EnvFilter;
 #[cfg(not(feature = "otel"))]
 use tracing_subscriber::fmt;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 #[cfg(feature = "otel")]
 use tracing_subscriber::layer::SubscriberExt;
 #[cfg(feature = "otel")]
 use tracing_subscriber::util::SubscriberInitExt;
 
-use crate::config::{AppConfig, load_or_fetch, resolve_config_path, resolve_data_dir};
+use crate::config::selection::{ImportMode, SelectionDocument, SelectionFormat, plan_import};
+use crate::config::{
+    AppConfig, ChannelsConfig, DEFAULT_TMDB_LANGUAGE, DEFAULT_TMDB_REGION, EmailConfig,
+    NotifyConfig, Paths, RateLimitConfig, load_or_fetch, read_toml_text, resolve_cache_dir,
+    resolve_config_path, resolve_data_dir, set_path_overrides,
+};
+use crate::exit::{ConfigError, ExitStatus};
+use crate::output::{OutputFormat, write_json};
+use crate::progress::TerminalProgress;
+use dtvmgr_api::audit::AuditLog;
+use dtvmgr_api::clock::{Clock, SystemClock};
 use dtvmgr_api::epgstation::{
     EncodeRequest, EpgStationClient, LocalEpgStationApi, RecordedItem, RecordedParams,
     RecordedResponse,
 };
+use dtvmgr_api::http::HttpOptions;
+use dtvmgr_api::replay::Cassette;
 use dtvmgr_api::syoboi::{
-    LocalSyoboiApi, ProgLookupParams, SyoboiClient, SyoboiProgram, SyoboiTitle,
-    lookup_all_programs, resolve_time_range,
+    DEFAULT_CAL_CHK_DAYS, DEFAULT_RANGE_DAYS, LocalSyoboiApi, NoProgress, ProgLookupParams,
+    ProgramFlags, SYOBOI_TIMEZONE, SyncProgress, SyoboiChannel, SyoboiClient, SyoboiClientBuilder,
+    SyoboiCredentials, SyoboiProgram, TidSelector, TimeRange, TitleCategory, TitleLookupParams,
+    checked_tids, lookup_all_programs, month_ranges, resolve_season_range, resolve_time_range_with,
+    to_naive_datetime_since, to_naive_datetime_until,
 };
 use dtvmgr_api::tmdb::{
-    LocalTmdbApi, SearchMultiParams, TmdbClient, TmdbMediaType, TmdbMultiSearchResult,
+    LocalTmdbApi, SearchMultiParams, TmdbClient, TmdbExternalSource, TmdbFindResponse, TmdbImage,
+    TmdbImagesResponse, TmdbMediaType, TmdbMovieSearchResult, TmdbMultiSearchResult,
+    TmdbTvSearchResult, TmdbWatchProvider, search_movie_all, search_tv_all,
+};
+use dtvmgr_core::automatch::{
+    AutoMatch, AutoMatchOptions, DEFAULT_THRESHOLD, DEFAULT_YEAR_TOLERANCE, Suggestion,
+    SuggestionsFile, auto_match_title,
+};
+use dtvmgr_core::budget::RetryBudget;
+use dtvmgr_core::channel_map::resolve_channel_map;
+use dtvmgr_core::conflicts::find_conflicts;
+use dtvmgr_core::coverage::{
+    MappedEpisodes, SeasonCoverage, fetch_mapped_episodes, title_coverage,
+};
+use dtvmgr_core::export::nfo::{
+    DEFAULT_EPISODE_FILE, DEFAULT_SEASON_DIR, DEFAULT_SHOW_DIR, NfoExporter,
 };
+use dtvmgr_core::export::{EXPORTERS, ExportData, Exporter, find_exporter};
+use dtvmgr_core::filter::FilterExpr;
+use dtvmgr_core::gaps::{TitleGaps, title_gaps};
+use dtvmgr_core::jobs::{Job, JobKind, JobQueue, JobState};
+use dtvmgr_core::listing::{FlagFilter, ProgramQuery, ProgramSort};
+use dtvmgr_core::matcher::{match_program, match_title};
+use dtvmgr_core::normalize::{normalize_title, search_titles_normalized};
+use dtvmgr_core::notify::{
+    Digest, ProgramChange, SmtpTls, build_digest, build_payload, diff_programs,
+};
+use dtvmgr_core::rules::{Rule, evaluate_rules, validate_rules};
+use dtvmgr_core::seasons::{detect_season_ranges, validate_season_ranges};
+use dtvmgr_core::sync::{
+    SyncEngine, TITLE_SYNC_FIELDS, TitleSync, to_cached_program, to_cached_title,
+    validate_program_fields, validate_title_fields,
+};
+use dtvmgr_db::bench::bench_upserts;
 use dtvmgr_db::channels::{CachedChannel, CachedChannelGroup};
 use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::recorded::{CachedRecordedItem, CachedVideoFile};
 use dtvmgr_db::titles::CachedTitle;
+use dtvmgr_db::watchlist::WatchlistEntry;
 use dtvmgr_db::{
-    delete_programs_by_tids_not_in, delete_titles_by_cat_not_in, load_channels, load_programs,
-    load_titles, load_titles_by_tids, open_db, update_tmdb_last_updated, update_tmdb_mapping,
-    update_tmdb_search_result, upsert_channel_groups, upsert_channels, upsert_programs,
-    upsert_titles,
+    ChannelAlias, ChannelProgramStats, ChannelUsage, CreditKind, DEFAULT_POOL_SIZE, DbOptions,
+    DbPool, DbSummary, ExternalIds, MappingsDocument, PooledConnection, ProgramChangeKind,
+    ProgramFilter, ProgramHistoryEntry, SeasonRange, SyncRunRecord, TitleCredit, TitleImage,
+    TitleLink, TitleLocalization, add_program_tag, create_snapshot, delete_channel_aliases,
+    delete_program_note, delete_snapshot, delete_watchlist_entries, diff_snapshots,
+    export_mappings, finish_sync_run, import_mappings, import_seed, load_category_counts,
+    load_channel_aliases, load_channel_groups, load_channel_program_stats, load_channel_usage,
+    load_channels, load_channels_refreshed_at, load_db_summary, load_episodes, load_followed_tids,
+    load_program, load_program_annotations, load_program_history, load_programs,
+    load_programs_by_tids, load_programs_filtered, load_recorded_items, load_season_ranges,
+    load_snapshots, load_sync_cursor, load_sync_run, load_sync_runs, load_title_aliases,
+    load_title_credits, load_title_images, load_title_links, load_title_localizations, load_titles,
+    load_titles_by_tids, load_video_file_hashes, load_watchlist, mark_channels_refreshed,
+    merge_titles, open_db_with_options, prune_programs, recompute_program_columns,
+    remove_program_tag, replace_season_ranges, resolve_db_path, run_sql, save_sync_cursor,
+    save_sync_params, set_program_note, set_titles_followed, start_sync_run, update_channel_logo,
+    update_external_ids, update_tmdb_episode_group, update_tmdb_episode_mapping,
+    update_tmdb_last_updated, update_tmdb_mapping, update_tmdb_movie_mapping,
+    update_tmdb_movie_search_result, update_tmdb_search_result, upsert_channel_aliases,
+    upsert_channel_groups, upsert_channels, upsert_title_image, upsert_title_localization,
+    upsert_video_file_hash, upsert_watchlist_entries, vacuum,
 };
 use dtvmgr_jlse::channel::{detect_channel, load_channels as load_jlse_channels};
 use dtvmgr_jlse::param::{detect_param, load_params};
@@ -128,17 +204,65 @@ use dtvmgr_tui::encode_selector::state::{
     StorageMessage, StorageStatsSnapshot, SubmissionProgress, SyncMessage,
 };
 use dtvmgr_tui::run_channel_selector;
-use dtvmgr_tui::state::{ChannelEntry, ChannelGroup};
+use dtvmgr_tui::state::{ChannelEntry, ChannelGroup, ChannelUpdate};
+use dtvmgr_tui::title_viewer::state::ProgramColumn;
+use dtvmgr_tui::title_viewer::tmdb_picker::{
+    TmdbPickerChannels, TmdbPickerMessage, TmdbPickerRequest, TmdbSeasonCandidate,
+    TmdbSeriesCandidate,
+};
 
 /// CLI argument parser.
 #[derive(Parser)]
 #[command(about, version)]
 struct Cli {
     /// Path to config file (relative or absolute).
-    /// Data directory defaults to the same directory as the config file.
-    #[arg(long, global = true)]
+    /// Data directory defaults to the same directory as the config file
+    /// unless `--data-dir` or `--dir` is given.
+    #[arg(long, global = true, env = "DTVMGR_CONFIG")]
     config: Option<PathBuf>,
 
+    /// Data directory (database), independent of the config file location.
+    #[arg(long, global = true, value_name = "DIR", env = "DTVMGR_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Append one JSON line per Syoboi/TMDB API request to this file.
+    #[arg(long, global = true, value_name = "FILE")]
+    audit_log: Option<PathBuf>,
+
+    /// Save every Syoboi/TMDB API response under this directory.
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer Syoboi/TMDB API requests from responses saved with `--record`
+    /// instead of the network.
+    #[arg(long, global = true, value_name = "DIR")]
+    replay: Option<PathBuf>,
+
+    /// Keep config, database, and cache under this single directory,
+    /// overriding the platform-native locations.
+    #[arg(long, global = true, value_name = "DIR", env = "DTVMGR_DIR")]
+    dir: Option<PathBuf>,
+
+    /// Apply the `[profile.<NAME>]` section of the config file (channel
+    /// selection, data directory, and API options).
+    #[arg(long, global = true, value_name = "NAME", env = "DTVMGR_PROFILE")]
+    profile: Option<String>,
+
+    /// Open the database read-only (e.g. for browsing while a daemon syncs).
+    /// Commands that write fail with `attempt to write a readonly database`.
+    #[arg(long, global = true, env = "DTVMGR_DB_READONLY")]
+    db_readonly: bool,
+
+    /// Result format of `syoboi prog/titles`, `tmdb` queries, and `db stats`.
+    /// `json` writes one JSON document to stdout and sends logs to stderr.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    /// Print no logs; write a single JSON summary line
+    /// (`{"status":..,"exit_code":..,"message":..}`) to stdout when done.
+    #[arg(long, short, global = true, conflicts_with = "output")]
+    quiet: bool,
+
     /// Subcommand to run.
     #[command(subcommand)]
     command: Commands,
@@ -157,12 +281,652 @@ enum Commands {
     Jlse(JlseCommand),
     /// `EPGStation` operations.
     Epgstation(EpgstationCommand),
+    /// Search cached titles (and optionally TMDB) in one ranked list.
+    Search(SearchArgs),
+    /// Manage watched titles and their change notifications.
+    Watchlist(WatchlistCommand),
+    /// Manage followed titles (restricts `db sync --followed-only`).
+    Titles(TitleFollowCommand),
+    /// Recording library checks.
+    Library(LibraryCommand),
+    /// Reports over cached data.
+    Report(ReportCommand),
+    /// Notifications for followed titles.
+    Notify(NotifyCommand),
+    /// Inspect and control background jobs.
+    Jobs(JobsCommand),
+    /// Evaluate recording rules (`[[rules]]`) against cached programs.
+    Rules(RulesCommand),
+    /// Run incremental syncs periodically until interrupted.
+    Daemon(DaemonArgs),
+    /// Export cached data for media servers.
+    Export(ExportCommand),
+    /// Serve the local cache as a read-only JSON REST API.
+    Serve(ServeArgs),
+    /// Map Syoboi channels to external tuner identifiers.
+    Channels(ChannelAliasesCommand),
     /// Initialize config file with default template.
     Init,
+    /// Print resolved config, data, database, and cache locations.
+    Paths,
+    /// Validate the config file.
+    Config(ConfigCommand),
     /// Generate shell completion script.
     Completion(CompletionCommand),
 }
 
+/// Arguments for the `config` subcommand.
+#[derive(clap::Args)]
+struct ConfigCommand {
+    /// Config subcommand to run.
+    #[command(subcommand)]
+    command: ConfigSubcommands,
+}
+
+/// Available config subcommands.
+#[derive(Subcommand)]
+enum ConfigSubcommands {
+    /// Print resolved paths and report unknown keys, unknown channel IDs,
+    /// invalid patterns, and a missing TMDB token. Fails on errors.
+    Check,
+}
+
+/// Arguments for the `search` subcommand.
+#[derive(clap::Args)]
+struct SearchArgs {
+    /// Search query (title, reading, English title, keyword, or TMDB name).
+    query: String,
+    /// Also query TMDB `search/multi` and merge the results.
+    #[arg(long, default_value_t = false)]
+    tmdb: bool,
+    /// Maximum number of results per source.
+    #[arg(long, default_value_t = 20)]
+    limit: u32,
+    /// TMDB response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
+
+/// Arguments for the `watchlist` subcommand.
+#[derive(clap::Args)]
+struct WatchlistCommand {
+    /// Watchlist subcommand to run.
+    #[command(subcommand)]
+    command: WatchlistSubcommands,
+}
+
+/// Available watchlist subcommands.
+#[derive(Subcommand)]
+enum WatchlistSubcommands {
+    /// Add titles or update their notification preferences.
+    Add(WatchlistAddArgs),
+    /// Remove titles from the watchlist.
+    Remove(WatchlistRemoveArgs),
+    /// List watched titles and their notification preferences.
+    List,
+}
+
+/// Program changes that can trigger a watchlist notification during `db sync`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum WatchEventKind {
+    /// A program not seen before.
+    New,
+    /// An existing program's start or end time moved.
+    TimeChange,
+    /// A program flagged as the final episode.
+    Finale,
+}
+
+/// Arguments for `watchlist add`.
+#[derive(clap::Args)]
+struct WatchlistAddArgs {
+    /// Comma-separated title IDs.
+    #[arg(long, required = true, value_delimiter = ',')]
+    tids: Vec<u32>,
+    /// Comma-separated events to notify on (replaces existing preferences).
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "new")]
+    notify: Vec<WatchEventKind>,
+}
+
+/// Arguments for `watchlist remove`.
+#[derive(clap::Args)]
+struct WatchlistRemoveArgs {
+    /// Comma-separated title IDs.
+    #[arg(long, required = true, value_delimiter = ',')]
+    tids: Vec<u32>,
+}
+
+/// Arguments for the top-level `titles` subcommand.
+#[derive(clap::Args)]
+struct TitleFollowCommand {
+    /// Titles subcommand to run.
+    #[command(subcommand)]
+    command: TitleFollowSubcommands,
+}
+
+/// Available top-level `titles` subcommands.
+#[derive(Subcommand)]
+enum TitleFollowSubcommands {
+    /// Mark titles as followed.
+    Follow(TitleFollowArgs),
+    /// Clear the followed mark of titles.
+    Unfollow(TitleFollowArgs),
+    /// List followed titles.
+    ListFollowed,
+    /// Follow the titles on the Syoboi check list of `[syoboi.account] user`.
+    ImportSyoboi(TitleImportSyoboiArgs),
+    /// Detect cross-season titles and map episode count ranges to TMDB seasons.
+    SplitSeasons(TitleSplitSeasonsArgs),
+    /// Merge a split or re-created title into its new TID.
+    Merge(TitleMergeArgs),
+    /// List merged TIDs and the titles they were folded into.
+    Aliases,
+}
+
+/// Arguments for `titles merge`.
+#[derive(clap::Args)]
+struct TitleMergeArgs {
+    /// TID to merge away; later syncs fold its programs into `NEW_TID`.
+    old_tid: u32,
+    /// Cached title that keeps the programs and TMDB mapping.
+    new_tid: u32,
+}
+
+/// Arguments for `titles split-seasons`.
+#[derive(clap::Args)]
+struct TitleSplitSeasonsArgs {
+    /// Title to inspect or update (default: all cached titles).
+    #[arg(long)]
+    tid: Option<u32>,
+    /// Count range mapped to a TMDB season as `FIRST-LAST:SEASON` (`13-:2` is
+    /// open-ended). Repeat for each season; replaces the stored ranges.
+    #[arg(long = "range", requires = "tid", value_parser = parse_count_range)]
+    ranges: Vec<CountRange>,
+    /// Store the detected ranges.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["ranges", "clear"])]
+    apply: bool,
+    /// Remove the stored ranges of `--tid`.
+    #[arg(
+        long,
+        default_value_t = false,
+        requires = "tid",
+        conflicts_with = "ranges"
+    )]
+    clear: bool,
+}
+
+/// A `--range` value of `titles split-seasons`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CountRange {
+    /// First episode count.
+    first: u32,
+    /// Last episode count (`None` = open-ended).
+    last: Option<u32>,
+    /// TMDB season number.
+    season: u32,
+}
+
+/// Parses `FIRST-LAST:SEASON` or `FIRST-:SEASON`.
+fn parse_count_range(s: &str) -> Result<CountRange, String> {
+    let invalid = || format!("invalid range {s:?}; expected FIRST-LAST:SEASON (e.g. 13-24:2)");
+    let (counts, season) = s.split_once(':').ok_or_else(invalid)?;
+    let (first, last) = counts.split_once('-').ok_or_else(invalid)?;
+    let first = first.trim().parse().map_err(|_| invalid())?;
+    let last = match last.trim() {
+        "" => None,
+        last => Some(last.parse().map_err(|_| invalid())?),
+    };
+    let season = season.trim().parse().map_err(|_| invalid())?;
+    Ok(CountRange {
+        first,
+        last,
+        season,
+    })
+}
+
+/// Formats season ranges as `--range` values (e.g. `1-12:1,13-:2`).
+fn format_season_ranges(ranges: &[SeasonRange]) -> String {
+    ranges
+        .iter()
+        .map(|r| {
+            format!(
+                "{}-{}:{}",
+                r.first_count,
+                r.last_count.map_or_else(String::new, |l| l.to_string()),
+                r.season_number
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Arguments for `titles import-syoboi`.
+#[derive(clap::Args)]
+struct TitleImportSyoboiArgs {
+    /// Days of the check list to read, starting today.
+    #[arg(long, default_value_t = DEFAULT_CAL_CHK_DAYS)]
+    days: u32,
+    /// Show the titles that would be followed without changing the database.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+}
+
+/// Arguments for `titles follow` / `titles unfollow`.
+#[derive(clap::Args)]
+struct TitleFollowArgs {
+    /// Comma-separated title IDs.
+    #[arg(long, required = true, value_delimiter = ',')]
+    tids: Vec<u32>,
+}
+
+/// Arguments for the top-level `channels` subcommand.
+#[derive(clap::Args)]
+struct ChannelAliasesCommand {
+    /// Channels subcommand to run.
+    #[command(subcommand)]
+    command: ChannelAliasesSubcommands,
+}
+
+/// Available top-level `channels` subcommands.
+#[derive(Subcommand)]
+enum ChannelAliasesSubcommands {
+    /// Manage Mirakurun / `EPGStation` aliases of Syoboi channels.
+    Map(ChannelsMapCommand),
+    /// Download channel logos of mapped channels into the data directory.
+    FetchLogos(ChannelsFetchLogosArgs),
+}
+
+/// Arguments for `channels fetch-logos`.
+#[derive(clap::Args)]
+struct ChannelsFetchLogosArgs {
+    /// Mirakurun base URL (e.g. `http://mirakurun:40772`); logos are fetched
+    /// by Mirakurun service ID. Defaults to the configured `EPGStation` URL,
+    /// fetching by `EPGStation` channel ID.
+    #[arg(long)]
+    mirakurun: Option<String>,
+    /// Download logos again even if the file already exists.
+    #[arg(long)]
+    force: bool,
+}
+
+/// Arguments for `channels map`.
+#[derive(clap::Args)]
+struct ChannelsMapCommand {
+    /// Channel map subcommand to run.
+    #[command(subcommand)]
+    command: ChannelsMapSubcommands,
+}
+
+/// Available `channels map` subcommands.
+#[derive(Subcommand)]
+enum ChannelsMapSubcommands {
+    /// Import aliases from a mapping file or a Mirakurun / `EPGStation` endpoint.
+    Import(ChannelsMapImportArgs),
+    /// List stored aliases.
+    List,
+    /// Remove aliases of the given channels.
+    Remove(ChannelsMapRemoveArgs),
+}
+
+/// Arguments for `channels map import`.
+#[derive(clap::Args)]
+struct ChannelsMapImportArgs {
+    /// File path, `file://` URL, or `http(s)://` URL returning a JSON array
+    /// (e.g. `http://mirakurun:40772/api/services`).
+    source: String,
+}
+
+/// Arguments for `channels map remove`.
+#[derive(clap::Args)]
+struct ChannelsMapRemoveArgs {
+    /// Comma-separated Syoboi channel IDs.
+    #[arg(long, required = true, value_delimiter = ',')]
+    ch_ids: Vec<u32>,
+}
+
+/// Arguments for the `library` subcommand.
+#[derive(clap::Args)]
+struct LibraryCommand {
+    /// Library subcommand to run.
+    #[command(subcommand)]
+    command: LibrarySubcommands,
+}
+
+/// Available library subcommands.
+#[derive(Subcommand)]
+enum LibrarySubcommands {
+    /// Verify each mapped TMDB episode of a title has exactly one healthy recording.
+    Verify(LibraryVerifyArgs),
+}
+
+/// Arguments for `library verify`.
+#[derive(clap::Args)]
+struct LibraryVerifyArgs {
+    /// Title ID to verify.
+    #[arg(long)]
+    tid: u32,
+    /// Local directory holding the recording files. Checks files on disk
+    /// instead of the cached `EPGStation` existence state.
+    #[arg(long)]
+    root: Option<PathBuf>,
+    /// Compute SHA-256 of each file and compare with the stored hash
+    /// (the first run stores it). Requires `--root`.
+    #[arg(long, requires = "root")]
+    hash: bool,
+    /// Write the verification manifest (TSV) to this file.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+/// Arguments for the `report` subcommand.
+#[derive(clap::Args)]
+struct ReportCommand {
+    /// Report subcommand to run.
+    #[command(subcommand)]
+    command: ReportSubcommands,
+}
+
+/// Available report subcommands.
+#[derive(Subcommand)]
+enum ReportSubcommands {
+    /// Compare aired TMDB episodes of mapped titles with cached programs.
+    Coverage(ReportCoverageArgs),
+    /// Find missing and duplicated episode counts in cached programs.
+    Gaps(ReportGapsArgs),
+}
+
+/// Arguments for `report coverage`.
+#[derive(clap::Args)]
+struct ReportCoverageArgs {
+    /// Comma-separated title IDs. Defaults to the followed titles.
+    #[arg(long, value_delimiter = ',', conflicts_with = "all")]
+    tids: Option<Vec<u32>>,
+    /// Report every title with a TMDB series mapping.
+    #[arg(long)]
+    all: bool,
+    /// TMDB response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
+
+/// Arguments for `report gaps`.
+#[derive(clap::Args)]
+struct ReportGapsArgs {
+    /// Comma-separated title IDs. Defaults to the followed titles.
+    #[arg(long, value_delimiter = ',', conflicts_with = "all")]
+    tids: Option<Vec<u32>>,
+    /// Check every cached title.
+    #[arg(long)]
+    all: bool,
+}
+
+/// Arguments for the `notify` subcommand.
+#[derive(clap::Args)]
+struct NotifyCommand {
+    /// Notify subcommand to run.
+    #[command(subcommand)]
+    command: NotifySubcommands,
+}
+
+/// Available notify subcommands.
+#[derive(Subcommand)]
+enum NotifySubcommands {
+    /// Email the digest of a day's programs of followed titles.
+    Digest(NotifyDigestArgs),
+}
+
+/// Arguments for `notify digest`.
+#[derive(clap::Args)]
+struct NotifyDigestArgs {
+    /// Day to list (YYYY-MM-DD, JST). Defaults to today.
+    #[arg(long)]
+    date: Option<chrono::NaiveDate>,
+    /// Print the digest instead of sending it.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Arguments for the `jobs` subcommand.
+#[derive(clap::Args)]
+struct JobsCommand {
+    /// Jobs subcommand to run.
+    #[command(subcommand)]
+    command: JobsSubcommands,
+}
+
+/// Available jobs subcommands.
+#[derive(Subcommand)]
+enum JobsSubcommands {
+    /// List jobs with their state and retry status.
+    List(JobsListArgs),
+    /// Queue a new job.
+    Add(JobsAddArgs),
+    /// Cancel a queued or running job.
+    Cancel(JobIdArgs),
+    /// Run a queued, failed, or cancelled job immediately.
+    RunNow(JobIdArgs),
+}
+
+/// Arguments for `jobs list`.
+#[derive(clap::Args)]
+struct JobsListArgs {
+    /// Only show jobs in this state (queued, running, succeeded, failed, cancelled).
+    #[arg(long)]
+    state: Option<JobState>,
+}
+
+/// Arguments for `jobs add`.
+#[derive(clap::Args)]
+struct JobsAddArgs {
+    /// Job kind (sync, prefetch, prune, notify).
+    kind: JobKind,
+    /// Kind-specific payload (prune: retention days, default 90).
+    #[arg(long)]
+    payload: Option<String>,
+    /// Delay before the job becomes due, in seconds.
+    #[arg(long, default_value_t = 0)]
+    delay: u32,
+}
+
+/// Arguments for jobs subcommands that target a single job.
+#[derive(clap::Args)]
+struct JobIdArgs {
+    /// Job ID.
+    id: i64,
+}
+
+/// Arguments for the `rules` subcommand.
+#[derive(clap::Args)]
+struct RulesCommand {
+    /// Rules subcommand to run.
+    #[command(subcommand)]
+    command: RulesSubcommands,
+}
+
+/// Available rules subcommands.
+#[derive(Subcommand)]
+enum RulesSubcommands {
+    /// List programs matched by the configured rules.
+    Run(RulesRunArgs),
+}
+
+/// Arguments for `rules run`.
+#[derive(clap::Args)]
+struct RulesRunArgs {
+    /// Comma-separated rule names to evaluate (default: all rules).
+    #[arg(long, value_delimiter = ',')]
+    rules: Option<Vec<String>>,
+    /// Only programs starting at or after this time (default: now).
+    /// Formats: "2024-01-01T00:00:00", "2024-01-01 00:00:00", "2024-01-01".
+    #[arg(long)]
+    time_since: Option<String>,
+    /// Only programs starting at or before this time. Same formats as --time-since.
+    #[arg(long)]
+    time_until: Option<String>,
+    /// Also write the match list as JSON to this file.
+    #[arg(long)]
+    file: Option<PathBuf>,
+    /// Only evaluate programs matching this filter expression, e.g.
+    /// `tid=6309 AND ch IN (7,19) AND flag~new AND st_time>=2024-04-01`.
+    #[arg(long)]
+    filter: Option<FilterExpr>,
+
+    #[command(flatten)]
+    query: ProgramQueryArgs,
+}
+
+/// Arguments for the `daemon` subcommand.
+#[derive(clap::Args)]
+struct DaemonArgs {
+    /// Minutes between sync runs. Falls back to `[daemon] interval_mins`.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    interval_mins: Option<u64>,
+    /// Run a single cycle and exit.
+    #[arg(long, default_value_t = false)]
+    once: bool,
+    /// Also serve the REST API and Prometheus `/metrics` on this address.
+    #[arg(long)]
+    metrics_bind: Option<SocketAddr>,
+}
+
+/// Arguments for the `export` subcommand.
+#[derive(clap::Args)]
+struct ExportCommand {
+    /// Export format to write.
+    #[command(subcommand)]
+    command: ExportSubcommands,
+}
+
+/// Available `export` formats (see `export list-formats`).
+#[derive(Subcommand)]
+enum ExportSubcommands {
+    /// Write cached programs as an iCalendar (.ics) file.
+    Ics(ExportFileArgs),
+    /// Write cached programs and channels as an XMLTV guide.
+    Xmltv(ExportFileArgs),
+    /// Write Kodi/Jellyfin `tvshow.nfo` and episode NFO files.
+    Nfo(ExportNfoArgs),
+    /// Write cached programs as a CSV file.
+    Csv(ExportFileArgs),
+    /// Write cached programs as a JSON array.
+    Json(ExportFileArgs),
+    /// List the registered export formats.
+    ListFormats,
+}
+
+/// Program filters shared by every export format.
+#[derive(clap::Args)]
+struct ExportFilterArgs {
+    /// Comma-separated channel IDs. Falls back to config selected channels if omitted.
+    #[arg(long, value_delimiter = ',')]
+    ch_ids: Option<Vec<u32>>,
+    /// Comma-separated TIDs to restrict the export to.
+    #[arg(long, value_delimiter = ',')]
+    tids: Option<Vec<u32>>,
+    /// Only programs starting at or after this time.
+    /// Formats: "2024-01-01T00:00:00", "2024-01-01 00:00:00", "2024-01-01".
+    #[arg(long)]
+    time_since: Option<String>,
+    /// Only programs starting at or before this time. Same formats as --time-since.
+    #[arg(long)]
+    time_until: Option<String>,
+    /// Comma-separated title categories (labels such as `anime`, `ova`,
+    /// `movie`, or `Cat` codes) to restrict the export to.
+    #[arg(long, value_delimiter = ',')]
+    category: Option<Vec<TitleCategory>>,
+    /// Program filter expression, e.g.
+    /// `tid=6309 AND ch IN (7,19) AND flag~new AND st_time>=2024-04-01`.
+    #[arg(long)]
+    filter: Option<FilterExpr>,
+}
+
+/// Arguments of the single-file export formats.
+#[derive(clap::Args)]
+struct ExportFileArgs {
+    /// Output file path (default: `dtvmgr.<ext>` of the format).
+    #[arg(long)]
+    file: Option<PathBuf>,
+    /// Program filters.
+    #[command(flatten)]
+    filter: ExportFilterArgs,
+    /// Use the stored TMDB title name (and overview, where the format has
+    /// one) in this language (default: Syoboi title).
+    #[arg(long, value_enum)]
+    metadata_language: Option<MetadataLanguage>,
+}
+
+/// Language of the TMDB names and overviews stored per title.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum MetadataLanguage {
+    /// Japanese (`ja-JP`).
+    Ja,
+    /// English (`en-US`).
+    En,
+}
+
+impl MetadataLanguage {
+    /// Languages fetched whenever a title is mapped.
+    const ALL: [Self; 2] = [Self::Ja, Self::En];
+
+    /// Returns the TMDB language tag.
+    const fn tmdb_language(self) -> &'static str {
+        match self {
+            Self::Ja => "ja-JP",
+            Self::En => "en-US",
+        }
+    }
+
+    /// Returns the XMLTV `lang` attribute value.
+    const fn xml_lang(self) -> &'static str {
+        match self {
+            Self::Ja => "ja",
+            Self::En => "en",
+        }
+    }
+}
+
+/// Arguments for the `export nfo` subcommand.
+///
+/// Path templates accept `{title}`, `{tid}`, `{season}`, and `{episode}`,
+/// with an optional zero-padded width such as `{season:02}`.
+#[derive(clap::Args)]
+struct ExportNfoArgs {
+    /// Root directory to write into.
+    #[arg(long, default_value = "nfo")]
+    out_dir: PathBuf,
+    /// Program filters. Without `--tids`, only titles mapped to a TMDB
+    /// series are exported.
+    #[command(flatten)]
+    filter: ExportFilterArgs,
+    /// Show directory template (holds `tvshow.nfo`).
+    #[arg(long, default_value = DEFAULT_SHOW_DIR)]
+    show_dir: String,
+    /// Season directory template, relative to the show directory.
+    #[arg(long, default_value = DEFAULT_SEASON_DIR)]
+    season_dir: String,
+    /// Episode NFO file name template.
+    #[arg(long, default_value = DEFAULT_EPISODE_FILE)]
+    episode_file: String,
+    /// Use Syoboi counts only, without fetching TMDB seasons.
+    #[arg(long, default_value_t = false)]
+    offline: bool,
+    /// TMDB language (e.g., "ja-JP"). Falls back to config.
+    #[arg(long)]
+    language: Option<String>,
+    /// Use the stored TMDB title name and overview in this language for
+    /// `tvshow.nfo` (default: Syoboi title without plot).
+    #[arg(long, value_enum)]
+    metadata_language: Option<MetadataLanguage>,
+}
+
+/// Arguments for the `serve` subcommand.
+#[derive(clap::Args)]
+struct ServeArgs {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: SocketAddr,
+}
+
 /// Arguments for the `epgstation` subcommand.
 #[derive(clap::Args)]
 struct EpgstationCommand {
@@ -338,44 +1102,446 @@ struct DbCommand {
 enum DbSubcommands {
     /// Sync Syoboi data to local database.
     Sync(DbSyncArgs),
+    /// Sync a span of past months, one month per sync run.
+    Backfill(DbBackfillArgs),
     /// Browse cached titles and programs via TUI.
-    List,
+    List(DbListArgs),
+    /// Browse cached programs as an EPG grid (channels x time) via TUI.
+    Grid(DbGridArgs),
+    /// Print everything cached about one title: Syoboi fields, sub-titles,
+    /// staff, TMDB mapping, and airings by channel.
+    Show(DbShowArgs),
     /// Preview title normalization results via TUI.
     Normalize,
     /// Search TMDB for cached titles and store results.
     TmdbLookup(DbTmdbLookupArgs),
+    /// Show cache statistics and per-channel broadcast usage.
+    Stats,
+    /// Recalculate derived program columns (`duration_min`) in bulk.
+    Recompute(DbRecomputeArgs),
+    /// Match cached programs to TMDB episodes of their mapped season.
+    TmdbMatch(DbTmdbMatchArgs),
+    /// Seed an empty database from a published snapshot, then sync.
+    Bootstrap(DbBootstrapArgs),
+    /// Export cached data to external file formats.
+    Export(DbExportCommand),
+    /// Report overlapping broadcasts of watched titles, grouped by day.
+    Conflicts(DbConflictsArgs),
+    /// Delete programs that ended before a cutoff date.
+    Prune(DbPruneArgs),
+    /// Compact the database file to reclaim space freed by deletions.
+    Vacuum,
+    /// Measure title and program upsert throughput on a scratch database.
+    Bench(DbBenchArgs),
+    /// Add or remove user tags on a program (e.g. recorded, failed, skip).
+    Tag(DbTagArgs),
+    /// Show, set, or clear the user note of a program.
+    Note(DbNoteArgs),
+    /// Show, set, or clear the TMDB episode group matched for a title.
+    EpisodeGroup(DbEpisodeGroupArgs),
+    /// Write all TMDB mappings (series, season, movie, episode group,
+    /// season ranges) to a JSON file.
+    ExportMappings(DbExportMappingsArgs),
+    /// Apply TMDB mappings from a file written by `db export-mappings`.
+    ImportMappings(DbImportMappingsArgs),
+    /// Save, list, or delete named copies of the cached schedule.
+    Snapshot(DbSnapshotCommand),
+    /// Report programs added, removed, or time-shifted between two snapshots.
+    Diff(DbDiffArgs),
+    /// Show the recorded start / end time and revision changes of a program.
+    History(DbHistoryArgs),
+    /// Run a SQL statement against the cache (read-only unless `--write`).
+    Sql(DbSqlArgs),
 }
 
-/// Arguments for the `db sync` subcommand.
+/// Arguments for the `db snapshot` subcommand.
 #[derive(clap::Args)]
-struct DbSyncArgs {
-    /// Start datetime (default: now - 1 day).
-    /// Formats: "2024-01-01T00:00:00", "2024-01-01 00:00:00", "2024-01-01".
-    #[arg(long)]
-    time_since: Option<String>,
+struct DbSnapshotCommand {
+    /// Snapshot subcommand to run.
+    #[command(subcommand)]
+    command: DbSnapshotSubcommands,
+}
+
+/// Available `db snapshot` subcommands.
+#[derive(Subcommand)]
+enum DbSnapshotSubcommands {
+    /// Copy the cached programs into a new snapshot.
+    Create {
+        /// Snapshot name (e.g. "2024-w14").
+        name: String,
+    },
+    /// List snapshots, oldest first.
+    List,
+    /// Delete a snapshot.
+    Delete {
+        /// Snapshot name.
+        name: String,
+    },
+}
+
+/// Arguments for the `db diff` subcommand.
+#[derive(clap::Args)]
+struct DbDiffArgs {
+    /// Older snapshot name.
+    old: String,
+    /// Newer snapshot name.
+    new: String,
+    /// Include programs of all titles, not only followed ones.
+    #[arg(long, default_value_t = false)]
+    all_titles: bool,
+}
+
+/// Arguments for the `db history` subcommand.
+#[derive(clap::Args)]
+struct DbHistoryArgs {
+    /// Syoboi program ID.
+    pid: u32,
+}
+
+/// Arguments for the `db sql` subcommand.
+#[derive(clap::Args)]
+struct DbSqlArgs {
+    /// SQL statement to run (e.g. "SELECT tid, title FROM titles LIMIT 5").
+    query: String,
+    /// Open the database read-write and allow statements that modify it.
+    #[arg(long)]
+    write: bool,
+}
+
+/// Arguments for the `db export-mappings` subcommand.
+#[derive(clap::Args)]
+struct DbExportMappingsArgs {
+    /// Output file path.
+    #[arg(long, default_value = "dtvmgr.mappings.json")]
+    file: PathBuf,
+}
+
+/// Arguments for the `db import-mappings` subcommand.
+#[derive(clap::Args)]
+struct DbImportMappingsArgs {
+    /// Mappings file written by `db export-mappings`.
+    file: PathBuf,
+    /// Replace titles that already have a different mapping.
+    #[arg(long, default_value_t = false)]
+    overwrite: bool,
+}
+
+/// Arguments for the `db grid` subcommand.
+#[derive(clap::Args)]
+struct DbGridArgs {
+    /// Broadcast day to show (YYYY-MM-DD). Defaults to today; the day runs
+    /// from 05:00 to 05:00 of the next day.
+    #[arg(long)]
+    date: Option<chrono::NaiveDate>,
+}
+
+/// Arguments for the `db show` subcommand.
+#[derive(clap::Args)]
+struct DbShowArgs {
+    /// Syoboi title ID.
+    tid: u32,
+    /// Fetch the mapped TMDB season(s) and list their episodes with the
+    /// matched Syoboi counts.
+    #[arg(long, default_value_t = false)]
+    tmdb: bool,
+    /// TMDB response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long, requires = "tmdb")]
+    language: Option<String>,
+}
+
+/// Arguments for the `db tag` subcommand.
+#[derive(clap::Args)]
+struct DbTagArgs {
+    /// Syoboi program ID.
+    pid: u32,
+    /// Tags to add (or remove with --remove). Stored lowercased.
+    #[arg(required = true)]
+    tags: Vec<String>,
+    /// Remove the given tags instead of adding them.
+    #[arg(long, default_value_t = false)]
+    remove: bool,
+}
+
+/// Arguments for the `db note` subcommand.
+#[derive(clap::Args)]
+struct DbNoteArgs {
+    /// Syoboi program ID.
+    pid: u32,
+    /// Note text. Prints the current note when omitted.
+    text: Option<String>,
+    /// Delete the note.
+    #[arg(long, default_value_t = false, conflicts_with = "text")]
+    clear: bool,
+}
+
+/// Arguments for the `db episode-group` subcommand.
+#[derive(clap::Args)]
+struct DbEpisodeGroupArgs {
+    /// Syoboi title ID.
+    tid: u32,
+    /// TMDB episode group ID (see `tmdb episode-groups`). Prints the current
+    /// group when omitted.
+    group_id: Option<String>,
+    /// Remove the episode group mapping.
+    #[arg(long, default_value_t = false, conflicts_with = "group_id")]
+    clear: bool,
+}
+
+/// Arguments for the `db prune` subcommand.
+#[derive(clap::Args)]
+struct DbPruneArgs {
+    /// Delete programs that ended before this datetime.
+    /// Formats: "2024-01-01T00:00:00", "2024-01-01 00:00:00", "2024-01-01".
+    #[arg(long)]
+    before: String,
+    /// Also delete titles left without programs (followed, watchlisted,
+    /// and TMDB-mapped titles are kept).
+    #[arg(long, default_value_t = false)]
+    orphan_titles: bool,
+    /// Report what would be deleted without changing the database.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+}
+
+/// Arguments for the `db bench` subcommand.
+#[derive(clap::Args)]
+struct DbBenchArgs {
+    /// Synthetic programs to write (one title per ten programs).
+    #[arg(long, default_value_t = 100_000)]
+    programs: usize,
+    /// Comma-separated rows per transaction to compare (`0` = one transaction).
+    #[arg(long, value_delimiter = ',', default_value = "100,1000,10000")]
+    batch_sizes: Vec<usize>,
+    /// Directory for the scratch databases. Defaults to the data directory,
+    /// so the disk holding the real database is measured.
+    #[arg(long)]
+    scratch_dir: Option<PathBuf>,
+}
+
+/// Arguments for the `db conflicts` subcommand.
+#[derive(clap::Args)]
+struct DbConflictsArgs {
+    /// Comma-separated channel IDs. Falls back to config selected channels if omitted.
+    #[arg(long, value_delimiter = ',')]
+    ch_ids: Option<Vec<u32>>,
+    /// Only programs starting at or after this time (default: now).
+    /// Formats: "2024-01-01T00:00:00", "2024-01-01 00:00:00", "2024-01-01".
+    #[arg(long)]
+    time_since: Option<String>,
+    /// Only programs starting at or before this time. Same formats as --time-since.
+    #[arg(long)]
+    time_until: Option<String>,
+    /// Check every cached title instead of only the watchlist.
+    #[arg(long, default_value_t = false)]
+    all_titles: bool,
+}
+
+/// Arguments for the `db export` subcommand.
+#[derive(clap::Args)]
+struct DbExportCommand {
+    /// Export format to write.
+    #[command(subcommand)]
+    command: DbExportSubcommands,
+}
+
+/// Available `db export` formats.
+#[derive(Subcommand)]
+enum DbExportSubcommands {
+    /// Write cached programs as an iCalendar (.ics) file (same as `export ics`).
+    Ics(ExportFileArgs),
+}
+
+/// Arguments for the `db list` subcommand.
+#[derive(clap::Args)]
+struct DbListArgs {
+    /// Comma-separated title categories (labels such as `anime`, `ova`,
+    /// `movie`, or `Cat` codes) to show.
+    #[arg(long, value_delimiter = ',')]
+    category: Option<Vec<TitleCategory>>,
+    /// Only show programs matching this filter expression (and their
+    /// titles), e.g. `flag~new AND st_time>=2024-04-01`.
+    #[arg(long)]
+    filter: Option<FilterExpr>,
+}
+
+/// Arguments for the `db bootstrap` subcommand.
+#[derive(clap::Args)]
+struct DbBootstrapArgs {
+    /// Seed URL (`https://` or `file://`) of a zstd-compressed dtvmgr database.
+    #[arg(long, required = true)]
+    from_url: String,
+    /// Expected SHA-256 of the download. Defaults to the `<url>.sha256` sidecar file.
+    #[arg(long)]
+    sha256: Option<String>,
+    /// Import even if the database already contains titles.
+    #[arg(long)]
+    force: bool,
+    /// Skip the incremental sync after the import.
+    #[arg(long)]
+    skip_sync: bool,
+}
+
+/// Arguments for the `db tmdb-match` subcommand.
+#[derive(clap::Args)]
+struct DbTmdbMatchArgs {
+    /// Comma-separated title IDs. If omitted, matches all titles with a TMDB series mapping.
+    #[arg(long, value_delimiter = ',')]
+    tids: Option<Vec<u32>>,
+    /// Match only this program with a single TMDB episode request.
+    #[arg(long, conflicts_with = "tids")]
+    pid: Option<u32>,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+    /// Replace existing episode mappings instead of only filling empty ones.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+/// Arguments for the `db recompute` subcommand.
+#[derive(clap::Args)]
+struct DbRecomputeArgs {
+    /// Rows per transaction.
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+    batch_size: u32,
+}
+
+/// Arguments for the `db sync` subcommand.
+#[derive(clap::Args)]
+struct DbSyncArgs {
+    /// Start datetime (default: now - 1 day).
+    /// Formats: "2024-01-01T00:00:00", "2024-01-01 00:00:00", "2024-01-01".
+    #[arg(long)]
+    time_since: Option<String>,
 
     /// End datetime (default: now + 1 day). Same formats as --time-since.
     #[arg(long)]
     time_until: Option<String>,
 
+    /// Sync an anime season (`2024q2` = April-June, or `now`), including
+    /// late-night programs of its last day.
+    #[arg(long, conflicts_with_all = ["time_since", "time_until"])]
+    season: Option<String>,
+
+    /// Comma-separated channel IDs. Falls back to config selected channels if omitted.
+    #[arg(long, value_delimiter = ',')]
+    ch_ids: Option<Vec<u32>>,
+
+    /// Comma-separated TIDs to restrict the sync to.
+    #[arg(long, value_delimiter = ',')]
+    tids: Option<Vec<u32>>,
+
+    /// Fetch only programs updated since the last incremental sync of the
+    /// same channels/TIDs (`LastUpdate`). The first run does a full fetch.
+    #[arg(long, conflicts_with_all = ["time_since", "time_until", "season"])]
+    incremental: bool,
+
+    /// Restrict the sync to followed titles (`titles follow`).
+    #[arg(long, conflicts_with = "tids")]
+    followed_only: bool,
+
+    /// Comma-separated title categories (labels such as `anime`, `ova`,
+    /// `movie`, or `Cat` codes) to store in this run. Cached titles of
+    /// other categories are kept.
+    #[arg(long, value_delimiter = ',', conflicts_with = "incremental")]
+    category: Option<Vec<TitleCategory>>,
+
+    /// Resume the interrupted run with this ID (see `sync_runs`), reusing
+    /// its parameters and skipping the title chunks it completed.
+    #[arg(long, conflicts_with_all = ["time_since", "time_until", "season", "ch_ids", "tids", "incremental", "followed_only", "category"])]
+    resume: Option<i64>,
+}
+
+/// Arguments for the `db backfill` subcommand.
+#[derive(clap::Args)]
+struct DbBackfillArgs {
+    /// First month to sync (`YYYY-MM`).
+    #[arg(long)]
+    from: String,
+
+    /// Last month to sync (`YYYY-MM`), inclusive.
+    #[arg(long)]
+    to: String,
+
     /// Comma-separated channel IDs. Falls back to config selected channels if omitted.
     #[arg(long, value_delimiter = ',')]
     ch_ids: Option<Vec<u32>>,
 }
 
+/// Resolved parameters of a `db sync` run, stored in `sync_runs.params` so
+/// `db sync --resume` repeats the same fetch.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct SyncRunParams {
+    /// Channel IDs.
+    ch_ids: Vec<u32>,
+    /// TIDs the sync is restricted to.
+    tids: Option<Vec<u32>>,
+    /// Start of the time range (`%Y-%m-%d %H:%M:%S`); `None` when
+    /// fetching by `updated_since`.
+    time_since: Option<String>,
+    /// End of the time range (`%Y-%m-%d %H:%M:%S`).
+    time_until: Option<String>,
+    /// `LastUpdate` cursor of an incremental run.
+    updated_since: Option<String>,
+    /// `sync_state` scope of an incremental run.
+    scope: Option<String>,
+    /// `Cat` codes stored by the run (`None` = all allowed by config).
+    #[serde(default)]
+    categories: Option<Vec<u32>>,
+}
+
+/// Payload of a `sync` job: the part of a sync run deferred after its
+/// retry budget ran out.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct SyncJobPayload {
+    /// TIDs whose titles were not fetched.
+    tids: Vec<u32>,
+    /// Start of the original time range.
+    time_since: String,
+    /// End of the original time range.
+    time_until: String,
+    /// Channel IDs of the original run (`None` = config selection).
+    ch_ids: Option<Vec<u32>>,
+    /// `Cat` codes stored by the original run.
+    #[serde(default)]
+    categories: Option<Vec<u32>>,
+}
+
+impl SyncJobPayload {
+    /// Converts the payload into `db sync` arguments.
+    fn into_args(self) -> DbSyncArgs {
+        DbSyncArgs {
+            time_since: Some(self.time_since),
+            time_until: Some(self.time_until),
+            season: None,
+            ch_ids: self.ch_ids,
+            tids: Some(self.tids),
+            incremental: false,
+            followed_only: false,
+            category: self.categories.map(|codes| {
+                codes
+                    .into_iter()
+                    .map(|c| TitleCategory::from_cat(Some(c)))
+                    .collect()
+            }),
+            resume: None,
+        }
+    }
+}
+
 /// Arguments for the `db tmdb-lookup` subcommand.
 #[derive(clap::Args)]
 struct DbTmdbLookupArgs {
     /// Comma-separated title IDs. If omitted, searches all titles without TMDB mapping.
     #[arg(long, value_delimiter = ',')]
     tids: Option<Vec<u32>>,
-    /// Response language (e.g. "ja-JP"). Falls back to config, then "en-US".
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
     #[arg(long)]
     language: Option<String>,
     /// Ignore cooldown and re-search all titles.
     #[arg(long)]
     force: bool,
-    /// Retry only unmapped titles (`tmdb_series_id` is NULL), ignoring cooldown.
+    /// Retry only unmapped titles (no series or movie mapping), ignoring cooldown.
     #[arg(long)]
     retry_unmapped: bool,
 }
@@ -395,6 +1561,37 @@ enum ChannelsSubcommands {
     Select,
     /// List currently selected channels.
     List,
+    /// Refresh cached channels and channel groups from the API.
+    Sync,
+    /// Write the selected channels with names and groups to a file.
+    Export(ChannelsExportArgs),
+    /// Load a selection written by `export`, validated against the channel list.
+    Import(ChannelsImportArgs),
+}
+
+/// Arguments for the `syoboi channels export` subcommand.
+#[derive(clap::Args)]
+struct ChannelsExportArgs {
+    /// Output file; `.json` writes JSON, anything else TOML.
+    #[arg(long, default_value = "dtvmgr.channels.toml")]
+    file: PathBuf,
+}
+
+/// Arguments for the `syoboi channels import` subcommand.
+#[derive(clap::Args)]
+struct ChannelsImportArgs {
+    /// Selection file written by `syoboi channels export`.
+    file: PathBuf,
+    /// Replace the current selection instead of adding to it.
+    #[arg(long, default_value_t = false)]
+    replace: bool,
+    /// Validate against the cached channel list instead of refreshing it
+    /// from the API.
+    #[arg(long, default_value_t = false)]
+    cached: bool,
+    /// Report the new selection without saving it.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
 }
 
 /// Arguments for the `syoboi` subcommand.
@@ -428,17 +1625,78 @@ struct ProgArgs {
     #[arg(long)]
     time_until: Option<String>,
 
+    /// List an anime season (`2024q2` = April-June, or `now`), including
+    /// late-night programs of its last day.
+    #[arg(long, conflicts_with_all = ["time_since", "time_until"])]
+    season: Option<String>,
+
     /// Comma-separated channel IDs (e.g. "1,7,19"). Falls back to config selected channels if omitted.
     #[arg(long, value_delimiter = ',')]
     ch_ids: Option<Vec<u32>>,
+
+    /// Comma-separated channel group IDs; lists channels of these groups
+    /// (narrowed by --ch-ids when both are given).
+    #[arg(long, value_delimiter = ',')]
+    ch_gids: Option<Vec<u32>>,
+
+    /// Comma-separated title IDs.
+    #[arg(long, value_delimiter = ',')]
+    tids: Option<Vec<u32>>,
+
+    /// Comma-separated program IDs. Without --time-since/--time-until the
+    /// programs are looked up regardless of air time.
+    #[arg(long, value_delimiter = ',')]
+    pids: Option<Vec<u32>>,
+
+    /// Episode number ("12") or range ("1-12", "13-").
+    #[arg(long)]
+    count: Option<dtvmgr_api::syoboi::CountRange>,
+
+    /// Do not join sub-titles (`JOIN=SubTitles`).
+    #[arg(long)]
+    no_join: bool,
+
+    #[command(flatten)]
+    query: ProgramQueryArgs,
+}
+
+/// Sort, flag filter, and limit options shared by program listings.
+#[derive(clap::Args)]
+struct ProgramQueryArgs {
+    /// Sort by `st_time`, `tid`, or `channel` (ties by start time). Default: source order.
+    #[arg(long)]
+    sort: Option<ProgramSort>,
+    /// Only programs with this flag: new, final, or rerun.
+    #[arg(long)]
+    flag_filter: Option<FlagFilter>,
+    /// Maximum number of programs to list.
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+impl ProgramQueryArgs {
+    /// Returns the query described by the options.
+    const fn query(&self) -> ProgramQuery {
+        ProgramQuery {
+            sort: self.sort,
+            flag: self.flag_filter,
+            limit: self.limit,
+        }
+    }
 }
 
 /// Arguments for the `syoboi titles` subcommand.
 #[derive(clap::Args)]
 struct TitlesArgs {
-    /// Comma-separated title IDs (e.g. "6309,7667").
-    #[arg(long, required = true, value_delimiter = ',')]
-    tids: Vec<u32>,
+    /// Title IDs: comma-separated list ("6309,7667"), range ("6000-6999",
+    /// "6000-"), or "*" for all titles. Defaults to "*" with --updated-since.
+    #[arg(long, required_unless_present = "updated_since")]
+    tids: Option<TidSelector>,
+
+    /// Only titles updated at or after this datetime (`LastUpdate`).
+    /// Formats: "2024-01-01T00:00:00", "2024-01-01 00:00:00", "2024-01-01".
+    #[arg(long)]
+    updated_since: Option<String>,
 }
 
 /// Arguments for the `tmdb` subcommand.
@@ -458,8 +1716,28 @@ enum TmdbSubcommands {
     SearchMovie(TmdbSearchMovieArgs),
     /// Get TV series details from TMDB.
     TvDetails(TmdbTvDetailsArgs),
+    /// Get movie details from TMDB.
+    MovieDetails(TmdbMovieDetailsArgs),
     /// Get TV season details from TMDB.
     TvSeason(TmdbTvSeasonArgs),
+    /// Get a single TV episode (guest stars, still, runtime) from TMDB.
+    TvEpisode(TmdbTvEpisodeArgs),
+    /// List episode groups (alternate orders) of a TV series from TMDB.
+    EpisodeGroups(TmdbEpisodeGroupsArgs),
+    /// Get an episode group with its episodes in absolute order from TMDB.
+    EpisodeGroup(TmdbEpisodeGroupArgs),
+    /// Get streaming availability for a TV series from TMDB.
+    WatchProviders(TmdbWatchProvidersArgs),
+    /// Find TMDB series/movies by `IMDb` or `TheTVDB` ID.
+    FindByExternalId(TmdbFindByExternalIdArgs),
+    /// Download poster/backdrop images for mapped titles.
+    Images(TmdbImagesArgs),
+    /// Match unmapped titles to TMDB series by scored search results.
+    AutoMatch(TmdbAutoMatchArgs),
+    /// Write scored TMDB candidates of unmapped titles to a reviewable TOML file.
+    Suggest(TmdbSuggestArgs),
+    /// Apply the approved mappings of a `tmdb suggest` file.
+    Apply(TmdbApplyArgs),
 }
 
 /// Arguments for the `tmdb search-tv` subcommand.
@@ -468,9 +1746,12 @@ struct TmdbSearchTvArgs {
     /// Search query (e.g. "SPY×FAMILY").
     #[arg(long, required = true)]
     query: String,
-    /// Response language (e.g. "ja-JP"). Falls back to config, then "en-US".
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
     #[arg(long)]
     language: Option<String>,
+    /// Fetch every result page instead of only the first.
+    #[arg(long)]
+    all_pages: bool,
 }
 
 /// Arguments for the `tmdb search-movie` subcommand.
@@ -479,9 +1760,12 @@ struct TmdbSearchMovieArgs {
     /// Search query (e.g. "すずめの戸締まり").
     #[arg(long, required = true)]
     query: String,
-    /// Response language (e.g. "ja-JP"). Falls back to config, then "en-US".
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
     #[arg(long)]
     language: Option<String>,
+    /// Fetch every result page instead of only the first.
+    #[arg(long)]
+    all_pages: bool,
 }
 
 /// Arguments for the `tmdb tv-details` subcommand.
@@ -490,7 +1774,18 @@ struct TmdbTvDetailsArgs {
     /// TMDB series ID.
     #[arg(long, required = true)]
     id: u64,
-    /// Response language (e.g. "ja-JP"). Falls back to config, then "en-US".
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
+
+/// Arguments for the `tmdb movie-details` subcommand.
+#[derive(clap::Args)]
+struct TmdbMovieDetailsArgs {
+    /// TMDB movie ID.
+    #[arg(long, required = true)]
+    id: u64,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
     #[arg(long)]
     language: Option<String>,
 }
@@ -504,90 +1799,313 @@ struct TmdbTvSeasonArgs {
     /// Season number.
     #[arg(long, required = true)]
     season: u32,
-    /// Response language (e.g. "ja-JP"). Falls back to config, then "en-US".
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
     #[arg(long)]
     language: Option<String>,
 }
 
-/// Runs the `syoboi prog` subcommand.
-///
-/// Falls back to config selected channels when `--ch-ids` is not specified.
-///
-/// # Errors
-///
-/// Returns an error if the API client fails to build, time range is invalid,
-/// or the API request fails.
-#[instrument(skip_all, err(level = "error"))]
-async fn run_syoboi_prog(args: &ProgArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let client = SyoboiClient::builder()
-        .user_agent(concat!(
-            env!("CARGO_PKG_NAME"),
-            "/",
-            env!("CARGO_PKG_VERSION")
-        ))
-        .build()
-        .context("failed to build API client")?;
-
-    let range = resolve_time_range(args.time_since.as_deref(), args.time_until.as_deref())
-        .context("failed to resolve time range")?;
-    tracing::info!(
-        "Time range: {} .. {}",
-        range.start.format("%Y-%m-%d %H:%M:%S"),
-        range.end.format("%Y-%m-%d %H:%M:%S"),
-    );
-
-    let ch_ids = resolve_ch_ids(args.ch_ids.clone(), config_file)
-        .context("failed to resolve channel IDs")?;
+/// Arguments for the `tmdb tv-episode` subcommand.
+#[derive(clap::Args)]
+struct TmdbTvEpisodeArgs {
+    /// TMDB series ID.
+    #[arg(long, required = true)]
+    id: u64,
+    /// Season number.
+    #[arg(long, required = true)]
+    season: u32,
+    /// Episode number within the season.
+    #[arg(long, required = true)]
+    episode: u32,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
 
-    let params = ProgLookupParams {
-        ch_ids: Some(ch_ids),
-        range: Some(range),
-        ..ProgLookupParams::default()
-    };
+/// Arguments for the `tmdb episode-groups` subcommand.
+#[derive(clap::Args)]
+struct TmdbEpisodeGroupsArgs {
+    /// TMDB series ID.
+    #[arg(long, required = true)]
+    id: u64,
+}
 
-    let programs = lookup_all_programs(&client, &params)
-        .await
-        .context("failed to fetch programs")?;
+/// Arguments for the `tmdb episode-group` subcommand.
+#[derive(clap::Args)]
+struct TmdbEpisodeGroupArgs {
+    /// TMDB episode group ID.
+    #[arg(long, required = true)]
+    id: String,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
 
-    tracing::info!("PID\t\tTID\tChID\tCount\tStTime\t\t\tEdTime\t\t\tSubTitle");
-    for prog in &programs {
-        tracing::info!(
-            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
-            prog.pid,
-            prog.tid,
-            prog.ch_id,
-            prog.count
-                .map_or_else(|| String::from("-"), |c| c.to_string()),
-            prog.st_time,
-            prog.ed_time,
-            prog.st_sub_title.as_deref().unwrap_or("-"),
-        );
-    }
-    tracing::info!("Total: {} programs", programs.len());
+/// Arguments for the `tmdb watch-providers` subcommand.
+#[derive(clap::Args)]
+struct TmdbWatchProvidersArgs {
+    /// TMDB series ID.
+    #[arg(long, required = true)]
+    id: u64,
+    /// Provider region (e.g. "JP"). Falls back to `[tmdb] region`, then "JP".
+    #[arg(long)]
+    region: Option<String>,
+}
 
-    Ok(())
+/// Arguments for the `tmdb find-by-external-id` subcommand.
+#[derive(clap::Args)]
+struct TmdbFindByExternalIdArgs {
+    /// External ID (e.g. "tt13706018" for `IMDb`, "405920" for `TheTVDB`).
+    #[arg(long, required = true)]
+    id: String,
+    /// ID namespace: "imdb" or "tvdb". Inferred from the ID when omitted.
+    #[arg(long)]
+    source: Option<TmdbExternalSource>,
+    /// Store the ID on this title and import or cross-check its TMDB mapping.
+    #[arg(long)]
+    tid: Option<u32>,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
 }
 
-/// Runs the `syoboi titles` subcommand.
-///
-/// # Errors
-///
-/// Returns an error if the API client fails to build or the API request fails.
+/// Arguments for the `tmdb images` subcommand.
+#[derive(clap::Args)]
+struct TmdbImagesArgs {
+    /// Comma-separated TIDs to download for (default: all TMDB-mapped titles).
+    #[arg(long, value_delimiter = ',')]
+    tids: Option<Vec<u32>>,
+    /// Comma-separated image kinds to download.
+    #[arg(long, value_delimiter = ',', default_value = "poster,backdrop")]
+    kinds: Vec<ImageKind>,
+    /// TMDB image size (e.g. "w780", "original"); must be one of the sizes
+    /// listed by the TMDB `configuration` endpoint.
+    #[arg(long, default_value = "original")]
+    size: String,
+    /// Preferred poster language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+    /// Re-download images that are already stored.
+    #[arg(long, default_value_t = false)]
+    force: bool,
+}
+
+/// Arguments for the `tmdb auto-match` subcommand.
+#[derive(clap::Args)]
+struct TmdbAutoMatchArgs {
+    /// Comma-separated TIDs to match (default: all unmapped titles).
+    #[arg(long, value_delimiter = ',')]
+    tids: Option<Vec<u32>>,
+    /// Minimum score (0.0-1.0) for a match to be applied.
+    #[arg(long, default_value_t = DEFAULT_THRESHOLD)]
+    threshold: f64,
+    /// Allowed difference in years between `FirstYear` and the first air date.
+    #[arg(long, default_value_t = DEFAULT_YEAR_TOLERANCE)]
+    year_tolerance: u32,
+    /// Path of the ambiguity report (default: `<data_dir>/auto-match-report.json`).
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Score and report without saving matches.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
+
+/// Arguments for the `tmdb suggest` subcommand.
+#[derive(clap::Args)]
+struct TmdbSuggestArgs {
+    /// Output file path.
+    #[arg(long, default_value = "suggestions.toml")]
+    out: PathBuf,
+    /// Comma-separated TIDs to suggest for (default: all unmapped titles).
+    #[arg(long, value_delimiter = ',')]
+    tids: Option<Vec<u32>>,
+    /// Minimum score (0.0-1.0) for a title to be marked `matched`.
+    #[arg(long, default_value_t = DEFAULT_THRESHOLD)]
+    threshold: f64,
+    /// Allowed difference in years between `FirstYear` and the first air date.
+    #[arg(long, default_value_t = DEFAULT_YEAR_TOLERANCE)]
+    year_tolerance: u32,
+    /// Pre-approve titles whose best candidate is confident (`matched`).
+    #[arg(long, default_value_t = false)]
+    approve_matched: bool,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
+
+/// Arguments for the `tmdb apply` subcommand.
+#[derive(clap::Args)]
+struct TmdbApplyArgs {
+    /// Suggestions file written by `tmdb suggest`.
+    file: PathBuf,
+    /// Replace titles that already have a different mapping.
+    #[arg(long, default_value_t = false)]
+    overwrite: bool,
+    /// Validate the file and report without saving mappings.
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+    /// Response language (e.g. "ja-JP"). Falls back to `[tmdb] language`, then "ja-JP".
+    #[arg(long)]
+    language: Option<String>,
+}
+
+/// Kind of TMDB artwork downloaded by `tmdb images`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ImageKind {
+    /// Series poster.
+    Poster,
+    /// Series backdrop (fanart).
+    Backdrop,
+}
+
+impl ImageKind {
+    /// Returns the name stored in `title_images.kind` and used as file stem.
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Poster => "poster",
+            Self::Backdrop => "backdrop",
+        }
+    }
+
+    /// Picks the image to download: posters prefer `language`, backdrops
+    /// prefer text-less images; otherwise the first (highest rated) entry.
+    fn pick<'a>(self, images: &'a TmdbImagesResponse, language: &str) -> Option<&'a TmdbImage> {
+        let (list, first, second) = match self {
+            Self::Poster => (&images.posters, Some(language), None),
+            Self::Backdrop => (&images.backdrops, None, Some(language)),
+        };
+        list.iter()
+            .find(|i| i.iso_639_1.as_deref() == first)
+            .or_else(|| list.iter().find(|i| i.iso_639_1.as_deref() == second))
+            .or_else(|| list.first())
+    }
+}
+
+/// Runs the `syoboi prog` subcommand.
+///
+/// Falls back to config selected channels when neither `--ch-ids`,
+/// `--ch-gids`, nor `--pids` is specified.
+///
+/// # Errors
+///
+/// Returns an error if the API client fails to build, time range is invalid,
+/// the API request fails, or JSON output cannot be written.
 #[instrument(skip_all, err(level = "error"))]
-async fn run_syoboi_titles(args: &TitlesArgs) -> Result<()> {
-    let client = SyoboiClient::builder()
-        .user_agent(concat!(
-            env!("CARGO_PKG_NAME"),
-            "/",
-            env!("CARGO_PKG_VERSION")
-        ))
-        .build()
-        .context("failed to build API client")?;
+async fn run_syoboi_prog(
+    args: &ProgArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = build_syoboi_client()?;
+
+    let by_pid = args.pids.is_some()
+        && args.time_since.is_none()
+        && args.time_until.is_none()
+        && args.season.is_none();
+    let range = if by_pid {
+        None
+    } else {
+        let range = resolve_cli_time_range(
+            args.time_since.as_deref(),
+            args.time_until.as_deref(),
+            args.season.as_deref(),
+            config_file,
+        )?;
+        tracing::info!(
+            "Time range: {} .. {}",
+            range.start.format("%Y-%m-%d %H:%M:%S"),
+            range.end.format("%Y-%m-%d %H:%M:%S"),
+        );
+        Some(range)
+    };
+
+    let ch_ids = if let Some(ref gids) = args.ch_gids {
+        let channels = client
+            .lookup_channels(None)
+            .await
+            .context("failed to fetch channels")?;
+        let ids = group_ch_ids(&channels, gids, args.ch_ids.as_deref());
+        if ids.is_empty() {
+            anyhow::bail!("No channels found in channel group(s) {gids:?}");
+        }
+        Some(ids)
+    } else if args.pids.is_some() {
+        args.ch_ids.clone()
+    } else {
+        Some(
+            resolve_ch_ids(args.ch_ids.clone(), config_file)
+                .context("failed to resolve channel IDs")?,
+        )
+    };
+
+    let mut params = ProgLookupParams::default().join_sub_titles(!args.no_join);
+    params.ch_ids = ch_ids;
+    params.tids.clone_from(&args.tids);
+    params.pids.clone_from(&args.pids);
+    params.count = args.count;
+    params.validate().context("invalid program lookup")?;
+
+    let programs = match range {
+        Some(range) => lookup_all_programs(&client, &params.range(range))
+            .await
+            .context("failed to fetch programs")?,
+        None => client
+            .lookup_programs(&params)
+            .await
+            .context("failed to fetch programs")?,
+    };
+    let programs = args.query.query().apply(programs);
+    if output.is_json() {
+        return write_json(&programs);
+    }
+
+    let tz = display_timezone();
+    tracing::info!("PID\t\tTID\tChID\tCount\tStTime\t\t\tEdTime\t\t\tSubTitle");
+    for prog in &programs {
+        tracing::info!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            prog.pid,
+            prog.tid,
+            prog.ch_id,
+            prog.count
+                .map_or_else(|| String::from("-"), |c| c.to_string()),
+            display_broadcast_time(&prog.st_time, tz),
+            display_broadcast_time(&prog.ed_time, tz),
+            prog.st_sub_title.as_deref().unwrap_or("-"),
+        );
+    }
+    tracing::info!("Total: {} programs", programs.len());
+
+    Ok(())
+}
+
+/// Runs the `syoboi titles` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the API client fails to build, the API request fails,
+/// or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_syoboi_titles(args: &TitlesArgs, output: OutputFormat) -> Result<()> {
+    let client = build_syoboi_client()?;
+
+    let mut params = TitleLookupParams {
+        tids: args.tids.clone().unwrap_or_default(),
+        ..TitleLookupParams::default()
+    };
+    if let Some(ref since) = args.updated_since {
+        params = params.last_update_since(to_naive_datetime_since(since)?);
+    }
 
     let titles = client
-        .lookup_titles(&args.tids, None)
+        .lookup_titles(&params)
         .await
         .context("failed to fetch titles")?;
+    if output.is_json() {
+        return write_json(&titles);
+    }
 
     tracing::info!("TID\tTitle\t\t\tFirstYear\tFirstMonth\tFirstCh\t\tUserPoint");
     for title in &titles {
@@ -612,6 +2130,47 @@ async fn run_syoboi_titles(args: &TitlesArgs) -> Result<()> {
     Ok(())
 }
 
+/// Resolves `--season`, or `--time-since` / `--time-until` defaulting to
+/// `[syoboi.sync] default_range_days` around now when both are omitted.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded or the range is invalid.
+fn resolve_cli_time_range(
+    time_since: Option<&str>,
+    time_until: Option<&str>,
+    season: Option<&str>,
+    config_file: Option<&PathBuf>,
+) -> Result<TimeRange> {
+    if let Some(season) = season {
+        return resolve_season_range(season, &SystemClock).context("failed to resolve season");
+    }
+    let range_days = if time_since.is_none() && time_until.is_none() {
+        let config_path =
+            resolve_config_path(config_file).context("failed to resolve config path")?;
+        AppConfig::load(&config_path)
+            .context("failed to load config")?
+            .syoboi
+            .sync
+            .default_range_days
+    } else {
+        DEFAULT_RANGE_DAYS
+    };
+    resolve_time_range_with(time_since, time_until, &SystemClock, range_days)
+        .context("failed to resolve time range")
+}
+
+/// Returns the IDs of `channels` in one of the groups `groups`, restricted
+/// to `only` when given.
+fn group_ch_ids(channels: &[SyoboiChannel], groups: &[u32], only: Option<&[u32]>) -> Vec<u32> {
+    channels
+        .iter()
+        .filter(|ch| ch.ch_gid.is_some_and(|gid| groups.contains(&gid)))
+        .filter(|ch| only.is_none_or(|ids| ids.contains(&ch.ch_id)))
+        .map(|ch| ch.ch_id)
+        .collect()
+}
+
 /// Resolves channel IDs from CLI args or config fallback.
 ///
 /// Returns an error if no channels are specified via `--ch-ids` or config.
@@ -636,366 +2195,767 @@ fn resolve_ch_ids(ch_ids: Option<Vec<u32>>, config_file: Option<&PathBuf>) -> Re
     Ok(config.syoboi.channels.selected)
 }
 
-/// Title lookup chunk size for Syoboi API.
-const TITLE_LOOKUP_CHUNK_SIZE: usize = 50;
+/// Delay before a deferred sync job (retry budget exhausted) runs.
+const SYNC_DEFER_DELAY_SECS: i64 = 15 * 60;
 
-/// Max retries per title chunk (rate-limit empty-response recovery).
-///
-/// Cloudflare rate-limit on cal.syoboi.jp typically lasts ~30-35s.
-/// With initial backoff 10s: 10+20+40+80+160 = 310s theoretical max,
-/// but usually resolves by retry 2-3 (cumulative 30-70s).
-const TITLE_CHUNK_MAX_RETRIES: u32 = 5;
+/// A program change on a watched title, reported after `db sync`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchEvent {
+    /// Kind of change.
+    kind: WatchEventKind,
+    /// Syoboi title ID.
+    tid: u32,
+    /// Syoboi program ID.
+    pid: u32,
+    /// Channel ID.
+    ch_id: u32,
+    /// Episode number (nullable).
+    count: Option<u32>,
+    /// Previous start time (only for time changes).
+    old_st_time: Option<String>,
+    /// Current start time.
+    st_time: String,
+}
 
-/// Initial backoff before retrying a title chunk (doubles each retry).
-const TITLE_CHUNK_INITIAL_BACKOFF: Duration = Duration::from_secs(10);
+/// Compares freshly fetched programs against the cached ones and returns
+/// the changes each watched title has opted into.
+fn detect_watch_events(
+    watchlist: &[WatchlistEntry],
+    previous: &[CachedProgram],
+    current: &[CachedProgram],
+) -> Vec<WatchEvent> {
+    let prefs: std::collections::HashMap<u32, &WatchlistEntry> =
+        watchlist.iter().map(|e| (e.tid, e)).collect();
+    let previous: std::collections::HashMap<u32, &CachedProgram> =
+        previous.iter().map(|p| (p.pid, p)).collect();
+
+    let mut events = Vec::new();
+    for program in current {
+        let Some(entry) = prefs.get(&program.tid) else {
+            continue;
+        };
+        let event = |kind: WatchEventKind, old_st_time: Option<String>| WatchEvent {
+            kind,
+            tid: program.tid,
+            pid: program.pid,
+            ch_id: program.ch_id,
+            count: program.count,
+            old_st_time,
+            st_time: program.st_time.clone(),
+        };
+        let old = previous.get(&program.pid);
+
+        match old {
+            None if entry.notify_new_program => events.push(event(WatchEventKind::New, None)),
+            Some(old)
+                if entry.notify_time_change
+                    && (old.st_time != program.st_time || old.ed_time != program.ed_time) =>
+            {
+                events.push(event(WatchEventKind::TimeChange, Some(old.st_time.clone())));
+            }
+            _ => {}
+        }
 
-/// Fields to request from `TitleLookup` during db sync.
-///
-/// Excludes `Comment` (contains unescaped `&` in URLs that breaks XML parsing)
-/// and other fields unused by `to_cached_title`.
-const TITLE_SYNC_FIELDS: &[&str] = &[
-    "TID",
-    "LastUpdate",
-    "Title",
-    "ShortTitle",
-    "TitleYomi",
-    "TitleEN",
-    "Cat",
-    "TitleFlag",
-    "FirstYear",
-    "FirstMonth",
-    "Keywords",
-    "SubTitles",
-];
+        let is_final = |flag: Option<u32>| ProgramFlags::from_flag(flag).is_final();
+        if entry.notify_finale && is_final(program.flag) && !old.is_some_and(|o| is_final(o.flag)) {
+            events.push(event(WatchEventKind::Finale, None));
+        }
+    }
+    events
+}
 
-/// Runs the `db sync` subcommand.
+/// Evaluates watchlist notification preferences against the fetched programs.
 ///
-/// Fetches programs and titles from Syoboi API and upserts into local DB.
-///
-/// # Errors
-///
-/// Returns an error if API calls or DB operations fail.
-#[instrument(skip_all)]
-/// Converts a `SyoboiTitle` to a `CachedTitle` for DB storage.
-fn to_cached_title(t: &SyoboiTitle) -> CachedTitle {
-    CachedTitle {
-        tid: t.tid,
-        tmdb_series_id: None,
-        tmdb_season_number: None,
-        tmdb_season_id: None,
-        title: t.title.clone(),
-        short_title: t.short_title.clone(),
-        title_yomi: t.title_yomi.clone(),
-        title_en: t.title_en.clone(),
-        cat: t.cat,
-        title_flag: t.title_flag,
-        first_year: t.first_year,
-        first_month: t.first_month,
-        keywords: dtvmgr_db::parse_keywords(t.keywords.clone()),
-        sub_titles: t.sub_titles.clone(),
-        last_update: t.last_update.clone(),
-        tmdb_original_name: None,
-        tmdb_name: None,
-        tmdb_alt_titles: None,
-        tmdb_last_updated: None,
-    }
-}
-
-/// Converts a `SyoboiProgram` to a `CachedProgram` for DB storage.
-fn to_cached_program(p: &SyoboiProgram) -> CachedProgram {
-    CachedProgram {
-        pid: p.pid,
-        tid: p.tid,
-        ch_id: p.ch_id,
-        tmdb_episode_id: None,
-        st_time: p.st_time.clone(),
-        st_offset: p.st_offset,
-        ed_time: p.ed_time.clone(),
-        count: p.count,
-        sub_title: p.sub_title.clone(),
-        flag: p.flag,
-        deleted: p.deleted,
-        warn: p.warn,
-        revision: p.revision,
-        last_update: p.last_update.clone(),
-        st_sub_title: p.st_sub_title.clone(),
-        duration_min: None,
-    }
-}
-
-/// Fetches titles in chunks with retry + exponential backoff for empty responses.
-///
-/// When the API returns an empty response for a non-empty chunk (likely
-/// rate-limited), retries up to `TITLE_CHUNK_MAX_RETRIES` times with
-/// exponential backoff starting at `TITLE_CHUNK_INITIAL_BACKOFF`.
-#[allow(clippy::arithmetic_side_effects)]
+/// Must run before the programs are upserted so the previous state is still cached.
 #[instrument(skip_all, err(level = "error"))]
-async fn fetch_titles_chunked(
-    client: &SyoboiClient,
-    unique_tids: &[u32],
-) -> Result<Vec<SyoboiTitle>> {
-    let mut all_titles = Vec::new();
-    let chunks: Vec<&[u32]> = unique_tids.chunks(TITLE_LOOKUP_CHUNK_SIZE).collect();
-    let total_chunks = chunks.len();
-
-    for (i, chunk) in chunks.into_iter().enumerate() {
-        tracing::debug!(?chunk, "TitleLookup requesting TIDs");
-
-        let mut titles = Vec::new();
-        let mut last_code: u16 = 0;
-        for retry in 0..=TITLE_CHUNK_MAX_RETRIES {
-            let (code, result) = client
-                .lookup_titles_with_status(chunk, Some(TITLE_SYNC_FIELDS))
-                .await
-                .with_context(|| {
-                    format!("failed to fetch titles for chunk of {} TIDs", chunk.len())
-                })?;
-            last_code = code;
-
-            if !result.is_empty() || chunk.is_empty() {
-                titles = result;
-                break;
-            }
-
-            // Empty response for a non-empty chunk — likely rate-limited.
-            if retry < TITLE_CHUNK_MAX_RETRIES {
-                let backoff = TITLE_CHUNK_INITIAL_BACKOFF * 2u32.pow(retry);
-                tracing::warn!(
-                    chunk = i + 1,
-                    total_chunks,
-                    code,
-                    retry = retry + 1,
-                    max_retries = TITLE_CHUNK_MAX_RETRIES,
-                    backoff_secs = backoff.as_secs(),
-                    "TitleLookup returned 0 titles for non-empty chunk, retrying after backoff"
-                );
-                tokio::time::sleep(backoff).await;
-            } else {
-                tracing::warn!(
-                    chunk = i + 1,
-                    total_chunks,
-                    code,
-                    requested = chunk.len(),
-                    "TitleLookup returned 0 titles after all retries, skipping chunk"
-                );
-            }
-        }
+fn evaluate_watchlist(
+    conn: &dtvmgr_db::Connection,
+    programs: &[SyoboiProgram],
+) -> Result<Vec<WatchEvent>> {
+    let watchlist = load_watchlist(conn).context("failed to load watchlist")?;
+    if watchlist.is_empty() {
+        return Ok(Vec::new());
+    }
+    let watched: HashSet<u32> = watchlist.iter().map(|e| e.tid).collect();
+    let watched_tids: Vec<u32> = watched.iter().copied().collect();
+    let previous =
+        load_programs_by_tids(conn, &watched_tids).context("failed to load watched programs")?;
+    let current: Vec<CachedProgram> = programs
+        .iter()
+        .filter(|p| watched.contains(&p.tid))
+        .map(to_cached_program)
+        .collect();
+    Ok(detect_watch_events(&watchlist, &previous, &current))
+}
 
-        if titles.is_empty() {
-            tracing::warn!(
-                chunk = i + 1,
-                total_chunks,
-                code = last_code,
-                fetched = 0,
-                "TitleLookup chunk completed"
-            );
-        } else {
-            tracing::info!(
-                chunk = i + 1,
-                total_chunks,
-                code = last_code,
-                fetched = titles.len(),
-                "TitleLookup chunk completed"
-            );
+/// Logs watchlist events, using cached title names where available.
+fn report_watch_events(events: &[WatchEvent], titles: &[CachedTitle]) {
+    let names: std::collections::HashMap<u32, &str> =
+        titles.iter().map(|t| (t.tid, t.title.as_str())).collect();
+    let tz = display_timezone();
+    for ev in events {
+        let st_time = display_broadcast_time(&ev.st_time, tz);
+        let name = names.get(&ev.tid).copied().unwrap_or("-");
+        let count = ev
+            .count
+            .map_or_else(|| String::from("-"), |c| format!("#{c}"));
+        match ev.kind {
+            WatchEventKind::New => tracing::info!(
+                tid = ev.tid,
+                pid = ev.pid,
+                "Watchlist: new program {name} {count} at {st_time} (ch {})",
+                ev.ch_id
+            ),
+            WatchEventKind::TimeChange => tracing::info!(
+                tid = ev.tid,
+                pid = ev.pid,
+                "Watchlist: time change {name} {count}: {} -> {st_time} (ch {})",
+                ev.old_st_time
+                    .as_deref()
+                    .map_or_else(|| String::from("-"), |t| display_broadcast_time(t, tz)),
+                ev.ch_id
+            ),
+            WatchEventKind::Finale => tracing::info!(
+                tid = ev.tid,
+                pid = ev.pid,
+                "Watchlist: finale {name} {count} at {st_time} (ch {})",
+                ev.ch_id
+            ),
         }
-        all_titles.extend(titles);
     }
+}
 
-    Ok(all_titles)
+/// Cached programs of followed titles taken before a sync stores new
+/// data, diffed afterwards for webhook notifications.
+struct FollowedSnapshot {
+    /// Followed title IDs.
+    tids: HashSet<u32>,
+    /// Their cached programs before the sync.
+    previous: Vec<CachedProgram>,
 }
 
-/// Filters and upserts programs, skipping those with missing FK references.
+/// Snapshots the followed titles' programs when a webhook is configured.
 ///
-/// `all_fetched_tids` contains TIDs from all API-fetched titles (before cat
-/// filtering) and is used to distinguish cat-filtered skips from genuine
-/// FK misses.
+/// Must run before the programs are upserted so the previous state is still cached.
 #[instrument(skip_all, err(level = "error"))]
-fn upsert_filtered_programs(
+fn snapshot_followed(
     conn: &dtvmgr_db::Connection,
+    notify: &NotifyConfig,
+) -> Result<Option<FollowedSnapshot>> {
+    if notify.webhook_url.is_none() {
+        return Ok(None);
+    }
+    let tids = load_followed_tids(conn).context("failed to load followed titles")?;
+    if tids.is_empty() {
+        return Ok(None);
+    }
+    let previous =
+        load_programs_by_tids(conn, &tids).context("failed to load followed programs")?;
+    Ok(Some(FollowedSnapshot {
+        tids: tids.into_iter().collect(),
+        previous,
+    }))
+}
+
+/// Diffs the fetched programs of followed titles against `snapshot`.
+fn followed_program_changes(
+    conn: &dtvmgr_db::Connection,
+    snapshot: &FollowedSnapshot,
     programs: &[SyoboiProgram],
-    valid_tids: &HashSet<u32>,
-    valid_ch_ids: &HashSet<u32>,
-    all_fetched_tids: &HashSet<u32>,
-) -> Result<(usize, usize)> {
-    let mut cat_filtered: usize = 0;
-    let mut fk_missing: usize = 0;
-    let cached: Vec<CachedProgram> = programs
+    titles: &[CachedTitle],
+) -> Vec<ProgramChange> {
+    let current: Vec<CachedProgram> = programs
         .iter()
-        .filter(|p| {
-            if valid_tids.contains(&p.tid) && valid_ch_ids.contains(&p.ch_id) {
-                return true;
-            }
-            if all_fetched_tids.contains(&p.tid) && !valid_tids.contains(&p.tid) {
-                cat_filtered = cat_filtered.saturating_add(1);
-            } else {
-                fk_missing = fk_missing.saturating_add(1);
-            }
-            false
-        })
+        .filter(|p| snapshot.tids.contains(&p.tid))
         .map(to_cached_program)
         .collect();
-    if cat_filtered > 0 {
-        tracing::info!(
-            skipped = cat_filtered,
-            "Skipped programs (title excluded by cat filter)"
-        );
+    let title_names: std::collections::HashMap<u32, String> =
+        titles.iter().map(|t| (t.tid, t.title.clone())).collect();
+    let channels: std::collections::HashMap<u32, String> = load_channels(conn)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| (c.ch_id, c.ch_name))
+        .collect();
+    diff_programs(
+        &snapshot.tids,
+        &snapshot.previous,
+        &current,
+        &title_names,
+        &channels,
+    )
+}
+
+/// Posts `changes` to the configured webhook. Delivery failures are logged
+/// and do not fail the sync.
+async fn notify_program_changes(notify: &NotifyConfig, changes: &[ProgramChange]) {
+    let Some(url) = notify.webhook_url.as_deref() else {
+        return;
+    };
+    if changes.is_empty() {
+        tracing::debug!("No program changes for followed titles");
+        return;
     }
-    if fk_missing > 0 {
-        tracing::warn!(
-            skipped = fk_missing,
-            "Skipped programs with missing FK references"
-        );
+    let payload = build_payload(notify.format, notify.template(), changes);
+    match post_webhook(url, &payload).await {
+        Ok(()) => tracing::info!("Notified {} program changes to webhook", changes.len()),
+        Err(e) => tracing::warn!(error = %e, "failed to post webhook notification"),
     }
-    let changed = upsert_programs(conn, &cached).context("failed to upsert programs")?;
-    tracing::info!(
-        changed,
-        unchanged = cached.len().saturating_sub(changed),
-        "Programs upsert complete"
-    );
-    Ok((cached.len(), changed))
 }
 
-/// Deletes titles and programs whose categories are not in the allowed set.
-#[instrument(skip_all, err(level = "error"))]
-fn cleanup_disallowed_cats(
-    conn: &dtvmgr_db::Connection,
-    allowed_cats: &HashSet<u32>,
-) -> Result<()> {
-    let allowed_cats_vec: Vec<u32> = allowed_cats.iter().copied().collect();
-    let titles_deleted = delete_titles_by_cat_not_in(conn, &allowed_cats_vec)
-        .context("failed to delete titles by cat filter")?;
-    if titles_deleted > 0 {
-        tracing::info!(
-            deleted = titles_deleted,
-            "Deleted titles with non-allowed categories"
-        );
-    }
+/// Posts `payload` as JSON to `url`.
+async fn post_webhook(url: &str, payload: &serde_json::Value) -> Result<()> {
+    reqwest::Client::new()
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .context("webhook request failed")?
+        .error_for_status()
+        .context("webhook returned an error status")?;
+    Ok(())
+}
 
-    let remaining_titles = load_titles(conn).context("failed to load titles after cleanup")?;
-    let remaining_tids: Vec<u32> = remaining_titles.iter().map(|t| t.tid).collect();
-    let programs_deleted = delete_programs_by_tids_not_in(conn, &remaining_tids)
-        .context("failed to delete programs by tid filter")?;
-    if programs_deleted > 0 {
-        tracing::info!(deleted = programs_deleted, "Deleted orphaned programs");
-    }
+/// Builds the digest of `date` from the cached programs of followed titles.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+fn followed_digest(conn: &dtvmgr_db::Connection, date: chrono::NaiveDate) -> Result<Digest> {
+    let tids = load_followed_tids(conn).context("failed to load followed titles")?;
+    let programs = load_programs_by_tids(conn, &tids).context("failed to load programs")?;
+    let titles: std::collections::HashMap<u32, String> = load_titles_by_tids(conn, &tids)
+        .context("failed to load titles")?
+        .into_iter()
+        .map(|t| (t.tid, t.title))
+        .collect();
+    let channels: std::collections::HashMap<u32, String> = load_channels(conn)
+        .context("failed to load channels")?
+        .into_iter()
+        .map(|c| (c.ch_id, c.ch_name))
+        .collect();
+    Ok(build_digest(
+        &date.format("%Y-%m-%d").to_string(),
+        &tids.into_iter().collect(),
+        &programs,
+        &titles,
+        &channels,
+    ))
+}
 
+/// Sends `digest` as a plain-text email through the `[notify.email]` SMTP
+/// server. The password is read from `SMTP_PASSWORD`, then the config.
+///
+/// # Errors
+///
+/// Returns an error if the host, sender, or recipients are missing or
+/// invalid, or the SMTP server rejects the message.
+async fn send_digest_email(email: &EmailConfig, digest: &Digest) -> Result<()> {
+    use lettre::message::header::ContentType;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport as _, Message, Tokio1Executor};
+
+    let Some(host) = email.host.as_deref() else {
+        anyhow::bail!("[notify.email] host is not configured");
+    };
+    let from = email
+        .from
+        .as_deref()
+        .context("[notify.email] from is not configured")?
+        .parse()
+        .context("invalid [notify.email] from address")?;
+    if email.to.is_empty() {
+        anyhow::bail!("[notify.email] to is empty");
+    }
+    let mut message = Message::builder()
+        .from(from)
+        .subject(digest.subject.as_str())
+        .header(ContentType::TEXT_PLAIN);
+    for to in &email.to {
+        message = message.to(to
+            .parse()
+            .with_context(|| format!("invalid [notify.email] to address {to}"))?);
+    }
+    let message = message
+        .body(digest.body.clone())
+        .context("failed to build digest email")?;
+
+    let transport = match email.tls {
+        SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .with_context(|| format!("failed to configure STARTTLS for {host}"))?,
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+            .with_context(|| format!("failed to configure TLS for {host}"))?,
+        SmtpTls::Plain => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    };
+    let mut transport = transport.port(email.port());
+    if let Some(user) = email.username.as_deref() {
+        let password = std::env::var("SMTP_PASSWORD")
+            .ok()
+            .or_else(|| email.password.clone())
+            .unwrap_or_default();
+        transport = transport.credentials(Credentials::new(user.to_owned(), password));
+    }
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("failed to send digest email via {host}"))?;
     Ok(())
 }
 
+/// Runs `notify digest`: builds the digest of the given day (today by
+/// default) and emails it, or prints it with `--dry-run`.
+///
+/// # Errors
+///
+/// Returns an error if config or DB operations fail or the email cannot be
+/// sent.
 #[instrument(skip_all, err(level = "error"))]
-#[allow(clippy::too_many_lines)]
-async fn run_db_sync(args: &DbSyncArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let client = build_syoboi_client().context("failed to build Syoboi client")?;
-
+async fn run_notify_digest(args: &NotifyDigestArgs, config_file: Option<&PathBuf>) -> Result<()> {
     let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
     let config = AppConfig::load(&config_path).context("failed to load config")?;
-    let allowed_cats: HashSet<u32> = config.syoboi.titles.cat.iter().copied().collect();
-    tracing::info!(?allowed_cats, "Category filter loaded from config");
-
-    let range = resolve_time_range(args.time_since.as_deref(), args.time_until.as_deref())
-        .context("failed to resolve time range")?;
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let date = args
+        .date
+        .unwrap_or_else(|| syoboi_local_time(Utc::now()).date());
+    let digest = {
+        let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+        followed_digest(&conn, date)?
+    };
+    if args.dry_run {
+        #[allow(clippy::print_stdout)]
+        {
+            println!("Subject: {}\n\n{}", digest.subject, digest.body);
+        }
+        return Ok(());
+    }
+    send_digest_email(&config.notify.email, &digest).await?;
     tracing::info!(
-        "Time range: {} .. {}",
-        range.start.format("%Y-%m-%d %H:%M:%S"),
-        range.end.format("%Y-%m-%d %H:%M:%S"),
+        "Sent digest of {} programs on {date} to {} recipient(s)",
+        digest.programs,
+        config.notify.email.to.len()
     );
+    Ok(())
+}
 
-    let ch_ids = resolve_ch_ids(args.ch_ids.clone(), config_file)
-        .context("failed to resolve channel IDs")?;
-
-    let params = ProgLookupParams {
-        ch_ids: Some(ch_ids),
-        range: Some(range),
-        ..ProgLookupParams::default()
-    };
+/// Totals of one `db sync` run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SyncSummary {
+    /// `sync_runs` row of the run.
+    run_id: i64,
+    /// Titles stored after the category filter.
+    titles: usize,
+    /// Titles inserted or changed.
+    titles_changed: usize,
+    /// Programs stored.
+    programs: usize,
+    /// Programs inserted or changed.
+    programs_changed: usize,
+}
 
-    tracing::info!("Fetching programs from Syoboi API...");
-    let programs = lookup_all_programs(&client, &params)
+/// Runs the `db sync` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the sync fails.
+async fn run_db_sync(args: &DbSyncArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let pool = open_data_pool(config_file)?;
+    sync_db(args, &pool, config_file, &TerminalProgress::new())
         .await
-        .context("failed to fetch programs")?;
-    tracing::info!("Fetched {} programs", programs.len());
-
-    // Extract unique TIDs and fetch titles in chunks
-    let all_fetched_tids: HashSet<u32> = programs.iter().map(|p| p.tid).collect();
-    let unique_tids: Vec<u32> = all_fetched_tids.iter().copied().collect();
-    tracing::info!("Fetching titles for {} unique TIDs...", unique_tids.len());
+        .map(|_| ())
+}
 
-    let all_titles = fetch_titles_chunked(&client, &unique_tids)
-        .await
-        .context("failed to fetch titles in chunks")?;
-    tracing::info!("Fetched {} titles total", all_titles.len());
+/// Runs the `db backfill` subcommand.
+///
+/// Syncs each month of `--from..=--to` as its own `db sync` run. The Syoboi
+/// rate limiter pauses between requests once the hourly or daily limit is
+/// reached, and each completed month is checkpointed in `sync_state`, so
+/// re-running the same backfill skips the months already done.
+///
+/// # Errors
+///
+/// Returns an error if the months are invalid or a month fails to sync.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_db_backfill(args: &DbBackfillArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let ranges = month_ranges(&args.from, &args.to).context("failed to resolve months")?;
+    let pool = open_data_pool(config_file)?;
+    let conn = pool.get().context("failed to open database")?;
+    let ch_ids = resolve_ch_ids(args.ch_ids.clone(), config_file)
+        .context("failed to resolve channel IDs")?;
+    let scope = format!(
+        "backfill:{}..{}:{}",
+        args.from,
+        args.to,
+        sync_scope(&ch_ids, None)
+    );
+    let done = load_sync_cursor(&conn, &scope).context("failed to load backfill checkpoint")?;
+
+    let progress = TerminalProgress::new();
+    let mut synced: Vec<(String, SyncSummary)> = Vec::new();
+    let mut skipped = 0_usize;
+    for (n, range) in (1_usize..).zip(&ranges) {
+        let month = range.start.format("%Y-%m").to_string();
+        if done.as_deref().is_some_and(|done| month.as_str() <= done) {
+            skipped = skipped.saturating_add(1);
+            continue;
+        }
+        tracing::info!("Backfilling {month} ({n}/{})", ranges.len());
+        let sync_args = DbSyncArgs {
+            time_since: Some(range.start.format("%Y-%m-%d %H:%M:%S").to_string()),
+            time_until: Some(range.end.format("%Y-%m-%d %H:%M:%S").to_string()),
+            season: None,
+            ch_ids: Some(ch_ids.clone()),
+            tids: None,
+            incremental: false,
+            followed_only: false,
+            category: None,
+            resume: None,
+        };
+        let summary = sync_db(&sync_args, &pool, config_file, &progress)
+            .await
+            .with_context(|| {
+                format!("failed to backfill {month}; re-run the same backfill to continue")
+            })?;
+        save_sync_cursor(&conn, &scope, &month).context("failed to save backfill checkpoint")?;
+        synced.push((month, summary));
+    }
 
-    // Filter titles by allowed categories
-    let filtered_titles: Vec<&SyoboiTitle> = all_titles
-        .iter()
-        .filter(|t| t.cat.is_some_and(|c| allowed_cats.contains(&c)))
-        .collect();
-    let cat_filtered = all_titles.len().saturating_sub(filtered_titles.len());
-    if cat_filtered > 0 {
+    if skipped > 0 {
+        tracing::info!("Skipped {skipped} months completed by an earlier backfill");
+    }
+    for (month, summary) in &synced {
         tracing::info!(
-            filtered = cat_filtered,
-            remaining = filtered_titles.len(),
-            "Filtered titles by category"
+            "{month}: run #{}, {} titles ({} changed), {} programs ({} changed)",
+            summary.run_id,
+            summary.titles,
+            summary.titles_changed,
+            summary.programs,
+            summary.programs_changed,
         );
     }
+    tracing::info!(
+        "Backfill complete: {} months synced, {} programs ({} changed)",
+        synced.len(),
+        synced.iter().map(|(_, s)| s.programs).sum::<usize>(),
+        synced
+            .iter()
+            .map(|(_, s)| s.programs_changed)
+            .sum::<usize>(),
+    );
+    Ok(())
+}
 
-    // Open DB and upsert
-    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
-    let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+/// Runs a sync recorded in `sync_runs`: starts a new run, or resumes
+/// `args.resume`, then stores its outcome.
+///
+/// Progress is checkpointed per title chunk, so a failed or killed run can
+/// be continued with `db sync --resume <ID>`.
+///
+/// # Errors
+///
+/// Returns an error if the run cannot be started or resumed, or the sync
+/// fails.
+#[instrument(skip_all, err(level = "error"))]
+async fn sync_db(
+    args: &DbSyncArgs,
+    pool: &DbPool,
+    config_file: Option<&PathBuf>,
+    progress: &dyn SyncProgress,
+) -> Result<SyncSummary> {
+    let conn = pool.get().context("failed to open database")?;
+    let (run_id, resumed) = if let Some(id) = args.resume {
+        (id, Some(load_resumable_run(&conn, id)?))
+    } else {
+        if let Some(reason) = stale_channel_cache(&conn, config_file) {
+            refresh_stale_channels(&reason, pool).await;
+        }
+        let started_at = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let id = start_sync_run(&conn, &started_at).context("failed to start sync run")?;
+        tracing::info!("Sync run #{id} started");
+        (id, None)
+    };
+
+    let result = async move {
+        let (params, done_tids) = if let Some(resumed) = resumed {
+            resumed
+        } else {
+            let params = resolve_sync_params(args, config_file, &conn)?;
+            let json =
+                serde_json::to_string(&params).context("failed to serialize sync parameters")?;
+            save_sync_params(&conn, run_id, &json).context("failed to save sync parameters")?;
+            (params, HashSet::new())
+        };
+        sync_run(run_id, &params, done_tids, conn, config_file, progress).await
+    }
+    .await;
+    let summary = result.as_ref().copied().unwrap_or_default();
+    let count = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+    let record = SyncRunRecord {
+        id: run_id,
+        finished_at: Some(Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        status: String::from(if result.is_ok() {
+            "succeeded"
+        } else {
+            "failed"
+        }),
+        titles: count(summary.titles),
+        titles_changed: count(summary.titles_changed),
+        programs: count(summary.programs),
+        programs_changed: count(summary.programs_changed),
+        error: result.as_ref().err().map(|e| format!("{e:#}")),
+        ..SyncRunRecord::default()
+    };
+    if let Err(e) = pool.get().and_then(|conn| finish_sync_run(&conn, &record)) {
+        tracing::warn!(error = %format!("{e:#}"), "failed to record sync run");
+    }
+    if result.is_err() {
+        tracing::warn!("Sync run #{run_id} failed; resume it with `db sync --resume {run_id}`");
+    }
+    result.map(|summary| SyncSummary { run_id, ..summary })
+}
+
+/// Resolves the channels, TIDs, and time range (or incremental cursor) of
+/// a new sync run.
+///
+/// # Errors
+///
+/// Returns an error if the channels, followed titles, cursor, or time
+/// range cannot be resolved.
+fn resolve_sync_params(
+    args: &DbSyncArgs,
+    config_file: Option<&PathBuf>,
+    conn: &dtvmgr_db::Connection,
+) -> Result<SyncRunParams> {
+    let ch_ids = resolve_ch_ids(args.ch_ids.clone(), config_file)
+        .context("failed to resolve channel IDs")?;
+
+    let tids = if args.followed_only {
+        let followed = load_followed_tids(conn).context("failed to load followed titles")?;
+        if followed.is_empty() {
+            anyhow::bail!("no followed titles; run `titles follow --tids <TIDS>` first");
+        }
+        tracing::info!("Restricting sync to {} followed titles", followed.len());
+        Some(followed)
+    } else {
+        args.tids.clone()
+    };
+
+    let scope = args
+        .incremental
+        .then(|| sync_scope(&ch_ids, tids.as_deref()));
+    let updated_since = match scope.as_deref() {
+        Some(scope) => load_sync_cursor(conn, scope).context("failed to load sync cursor")?,
+        None => None,
+    };
+    let (time_since, time_until) = if updated_since.is_some() {
+        (None, None)
+    } else {
+        let range = resolve_cli_time_range(
+            args.time_since.as_deref(),
+            args.time_until.as_deref(),
+            args.season.as_deref(),
+            config_file,
+        )?;
+        (
+            Some(range.start.format("%Y-%m-%d %H:%M:%S").to_string()),
+            Some(range.end.format("%Y-%m-%d %H:%M:%S").to_string()),
+        )
+    };
+    Ok(SyncRunParams {
+        ch_ids,
+        tids,
+        time_since,
+        time_until,
+        updated_since,
+        scope,
+        categories: args
+            .category
+            .as_ref()
+            .map(|cats| cats.iter().map(|c| c.code()).collect()),
+    })
+}
 
-    let cached_titles: Vec<CachedTitle> =
-        filtered_titles.iter().map(|t| to_cached_title(t)).collect();
-    let titles_changed = upsert_titles(&conn, &cached_titles).context("failed to upsert titles")?;
+/// Loads the parameters and completed TIDs of sync run `id` for resuming.
+///
+/// # Errors
+///
+/// Returns an error if the run does not exist, already succeeded, or has
+/// no stored parameters.
+fn load_resumable_run(
+    conn: &dtvmgr_db::Connection,
+    id: i64,
+) -> Result<(SyncRunParams, HashSet<u32>)> {
+    let run = load_sync_run(conn, id)
+        .context("failed to load sync run")?
+        .with_context(|| format!("sync run #{id} not found"))?;
+    if run.status == "succeeded" {
+        anyhow::bail!("sync run #{id} already succeeded");
+    }
+    let params = run
+        .params
+        .as_deref()
+        .with_context(|| format!("sync run #{id} has no stored parameters"))?;
+    let params: SyncRunParams =
+        serde_json::from_str(params).context("invalid stored sync parameters")?;
     tracing::info!(
-        changed = titles_changed,
-        unchanged = cached_titles.len().saturating_sub(titles_changed),
-        "Titles upsert complete"
+        "Resuming sync run #{id}: {} programs fetched, {}/{} title chunks done",
+        run.programs_fetched,
+        run.title_chunks_done,
+        run.title_chunks_total,
     );
+    Ok((params, run.done_tids.into_iter().collect()))
+}
+
+/// Fetches programs, titles, and channels from Syoboi and stores them,
+/// reporting fetch progress to `progress` and checkpoints to `run_id`.
+///
+/// # Errors
+///
+/// Returns an error if an API request or DB operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::too_many_lines)]
+async fn sync_run(
+    run_id: i64,
+    params: &SyncRunParams,
+    done_tids: HashSet<u32>,
+    conn: PooledConnection,
+    config_file: Option<&PathBuf>,
+    progress: &dyn SyncProgress,
+) -> Result<SyncSummary> {
+    let client = build_syoboi_client().context("failed to build Syoboi client")?;
+
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let allowed_cats: HashSet<u32> = config.syoboi.titles.cat.iter().copied().collect();
+    tracing::info!(?allowed_cats, "Category filter loaded from config");
+    let sync_config = &config.syoboi.sync;
+    let title_fields = sync_config
+        .title_fields
+        .clone()
+        .unwrap_or_else(|| TITLE_SYNC_FIELDS.iter().map(|f| (*f).to_owned()).collect());
+    validate_title_fields(&title_fields)
+        .context(ConfigError(String::from("invalid [syoboi.sync] config")))?;
+    if let Some(fields) = &sync_config.program_fields {
+        validate_program_fields(fields)
+            .context(ConfigError(String::from("invalid [syoboi.sync] config")))?;
+    }
+
+    let lookup = ProgLookupParams {
+        ch_ids: Some(params.ch_ids.clone()),
+        tids: params.tids.clone(),
+        fields: sync_config.program_fields.clone(),
+        ..ProgLookupParams::default()
+    };
+    let mut engine = SyncEngine::new(&client, conn)
+        .allowed_cats(allowed_cats)
+        .progress(progress)
+        .budget(RetryBudget::new(
+            config.syoboi.sync.retry_budget,
+            Duration::from_secs(config.syoboi.sync.retry_budget_secs),
+        ))
+        .batch_size(config.syoboi.sync.batch_size)
+        .title_fields(title_fields)
+        .checkpoint(run_id, done_tids);
+    if let Some(cats) = &params.categories {
+        tracing::info!(?cats, "Storing only these categories in this run");
+        engine = engine.run_cats(cats.iter().copied().collect());
+    }
+
+    tracing::info!("Fetching programs from Syoboi API...");
+    let (programs, range) = if let Some(cursor) = params.updated_since.as_deref() {
+        let since = to_naive_datetime_since(cursor)
+            .with_context(|| format!("invalid sync cursor: {cursor}"))?;
+        tracing::info!("Incremental sync: programs updated since {cursor}");
+        let programs = engine.fetch_updated_programs(&lookup, since).await?;
+        (programs, None)
+    } else {
+        let range = resolve_time_range_with(
+            params.time_since.as_deref(),
+            params.time_until.as_deref(),
+            &SystemClock,
+            config.syoboi.sync.default_range_days,
+        )
+        .context("failed to resolve time range")?;
+        tracing::info!(
+            "Time range: {} .. {}",
+            range.start.format("%Y-%m-%d %H:%M:%S"),
+            range.end.format("%Y-%m-%d %H:%M:%S"),
+        );
+        let lookup = ProgLookupParams {
+            range: Some(range.clone()),
+            ..lookup
+        };
+        let programs = engine.fetch_programs(&lookup).await?;
+        (programs, Some(range))
+    };
+    tracing::info!("Fetched {} programs", programs.len());
+    if programs.is_empty() {
+        exit::mark(ExitStatus::NothingToDo, "no programs to sync");
+    }
 
-    // Ensure channels referenced by programs exist in DB
-    let unique_ch_ids: Vec<u32> = programs
+    let unique_tids: Vec<u32> = programs
         .iter()
-        .map(|p| p.ch_id)
+        .map(|p| p.tid)
         .collect::<HashSet<u32>>()
         .into_iter()
         .collect();
-    tracing::info!(
-        "Fetching channels for {} unique ch_ids...",
-        unique_ch_ids.len()
-    );
-    let api_channels = client
-        .lookup_channels(Some(&unique_ch_ids))
+    let title_sync = engine
+        .sync_titles(&unique_tids)
         .await
-        .context("failed to fetch channels")?;
-    let cached_channels: Vec<CachedChannel> = api_channels
-        .iter()
-        .map(|ch| CachedChannel {
-            ch_id: ch.ch_id,
-            ch_gid: None,
-            ch_name: ch.ch_name.clone(),
-        })
-        .collect();
-    let ch_changed =
-        upsert_channels(&conn, &cached_channels).context("failed to upsert channels")?;
-    tracing::info!(
-        fetched = cached_channels.len(),
-        changed = ch_changed,
-        "Channels upsert complete"
-    );
+        .context("failed to sync titles")?;
 
-    let valid_tids: HashSet<u32> = cached_titles.iter().map(|t| t.tid).collect();
-    let valid_ch_ids: HashSet<u32> = cached_channels.iter().map(|ch| ch.ch_id).collect();
-    let (total_programs, programs_changed) = upsert_filtered_programs(
-        &conn,
-        &programs,
-        &valid_tids,
-        &valid_ch_ids,
-        &all_fetched_tids,
-    )
-    .context("failed to upsert filtered programs")?;
+    // Must run before the programs are upserted.
+    let watch_events =
+        evaluate_watchlist(engine.conn(), &programs).context("failed to evaluate watchlist")?;
+    let followed_snapshot = snapshot_followed(engine.conn(), &config.notify)?;
 
-    cleanup_disallowed_cats(&conn, &allowed_cats)
-        .context("failed to clean up disallowed categories")?;
+    let stored = engine
+        .store_programs(&programs, &title_sync)
+        .await
+        .context("failed to store programs")?;
+    let budget = *engine.retry_budget();
+    let conn = engine.into_conn();
+    let TitleSync {
+        titles: cached_titles,
+        changed: titles_changed,
+        deferred_tids,
+        ..
+    } = title_sync;
+    let total_programs = stored.programs;
+    let programs_changed = stored.changed;
+    let ch_changed = stored.channels_changed;
+
+    report_watch_events(&watch_events, &cached_titles);
+    if let Some(snapshot) = &followed_snapshot {
+        let changes = followed_program_changes(&conn, snapshot, &programs, &cached_titles);
+        notify_program_changes(&config.notify, &changes).await;
+    }
+
+    if deferred_tids.is_empty() {
+        if let Some(scope) = params.scope.as_deref() {
+            advance_sync_cursor(&conn, scope, &programs)?;
+        }
+    } else if let Some(range) = range {
+        let payload = SyncJobPayload {
+            tids: deferred_tids,
+            time_since: range.start.format("%Y-%m-%d %H:%M:%S").to_string(),
+            time_until: range.end.format("%Y-%m-%d %H:%M:%S").to_string(),
+            ch_ids: Some(params.ch_ids.clone()),
+            categories: params.categories.clone(),
+        };
+        defer_sync_remainder(&conn, &payload, &budget)?;
+    } else {
+        tracing::warn!(
+            deferred_tids = deferred_tids.len(),
+            "Retry budget exhausted; sync cursor kept so the next incremental sync refetches them"
+        );
+        exit::mark(
+            ExitStatus::PartialSync,
+            format!(
+                "retry budget exhausted; {} titles left for the next incremental sync",
+                deferred_tids.len()
+            ),
+        );
+    }
 
     tracing::info!(
         "Sync complete: {} titles ({} changed), {} programs ({} changed)",
@@ -1034,6 +2994,92 @@ async fn run_db_sync(args: &DbSyncArgs, config_file: Option<&PathBuf>) -> Result
         }
     }
 
+    Ok(SyncSummary {
+        run_id,
+        titles: cached_titles.len(),
+        titles_changed,
+        programs: total_programs,
+        programs_changed,
+    })
+}
+
+/// Returns the `sync_state` scope of an incremental sync over `ch_ids`
+/// (and `tids`, when restricted). IDs are sorted so argument order does not
+/// matter.
+fn sync_scope(ch_ids: &[u32], tids: Option<&[u32]>) -> String {
+    let join = |ids: &[u32]| {
+        let sorted: BTreeSet<u32> = ids.iter().copied().collect();
+        sorted
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let mut scope = format!("programs:ch={}", join(ch_ids));
+    if let Some(tids) = tids {
+        scope.push_str(";tid=");
+        scope.push_str(&join(tids));
+    }
+    scope
+}
+
+/// Moves the incremental sync cursor of `scope` to the newest `LastUpdate`
+/// among `programs`. Leaves it unchanged when no program carries one.
+///
+/// # Errors
+///
+/// Returns an error if the cursor cannot be saved.
+fn advance_sync_cursor(
+    conn: &dtvmgr_db::Connection,
+    scope: &str,
+    programs: &[SyoboiProgram],
+) -> Result<()> {
+    let Some(newest) = programs
+        .iter()
+        .filter_map(|p| p.last_update.as_deref())
+        .max()
+    else {
+        tracing::info!(scope, "No updated programs; sync cursor unchanged");
+        return Ok(());
+    };
+    save_sync_cursor(conn, scope, newest).context("failed to save sync cursor")?;
+    tracing::info!(scope, cursor = newest, "Sync cursor advanced");
+    Ok(())
+}
+
+/// Reports a partial sync and queues a `sync` job for the deferred TIDs.
+///
+/// # Errors
+///
+/// Returns an error if the payload cannot be serialized or the job cannot be queued.
+fn defer_sync_remainder(
+    conn: &dtvmgr_db::Connection,
+    payload: &SyncJobPayload,
+    budget: &RetryBudget,
+) -> Result<()> {
+    let json = serde_json::to_string(payload).context("failed to serialize sync job payload")?;
+    let run_at = Utc::now()
+        .checked_add_signed(chrono::TimeDelta::seconds(SYNC_DEFER_DELAY_SECS))
+        .context("deferred sync time out of range")?;
+    let id = JobQueue::new(conn)
+        .enqueue(JobKind::Sync, Some(&json), run_at)
+        .context("failed to queue deferred sync job")?;
+    tracing::warn!(
+        deferred_tids = payload.tids.len(),
+        retries = budget.retries(),
+        max_retries = budget.max_retries(),
+        slept_secs = budget.slept().as_secs(),
+        max_sleep_secs = budget.max_sleep().as_secs(),
+        job_id = id,
+        "Sync finished early (retry budget exhausted); remaining titles queued as sync job #{id}"
+    );
+    exit::mark(
+        ExitStatus::PartialSync,
+        format!(
+            "retry budget exhausted; {} titles queued as sync job #{id}",
+            payload.tids.len()
+        ),
+    );
     Ok(())
 }
 
@@ -1081,6 +3127,19 @@ fn compile_regex_titles(patterns: &[String]) -> Option<regex::Regex> {
         .ok()
 }
 
+/// Parses `[viewer] hidden_program_columns` keys, skipping unknown ones.
+fn parse_hidden_columns(keys: &[String]) -> Vec<ProgramColumn> {
+    keys.iter()
+        .filter_map(|key| {
+            let column = ProgramColumn::from_key(key);
+            if column.is_none() {
+                tracing::warn!(column = %key, "Unknown program column in hidden_program_columns");
+            }
+            column
+        })
+        .collect()
+}
+
 /// Regex to extract the first number from matched text.
 #[allow(clippy::expect_used)]
 static FIRST_DIGIT_RE: LazyLock<regex::Regex> =
@@ -1145,86 +3204,450 @@ fn resolve_media_type(cat: Option<u32>, cat_movie: &HashSet<u32>) -> TmdbMediaTy
     }
 }
 
-/// Fetches alternative titles and builds a `LookupOutcome::Success`.
+/// Runs the `tmdb auto-match` subcommand.
+///
+/// Searches TMDB for every unmapped title (excluding `[syoboi.titles]`
+/// excludes and movie categories), applies confident matches, and writes the
+/// ambiguous / not found titles with their candidates to a JSON report.
+///
+/// # Errors
+///
+/// Returns an error if config, DB, or report operations fail.
 #[instrument(skip_all, err(level = "error"))]
-async fn fetch_alt_and_build_outcome(
-    tmdb_client: &TmdbClient,
-    tid: u32,
-    media_type: TmdbMediaType,
-    tmdb_id: u64,
-    original_name: &str,
-    name: &str,
-) -> Result<LookupOutcome> {
-    let alt_titles = match tmdb_client.alternative_titles(media_type, tmdb_id).await {
-        Ok(r) => r,
-        Err(e) => {
-            tracing::error!(
-                tid, tmdb_id, error = %e, "Failed to fetch alternative titles"
-            );
-            return Ok(LookupOutcome::Error);
-        }
+#[allow(clippy::too_many_lines, clippy::future_not_send)]
+async fn run_tmdb_auto_match(
+    args: &TmdbAutoMatchArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let data_dir = resolve_data_dir(config_file)
+        .context("failed to resolve data directory")?
+        .context("no data directory available")?;
+    let conn = open_db(Some(&data_dir)).context("failed to open database")?;
+    let titles = load_unmapped_tv_titles(&conn, args.tids.as_deref(), config_file)?;
+    if titles.is_empty() {
+        tracing::info!("No unmapped titles");
+        return Ok(());
+    }
+
+    let client = build_tmdb_client(config_file)?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+    let options = AutoMatchOptions {
+        threshold: args.threshold,
+        year_tolerance: args.year_tolerance,
     };
-    let alt_json =
-        serde_json::to_string(&alt_titles.results).context("failed to serialize alt titles")?;
+
+    let mut matched: Vec<AutoMatch> = Vec::new();
+    let mut unresolved: Vec<AutoMatch> = Vec::new();
+    let mut failed: usize = 0;
+    for title in &titles {
+        let result = match auto_match_title(&client, title, &language, options).await {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!(tid = title.tid, error = %e, "Auto-match search failed");
+                failed = failed.saturating_add(1);
+                continue;
+            }
+        };
+        let Some(best) = result.matched() else {
+            unresolved.push(result);
+            continue;
+        };
+        if !args.dry_run
+            && !store_tv_match(
+                &conn,
+                &client,
+                title.tid,
+                best.tmdb_id,
+                &best.original_name,
+                &best.name,
+            )
+            .await?
+        {
+            failed = failed.saturating_add(1);
+            continue;
+        }
+        tracing::info!(
+            tid = title.tid,
+            tmdb_id = best.tmdb_id,
+            score = best.score,
+            "{} -> {}",
+            title.title,
+            best.name
+        );
+        matched.push(result);
+    }
+
+    let report = args
+        .report
+        .clone()
+        .unwrap_or_else(|| data_dir.join("auto-match-report.json"));
+    let json = serde_json::to_string_pretty(&unresolved).context("failed to serialize report")?;
+    std::fs::write(&report, json)
+        .with_context(|| format!("failed to write {}", report.display()))?;
+
+    if output.is_json() {
+        return write_json(&serde_json::json!({
+            "matched": matched,
+            "unresolved": unresolved,
+            "failed": failed,
+            "dry_run": args.dry_run,
+            "report": report,
+        }));
+    }
     tracing::info!(
-        tid,
-        tmdb_id,
-        original_name,
-        name,
-        alt_titles_count = alt_titles.results.len(),
-        "TMDB result matched ({media_type:?})"
+        "Auto-matched {} of {} title(s){} ({} unresolved, {failed} failed); report: {}",
+        matched.len(),
+        titles.len(),
+        if args.dry_run { " [dry run]" } else { "" },
+        unresolved.len(),
+        report.display()
     );
-    Ok(LookupOutcome::Success(
-        tmdb_id,
-        original_name.to_owned(),
-        name.to_owned(),
-        alt_json,
-        None,
-    ))
+    Ok(())
 }
 
-/// Searches TMDB with a query and returns the first matching result.
+/// Header written at the top of a `tmdb suggest` file.
+const SUGGESTIONS_HEADER: &str = "\
+# TMDB mapping suggestions written by `dtvmgr tmdb suggest`.
+# Review each [[suggestions]] entry: set `approve = true` to accept `tmdb_id`
+# (the best candidate), or replace `tmdb_id` with another series ID first.
+# Then run `dtvmgr tmdb apply <this file>`.
+
+";
+
+/// Runs the `tmdb suggest` subcommand.
 ///
-/// Returns `Ok(Some(LookupOutcome::Success(...)))` on match,
-/// `Ok(None)` when no result passes filters (caller should try next query),
-/// or `Ok(Some(LookupOutcome::Error))` / `Err` on API failure.
+/// Scores TMDB candidates for every unmapped title (same selection as
+/// `tmdb auto-match`) and writes them to a TOML file for review. Nothing is
+/// stored in the DB.
+///
+/// # Errors
+///
+/// Returns an error if config, DB, or file operations fail.
 #[instrument(skip_all, err(level = "error"))]
-async fn search_tmdb_filtered(
-    tmdb_client: &TmdbClient,
-    query: &str,
-    language: &str,
-    tid: u32,
-    expected_type: TmdbMediaType,
-    check_animation: bool,
-) -> Result<Option<LookupOutcome>> {
-    let mut page = 1u32;
+async fn run_tmdb_suggest(
+    args: &TmdbSuggestArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+    let titles = load_unmapped_tv_titles(&conn, args.tids.as_deref(), config_file)?;
+    if titles.is_empty() {
+        tracing::info!("No unmapped titles");
+        return Ok(());
+    }
 
-    loop {
-        let params = SearchMultiParams::new(query).language(language).page(page);
-        let search_result = match tmdb_client.search_multi(&params).await {
-            Ok(r) => r,
+    let client = build_tmdb_client(config_file)?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+    let options = AutoMatchOptions {
+        threshold: args.threshold,
+        year_tolerance: args.year_tolerance,
+    };
+
+    let mut file = SuggestionsFile::default();
+    let mut failed: usize = 0;
+    for title in &titles {
+        match auto_match_title(&client, title, &language, options).await {
+            Ok(result) => file
+                .suggestions
+                .push(Suggestion::from_match(result, args.approve_matched)),
             Err(e) => {
-                tracing::error!(tid, error = %e, "TMDB search failed");
-                return Ok(Some(LookupOutcome::Error));
+                tracing::warn!(tid = title.tid, error = %e, "Suggestion search failed");
+                failed = failed.saturating_add(1);
             }
-        };
-
-        if search_result.results.is_empty() {
-            return Ok(None);
         }
+    }
 
-        for result in &search_result.results {
-            match result {
-                TmdbMultiSearchResult::Tv(tv) if expected_type == TmdbMediaType::Tv => {
-                    let animation =
-                        !check_animation || tv.genre_ids.contains(&TMDB_GENRE_ANIMATION);
-                    let lang_ja = tv.original_language == "ja";
-                    tracing::info!(
-                        tid, tmdb_id = tv.id, name = %tv.name,
-                        animation, lang_ja,
-                        matched = animation, "Filter check (TV)"
-                    );
-                    if animation {
+    let body = toml::to_string_pretty(&file).context("failed to serialize suggestions")?;
+    std::fs::write(&args.out, format!("{SUGGESTIONS_HEADER}{body}"))
+        .with_context(|| format!("failed to write {}", args.out.display()))?;
+
+    let approved = file
+        .suggestions
+        .iter()
+        .filter(|s| s.approved().is_some())
+        .count();
+    if output.is_json() {
+        return write_json(&serde_json::json!({
+            "suggested": file.suggestions.len(),
+            "approved": approved,
+            "failed": failed,
+            "file": args.out,
+        }));
+    }
+    tracing::info!(
+        "Wrote suggestions for {} of {} title(s) ({approved} pre-approved, {failed} failed) to {}",
+        file.suggestions.len(),
+        titles.len(),
+        args.out.display()
+    );
+    Ok(())
+}
+
+/// Runs the `tmdb apply` subcommand.
+///
+/// Stores the approved entries of a `tmdb suggest` file. TIDs missing from
+/// the cache are skipped; titles that already have a different mapping are
+/// kept unless `--overwrite` is given. Series IDs that are not among the
+/// listed candidates are looked up on TMDB for their names.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, or DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::too_many_lines, clippy::future_not_send)]
+async fn run_tmdb_apply(
+    args: &TmdbApplyArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let content = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("failed to read {}", args.file.display()))?;
+    let file: SuggestionsFile = toml::from_str(&content)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+    let approved: Vec<&Suggestion> = file
+        .suggestions
+        .iter()
+        .filter(|s| s.approved().is_some())
+        .collect();
+
+    let conn = open_data_db(config_file)?;
+    let tids: Vec<u32> = approved.iter().map(|s| s.tid).collect();
+    let cached: std::collections::HashMap<u32, CachedTitle> = load_titles_by_tids(&conn, &tids)
+        .context("failed to load titles")?
+        .into_iter()
+        .map(|t| (t.tid, t))
+        .collect();
+
+    let needs_client = !args.dry_run || approved.iter().any(|s| s.chosen().is_none());
+    let client = if needs_client && !approved.is_empty() {
+        Some(build_tmdb_client(config_file)?)
+    } else {
+        None
+    };
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+
+    let mut applied: Vec<serde_json::Value> = Vec::new();
+    let mut unchanged: usize = 0;
+    let mut unknown_tids: Vec<u32> = Vec::new();
+    let mut conflicts: Vec<u32> = Vec::new();
+    let mut failed: usize = 0;
+    for suggestion in &approved {
+        let tid = suggestion.tid;
+        let tmdb_id = suggestion.tmdb_id;
+        let Some(title) = cached.get(&tid) else {
+            unknown_tids.push(tid);
+            continue;
+        };
+        match title.tmdb_series_id {
+            Some(current) if current == tmdb_id => {
+                unchanged = unchanged.saturating_add(1);
+                continue;
+            }
+            Some(_) if !args.overwrite => {
+                conflicts.push(tid);
+                continue;
+            }
+            _ => {}
+        }
+
+        let (original_name, name) = if let Some(candidate) = suggestion.chosen() {
+            (candidate.original_name.clone(), candidate.name.clone())
+        } else {
+            let Some(client) = client.as_ref() else {
+                continue;
+            };
+            match client.tv_details(tmdb_id, &language).await {
+                Ok(details) => (details.original_name, details.name),
+                Err(e) => {
+                    tracing::warn!(tid, tmdb_id, error = %e, "Failed to fetch TV details");
+                    failed = failed.saturating_add(1);
+                    continue;
+                }
+            }
+        };
+        if !args.dry_run
+            && let Some(client) = client.as_ref()
+            && !store_tv_match(&conn, client, tid, tmdb_id, &original_name, &name).await?
+        {
+            failed = failed.saturating_add(1);
+            continue;
+        }
+        tracing::info!(tid, tmdb_id, "{} -> {name}", title.title);
+        applied.push(serde_json::json!({ "tid": tid, "tmdb_id": tmdb_id, "name": name }));
+    }
+
+    if output.is_json() {
+        return write_json(&serde_json::json!({
+            "applied": applied,
+            "unchanged": unchanged,
+            "unknown_tids": unknown_tids,
+            "conflicts": conflicts,
+            "failed": failed,
+            "dry_run": args.dry_run,
+        }));
+    }
+    if !unknown_tids.is_empty() {
+        tracing::warn!(
+            tids = ?unknown_tids,
+            "Skipped {} title(s) not in the cache; run `db sync` for them and apply again",
+            unknown_tids.len()
+        );
+    }
+    if !conflicts.is_empty() {
+        tracing::warn!(
+            tids = ?conflicts,
+            "Kept {} title(s) with a different mapping; use --overwrite to replace them",
+            conflicts.len()
+        );
+    }
+    tracing::info!(
+        "Applied {} of {} approved suggestion(s){} ({unchanged} unchanged, {failed} failed)",
+        applied.len(),
+        approved.len(),
+        if args.dry_run { " [dry run]" } else { "" }
+    );
+    Ok(())
+}
+
+/// Loads the titles `tmdb auto-match` / `tmdb suggest` work on: cached TV
+/// titles without a series mapping, minus `[syoboi.titles]` excludes.
+///
+/// # Errors
+///
+/// Returns an error if the config or the titles cannot be loaded.
+fn load_unmapped_tv_titles(
+    conn: &dtvmgr_db::Connection,
+    tids: Option<&[u32]>,
+    config_file: Option<&PathBuf>,
+) -> Result<Vec<CachedTitle>> {
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let excluded: HashSet<u32> = config.syoboi.titles.excludes.iter().copied().collect();
+    let cat_movie: HashSet<u32> = config.syoboi.titles.cat_movie.iter().copied().collect();
+
+    Ok(tids
+        .map_or_else(|| load_titles(conn), |tids| load_titles_by_tids(conn, tids))
+        .context("failed to load titles")?
+        .into_iter()
+        .filter(|t| t.tmdb_series_id.is_none() && !excluded.contains(&t.tid))
+        .filter(|t| resolve_media_type(t.cat, &cat_movie) == TmdbMediaType::Tv)
+        .collect())
+}
+
+/// Stores a TV series mapping with its alternative titles and
+/// localizations. Returns `false` (after logging) when the alternative
+/// titles cannot be fetched; nothing is stored then.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+#[allow(clippy::future_not_send)]
+async fn store_tv_match(
+    conn: &dtvmgr_db::Connection,
+    client: &TmdbClient,
+    tid: u32,
+    tmdb_id: u64,
+    original_name: &str,
+    name: &str,
+) -> Result<bool> {
+    let alt_titles = match client.alternative_titles(TmdbMediaType::Tv, tmdb_id).await {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!(tid, tmdb_id, error = %e, "Failed to fetch alternative titles");
+            return Ok(false);
+        }
+    };
+    let alt_json =
+        serde_json::to_string(&alt_titles.results).context("failed to serialize alt titles")?;
+    let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    update_tmdb_search_result(conn, tid, tmdb_id, original_name, name, &alt_json, &now)
+        .with_context(|| format!("failed to update TMDB result for tid {tid}"))?;
+    let localizations = fetch_title_localizations(client, tid, TmdbMediaType::Tv, tmdb_id).await;
+    store_title_localizations(conn, &localizations)?;
+    Ok(true)
+}
+
+/// Fetches alternative titles and builds a `LookupOutcome::Success`.
+#[instrument(skip_all, err(level = "error"))]
+async fn fetch_alt_and_build_outcome(
+    tmdb_client: &TmdbClient,
+    tid: u32,
+    media_type: TmdbMediaType,
+    tmdb_id: u64,
+    original_name: &str,
+    name: &str,
+) -> Result<LookupOutcome> {
+    let alt_titles = match tmdb_client.alternative_titles(media_type, tmdb_id).await {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::error!(
+                tid, tmdb_id, error = %e, "Failed to fetch alternative titles"
+            );
+            return Ok(LookupOutcome::Error);
+        }
+    };
+    let alt_json =
+        serde_json::to_string(&alt_titles.results).context("failed to serialize alt titles")?;
+    tracing::info!(
+        tid,
+        tmdb_id,
+        original_name,
+        name,
+        alt_titles_count = alt_titles.results.len(),
+        "TMDB result matched ({media_type:?})"
+    );
+    Ok(LookupOutcome::Success(
+        tmdb_id,
+        original_name.to_owned(),
+        name.to_owned(),
+        alt_json,
+        None,
+    ))
+}
+
+/// Searches TMDB with a query and returns the first matching result.
+///
+/// Returns `Ok(Some(LookupOutcome::Success(...)))` on match,
+/// `Ok(None)` when no result passes filters (caller should try next query),
+/// or `Ok(Some(LookupOutcome::Error))` / `Err` on API failure.
+#[instrument(skip_all, err(level = "error"))]
+async fn search_tmdb_filtered(
+    tmdb_client: &TmdbClient,
+    query: &str,
+    language: &str,
+    tid: u32,
+    expected_type: TmdbMediaType,
+    check_animation: bool,
+) -> Result<Option<LookupOutcome>> {
+    let mut page = 1u32;
+
+    loop {
+        let params = SearchMultiParams::new(query).language(language).page(page);
+        let search_result = match tmdb_client.search_multi(&params).await {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::error!(tid, error = %e, "TMDB search failed");
+                return Ok(Some(LookupOutcome::Error));
+            }
+        };
+
+        if search_result.results.is_empty() {
+            return Ok(None);
+        }
+
+        for result in &search_result.results {
+            match result {
+                TmdbMultiSearchResult::Tv(tv) if expected_type == TmdbMediaType::Tv => {
+                    let animation =
+                        !check_animation || tv.genre_ids.contains(&TMDB_GENRE_ANIMATION);
+                    let lang_ja = tv.original_language == "ja";
+                    tracing::info!(
+                        tid, tmdb_id = tv.id, name = %tv.name,
+                        animation, lang_ja,
+                        matched = animation, "Filter check (TV)"
+                    );
+                    if animation {
                         let outcome = fetch_alt_and_build_outcome(
                             tmdb_client,
                             tid,
@@ -1408,7 +3831,7 @@ fn is_within_cooldown(tmdb_last_updated: Option<&str>) -> bool {
 /// Filters titles based on `--force` and `--retry-unmapped` flags.
 ///
 /// - `force`: return all titles (skip no filtering).
-/// - `retry_unmapped`: return only titles with `tmdb_series_id IS NULL` (ignore cooldown).
+/// - `retry_unmapped`: return only titles without a series or movie mapping (ignore cooldown).
 /// - default: skip titles within the cooldown period.
 fn filter_titles(titles: Vec<CachedTitle>, force: bool, retry_unmapped: bool) -> Vec<CachedTitle> {
     if force {
@@ -1417,7 +3840,7 @@ fn filter_titles(titles: Vec<CachedTitle>, force: bool, retry_unmapped: bool) ->
     if retry_unmapped {
         return titles
             .into_iter()
-            .filter(|t| t.tmdb_series_id.is_none())
+            .filter(|t| t.tmdb_series_id.is_none() && t.tmdb_movie_id.is_none())
             .collect();
     }
     titles
@@ -1435,10 +3858,24 @@ fn filter_titles(titles: Vec<CachedTitle>, force: bool, retry_unmapped: bool) ->
 ///
 /// Returns an error if API calls or DB operations fail.
 #[instrument(skip_all, err(level = "error"))]
-#[allow(clippy::too_many_lines)]
 async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
-    let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+    let pool = open_data_pool(config_file)?;
+    tmdb_lookup(args, &pool, config_file).await
+}
+
+/// Looks up cached titles on TMDB (see [`run_db_tmdb_lookup`]) on a
+/// connection from `pool`.
+///
+/// # Errors
+///
+/// Returns an error if API calls or DB operations fail.
+#[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
+async fn tmdb_lookup(
+    args: &DbTmdbLookupArgs,
+    pool: &DbPool,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let conn = pool.get().context("failed to open database")?;
     let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
     let config = AppConfig::load(&config_path).context("failed to load config")?;
 
@@ -1471,6 +3908,7 @@ async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBu
 
     if titles.is_empty() {
         tracing::info!("No titles to process");
+        exit::mark(ExitStatus::NothingToDo, "no titles to look up");
         return Ok(());
     }
 
@@ -1498,6 +3936,36 @@ async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBu
         let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
 
         // Check manual mapping first
+        if let Some(entry) = mapping_index.get(&title.tid)
+            && let Some(movie_id) = entry.tmdb_movie_id.filter(|id| *id > 0)
+        {
+            update_tmdb_movie_mapping(&conn, title.tid, Some(movie_id)).with_context(|| {
+                format!("failed to apply manual movie mapping for tid {}", title.tid)
+            })?;
+            update_tmdb_last_updated(&conn, title.tid, &now).with_context(|| {
+                format!("failed to update tmdb_last_updated for tid {}", title.tid)
+            })?;
+            let localizations =
+                fetch_title_localizations(&tmdb_client, title.tid, TmdbMediaType::Movie, movie_id)
+                    .await;
+            store_title_localizations(&conn, &localizations)?;
+            tracing::info!(
+                tid = title.tid,
+                tmdb_movie_id = movie_id,
+                "Applied manual movie mapping"
+            );
+            mapped_count = mapped_count.saturating_add(1);
+
+            let current = i.saturating_add(1);
+            #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
+            let pct = (current as f64 / total as f64) * 100.0;
+            let miss = skip_count.saturating_add(error_count);
+            tracing::info!(
+                "{current:0>width$}/{total:0>width$} ({pct:06.2}%), match={}, miss={miss}",
+                success_count.saturating_add(mapped_count),
+            );
+            continue;
+        }
         if let Some(entry) = mapping_index.get(&title.tid)
             && entry.tmdb_series_id > 0
         {
@@ -1534,9 +4002,22 @@ async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBu
                 resolved_season_id,
             )
             .with_context(|| format!("failed to apply manual mapping for tid {}", title.tid))?;
+            if let Some(group_id) = entry.tmdb_episode_group_id.as_deref() {
+                update_tmdb_episode_group(&conn, title.tid, Some(group_id)).with_context(|| {
+                    format!("failed to apply manual episode group for tid {}", title.tid)
+                })?;
+            }
             update_tmdb_last_updated(&conn, title.tid, &now).with_context(|| {
                 format!("failed to update tmdb_last_updated for tid {}", title.tid)
             })?;
+            let localizations = fetch_title_localizations(
+                &tmdb_client,
+                title.tid,
+                TmdbMediaType::Tv,
+                entry.tmdb_series_id,
+            )
+            .await;
+            store_title_localizations(&conn, &localizations)?;
             tracing::info!(
                 tid = title.tid,
                 tmdb_series_id = entry.tmdb_series_id,
@@ -1567,7 +4048,13 @@ async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBu
         .await?
         {
             LookupOutcome::Success(tmdb_id, original_name, name, alt_json, season_info) => {
-                update_tmdb_search_result(
+                let media_type = resolve_media_type(title.cat, &cat_movie);
+                let store = if media_type == TmdbMediaType::Movie {
+                    update_tmdb_movie_search_result
+                } else {
+                    update_tmdb_search_result
+                };
+                store(
                     &conn,
                     title.tid,
                     tmdb_id,
@@ -1590,6 +4077,9 @@ async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBu
                         "Season number saved"
                     );
                 }
+                let localizations =
+                    fetch_title_localizations(&tmdb_client, title.tid, media_type, tmdb_id).await;
+                store_title_localizations(&conn, &localizations)?;
                 tracing::info!(tid = title.tid, tmdb_id, "TMDB result saved");
                 success_count = success_count.saturating_add(1);
             }
@@ -1689,6 +4179,79 @@ async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBu
     Ok(())
 }
 
+/// Fetches the TMDB name and overview of a mapped title in every
+/// [`MetadataLanguage`]. Failed requests are logged and skipped so the
+/// mapping itself is still stored.
+async fn fetch_title_localizations(
+    client: &TmdbClient,
+    tid: u32,
+    media_type: TmdbMediaType,
+    tmdb_id: u64,
+) -> Vec<TitleLocalization> {
+    let mut localizations = Vec::new();
+    for lang in MetadataLanguage::ALL {
+        let language = lang.tmdb_language();
+        let fetched = match media_type {
+            TmdbMediaType::Movie => client
+                .movie_details(tmdb_id, language)
+                .await
+                .map(|d| (d.title, d.overview)),
+            TmdbMediaType::Tv => client
+                .tv_details(tmdb_id, language)
+                .await
+                .map(|d| (d.name, d.overview)),
+        };
+        match fetched {
+            Ok((name, overview)) => localizations.push(TitleLocalization {
+                tid,
+                language: language.to_owned(),
+                name,
+                overview: overview.filter(|o| !o.is_empty()),
+            }),
+            Err(e) => {
+                tracing::warn!(tid, tmdb_id, language, error = %e, "Failed to fetch localized name");
+            }
+        }
+    }
+    localizations
+}
+
+/// Stores localizations fetched by [`fetch_title_localizations`].
+///
+/// # Errors
+///
+/// Returns an error if a database write fails.
+fn store_title_localizations(
+    conn: &dtvmgr_db::Connection,
+    localizations: &[TitleLocalization],
+) -> Result<()> {
+    let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    for loc in localizations {
+        upsert_title_localization(conn, loc, &now)?;
+    }
+    Ok(())
+}
+
+/// Loads the stored localizations in `language` keyed by TID (empty when
+/// no language is selected).
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+fn load_localizations(
+    conn: &dtvmgr_db::Connection,
+    language: Option<MetadataLanguage>,
+) -> Result<std::collections::HashMap<u32, TitleLocalization>> {
+    let Some(language) = language else {
+        return Ok(std::collections::HashMap::new());
+    };
+    Ok(load_title_localizations(conn, language.tmdb_language())
+        .context("failed to load title localizations")?
+        .into_iter()
+        .map(|l| (l.tid, l))
+        .collect())
+}
+
 /// Builds a `TmdbClient` from `TMDB_API_TOKEN` env var with config file fallback.
 ///
 /// # Errors
@@ -1699,70 +4262,171 @@ async fn run_db_tmdb_lookup(args: &DbTmdbLookupArgs, config_file: Option<&PathBu
 fn build_tmdb_client(config_file: Option<&PathBuf>) -> Result<TmdbClient> {
     let api_token = if let Ok(token) = std::env::var("TMDB_API_TOKEN") {
         token
+    } else if CASSETTE.get().is_some_and(Cassette::is_replay) {
+        // Replayed requests never reach TMDB, so no token is needed.
+        String::from("replay")
     } else {
         let config_path =
             resolve_config_path(config_file).context("failed to resolve config path")?;
         let config = AppConfig::load(&config_path).context("failed to load config")?;
-        config
-            .tmdb
-            .api_key
-            .context("TMDB_API_TOKEN env var is not set and tmdb.api_key is not configured")?
+        config.tmdb.api_key.context(ConfigError(String::from(
+            "TMDB_API_TOKEN env var is not set and tmdb.api_key is not configured",
+        )))?
     };
 
-    TmdbClient::builder()
+    let mut builder = TmdbClient::builder()
         .api_token(api_token)
         .user_agent(concat!(
             env!("CARGO_PKG_NAME"),
             "/",
             env!("CARGO_PKG_VERSION")
-        ))
-        .build()
-        .context("failed to build TMDB client")
-}
-
-/// Resolves TMDB language: CLI arg > config > "en-US".
-fn resolve_tmdb_language(cli_lang: Option<&str>, config_file: Option<&PathBuf>) -> String {
-    if let Some(lang) = cli_lang {
-        return lang.to_owned();
+        ));
+    if let Some(log) = AUDIT_LOG.get() {
+        builder = builder.audit_log(log.clone());
     }
-    if let Ok(config_path) = resolve_config_path(config_file)
-        && let Ok(config) = AppConfig::load(&config_path)
-        && let Some(lang) = config.tmdb.language
-    {
-        return lang;
+    if let Some(limits) = RATE_LIMITS.get() {
+        builder = builder.rate_limit(limits.tmdb.apply(dtvmgr_api::tmdb::default_rate_limit()));
+    }
+    if let Some(options) = HTTP_OPTIONS.get() {
+        builder = builder.http_options(options.clone());
+    }
+    if let Some(cassette) = CASSETTE.get() {
+        builder = builder.cassette(cassette.clone());
+    }
+    if let Ok(url) = std::env::var(TMDB_URL_ENV) {
+        builder = builder.base_url(
+            reqwest::Url::parse(&url).with_context(|| format!("invalid {TMDB_URL_ENV}"))?,
+        );
     }
-    String::from("en-US")
+    builder.build().context("failed to build TMDB client")
 }
 
-/// Runs the `tmdb search-tv` subcommand (internally uses `search/multi`).
-///
-/// # Errors
+/// Resolved TMDB language and region shared by all TMDB calls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TmdbLocale {
+    /// Response language (IETF tag, e.g. "ja-JP").
+    language: String,
+    /// Region (ISO 3166-1, e.g. "JP").
+    region: String,
+}
+
+/// Resolves TMDB language and region: CLI arg > `[tmdb]` config > defaults.
 ///
-/// Returns an error if the TMDB client fails to build or the API request fails.
-#[instrument(skip_all, err(level = "error"))]
-async fn run_tmdb_search_tv(args: &TmdbSearchTvArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let client = build_tmdb_client(config_file)?;
+/// A language without a region subtag (e.g. "ja") is completed with the
+/// resolved region so every call sends a full tag.
+fn resolve_tmdb_locale(
+    cli_lang: Option<&str>,
+    cli_region: Option<&str>,
+    config_file: Option<&PathBuf>,
+) -> TmdbLocale {
+    let config = if cli_lang.is_some() && cli_region.is_some() {
+        None
+    } else {
+        resolve_config_path(config_file)
+            .ok()
+            .and_then(|path| AppConfig::load(&path).ok())
+    };
+    let (config_lang, config_region) =
+        config.map_or((None, None), |c| (c.tmdb.language, c.tmdb.region));
+    let region = cli_region
+        .map(str::to_owned)
+        .or(config_region)
+        .unwrap_or_else(|| String::from(DEFAULT_TMDB_REGION));
+    let language = cli_lang
+        .map(str::to_owned)
+        .or(config_lang)
+        .unwrap_or_else(|| String::from(DEFAULT_TMDB_LANGUAGE));
+    TmdbLocale {
+        language: tmdb_language_tag(&language, &region),
+        region,
+    }
+}
+
+/// Completes a bare ISO 639-1 language with the region (e.g. "ja" + "JP" → "ja-JP").
+fn tmdb_language_tag(language: &str, region: &str) -> String {
+    if language.is_empty() || language.contains('-') || region.is_empty() {
+        language.to_owned()
+    } else {
+        format!("{language}-{region}")
+    }
+}
+
+/// Resolves TMDB language: CLI arg > config > "ja-JP".
+fn resolve_tmdb_language(cli_lang: Option<&str>, config_file: Option<&PathBuf>) -> String {
+    resolve_tmdb_locale(cli_lang, None, config_file).language
+}
+
+/// Resolves TMDB region: CLI arg > config > "JP".
+fn resolve_tmdb_region(cli_region: Option<&str>, config_file: Option<&PathBuf>) -> String {
+    resolve_tmdb_locale(None, cli_region, config_file).region
+}
+
+/// Formats watch providers as a comma-separated list ordered by display priority.
+fn format_watch_providers(providers: &[TmdbWatchProvider]) -> String {
+    if providers.is_empty() {
+        return String::from("-");
+    }
+    let mut sorted: Vec<&TmdbWatchProvider> = providers.iter().collect();
+    sorted.sort_by_key(|p| p.display_priority);
+    sorted
+        .iter()
+        .map(|p| p.provider_name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Runs the `tmdb search-tv` subcommand (internally uses `search/multi`).
+///
+/// # Errors
+///
+/// Returns an error if the TMDB client fails to build, the API request fails,
+/// or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_tmdb_search_tv(
+    args: &TmdbSearchTvArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = build_tmdb_client(config_file)?;
     let language = resolve_tmdb_language(args.language.as_deref(), config_file);
 
     let params = SearchMultiParams::new(&args.query).language(&language);
-    let response = client
-        .search_multi(&params)
-        .await
-        .context("TMDB search/multi request failed")?;
+    let (results, total_results) = if args.all_pages {
+        let results = search_tv_all(&client, &params)
+            .await
+            .context("TMDB search/multi request failed")?;
+        let total = u32::try_from(results.len()).unwrap_or(u32::MAX);
+        (results, total)
+    } else {
+        let response = client
+            .search_multi(&params)
+            .await
+            .context("TMDB search/multi request failed")?;
+        let results: Vec<TmdbTvSearchResult> = response
+            .results
+            .into_iter()
+            .filter_map(|r| match r {
+                TmdbMultiSearchResult::Tv(tv) => Some(tv),
+                _ => None,
+            })
+            .collect();
+        (results, response.total_results)
+    };
+    if output.is_json() {
+        return write_json(&results);
+    }
 
-    tracing::info!("Total results: {}", response.total_results);
+    tracing::info!("Total results: {total_results}");
     tracing::info!("ID\tName\t\t\tOrigLang\tCountry\t\tFirstAirDate");
-    for result in &response.results {
-        if let TmdbMultiSearchResult::Tv(tv) = result {
-            tracing::info!(
-                "{}\t\t{}\t{}\t\t{}\t\t{}",
-                tv.id,
-                tv.name,
-                tv.original_language,
-                tv.origin_country.join(","),
-                tv.first_air_date.as_deref().unwrap_or("-"),
-            );
-        }
+    for tv in &results {
+        tracing::info!(
+            "{}\t\t{}\t{}\t\t{}\t\t{}",
+            tv.id,
+            tv.name,
+            tv.original_language,
+            tv.origin_country.join(","),
+            tv.first_air_date.as_deref().unwrap_or("-"),
+        );
     }
 
     Ok(())
@@ -1772,33 +4436,53 @@ async fn run_tmdb_search_tv(args: &TmdbSearchTvArgs, config_file: Option<&PathBu
 ///
 /// # Errors
 ///
-/// Returns an error if the TMDB client fails to build or the API request fails.
+/// Returns an error if the TMDB client fails to build, the API request fails,
+/// or JSON output cannot be written.
 #[instrument(skip_all, err(level = "error"))]
 async fn run_tmdb_search_movie(
     args: &TmdbSearchMovieArgs,
     config_file: Option<&PathBuf>,
+    output: OutputFormat,
 ) -> Result<()> {
     let client = build_tmdb_client(config_file)?;
     let language = resolve_tmdb_language(args.language.as_deref(), config_file);
 
     let params = SearchMultiParams::new(&args.query).language(&language);
-    let response = client
-        .search_multi(&params)
-        .await
-        .context("TMDB search/multi request failed")?;
+    let (results, total_results) = if args.all_pages {
+        let results = search_movie_all(&client, &params)
+            .await
+            .context("TMDB search/multi request failed")?;
+        let total = u32::try_from(results.len()).unwrap_or(u32::MAX);
+        (results, total)
+    } else {
+        let response = client
+            .search_multi(&params)
+            .await
+            .context("TMDB search/multi request failed")?;
+        let results: Vec<TmdbMovieSearchResult> = response
+            .results
+            .into_iter()
+            .filter_map(|r| match r {
+                TmdbMultiSearchResult::Movie(movie) => Some(movie),
+                _ => None,
+            })
+            .collect();
+        (results, response.total_results)
+    };
+    if output.is_json() {
+        return write_json(&results);
+    }
 
-    tracing::info!("Total results: {}", response.total_results);
+    tracing::info!("Total results: {total_results}");
     tracing::info!("ID\tTitle\t\t\tOrigLang\tReleaseDate");
-    for result in &response.results {
-        if let TmdbMultiSearchResult::Movie(movie) = result {
-            tracing::info!(
-                "{}\t{}\t{}\t\t{}",
-                movie.id,
-                movie.title,
-                movie.original_language,
-                movie.release_date.as_deref().unwrap_or("-"),
-            );
-        }
+    for movie in &results {
+        tracing::info!(
+            "{}\t{}\t{}\t\t{}",
+            movie.id,
+            movie.title,
+            movie.original_language,
+            movie.release_date.as_deref().unwrap_or("-"),
+        );
     }
 
     Ok(())
@@ -1808,11 +4492,13 @@ async fn run_tmdb_search_movie(
 ///
 /// # Errors
 ///
-/// Returns an error if the TMDB client fails to build or the API request fails.
+/// Returns an error if the TMDB client fails to build, the API request fails,
+/// or JSON output cannot be written.
 #[instrument(skip_all, err(level = "error"))]
 async fn run_tmdb_tv_details(
     args: &TmdbTvDetailsArgs,
     config_file: Option<&PathBuf>,
+    output: OutputFormat,
 ) -> Result<()> {
     let client = build_tmdb_client(config_file)?;
     let language = resolve_tmdb_language(args.language.as_deref(), config_file);
@@ -1821,6 +4507,9 @@ async fn run_tmdb_tv_details(
         .tv_details(args.id, &language)
         .await
         .context("TMDB tv details request failed")?;
+    if output.is_json() {
+        return write_json(&details);
+    }
 
     tracing::info!("ID: {}", details.id);
     tracing::info!("Name: {}", details.name);
@@ -1845,13 +4534,59 @@ async fn run_tmdb_tv_details(
     Ok(())
 }
 
+/// Runs the `tmdb movie-details` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the TMDB client fails to build, the API request fails,
+/// or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_tmdb_movie_details(
+    args: &TmdbMovieDetailsArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = build_tmdb_client(config_file)?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+
+    let details = client
+        .movie_details(args.id, &language)
+        .await
+        .context("TMDB movie details request failed")?;
+    if output.is_json() {
+        return write_json(&details);
+    }
+
+    tracing::info!("ID: {}", details.id);
+    tracing::info!("Title: {}", details.title);
+    tracing::info!("Original Title: {}", details.original_title);
+    tracing::info!(
+        "Release Date: {}",
+        details.release_date.as_deref().unwrap_or("-")
+    );
+    tracing::info!("Status: {}", details.status.as_deref().unwrap_or("-"));
+    tracing::info!(
+        "Runtime: {}",
+        details
+            .runtime
+            .map_or_else(|| String::from("-"), |m| format!("{m} min"))
+    );
+
+    Ok(())
+}
+
 /// Runs the `tmdb tv-season` subcommand.
 ///
 /// # Errors
 ///
-/// Returns an error if the TMDB client fails to build or the API request fails.
+/// Returns an error if the TMDB client fails to build, the API request fails,
+/// or JSON output cannot be written.
 #[instrument(skip_all, err(level = "error"))]
-async fn run_tmdb_tv_season(args: &TmdbTvSeasonArgs, config_file: Option<&PathBuf>) -> Result<()> {
+async fn run_tmdb_tv_season(
+    args: &TmdbTvSeasonArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
     let client = build_tmdb_client(config_file)?;
     let language = resolve_tmdb_language(args.language.as_deref(), config_file);
 
@@ -1859,6 +4594,9 @@ async fn run_tmdb_tv_season(args: &TmdbTvSeasonArgs, config_file: Option<&PathBu
         .tv_season(args.id, args.season, &language)
         .await
         .context("TMDB tv season request failed")?;
+    if output.is_json() {
+        return write_json(&season);
+    }
 
     tracing::info!(
         "Season {}: {}",
@@ -1880,298 +4618,2622 @@ async fn run_tmdb_tv_season(args: &TmdbTvSeasonArgs, config_file: Option<&PathBu
     Ok(())
 }
 
-// ── jlse subcommands ──────────────────────────────────────────
-
-/// Resolves the `JlseConfig` from the app config.
+/// Runs the `tmdb tv-episode` subcommand.
 ///
 /// # Errors
 ///
-/// Returns an error if the jlse section is not configured.
-fn resolve_jlse_config(config_file: Option<&PathBuf>) -> Result<JlseConfig> {
-    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
-    let config = AppConfig::load(&config_path).context("failed to load config")?;
-    let jlse = config
-        .jlse
-        .context("jlse config not found in dtvmgr.toml; add [jlse.dirs] with jl, logo, result")?;
-    anyhow::ensure!(
-        jlse.dirs.is_configured(),
-        "jlse.dirs is not configured in dtvmgr.toml; set jl, logo, result paths"
+/// Returns an error if the TMDB client fails to build, the API request fails,
+/// or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_tmdb_tv_episode(
+    args: &TmdbTvEpisodeArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = build_tmdb_client(config_file)?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+
+    let episode = client
+        .tv_episode(args.id, args.season, args.episode, &language)
+        .await
+        .context("TMDB tv episode request failed")?;
+    if output.is_json() {
+        return write_json(&episode);
+    }
+
+    tracing::info!(
+        "S{:02}E{:02}: {} (id: {})",
+        episode.season_number,
+        episode.episode_number,
+        episode.name,
+        episode.id
     );
-    Ok(jlse)
+    tracing::info!(
+        "  air_date: {}, runtime: {}min",
+        episode.air_date.as_deref().unwrap_or("-"),
+        episode
+            .runtime
+            .map_or_else(|| String::from("-"), |r| r.to_string()),
+    );
+    if let Some(still) = episode.still_path.as_deref() {
+        let url = client
+            .image_url(still, "original")
+            .await
+            .context("failed to resolve TMDB image URL")?;
+        tracing::info!("  still: {url}");
+    }
+    if let Some(overview) = episode.overview.as_deref().filter(|o| !o.is_empty()) {
+        tracing::info!("  {overview}");
+    }
+    if !episode.guest_stars.is_empty() {
+        tracing::info!("Guest stars:");
+    }
+    for star in &episode.guest_stars {
+        tracing::info!(
+            "  {} as {}",
+            star.original_name.as_deref().unwrap_or(&star.name),
+            star.character.as_deref().unwrap_or("-"),
+        );
+    }
+
+    Ok(())
 }
 
-/// Environment variable name for channel name override.
+/// Runs the `tmdb episode-groups` subcommand.
 ///
-/// The original Node.js tool uses `CHNNELNAME` (intentional typo preserved).
-const CHANNEL_ENV_VAR: &str = "CHNNELNAME";
+/// # Errors
+///
+/// Returns an error if the TMDB client fails to build, the API request
+/// fails, or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_tmdb_episode_groups(
+    args: &TmdbEpisodeGroupsArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = build_tmdb_client(config_file)?;
 
-/// Resolve channel name from CLI argument or environment variable.
-fn resolve_channel_name(arg: Option<&str>) -> Option<String> {
-    arg.map(ToOwned::to_owned)
-        .or_else(|| std::env::var(CHANNEL_ENV_VAR).ok())
-}
+    let response = client
+        .tv_episode_groups(args.id)
+        .await
+        .context("TMDB tv episode groups request failed")?;
+    if output.is_json() {
+        return write_json(&response.results);
+    }
 
-/// Detect the recording target from the middle of a TS file.
-///
-/// Thin wrapper around [`dtvmgr_tsduck::detect_target_from_middle`] that
-/// discards the raw XML.
-fn detect_target_from_middle(
-    bin: &Path,
-    input: &Path,
-) -> Result<Option<dtvmgr_tsduck::eit::RecordingTarget>> {
-    let (target, _xml) = dtvmgr_tsduck::detect_target_from_middle(bin, input)?;
-    Ok(target)
+    if response.results.is_empty() {
+        tracing::info!("No episode groups for series {}", args.id);
+        return Ok(());
+    }
+    tracing::info!("id\ttype\tepisodes\tparts\tname");
+    for group in &response.results {
+        tracing::info!(
+            "{}\t{}\t{}\t{}\t{}",
+            group.id,
+            group.type_name(),
+            group.episode_count,
+            group.group_count,
+            group.name,
+        );
+    }
+
+    Ok(())
 }
 
-/// Runs the `jlse tsduck` subcommand.
+/// Runs the `tmdb episode-group` subcommand.
 ///
-/// Extracts EIT program information from a TS file using `TSDuck`.
-/// When `--sid` or `-c` is given, filters by that service ID using EIT-only
-/// extraction. Otherwise, extracts all tables (PAT + EIT) and auto-detects
-/// the recording target from PAT's first service ID.
+/// Lists the episodes in absolute order, the numbering `db tmdb-match`
+/// uses for titles mapped to the group.
 ///
 /// # Errors
 ///
-/// Returns an error if `TSDuck` fails or the EIT XML cannot be parsed.
-#[allow(clippy::print_stdout)]
+/// Returns an error if the TMDB client fails to build, the API request
+/// fails, or JSON output cannot be written.
 #[instrument(skip_all, err(level = "error"))]
-fn run_jlse_tsduck(args: &JlseTsduckArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let jlse_config = resolve_jlse_config(config_file)?;
-    let bins = BinaryPaths::from_config(&jlse_config);
+async fn run_tmdb_episode_group(
+    args: &TmdbEpisodeGroupArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let client = build_tmdb_client(config_file)?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
 
-    // --sid takes priority over -c (channel name detection).
-    let explicit_sid = if let Some(ref sid) = args.sid {
-        Some(sid.clone())
-    } else if args.channel.is_some() {
-        let data = DataPaths::from_config(&jlse_config);
-        let channels = load_jlse_channels(&data.channel_list)?;
-        let channel_name = resolve_channel_name(args.channel.as_deref());
-        let filepath = args.input.to_string_lossy();
-        let ch = detect_channel(&channels, &filepath, channel_name.as_deref());
-        if let Some(ref ch) = ch {
-            println!("=== Channel Detection ===");
-            println!("short: {}", ch.short);
-            println!("service_id: {}", ch.service_id);
-            println!();
-        }
-        ch.map(|c| c.service_id)
-    } else {
-        None
-    };
+    let group = client
+        .episode_group(&args.id, &language)
+        .await
+        .context("TMDB episode group request failed")?;
+    if output.is_json() {
+        return write_json(&group);
+    }
 
-    let programs = if let Some(ref sid) = explicit_sid {
-        // Explicit SID: extract EIT only (PID 0x12).
-        let xml = dtvmgr_tsduck::command::extract_eit(&bins.tstables, &args.input)?;
-        println!("=== EIT Program Information (SID: {sid}) ===");
-        dtvmgr_tsduck::eit::parse_eit_xml_by_sid(&xml, sid)
-            .with_context(|| format!("failed to parse EIT XML for SID {sid}"))?
-    } else {
-        // No explicit SID: extract PAT (PID 0) and EIT (PID 0x12) separately.
-        let pat_xml = dtvmgr_tsduck::command::extract_pat(&bins.tstables, &args.input)?;
-        let pat_sid = dtvmgr_tsduck::pat::parse_pat_first_service_id(&pat_xml)
-            .context("failed to parse PAT XML")?;
+    tracing::info!("{} ({})", group.name, group.type_name());
+    tracing::info!("Episodes:");
+    for ep in group.absolute_episodes() {
+        tracing::info!(
+            "  #{:03}: {} (season: {}, id: {}, air_date: {})",
+            ep.episode_number,
+            ep.name,
+            ep.season_number,
+            ep.id,
+            ep.air_date.as_deref().unwrap_or("-"),
+        );
+    }
 
-        let eit_xml = dtvmgr_tsduck::command::extract_eit(&bins.tstables, &args.input)?;
-        if let Some(sid) = pat_sid {
-            println!("=== EIT Program Information (SID: {sid} from PAT) ===");
-            let all =
-                dtvmgr_tsduck::eit::parse_eit_xml(&eit_xml).context("failed to parse EIT XML")?;
-            all.into_iter().filter(|p| p.service_id == sid).collect()
-        } else {
-            println!("=== EIT Program Information ===");
-            dtvmgr_tsduck::eit::parse_eit_xml(&eit_xml).context("failed to parse EIT XML")?
-        }
-    };
+    Ok(())
+}
 
-    let programs = dtvmgr_tsduck::eit::dedup_programs(programs);
+/// Runs the `tmdb watch-providers` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the TMDB client fails to build or the API request fails.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_tmdb_watch_providers(
+    args: &TmdbWatchProvidersArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let client = build_tmdb_client(config_file)?;
+    let region = resolve_tmdb_region(args.region.as_deref(), config_file);
 
-    // Detect recording target from middle-of-file EIT p/f.
-    let recording_target = detect_target_from_middle(&bins.tstables, &args.input);
-    let target_event_id = match &recording_target {
-        Ok(Some(target)) => {
-            println!(
-                "=== Recording Target: event_id={} ({:?}) ===",
-                target.program.event_id, target.detection_method
-            );
-            println!();
-            Some(target.program.event_id.clone())
-        }
-        Ok(None) => {
-            println!("=== Recording Target: not detected ===");
-            println!();
-            None
-        }
-        Err(e) => {
-            println!("=== Recording Target: detection failed ({e:#}) ===");
-            println!();
-            None
-        }
+    let response = client
+        .tv_watch_providers(args.id)
+        .await
+        .context("TMDB tv watch providers request failed")?;
+
+    let Some(availability) = response.region(&region) else {
+        tracing::info!("No watch providers for series {} in {region}", args.id);
+        return Ok(());
     };
 
-    for p in &programs {
-        print_program_info(p, target_event_id.as_deref());
-    }
-    Ok(())
-}
+    tracing::info!("Region: {region}");
+    tracing::info!(
+        "Flatrate: {}",
+        format_watch_providers(&availability.flatrate)
+    );
+    tracing::info!("Ads: {}", format_watch_providers(&availability.ads));
+    tracing::info!("Free: {}", format_watch_providers(&availability.free));
+    tracing::info!("Rent: {}", format_watch_providers(&availability.rent));
+    tracing::info!("Buy: {}", format_watch_providers(&availability.buy));
+    tracing::info!("Link: {}", availability.link.as_deref().unwrap_or("-"));
 
-/// Format a single program's information as a string.
-#[allow(clippy::unwrap_used)]
-fn format_program_info(
-    p: &dtvmgr_tsduck::eit::ProgramInfo,
-    target_event_id: Option<&str>,
-) -> String {
-    use std::fmt::Write as _;
+    Ok(())
+}
 
-    let mut out = String::new();
-    let marker = if target_event_id == Some(&p.event_id) {
-        "[recording_target] "
-    } else {
-        ""
-    };
-    writeln!(out, "--- {marker}event_id: {} ---", p.event_id).unwrap();
-    writeln!(out, "  service_id: {}", p.service_id).unwrap();
-    writeln!(out, "  start_time: {}", p.start_time).unwrap();
-    writeln!(
-        out,
-        "  duration: {} ({} min / {} sec)",
-        p.duration_raw,
-        p.duration_min(),
-        p.duration_sec
-    )
-    .unwrap();
-    writeln!(out, "  running_status: {}", p.running_status).unwrap();
-    if let Some(name) = &p.program_name {
-        writeln!(out, "  program_name: {name}").unwrap();
-    }
-    if let Some(desc) = &p.description {
-        writeln!(out, "  description: {desc}").unwrap();
-    }
-    if let Some(tt) = &p.table_type {
-        writeln!(out, "  table_type: {tt}").unwrap();
-    }
-    if let Some(ext) = p.extended() {
-        writeln!(out, "  extended: {ext}").unwrap();
+/// Result of comparing a title's TMDB mapping with `find` results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExternalIdCheck {
+    /// No TV series has the external ID.
+    NotFound,
+    /// The current mapping is among the results.
+    Matches(u64),
+    /// The current mapping differs from every result.
+    Mismatch(u64),
+    /// Unmapped title with exactly one result; import it.
+    Import(u64),
+    /// Unmapped title with several results; left unchanged.
+    Ambiguous,
+}
+
+/// Compares the current TMDB series mapping with the series found by ID.
+fn check_external_mapping(current: Option<u64>, found: &[u64]) -> ExternalIdCheck {
+    match (current, found) {
+        (_, []) => ExternalIdCheck::NotFound,
+        (Some(id), _) if found.contains(&id) => ExternalIdCheck::Matches(id),
+        (Some(id), _) => ExternalIdCheck::Mismatch(id),
+        (None, [id]) => ExternalIdCheck::Import(*id),
+        (None, _) => ExternalIdCheck::Ambiguous,
     }
-    if !p.raw_extended.is_empty() {
-        for (key, value) in &p.raw_extended {
-            writeln!(out, "  raw_extended[{key}]: {value}").unwrap();
+}
+
+/// Stores `external_id` on title `tid` and imports or cross-checks its
+/// TMDB series mapping against `response`.
+///
+/// # Errors
+///
+/// Returns an error if the title does not exist or a DB operation fails.
+fn apply_external_id(
+    config_file: Option<&PathBuf>,
+    tid: u32,
+    source: TmdbExternalSource,
+    external_id: &str,
+    response: &TmdbFindResponse,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+    let title = load_titles_by_tids(&conn, &[tid])
+        .context("failed to load title")?
+        .into_iter()
+        .next()
+        .with_context(|| format!("title {tid} not found in the local cache"))?;
+
+    let ids = match source {
+        TmdbExternalSource::Imdb => ExternalIds {
+            imdb_id: Some(external_id.to_owned()),
+            tvdb_id: None,
+        },
+        TmdbExternalSource::Tvdb => ExternalIds {
+            imdb_id: None,
+            tvdb_id: Some(
+                external_id
+                    .parse()
+                    .with_context(|| format!("invalid TheTVDB ID: {external_id}"))?,
+            ),
+        },
+    };
+    update_external_ids(&conn, tid, &ids).context("failed to store external ID")?;
+    tracing::info!("Stored {} {external_id} on title {tid}", source.as_str());
+
+    let found: Vec<u64> = response.tv_results.iter().map(|tv| tv.id).collect();
+    match check_external_mapping(title.tmdb_series_id, &found) {
+        ExternalIdCheck::NotFound => {
+            tracing::warn!("No TMDB series found for {external_id}");
+        }
+        ExternalIdCheck::Matches(id) => {
+            tracing::info!("TMDB mapping of title {tid} (series {id}) matches {external_id}");
+        }
+        ExternalIdCheck::Mismatch(id) => {
+            tracing::warn!(
+                "TMDB mapping of title {tid} (series {id}) does not match {external_id} (found {found:?})"
+            );
+        }
+        ExternalIdCheck::Import(id) => {
+            update_tmdb_mapping(&conn, tid, Some(id), None, None)
+                .context("failed to store TMDB mapping")?;
+            tracing::info!("Mapped title {tid} to TMDB series {id}");
+        }
+        ExternalIdCheck::Ambiguous => {
+            tracing::warn!(
+                "{external_id} matches several TMDB series {found:?}; title {tid} left unmapped"
+            );
         }
     }
-    if let Some(g) = p.genre1 {
-        writeln!(out, "  genre: {g}/{}", p.sub_genre1.unwrap_or(0)).unwrap();
+    Ok(())
+}
+
+/// Runs the `tmdb find-by-external-id` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the source cannot be inferred, the TMDB request
+/// fails, storing the ID fails, or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_tmdb_find_by_external_id(
+    args: &TmdbFindByExternalIdArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let source = args
+        .source
+        .or_else(|| TmdbExternalSource::infer(&args.id))
+        .with_context(|| format!("cannot infer the source of {}; pass --source", args.id))?;
+    let client = build_tmdb_client(config_file)?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+
+    let response = client
+        .find_by_external_id(&args.id, source, &language)
+        .await
+        .context("TMDB find request failed")?;
+
+    if let Some(tid) = args.tid {
+        apply_external_id(config_file, tid, source, &args.id, &response)?;
     }
-    if let Some(r) = p.video_resolution() {
-        writeln!(out, "  video: {r}").unwrap();
+    if output.is_json() {
+        return write_json(&response);
     }
-    if let Some(rate) = p.audio_sampling_rate() {
-        writeln!(out, "  audio: {rate}Hz").unwrap();
+
+    tracing::info!("Type\tID\tName\t\t\tDate");
+    for tv in &response.tv_results {
+        tracing::info!(
+            "tv\t{}\t{}\t\t{}",
+            tv.id,
+            tv.name,
+            tv.first_air_date.as_deref().unwrap_or("-")
+        );
     }
-    writeln!(out).unwrap();
-    out
+    for movie in &response.movie_results {
+        tracing::info!(
+            "movie\t{}\t{}\t\t{}",
+            movie.id,
+            movie.title,
+            movie.release_date.as_deref().unwrap_or("-")
+        );
+    }
+
+    Ok(())
 }
 
-/// Print a single program's information to stdout.
-#[allow(clippy::print_stdout)]
-fn print_program_info(p: &dtvmgr_tsduck::eit::ProgramInfo, target_event_id: Option<&str>) {
-    print!("{}", format_program_info(p, target_event_id));
+/// Downloads one TMDB image from `url` into `dir` as `<kind>.<ext>` and
+/// returns the record to store.
+///
+/// # Errors
+///
+/// Returns an error if the download fails or the file cannot be written.
+async fn download_title_image(
+    tid: u32,
+    kind: ImageKind,
+    image: &TmdbImage,
+    url: &str,
+    dir: &Path,
+) -> Result<TitleImage> {
+    let bytes = read_source_bytes(url).await?;
+    let ext = Path::new(&image.file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("jpg");
+    let path = dir.join(format!("{}.{ext}", kind.as_str()));
+    std::fs::write(&path, &bytes).with_context(|| format!("failed to write {}", path.display()))?;
+    let sha256 = sha256_hex(bytes.as_slice()).context("failed to hash image")?;
+    Ok(TitleImage {
+        tid,
+        kind: String::from(kind.as_str()),
+        file_path: image.file_path.clone(),
+        local_path: path.to_string_lossy().into_owned(),
+        sha256,
+        downloaded_at: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+    })
 }
 
-/// Runs the `jlse channel` subcommand.
+/// Runs the `tmdb images` subcommand.
 ///
-/// Detects broadcast channel from the input filename.
+/// Saves images under `<data_dir>/assets/<TID>/` and records them in
+/// `title_images`. Images whose TMDB path is unchanged are skipped unless
+/// `--force` is given.
 ///
 /// # Errors
 ///
-/// Returns an error if the channel list cannot be loaded.
-#[allow(clippy::print_stdout)]
+/// Returns an error if the TMDB client cannot be built, DB operations fail,
+/// or the assets directory cannot be created.
 #[instrument(skip_all, err(level = "error"))]
-fn run_jlse_channel(args: &JlseChannelArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let jlse_config = resolve_jlse_config(config_file)?;
-    let data = DataPaths::from_config(&jlse_config);
-    let channels = load_jlse_channels(&data.channel_list)?;
+async fn run_tmdb_images(args: &TmdbImagesArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let data_dir = resolve_data_dir(config_file)
+        .context("failed to resolve data directory")?
+        .context("no data directory available")?;
+    let conn = open_db(Some(&data_dir)).context("failed to open database")?;
+    let titles: Vec<CachedTitle> = args
+        .tids
+        .as_ref()
+        .map_or_else(
+            || load_titles(&conn),
+            |tids| load_titles_by_tids(&conn, tids),
+        )
+        .context("failed to load titles")?
+        .into_iter()
+        .filter(|t| t.tmdb_series_id.is_some())
+        .collect();
+    if titles.is_empty() {
+        tracing::info!("No TMDB-mapped titles");
+        return Ok(());
+    }
+    let stored: std::collections::HashMap<(u32, String), TitleImage> = load_title_images(&conn)
+        .context("failed to load title images")?
+        .into_iter()
+        .map(|i| ((i.tid, i.kind.clone()), i))
+        .collect();
+    let client = build_tmdb_client(config_file)?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+    let image_language = language.split('-').next().unwrap_or_default();
+    let include = format!("{image_language},null");
+    let assets_dir = data_dir.join("assets");
+    let image_config = client
+        .image_configuration()
+        .await
+        .context("TMDB configuration request failed")?;
 
-    let channel_name = resolve_channel_name(args.channel.as_deref());
+    let (mut downloaded, mut unchanged, mut failed) = (0_usize, 0_usize, 0_usize);
+    for title in &titles {
+        let Some(series_id) = title.tmdb_series_id else {
+            continue;
+        };
+        let images = match client.tv_images(series_id, &include).await {
+            Ok(images) => images,
+            Err(e) => {
+                tracing::warn!(tid = title.tid, error = %e, "Failed to fetch TMDB images");
+                failed = failed.saturating_add(1);
+                continue;
+            }
+        };
+        let dir = assets_dir.join(title.tid.to_string());
+        for &kind in &args.kinds {
+            let Some(image) = kind.pick(&images, image_language) else {
+                continue;
+            };
+            let current = stored.get(&(title.tid, String::from(kind.as_str())));
+            if !args.force
+                && current.is_some_and(|c| {
+                    c.file_path == image.file_path && Path::new(&c.local_path).exists()
+                })
+            {
+                unchanged = unchanged.saturating_add(1);
+                continue;
+            }
+            let url = image_config
+                .url(&image.file_path, &args.size)
+                .context("failed to resolve TMDB image URL")?;
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+            match download_title_image(title.tid, kind, image, &url, &dir).await {
+                Ok(record) => {
+                    upsert_title_image(&conn, &record).context("failed to save title image")?;
+                    downloaded = downloaded.saturating_add(1);
+                }
+                Err(e) => {
+                    tracing::warn!(tid = title.tid, kind = kind.as_str(), error = %e, "Failed to download image");
+                    failed = failed.saturating_add(1);
+                }
+            }
+        }
+    }
+    tracing::info!(
+        "Downloaded {downloaded} image(s) into {} ({unchanged} unchanged, {failed} failed)",
+        assets_dir.display()
+    );
+    Ok(())
+}
 
-    let filepath = args.input.to_string_lossy();
-    let result = detect_channel(&channels, &filepath, channel_name.as_deref());
+// ── search subcommand ─────────────────────────────────────────
 
-    match result {
-        Some(ch) => {
-            println!("recognize: {}", ch.recognize);
-            println!("install: {}", ch.install);
-            println!("short: {}", ch.short);
-            println!("service_id: {}", ch.service_id);
-        }
-        None => {
-            println!("No channel detected.");
+/// Origin of a unified search hit. Declaration order is the tie-break order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SearchSource {
+    /// Local title cache.
+    Local,
+    /// TMDB `search/multi`.
+    Tmdb,
+}
+
+impl SearchSource {
+    /// Returns the label shown in the result table.
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Tmdb => "tmdb",
         }
     }
-    Ok(())
 }
 
-/// Runs the `jlse param` subcommand.
+/// A single row in the unified search result list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SearchHit {
+    /// Where the hit came from.
+    source: SearchSource,
+    /// Source-specific ID (Syoboi TID or TMDB ID).
+    id: u64,
+    /// Display name.
+    name: String,
+    /// Extra context (TMDB mapping, media type, air date).
+    detail: String,
+    /// Match quality: 3 = exact, 2 = prefix, 1 = substring, 0 = other.
+    score: u8,
+}
+
+/// Scores how well `query` matches the best of `candidates`, compared after
+/// [`normalize_title`].
+fn match_score(query: &str, candidates: &[Option<&str>]) -> u8 {
+    let query = normalize_title(query);
+    if query.is_empty() {
+        return 0;
+    }
+    candidates
+        .iter()
+        .flatten()
+        .map(|candidate| {
+            let candidate = normalize_title(candidate);
+            if candidate == query {
+                3
+            } else if candidate.starts_with(&query) {
+                2
+            } else {
+                u8::from(candidate.contains(&query))
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Converts cached titles into search hits.
+fn local_search_hits(titles: &[CachedTitle], query: &str) -> Vec<SearchHit> {
+    titles
+        .iter()
+        .map(|t| SearchHit {
+            source: SearchSource::Local,
+            id: u64::from(t.tid),
+            name: t.title.clone(),
+            detail: t
+                .tmdb_series_id
+                .map_or_else(|| String::from("-"), |id| format!("tmdb:{id}")),
+            score: match_score(
+                query,
+                &[
+                    Some(t.title.as_str()),
+                    t.short_title.as_deref(),
+                    t.title_en.as_deref(),
+                    t.title_yomi.as_deref(),
+                    t.tmdb_name.as_deref(),
+                    t.tmdb_original_name.as_deref(),
+                ],
+            ),
+        })
+        .collect()
+}
+
+/// Converts TMDB multi-search results into search hits.
 ///
-/// Detects JL parameters from the channel and input filename.
+/// People are dropped, as are TV series already mapped by a local hit.
+fn tmdb_search_hits(
+    results: &[TmdbMultiSearchResult],
+    query: &str,
+    mapped_series: &HashSet<u64>,
+) -> Vec<SearchHit> {
+    results
+        .iter()
+        .filter_map(|result| match result {
+            TmdbMultiSearchResult::Tv(tv) if !mapped_series.contains(&tv.id) => Some(SearchHit {
+                source: SearchSource::Tmdb,
+                id: tv.id,
+                name: tv.name.clone(),
+                detail: format!("tv {}", tv.first_air_date.as_deref().unwrap_or("-")),
+                score: match_score(
+                    query,
+                    &[Some(tv.name.as_str()), Some(tv.original_name.as_str())],
+                ),
+            }),
+            TmdbMultiSearchResult::Movie(movie) => Some(SearchHit {
+                source: SearchSource::Tmdb,
+                id: movie.id,
+                name: movie.title.clone(),
+                detail: format!("movie {}", movie.release_date.as_deref().unwrap_or("-")),
+                score: match_score(
+                    query,
+                    &[
+                        Some(movie.title.as_str()),
+                        Some(movie.original_title.as_str()),
+                    ],
+                ),
+            }),
+            TmdbMultiSearchResult::Tv(_) | TmdbMultiSearchResult::Person(_) => None,
+        })
+        .collect()
+}
+
+/// Orders hits by score (best first), then source. Source order is kept within ties.
+fn rank_search_hits(mut hits: Vec<SearchHit>) -> Vec<SearchHit> {
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.source.cmp(&b.source)));
+    hits
+}
+
+/// Runs the `search` subcommand.
+///
+/// Searches the local title cache first, then TMDB when `--tmdb` is given,
+/// and prints a single ranked list labelled by source.
 ///
 /// # Errors
 ///
-/// Returns an error if the param lists cannot be loaded.
-#[allow(clippy::print_stdout)]
+/// Returns an error if the database query or TMDB request fails.
 #[instrument(skip_all, err(level = "error"))]
-fn run_jlse_param(args: &JlseParamArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let jlse_config = resolve_jlse_config(config_file)?;
-    let data = DataPaths::from_config(&jlse_config);
-    let channels = load_jlse_channels(&data.channel_list)?;
-
-    let channel_name = resolve_channel_name(args.channel.as_deref());
-    let filepath = args.input.to_string_lossy();
-    let channel = detect_channel(&channels, &filepath, channel_name.as_deref());
+async fn run_search(args: &SearchArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let titles = search_titles_normalized(&conn, &args.query, args.limit)
+        .context("failed to search cached titles")?;
+    let mapped_series: HashSet<u64> = titles.iter().filter_map(|t| t.tmdb_series_id).collect();
+    let mut hits = local_search_hits(&titles, &args.query);
+
+    if args.tmdb {
+        let client = build_tmdb_client(config_file)?;
+        let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+        let params = SearchMultiParams::new(&args.query).language(&language);
+        let response = client
+            .search_multi(&params)
+            .await
+            .context("TMDB search/multi request failed")?;
+        let limit = usize::try_from(args.limit).unwrap_or(usize::MAX);
+        hits.extend(
+            tmdb_search_hits(&response.results, &args.query, &mapped_series)
+                .into_iter()
+                .take(limit),
+        );
+    }
 
-    let params_jl1 = load_params(&data.param_jl1)?;
-    let params_jl2 = load_params(&data.param_jl2)?;
+    let hits = rank_search_hits(hits);
+    if hits.is_empty() {
+        tracing::info!("No results for {:?}", args.query);
+        return Ok(());
+    }
 
-    let filename = args.input.file_stem().unwrap_or_default().to_string_lossy();
-    let result = detect_param(&params_jl1, &params_jl2, channel.as_ref(), &filename);
+    tracing::info!("Source\tID\tName\tDetail");
+    for hit in &hits {
+        tracing::info!(
+            "{}\t{}\t{}\t{}",
+            hit.source.label(),
+            hit.id,
+            hit.name,
+            hit.detail
+        );
+    }
 
-    println!("jl_run: {}", result.jl_run);
-    println!("flags: {}", result.flags);
-    println!("options: {}", result.options);
     Ok(())
 }
 
-/// Spawns a pipeline thread and runs the TUI progress viewer.
+// ── watchlist subcommand ──────────────────────────────────────
+
+/// Runs the `watchlist add` subcommand.
 ///
-/// Suppresses tracing output on the pipeline thread to prevent
-/// log lines from corrupting the TUI alternate screen.
+/// # Errors
+///
+/// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-fn run_pipeline_with_tui(ctx: PipelineContext) -> Result<()> {
-    let (tx, rx) = std::sync::mpsc::channel::<ProgressEvent>();
-    let parent_span = tracing::Span::current();
-    let handle = std::thread::spawn(move || {
-        let _entered = parent_span.enter();
-        let cb = move |event: ProgressEvent| {
-            // Ignore send errors (receiver may have been dropped on quit)
-            let _ = tx.send(event);
-        };
-        run_pipeline(&ctx, Some(&cb))
-    });
-    dtvmgr_tui::progress_viewer::run_progress_viewer(&rx, handle)
+fn run_watchlist_add(args: &WatchlistAddArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let entries: Vec<WatchlistEntry> = args
+        .tids
+        .iter()
+        .map(|&tid| WatchlistEntry {
+            tid,
+            notify_new_program: args.notify.contains(&WatchEventKind::New),
+            notify_time_change: args.notify.contains(&WatchEventKind::TimeChange),
+            notify_finale: args.notify.contains(&WatchEventKind::Finale),
+            added_at: String::new(),
+        })
+        .collect();
+    let changed =
+        upsert_watchlist_entries(&conn, &entries).context("failed to update watchlist")?;
+
+    tracing::info!(
+        "Watchlist updated: {} titles ({} changed)",
+        entries.len(),
+        changed
+    );
+    Ok(())
 }
 
-/// Runs the `jlse run` subcommand.
-///
-/// Executes the full CM detection pipeline.
+/// Runs the `watchlist remove` subcommand.
 ///
 /// # Errors
 ///
-/// Returns an error if the pipeline fails.
+/// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-fn run_jlse_run(args: &JlseRunArgs, config_file: Option<&PathBuf>) -> Result<()> {
-    let jlse_config = resolve_jlse_config(config_file)?;
+fn run_watchlist_remove(args: &WatchlistRemoveArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
 
-    let channel_name = resolve_channel_name(args.channel.as_deref());
+    let deleted = delete_watchlist_entries(&conn, &args.tids)
+        .context("failed to remove watchlist entries")?;
+    tracing::info!("Removed {deleted} titles from watchlist");
+    Ok(())
+}
 
-    if args.epgstation {
+/// Formats a yes/no column for table output.
+const fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+/// Runs the `watchlist list` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+fn run_watchlist_list(config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let entries = load_watchlist(&conn).context("failed to load watchlist")?;
+    if entries.is_empty() {
+        tracing::info!("Watchlist is empty. Add titles with `watchlist add --tids ...`.");
+        return Ok(());
+    }
+    let tids: Vec<u32> = entries.iter().map(|e| e.tid).collect();
+    let titles = load_titles_by_tids(&conn, &tids).context("failed to load titles")?;
+    let names: std::collections::HashMap<u32, &str> =
+        titles.iter().map(|t| (t.tid, t.title.as_str())).collect();
+
+    tracing::info!("TID\tNew\tTime\tFinale\tAdded\t\t\tTitle");
+    for e in &entries {
+        tracing::info!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            e.tid,
+            yes_no(e.notify_new_program),
+            yes_no(e.notify_time_change),
+            yes_no(e.notify_finale),
+            e.added_at,
+            names.get(&e.tid).copied().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+// ── titles subcommand ─────────────────────────────────────────
+
+/// Runs the `titles follow` / `titles unfollow` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+fn run_titles_follow(
+    args: &TitleFollowArgs,
+    followed: bool,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let changed = set_titles_followed(&conn, &args.tids, followed)
+        .context("failed to update followed titles")?;
+    if changed < args.tids.len() {
+        tracing::warn!(
+            "{} TIDs were unknown or already {}",
+            args.tids.len().saturating_sub(changed),
+            if followed { "followed" } else { "unfollowed" },
+        );
+    }
+    tracing::info!(
+        "{} {changed} titles",
+        if followed { "Followed" } else { "Unfollowed" }
+    );
+    Ok(())
+}
+
+/// Runs the `titles list-followed` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails or JSON output cannot be
+/// written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_titles_list_followed(config_file: Option<&PathBuf>, output: OutputFormat) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let tids = load_followed_tids(&conn).context("failed to load followed titles")?;
+    let titles = load_titles_by_tids(&conn, &tids).context("failed to load titles")?;
+    if output.is_json() {
+        return write_json(&titles);
+    }
+    if titles.is_empty() {
+        tracing::info!("No followed titles. Follow titles with `titles follow --tids ...`.");
+        return Ok(());
+    }
+    tracing::info!("TID	Year	TMDB	Title");
+    for t in &titles {
+        tracing::info!(
+            "{}	{}	{}	{}",
+            t.tid,
+            t.first_year
+                .map_or_else(|| String::from("-"), |y| y.to_string()),
+            t.tmdb_series_id
+                .map_or_else(|| String::from("-"), |id| id.to_string()),
+            t.title,
+        );
+    }
+    Ok(())
+}
+
+/// Runs the `titles merge` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if either title is not cached or the database
+/// operation fails.
+#[instrument(skip_all, err(level = "error"))]
+fn run_titles_merge(
+    args: &TitleMergeArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let merge = merge_titles(&conn, args.old_tid, args.new_tid).with_context(|| {
+        format!(
+            "failed to merge title {} into {}",
+            args.old_tid, args.new_tid
+        )
+    })?;
+    if output.is_json() {
+        return write_json(&merge);
+    }
+    tracing::info!(
+        "Merged title {} into {}: {} programs re-linked{}",
+        args.old_tid,
+        args.new_tid,
+        merge.programs,
+        if merge.mapping_copied {
+            ", TMDB mapping copied"
+        } else {
+            ""
+        },
+    );
+    if merge.aliases_repointed > 0 {
+        tracing::info!(
+            "Re-pointed {} earlier aliases to {}",
+            merge.aliases_repointed,
+            args.new_tid
+        );
+    }
+    Ok(())
+}
+
+/// Runs the `titles aliases` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails or JSON output cannot be
+/// written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_titles_aliases(config_file: Option<&PathBuf>, output: OutputFormat) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let aliases = load_title_aliases(&conn).context("failed to load title aliases")?;
+    if output.is_json() {
+        return write_json(&aliases);
+    }
+    if aliases.is_empty() {
+        tracing::info!("No merged titles. Merge titles with `titles merge OLD_TID NEW_TID`.");
+        return Ok(());
+    }
+    tracing::info!("Old TID	New TID	Merged at");
+    for a in &aliases {
+        tracing::info!("{}	{}	{}", a.old_tid, a.new_tid, a.merged_at);
+    }
+    Ok(())
+}
+
+/// Runs the `titles import-syoboi` subcommand.
+///
+/// Reads the Syoboi check list of `[syoboi.account] user` (password from
+/// `SYOBOI_PASSWORD` with config fallback), caches titles that are not in
+/// the DB yet, and follows every listed title.
+///
+/// # Errors
+///
+/// Returns an error if no user is configured, or API or DB operations fail.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, err(level = "error"))]
+async fn run_titles_import_syoboi(
+    args: &TitleImportSyoboiArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let Some(user) = config.syoboi.account.user else {
+        anyhow::bail!("[syoboi.account] user is not configured");
+    };
+    let password = std::env::var("SYOBOI_PASSWORD")
+        .ok()
+        .or(config.syoboi.account.password);
+
+    let client = syoboi_client_builder()?
+        .credentials(SyoboiCredentials { user, password })
+        .build()
+        .context("failed to build Syoboi API client")?;
+    let programs = client
+        .lookup_checked_programs(args.days)
+        .await
+        .context("failed to fetch Syoboi check list")?;
+    let tids = checked_tids(&programs);
+    if tids.is_empty() {
+        tracing::info!("Check list has no programs in the next {} days", args.days);
+        return Ok(());
+    }
+
+    let conn = open_data_db(config_file)?;
+    let cached: HashSet<u32> = load_titles_by_tids(&conn, &tids)
+        .context("failed to load titles")?
+        .iter()
+        .map(|t| t.tid)
+        .collect();
+    let missing: Vec<u32> = tids
+        .iter()
+        .copied()
+        .filter(|tid| !cached.contains(tid))
+        .collect();
+
+    if args.dry_run {
+        for tid in &tids {
+            let title = programs
+                .iter()
+                .find(|p| p.tid == *tid)
+                .map_or("", |p| p.title.as_str());
+            let note = if cached.contains(tid) {
+                ""
+            } else {
+                "  (not cached)"
+            };
+            tracing::info!("{tid}\t{title}{note}");
+        }
+        tracing::info!(
+            "Would follow {} titles ({} not cached yet)",
+            tids.len(),
+            missing.len()
+        );
+        return Ok(());
+    }
+
+    if !missing.is_empty() {
+        let titles = client
+            .lookup_titles(&TitleLookupParams::from_tids(&missing))
+            .await
+            .context("failed to fetch titles")?;
+        let cached_titles: Vec<CachedTitle> = titles.iter().map(to_cached_title).collect();
+        dtvmgr_db::upsert_titles(&conn, &cached_titles).context("failed to cache titles")?;
+    }
+    let changed =
+        set_titles_followed(&conn, &tids, true).context("failed to update followed titles")?;
+    tracing::info!(
+        "Followed {changed} new titles from the Syoboi check list ({} listed, {} fetched)",
+        tids.len(),
+        missing.len()
+    );
+    Ok(())
+}
+
+/// Detected and stored season ranges of one title.
+#[derive(Debug, serde::Serialize)]
+struct SeasonSplitRow {
+    /// Syoboi title ID.
+    tid: u32,
+    /// Syoboi title.
+    title: String,
+    /// Ranges suggested from the broadcasts.
+    detected: Vec<SeasonRange>,
+    /// Ranges stored in `title_season_ranges`.
+    stored: Vec<SeasonRange>,
+}
+
+/// Runs the `titles split-seasons` subcommand.
+///
+/// With `--range` / `--clear` the ranges of `--tid` are replaced. Otherwise
+/// cross-season titles (excluding `cat_movie` categories) are detected from
+/// the cached programs and listed with their stored ranges; `--apply` stores
+/// the detected ranges.
+///
+/// # Errors
+///
+/// Returns an error if config or DB operations fail or the ranges overlap.
+#[instrument(skip_all, err(level = "error"))]
+fn run_titles_split_seasons(
+    args: &TitleSplitSeasonsArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    if let Some(tid) = args.tid
+        && (args.clear || !args.ranges.is_empty())
+    {
+        let ranges: Vec<SeasonRange> = args
+            .ranges
+            .iter()
+            .map(|r| SeasonRange {
+                tid,
+                first_count: r.first,
+                last_count: r.last,
+                season_number: r.season,
+            })
+            .collect();
+        validate_season_ranges(&ranges)?;
+        if load_titles_by_tids(&conn, &[tid])
+            .context("failed to load title")?
+            .is_empty()
+        {
+            anyhow::bail!("title {tid} is not cached");
+        }
+        replace_season_ranges(&conn, tid, &ranges).context("failed to save season ranges")?;
+        if ranges.is_empty() {
+            tracing::info!("Cleared season ranges of TID {tid}");
+        } else {
+            tracing::info!(
+                "Saved season ranges of TID {tid}: {}",
+                format_season_ranges(&ranges)
+            );
+        }
+        return Ok(());
+    }
+
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let cat_movie: HashSet<u32> = config.syoboi.titles.cat_movie.iter().copied().collect();
+    let titles = args
+        .tid
+        .map_or_else(
+            || load_titles(&conn),
+            |tid| load_titles_by_tids(&conn, &[tid]),
+        )
+        .context("failed to load titles")?;
+    let titles: Vec<CachedTitle> = titles
+        .into_iter()
+        .filter(|t| resolve_media_type(t.cat, &cat_movie) == TmdbMediaType::Tv)
+        .collect();
+    let tids: Vec<u32> = titles.iter().map(|t| t.tid).collect();
+    let programs = load_programs_by_tids(&conn, &tids).context("failed to load programs")?;
+    let stored = load_season_ranges(&conn).context("failed to load season ranges")?;
+
+    let rows: Vec<SeasonSplitRow> = titles
+        .iter()
+        .filter_map(|t| {
+            let detected = detect_season_ranges(t, &programs);
+            let stored: Vec<SeasonRange> =
+                stored.iter().filter(|r| r.tid == t.tid).copied().collect();
+            (!detected.is_empty() || !stored.is_empty()).then(|| SeasonSplitRow {
+                tid: t.tid,
+                title: t.title.clone(),
+                detected,
+                stored,
+            })
+        })
+        .collect();
+    if args.apply {
+        for row in rows.iter().filter(|r| !r.detected.is_empty()) {
+            replace_season_ranges(&conn, row.tid, &row.detected)
+                .with_context(|| format!("failed to save season ranges of TID {}", row.tid))?;
+        }
+    }
+    if output.is_json() {
+        return write_json(&rows);
+    }
+    if rows.is_empty() {
+        tracing::info!("No cross-season titles detected");
+        return Ok(());
+    }
+    tracing::info!("TID\tDetected\tStored\tTitle");
+    for row in &rows {
+        tracing::info!(
+            "{}\t{}\t{}\t{}",
+            row.tid,
+            non_empty_or_dash(format_season_ranges(&row.detected)),
+            non_empty_or_dash(format_season_ranges(&row.stored)),
+            row.title,
+        );
+    }
+    if args.apply {
+        tracing::info!(
+            "Stored detected ranges of {} title(s)",
+            rows.iter().filter(|r| !r.detected.is_empty()).count()
+        );
+    }
+    Ok(())
+}
+
+/// Returns `s`, or `-` when it is empty.
+fn non_empty_or_dash(s: String) -> String {
+    if s.is_empty() { String::from("-") } else { s }
+}
+
+// ── channels subcommand ───────────────────────────────────────
+
+/// Reads a local file (plain path or `file://`) or fetches an `http(s)://` URL.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the request fails.
+async fn read_source_bytes(source: &str) -> Result<Vec<u8>> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let body = reqwest::get(source)
+            .await
+            .with_context(|| format!("failed to fetch {source}"))?
+            .error_for_status()
+            .with_context(|| format!("HTTP error fetching {source}"))?
+            .bytes()
+            .await
+            .with_context(|| format!("failed to read response body from {source}"))?;
+        return Ok(body.to_vec());
+    }
+    let path = source.strip_prefix("file://").unwrap_or(source);
+    std::fs::read(path).with_context(|| format!("failed to read {path}"))
+}
+
+/// Runs the `channels map import` subcommand.
+///
+/// Entries are resolved against cached channels (see
+/// [`resolve_channel_map`]); unmatched names are logged.
+///
+/// # Errors
+///
+/// Returns an error if the source cannot be read or parsed, or DB
+/// operations fail.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_channels_map_import(
+    args: &ChannelsMapImportArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let body = read_source_bytes(&args.source).await?;
+    let conn = open_data_db(config_file)?;
+    let channels = load_channels(&conn).context("failed to load channels")?;
+    let mapping = resolve_channel_map(&body, &channels)
+        .with_context(|| format!("failed to parse channel map from {}", args.source))?;
+    for name in &mapping.unmatched {
+        tracing::warn!(name = %name, "No cached channel matches; add an entry with ch_id");
+    }
+    let changed = upsert_channel_aliases(&conn, &mapping.aliases)
+        .context("failed to save channel aliases")?;
+    tracing::info!(
+        "Mapped {} channel(s) ({changed} changed), {} unmatched",
+        mapping.aliases.len(),
+        mapping.unmatched.len()
+    );
+    Ok(())
+}
+
+/// Runs the `channels map list` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails or JSON output cannot be
+/// written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_channels_map_list(config_file: Option<&PathBuf>, output: OutputFormat) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+    let aliases = load_channel_aliases(&conn).context("failed to load channel aliases")?;
+    if output.is_json() {
+        return write_json(&aliases);
+    }
+    if aliases.is_empty() {
+        tracing::info!("No channel aliases. Import them with `channels map import <SOURCE>`.");
+        return Ok(());
+    }
+    let names: std::collections::HashMap<u32, String> = load_channels(&conn)
+        .context("failed to load channels")?
+        .into_iter()
+        .map(|c| (c.ch_id, c.ch_name))
+        .collect();
+    let opt = |v: Option<u64>| v.map_or_else(|| String::from("-"), |v| v.to_string());
+    tracing::info!("ChID\tType\tMirakurun\tEPGStation\tChannel\tService");
+    for a in &aliases {
+        tracing::info!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            a.ch_id,
+            a.channel_type.as_deref().unwrap_or("-"),
+            opt(a.mirakurun_service_id),
+            opt(a.epgstation_channel_id),
+            names.get(&a.ch_id).map_or("-", String::as_str),
+            a.name.as_deref().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+/// Runs the `channels map remove` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+fn run_channels_map_remove(
+    args: &ChannelsMapRemoveArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+    let deleted =
+        delete_channel_aliases(&conn, &args.ch_ids).context("failed to delete channel aliases")?;
+    tracing::info!("Removed {deleted} channel alias(es)");
+    Ok(())
+}
+
+/// Server that channel logos are downloaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogoSource<'a> {
+    /// Mirakurun base URL (`/api/services/{id}/logo`).
+    Mirakurun(&'a str),
+    /// `EPGStation` base URL (`/api/channels/{id}/logo`).
+    EpgStation(&'a str),
+}
+
+impl LogoSource<'_> {
+    /// Returns the logo URL of `alias`, or `None` if it lacks the ID this
+    /// source needs.
+    fn logo_url(self, alias: &ChannelAlias) -> Option<String> {
+        match self {
+            Self::Mirakurun(base) => alias
+                .mirakurun_service_id
+                .map(|id| format!("{}/api/services/{id}/logo", base.trim_end_matches('/'))),
+            Self::EpgStation(base) => alias
+                .epgstation_channel_id
+                .map(|id| format!("{}/api/channels/{id}/logo", base.trim_end_matches('/'))),
+        }
+    }
+}
+
+/// Runs the `channels fetch-logos` subcommand.
+///
+/// Saves each logo as `<data_dir>/logos/<ChID>.png` and records its URL in
+/// `channels.logo_url`, which the exports use. Channels without a matching
+/// alias ID or whose download fails are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the config or DB cannot be read, or the logo
+/// directory cannot be created.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_channels_fetch_logos(
+    args: &ChannelsFetchLogosArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let epgstation_url = if args.mirakurun.is_none() {
+        let config_path =
+            resolve_config_path(config_file).context("failed to resolve config path")?;
+        let config = AppConfig::load(&config_path).context("failed to load config")?;
+        config.epgstation.base_url
+    } else {
+        None
+    };
+    let source = args.mirakurun.as_deref().map_or_else(
+        || LogoSource::EpgStation(epgstation_url.as_deref().unwrap_or("http://localhost:8888")),
+        LogoSource::Mirakurun,
+    );
+
+    let data_dir = resolve_data_dir(config_file)
+        .context("failed to resolve data directory")?
+        .context("no data directory available")?;
+    let conn = open_db(Some(&data_dir)).context("failed to open database")?;
+    let aliases = load_channel_aliases(&conn).context("failed to load channel aliases")?;
+    if aliases.is_empty() {
+        tracing::info!("No channel aliases. Import them with `channels map import <SOURCE>`.");
+        return Ok(());
+    }
+    let logo_dir = data_dir.join("logos");
+    std::fs::create_dir_all(&logo_dir)
+        .with_context(|| format!("failed to create {}", logo_dir.display()))?;
+
+    let (mut fetched, mut skipped, mut failed) = (0_usize, 0_usize, 0_usize);
+    for alias in &aliases {
+        let Some(url) = source.logo_url(alias) else {
+            skipped = skipped.saturating_add(1);
+            continue;
+        };
+        let path = logo_dir.join(format!("{}.png", alias.ch_id));
+        if args.force || !path.exists() {
+            match read_source_bytes(&url).await {
+                Ok(bytes) => std::fs::write(&path, bytes)
+                    .with_context(|| format!("failed to write {}", path.display()))?,
+                Err(e) => {
+                    tracing::warn!(ch_id = alias.ch_id, error = %e, "Failed to fetch logo");
+                    failed = failed.saturating_add(1);
+                    continue;
+                }
+            }
+            fetched = fetched.saturating_add(1);
+        }
+        update_channel_logo(&conn, alias.ch_id, Some(&url)).context("failed to save logo URL")?;
+    }
+    tracing::info!(
+        "Fetched {fetched} logo(s) into {} ({skipped} without ID, {failed} failed)",
+        logo_dir.display()
+    );
+    Ok(())
+}
+
+// ── library subcommand ────────────────────────────────────────
+
+/// Converts a Syoboi `YYYY-MM-DD HH:MM:SS` (JST) timestamp into Unix milliseconds.
+fn syoboi_time_to_unix_ms(value: &str) -> Option<i64> {
+    syoboi_time_in(value, SYOBOI_TIMEZONE).map(|dt| dt.timestamp_millis())
+}
+
+/// Converts a Syoboi `YYYY-MM-DD HH:MM:SS` (JST) timestamp into `tz`.
+fn syoboi_time_in(value: &str, tz: Tz) -> Option<chrono::DateTime<Tz>> {
+    let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()?;
+    naive
+        .and_local_timezone(SYOBOI_TIMEZONE)
+        .single()
+        .map(|dt| dt.with_timezone(&tz))
+}
+
+/// Formats a Syoboi (JST) timestamp for display in `tz`, appending the
+/// zone abbreviation outside JST. Unparsable values are returned as-is.
+fn display_broadcast_time(value: &str, tz: Tz) -> String {
+    match syoboi_time_in(value, tz) {
+        Some(dt) if tz == SYOBOI_TIMEZONE => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
+        Some(dt) => dt.format("%Y-%m-%d %H:%M:%S %Z").to_string(),
+        None => value.to_owned(),
+    }
+}
+
+/// Verification outcome for a single TMDB episode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EpisodeStatus {
+    /// Exactly one healthy recording.
+    Ok,
+    /// No recording matched the episode's broadcasts.
+    Missing,
+    /// Recordings matched but none is healthy.
+    Unhealthy,
+    /// More than one healthy recording.
+    Duplicate,
+}
+
+impl EpisodeStatus {
+    /// Classifies an episode from its matched and healthy recording counts.
+    const fn classify(matched: usize, healthy: usize) -> Self {
+        match (matched, healthy) {
+            (0, _) => Self::Missing,
+            (_, 0) => Self::Unhealthy,
+            (_, 1) => Self::Ok,
+            _ => Self::Duplicate,
+        }
+    }
+
+    /// Returns the manifest label.
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Missing => "missing",
+            Self::Unhealthy => "unhealthy",
+            Self::Duplicate => "duplicate",
+        }
+    }
+}
+
+/// The representative file of a recording inspected by `library verify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VerifiedFile {
+    /// `EPGStation` recorded item ID.
+    recorded_id: i64,
+    /// Filename (or display name when no filename is known).
+    filename: String,
+    /// File size in bytes.
+    size: i64,
+    /// SHA-256 digest (computed or stored), if known.
+    sha256: Option<String>,
+    /// Reason the file is unhealthy, `None` when healthy.
+    problem: Option<&'static str>,
+}
+
+/// Verification result for one TMDB episode.
+#[derive(Debug)]
+struct EpisodeVerification {
+    /// TMDB episode ID.
+    tmdb_episode_id: u64,
+    /// Syoboi episode number, if known.
+    count: Option<u32>,
+    /// Outcome.
+    status: EpisodeStatus,
+    /// One representative file per matched recording.
+    files: Vec<VerifiedFile>,
+}
+
+/// Returns `true` when the recording name mentions one of `names` and its
+/// time range overlaps one of the broadcast `windows` (Unix ms).
+fn recording_matches(item: &CachedRecordedItem, names: &[&str], windows: &[(i64, i64)]) -> bool {
+    let name = item.name.to_lowercase();
+    names
+        .iter()
+        .any(|n| !n.is_empty() && name.contains(&n.to_lowercase()))
+        && windows
+            .iter()
+            .any(|&(st, ed)| item.start_at < ed && item.end_at > st)
+}
+
+/// Checks a video file using the cached `EPGStation` existence state.
+const fn inspect_cached_file(vf: &CachedVideoFile) -> Option<&'static str> {
+    match vf.file_exists {
+        Some(false) => Some("missing"),
+        None => Some("unchecked"),
+        Some(true) if vf.size <= 0 => Some("empty"),
+        Some(true) => None,
+    }
+}
+
+/// Computes the lowercase hex SHA-256 digest of a reader.
+fn sha256_hex(mut reader: impl std::io::Read) -> std::io::Result<String> {
+    use sha2::Digest as _;
+
+    let mut hasher = sha2::Sha256::new();
+    std::io::copy(&mut reader, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Inspects a video file, on disk under `root` when given, otherwise from cache.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+fn inspect_video_file(
+    recorded_id: i64,
+    vf: &CachedVideoFile,
+    root: Option<&Path>,
+    hash: bool,
+    stored_hash: Option<&String>,
+) -> Result<VerifiedFile> {
+    let mut file = VerifiedFile {
+        recorded_id,
+        filename: vf.filename.clone().unwrap_or_else(|| vf.name.clone()),
+        size: vf.size,
+        sha256: stored_hash.cloned(),
+        problem: None,
+    };
+    let Some(root) = root else {
+        file.problem = inspect_cached_file(vf);
+        return Ok(file);
+    };
+    let Some(filename) = vf.filename.as_deref() else {
+        file.problem = Some("no filename");
+        return Ok(file);
+    };
+
+    let path = root.join(filename);
+    let meta = match std::fs::metadata(&path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            file.problem = Some("missing");
+            return Ok(file);
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to stat {}", path.display()));
+        }
+    };
+    file.size = i64::try_from(meta.len()).unwrap_or(i64::MAX);
+    if file.size == 0 {
+        file.problem = Some("empty");
+        return Ok(file);
+    }
+    if hash {
+        let reader = std::fs::File::open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        let computed = sha256_hex(std::io::BufReader::new(reader))
+            .with_context(|| format!("failed to hash {}", path.display()))?;
+        if stored_hash.is_some_and(|stored| *stored != computed) {
+            file.problem = Some("hash mismatch");
+        }
+        file.sha256 = Some(computed);
+    }
+    Ok(file)
+}
+
+/// Renders the verification manifest as TSV with a commented header.
+#[allow(clippy::unwrap_used)]
+fn render_verify_manifest(
+    title: &CachedTitle,
+    results: &[EpisodeVerification],
+    generated_at: &str,
+) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::new();
+    writeln!(out, "# dtvmgr library verify").unwrap();
+    writeln!(out, "# tid: {}", title.tid).unwrap();
+    writeln!(out, "# title: {}", title.title).unwrap();
+    writeln!(out, "# generated_at: {generated_at}").unwrap();
+    writeln!(
+        out,
+        "tmdb_episode_id\tepisode\tstatus\trecorded_id\tfile\tsize\tsha256\tproblem"
+    )
+    .unwrap();
+    for ep in results {
+        let episode = ep
+            .count
+            .map_or_else(|| String::from("-"), |c| c.to_string());
+        if ep.files.is_empty() {
+            writeln!(
+                out,
+                "{}\t{episode}\t{}\t-\t-\t-\t-\t-",
+                ep.tmdb_episode_id,
+                ep.status.label()
+            )
+            .unwrap();
+        }
+        for f in &ep.files {
+            writeln!(
+                out,
+                "{}\t{episode}\t{}\t{}\t{}\t{}\t{}\t{}",
+                ep.tmdb_episode_id,
+                ep.status.label(),
+                f.recorded_id,
+                f.filename,
+                f.size,
+                f.sha256.as_deref().unwrap_or("-"),
+                f.problem.unwrap_or("-"),
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// Runs `library verify`.
+///
+/// Matches cached `EPGStation` recordings to the title's broadcasts by name
+/// and time overlap, then checks that each mapped TMDB episode has exactly
+/// one healthy recording.
+///
+/// # Errors
+///
+/// Returns an error if DB or file operations fail, or if any episode is not `ok`.
+#[instrument(skip_all, err(level = "error"))]
+fn run_library_verify(args: &LibraryVerifyArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let title = load_titles_by_tids(&conn, &[args.tid])
+        .context("failed to load title")?
+        .into_iter()
+        .next()
+        .with_context(|| format!("TID {} is not cached; run `db sync` first", args.tid))?;
+    let programs = load_programs_by_tids(&conn, &[args.tid]).context("failed to load programs")?;
+    let mut episodes: std::collections::BTreeMap<u64, Vec<&CachedProgram>> =
+        std::collections::BTreeMap::new();
+    for p in &programs {
+        if let Some(ep) = p.tmdb_episode_id {
+            episodes.entry(ep).or_default().push(p);
+        }
+    }
+    if episodes.is_empty() {
+        tracing::info!("No TMDB episode mappings for TID {}.", args.tid);
+        return Ok(());
+    }
+
+    let names: Vec<&str> = std::iter::once(title.title.as_str())
+        .chain(title.short_title.as_deref())
+        .collect();
+    let recordings = load_recorded_items(&conn).context("failed to load recorded items")?;
+    let stored_hashes = load_video_file_hashes(&conn).context("failed to load file hashes")?;
+    let now = Utc::now().to_rfc3339();
+
+    let mut results = Vec::with_capacity(episodes.len());
+    for (&tmdb_episode_id, progs) in &episodes {
+        let windows: Vec<(i64, i64)> = progs
+            .iter()
+            .filter_map(|p| {
+                Some((
+                    syoboi_time_to_unix_ms(&p.st_time)?,
+                    syoboi_time_to_unix_ms(&p.ed_time)?,
+                ))
+            })
+            .collect();
+        let mut files = Vec::new();
+        for (item, video_files) in recordings
+            .iter()
+            .filter(|(item, _)| recording_matches(item, &names, &windows))
+        {
+            let mut inspected = Vec::with_capacity(video_files.len());
+            for vf in video_files {
+                let stored = stored_hashes.get(&vf.id);
+                let f = inspect_video_file(item.id, vf, args.root.as_deref(), args.hash, stored)?;
+                if let (None, None, Some(sha)) = (f.problem, stored, f.sha256.as_deref()) {
+                    upsert_video_file_hash(&conn, vf.id, sha, f.size, &now)
+                        .context("failed to store file hash")?;
+                }
+                inspected.push(f);
+            }
+            // One line per recording: its first healthy file, else its first problem.
+            let representative = inspected
+                .into_iter()
+                .min_by_key(|f| f.problem.is_some())
+                .unwrap_or_else(|| VerifiedFile {
+                    recorded_id: item.id,
+                    filename: item.name.clone(),
+                    size: 0,
+                    sha256: None,
+                    problem: Some("no video files"),
+                });
+            files.push(representative);
+        }
+        let healthy = files.iter().filter(|f| f.problem.is_none()).count();
+        results.push(EpisodeVerification {
+            tmdb_episode_id,
+            count: progs.iter().find_map(|p| p.count),
+            status: EpisodeStatus::classify(files.len(), healthy),
+            files,
+        });
+    }
+
+    let manifest = render_verify_manifest(&title, &results, &now);
+    for line in manifest.lines() {
+        tracing::info!("{line}");
+    }
+    if let Some(output) = &args.output {
+        std::fs::write(output, &manifest)
+            .with_context(|| format!("failed to write manifest to {}", output.display()))?;
+        tracing::info!("Manifest written to {}", output.display());
+    }
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status != EpisodeStatus::Ok)
+        .count();
+    anyhow::ensure!(
+        failed == 0,
+        "{failed} of {} episodes failed verification",
+        results.len()
+    );
+    tracing::info!("All {} episodes verified.", results.len());
+    Ok(())
+}
+
+// ── report subcommand ─────────────────────────────────────────
+
+/// Runs `report coverage`: for each mapped title, compares the aired TMDB
+/// episodes with the cached programs and lists the missing episode numbers.
+///
+/// # Errors
+///
+/// Returns an error if the TMDB client cannot be built or DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_report_coverage(
+    args: &ReportCoverageArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+    let titles = match (&args.tids, args.all) {
+        (Some(tids), _) => load_titles_by_tids(&conn, tids).context("failed to load titles")?,
+        (None, true) => load_titles(&conn).context("failed to load titles")?,
+        (None, false) => {
+            let followed = load_followed_tids(&conn).context("failed to load followed titles")?;
+            load_titles_by_tids(&conn, &followed).context("failed to load titles")?
+        }
+    };
+    let titles: Vec<CachedTitle> = titles
+        .into_iter()
+        .filter(|t| t.tmdb_series_id.is_some())
+        .collect();
+    if titles.is_empty() && !output.is_json() {
+        tracing::info!("No titles with a TMDB series mapping (follow titles or pass --all)");
+        return Ok(());
+    }
+
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+    let tmdb_client = build_tmdb_client(config_file).context("failed to build TMDB client")?;
+    let tids: Vec<u32> = titles.iter().map(|t| t.tid).collect();
+    let programs = load_programs_by_tids(&conn, &tids).context("failed to load programs")?;
+    let ranges = load_season_ranges(&conn).context("failed to load season ranges")?;
+    let today = syoboi_local_time(Utc::now()).format("%Y-%m-%d").to_string();
+
+    let mut rows = Vec::new();
+    for title in &titles {
+        match title_coverage(&tmdb_client, title, &programs, &ranges, &language, &today).await {
+            Ok(coverage) => rows.extend(coverage),
+            Err(e) => tracing::warn!(tid = title.tid, error = %e, "Skipping title"),
+        }
+    }
+    if output.is_json() {
+        return write_json(&rows);
+    }
+    tracing::info!("tid\tseason\taired\tcached\tpercent\ttitle\tmissing");
+    for row in &rows {
+        tracing::info!("{}", format_coverage_row(row));
+    }
+    let incomplete = rows.iter().filter(|r| !r.missing.is_empty()).count();
+    tracing::info!(
+        "{incomplete} of {} season(s) have missing episodes",
+        rows.len()
+    );
+    Ok(())
+}
+
+/// Formats one `report coverage` text line.
+fn format_coverage_row(row: &SeasonCoverage) -> String {
+    let season = row
+        .season_number
+        .map_or_else(|| String::from("group"), |s| s.to_string());
+    let missing = if row.missing.is_empty() {
+        String::from("-")
+    } else {
+        row.missing
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(
+        "{}\t{season}\t{}\t{}\t{}%\t{}\t{missing}",
+        row.tid,
+        row.aired,
+        row.cached,
+        row.percent(),
+        row.title
+    )
+}
+
+/// Runs `report gaps`: for each title, lists episode numbers missing from
+/// the cached `count` sequence and counts used with different subtitles.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_report_gaps(
+    args: &ReportGapsArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+    let titles = match (&args.tids, args.all) {
+        (Some(tids), _) => load_titles_by_tids(&conn, tids).context("failed to load titles")?,
+        (None, true) => load_titles(&conn).context("failed to load titles")?,
+        (None, false) => {
+            let followed = load_followed_tids(&conn).context("failed to load followed titles")?;
+            load_titles_by_tids(&conn, &followed).context("failed to load titles")?
+        }
+    };
+    let tids: Vec<u32> = titles.iter().map(|t| t.tid).collect();
+    let programs = load_programs_by_tids(&conn, &tids).context("failed to load programs")?;
+    let rows: Vec<TitleGaps> = titles
+        .iter()
+        .map(|t| title_gaps(t, &programs))
+        .filter(TitleGaps::has_issues)
+        .collect();
+    if output.is_json() {
+        return write_json(&rows);
+    }
+    if titles.is_empty() {
+        tracing::info!("No titles to check (follow titles or pass --all)");
+        return Ok(());
+    }
+    tracing::info!("tid\tfirst\tlast\tmissing\tduplicates\ttitle");
+    for row in &rows {
+        tracing::info!("{}", format_gaps_row(row));
+    }
+    tracing::info!(
+        "{} of {} title(s) have missing or duplicated counts",
+        rows.len(),
+        titles.len()
+    );
+    Ok(())
+}
+
+/// Formats one `report gaps` text line; duplicates are listed as
+/// `count(subtitle|subtitle)`.
+fn format_gaps_row(row: &TitleGaps) -> String {
+    let bound = |c: Option<u32>| c.map_or_else(|| String::from("-"), |c| c.to_string());
+    let missing = if row.missing.is_empty() {
+        String::from("-")
+    } else {
+        row.missing
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let duplicates = if row.duplicates.is_empty() {
+        String::from("-")
+    } else {
+        row.duplicates
+            .iter()
+            .map(|d| format!("{}({})", d.count, d.sub_titles.join("|")))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(
+        "{}\t{}\t{}\t{missing}\t{duplicates}\t{}",
+        row.tid,
+        bound(row.first),
+        bound(row.last),
+        row.title
+    )
+}
+
+// ── jobs subcommand ───────────────────────────────────────────
+
+/// Default retention for `prune` jobs, in days.
+const DEFAULT_PRUNE_RETENTION_DAYS: u32 = 90;
+
+/// Look-ahead window for `notify` jobs, in hours.
+const NOTIFY_LOOKAHEAD_HOURS: i64 = 24;
+
+/// Runs `jobs list`.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_jobs_list(args: &JobsListArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let jobs = JobQueue::new(&conn)
+        .list(args.state)
+        .context("failed to load jobs")?;
+    if jobs.is_empty() {
+        tracing::info!("No jobs.");
+        return Ok(());
+    }
+    tracing::info!("ID\tKind\tState\tAttempts\tScheduled\t\tUpdated\t\t\tLastError");
+    for job in &jobs {
+        tracing::info!(
+            "{}\t{}\t{}\t{}/{}\t{}\t{}\t{}",
+            job.id,
+            job.kind,
+            job.state,
+            job.attempts,
+            job.max_attempts,
+            job.scheduled_at,
+            job.updated_at,
+            job.last_error.as_deref().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+/// Runs `jobs add`.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_jobs_add(args: &JobsAddArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let now = Utc::now();
+    let run_at = now
+        .checked_add_signed(chrono::TimeDelta::seconds(i64::from(args.delay)))
+        .unwrap_or(now);
+    let id = JobQueue::new(&conn)
+        .enqueue(args.kind, args.payload.as_deref(), run_at)
+        .context("failed to enqueue job")?;
+    tracing::info!("Queued {} job {id}", args.kind);
+    Ok(())
+}
+
+/// Runs `jobs cancel`.
+///
+/// # Errors
+///
+/// Returns an error if the job does not exist or DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_jobs_cancel(args: &JobIdArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    if JobQueue::new(&conn)
+        .cancel(args.id, Utc::now())
+        .context("failed to cancel job")?
+    {
+        tracing::info!("Cancelled job {}", args.id);
+    } else {
+        tracing::info!("Job {} already finished; nothing to cancel", args.id);
+    }
+    Ok(())
+}
+
+/// Runs `jobs run-now`: claims the job, executes it inline, and records
+/// success or failure (with retry scheduling) in the queue.
+///
+/// # Errors
+///
+/// Returns an error if the job cannot be claimed or its execution fails.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_jobs_run_now(args: &JobIdArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let pool = open_data_pool(config_file)?;
+    // The connection is not `Sync`, so it is returned before the await point.
+    let job = {
+        let conn = pool.get().context("failed to open database")?;
+        JobQueue::new(&conn)
+            .claim_now(args.id, Utc::now())
+            .context("failed to claim job")?
+    };
+    run_claimed_job(&job, &pool, config_file).await
+}
+
+/// Executes a claimed job and records success or failure (with retry
+/// scheduling) in the queue.
+///
+/// # Errors
+///
+/// Returns an error if the job fails or its outcome cannot be recorded.
+async fn run_claimed_job(job: &Job, pool: &DbPool, config_file: Option<&PathBuf>) -> Result<()> {
+    tracing::info!(
+        "Running {} job {} (attempt {}/{})",
+        job.kind,
+        job.id,
+        job.attempts,
+        job.max_attempts
+    );
+    let result = Box::pin(execute_job(job, pool, config_file)).await;
+
+    let conn = pool.get().context("failed to open database")?;
+    let queue = JobQueue::new(&conn);
+    match result {
+        Ok(()) => {
+            queue
+                .complete(job.id, Utc::now())
+                .context("failed to mark job succeeded")?;
+            tracing::info!("Job {} succeeded", job.id);
+            Ok(())
+        }
+        Err(e) => {
+            let updated = queue
+                .fail(job.id, &format!("{e:#}"), Utc::now())
+                .context("failed to record job failure")?;
+            if updated.state == JobState::Queued {
+                tracing::warn!("Job {} will retry at {}", job.id, updated.scheduled_at);
+            }
+            Err(e).with_context(|| format!("{} job {} failed", job.kind, job.id))
+        }
+    }
+}
+
+/// Runs the `daemon` subcommand: an incremental sync every interval, each
+/// recorded in `sync_runs`, until SIGINT/SIGTERM.
+///
+/// A signal received during a run lets the run finish before exiting.
+///
+/// # Errors
+///
+/// Returns an error if the config or database cannot be opened. Failed sync
+/// runs are recorded and logged without stopping the daemon.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_daemon(args: &DaemonArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let interval_mins = args
+        .interval_mins
+        .unwrap_or(config.daemon.interval_mins)
+        .max(1);
+    let interval = Duration::from_secs(interval_mins.saturating_mul(60));
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let pool = open_db_pool(data_dir.clone());
+    {
+        let conn = pool.get().context("failed to open database")?;
+        if let Some(last) = load_sync_runs(&conn, 1)
+            .context("failed to load sync runs")?
+            .first()
+        {
+            tracing::info!(
+                "Last sync run #{}: {} at {}",
+                last.id,
+                last.status,
+                last.finished_at.as_deref().unwrap_or(&last.started_at)
+            );
+        }
+    }
+    let metrics_server = match args.metrics_bind {
+        Some(bind) => Some(spawn_metrics_server(bind, pool.clone()).await?),
+        None => None,
+    };
+    tracing::info!("Daemon started (interval: {interval_mins} min)");
+
+    run_daemon_loop(
+        || {
+            Box::pin(run_daemon_cycle(
+                &pool,
+                config_file,
+                config.daemon.process_jobs,
+            ))
+        },
+        interval,
+        args.once,
+        shutdown_signal(),
+    )
+    .await;
+    if let Some(server) = metrics_server {
+        server.abort();
+    }
+    tracing::info!("Daemon stopped");
+    Ok(())
+}
+
+/// Runs `cycle` every `interval` (once with `once`) until `shutdown`
+/// resolves. A shutdown during a cycle lets the cycle finish first.
+///
+/// Each cycle runs in its own [`exit::scoped`] scope: its outcome is logged
+/// and recorded in `sync_runs`, and must not become the daemon's exit
+/// status.
+async fn run_daemon_loop<C, F>(
+    mut cycle: C,
+    interval: Duration,
+    once: bool,
+    shutdown: impl Future<Output = ()>,
+) where
+    C: FnMut() -> F,
+    F: Future<Output = ()>,
+{
+    tokio::pin!(shutdown);
+    loop {
+        let current = exit::scoped(cycle());
+        tokio::pin!(current);
+        let interrupted = tokio::select! {
+            _ = &mut current => false,
+            () = &mut shutdown => true,
+        };
+        if interrupted {
+            tracing::info!("Shutdown requested; finishing the current run");
+            current.await;
+            break;
+        }
+        if once {
+            break;
+        }
+        tokio::select! {
+            () = tokio::time::sleep(interval) => {}
+            () = &mut shutdown => {
+                tracing::info!("Shutdown requested");
+                break;
+            }
+        }
+    }
+}
+
+/// Binds `bind` and serves the REST API (including `/metrics`) in the
+/// background until the returned task is aborted.
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound.
+async fn spawn_metrics_server(
+    bind: SocketAddr,
+    pool: DbPool,
+) -> Result<tokio::task::JoinHandle<()>> {
+    let listener = tokio::net::TcpListener::bind(bind)
+        .await
+        .with_context(|| format!("failed to bind {bind}"))?;
+    let addr = listener
+        .local_addr()
+        .context("failed to read bound address")?;
+    tracing::info!("Serving metrics on http://{addr}/metrics");
+    Ok(tokio::spawn(async move {
+        if let Err(e) = dtvmgr_server::serve(listener, pool, core::future::pending::<()>()).await {
+            tracing::warn!(error = %format!("{e:#}"), "metrics server stopped");
+        }
+    }))
+}
+
+/// Records the outcome of one daemon sync in the Prometheus registry.
+fn record_sync_metrics(summary: Option<&SyncSummary>, elapsed: Duration) {
+    let registry = dtvmgr_api::prometheus::global();
+    let result = if summary.is_some() { "ok" } else { "error" };
+    registry.add(
+        "dtvmgr_sync_runs_total",
+        "Daemon sync runs by result.",
+        &[("result", result)],
+        1.0,
+    );
+    registry.add(
+        "dtvmgr_sync_duration_seconds_total",
+        "Total time spent in daemon sync runs.",
+        &[],
+        elapsed.as_secs_f64(),
+    );
+    registry.set(
+        "dtvmgr_sync_last_duration_seconds",
+        "Duration of the most recent daemon sync run.",
+        &[],
+        elapsed.as_secs_f64(),
+    );
+    if let Some(summary) = summary {
+        for (table, rows) in [
+            ("titles", summary.titles_changed),
+            ("programs", summary.programs_changed),
+        ] {
+            #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
+            let rows = rows as f64;
+            registry.add(
+                "dtvmgr_sync_rows_upserted_total",
+                "Rows inserted or changed by daemon sync runs.",
+                &[("table", table)],
+                rows,
+            );
+        }
+    }
+}
+
+/// Runs one daemon cycle: an incremental sync, its `sync_runs` record, and
+/// (optionally) every due job. Failures are logged, never returned.
+async fn run_daemon_cycle(pool: &DbPool, config_file: Option<&PathBuf>, process_jobs: bool) {
+    let args = DbSyncArgs {
+        time_since: None,
+        time_until: None,
+        season: None,
+        ch_ids: None,
+        tids: None,
+        incremental: true,
+        followed_only: false,
+        category: None,
+        resume: None,
+    };
+    // `sync_db` records the run (and its failure) in `sync_runs`.
+    let started = std::time::Instant::now();
+    let result = sync_db(&args, pool, config_file, &NoProgress).await;
+    record_sync_metrics(result.as_ref().ok(), started.elapsed());
+    if let Ok(summary) = result {
+        tracing::info!(
+            "Sync run #{} succeeded: {} titles ({} changed), {} programs ({} changed)",
+            summary.run_id,
+            summary.titles,
+            summary.titles_changed,
+            summary.programs,
+            summary.programs_changed,
+        );
+    }
+
+    if !process_jobs {
+        return;
+    }
+    loop {
+        let claimed = pool
+            .get()
+            .and_then(|conn| JobQueue::new(&conn).claim_next_due(Utc::now()));
+        match claimed {
+            Ok(Some(job)) => {
+                // Failures are recorded in the queue and logged by `run_claimed_job`.
+                let _ = run_claimed_job(&job, pool, config_file).await;
+            }
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!(error = %format!("{e:#}"), "failed to claim due job");
+                break;
+            }
+        }
+    }
+}
+
+/// Runs the `serve` subcommand: the REST API on `--bind` until
+/// SIGINT/SIGTERM.
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened, the address cannot be
+/// bound, or accepting connections fails.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_serve(args: &ServeArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let pool = open_db_pool(data_dir);
+    // Fail fast on a broken database instead of on the first request.
+    drop(pool.get().context("failed to open database")?);
+    let listener = tokio::net::TcpListener::bind(args.bind)
+        .await
+        .with_context(|| format!("failed to bind {}", args.bind))?;
+    let addr = listener
+        .local_addr()
+        .context("failed to read bound address")?;
+    tracing::info!("Serving on http://{addr}");
+    dtvmgr_server::serve(listener, pool, shutdown_signal()).await?;
+    tracing::info!("Server stopped");
+    Ok(())
+}
+
+/// Resolves when SIGINT (Ctrl-C) or, on Unix, SIGTERM is received.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %e, "failed to listen for Ctrl-C");
+            core::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to listen for SIGTERM");
+                core::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = core::future::pending::<()>();
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
+/// Executes a single job on connections from `pool`.
+///
+/// # Errors
+///
+/// Returns an error if the underlying operation fails.
+async fn execute_job(job: &Job, pool: &DbPool, config_file: Option<&PathBuf>) -> Result<()> {
+    match job.kind {
+        JobKind::Sync => {
+            let args = match job.payload.as_deref() {
+                Some(payload) => serde_json::from_str::<SyncJobPayload>(payload)
+                    .context("invalid sync job payload")?
+                    .into_args(),
+                None => DbSyncArgs {
+                    time_since: None,
+                    time_until: None,
+                    season: None,
+                    ch_ids: None,
+                    tids: None,
+                    incremental: false,
+                    followed_only: false,
+                    category: None,
+                    resume: None,
+                },
+            };
+            sync_db(&args, pool, config_file, &TerminalProgress::new())
+                .await
+                .map(|_| ())
+        }
+        JobKind::Prefetch => {
+            let args = DbTmdbLookupArgs {
+                tids: None,
+                language: None,
+                force: false,
+                retry_unmapped: false,
+            };
+            tmdb_lookup(&args, pool, config_file).await
+        }
+        JobKind::Prune => {
+            let days = parse_prune_days(job.payload.as_deref())?;
+            let cutoff = prune_cutoff(syoboi_local_time(Utc::now()), days)?;
+            let report =
+                prune_programs(pool, &cutoff, false, false).context("failed to prune programs")?;
+            tracing::info!("Pruned {} programs ended before {cutoff}", report.programs);
+            Ok(())
+        }
+        JobKind::Notify => {
+            let now = syoboi_local_time(Utc::now());
+            let digest = {
+                let conn = pool.get().context("failed to open database")?;
+                report_upcoming_watchlist(&conn, now)?;
+                followed_digest(&conn, now.date())?
+            };
+            let config_path =
+                resolve_config_path(config_file).context("failed to resolve config path")?;
+            let config = AppConfig::load(&config_path).context("failed to load config")?;
+            if config.notify.email.host.is_none() {
+                return Ok(());
+            }
+            send_digest_email(&config.notify.email, &digest).await?;
+            tracing::info!("Sent digest of {} programs", digest.programs);
+            Ok(())
+        }
+    }
+}
+
+/// Parses the retention days of a `prune` job payload.
+///
+/// # Errors
+///
+/// Returns an error if the payload is not a non-negative integer.
+fn parse_prune_days(payload: Option<&str>) -> Result<u32> {
+    payload
+        .map(|p| p.trim().parse::<u32>())
+        .transpose()
+        .context("prune payload must be a number of days")
+        .map(|days| days.unwrap_or(DEFAULT_PRUNE_RETENTION_DAYS))
+}
+
+/// Returns the `ed_time` cutoff (Syoboi format) for pruning `days` before `now`.
+///
+/// # Errors
+///
+/// Returns an error if the cutoff is out of range.
+fn prune_cutoff(now: chrono::NaiveDateTime, days: u32) -> Result<String> {
+    let cutoff = now
+        .checked_sub_signed(chrono::TimeDelta::days(i64::from(days)))
+        .context("prune cutoff out of range")?;
+    Ok(cutoff.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Converts a UTC instant into Syoboi local time (JST, naive).
+fn syoboi_local_time(now: chrono::DateTime<Utc>) -> chrono::NaiveDateTime {
+    now.with_timezone(&SYOBOI_TIMEZONE).naive_local()
+}
+
+/// Logs programs of watched titles that start within the look-ahead window.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+fn report_upcoming_watchlist(
+    conn: &dtvmgr_db::Connection,
+    now: chrono::NaiveDateTime,
+) -> Result<()> {
+    let watchlist = load_watchlist(conn).context("failed to load watchlist")?;
+    let tids: Vec<u32> = watchlist.iter().map(|e| e.tid).collect();
+    let programs = load_programs_by_tids(conn, &tids).context("failed to load programs")?;
+    let titles = load_titles_by_tids(conn, &tids).context("failed to load titles")?;
+    let names: std::collections::HashMap<u32, &str> =
+        titles.iter().map(|t| (t.tid, t.title.as_str())).collect();
+
+    let from = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let until = now
+        .checked_add_signed(chrono::TimeDelta::hours(NOTIFY_LOOKAHEAD_HOURS))
+        .unwrap_or(now)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    let upcoming: Vec<&CachedProgram> = programs
+        .iter()
+        .filter(|p| p.st_time >= from && p.st_time < until)
+        .collect();
+    tracing::info!(
+        "{} upcoming programs for watched titles in the next {NOTIFY_LOOKAHEAD_HOURS}h",
+        upcoming.len()
+    );
+    let tz = display_timezone();
+    for p in upcoming {
+        let weekday = syoboi_time_in(&p.st_time, tz)
+            .map_or_else(String::new, |dt| dt.format(" (%a)").to_string());
+        tracing::info!(
+            "{}{weekday}\tch {}\t#{}\t{}",
+            display_broadcast_time(&p.st_time, tz),
+            p.ch_id,
+            p.count.map_or_else(|| String::from("-"), |c| c.to_string()),
+            names.get(&p.tid).copied().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+// ── jlse subcommands ──────────────────────────────────────────
+
+/// Resolves the `JlseConfig` from the app config.
+///
+/// # Errors
+///
+/// Returns an error if the jlse section is not configured.
+fn resolve_jlse_config(config_file: Option<&PathBuf>) -> Result<JlseConfig> {
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let jlse = config
+        .jlse
+        .context("jlse config not found in dtvmgr.toml; add [jlse.dirs] with jl, logo, result")?;
+    anyhow::ensure!(
+        jlse.dirs.is_configured(),
+        "jlse.dirs is not configured in dtvmgr.toml; set jl, logo, result paths"
+    );
+    Ok(jlse)
+}
+
+/// Environment variable name for channel name override.
+///
+/// The original Node.js tool uses `CHNNELNAME` (intentional typo preserved).
+const CHANNEL_ENV_VAR: &str = "CHNNELNAME";
+
+/// Resolve channel name from CLI argument or environment variable.
+fn resolve_channel_name(arg: Option<&str>) -> Option<String> {
+    arg.map(ToOwned::to_owned)
+        .or_else(|| std::env::var(CHANNEL_ENV_VAR).ok())
+}
+
+/// Detect the recording target from the middle of a TS file.
+///
+/// Thin wrapper around [`dtvmgr_tsduck::detect_target_from_middle`] that
+/// discards the raw XML.
+fn detect_target_from_middle(
+    bin: &Path,
+    input: &Path,
+) -> Result<Option<dtvmgr_tsduck::eit::RecordingTarget>> {
+    let (target, _xml) = dtvmgr_tsduck::detect_target_from_middle(bin, input)?;
+    Ok(target)
+}
+
+/// Runs the `jlse tsduck` subcommand.
+///
+/// Extracts EIT program information from a TS file using `TSDuck`.
+/// When `--sid` or `-c` is given, filters by that service ID using EIT-only
+/// extraction. Otherwise, extracts all tables (PAT + EIT) and auto-detects
+/// the recording target from PAT's first service ID.
+///
+/// # Errors
+///
+/// Returns an error if `TSDuck` fails or the EIT XML cannot be parsed.
+#[allow(clippy::print_stdout)]
+#[instrument(skip_all, err(level = "error"))]
+fn run_jlse_tsduck(args: &JlseTsduckArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let jlse_config = resolve_jlse_config(config_file)?;
+    let bins = BinaryPaths::from_config(&jlse_config);
+
+    // --sid takes priority over -c (channel name detection).
+    let explicit_sid = if let Some(ref sid) = args.sid {
+        Some(sid.clone())
+    } else if args.channel.is_some() {
+        let data = DataPaths::from_config(&jlse_config);
+        let channels = load_jlse_channels(&data.channel_list)?;
+        let channel_name = resolve_channel_name(args.channel.as_deref());
+        let filepath = args.input.to_string_lossy();
+        let ch = detect_channel(&channels, &filepath, channel_name.as_deref());
+        if let Some(ref ch) = ch {
+            println!("=== Channel Detection ===");
+            println!("short: {}", ch.short);
+            println!("service_id: {}", ch.service_id);
+            println!();
+        }
+        ch.map(|c| c.service_id)
+    } else {
+        None
+    };
+
+    let programs = if let Some(ref sid) = explicit_sid {
+        // Explicit SID: extract EIT only (PID 0x12).
+        let xml = dtvmgr_tsduck::command::extract_eit(&bins.tstables, &args.input)?;
+        println!("=== EIT Program Information (SID: {sid}) ===");
+        dtvmgr_tsduck::eit::parse_eit_xml_by_sid(&xml, sid)
+            .with_context(|| format!("failed to parse EIT XML for SID {sid}"))?
+    } else {
+        // No explicit SID: extract PAT (PID 0) and EIT (PID 0x12) separately.
+        let pat_xml = dtvmgr_tsduck::command::extract_pat(&bins.tstables, &args.input)?;
+        let pat_sid = dtvmgr_tsduck::pat::parse_pat_first_service_id(&pat_xml)
+            .context("failed to parse PAT XML")?;
+
+        let eit_xml = dtvmgr_tsduck::command::extract_eit(&bins.tstables, &args.input)?;
+        if let Some(sid) = pat_sid {
+            println!("=== EIT Program Information (SID: {sid} from PAT) ===");
+            let all =
+                dtvmgr_tsduck::eit::parse_eit_xml(&eit_xml).context("failed to parse EIT XML")?;
+            all.into_iter().filter(|p| p.service_id == sid).collect()
+        } else {
+            println!("=== EIT Program Information ===");
+            dtvmgr_tsduck::eit::parse_eit_xml(&eit_xml).context("failed to parse EIT XML")?
+        }
+    };
+
+    let programs = dtvmgr_tsduck::eit::dedup_programs(programs);
+
+    // Detect recording target from middle-of-file EIT p/f.
+    let recording_target = detect_target_from_middle(&bins.tstables, &args.input);
+    let target_event_id = match &recording_target {
+        Ok(Some(target)) => {
+            println!(
+                "=== Recording Target: event_id={} ({:?}) ===",
+                target.program.event_id, target.detection_method
+            );
+            println!();
+            Some(target.program.event_id.clone())
+        }
+        Ok(None) => {
+            println!("=== Recording Target: not detected ===");
+            println!();
+            None
+        }
+        Err(e) => {
+            println!("=== Recording Target: detection failed ({e:#}) ===");
+            println!();
+            None
+        }
+    };
+
+    for p in &programs {
+        print_program_info(p, target_event_id.as_deref());
+    }
+    Ok(())
+}
+
+/// Format a single program's information as a string.
+#[allow(clippy::unwrap_used)]
+fn format_program_info(
+    p: &dtvmgr_tsduck::eit::ProgramInfo,
+    target_event_id: Option<&str>,
+) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::new();
+    let marker = if target_event_id == Some(&p.event_id) {
+        "[recording_target] "
+    } else {
+        ""
+    };
+    writeln!(out, "--- {marker}event_id: {} ---", p.event_id).unwrap();
+    writeln!(out, "  service_id: {}", p.service_id).unwrap();
+    writeln!(out, "  start_time: {}", p.start_time).unwrap();
+    writeln!(
+        out,
+        "  duration: {} ({} min / {} sec)",
+        p.duration_raw,
+        p.duration_min(),
+        p.duration_sec
+    )
+    .unwrap();
+    writeln!(out, "  running_status: {}", p.running_status).unwrap();
+    if let Some(name) = &p.program_name {
+        writeln!(out, "  program_name: {name}").unwrap();
+    }
+    if let Some(desc) = &p.description {
+        writeln!(out, "  description: {desc}").unwrap();
+    }
+    if let Some(tt) = &p.table_type {
+        writeln!(out, "  table_type: {tt}").unwrap();
+    }
+    if let Some(ext) = p.extended() {
+        writeln!(out, "  extended: {ext}").unwrap();
+    }
+    if !p.raw_extended.is_empty() {
+        for (key, value) in &p.raw_extended {
+            writeln!(out, "  raw_extended[{key}]: {value}").unwrap();
+        }
+    }
+    if let Some(g) = p.genre1 {
+        writeln!(out, "  genre: {g}/{}", p.sub_genre1.unwrap_or(0)).unwrap();
+    }
+    if let Some(r) = p.video_resolution() {
+        writeln!(out, "  video: {r}").unwrap();
+    }
+    if let Some(rate) = p.audio_sampling_rate() {
+        writeln!(out, "  audio: {rate}Hz").unwrap();
+    }
+    writeln!(out).unwrap();
+    out
+}
+
+/// Print a single program's information to stdout.
+#[allow(clippy::print_stdout)]
+fn print_program_info(p: &dtvmgr_tsduck::eit::ProgramInfo, target_event_id: Option<&str>) {
+    print!("{}", format_program_info(p, target_event_id));
+}
+
+/// Runs the `jlse channel` subcommand.
+///
+/// Detects broadcast channel from the input filename.
+///
+/// # Errors
+///
+/// Returns an error if the channel list cannot be loaded.
+#[allow(clippy::print_stdout)]
+#[instrument(skip_all, err(level = "error"))]
+fn run_jlse_channel(args: &JlseChannelArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let jlse_config = resolve_jlse_config(config_file)?;
+    let data = DataPaths::from_config(&jlse_config);
+    let channels = load_jlse_channels(&data.channel_list)?;
+
+    let channel_name = resolve_channel_name(args.channel.as_deref());
+
+    let filepath = args.input.to_string_lossy();
+    let result = detect_channel(&channels, &filepath, channel_name.as_deref());
+
+    match result {
+        Some(ch) => {
+            println!("recognize: {}", ch.recognize);
+            println!("install: {}", ch.install);
+            println!("short: {}", ch.short);
+            println!("service_id: {}", ch.service_id);
+        }
+        None => {
+            println!("No channel detected.");
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `jlse param` subcommand.
+///
+/// Detects JL parameters from the channel and input filename.
+///
+/// # Errors
+///
+/// Returns an error if the param lists cannot be loaded.
+#[allow(clippy::print_stdout)]
+#[instrument(skip_all, err(level = "error"))]
+fn run_jlse_param(args: &JlseParamArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let jlse_config = resolve_jlse_config(config_file)?;
+    let data = DataPaths::from_config(&jlse_config);
+    let channels = load_jlse_channels(&data.channel_list)?;
+
+    let channel_name = resolve_channel_name(args.channel.as_deref());
+    let filepath = args.input.to_string_lossy();
+    let channel = detect_channel(&channels, &filepath, channel_name.as_deref());
+
+    let params_jl1 = load_params(&data.param_jl1)?;
+    let params_jl2 = load_params(&data.param_jl2)?;
+
+    let filename = args.input.file_stem().unwrap_or_default().to_string_lossy();
+    let result = detect_param(&params_jl1, &params_jl2, channel.as_ref(), &filename);
+
+    println!("jl_run: {}", result.jl_run);
+    println!("flags: {}", result.flags);
+    println!("options: {}", result.options);
+    Ok(())
+}
+
+/// Spawns a pipeline thread and runs the TUI progress viewer.
+///
+/// Suppresses tracing output on the pipeline thread to prevent
+/// log lines from corrupting the TUI alternate screen.
+#[instrument(skip_all, err(level = "error"))]
+fn run_pipeline_with_tui(ctx: PipelineContext) -> Result<()> {
+    let (tx, rx) = std::sync::mpsc::channel::<ProgressEvent>();
+    let parent_span = tracing::Span::current();
+    let handle = std::thread::spawn(move || {
+        let _entered = parent_span.enter();
+        let cb = move |event: ProgressEvent| {
+            // Ignore send errors (receiver may have been dropped on quit)
+            let _ = tx.send(event);
+        };
+        run_pipeline(&ctx, Some(&cb))
+    });
+    dtvmgr_tui::progress_viewer::run_progress_viewer(&rx, handle)
+}
+
+/// Runs the `jlse run` subcommand.
+///
+/// Executes the full CM detection pipeline.
+///
+/// # Errors
+///
+/// Returns an error if the pipeline fails.
+#[instrument(skip_all, err(level = "error"))]
+fn run_jlse_run(args: &JlseRunArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let jlse_config = resolve_jlse_config(config_file)?;
+
+    let channel_name = resolve_channel_name(args.channel.as_deref());
+
+    if args.epgstation {
         // EPGStation mode: read INPUT/OUTPUT from environment variables.
         let input = args
             .input
@@ -2179,340 +7241,2685 @@ fn run_jlse_run(args: &JlseRunArgs, config_file: Option<&PathBuf>) -> Result<()>
             .or_else(|| std::env::var("INPUT").ok().map(PathBuf::from))
             .context("INPUT environment variable is required in --epgstation mode")?;
 
-        let (out_dir, out_name, out_extension) = std::env::var("OUTPUT").map_or_else(
-            |_| (args.outdir.clone(), args.outname.clone(), None),
-            |output_str| {
-                let output_path = PathBuf::from(&output_str);
-                let dir = output_path
-                    .parent()
-                    .filter(|p| !p.as_os_str().is_empty())
-                    .map(Path::to_path_buf);
-                let name = output_path
-                    .file_stem()
-                    .map(|s| s.to_string_lossy().into_owned());
-                let ext = output_path
-                    .extension()
-                    .map(|e| e.to_string_lossy().into_owned());
-                (dir, name, ext)
-            },
-        );
+        let (out_dir, out_name, out_extension) = std::env::var("OUTPUT").map_or_else(
+            |_| (args.outdir.clone(), args.outname.clone(), None),
+            |output_str| {
+                let output_path = PathBuf::from(&output_str);
+                let dir = output_path
+                    .parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .map(Path::to_path_buf);
+                let name = output_path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned());
+                let ext = output_path
+                    .extension()
+                    .map(|e| e.to_string_lossy().into_owned());
+                (dir, name, ext)
+            },
+        );
+
+        let ctx = PipelineContext {
+            input,
+            channel_name,
+            config: jlse_config,
+            encode: true, // implicitly enabled
+            target: AvsTarget::from(args.target),
+            add_chapter: args.add_chapter,
+            out_dir,
+            out_name,
+            out_extension,
+            remove: args.remove,
+            progress_mode: Some(ProgressMode::EpgStation),
+            skip_duration_check: args.skip_duration_check,
+            force: args.force,
+        };
+
+        record_storage_metrics(&ctx);
+
+        if args.tui {
+            run_pipeline_with_tui(ctx)
+        } else {
+            // EPGStation callback mode (JSON output)
+            let last_stage = Cell::new((0_u8, 0_u8));
+            let cb = |event: ProgressEvent| {
+                use dtvmgr_jlse::progress::emit_epgstation;
+                match event {
+                    ProgressEvent::StageStart { stage, total, name } => {
+                        last_stage.set((stage, total));
+                        emit_epgstation(0.0, &format!("({stage}/{total}) {name}: starting"));
+                    }
+                    ProgressEvent::StageProgress { percent, log } => {
+                        emit_epgstation(percent, &log);
+                    }
+                    ProgressEvent::Encoding { percent, log } => {
+                        let (s, t) = last_stage.get();
+                        let prefixed = format!("({s}/{t}) FFmpeg: {log}");
+                        emit_epgstation(percent, &prefixed);
+                    }
+                    ProgressEvent::Log(_) | ProgressEvent::Finished => {}
+                }
+            };
+
+            run_pipeline(&ctx, Some(&cb))
+        }
+    } else {
+        let input = args.input.clone().context("--input is required")?;
+
+        let ctx = PipelineContext {
+            input,
+            channel_name,
+            config: jlse_config,
+            encode: args.encode,
+            target: AvsTarget::from(args.target),
+            add_chapter: args.add_chapter,
+            out_dir: args.outdir.clone(),
+            out_name: args.outname.clone(),
+            out_extension: None,
+            remove: args.remove,
+            progress_mode: None,
+            skip_duration_check: args.skip_duration_check,
+            force: args.force,
+        };
+
+        record_storage_metrics(&ctx);
+
+        if args.tui {
+            run_pipeline_with_tui(ctx)
+        } else {
+            run_pipeline(&ctx, None)
+        }
+    }
+}
+
+/// Record `OTel` gauge metrics for storage directories referenced by the pipeline.
+fn record_storage_metrics(ctx: &PipelineContext) {
+    let ts_dir = ctx.input.parent().unwrap_or_else(|| Path::new("/"));
+    record_storage_dir_metrics(ts_dir, "ts_input");
+    if let Some(dir) = ctx.out_dir.as_deref() {
+        record_storage_dir_metrics(dir, "encoded_output");
+    }
+}
+
+/// Record `OTel` gauge metrics for a single directory.
+#[cfg(feature = "otel")]
+fn record_storage_dir_metrics(dir: &Path, role: &str) {
+    if let Some(stats) = collect_storage_stats(dir) {
+        use opentelemetry::KeyValue;
+
+        let attrs = &[
+            KeyValue::new("path", stats.path.clone()),
+            KeyValue::new("role", role.to_owned()),
+        ];
+        cli_metrics::STORAGE_FREE_BYTES.record(stats.free_bytes, attrs);
+        cli_metrics::STORAGE_TOTAL_BYTES.record(stats.total_bytes, attrs);
+        cli_metrics::STORAGE_USED_BYTES.record(stats.used_bytes, attrs);
+        cli_metrics::STORAGE_USAGE_RATIO.record(stats.usage_ratio, attrs);
+        cli_metrics::STORAGE_FILE_COUNT.record(stats.file_count, attrs);
+    }
+}
+
+/// No-op when OTel is disabled.
+#[cfg(not(feature = "otel"))]
+fn record_storage_dir_metrics(_dir: &Path, _role: &str) {}
+
+/// Convert `StorageStats` to `StorageStatsSnapshot` for TUI display.
+const fn to_snapshot(s: &dtvmgr_jlse::storage::StorageStats) -> StorageStatsSnapshot {
+    StorageStatsSnapshot {
+        total_bytes: s.total_bytes,
+        used_bytes: s.used_bytes,
+        usage_ratio: s.usage_ratio,
+        file_count: s.file_count,
+    }
+}
+
+/// Populate storage stats in `EncodeSelectorState` entries.
+fn populate_storage_stats(state: &mut EncodeSelectorState) {
+    for entry in &mut state.storage_dirs {
+        entry.stats = collect_storage_stats(Path::new(&entry.path)).map(|s| to_snapshot(&s));
+    }
+}
+
+// ── Syoboi / TMDB helpers ────────────────────────────────────
+
+/// Process-wide database connection options (set from `--db-readonly`).
+static DB_OPTIONS: OnceLock<DbOptions> = OnceLock::new();
+
+/// Opens the database with the process-wide [`DbOptions`].
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened or migrated.
+fn open_db(dir: Option<&PathBuf>) -> Result<dtvmgr_db::Connection> {
+    open_db_with_options(dir, DB_OPTIONS.get_or_init(DbOptions::default))
+}
+
+/// Opens the database in the resolved data directory ([`Paths::open_db`])
+/// with the process-wide [`DbOptions`].
+///
+/// # Errors
+///
+/// Returns an error if the data directory cannot be resolved or the
+/// database cannot be opened or migrated.
+fn open_data_db(config_file: Option<&PathBuf>) -> Result<dtvmgr_db::Connection> {
+    Paths::current()?.open_db(config_file, DB_OPTIONS.get_or_init(DbOptions::default))
+}
+
+/// Creates a connection pool with the process-wide [`DbOptions`] for
+/// long-running commands whose tasks use the database concurrently.
+fn open_db_pool(dir: Option<PathBuf>) -> DbPool {
+    DbPool::new(
+        dir,
+        *DB_OPTIONS.get_or_init(DbOptions::default),
+        DEFAULT_POOL_SIZE,
+    )
+}
+
+/// Creates a connection pool over the database in the data directory.
+///
+/// # Errors
+///
+/// Returns an error if the data directory cannot be resolved.
+fn open_data_pool(config_file: Option<&PathBuf>) -> Result<DbPool> {
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    Ok(open_db_pool(data_dir))
+}
+
+/// Process-wide API audit log (set from `--audit-log`).
+static AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+
+/// Per-host request rate overrides from the config file.
+#[derive(Debug, Clone, Copy)]
+struct RateLimits {
+    /// `[syoboi.rate_limit]`.
+    syoboi: RateLimitConfig,
+    /// `[tmdb.rate_limit]`.
+    tmdb: RateLimitConfig,
+}
+
+/// Process-wide rate limit overrides (set from the config file at startup).
+static RATE_LIMITS: OnceLock<RateLimits> = OnceLock::new();
+
+/// Process-wide `[http]` proxy, timeout, and TLS settings.
+static HTTP_OPTIONS: OnceLock<HttpOptions> = OnceLock::new();
+
+/// Process-wide display timezone (set from the config `timezone` key).
+static DISPLAY_TIMEZONE: OnceLock<Tz> = OnceLock::new();
+
+/// Returns the timezone broadcast times are displayed in.
+fn display_timezone() -> Tz {
+    DISPLAY_TIMEZONE.get().copied().unwrap_or(SYOBOI_TIMEZONE)
+}
+
+/// Reads the `timezone`, `rate_limit`, and `[http]` settings of the config
+/// file, if it exists.
+///
+/// Runs before logging is set up, so an unreadable or invalid config is
+/// skipped silently here and reported by the command that loads it.
+fn init_client_settings(config_file: Option<&PathBuf>) {
+    let Ok(path) = resolve_config_path(config_file) else {
+        return;
+    };
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return;
+    };
+    if let Ok((config, _)) = AppConfig::parse(&content) {
+        let _ = RATE_LIMITS.set(RateLimits {
+            syoboi: config.syoboi.rate_limit,
+            tmdb: config.tmdb.rate_limit,
+        });
+        let _ = HTTP_OPTIONS.set(config.http.to_options());
+        if let Ok(tz) = config.timezone() {
+            let _ = DISPLAY_TIMEZONE.set(tz);
+        }
+    }
+}
+
+/// Process-wide response recording or replay (set from `--record` / `--replay`).
+static CASSETTE: OnceLock<Cassette> = OnceLock::new();
+
+/// Selects the `--record` or `--replay` cassette, if requested.
+fn init_cassette(record: Option<&Path>, replay: Option<&Path>) {
+    let cassette = match (record, replay) {
+        (Some(dir), _) => Cassette::record(dir),
+        (None, Some(dir)) => Cassette::replay(dir),
+        (None, None) => return,
+    };
+    let _ = CASSETTE.set(cassette);
+}
+
+/// File name (under the cache directory) of the persisted Syoboi rate limit state.
+const SYOBOI_RATE_LIMIT_STATE_FILE: &str = "syoboi_rate_limit.json";
+
+/// Env var overriding the Syoboi `db.php` URL (e.g. `dtvmgr-mock-server`).
+const SYOBOI_URL_ENV: &str = "DTVMGR_SYOBOI_URL";
+
+/// Env var overriding the TMDB API base URL (e.g. `dtvmgr-mock-server`).
+const TMDB_URL_ENV: &str = "DTVMGR_TMDB_URL";
+
+/// Opens the `--audit-log` file, if requested.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened.
+fn init_audit_log(path: Option<&Path>) -> Result<()> {
+    if let Some(path) = path {
+        let log = AuditLog::open(path)?;
+        let _ = AUDIT_LOG.set(log);
+    }
+    Ok(())
+}
+
+/// Builds a `SyoboiClient` with default user agent and persisted rate limit state.
+///
+/// # Errors
+///
+/// Returns an error if the client fails to build.
+#[instrument(skip_all, err(level = "error"))]
+fn build_syoboi_client() -> Result<SyoboiClient> {
+    syoboi_client_builder()?
+        .build()
+        .context("failed to build Syoboi API client")
+}
+
+/// Returns a `SyoboiClientBuilder` with the shared user agent, audit log,
+/// configured rate limit and HTTP options, `--record`/`--replay` cassette,
+/// and persisted rate limit state.
+///
+/// # Errors
+///
+/// Returns an error if `DTVMGR_SYOBOI_URL` is set to an invalid URL.
+fn syoboi_client_builder() -> Result<SyoboiClientBuilder> {
+    let mut builder = SyoboiClient::builder()
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .sanitize_xml(true);
+    if let Some(log) = AUDIT_LOG.get() {
+        builder = builder.audit_log(log.clone());
+    }
+    if let Some(limits) = RATE_LIMITS.get() {
+        builder = builder.rate_limit(
+            limits
+                .syoboi
+                .apply(dtvmgr_api::syoboi::default_rate_limit()),
+        );
+    }
+    if let Some(options) = HTTP_OPTIONS.get() {
+        builder = builder.http_options(options.clone());
+    }
+    if let Some(cassette) = CASSETTE.get() {
+        builder = builder.cassette(cassette.clone());
+    }
+    if let Ok(url) = std::env::var(SYOBOI_URL_ENV) {
+        builder = builder.base_url(
+            reqwest::Url::parse(&url).with_context(|| format!("invalid {SYOBOI_URL_ENV}"))?,
+        );
+    }
+    match resolve_cache_dir() {
+        Ok(dir) => builder = builder.state_file(dir.join(SYOBOI_RATE_LIMIT_STATE_FILE)),
+        Err(e) => tracing::warn!(error = %e, "Syoboi rate limit state will not persist"),
+    }
+    Ok(builder)
+}
+
+/// Runs the `syoboi channels select` subcommand.
+///
+/// Shows cached channels/groups immediately and refreshes them from the API
+/// in the background; without a cache, fetches from the API before launching
+/// the TUI. Saves the selection to `dtvmgr.toml`.
+///
+/// # Errors
+///
+/// Returns an error if API calls, DB operations, or TUI fails.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, err(level = "error"))]
+async fn run_channels_select(config_file: Option<&PathBuf>) -> Result<()> {
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let (cached_groups, cached_channels, stats) = {
+        let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+        (
+            load_channel_groups(&conn).context("failed to load cached channel groups")?,
+            load_channels(&conn).context("failed to load cached channels")?,
+            load_channel_program_stats(&conn).context("failed to load channel program stats")?,
+        )
+    };
+
+    let (groups, updates) = if cached_channels.is_empty() {
+        let (api_groups, api_channels) =
+            fetch_and_cache_channels(&open_db_pool(data_dir.clone())).await?;
+        tracing::info!(
+            "Loaded {} groups, {} channels. Launching TUI...",
+            api_groups.len(),
+            api_channels.len()
+        );
+        (build_tui_groups(&api_groups, &api_channels, &stats), None)
+    } else {
+        tracing::info!(
+            "Loaded {} cached groups, {} channels. Launching TUI (refreshing in background)...",
+            cached_groups.len(),
+            cached_channels.len()
+        );
+        let (tx, rx) = std::sync::mpsc::channel::<ChannelUpdate>();
+        let bg_pool = open_db_pool(data_dir.clone());
+        let bg_stats = stats.clone();
+        tokio::spawn(
+            async move {
+                let update = match fetch_and_cache_channels(&bg_pool).await {
+                    Ok((g, c)) => ChannelUpdate::Refreshed(build_tui_groups(&g, &c, &bg_stats)),
+                    Err(e) => ChannelUpdate::Failed(format!("{e:#}")),
+                };
+                let _ = tx.send(update);
+            }
+            .instrument({
+                let span =
+                    tracing::info_span!(parent: tracing::Span::none(), "channel_refresh_worker");
+                span.follows_from(tracing::Span::current());
+                span
+            }),
+        );
+        (
+            build_tui_groups(&cached_groups, &cached_channels, &stats),
+            Some(rx),
+        )
+    };
+
+    // Load config
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let initial_selected: BTreeSet<u32> = config.syoboi.channels.selected.into_iter().collect();
+
+    // Run TUI
+    let result = run_channel_selector(groups, initial_selected, updates)
+        .await
+        .context("channel selector TUI failed")?;
+
+    if let Some(selected) = result {
+        let mut config = AppConfig::load(&config_path).unwrap_or_default();
+        config.syoboi.channels.selected = selected;
+        config.save(&config_path).context("failed to save config")?;
+        tracing::info!(
+            "Saved {} selected channel(s) to {}",
+            config.syoboi.channels.selected.len(),
+            config_path.display()
+        );
+    } else {
+        tracing::info!("Selection cancelled");
+    }
+
+    Ok(())
+}
+
+/// Fetches channel groups and channels from the API and caches them in DB.
+///
+/// # Errors
+///
+/// Returns an error if API calls or DB operations fail.
+async fn fetch_and_cache_channels(
+    pool: &DbPool,
+) -> Result<(Vec<CachedChannelGroup>, Vec<CachedChannel>)> {
+    let client = build_syoboi_client()?;
+
+    tracing::info!("Fetching channel groups from API...");
+    let api_groups = client
+        .lookup_channel_groups(None)
+        .await
+        .context("failed to fetch channel groups")?;
+
+    tracing::info!("Fetching channels from API...");
+    let api_channels = client
+        .lookup_channels(None)
+        .await
+        .context("failed to fetch channels")?;
+
+    let conn = pool.get().context("failed to open database")?;
+
+    let cached_groups: Vec<CachedChannelGroup> = api_groups
+        .iter()
+        .map(|g| CachedChannelGroup {
+            ch_gid: g.ch_gid,
+            ch_group_name: g.ch_group_name.clone(),
+            ch_group_order: g.ch_group_order,
+        })
+        .collect();
+    let groups_changed =
+        upsert_channel_groups(&conn, &cached_groups).context("failed to cache channel groups")?;
+    tracing::info!(changed = groups_changed, "Channel groups upsert complete");
+
+    let valid_ch_gids: HashSet<u32> = cached_groups.iter().map(|g| g.ch_gid).collect();
+    let cached_channels: Vec<CachedChannel> = api_channels
+        .iter()
+        .map(|ch| CachedChannel {
+            ch_id: ch.ch_id,
+            ch_gid: ch.ch_gid.filter(|gid| valid_ch_gids.contains(gid)),
+            ch_name: ch.ch_name.clone(),
+            ch_url: ch.ch_url.clone(),
+            ch_iepg_name: ch.ch_iepg_name.clone(),
+            ch_comment: ch.ch_comment.clone(),
+            logo_url: None,
+        })
+        .collect();
+    let channels_changed =
+        upsert_channels(&conn, &cached_channels).context("failed to cache channels")?;
+    tracing::info!(changed = channels_changed, "Channels upsert complete");
+    let refreshed_at = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    mark_channels_refreshed(&conn, &refreshed_at).context("failed to record channel refresh")?;
+
+    Ok((cached_groups, cached_channels))
+}
+
+/// Runs the `syoboi channels sync` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the API calls or DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_channels_sync(config_file: Option<&PathBuf>) -> Result<()> {
+    let pool = open_data_pool(config_file)?;
+    let (groups, channels) = fetch_and_cache_channels(&pool).await?;
+    tracing::info!(
+        "Refreshed {} channel groups, {} channels",
+        groups.len(),
+        channels.len()
+    );
+    Ok(())
+}
+
+/// Describes why the channel cache is stale, or returns `None` when it was
+/// refreshed within `ttl_hours` (or `ttl_hours` is 0).
+fn channel_cache_staleness(
+    refreshed_at: Option<&str>,
+    ttl_hours: u64,
+    now: chrono::DateTime<Utc>,
+) -> Option<String> {
+    if ttl_hours == 0 {
+        return None;
+    }
+    let Some(refreshed_at) = refreshed_at else {
+        return Some(String::from("has never been refreshed"));
+    };
+    let ttl = i64::try_from(ttl_hours)
+        .ok()
+        .and_then(chrono::Duration::try_hours)?;
+    match chrono::DateTime::parse_from_rfc3339(refreshed_at) {
+        Ok(at) if now.signed_duration_since(at) < ttl => None,
+        _ => Some(format!(
+            "was last refreshed at {refreshed_at} (older than {ttl_hours} hours)"
+        )),
+    }
+}
+
+/// Returns the staleness of the channel cache in `conn` per the configured
+/// `[syoboi.channels] ttl_hours`.
+fn stale_channel_cache(
+    conn: &dtvmgr_db::Connection,
+    config_file: Option<&PathBuf>,
+) -> Option<String> {
+    let ttl_hours = resolve_config_path(config_file)
+        .and_then(|path| AppConfig::load(&path))
+        .map_or_else(
+            |_| ChannelsConfig::default().ttl_hours,
+            |config| config.syoboi.channels.ttl_hours,
+        );
+    let refreshed_at = load_channels_refreshed_at(conn).ok().flatten();
+    channel_cache_staleness(refreshed_at.as_deref(), ttl_hours, Utc::now())
+}
+
+/// Logs a warning when the channel cache is stale.
+fn warn_if_channels_stale(conn: &dtvmgr_db::Connection, config_file: Option<&PathBuf>) {
+    if let Some(reason) = stale_channel_cache(conn, config_file) {
+        tracing::warn!("Channel cache {reason}; run `syoboi channels sync` to refresh it");
+    }
+}
+
+/// Refreshes the channel cache that is stale for `reason` before a sync.
+/// A failed refresh is logged and does not stop the sync.
+async fn refresh_stale_channels(reason: &str, pool: &DbPool) {
+    tracing::info!("Channel cache {reason}; refreshing channels");
+    if let Err(e) = fetch_and_cache_channels(pool).await {
+        tracing::warn!(error = %format!("{e:#}"), "failed to refresh channels");
+    }
+}
+
+/// Builds TUI channel groups from cached data, annotating each channel with
+/// its cached program count and last synced change.
+fn build_tui_groups(
+    groups: &[CachedChannelGroup],
+    channels: &[CachedChannel],
+    stats: &[ChannelProgramStats],
+) -> Vec<ChannelGroup> {
+    let mut tui_groups: Vec<ChannelGroup> = groups
+        .iter()
+        .map(|g| ChannelGroup {
+            ch_gid: g.ch_gid,
+            name: g.ch_group_name.clone(),
+            channels: Vec::new(),
+        })
+        .collect();
+
+    // Sort by ch_group_order (already sorted from DB, but ensure)
+    tui_groups.sort_by_key(|g| {
+        groups
+            .iter()
+            .find(|cg| cg.ch_gid == g.ch_gid)
+            .map_or(0, |cg| cg.ch_group_order)
+    });
+
+    for ch in channels {
+        if let Some(ch_gid) = ch.ch_gid
+            && let Some(group) = tui_groups.iter_mut().find(|g| g.ch_gid == ch_gid)
+        {
+            let stat = stats.iter().find(|s| s.ch_id == ch.ch_id);
+            group.channels.push(ChannelEntry {
+                ch_id: ch.ch_id,
+                ch_name: ch.ch_name.clone(),
+                program_count: stat.map_or(0, |s| s.program_count),
+                last_update: stat.and_then(|s| s.last_update.clone()),
+            });
+        }
+    }
+
+    // Sort channels within each group by ch_id
+    for group in &mut tui_groups {
+        group.channels.sort_by_key(|ch| ch.ch_id);
+    }
+
+    tui_groups
+}
+
+/// Runs the `syoboi channels list` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if config or DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_channels_list(config_file: Option<&PathBuf>) -> Result<()> {
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+
+    if config.syoboi.channels.selected.is_empty() {
+        tracing::info!("No channels selected. Run `syoboi channels select` to choose channels.");
+        return Ok(());
+    }
+
+    // Try to load names from DB cache
+    let data_dir = resolve_data_dir(config_file).ok().flatten();
+    let conn = open_db(data_dir.as_ref());
+    let cached_channels = conn
+        .as_ref()
+        .ok()
+        .and_then(|c| load_channels(c).ok())
+        .unwrap_or_default();
+    if let Ok(conn) = &conn {
+        warn_if_channels_stale(conn, config_file);
+    }
+
+    tracing::info!(
+        "Selected channels ({}):",
+        config.syoboi.channels.selected.len()
+    );
+    for ch_id in &config.syoboi.channels.selected {
+        let name = cached_channels
+            .iter()
+            .find(|c| c.ch_id == *ch_id)
+            .map_or("(unknown)", |c| c.ch_name.as_str());
+        tracing::info!("  {:>3}  {}", ch_id, name);
+    }
+
+    Ok(())
+}
+
+/// Runs the `syoboi channels export` subcommand.
+///
+/// Channel names and groups come from the channel cache; channels missing
+/// from it are exported with their ID only.
+///
+/// # Errors
+///
+/// Returns an error if config, DB, or file operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_channels_export(
+    args: &ChannelsExportArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    let selected = &config.syoboi.channels.selected;
+    if selected.is_empty() {
+        anyhow::bail!("no channels selected; run `syoboi channels select` first");
+    }
+
+    let conn = open_data_db(config_file)?;
+    let channels = load_channels(&conn).context("failed to load cached channels")?;
+    let groups = load_channel_groups(&conn).context("failed to load cached channel groups")?;
+    let doc = SelectionDocument::build(selected, &channels, &groups);
+    let unnamed: Vec<u32> = doc
+        .channels
+        .iter()
+        .filter(|c| c.ch_name.is_empty())
+        .map(|c| c.ch_id)
+        .collect();
+    if !unnamed.is_empty() {
+        tracing::warn!(
+            ch_ids = ?unnamed,
+            "{} channel(s) are not in the channel cache; run `syoboi channels sync` to include their names",
+            unnamed.len()
+        );
+    }
+
+    let content = doc.to_string(SelectionFormat::from_path(&args.file))?;
+    std::fs::write(&args.file, content)
+        .with_context(|| format!("failed to write {}", args.file.display()))?;
+
+    if output.is_json() {
+        return write_json(&serde_json::json!({
+            "file": args.file,
+            "channels": doc.channels.len(),
+        }));
+    }
+    tracing::info!(
+        "Exported {} selected channel(s) to {}",
+        doc.channels.len(),
+        args.file.display()
+    );
+    Ok(())
+}
+
+/// Runs the `syoboi channels import` subcommand.
+///
+/// Channel IDs are validated against the channel list, refreshed from the
+/// API unless `--cached` is given; unknown channels are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the file is invalid, or config, API, or DB
+/// operations fail.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, err(level = "error"))]
+async fn run_channels_import(
+    args: &ChannelsImportArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let content = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("failed to read {}", args.file.display()))?;
+    let doc = SelectionDocument::parse(&content, SelectionFormat::from_path(&args.file))
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
+
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let channels = if args.cached {
+        let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+        warn_if_channels_stale(&conn, config_file);
+        load_channels(&conn).context("failed to load cached channels")?
+    } else {
+        fetch_and_cache_channels(&open_db_pool(data_dir)).await?.1
+    };
+    if channels.is_empty() {
+        anyhow::bail!(
+            "channel cache is empty; run `syoboi channels sync` or import without --cached"
+        );
+    }
+
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let mut config = AppConfig::load(&config_path).context("failed to load config")?;
+    let mode = if args.replace {
+        ImportMode::Replace
+    } else {
+        ImportMode::Merge
+    };
+    let plan = plan_import(&doc, &config.syoboi.channels.selected, &channels, mode);
+    if !args.dry_run && plan.changed() {
+        config.syoboi.channels.selected.clone_from(&plan.selected);
+        config.save(&config_path).context("failed to save config")?;
+    }
+
+    if output.is_json() {
+        return write_json(&serde_json::json!({
+            "selected": plan.selected,
+            "added": plan.added,
+            "removed": plan.removed,
+            "unknown": plan.unknown,
+            "renamed": plan
+                .renamed
+                .iter()
+                .map(|(ch_id, from, to)| serde_json::json!({ "ch_id": ch_id, "from": from, "to": to }))
+                .collect::<Vec<_>>(),
+            "dry_run": args.dry_run,
+        }));
+    }
+    if !plan.unknown.is_empty() {
+        tracing::warn!(
+            ch_ids = ?plan.unknown,
+            "Skipped {} channel(s) not in the channel list",
+            plan.unknown.len()
+        );
+    }
+    for (ch_id, from, to) in &plan.renamed {
+        tracing::warn!(ch_id, "Channel renamed: {from} -> {to}");
+    }
+    tracing::info!(
+        "Selection now has {} channel(s) ({} added, {} removed){}",
+        plan.selected.len(),
+        plan.added.len(),
+        plan.removed.len(),
+        if args.dry_run { " [dry run]" } else { "" }
+    );
+    Ok(())
+}
+
+/// Formats busiest hour slots as `HH:00(n)` joined by commas, or "-" when empty.
+fn format_busiest_hours(hours: &[(u8, u32)]) -> String {
+    if hours.is_empty() {
+        return String::from("-");
+    }
+    hours
+        .iter()
+        .map(|(hour, count)| format!("{hour:02}:00({count})"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Runs the `db recompute` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_recompute(args: &DbRecomputeArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let batch_size = usize::try_from(args.batch_size).unwrap_or(usize::MAX);
+    let result = recompute_program_columns(&conn, batch_size, |p| {
+        tracing::info!(
+            scanned = p.scanned,
+            total = p.total,
+            updated = p.updated,
+            "Recomputing programs..."
+        );
+    })
+    .context("failed to recompute program columns")?;
+    tracing::info!(
+        "Recomputed {} program(s), {} updated",
+        result.scanned,
+        result.updated
+    );
+    Ok(())
+}
+
+/// Reads a seed resource from an `https://` or `file://` URL.
+///
+/// # Errors
+///
+/// Returns an error for other schemes or if the read fails.
+async fn fetch_seed_bytes(url: &str) -> Result<Vec<u8>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return std::fs::read(path).with_context(|| format!("failed to read {path}"));
+    }
+    if !url.starts_with("https://") {
+        anyhow::bail!("unsupported seed URL {url}: use https:// or file://");
+    }
+    let body = reqwest::get(url)
+        .await
+        .with_context(|| format!("failed to fetch {url}"))?
+        .error_for_status()
+        .with_context(|| format!("HTTP error fetching {url}"))?
+        .bytes()
+        .await
+        .with_context(|| format!("failed to read response body from {url}"))?;
+    Ok(body.to_vec())
+}
+
+/// Decompresses a zstd seed into `path`.
+///
+/// # Errors
+///
+/// Returns an error if the data is not zstd or the file cannot be written.
+fn decompress_seed(compressed: &[u8], path: &Path) -> Result<()> {
+    let mut decoder = ruzstd::decoding::StreamingDecoder::new(compressed)
+        .context("seed is not zstd-compressed")?;
+    let mut file = std::fs::File::create(path)
+        .with_context(|| format!("failed to create {}", path.display()))?;
+    std::io::copy(&mut decoder, &mut file).context("failed to decompress seed")?;
+    Ok(())
+}
+
+/// Runs the `db bootstrap` subcommand.
+///
+/// Downloads the seed, verifies its SHA-256, decompresses it next to the
+/// database, imports channels and titles, and then runs `db sync
+/// --incremental` unless `--skip-sync` is given. The seed's sync cursor
+/// becomes the cursor of the selected channels, so the sync only fetches
+/// programs updated after the seed was taken.
+///
+/// # Errors
+///
+/// Returns an error if the database already has titles (without `--force`),
+/// the download or checksum fails, the seed schema is unsupported, or the
+/// follow-up sync fails.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_db_bootstrap(args: &DbBootstrapArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let db_path = resolve_db_path(data_dir.as_ref()).context("failed to resolve database path")?;
+    {
+        let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+        let summary = load_db_summary(&conn).context("failed to load database summary")?;
+        if summary.total_titles > 0 && !args.force {
+            anyhow::bail!(
+                "database already contains {} titles; use --force to import the seed anyway",
+                summary.total_titles
+            );
+        }
+    }
+
+    let expected = if let Some(ref sha) = args.sha256 {
+        sha.clone()
+    } else {
+        let sidecar = format!("{}.sha256", args.from_url);
+        let body = fetch_seed_bytes(&sidecar)
+            .await
+            .context("failed to fetch checksum; pass --sha256 to supply it")?;
+        String::from_utf8_lossy(&body)
+            .split_whitespace()
+            .next()
+            .with_context(|| format!("checksum file {sidecar} is empty"))?
+            .to_owned()
+    };
+    tracing::info!(url = %args.from_url, "Downloading seed...");
+    let compressed = fetch_seed_bytes(&args.from_url).await?;
+    let actual = sha256_hex(compressed.as_slice()).context("failed to hash seed")?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        anyhow::bail!("seed checksum mismatch: expected {expected}, got {actual}");
+    }
+
+    let seed_path = db_path.with_extension("seed");
+    let imported = decompress_seed(&compressed, &seed_path).and_then(|()| {
+        let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+        import_seed(&conn, &seed_path)
+    });
+    if let Err(e) = std::fs::remove_file(&seed_path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!(path = %seed_path.display(), error = %e, "failed to remove seed file");
+    }
+    let imported = imported.context("failed to import seed")?;
+    tracing::info!(
+        "Imported seed (schema v{}): {} channel group(s), {} channel(s), {} title(s)",
+        imported.seed_version,
+        imported.channel_groups,
+        imported.channels,
+        imported.titles
+    );
+
+    if args.skip_sync {
+        return Ok(());
+    }
+    if let Some(cursor) = imported.cursor.as_deref() {
+        let ch_ids = resolve_ch_ids(None, config_file).context("failed to resolve channel IDs")?;
+        let scope = sync_scope(&ch_ids, None);
+        let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+        save_sync_cursor(&conn, &scope, cursor).context("failed to save sync cursor")?;
+        tracing::info!(scope, cursor, "Sync cursor set from the seed");
+    } else {
+        tracing::info!("Seed has no sync cursor; syncing the default range");
+    }
+    let sync_args = DbSyncArgs {
+        time_since: None,
+        time_until: None,
+        season: None,
+        ch_ids: None,
+        tids: None,
+        incremental: true,
+        followed_only: false,
+        category: None,
+        resume: None,
+    };
+    run_db_sync(&sync_args, config_file).await
+}
+
+/// Loads the programs matching `filter`, ordered by start time and PID.
+///
+/// Channels come from `filter.ch_ids`, else the config selection, else all.
+///
+/// # Errors
+///
+/// Returns an error if a time filter is invalid or the DB query fails.
+fn load_export_programs(
+    conn: &dtvmgr_db::Connection,
+    filter: &ExportFilterArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<Vec<CachedProgram>> {
+    const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+    let since = filter
+        .time_since
+        .as_deref()
+        .map(to_naive_datetime_since)
+        .transpose()?
+        .map(|t| t.format(TIME_FORMAT).to_string());
+    let until = filter
+        .time_until
+        .as_deref()
+        .map(to_naive_datetime_until)
+        .transpose()?
+        .map(|t| t.format(TIME_FORMAT).to_string());
+    let ch_ids: Option<Vec<u32>> =
+        channel_filter(filter.ch_ids.as_ref(), config_file).map(|ids| ids.into_iter().collect());
+    let cats: Option<Vec<u32>> = filter
+        .category
+        .as_ref()
+        .map(|cats| cats.iter().map(|c| c.code()).collect());
+    let condition = filter.filter.as_ref().map(FilterExpr::to_sql);
+
+    load_programs_filtered(
+        conn,
+        &ProgramFilter {
+            tids: filter.tids.as_deref(),
+            ch_ids: ch_ids.as_deref(),
+            cats: cats.as_deref(),
+            since: since.as_deref(),
+            until: until.as_deref(),
+            exclude_deleted: false,
+            condition: condition.as_ref(),
+        },
+    )
+    .context("failed to load programs")
+}
+
+/// Runs `exporter` on the programs selected by `filter`, writing to `output`.
+///
+/// TMDB seasons are fetched through `tmdb` (client and language) only for
+/// formats that read them.
+///
+/// # Errors
+///
+/// Returns an error if a time filter is invalid, DB operations fail, or the
+/// exporter cannot write its output.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_export(
+    exporter: &dyn Exporter,
+    output: &Path,
+    filter: &ExportFilterArgs,
+    metadata_language: Option<MetadataLanguage>,
+    tmdb: Option<(&TmdbClient, &str)>,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let (programs, titles, channels, localized, images) = {
+        let conn = open_data_db(config_file)?;
+        warn_if_channels_stale(&conn, config_file);
+        let programs = load_export_programs(&conn, filter, config_file)?;
+        let titles: std::collections::HashMap<u32, CachedTitle> = load_titles(&conn)
+            .context("failed to load titles")?
+            .into_iter()
+            .map(|t| (t.tid, t))
+            .collect();
+        let channels: std::collections::HashMap<u32, CachedChannel> = load_channels(&conn)
+            .context("failed to load channels")?
+            .into_iter()
+            .map(|c| (c.ch_id, c))
+            .collect();
+        let localized = load_localizations(&conn, metadata_language)?;
+        let images = load_title_images(&conn).context("failed to load title images")?;
+        (programs, titles, channels, localized, images)
+    };
+    let tmdb_episodes = match tmdb {
+        Some((client, language)) if exporter.uses_tmdb_episodes() => {
+            fetch_export_episodes(client, &programs, &titles, language).await
+        }
+        _ => std::collections::HashMap::new(),
+    };
+    let data = ExportData {
+        programs: &programs,
+        titles: &titles,
+        channels: &channels,
+        localized: &localized,
+        lang: metadata_language.map_or("ja", MetadataLanguage::xml_lang),
+        tmdb_episodes: &tmdb_episodes,
+        images: &images,
+        generated_at: Utc::now(),
+    };
+    let summary = exporter.export(&data, output)?;
+    tracing::info!("{summary}");
+    Ok(())
+}
+
+/// Fetches the TMDB season episodes of every mapped title among
+/// `programs`. A failed season request is logged and the title falls back
+/// to Syoboi counts.
+async fn fetch_export_episodes(
+    client: &TmdbClient,
+    programs: &[CachedProgram],
+    titles: &std::collections::HashMap<u32, CachedTitle>,
+    language: &str,
+) -> std::collections::HashMap<u32, Vec<dtvmgr_api::tmdb::TmdbEpisode>> {
+    let tids: BTreeSet<u32> = programs.iter().map(|p| p.tid).collect();
+    let mut episodes = std::collections::HashMap::new();
+    for title in tids.iter().filter_map(|tid| titles.get(tid)) {
+        let Some(series_id) = title.tmdb_series_id else {
+            continue;
+        };
+        let season = title.tmdb_season_number.unwrap_or(1);
+        match client.tv_season(series_id, season, language).await {
+            Ok(s) => {
+                episodes.insert(title.tid, s.episodes);
+            }
+            Err(e) => tracing::warn!(tid = title.tid, error = %e, "Using Syoboi counts"),
+        }
+    }
+    episodes
+}
+
+/// Runs a single-file `export <format>` subcommand (also `db export ics`).
+///
+/// Programs are filtered by channel (`--ch-ids`, else the config selection,
+/// else all), TID, category, and start time.
+///
+/// # Errors
+///
+/// Returns an error if the format is not registered or the export fails.
+async fn run_export_file(
+    format: &str,
+    args: &ExportFileArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let exporter =
+        find_exporter(format).with_context(|| format!("unknown export format {format}"))?;
+    let output = args
+        .file
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(exporter.default_output()));
+    Box::pin(run_export(
+        exporter,
+        &output,
+        &args.filter,
+        args.metadata_language,
+        None,
+        config_file,
+    ))
+    .await
+}
+
+/// Runs the `export nfo` subcommand.
+///
+/// Writes `tvshow.nfo` per title and one episode NFO per episode number.
+/// Mapped titles take episode numbers, names, and synopses from their TMDB
+/// season unless `--offline`.
+///
+/// # Errors
+///
+/// Returns an error if the TMDB client cannot be built or the export fails.
+async fn run_export_nfo(args: &ExportNfoArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let exporter = NfoExporter {
+        show_dir: &args.show_dir,
+        season_dir: &args.season_dir,
+        episode_file: &args.episode_file,
+        mapped_only: args.filter.tids.is_none(),
+    };
+    let tmdb_client = if args.offline {
+        None
+    } else {
+        Some(build_tmdb_client(config_file).context("failed to build TMDB client")?)
+    };
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+    Box::pin(run_export(
+        &exporter,
+        &args.out_dir,
+        &args.filter,
+        args.metadata_language,
+        tmdb_client.as_ref().map(|c| (c, language.as_str())),
+        config_file,
+    ))
+    .await
+}
+
+/// One row of `export list-formats`.
+#[derive(Debug, serde::Serialize)]
+struct ExportFormatInfo {
+    /// Format name (the `export` subcommand).
+    name: &'static str,
+    /// Output path used when none is given.
+    default_output: &'static str,
+    /// One-line description.
+    description: &'static str,
+}
+
+/// Runs the `export list-formats` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if JSON output cannot be written.
+#[allow(clippy::print_stdout)]
+fn run_export_list_formats(output: OutputFormat) -> Result<()> {
+    let formats: Vec<ExportFormatInfo> = EXPORTERS
+        .iter()
+        .map(|e| ExportFormatInfo {
+            name: e.name(),
+            default_output: e.default_output(),
+            description: e.description(),
+        })
+        .collect();
+    if output.is_json() {
+        return write_json(&formats);
+    }
+    for f in &formats {
+        println!("{}\t{}\t{}", f.name, f.default_output, f.description);
+    }
+    Ok(())
+}
+
+/// Returns the channel IDs to filter by: `ch_ids` when given, else the
+/// config selection, else `None` (all channels).
+fn channel_filter(
+    ch_ids: Option<&Vec<u32>>,
+    config_file: Option<&PathBuf>,
+) -> Option<HashSet<u32>> {
+    ch_ids
+        .cloned()
+        .or_else(|| {
+            let path = resolve_config_path(config_file).ok()?;
+            let ids = AppConfig::load(&path).ok()?.syoboi.channels.selected;
+            (!ids.is_empty()).then_some(ids)
+        })
+        .map(|ids| ids.into_iter().collect())
+}
+
+/// Conflicts of one broadcast day, as written by `db conflicts --output json`.
+#[derive(Debug, serde::Serialize)]
+struct ConflictDayOutput<'a> {
+    /// Broadcast day (`YYYY-MM-DD`).
+    day: &'a str,
+    /// Overlap groups starting on this day.
+    conflicts: Vec<ConflictOutput<'a>>,
+}
+
+/// One group of overlapping programs in [`ConflictDayOutput`].
+#[derive(Debug, serde::Serialize)]
+struct ConflictOutput<'a> {
+    /// Earliest start in the group.
+    start: &'a str,
+    /// Latest end in the group.
+    end: &'a str,
+    /// Tuners needed to record every program in the group.
+    peak: usize,
+    /// Programs in start order.
+    programs: Vec<ConflictProgramOutput<'a>>,
+}
+
+/// A program in [`ConflictOutput`], with title and channel names resolved.
+#[derive(Debug, serde::Serialize)]
+struct ConflictProgramOutput<'a> {
+    /// Syoboi program ID.
+    pid: u32,
+    /// Syoboi title ID.
+    tid: u32,
+    /// Title name.
+    title: &'a str,
+    /// Channel ID.
+    ch_id: u32,
+    /// Channel name.
+    channel: &'a str,
+    /// Broadcast start.
+    st_time: &'a str,
+    /// Broadcast end.
+    ed_time: &'a str,
+}
+
+/// Runs the `db conflicts` subcommand.
+///
+/// Programs of watched titles (or all titles with `--all-titles`) on the
+/// selected channels are grouped into overlapping sets, printed per day with
+/// the number of tuners each set needs.
+///
+/// # Errors
+///
+/// Returns an error if a time filter is invalid, DB operations fail, or JSON
+/// output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_conflicts(
+    args: &DbConflictsArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+    let since = args
+        .time_since
+        .as_deref()
+        .map(to_naive_datetime_since)
+        .transpose()?
+        .unwrap_or_else(|| syoboi_local_time(Utc::now()))
+        .format(TIME_FORMAT)
+        .to_string();
+    let until = args
+        .time_until
+        .as_deref()
+        .map(to_naive_datetime_until)
+        .transpose()?
+        .map(|t| t.format(TIME_FORMAT).to_string());
+    let ch_ids: Option<Vec<u32>> =
+        channel_filter(args.ch_ids.as_ref(), config_file).map(|ids| ids.into_iter().collect());
+
+    let conn = open_data_db(config_file)?;
+    let watched: Option<Vec<u32>> = if args.all_titles {
+        None
+    } else {
+        let tids: Vec<u32> = load_watchlist(&conn)
+            .context("failed to load watchlist")?
+            .iter()
+            .map(|e| e.tid)
+            .collect();
+        if tids.is_empty() && !output.is_json() {
+            tracing::info!("Watchlist is empty. Add titles or pass --all-titles.");
+            return Ok(());
+        }
+        Some(tids)
+    };
+    let programs = load_programs_filtered(
+        &conn,
+        &ProgramFilter {
+            tids: watched.as_deref(),
+            ch_ids: ch_ids.as_deref(),
+            cats: None,
+            since: Some(&since),
+            until: until.as_deref(),
+            exclude_deleted: true,
+            condition: None,
+        },
+    )
+    .context("failed to load programs")?;
+    let titles: std::collections::HashMap<u32, String> = load_titles(&conn)
+        .context("failed to load titles")?
+        .into_iter()
+        .map(|t| (t.tid, t.title))
+        .collect();
+    let channels: std::collections::HashMap<u32, String> = load_channels(&conn)
+        .context("failed to load channels")?
+        .into_iter()
+        .map(|c| (c.ch_id, c.ch_name))
+        .collect();
+
+    let mut days: Vec<ConflictDayOutput<'_>> = Vec::new();
+    for conflict in find_conflicts(&programs) {
+        let entry = ConflictOutput {
+            start: conflict.start,
+            end: conflict.end,
+            peak: conflict.peak,
+            programs: conflict
+                .programs
+                .iter()
+                .map(|p| ConflictProgramOutput {
+                    pid: p.pid,
+                    tid: p.tid,
+                    title: titles.get(&p.tid).map_or("", String::as_str),
+                    ch_id: p.ch_id,
+                    channel: channels.get(&p.ch_id).map_or("", String::as_str),
+                    st_time: &p.st_time,
+                    ed_time: &p.ed_time,
+                })
+                .collect(),
+        };
+        match days.last_mut() {
+            Some(last) if last.day == conflict.day() => last.conflicts.push(entry),
+            _ => days.push(ConflictDayOutput {
+                day: conflict.day(),
+                conflicts: vec![entry],
+            }),
+        }
+    }
+    if output.is_json() {
+        return write_json(&days);
+    }
+    log_conflicts(&days);
+    Ok(())
+}
+
+/// A program matched by `rules run`, with title and channel names resolved.
+#[derive(Debug, serde::Serialize)]
+struct RuleMatchOutput<'a> {
+    /// Syoboi program ID.
+    pid: u32,
+    /// Syoboi title ID.
+    tid: u32,
+    /// Title name.
+    title: &'a str,
+    /// Channel ID.
+    ch_id: u32,
+    /// Channel name.
+    channel: &'a str,
+    /// Broadcast start.
+    st_time: &'a str,
+    /// Broadcast end.
+    ed_time: &'a str,
+    /// Episode number.
+    count: Option<u32>,
+    /// Episode subtitle.
+    sub_title: Option<&'a str>,
+    /// Names of the matching rules.
+    rules: Vec<&'a str>,
+}
+
+/// Runs the `rules run` subcommand.
+///
+/// Evaluates the configured rules against cached programs in the time
+/// range and prints the matches (or writes them as JSON).
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be loaded, a rule is invalid or
+/// unknown, DB operations fail, or the output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_rules_run(
+    args: &RulesRunArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let config = AppConfig::load(&config_path).context("failed to load config")?;
+    validate_rules(&config.rules).context("invalid rules in config")?;
+    let rules: Vec<Rule> = match &args.rules {
+        Some(names) => {
+            if let Some(unknown) = names
+                .iter()
+                .find(|n| !config.rules.iter().any(|r| r.name.trim() == n.trim()))
+            {
+                anyhow::bail!("unknown rule {unknown}");
+            }
+            config
+                .rules
+                .into_iter()
+                .filter(|r| names.iter().any(|n| r.name.trim() == n.trim()))
+                .collect()
+        }
+        None => config.rules,
+    };
+    if rules.is_empty() && !output.is_json() {
+        tracing::info!("No rules configured. Add [[rules]] sections to the config file.");
+        return Ok(());
+    }
+
+    let since = args
+        .time_since
+        .as_deref()
+        .map(to_naive_datetime_since)
+        .transpose()?
+        .unwrap_or_else(|| syoboi_local_time(Utc::now()))
+        .format(TIME_FORMAT)
+        .to_string();
+    let until = args
+        .time_until
+        .as_deref()
+        .map(to_naive_datetime_until)
+        .transpose()?
+        .map(|t| t.format(TIME_FORMAT).to_string());
+
+    let conn = open_data_db(config_file)?;
+    let condition = args.filter.as_ref().map(FilterExpr::to_sql);
+    let programs = load_programs_filtered(
+        &conn,
+        &ProgramFilter {
+            since: Some(&since),
+            until: until.as_deref(),
+            condition: condition.as_ref(),
+            ..ProgramFilter::default()
+        },
+    )
+    .context("failed to load programs")?;
+    let titles = load_titles(&conn).context("failed to load titles")?;
+    let channels: std::collections::HashMap<u32, String> = load_channels(&conn)
+        .context("failed to load channels")?
+        .into_iter()
+        .map(|c| (c.ch_id, c.ch_name))
+        .collect();
+    let title_names: std::collections::HashMap<u32, &str> =
+        titles.iter().map(|t| (t.tid, t.title.as_str())).collect();
+
+    let selected = evaluate_rules(&rules, &titles, &programs)?;
+    let matches: Vec<RuleMatchOutput<'_>> = args
+        .query
+        .query()
+        .apply(selected)
+        .into_iter()
+        .map(|m| {
+            let p = m.program;
+            RuleMatchOutput {
+                pid: p.pid,
+                tid: p.tid,
+                title: title_names.get(&p.tid).copied().unwrap_or(""),
+                ch_id: p.ch_id,
+                channel: channels.get(&p.ch_id).map_or("", String::as_str),
+                st_time: &p.st_time,
+                ed_time: &p.ed_time,
+                count: p.count,
+                sub_title: p.st_sub_title.as_deref().or(p.sub_title.as_deref()),
+                rules: m.rules,
+            }
+        })
+        .collect();
+
+    if let Some(path) = &args.file {
+        let json = serde_json::to_string_pretty(&matches).context("failed to serialize matches")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        tracing::info!("Wrote {} matches to {}", matches.len(), path.display());
+    }
+    if output.is_json() {
+        return write_json(&matches);
+    }
+    log_rule_matches(&matches);
+    Ok(())
+}
+
+/// Logs `rules run` matches, one program per line.
+fn log_rule_matches(matches: &[RuleMatchOutput<'_>]) {
+    if matches.is_empty() {
+        tracing::info!("No programs matched");
+        return;
+    }
+    for m in matches {
+        tracing::info!(
+            "{}\t{}\t{}\t{}\t[{}]",
+            m.pid,
+            m.st_time,
+            if m.channel.is_empty() { "-" } else { m.channel },
+            m.title,
+            m.rules.join(", "),
+        );
+    }
+    tracing::info!("{} programs matched", matches.len());
+}
+
+/// Logs conflicts as a per-day table.
+fn log_conflicts(days: &[ConflictDayOutput<'_>]) {
+    if days.is_empty() {
+        tracing::info!("No conflicts found");
+        return;
+    }
+    for day in days {
+        tracing::info!("{}", day.day);
+        for conflict in &day.conflicts {
+            tracing::info!(
+                "  {} - {}  ({} tuners)",
+                conflict.start,
+                conflict.end,
+                conflict.peak
+            );
+            for p in &conflict.programs {
+                tracing::info!(
+                    "    {}\t{} - {}\t{}\t{}",
+                    p.pid,
+                    p.st_time,
+                    p.ed_time,
+                    if p.channel.is_empty() { "-" } else { p.channel },
+                    p.title,
+                );
+            }
+        }
+    }
+}
+
+/// Runs the `db tmdb-match` subcommand.
+///
+/// Prints one `tid\tmatched\tunmatched\tupdated` line per title. Titles
+/// whose season request fails are logged and skipped.
+///
+/// # Errors
+///
+/// Returns an error if the TMDB client cannot be built or DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+async fn run_db_tmdb_match(args: &DbTmdbMatchArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+    if let Some(pid) = args.pid {
+        return run_db_tmdb_match_program(args, config_file, conn, pid).await;
+    }
+
+    let titles = match args.tids {
+        Some(ref tids) => load_titles_by_tids(&conn, tids).context("failed to load titles")?,
+        None => load_titles(&conn).context("failed to load titles")?,
+    };
+    let titles: Vec<CachedTitle> = titles
+        .into_iter()
+        .filter(|t| t.tmdb_series_id.is_some())
+        .collect();
+    if titles.is_empty() {
+        tracing::info!("No titles with a TMDB series mapping");
+        return Ok(());
+    }
+
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+    let tmdb_client = build_tmdb_client(config_file).context("failed to build TMDB client")?;
+    let tids: Vec<u32> = titles.iter().map(|t| t.tid).collect();
+    let programs = load_programs_by_tids(&conn, &tids).context("failed to load programs")?;
+    let ranges = load_season_ranges(&conn).context("failed to load season ranges")?;
 
-        let ctx = PipelineContext {
-            input,
-            channel_name,
-            config: jlse_config,
-            encode: true, // implicitly enabled
-            target: AvsTarget::from(args.target),
-            add_chapter: args.add_chapter,
-            out_dir,
-            out_name,
-            out_extension,
-            remove: args.remove,
-            progress_mode: Some(ProgressMode::EpgStation),
-            skip_duration_check: args.skip_duration_check,
-            force: args.force,
+    tracing::info!("tid\tmatched\tunmatched\tupdated");
+    for title in &titles {
+        let matches = match match_title(&tmdb_client, title, &programs, &ranges, &language).await {
+            Ok(matches) => matches,
+            Err(e) => {
+                tracing::warn!(tid = title.tid, error = %e, "Skipping title");
+                continue;
+            }
         };
+        let own = programs.iter().filter(|p| p.tid == title.tid);
+        let existing: std::collections::HashMap<u32, Option<u64>> =
+            own.clone().map(|p| (p.pid, p.tmdb_episode_id)).collect();
+        let mappings: Vec<(u32, Option<u64>)> = matches
+            .iter()
+            .filter(|m| args.overwrite || existing.get(&m.pid).is_some_and(Option::is_none))
+            .map(|m| (m.pid, Some(m.tmdb_episode_id)))
+            .collect();
+        let updated = update_tmdb_episode_mapping(&conn, &mappings)
+            .with_context(|| format!("failed to store episode mappings for tid {}", title.tid))?;
+        let unmatched = own.count().saturating_sub(matches.len());
+        tracing::info!("{}\t{}\t{unmatched}\t{updated}", title.tid, matches.len());
+    }
+    Ok(())
+}
 
-        record_storage_metrics(&ctx);
+/// Runs `db tmdb-match --pid`: resolves one program with a single TMDB
+/// episode request and prints its `pid\ttmdb_episode_id` line.
+///
+/// # Errors
+///
+/// Returns an error if the program or its title is not cached, the title
+/// has no TMDB series mapping, or a TMDB request or DB operation fails.
+async fn run_db_tmdb_match_program(
+    args: &DbTmdbMatchArgs,
+    config_file: Option<&PathBuf>,
+    conn: dtvmgr_db::Connection,
+    pid: u32,
+) -> Result<()> {
+    let program = load_program(&conn, pid)
+        .context("failed to load program")?
+        .with_context(|| format!("program {pid} is not cached"))?;
+    let title = load_titles_by_tids(&conn, &[program.tid])
+        .context("failed to load title")?
+        .into_iter()
+        .next()
+        .with_context(|| format!("title {} is not cached", program.tid))?;
+    if title.tmdb_series_id.is_none() {
+        anyhow::bail!("title {} has no TMDB series mapping", title.tid);
+    }
+    if program.tmdb_episode_id.is_some() && !args.overwrite {
+        tracing::info!(pid, "Program already mapped (use --overwrite to replace)");
+        return Ok(());
+    }
+    let ranges = load_season_ranges(&conn).context("failed to load season ranges")?;
+    let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+    let tmdb_client = build_tmdb_client(config_file).context("failed to build TMDB client")?;
 
-        if args.tui {
-            run_pipeline_with_tui(ctx)
+    let Some(matched) = match_program(&tmdb_client, &title, &program, &ranges, &language).await?
+    else {
+        tracing::info!(pid, "No TMDB episode found");
+        return Ok(());
+    };
+    update_tmdb_episode_mapping(&conn, &[(pid, Some(matched.tmdb_episode_id))])
+        .with_context(|| format!("failed to store episode mapping for pid {pid}"))?;
+    tracing::info!("{pid}\t{}", matched.tmdb_episode_id);
+    Ok(())
+}
+
+/// JSON document written by `db stats --output json`.
+#[derive(Debug, serde::Serialize)]
+struct DbStatsOutput<'a> {
+    /// Cache totals.
+    summary: &'a DbSummary,
+    /// Title and program counts per Syoboi category.
+    categories: Vec<CategoryCountOutput>,
+    /// Per-channel broadcast usage.
+    channels: &'a [ChannelUsage],
+}
+
+/// Counts for one Syoboi category in [`DbStatsOutput`].
+#[derive(Debug, serde::Serialize)]
+struct CategoryCountOutput {
+    /// Raw category code.
+    cat: Option<u32>,
+    /// Category name.
+    category: String,
+    /// Number of titles.
+    titles: u32,
+    /// Number of programs.
+    programs: u32,
+}
+
+/// Runs the `db prune` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if `--before` is invalid or DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_prune(args: &DbPruneArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let cutoff = to_naive_datetime_since(&args.before)?
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    let conn = open_data_db(config_file)?;
+
+    let report = prune_programs(&conn, &cutoff, args.orphan_titles, args.dry_run)
+        .context("failed to prune programs")?;
+    let verb = if args.dry_run {
+        "Would prune"
+    } else {
+        "Pruned"
+    };
+    tracing::info!(
+        "{verb} {} programs ended before {cutoff} and {} orphan titles",
+        report.programs,
+        report.titles
+    );
+    Ok(())
+}
+
+/// Runs the `db vacuum` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_vacuum(config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let report = vacuum(&conn).context("failed to vacuum database")?;
+    tracing::info!(
+        "Vacuumed database: {} KiB -> {} KiB",
+        report.before_bytes / 1024,
+        report.after_bytes / 1024
+    );
+    Ok(())
+}
+
+/// Runs the `db bench` subcommand: for each batch size, writes synthetic
+/// rows into a fresh scratch database and reports the throughput. The
+/// scratch databases are removed afterwards; the real cache is not opened.
+///
+/// # Errors
+///
+/// Returns an error if the scratch directory cannot be created or removed,
+/// or an upsert fails.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_bench(
+    args: &DbBenchArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let base = if let Some(dir) = &args.scratch_dir {
+        dir.clone()
+    } else {
+        let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+        let db = resolve_db_path(data_dir.as_ref()).context("failed to resolve database path")?;
+        db.parent().map_or_else(PathBuf::new, PathBuf::from)
+    };
+    let scratch = base.join(format!("dtvmgr-bench-{}", std::process::id()));
+
+    let mut samples = Vec::new();
+    for &batch_size in &args.batch_sizes {
+        let dir = scratch.join(batch_size.to_string());
+        let result = open_db_with_options(Some(&dir), &DbOptions::default())
+            .and_then(|conn| bench_upserts(&conn, args.programs, batch_size));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .with_context(|| format!("failed to remove {}", dir.display()))?;
+        }
+        samples.extend(
+            result.with_context(|| format!("benchmark with batch size {batch_size} failed"))?,
+        );
+    }
+    if scratch.exists() {
+        std::fs::remove_dir(&scratch)
+            .with_context(|| format!("failed to remove {}", scratch.display()))?;
+    }
+
+    if output.is_json() {
+        return write_json(&samples);
+    }
+    tracing::info!("batch_size\tpass\trows\tchanged\tsecs\trows_per_sec");
+    for s in &samples {
+        tracing::info!(
+            "{}\t{}\t{}\t{}\t{:.3}\t{:.0}",
+            s.batch_size,
+            s.pass,
+            s.rows,
+            s.changed,
+            s.secs,
+            s.rows_per_sec()
+        );
+    }
+    Ok(())
+}
+
+/// Runs the `db tag` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if a tag is empty, the program is not cached, or DB
+/// operations fail.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_tag(args: &DbTagArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let tags: Vec<String> = args.tags.iter().map(|t| t.trim().to_lowercase()).collect();
+    if tags.iter().any(String::is_empty) {
+        anyhow::bail!("tags must not be empty");
+    }
+    let conn = open_data_db(config_file)?;
+
+    let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    for tag in &tags {
+        if args.remove {
+            if remove_program_tag(&conn, args.pid, tag)? {
+                tracing::info!("Removed tag '{tag}' from program {}", args.pid);
+            } else {
+                tracing::info!("Program {} has no tag '{tag}'", args.pid);
+            }
+        } else if add_program_tag(&conn, args.pid, tag, &now)? {
+            tracing::info!("Tagged program {} as '{tag}'", args.pid);
         } else {
-            // EPGStation callback mode (JSON output)
-            let last_stage = Cell::new((0_u8, 0_u8));
-            let cb = |event: ProgressEvent| {
-                use dtvmgr_jlse::progress::emit_epgstation;
-                match event {
-                    ProgressEvent::StageStart { stage, total, name } => {
-                        last_stage.set((stage, total));
-                        emit_epgstation(0.0, &format!("({stage}/{total}) {name}: starting"));
-                    }
-                    ProgressEvent::StageProgress { percent, log } => {
-                        emit_epgstation(percent, &log);
-                    }
-                    ProgressEvent::Encoding { percent, log } => {
-                        let (s, t) = last_stage.get();
-                        let prefixed = format!("({s}/{t}) FFmpeg: {log}");
-                        emit_epgstation(percent, &prefixed);
-                    }
-                    ProgressEvent::Log(_) | ProgressEvent::Finished => {}
-                }
-            };
+            tracing::info!("Program {} is already tagged '{tag}'", args.pid);
+        }
+    }
+    Ok(())
+}
 
-            run_pipeline(&ctx, Some(&cb))
+/// Runs the `db note` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the program is not cached or DB operations fail.
+#[allow(clippy::print_stdout)]
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_note(args: &DbNoteArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    if args.clear {
+        if delete_program_note(&conn, args.pid)? {
+            tracing::info!("Deleted note of program {}", args.pid);
+        } else {
+            tracing::info!("Program {} has no note", args.pid);
         }
+        return Ok(());
+    }
+    if let Some(text) = &args.text {
+        let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        set_program_note(&conn, args.pid, text, &now)?;
+        tracing::info!("Saved note of program {}", args.pid);
+        return Ok(());
+    }
+    let annotations = load_program_annotations(&conn).context("failed to load program notes")?;
+    if let Some(body) = annotations.notes.get(&args.pid) {
+        println!("{body}");
     } else {
-        let input = args.input.clone().context("--input is required")?;
+        tracing::info!("Program {} has no note", args.pid);
+    }
+    Ok(())
+}
 
-        let ctx = PipelineContext {
-            input,
-            channel_name,
-            config: jlse_config,
-            encode: args.encode,
-            target: AvsTarget::from(args.target),
-            add_chapter: args.add_chapter,
-            out_dir: args.outdir.clone(),
-            out_name: args.outname.clone(),
-            out_extension: None,
-            remove: args.remove,
-            progress_mode: None,
-            skip_duration_check: args.skip_duration_check,
-            force: args.force,
-        };
+/// Merged view of one title printed by `db show` (and written as JSON with
+/// `--output json`).
+#[derive(Debug, serde::Serialize)]
+struct TitleDetails {
+    /// Cached title, including the TMDB mapping.
+    title: CachedTitle,
+    /// Decoded category label.
+    category: &'static str,
+    /// Whether the title is followed.
+    followed: bool,
+    /// Episode sub-titles parsed from `SubTitles`, by count.
+    sub_titles: Vec<SubTitleEntry>,
+    /// Staff and cast parsed from the title comment.
+    credits: Vec<TitleCredit>,
+    /// Links parsed from the title comment.
+    links: Vec<TitleLink>,
+    /// Episode count ranges mapped to TMDB seasons.
+    season_ranges: Vec<SeasonRange>,
+    /// Episodes of the mapped TMDB seasons (only with `--tmdb`).
+    episodes: Vec<TitleEpisodeRow>,
+    /// Cached programs grouped by channel.
+    airings: Vec<ChannelAirings>,
+}
 
-        record_storage_metrics(&ctx);
+/// One parsed `SubTitles` entry in [`TitleDetails`].
+#[derive(Debug, serde::Serialize)]
+struct SubTitleEntry {
+    /// Episode count.
+    count: u32,
+    /// Episode sub-title.
+    sub_title: String,
+}
 
-        if args.tui {
-            run_pipeline_with_tui(ctx)
+/// One TMDB episode in [`TitleDetails`].
+#[derive(Debug, serde::Serialize)]
+struct TitleEpisodeRow {
+    /// TMDB season number (`None` for an episode group).
+    season_number: Option<u32>,
+    /// Episode number within the season (or group).
+    episode_number: u32,
+    /// TMDB episode ID.
+    tmdb_episode_id: u64,
+    /// Episode name.
+    name: String,
+    /// Air date (`YYYY-MM-DD`).
+    air_date: Option<String>,
+    /// Syoboi counts of the programs mapped to this episode.
+    counts: Vec<u32>,
+}
+
+/// Cached programs of one channel in [`TitleDetails`].
+#[derive(Debug, serde::Serialize)]
+struct ChannelAirings {
+    /// Syoboi channel ID.
+    ch_id: u32,
+    /// Channel name (`None` when the channel is not cached).
+    ch_name: Option<String>,
+    /// Programs in broadcast order.
+    programs: Vec<CachedProgram>,
+}
+
+/// Runs the `db show` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the title does not exist, DB operations fail, the
+/// TMDB client cannot be built (`--tmdb`), or JSON output cannot be written.
+#[allow(clippy::print_stdout)]
+#[instrument(skip_all, err(level = "error"))]
+async fn run_db_show(
+    args: &DbShowArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let title = load_titles_by_tids(&conn, &[args.tid])
+        .context("failed to load title")?
+        .pop()
+        .with_context(|| format!("title {} not found", args.tid))?;
+    let season_ranges: Vec<SeasonRange> = load_season_ranges(&conn)
+        .context("failed to load season ranges")?
+        .into_iter()
+        .filter(|r| r.tid == args.tid)
+        .collect();
+    let programs = load_programs_by_tids(&conn, &[args.tid]).context("failed to load programs")?;
+
+    let mut episodes = Vec::new();
+    if args.tmdb && title.tmdb_series_id.is_some() {
+        let language = resolve_tmdb_language(args.language.as_deref(), config_file);
+        let tmdb_client = build_tmdb_client(config_file).context("failed to build TMDB client")?;
+        let mapped = fetch_mapped_episodes(&tmdb_client, &title, &season_ranges, &language)
+            .await
+            .context("failed to fetch TMDB episodes")?;
+        episodes = title_episode_rows(&mapped, &programs);
+    } else if args.tmdb {
+        tracing::info!("Title {} has no TMDB series mapping", args.tid);
+    }
+
+    let details = TitleDetails {
+        category: TitleCategory::from_cat(title.cat).label(),
+        followed: load_followed_tids(&conn)
+            .context("failed to load followed titles")?
+            .contains(&args.tid),
+        sub_titles: load_episodes(&conn, args.tid)
+            .context("failed to load sub-titles")?
+            .into_iter()
+            .map(|e| SubTitleEntry {
+                count: e.count,
+                sub_title: e.sub_title,
+            })
+            .collect(),
+        credits: load_title_credits(&conn, args.tid).context("failed to load title staff")?,
+        links: load_title_links(&conn, args.tid).context("failed to load title links")?,
+        season_ranges,
+        episodes,
+        airings: group_airings_by_channel(
+            programs,
+            &load_channels(&conn).context("failed to load channels")?,
+        ),
+        title,
+    };
+    if output.is_json() {
+        return write_json(&details);
+    }
+    print!("{}", format_title_details(&details));
+    Ok(())
+}
+
+/// Lists the fetched TMDB episodes with the counts of the programs mapped
+/// to each (`tmdb_episode_id`).
+fn title_episode_rows(
+    mapped: &[MappedEpisodes],
+    programs: &[CachedProgram],
+) -> Vec<TitleEpisodeRow> {
+    mapped
+        .iter()
+        .flat_map(|m| {
+            m.episodes.iter().map(|e| {
+                let mut counts: Vec<u32> = programs
+                    .iter()
+                    .filter(|p| p.tmdb_episode_id == Some(e.id))
+                    .filter_map(|p| p.count)
+                    .collect();
+                counts.sort_unstable();
+                counts.dedup();
+                TitleEpisodeRow {
+                    season_number: m.season_number,
+                    episode_number: e.episode_number,
+                    tmdb_episode_id: e.id,
+                    name: e.name.clone(),
+                    air_date: e.air_date.clone(),
+                    counts,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Groups programs by channel, channels ordered by their first airing and
+/// programs by start time.
+fn group_airings_by_channel(
+    mut programs: Vec<CachedProgram>,
+    channels: &[CachedChannel],
+) -> Vec<ChannelAirings> {
+    programs.sort_by(|a, b| a.st_time.cmp(&b.st_time).then(a.pid.cmp(&b.pid)));
+    let mut airings: Vec<ChannelAirings> = Vec::new();
+    for program in programs {
+        if let Some(group) = airings.iter_mut().find(|g| g.ch_id == program.ch_id) {
+            group.programs.push(program);
         } else {
-            run_pipeline(&ctx, None)
+            airings.push(ChannelAirings {
+                ch_id: program.ch_id,
+                ch_name: channels
+                    .iter()
+                    .find(|c| c.ch_id == program.ch_id)
+                    .map(|c| c.ch_name.clone()),
+                programs: vec![program],
+            });
         }
     }
+    airings
 }
 
-/// Record `OTel` gauge metrics for storage directories referenced by the pipeline.
-fn record_storage_metrics(ctx: &PipelineContext) {
-    let ts_dir = ctx.input.parent().unwrap_or_else(|| Path::new("/"));
-    record_storage_dir_metrics(ts_dir, "ts_input");
-    if let Some(dir) = ctx.out_dir.as_deref() {
-        record_storage_dir_metrics(dir, "encoded_output");
+/// Writes the Syoboi fields and TMDB mapping of [`TitleDetails`].
+fn write_title_fields(out: &mut String, details: &TitleDetails) {
+    use std::fmt::Write as _;
+
+    let title = &details.title;
+    let mut field = |key: &str, value: Option<String>| {
+        if let Some(value) = value {
+            let _ = writeln!(out, "  {key}: {value}");
+        }
+    };
+    field("Short title", title.short_title.clone());
+    field("Yomi", title.title_yomi.clone());
+    field("English", title.title_en.clone());
+    field(
+        "Category",
+        Some(format!("{} ({})", details.category, title.cat.unwrap_or(0))),
+    );
+    field(
+        "First aired",
+        title.first_year.map(|y| {
+            title
+                .first_month
+                .map_or_else(|| y.to_string(), |m| format!("{y}-{m:02}"))
+        }),
+    );
+    field(
+        "Keywords",
+        (!title.keywords.is_empty()).then(|| title.keywords.join(", ")),
+    );
+    field("Followed", details.followed.then(|| String::from("yes")));
+    field("Last update", Some(title.last_update.clone()));
+
+    let tmdb: Vec<(&str, Option<String>)> = vec![
+        (
+            "Series",
+            title
+                .tmdb_series_id
+                .map(|id| format!("{id} (season {})", title.tmdb_season_number.unwrap_or(1))),
+        ),
+        ("Movie", title.tmdb_movie_id.map(|id| id.to_string())),
+        ("Episode group", title.tmdb_episode_group_id.clone()),
+        ("Name", title.tmdb_name.clone()),
+        (
+            "Season ranges",
+            (!details.season_ranges.is_empty()).then(|| {
+                details
+                    .season_ranges
+                    .iter()
+                    .map(|r| {
+                        let last = r.last_count.map_or_else(String::new, |c| c.to_string());
+                        format!("#{}-{last} -> S{}", r.first_count, r.season_number)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }),
+        ),
+    ];
+    if tmdb.iter().any(|(_, v)| v.is_some()) {
+        out.push_str("TMDB:\n");
+        for (key, value) in tmdb {
+            if let Some(value) = value {
+                let _ = writeln!(out, "  {key}: {value}");
+            }
+        }
     }
 }
 
-/// Record `OTel` gauge metrics for a single directory.
-#[cfg(feature = "otel")]
-fn record_storage_dir_metrics(dir: &Path, role: &str) {
-    if let Some(stats) = collect_storage_stats(dir) {
-        use opentelemetry::KeyValue;
+/// Renders [`TitleDetails`] for `db show`. Unset fields and empty sections
+/// are omitted.
+fn format_title_details(details: &TitleDetails) -> String {
+    use std::fmt::Write as _;
 
-        let attrs = &[
-            KeyValue::new("path", stats.path.clone()),
-            KeyValue::new("role", role.to_owned()),
-        ];
-        cli_metrics::STORAGE_FREE_BYTES.record(stats.free_bytes, attrs);
-        cli_metrics::STORAGE_TOTAL_BYTES.record(stats.total_bytes, attrs);
-        cli_metrics::STORAGE_USED_BYTES.record(stats.used_bytes, attrs);
-        cli_metrics::STORAGE_USAGE_RATIO.record(stats.usage_ratio, attrs);
-        cli_metrics::STORAGE_FILE_COUNT.record(stats.file_count, attrs);
+    let mut out = format!("TID {}: {}\n", details.title.tid, details.title.title);
+    write_title_fields(&mut out, details);
+    if !details.sub_titles.is_empty() {
+        out.push_str("Sub-titles:\n");
+        for entry in &details.sub_titles {
+            let _ = writeln!(out, "  #{} {}", entry.count, entry.sub_title);
+        }
+    }
+    for (kind, heading) in [(CreditKind::Staff, "Staff"), (CreditKind::Cast, "Cast")] {
+        let mut entries = details.credits.iter().filter(|c| c.kind == kind).peekable();
+        if entries.peek().is_none() {
+            continue;
+        }
+        let _ = writeln!(out, "{heading}:");
+        for credit in entries {
+            if credit.role.is_empty() {
+                let _ = writeln!(out, "  {}", credit.name);
+            } else {
+                let _ = writeln!(out, "  {}: {}", credit.role, credit.name);
+            }
+        }
+    }
+    if !details.links.is_empty() {
+        out.push_str("Links:\n");
+        for link in &details.links {
+            if link.label.is_empty() {
+                let _ = writeln!(out, "  {}", link.url);
+            } else {
+                let _ = writeln!(out, "  {}: {}", link.label, link.url);
+            }
+        }
     }
-}
-
-/// No-op when OTel is disabled.
-#[cfg(not(feature = "otel"))]
-fn record_storage_dir_metrics(_dir: &Path, _role: &str) {}
-
-/// Convert `StorageStats` to `StorageStatsSnapshot` for TUI display.
-const fn to_snapshot(s: &dtvmgr_jlse::storage::StorageStats) -> StorageStatsSnapshot {
-    StorageStatsSnapshot {
-        total_bytes: s.total_bytes,
-        used_bytes: s.used_bytes,
-        usage_ratio: s.usage_ratio,
-        file_count: s.file_count,
+    if !details.episodes.is_empty() {
+        out.push_str("Episodes (TMDB):\n");
+        for e in &details.episodes {
+            let number = e.season_number.map_or_else(
+                || format!("E{:02}", e.episode_number),
+                |s| format!("S{s}E{:02}", e.episode_number),
+            );
+            let counts: Vec<String> = e.counts.iter().map(|c| format!("#{c}")).collect();
+            let _ = writeln!(
+                out,
+                "  {number}\t{}\t{}\t{}",
+                e.air_date.as_deref().unwrap_or("-"),
+                e.name,
+                counts.join(",")
+            );
+        }
+    }
+    if !details.airings.is_empty() {
+        out.push_str("Airings:\n");
+        for group in &details.airings {
+            let _ = writeln!(
+                out,
+                "  {} ({}):",
+                group.ch_name.as_deref().unwrap_or("?"),
+                group.ch_id
+            );
+            for p in &group.programs {
+                let count = p
+                    .count
+                    .map_or_else(|| String::from("-"), |c| format!("#{c}"));
+                let sub_title = p
+                    .st_sub_title
+                    .as_deref()
+                    .or(p.sub_title.as_deref())
+                    .unwrap_or("");
+                let tmdb = p
+                    .tmdb_episode_id
+                    .map_or_else(String::new, |id| format!("\ttmdb {id}"));
+                let _ = writeln!(
+                    out,
+                    "    {}\t{count}\tPID {}\t{sub_title}{tmdb}",
+                    p.st_time, p.pid
+                );
+            }
+        }
     }
+    out
 }
 
-/// Populate storage stats in `EncodeSelectorState` entries.
-fn populate_storage_stats(state: &mut EncodeSelectorState) {
-    for entry in &mut state.storage_dirs {
-        entry.stats = collect_storage_stats(Path::new(&entry.path)).map(|s| to_snapshot(&s));
+/// Runs the `db episode-group` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the title does not exist or DB operations fail.
+#[allow(clippy::print_stdout)]
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_episode_group(args: &DbEpisodeGroupArgs, config_file: Option<&PathBuf>) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let title = load_titles_by_tids(&conn, &[args.tid])
+        .context("failed to load title")?
+        .pop()
+        .with_context(|| format!("title {} not found", args.tid))?;
+    if args.clear {
+        update_tmdb_episode_group(&conn, args.tid, None)?;
+        tracing::info!("Cleared episode group of title {}", args.tid);
+        return Ok(());
+    }
+    if let Some(group_id) = &args.group_id {
+        if title.tmdb_series_id.is_none() {
+            tracing::warn!(
+                tid = args.tid,
+                "Title has no TMDB series mapping; the episode group is used once it is mapped"
+            );
+        }
+        update_tmdb_episode_group(&conn, args.tid, Some(group_id))?;
+        tracing::info!("Saved episode group {group_id} for title {}", args.tid);
+        return Ok(());
+    }
+    if let Some(group_id) = &title.tmdb_episode_group_id {
+        println!("{group_id}");
+    } else {
+        tracing::info!("Title {} has no episode group", args.tid);
     }
+    Ok(())
 }
 
-// ── Syoboi / TMDB helpers ────────────────────────────────────
-
-/// Builds a `SyoboiClient` with default user agent.
+/// Runs the `db export-mappings` subcommand.
 ///
 /// # Errors
 ///
-/// Returns an error if the client fails to build.
+/// Returns an error if DB operations or the file write fail.
 #[instrument(skip_all, err(level = "error"))]
-fn build_syoboi_client() -> Result<SyoboiClient> {
-    SyoboiClient::builder()
-        .user_agent(concat!(
-            env!("CARGO_PKG_NAME"),
-            "/",
-            env!("CARGO_PKG_VERSION")
-        ))
-        .build()
-        .context("failed to build Syoboi API client")
+fn run_db_export_mappings(
+    args: &DbExportMappingsArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let doc = export_mappings(&conn).context("failed to export mappings")?;
+    let mut json = serde_json::to_string_pretty(&doc).context("failed to serialize mappings")?;
+    json.push('\n');
+    std::fs::write(&args.file, json)
+        .with_context(|| format!("failed to write {}", args.file.display()))?;
+    tracing::info!(
+        "Exported {} title mapping(s) to {}",
+        doc.mappings.len(),
+        args.file.display()
+    );
+    Ok(())
 }
 
-/// Runs the `syoboi channels select` subcommand.
+/// Runs the `db import-mappings` subcommand.
 ///
-/// Fetches channels/groups from API, caches in DB, launches TUI,
-/// and saves selection to `dtvmgr.toml`.
+/// TIDs missing from the cache are skipped with a warning; titles that
+/// already have a different mapping are kept unless `--overwrite` is given.
 ///
 /// # Errors
 ///
-/// Returns an error if API calls, DB operations, or TUI fails.
+/// Returns an error if the file cannot be read or parsed, or DB operations fail.
 #[instrument(skip_all, err(level = "error"))]
-async fn run_channels_select(config_file: Option<&PathBuf>) -> Result<()> {
-    let client = build_syoboi_client()?;
-
-    tracing::info!("Fetching channel groups from API...");
-    let api_groups = client
-        .lookup_channel_groups(None)
-        .await
-        .context("failed to fetch channel groups")?;
-
-    tracing::info!("Fetching channels from API...");
-    let api_channels = client
-        .lookup_channels(None)
-        .await
-        .context("failed to fetch channels")?;
-
-    // Cache in DB
-    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
-    let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
-
-    let cached_groups: Vec<CachedChannelGroup> = api_groups
-        .iter()
-        .map(|g| CachedChannelGroup {
-            ch_gid: g.ch_gid,
-            ch_group_name: g.ch_group_name.clone(),
-            ch_group_order: g.ch_group_order,
-        })
-        .collect();
-    let groups_changed =
-        upsert_channel_groups(&conn, &cached_groups).context("failed to cache channel groups")?;
-    tracing::info!(changed = groups_changed, "Channel groups upsert complete");
-
-    let valid_ch_gids: HashSet<u32> = cached_groups.iter().map(|g| g.ch_gid).collect();
-    let cached_channels: Vec<CachedChannel> = api_channels
-        .iter()
-        .map(|ch| CachedChannel {
-            ch_id: ch.ch_id,
-            ch_gid: ch.ch_gid.filter(|gid| valid_ch_gids.contains(gid)),
-            ch_name: ch.ch_name.clone(),
-        })
-        .collect();
-    let channels_changed =
-        upsert_channels(&conn, &cached_channels).context("failed to cache channels")?;
-    tracing::info!(changed = channels_changed, "Channels upsert complete");
-
-    // Load config
-    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
-    let config = AppConfig::load(&config_path).context("failed to load config")?;
-    let initial_selected: BTreeSet<u32> = config.syoboi.channels.selected.into_iter().collect();
+fn run_db_import_mappings(
+    args: &DbImportMappingsArgs,
+    config_file: Option<&PathBuf>,
+) -> Result<()> {
+    let content = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("failed to read {}", args.file.display()))?;
+    let doc: MappingsDocument = serde_json::from_str(&content)
+        .with_context(|| format!("failed to parse {}", args.file.display()))?;
 
-    // Build TUI data model
-    let groups = build_tui_groups(&cached_groups, &cached_channels);
+    let conn = open_data_db(config_file)?;
+    let report = import_mappings(&conn, &doc, args.overwrite)?;
 
+    if !report.unknown_tids.is_empty() {
+        tracing::warn!(
+            tids = ?report.unknown_tids,
+            "Skipped {} title(s) not in the cache; run `db sync` for them and import again",
+            report.unknown_tids.len()
+        );
+    }
+    if !report.conflicts.is_empty() {
+        tracing::warn!(
+            tids = ?report.conflicts,
+            "Kept {} title(s) with a different mapping; use --overwrite to replace them",
+            report.conflicts.len()
+        );
+    }
     tracing::info!(
-        "Loaded {} groups, {} channels. Launching TUI...",
-        cached_groups.len(),
-        cached_channels.len()
+        "Imported {} title mapping(s), {} unchanged",
+        report.imported,
+        report.unchanged
     );
+    Ok(())
+}
 
-    // Run TUI (blocking)
-    let result =
-        run_channel_selector(groups, initial_selected).context("channel selector TUI failed")?;
+/// Runs the `db snapshot` subcommands.
+///
+/// # Errors
+///
+/// Returns an error if the snapshot already exists (create), DB operations
+/// fail, or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_snapshot(
+    command: &DbSnapshotSubcommands,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
 
-    if let Some(selected) = result {
-        let mut config = AppConfig::load(&config_path).unwrap_or_default();
-        config.syoboi.channels.selected = selected;
-        config.save(&config_path).context("failed to save config")?;
+    match command {
+        DbSnapshotSubcommands::Create { name } => {
+            let now = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            let info = create_snapshot(&conn, name, &now)?;
+            tracing::info!(
+                "Created snapshot '{}' with {} programs",
+                info.name,
+                info.program_count
+            );
+        }
+        DbSnapshotSubcommands::List => {
+            let snapshots = load_snapshots(&conn).context("failed to load snapshots")?;
+            if output.is_json() {
+                return write_json(&snapshots);
+            }
+            tracing::info!("Name\tCreated\t\t\tPrograms");
+            for snapshot in &snapshots {
+                tracing::info!(
+                    "{}\t{}\t{}",
+                    snapshot.name,
+                    snapshot.created_at,
+                    snapshot.program_count
+                );
+            }
+            tracing::info!("Total: {} snapshots", snapshots.len());
+        }
+        DbSnapshotSubcommands::Delete { name } => {
+            if delete_snapshot(&conn, name)? {
+                tracing::info!("Deleted snapshot '{name}'");
+            } else {
+                tracing::info!("No snapshot named '{name}'");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `db diff` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if a snapshot does not exist, DB operations fail, or
+/// JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_diff(
+    args: &DbDiffArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let changes = diff_snapshots(&conn, &args.old, &args.new, !args.all_titles)?;
+    if output.is_json() {
+        return write_json(&changes);
+    }
+    for change in &changes {
+        let count = change
+            .count
+            .map_or_else(|| String::from("-"), |c| c.to_string());
+        let when = match change.kind {
+            ProgramChangeKind::Added => {
+                format!("+ {}", change.new_st_time.as_deref().unwrap_or(""))
+            }
+            ProgramChangeKind::Removed => {
+                format!("- {}", change.old_st_time.as_deref().unwrap_or(""))
+            }
+            ProgramChangeKind::TimeShifted => format!(
+                "~ {} -> {}",
+                change.old_st_time.as_deref().unwrap_or(""),
+                change.new_st_time.as_deref().unwrap_or("")
+            ),
+        };
         tracing::info!(
-            "Saved {} selected channel(s) to {}",
-            config.syoboi.channels.selected.len(),
-            config_path.display()
+            "{when}\tPID {}\tCh {}\t#{count}\t{}",
+            change.pid,
+            change.ch_id,
+            change.title
         );
-    } else {
-        tracing::info!("Selection cancelled");
     }
-
+    let tally = |kind| changes.iter().filter(|c| c.kind == kind).count();
+    tracing::info!(
+        "{} -> {}: {} added, {} removed, {} time-shifted",
+        args.old,
+        args.new,
+        tally(ProgramChangeKind::Added),
+        tally(ProgramChangeKind::Removed),
+        tally(ProgramChangeKind::TimeShifted)
+    );
     Ok(())
 }
 
-/// Builds TUI channel groups from cached data.
-fn build_tui_groups(
-    groups: &[CachedChannelGroup],
-    channels: &[CachedChannel],
-) -> Vec<ChannelGroup> {
-    let mut tui_groups: Vec<ChannelGroup> = groups
-        .iter()
-        .map(|g| ChannelGroup {
-            ch_gid: g.ch_gid,
-            name: g.ch_group_name.clone(),
-            channels: Vec::new(),
-        })
-        .collect();
+/// Formats one `db history` line in `tz`: when the change was stored, the
+/// old and new time range with revisions, and the start shift in minutes.
+fn format_history_entry(entry: &ProgramHistoryEntry, tz: Tz) -> String {
+    let revision = |r: Option<u32>| r.map_or_else(|| String::from("-"), |r| r.to_string());
+    let shift = syoboi_time_to_unix_ms(&entry.old_st_time)
+        .zip(syoboi_time_to_unix_ms(&entry.new_st_time))
+        .map_or_else(String::new, |(old, new)| {
+            let minutes = new.saturating_sub(old) / 60_000;
+            if minutes == 0 {
+                String::new()
+            } else {
+                format!("\t{minutes:+}m")
+            }
+        });
+    let changed_at = chrono::DateTime::parse_from_rfc3339(&entry.changed_at).map_or_else(
+        |_| entry.changed_at.clone(),
+        |dt| {
+            dt.with_timezone(&tz)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        },
+    );
+    format!(
+        "{changed_at}\t{} - {} (rev {}) -> {} - {} (rev {}){shift}",
+        display_broadcast_time(&entry.old_st_time, tz),
+        display_broadcast_time(&entry.old_ed_time, tz),
+        revision(entry.old_revision),
+        display_broadcast_time(&entry.new_st_time, tz),
+        display_broadcast_time(&entry.new_ed_time, tz),
+        revision(entry.new_revision),
+    )
+}
 
-    // Sort by ch_group_order (already sorted from DB, but ensure)
-    tui_groups.sort_by_key(|g| {
-        groups
-            .iter()
-            .find(|cg| cg.ch_gid == g.ch_gid)
-            .map_or(0, |cg| cg.ch_group_order)
-    });
+/// Runs the `db history` subcommand.
+///
+/// # Errors
+///
+/// Returns an error if the program is neither cached nor has history, DB
+/// operations fail, or JSON output cannot be written.
+#[allow(clippy::print_stdout)]
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_history(
+    args: &DbHistoryArgs,
+    config_file: Option<&PathBuf>,
+    output: OutputFormat,
+) -> Result<()> {
+    let conn = open_data_db(config_file)?;
 
-    for ch in channels {
-        if let Some(ch_gid) = ch.ch_gid
-            && let Some(group) = tui_groups.iter_mut().find(|g| g.ch_gid == ch_gid)
-        {
-            group.channels.push(ChannelEntry {
-                ch_id: ch.ch_id,
-                ch_name: ch.ch_name.clone(),
-            });
-        }
+    let history = load_program_history(&conn, args.pid)?;
+    let program = load_program(&conn, args.pid)?;
+    if program.is_none() && history.is_empty() {
+        anyhow::bail!("program {} is not cached", args.pid);
     }
-
-    // Sort channels within each group by ch_id
-    for group in &mut tui_groups {
-        group.channels.sort_by_key(|ch| ch.ch_id);
+    if output.is_json() {
+        return write_json(&history);
     }
-
-    tui_groups
+    let tz = display_timezone();
+    if let Some(p) = &program {
+        tracing::info!(
+            "PID {}\tTID {}\tCh {}\t#{}\t{} - {} (rev {})",
+            p.pid,
+            p.tid,
+            p.ch_id,
+            p.count.map_or_else(|| String::from("-"), |c| c.to_string()),
+            display_broadcast_time(&p.st_time, tz),
+            display_broadcast_time(&p.ed_time, tz),
+            p.revision
+                .map_or_else(|| String::from("-"), |r| r.to_string()),
+        );
+    }
+    if history.is_empty() {
+        tracing::info!("No time changes recorded for program {}", args.pid);
+    }
+    for entry in &history {
+        println!("{}", format_history_entry(entry, tz));
+    }
+    Ok(())
 }
 
-/// Runs the `syoboi channels list` subcommand.
+/// Runs the `db sql` subcommand.
+///
+/// The database is opened read-only unless `--write` is given, so a
+/// statement that modifies it cannot run by accident. Rows are printed
+/// tab-separated with a header line.
 ///
 /// # Errors
 ///
-/// Returns an error if config or DB operations fail.
+/// Returns an error if the database cannot be opened, the statement is
+/// invalid or writes without `--write`, or JSON output cannot be written.
+#[allow(clippy::print_stdout)]
 #[instrument(skip_all, err(level = "error"))]
-fn run_channels_list(config_file: Option<&PathBuf>) -> Result<()> {
-    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
-    let config = AppConfig::load(&config_path).context("failed to load config")?;
+fn run_db_sql(args: &DbSqlArgs, config_file: Option<&PathBuf>, output: OutputFormat) -> Result<()> {
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let defaults = *DB_OPTIONS.get_or_init(DbOptions::default);
+    let options = DbOptions {
+        read_only: defaults.read_only || !args.write,
+        ..defaults
+    };
+    let conn =
+        open_db_with_options(data_dir.as_ref(), &options).context("failed to open database")?;
 
-    if config.syoboi.channels.selected.is_empty() {
-        tracing::info!("No channels selected. Run `syoboi channels select` to choose channels.");
+    let result = run_sql(&conn, &args.query, args.write)?;
+    if output.is_json() {
+        return write_json(&result);
+    }
+    if let Some(changes) = result.changes {
+        tracing::info!("{changes} row(s) changed");
         return Ok(());
     }
+    println!("{}", result.columns.join("\t"));
+    for row in &result.rows {
+        let cells: Vec<String> = row.iter().map(ToString::to_string).collect();
+        println!("{}", cells.join("\t"));
+    }
+    tracing::info!("{} row(s)", result.rows.len());
+    Ok(())
+}
 
-    // Try to load names from DB cache
-    let data_dir = resolve_data_dir(config_file).ok().flatten();
-    let conn = open_db(data_dir.as_ref());
-    let cached_channels = conn
-        .as_ref()
-        .ok()
-        .and_then(|c| load_channels(c).ok())
-        .unwrap_or_default();
-
-    tracing::info!(
-        "Selected channels ({}):",
-        config.syoboi.channels.selected.len()
-    );
-    for ch_id in &config.syoboi.channels.selected {
-        let name = cached_channels
-            .iter()
-            .find(|c| c.ch_id == *ch_id)
-            .map_or("(unknown)", |c| c.ch_name.as_str());
-        tracing::info!("  {:>3}  {}", ch_id, name);
+/// Runs the `db stats` subcommand.
+///
+/// Prints cache totals followed by per-channel broadcast usage.
+///
+/// # Errors
+///
+/// Returns an error if DB operations fail or JSON output cannot be written.
+#[instrument(skip_all, err(level = "error"))]
+fn run_db_stats(config_file: Option<&PathBuf>, output: OutputFormat) -> Result<()> {
+    let conn = open_data_db(config_file)?;
+
+    let summary = load_db_summary(&conn).context("failed to load db summary")?;
+    if output.is_json() {
+        let categories = load_category_counts(&conn).context("failed to load category counts")?;
+        let channels = load_channel_usage(&conn).context("failed to load channel usage")?;
+        return write_json(&DbStatsOutput {
+            summary: &summary,
+            categories: categories
+                .iter()
+                .map(|&(cat, titles, programs)| CategoryCountOutput {
+                    cat,
+                    category: TitleCategory::from_cat(cat).to_string(),
+                    titles,
+                    programs,
+                })
+                .collect(),
+            channels: &channels,
+        });
+    }
+    tracing::info!(
+        "Titles: {} (TMDB matched: {}), Programs: {}, Channels: {}",
+        summary.total_titles,
+        summary.tmdb_matched,
+        summary.total_programs,
+        summary.unique_channels,
+    );
+    tracing::info!(
+        "Range: {} - {}",
+        summary.oldest_st_time.as_deref().unwrap_or("-"),
+        summary.newest_st_time.as_deref().unwrap_or("-"),
+    );
+
+    let categories = load_category_counts(&conn).context("failed to load category counts")?;
+    if !categories.is_empty() {
+        tracing::info!("Cat\tCategory\tTitles\tPrograms");
+        for (cat, titles, programs) in &categories {
+            tracing::info!(
+                "{}\t{}\t{titles}\t{programs}",
+                cat.map_or_else(|| String::from("-"), |c| c.to_string()),
+                TitleCategory::from_cat(*cat),
+            );
+        }
     }
 
+    let usage = load_channel_usage(&conn).context("failed to load channel usage")?;
+    if usage.is_empty() {
+        tracing::info!("No programs cached. Run `db sync` first.");
+        return Ok(());
+    }
+    tracing::info!("ChID\tPrograms\tTotalMin\tAirtimeMin\tOverlapMin\tAvgMin\tBusiest\tChannel");
+    for u in &usage {
+        tracing::info!(
+            "{}\t{}\t{}\t{}\t{}\t{:.1}\t{}\t{}",
+            u.ch_id,
+            u.program_count,
+            u.total_minutes,
+            u.airtime_minutes,
+            u.overlap_minutes(),
+            u.avg_minutes,
+            format_busiest_hours(&u.busiest_hours),
+            u.ch_name.as_deref().unwrap_or("-"),
+        );
+    }
     Ok(())
 }
 
+/// Spawns the background worker behind the title viewer's TMDB picker.
+///
+/// Each request is handled in order; results (including failures) are sent
+/// back to the picker.
+fn spawn_tmdb_picker_worker(
+    client: TmdbClient,
+    language: String,
+    data_dir: Option<PathBuf>,
+) -> TmdbPickerChannels {
+    let (request_tx, mut request_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (message_tx, message_rx) = std::sync::mpsc::channel();
+    tokio::spawn(async move {
+        while let Some(request) = request_rx.recv().await {
+            let message =
+                handle_tmdb_picker_request(&client, &language, data_dir.as_ref(), request).await;
+            if message_tx.send(message).is_err() {
+                break;
+            }
+        }
+    });
+    TmdbPickerChannels {
+        requests: request_tx,
+        messages: message_rx,
+    }
+}
+
+/// Searches TMDB, lists seasons, or stores a mapping for the TMDB picker.
+async fn handle_tmdb_picker_request(
+    client: &TmdbClient,
+    language: &str,
+    data_dir: Option<&PathBuf>,
+    request: TmdbPickerRequest,
+) -> TmdbPickerMessage {
+    let failed = |tid: u32, e: &anyhow::Error| TmdbPickerMessage::Failed {
+        tid,
+        error: format!("{e:#}"),
+    };
+    match request {
+        TmdbPickerRequest::Search { tid, query, movie } => {
+            let params = SearchMultiParams::new(&query).language(language);
+            match client.search_multi(&params).await {
+                Ok(response) => TmdbPickerMessage::Series {
+                    tid,
+                    candidates: response
+                        .results
+                        .into_iter()
+                        .filter_map(|r| match r {
+                            TmdbMultiSearchResult::Tv(tv) if !movie => Some(TmdbSeriesCandidate {
+                                id: tv.id,
+                                name: tv.name,
+                                original_name: tv.original_name,
+                                first_air_date: tv.first_air_date,
+                            }),
+                            TmdbMultiSearchResult::Movie(m) if movie => Some(TmdbSeriesCandidate {
+                                id: m.id,
+                                name: m.title,
+                                original_name: m.original_title,
+                                first_air_date: m.release_date,
+                            }),
+                            _ => None,
+                        })
+                        .collect(),
+                },
+                Err(e) => failed(tid, &e.into()),
+            }
+        }
+        TmdbPickerRequest::Seasons { tid, series_id } => {
+            match client.tv_details(series_id, language).await {
+                Ok(details) => TmdbPickerMessage::Seasons {
+                    tid,
+                    seasons: details
+                        .seasons
+                        .into_iter()
+                        .map(|s| TmdbSeasonCandidate {
+                            id: s.id,
+                            season_number: s.season_number,
+                            name: s.name,
+                            episode_count: s.episode_count,
+                            air_date: s.air_date,
+                        })
+                        .collect(),
+                },
+                Err(e) => failed(tid, &e.into()),
+            }
+        }
+        TmdbPickerRequest::Apply(choice) => {
+            let media_type = if choice.movie {
+                TmdbMediaType::Movie
+            } else {
+                TmdbMediaType::Tv
+            };
+            let localizations =
+                fetch_title_localizations(client, choice.tid, media_type, choice.series_id).await;
+            let result = open_db(data_dir).and_then(|conn| {
+                if choice.movie {
+                    update_tmdb_movie_mapping(&conn, choice.tid, Some(choice.series_id))?;
+                } else {
+                    update_tmdb_mapping(
+                        &conn,
+                        choice.tid,
+                        Some(choice.series_id),
+                        choice.season_number,
+                        choice.season_id,
+                    )?;
+                }
+                store_title_localizations(&conn, &localizations)
+            });
+            match result {
+                Ok(()) => TmdbPickerMessage::Applied(choice),
+                Err(e) => failed(choice.tid, &e),
+            }
+        }
+    }
+}
+
 /// Runs the `db list` subcommand.
 ///
 /// Loads titles, programs, and channels from local DB and launches the TUI viewer.
+/// When a TMDB client is available, the viewer's `m` key maps titles to TMDB.
 ///
 /// # Errors
 ///
 /// Returns an error if DB operations or TUI fails.
+#[allow(clippy::future_not_send)]
 #[instrument(skip_all, err(level = "error"))]
-fn run_db_list(config_file: Option<&PathBuf>) -> Result<()> {
+async fn run_db_list(args: &DbListArgs, config_file: Option<&PathBuf>) -> Result<()> {
     let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
     let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
     let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
@@ -2521,9 +9928,34 @@ fn run_db_list(config_file: Option<&PathBuf>) -> Result<()> {
     let excluded_tids: std::collections::HashSet<u32> =
         config.syoboi.titles.excludes.iter().copied().collect();
 
-    let titles = load_titles(&conn).context("failed to load titles")?;
-    let programs = load_programs(&conn).context("failed to load programs")?;
+    let mut titles = filter_titles_by_category(
+        load_titles(&conn).context("failed to load titles")?,
+        args.category.as_deref(),
+    );
+    let condition = args.filter.as_ref().map(FilterExpr::to_sql);
+    let matched = load_programs_filtered(
+        &conn,
+        &ProgramFilter {
+            condition: condition.as_ref(),
+            ..ProgramFilter::default()
+        },
+    )
+    .context("failed to load programs")?;
+    if args.filter.is_some() {
+        let matched_tids: HashSet<u32> = matched.iter().map(|p| p.tid).collect();
+        titles.retain(|t| matched_tids.contains(&t.tid));
+    }
+    let shown: HashSet<u32> = titles.iter().map(|t| t.tid).collect();
+    let programs: Vec<CachedProgram> = matched
+        .into_iter()
+        .filter(|p| shown.contains(&p.tid))
+        .collect();
     let channels = load_channels(&conn).context("failed to load channels")?;
+    let annotations = load_program_annotations(&conn).context("failed to load program tags")?;
+    let followed_tids: std::collections::HashSet<u32> = load_followed_tids(&conn)
+        .context("failed to load followed titles")?
+        .into_iter()
+        .collect();
 
     if titles.is_empty() {
         tracing::info!("No titles in database. Run `db sync` first.");
@@ -2539,23 +9971,79 @@ fn run_db_list(config_file: Option<&PathBuf>) -> Result<()> {
         channels.len()
     );
 
+    let tmdb = build_tmdb_client(config_file).ok().map(|client| {
+        let language = resolve_tmdb_language(None, config_file);
+        spawn_tmdb_picker_worker(client, language, data_dir.clone())
+    });
+
     let output = dtvmgr_tui::title_viewer::run_title_viewer(
         &titles,
         &programs,
+        &annotations,
         channels,
         excluded_tids,
+        followed_tids,
         compiled_regex.as_ref(),
+        &config.syoboi.titles.cat_movie.iter().copied().collect(),
+        tmdb,
+        None,
+        &parse_hidden_columns(&config.viewer.hidden_program_columns),
     )
+    .await
     .context("title viewer TUI failed")?;
 
+    apply_title_viewer_output(&conn, &config_path, &output)
+}
+
+/// Keeps the titles in `categories` (all when `None`).
+fn filter_titles_by_category(
+    titles: Vec<CachedTitle>,
+    categories: Option<&[TitleCategory]>,
+) -> Vec<CachedTitle> {
+    let Some(categories) = categories else {
+        return titles;
+    };
+    titles
+        .into_iter()
+        .filter(|t| categories.contains(&TitleCategory::from_cat(t.cat)))
+        .collect()
+}
+
+/// Saves follow changes, new excludes, and column toggles chosen in the
+/// title viewer.
+///
+/// # Errors
+///
+/// Returns an error if DB operations or config I/O fails.
+fn apply_title_viewer_output(
+    conn: &dtvmgr_db::Connection,
+    config_path: &Path,
+    output: &dtvmgr_tui::title_viewer::TitleViewerOutput,
+) -> Result<()> {
+    if !output.mapped.is_empty() {
+        tracing::info!("Updated TMDB mapping for {} titles", output.mapped.len());
+    }
+
+    if !output.followed.is_empty() || !output.unfollowed.is_empty() {
+        set_titles_followed(conn, &output.followed, true)
+            .context("failed to save followed titles")?;
+        set_titles_followed(conn, &output.unfollowed, false)
+            .context("failed to save unfollowed titles")?;
+        tracing::info!(
+            "Followed {} titles, unfollowed {} titles",
+            output.followed.len(),
+            output.unfollowed.len(),
+        );
+    }
+
     if !output.new_excludes.is_empty() {
         // Reload config to merge with any concurrent changes
-        let mut config = AppConfig::load(&config_path).context("failed to reload config")?;
+        let mut config = AppConfig::load(config_path).context("failed to reload config")?;
         let mut excludes: std::collections::HashSet<u32> =
             config.syoboi.titles.excludes.drain(..).collect();
         excludes.extend(&output.new_excludes);
         config.syoboi.titles.excludes = excludes.into_iter().collect();
-        config.save(&config_path).context("failed to save config")?;
+        config.save(config_path).context("failed to save config")?;
         tracing::info!(
             "Added {} TIDs to excludes (total: {})",
             output.new_excludes.len(),
@@ -2563,9 +10051,96 @@ fn run_db_list(config_file: Option<&PathBuf>) -> Result<()> {
         );
     }
 
+    if let Some(hidden) = &output.hidden_columns {
+        let mut config = AppConfig::load(config_path).context("failed to reload config")?;
+        config.viewer.hidden_program_columns = hidden.iter().map(|c| c.key().to_owned()).collect();
+        config.save(config_path).context("failed to save config")?;
+        tracing::info!("Saved {} hidden program columns", hidden.len());
+    }
+
     Ok(())
 }
 
+/// Runs the `db grid` subcommand.
+///
+/// Shows cached programs as an EPG grid (channels x 30-minute slots) for one
+/// broadcast day. Opening a program launches the title viewer focused on its
+/// title; quitting the viewer returns to the grid at the same position.
+///
+/// # Errors
+///
+/// Returns an error if DB operations or TUI fails.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, err(level = "error"))]
+async fn run_db_grid(config_file: Option<&PathBuf>, args: &DbGridArgs) -> Result<()> {
+    use dtvmgr_tui::program_grid::state::{GridPosition, broadcast_date};
+
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+
+    let mut titles = load_titles(&conn).context("failed to load titles")?;
+    let programs = load_programs(&conn).context("failed to load programs")?;
+    let channels = load_channels(&conn).context("failed to load channels")?;
+
+    if programs.is_empty() {
+        tracing::info!("No programs in database. Run `db sync` first.");
+        return Ok(());
+    }
+    warn_if_channels_stale(&conn, config_file);
+
+    let now = SystemClock.now_in(SYOBOI_TIMEZONE);
+    let today = broadcast_date(now);
+    let mut position = args
+        .date
+        .map_or_else(|| GridPosition::at(now), GridPosition::day);
+
+    loop {
+        let grid = dtvmgr_tui::program_grid::run_program_grid(
+            &titles, &programs, &channels, position, today,
+        )
+        .context("program grid TUI failed")?;
+        position = grid.position;
+        let Some(tid) = grid.open_tid else {
+            return Ok(());
+        };
+
+        // Reload per round trip: the previous viewer session may have
+        // changed excludes, follows, or mappings.
+        let config = AppConfig::load(&config_path).context("failed to load config")?;
+        let annotations = load_program_annotations(&conn).context("failed to load program tags")?;
+        let followed_tids: std::collections::HashSet<u32> = load_followed_tids(&conn)
+            .context("failed to load followed titles")?
+            .into_iter()
+            .collect();
+        let tmdb = build_tmdb_client(config_file).ok().map(|client| {
+            let language = resolve_tmdb_language(None, config_file);
+            spawn_tmdb_picker_worker(client, language, data_dir.clone())
+        });
+
+        let output = dtvmgr_tui::title_viewer::run_title_viewer(
+            &titles,
+            &programs,
+            &annotations,
+            channels.clone(),
+            config.syoboi.titles.excludes.iter().copied().collect(),
+            followed_tids,
+            compile_regex_titles(&config.normalize.regex_titles).as_ref(),
+            &config.syoboi.titles.cat_movie.iter().copied().collect(),
+            tmdb,
+            Some(tid),
+            &parse_hidden_columns(&config.viewer.hidden_program_columns),
+        )
+        .await
+        .context("title viewer TUI failed")?;
+
+        apply_title_viewer_output(&conn, &config_path, &output)?;
+        if !output.mapped.is_empty() {
+            titles = load_titles(&conn).context("failed to reload titles")?;
+        }
+    }
+}
+
 /// Runs the `db normalize` subcommand.
 ///
 /// Loads titles from local DB and regex history from config, launches the
@@ -2577,8 +10152,7 @@ fn run_db_list(config_file: Option<&PathBuf>) -> Result<()> {
 /// Returns an error if DB operations, config I/O, or TUI fails.
 #[instrument(skip_all, err(level = "error"))]
 fn run_db_normalize(config_file: Option<&PathBuf>) -> Result<()> {
-    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
-    let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+    let conn = open_data_db(config_file)?;
     let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
     let config = AppConfig::load(&config_path).context("failed to load config")?;
 
@@ -2626,13 +10200,39 @@ fn default_env_filter() -> EnvFilter {
 
 /// Entry point.
 ///
+/// Exits with the code of the command outcome (see [`exit`]).
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let quiet = cli.quiet;
+    let (result, marked) = exit::scoped(Box::pin(run(cli))).await;
+    exit::finish(&result, marked, quiet)
+}
+
+/// Sets up logging and runs the selected subcommand.
+///
 /// # Errors
 ///
 /// Returns an error if subcommand execution fails.
-#[tokio::main(flavor = "current_thread")]
-#[allow(clippy::too_many_lines)]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+#[allow(
+    clippy::too_many_lines,
+    clippy::cognitive_complexity,
+    clippy::future_not_send
+)]
+async fn run(cli: Cli) -> Result<()> {
+    let moved_config = set_path_overrides(
+        cli.dir.as_deref(),
+        cli.data_dir.as_deref(),
+        cli.profile.as_deref(),
+        cli.config.as_ref(),
+    )?;
+    init_audit_log(cli.audit_log.as_deref())?;
+    init_client_settings(cli.config.as_ref());
+    init_cassette(cli.record.as_deref(), cli.replay.as_deref());
+    let _ = DB_OPTIONS.set(DbOptions {
+        read_only: cli.db_readonly,
+        ..DbOptions::default()
+    });
 
     // Detect TUI mode to suppress fmt output (alternate screen conflicts).
     let tui_mode = match &cli.command {
@@ -2646,6 +10246,15 @@ async fn main() -> Result<()> {
         _ => false,
     };
 
+    // JSON mode: keep stdout for the result document.
+    let log_writer = if cli.quiet {
+        BoxMakeWriter::new(std::io::sink)
+    } else if cli.output.is_json() {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     #[cfg(not(feature = "otel"))]
     {
         if tui_mode {
@@ -2658,6 +10267,8 @@ async fn main() -> Result<()> {
             fmt()
                 .with_env_filter(default_env_filter())
                 .with_target(false)
+                .with_ansi(dtvmgr_tui::term::current().color)
+                .with_writer(log_writer)
                 .init();
         }
     }
@@ -2670,7 +10281,12 @@ async fn main() -> Result<()> {
         let fmt_layer = if tui_mode {
             None
         } else {
-            Some(tracing_subscriber::fmt::layer().with_target(false))
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_ansi(dtvmgr_tui::term::current().color)
+                    .with_writer(log_writer),
+            )
         };
 
         let otel_parts = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
@@ -2769,30 +10385,168 @@ async fn main() -> Result<()> {
         (tracer_provider, logger_provider, meter_provider)
     };
 
-    let result = match cli.command {
+    if let Some(path) = moved_config {
+        tracing::info!("Moved config to {}", path.display());
+    }
+    let migrated = if cli.db_readonly {
+        Ok(())
+    } else {
+        migrate_legacy_db(cli.config.as_ref())
+    };
+    let result = match migrated {
+        Ok(()) => Box::pin(run_command(cli)).await,
+        Err(e) => Err(e),
+    };
+
+    // Shut down in reverse initialization order — logger last so that
+    // log export remains available while traces and metrics are flushing.
+    #[cfg(feature = "otel")]
+    {
+        if let Some(provider) = tracer_provider {
+            if let Err(e) = provider.force_flush() {
+                tracing::warn!("failed to flush OTel tracer provider: {e:#}");
+            }
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!("failed to shutdown OTel tracer provider: {e:#}");
+            }
+        }
+        if let Some(provider) = meter_provider {
+            if let Err(e) = provider.force_flush() {
+                tracing::warn!("failed to flush OTel meter provider: {e:#}");
+            }
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!("failed to shutdown OTel meter provider: {e:#}");
+            }
+        }
+        if let Some(provider) = logger_provider {
+            if let Err(e) = provider.force_flush() {
+                tracing::warn!("failed to flush OTel logger provider: {e:#}");
+            }
+            if let Err(e) = provider.shutdown() {
+                tracing::warn!("failed to shutdown OTel logger provider: {e:#}");
+            }
+        }
+    }
+
+    result
+}
+
+/// Runs the selected subcommand.
+///
+/// # Errors
+///
+/// Returns the error of the subcommand.
+#[allow(
+    clippy::too_many_lines,
+    clippy::cognitive_complexity,
+    clippy::future_not_send
+)]
+async fn run_command(cli: Cli) -> Result<()> {
+    match cli.command {
         Commands::Syoboi(cmd) => match cmd.command {
-            SyoboiSubcommands::Prog(args) => run_syoboi_prog(&args, cli.config.as_ref()).await,
-            SyoboiSubcommands::Titles(args) => run_syoboi_titles(&args).await,
+            SyoboiSubcommands::Prog(args) => {
+                run_syoboi_prog(&args, cli.config.as_ref(), cli.output).await
+            }
+            SyoboiSubcommands::Titles(args) => run_syoboi_titles(&args, cli.output).await,
             SyoboiSubcommands::Channels(ch) => match ch.command {
                 ChannelsSubcommands::Select => run_channels_select(cli.config.as_ref()).await,
                 ChannelsSubcommands::List => run_channels_list(cli.config.as_ref()),
+                ChannelsSubcommands::Sync => run_channels_sync(cli.config.as_ref()).await,
+                ChannelsSubcommands::Export(args) => {
+                    run_channels_export(&args, cli.config.as_ref(), cli.output)
+                }
+                ChannelsSubcommands::Import(args) => {
+                    Box::pin(run_channels_import(&args, cli.config.as_ref(), cli.output)).await
+                }
             },
         },
         Commands::Tmdb(tmdb) => match tmdb.command {
-            TmdbSubcommands::SearchTv(args) => run_tmdb_search_tv(&args, cli.config.as_ref()).await,
+            TmdbSubcommands::SearchTv(args) => {
+                Box::pin(run_tmdb_search_tv(&args, cli.config.as_ref(), cli.output)).await
+            }
             TmdbSubcommands::SearchMovie(args) => {
-                run_tmdb_search_movie(&args, cli.config.as_ref()).await
+                Box::pin(run_tmdb_search_movie(
+                    &args,
+                    cli.config.as_ref(),
+                    cli.output,
+                ))
+                .await
             }
             TmdbSubcommands::TvDetails(args) => {
-                run_tmdb_tv_details(&args, cli.config.as_ref()).await
+                run_tmdb_tv_details(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::MovieDetails(args) => {
+                run_tmdb_movie_details(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::TvSeason(args) => {
+                run_tmdb_tv_season(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::TvEpisode(args) => {
+                run_tmdb_tv_episode(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::EpisodeGroups(args) => {
+                run_tmdb_episode_groups(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::EpisodeGroup(args) => {
+                run_tmdb_episode_group(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::WatchProviders(args) => {
+                run_tmdb_watch_providers(&args, cli.config.as_ref()).await
+            }
+            TmdbSubcommands::FindByExternalId(args) => {
+                run_tmdb_find_by_external_id(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::Images(args) => run_tmdb_images(&args, cli.config.as_ref()).await,
+            TmdbSubcommands::AutoMatch(args) => {
+                run_tmdb_auto_match(&args, cli.config.as_ref(), cli.output).await
+            }
+            TmdbSubcommands::Suggest(args) => {
+                Box::pin(run_tmdb_suggest(&args, cli.config.as_ref(), cli.output)).await
+            }
+            TmdbSubcommands::Apply(args) => {
+                Box::pin(run_tmdb_apply(&args, cli.config.as_ref(), cli.output)).await
             }
-            TmdbSubcommands::TvSeason(args) => run_tmdb_tv_season(&args, cli.config.as_ref()).await,
         },
         Commands::Db(db) => match db.command {
             DbSubcommands::Sync(args) => run_db_sync(&args, cli.config.as_ref()).await,
-            DbSubcommands::List => run_db_list(cli.config.as_ref()),
+            DbSubcommands::Backfill(args) => run_db_backfill(&args, cli.config.as_ref()).await,
+            DbSubcommands::List(args) => run_db_list(&args, cli.config.as_ref()).await,
+            DbSubcommands::Grid(args) => run_db_grid(cli.config.as_ref(), &args).await,
             DbSubcommands::Normalize => run_db_normalize(cli.config.as_ref()),
-            DbSubcommands::TmdbLookup(args) => run_db_tmdb_lookup(&args, cli.config.as_ref()).await,
+            DbSubcommands::TmdbLookup(args) => {
+                Box::pin(run_db_tmdb_lookup(&args, cli.config.as_ref())).await
+            }
+            DbSubcommands::Stats => run_db_stats(cli.config.as_ref(), cli.output),
+            DbSubcommands::Recompute(args) => run_db_recompute(&args, cli.config.as_ref()),
+            DbSubcommands::TmdbMatch(args) => run_db_tmdb_match(&args, cli.config.as_ref()).await,
+            DbSubcommands::Bootstrap(args) => run_db_bootstrap(&args, cli.config.as_ref()).await,
+            DbSubcommands::Export(export) => match export.command {
+                DbExportSubcommands::Ics(args) => {
+                    run_export_file("ics", &args, cli.config.as_ref()).await
+                }
+            },
+            DbSubcommands::Conflicts(args) => {
+                run_db_conflicts(&args, cli.config.as_ref(), cli.output)
+            }
+            DbSubcommands::Prune(args) => run_db_prune(&args, cli.config.as_ref()),
+            DbSubcommands::Vacuum => run_db_vacuum(cli.config.as_ref()),
+            DbSubcommands::Bench(args) => run_db_bench(&args, cli.config.as_ref(), cli.output),
+            DbSubcommands::Tag(args) => run_db_tag(&args, cli.config.as_ref()),
+            DbSubcommands::Note(args) => run_db_note(&args, cli.config.as_ref()),
+            DbSubcommands::Show(args) => run_db_show(&args, cli.config.as_ref(), cli.output).await,
+            DbSubcommands::EpisodeGroup(args) => run_db_episode_group(&args, cli.config.as_ref()),
+            DbSubcommands::ExportMappings(args) => {
+                run_db_export_mappings(&args, cli.config.as_ref())
+            }
+            DbSubcommands::ImportMappings(args) => {
+                run_db_import_mappings(&args, cli.config.as_ref())
+            }
+            DbSubcommands::Snapshot(snapshot) => {
+                run_db_snapshot(&snapshot.command, cli.config.as_ref(), cli.output)
+            }
+            DbSubcommands::Diff(args) => run_db_diff(&args, cli.config.as_ref(), cli.output),
+            DbSubcommands::History(args) => run_db_history(&args, cli.config.as_ref(), cli.output),
+            DbSubcommands::Sql(args) => run_db_sql(&args, cli.config.as_ref(), cli.output),
         },
         Commands::Jlse(jlse) => match jlse.command {
             JlseSubcommands::Channel(args) => run_jlse_channel(&args, cli.config.as_ref()),
@@ -2805,45 +10559,101 @@ async fn main() -> Result<()> {
                 run_epgstation_encode(&args, cli.config.as_ref()).await
             }
         },
-        Commands::Init => run_init(cli.config.as_ref()),
-        Commands::Completion(comp) => {
-            let mut cmd = Cli::command();
-            clap_complete::generate(comp.shell, &mut cmd, "dtvmgr", &mut std::io::stdout());
-            Ok(())
-        }
-    };
-
-    // Shut down in reverse initialization order — logger last so that
-    // log export remains available while traces and metrics are flushing.
-    #[cfg(feature = "otel")]
-    {
-        if let Some(provider) = tracer_provider {
-            if let Err(e) = provider.force_flush() {
-                tracing::warn!("failed to flush OTel tracer provider: {e:#}");
+        Commands::Search(args) => run_search(&args, cli.config.as_ref()).await,
+        Commands::Watchlist(wl) => match wl.command {
+            WatchlistSubcommands::Add(args) => run_watchlist_add(&args, cli.config.as_ref()),
+            WatchlistSubcommands::Remove(args) => run_watchlist_remove(&args, cli.config.as_ref()),
+            WatchlistSubcommands::List => run_watchlist_list(cli.config.as_ref()),
+        },
+        Commands::Titles(titles) => match titles.command {
+            TitleFollowSubcommands::Follow(args) => {
+                run_titles_follow(&args, true, cli.config.as_ref())
             }
-            if let Err(e) = provider.shutdown() {
-                tracing::warn!("failed to shutdown OTel tracer provider: {e:#}");
+            TitleFollowSubcommands::Unfollow(args) => {
+                run_titles_follow(&args, false, cli.config.as_ref())
             }
-        }
-        if let Some(provider) = meter_provider {
-            if let Err(e) = provider.force_flush() {
-                tracing::warn!("failed to flush OTel meter provider: {e:#}");
+            TitleFollowSubcommands::ListFollowed => {
+                run_titles_list_followed(cli.config.as_ref(), cli.output)
             }
-            if let Err(e) = provider.shutdown() {
-                tracing::warn!("failed to shutdown OTel meter provider: {e:#}");
+            TitleFollowSubcommands::ImportSyoboi(args) => {
+                run_titles_import_syoboi(&args, cli.config.as_ref()).await
             }
-        }
-        if let Some(provider) = logger_provider {
-            if let Err(e) = provider.force_flush() {
-                tracing::warn!("failed to flush OTel logger provider: {e:#}");
+            TitleFollowSubcommands::SplitSeasons(args) => {
+                run_titles_split_seasons(&args, cli.config.as_ref(), cli.output)
             }
-            if let Err(e) = provider.shutdown() {
-                tracing::warn!("failed to shutdown OTel logger provider: {e:#}");
+            TitleFollowSubcommands::Merge(args) => {
+                run_titles_merge(&args, cli.config.as_ref(), cli.output)
+            }
+            TitleFollowSubcommands::Aliases => run_titles_aliases(cli.config.as_ref(), cli.output),
+        },
+        Commands::Channels(channels) => match channels.command {
+            ChannelAliasesSubcommands::Map(map) => match map.command {
+                ChannelsMapSubcommands::Import(args) => {
+                    run_channels_map_import(&args, cli.config.as_ref()).await
+                }
+                ChannelsMapSubcommands::List => {
+                    run_channels_map_list(cli.config.as_ref(), cli.output)
+                }
+                ChannelsMapSubcommands::Remove(args) => {
+                    run_channels_map_remove(&args, cli.config.as_ref())
+                }
+            },
+            ChannelAliasesSubcommands::FetchLogos(args) => {
+                run_channels_fetch_logos(&args, cli.config.as_ref()).await
+            }
+        },
+        Commands::Library(lib) => match lib.command {
+            LibrarySubcommands::Verify(args) => run_library_verify(&args, cli.config.as_ref()),
+        },
+        Commands::Report(report) => match report.command {
+            ReportSubcommands::Coverage(args) => {
+                run_report_coverage(&args, cli.config.as_ref(), cli.output).await
+            }
+            ReportSubcommands::Gaps(args) => {
+                run_report_gaps(&args, cli.config.as_ref(), cli.output)
+            }
+        },
+        Commands::Notify(notify) => match notify.command {
+            NotifySubcommands::Digest(args) => run_notify_digest(&args, cli.config.as_ref()).await,
+        },
+        Commands::Jobs(jobs) => match jobs.command {
+            JobsSubcommands::List(args) => run_jobs_list(&args, cli.config.as_ref()),
+            JobsSubcommands::Add(args) => run_jobs_add(&args, cli.config.as_ref()),
+            JobsSubcommands::Cancel(args) => run_jobs_cancel(&args, cli.config.as_ref()),
+            JobsSubcommands::RunNow(args) => run_jobs_run_now(&args, cli.config.as_ref()).await,
+        },
+        Commands::Rules(rules) => match rules.command {
+            RulesSubcommands::Run(args) => run_rules_run(&args, cli.config.as_ref(), cli.output),
+        },
+        Commands::Daemon(args) => run_daemon(&args, cli.config.as_ref()).await,
+        Commands::Export(export) => match export.command {
+            ExportSubcommands::Ics(args) => {
+                run_export_file("ics", &args, cli.config.as_ref()).await
+            }
+            ExportSubcommands::Xmltv(args) => {
+                run_export_file("xmltv", &args, cli.config.as_ref()).await
+            }
+            ExportSubcommands::Nfo(args) => run_export_nfo(&args, cli.config.as_ref()).await,
+            ExportSubcommands::Csv(args) => {
+                run_export_file("csv", &args, cli.config.as_ref()).await
             }
+            ExportSubcommands::Json(args) => {
+                run_export_file("json", &args, cli.config.as_ref()).await
+            }
+            ExportSubcommands::ListFormats => run_export_list_formats(cli.output),
+        },
+        Commands::Serve(args) => run_serve(&args, cli.config.as_ref()).await,
+        Commands::Init => run_init(cli.config.as_ref()),
+        Commands::Paths => run_paths(cli.config.as_ref()),
+        Commands::Config(cmd) => match cmd.command {
+            ConfigSubcommands::Check => run_config_check(cli.config.as_ref()),
+        },
+        Commands::Completion(comp) => {
+            let mut cmd = Cli::command();
+            clap_complete::generate(comp.shell, &mut cmd, "dtvmgr", &mut std::io::stdout());
+            Ok(())
         }
     }
-
-    result
 }
 
 /// Converts an API `RecordedItem` page to cached DB items.
@@ -3610,6 +11420,10 @@ async fn run_epgstation_encode(
                     terminal
                         .draw(|frame| {
                             dtvmgr_tui::encode_selector::draw_confirm(frame, &state);
+                            dtvmgr_tui::term::degrade(
+                                frame.buffer_mut(),
+                                dtvmgr_tui::term::current(),
+                            );
                         })
                         .context("failed to draw submission progress")?;
 
@@ -3736,6 +11550,133 @@ fn run_init(config_file: Option<&PathBuf>) -> Result<()> {
     Ok(())
 }
 
+/// Moves a database left in the platform config directory or an earlier
+/// layout into the platform data directory.
+///
+/// # Errors
+///
+/// Returns an error if the legacy database is in use (e.g. by a running
+/// `daemon` or `serve`) or cannot be moved, so the command does not run
+/// against a fresh database in the data directory instead.
+fn migrate_legacy_db(config_file: Option<&PathBuf>) -> Result<()> {
+    let moved = Paths::current()
+        .and_then(|p| p.migrate_legacy_db(config_file))
+        .context(
+            "failed to move the database out of the legacy directory; \
+             stop any running `daemon` / `serve` and try again",
+        )?;
+    if let Some(path) = moved {
+        tracing::info!("Moved database to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Prints the resolved config, data, database, and cache locations.
+///
+/// # Errors
+///
+/// Returns an error if any path cannot be resolved.
+#[instrument(skip_all, err(level = "error"))]
+fn run_paths(config_file: Option<&PathBuf>) -> Result<()> {
+    let paths = Paths::current()?;
+    let config = paths
+        .config_path(config_file)
+        .context("failed to resolve config path")?;
+    let db = paths
+        .db_path(config_file)
+        .context("failed to resolve database path")?;
+    let cache = paths
+        .cache_dir()
+        .context("failed to resolve cache directory")?;
+    let data = db.parent().map_or_else(PathBuf::new, PathBuf::from);
+
+    tracing::info!("Kind\tPath\tExists");
+    for (kind, path) in [
+        ("config", &config),
+        ("data", &data),
+        ("database", &db),
+        ("cache", &cache),
+    ] {
+        tracing::info!("{kind}\t{}\t{}", path.display(), path.exists());
+    }
+    Ok(())
+}
+
+/// Runs the `config check` subcommand.
+///
+/// Prints the resolved paths, parses the config file strictly, and reports
+/// unknown keys, channel IDs missing from the channel cache, invalid
+/// normalize patterns, and a missing TMDB token. Does not create the config
+/// file or the database.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be read or parsed, or if any
+/// error-level issue is found.
+#[instrument(skip_all, err(level = "error"))]
+fn run_config_check(config_file: Option<&PathBuf>) -> Result<()> {
+    use crate::config::check::{CheckContext, Severity, check_config};
+    use crate::config::mapping::MAPPING_FILENAME;
+
+    run_paths(config_file)?;
+
+    let config_path = resolve_config_path(config_file).context("failed to resolve config path")?;
+    let content = match read_toml_text(&config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            tracing::warn!("Config file not found; built-in defaults are used (run `init`)");
+            String::new()
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read {}", config_path.display()));
+        }
+    };
+    let (config, unknown_keys) = AppConfig::parse(&content)
+        .with_context(|| ConfigError(format!("failed to parse {}", config_path.display())))?;
+
+    let data_dir = resolve_data_dir(config_file).context("failed to resolve data directory")?;
+    let db_path = resolve_db_path(data_dir.as_ref()).context("failed to resolve database path")?;
+    let cached_channels = if db_path.exists() {
+        let conn = open_db(data_dir.as_ref()).context("failed to open database")?;
+        let ids: std::collections::HashSet<u32> = load_channels(&conn)
+            .context("failed to load channels")?
+            .into_iter()
+            .map(|c| c.ch_id)
+            .collect();
+        (!ids.is_empty()).then_some(ids)
+    } else {
+        None
+    };
+    let ctx = CheckContext {
+        cached_channels,
+        tmdb_token_env: std::env::var_os("TMDB_API_TOKEN").is_some(),
+        mapping_file: config_path
+            .parent()
+            .is_some_and(|dir| dir.join(MAPPING_FILENAME).exists()),
+    };
+
+    let issues = check_config(&config, &unknown_keys, &ctx);
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    for issue in &issues {
+        match issue.severity {
+            Severity::Error => tracing::error!("{}", issue.message),
+            Severity::Warning => tracing::warn!("{}", issue.message),
+        }
+    }
+    if errors > 0 {
+        return Err(ConfigError(format!("config check found {errors} errors")).into());
+    }
+    tracing::info!(
+        "Config OK ({} warnings): {}",
+        issues.len(),
+        config_path.display()
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(
@@ -3746,6 +11687,10 @@ mod tests {
 
     use super::*;
     use dtvmgr_api::epgstation::{DropLogFile, VideoFile};
+    use dtvmgr_api::syoboi::SyoboiTitle;
+    use dtvmgr_core::notify::ChangeKind;
+    use dtvmgr_core::sync::{cleanup_disallowed_cats, upsert_filtered_programs};
+    use dtvmgr_db::DEFAULT_UPSERT_BATCH_SIZE;
 
     #[test]
     fn test_compile_regex_titles_empty() {
@@ -3918,6 +11863,8 @@ mod tests {
             tmdb_series_id,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: format!("Title {tid}"),
             short_title: None,
             title_yomi: None,
@@ -3927,15 +11874,282 @@ mod tests {
             first_year: None,
             first_month: None,
             keywords: vec![],
-            sub_titles: None,
             last_update: String::new(),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: tmdb_last_updated.map(String::from),
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_titles_by_category() {
+        // Arrange: a missing `cat` decodes as `other`
+        let mut anime = make_cached_title(1, None, None);
+        anime.cat = Some(1);
+        let mut ova = make_cached_title(2, None, None);
+        ova.cat = Some(7);
+        let unknown = make_cached_title(3, None, None);
+        let titles = vec![anime, ova, unknown];
+
+        // Act
+        let kept = filter_titles_by_category(
+            titles.clone(),
+            Some(&[TitleCategory::Ova, TitleCategory::Other]),
+        );
+        let all = filter_titles_by_category(titles, None);
+
+        // Assert
+        let tids: Vec<u32> = kept.iter().map(|t| t.tid).collect();
+        assert_eq!(tids, vec![2, 3]);
+        assert_eq!(all.len(), 3);
+    }
+
+    fn make_show_program(pid: u32, ch_id: u32, st_time: &str, count: u32) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid: 6309,
+            ch_id,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: st_time.to_owned(),
+            count: Some(count),
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
         }
     }
 
+    #[test]
+    fn test_group_airings_by_channel_orders_by_first_airing() {
+        // Arrange
+        let programs = vec![
+            make_show_program(3, 7, "2022-04-16 23:00:00", 2),
+            make_show_program(2, 5, "2022-04-10 01:00:00", 1),
+            make_show_program(1, 7, "2022-04-09 23:00:00", 1),
+        ];
+        let channels = vec![CachedChannel {
+            ch_id: 7,
+            ch_name: String::from("テレビ東京"),
+            ..CachedChannel::default()
+        }];
+
+        // Act
+        let airings = group_airings_by_channel(programs, &channels);
+
+        // Assert
+        let layout: Vec<(u32, Option<&str>, Vec<u32>)> = airings
+            .iter()
+            .map(|g| {
+                (
+                    g.ch_id,
+                    g.ch_name.as_deref(),
+                    g.programs.iter().map(|p| p.pid).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            vec![(7, Some("テレビ東京"), vec![1, 3]), (5, None, vec![2])]
+        );
+    }
+
+    #[test]
+    fn test_title_episode_rows_lists_mapped_counts() {
+        // Arrange
+        let mut first = make_show_program(1, 7, "2022-04-09 23:00:00", 1);
+        first.tmdb_episode_id = Some(501);
+        let mut rerun = make_show_program(2, 5, "2022-04-12 01:00:00", 1);
+        rerun.tmdb_episode_id = Some(501);
+        let episode = |id, episode_number| dtvmgr_api::tmdb::TmdbEpisode {
+            id,
+            episode_number,
+            name: format!("Episode {episode_number}"),
+            overview: None,
+            air_date: Some(String::from("2022-04-09")),
+            season_number: 1,
+            show_id: 120_089,
+            runtime: None,
+            vote_average: 0.0,
+            episode_type: None,
+        };
+        let mapped = vec![MappedEpisodes {
+            season_number: Some(1),
+            range: None,
+            episodes: vec![episode(501, 1), episode(502, 2)],
+        }];
+
+        // Act
+        let rows = title_episode_rows(&mapped, &[first, rerun]);
+
+        // Assert
+        let counts: Vec<(u32, Vec<u32>)> = rows
+            .iter()
+            .map(|r| (r.episode_number, r.counts.clone()))
+            .collect();
+        assert_eq!(counts, vec![(1, vec![1]), (2, Vec::new())]);
+    }
+
+    #[test]
+    fn test_channel_cache_staleness() {
+        // Arrange
+        let now = chrono::DateTime::parse_from_rfc3339("2024-04-08T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // Act
+        let never = channel_cache_staleness(None, 168, now);
+        let fresh = channel_cache_staleness(Some("2024-04-02T12:00:00Z"), 168, now);
+        let stale = channel_cache_staleness(Some("2024-04-01T12:00:00Z"), 168, now);
+        let disabled = channel_cache_staleness(None, 0, now);
+
+        // Assert
+        assert_eq!(never.as_deref(), Some("has never been refreshed"));
+        assert!(fresh.is_none());
+        assert_eq!(
+            stale.as_deref(),
+            Some("was last refreshed at 2024-04-01T12:00:00Z (older than 168 hours)")
+        );
+        assert!(disabled.is_none());
+    }
+
+    #[test]
+    fn test_format_history_entry_shows_shift() {
+        // Arrange
+        let entry = ProgramHistoryEntry {
+            pid: 100,
+            changed_at: String::from("2024-04-05T12:00:00Z"),
+            old_st_time: String::from("2024-04-06 23:00:00"),
+            old_ed_time: String::from("2024-04-06 23:30:00"),
+            old_revision: Some(0),
+            new_st_time: String::from("2024-04-06 23:15:00"),
+            new_ed_time: String::from("2024-04-06 23:45:00"),
+            new_revision: None,
+        };
+        let unshifted = ProgramHistoryEntry {
+            new_st_time: entry.old_st_time.clone(),
+            new_ed_time: String::from("2024-04-07 00:00:00"),
+            ..entry.clone()
+        };
+
+        // Act
+        let line = format_history_entry(&entry, SYOBOI_TIMEZONE);
+        let extended = format_history_entry(&unshifted, SYOBOI_TIMEZONE);
+        let utc = format_history_entry(&entry, chrono_tz::UTC);
+
+        // Assert
+        assert_eq!(
+            line,
+            "2024-04-05 21:00:00\t2024-04-06 23:00:00 - 2024-04-06 23:30:00 (rev 0) \
+             -> 2024-04-06 23:15:00 - 2024-04-06 23:45:00 (rev -)\t+15m"
+        );
+        assert!(extended.ends_with("2024-04-07 00:00:00 (rev -)"));
+        assert!(utc.starts_with("2024-04-05 12:00:00\t2024-04-06 14:00:00 UTC - "));
+    }
+
+    #[test]
+    fn test_format_title_details_groups_sections() {
+        // Arrange
+        let mut title = make_cached_title(6309, Some(120_089), None);
+        title.cat = Some(1);
+        title.first_year = Some(2022);
+        title.first_month = Some(4);
+        let credit = |kind, role: &str, name: &str| TitleCredit {
+            tid: 6309,
+            kind,
+            role: role.to_owned(),
+            name: name.to_owned(),
+        };
+        let mut program = make_show_program(100, 7, "2022-04-09 23:00:00", 1);
+        program.st_sub_title = Some(String::from("オペレーション〈梟〉"));
+        program.tmdb_episode_id = Some(501);
+        let details = TitleDetails {
+            title,
+            category: "anime",
+            followed: true,
+            sub_titles: vec![SubTitleEntry {
+                count: 1,
+                sub_title: String::from("オペレーション〈梟〉"),
+            }],
+            credits: vec![
+                credit(CreditKind::Staff, "監督", "古橋一浩"),
+                credit(CreditKind::Cast, "", "種﨑敦美"),
+            ],
+            links: vec![TitleLink {
+                tid: 6309,
+                label: String::from("公式"),
+                url: String::from("https://example.com/"),
+            }],
+            season_ranges: Vec::new(),
+            episodes: vec![TitleEpisodeRow {
+                season_number: Some(1),
+                episode_number: 1,
+                tmdb_episode_id: 501,
+                name: String::from("Operation Strix"),
+                air_date: Some(String::from("2022-04-09")),
+                counts: vec![1],
+            }],
+            airings: vec![ChannelAirings {
+                ch_id: 7,
+                ch_name: Some(String::from("テレビ東京")),
+                programs: vec![program],
+            }],
+        };
+
+        // Act
+        let text = format_title_details(&details);
+
+        // Assert
+        assert_eq!(
+            text,
+            "TID 6309: Title 6309\n\
+             \x20 Category: anime (1)\n\
+             \x20 First aired: 2022-04\n\
+             \x20 Followed: yes\n\
+             \x20 Last update: \n\
+             TMDB:\n\
+             \x20 Series: 120089 (season 1)\n\
+             Sub-titles:\n\
+             \x20 #1 オペレーション〈梟〉\n\
+             Staff:\n\
+             \x20 監督: 古橋一浩\n\
+             Cast:\n\
+             \x20 種﨑敦美\n\
+             Links:\n\
+             \x20 公式: https://example.com/\n\
+             Episodes (TMDB):\n\
+             \x20 S1E01\t2022-04-09\tOperation Strix\t#1\n\
+             Airings:\n\
+             \x20 テレビ東京 (7):\n\
+             \x20   2022-04-09 23:00:00\t#1\tPID 100\tオペレーション〈梟〉\ttmdb 501\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_hidden_columns_skips_unknown_keys() {
+        // Arrange
+        let keys = vec![
+            String::from("tags"),
+            String::from("bogus"),
+            String::from("pid"),
+        ];
+
+        // Act
+        let columns = parse_hidden_columns(&keys);
+
+        // Assert
+        assert_eq!(columns, vec![ProgramColumn::Tags, ProgramColumn::Pid]);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_filter_titles_default() {
@@ -4046,7 +12260,12 @@ mod tests {
         assert_eq!(ct.first_year, Some(2024));
         assert_eq!(ct.first_month, Some(1));
         assert_eq!(ct.keywords, vec!["key1", "key2"]);
-        assert_eq!(ct.sub_titles.as_deref(), Some("*01*EP1\r\n*02*EP2"));
+        let episodes = dtvmgr_core::sync::to_episodes(&src).unwrap();
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(
+            (episodes[1].count, episodes[1].sub_title.as_str()),
+            (2, "EP2")
+        );
         assert_eq!(ct.last_update, "2024-01-01T00:00:00Z");
         // TMDB fields must be None for fresh conversion
         assert!(ct.tmdb_series_id.is_none());
@@ -4094,7 +12313,7 @@ mod tests {
         assert!(ct.first_year.is_none());
         assert!(ct.first_month.is_none());
         assert!(ct.keywords.is_empty());
-        assert!(ct.sub_titles.is_none());
+        assert!(dtvmgr_core::sync::to_episodes(&src).is_none());
     }
 
     fn make_syoboi_program(pid: u32, tid: u32, ch_id: u32) -> SyoboiProgram {
@@ -4117,6 +12336,50 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_followed_program_changes_diffs_snapshot() {
+        // Arrange: followed title 42 has program 100 cached; 43 is not followed
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channels (ch_id, ch_name) VALUES (5, 'TOKYO MX');
+             INSERT INTO titles (tid, title, last_update, followed) VALUES
+                 (42, 'Followed', '2024-01-01', 1),
+                 (43, 'Other', '2024-01-01', 0);
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time) VALUES
+                 (100, 42, 5, '2024-01-15T19:00:00', '2024-01-15T19:30:00');",
+        )
+        .unwrap();
+        let notify = NotifyConfig {
+            webhook_url: Some(String::from("http://127.0.0.1:9/hook")),
+            ..NotifyConfig::default()
+        };
+        let snapshot = snapshot_followed(&conn, &notify).unwrap().unwrap();
+        let programs = vec![
+            make_syoboi_program(100, 42, 5),
+            make_syoboi_program(101, 42, 5),
+            make_syoboi_program(102, 43, 5),
+        ];
+        let titles = vec![make_cached_title(42, None, None)];
+
+        // Act
+        let changes = followed_program_changes(&conn, &snapshot, &programs, &titles);
+
+        // Assert
+        let kinds: Vec<(u32, ChangeKind)> = changes.iter().map(|c| (c.pid, c.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![(100, ChangeKind::TimeChange), (101, ChangeKind::New)]
+        );
+        assert_eq!(changes[0].channel, "TOKYO MX");
+        assert!(
+            snapshot_followed(&conn, &NotifyConfig::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_to_cached_program_maps_all_fields() {
         // Arrange
@@ -4179,6 +12442,91 @@ mod tests {
         assert!(cp.st_sub_title.is_none());
     }
 
+    // ── watchlist ──────────────────────────────────────────────
+
+    fn make_watch_entry(
+        tid: u32,
+        new_program: bool,
+        time_change: bool,
+        finale: bool,
+    ) -> WatchlistEntry {
+        WatchlistEntry {
+            tid,
+            notify_new_program: new_program,
+            notify_time_change: time_change,
+            notify_finale: finale,
+            added_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_detect_watch_events_new_and_time_change() {
+        // Arrange
+        let watchlist = vec![make_watch_entry(42, true, true, false)];
+        let previous = vec![to_cached_program(&make_syoboi_program(1, 42, 5))];
+        let mut moved = make_syoboi_program(1, 42, 5);
+        moved.st_time = "2024-01-15T20:30:00".to_owned();
+        moved.ed_time = "2024-01-15T21:00:00".to_owned();
+        let current = vec![
+            to_cached_program(&moved),
+            to_cached_program(&make_syoboi_program(2, 42, 5)),
+            to_cached_program(&make_syoboi_program(3, 99, 5)),
+        ];
+
+        // Act
+        let events = detect_watch_events(&watchlist, &previous, &current);
+
+        // Assert: unwatched TID 99 is ignored
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, WatchEventKind::TimeChange);
+        assert_eq!(
+            events[0].old_st_time.as_deref(),
+            Some("2024-01-15T20:00:00")
+        );
+        assert_eq!(events[0].st_time, "2024-01-15T20:30:00");
+        assert_eq!(events[1].kind, WatchEventKind::New);
+        assert_eq!(events[1].pid, 2);
+    }
+
+    #[test]
+    fn test_detect_watch_events_respects_prefs() {
+        // Arrange: only finale notifications enabled
+        let watchlist = vec![make_watch_entry(42, false, false, true)];
+        let mut moved = make_syoboi_program(1, 42, 5);
+        moved.st_time = "2024-01-15T21:00:00".to_owned();
+        let previous = vec![to_cached_program(&make_syoboi_program(1, 42, 5))];
+        let current = vec![
+            to_cached_program(&moved),
+            to_cached_program(&make_syoboi_program(2, 42, 5)),
+        ];
+
+        // Act
+        let events = detect_watch_events(&watchlist, &previous, &current);
+
+        // Assert
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_detect_watch_events_finale_reported_once() {
+        // Arrange
+        let watchlist = vec![make_watch_entry(42, false, false, true)];
+        let mut finale = make_syoboi_program(1, 42, 5);
+        finale.flag = Some((ProgramFlags::FINAL | ProgramFlags::NEW).bits());
+        let newly_flagged = vec![to_cached_program(&make_syoboi_program(1, 42, 5))];
+        let already_flagged = vec![to_cached_program(&finale)];
+        let current = vec![to_cached_program(&finale)];
+
+        // Act
+        let first = detect_watch_events(&watchlist, &newly_flagged, &current);
+        let repeat = detect_watch_events(&watchlist, &already_flagged, &current);
+
+        // Assert
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].kind, WatchEventKind::Finale);
+        assert!(repeat.is_empty());
+    }
+
     // ── requires_animation_filter ──────────────────────────────
 
     #[test]
@@ -4263,22 +12611,135 @@ mod tests {
         assert_eq!(result, "タイトル");
     }
 
-    // ── resolve_channel_name ───────────────────────────────────
+    // ── resolve_channel_name ───────────────────────────────────
+
+    #[test]
+    fn test_resolve_channel_name_from_arg() {
+        // Act & Assert
+        assert_eq!(resolve_channel_name(Some("NHK")), Some("NHK".to_owned()));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_resolve_channel_name_none() {
+        // Arrange: clear env var to ensure None fallback
+        unsafe { std::env::remove_var(CHANNEL_ENV_VAR) };
+
+        // Act & Assert
+        assert_eq!(resolve_channel_name(None), None);
+    }
+
+    // ── LogoSource ─────────────────────────────────────────────
+
+    #[test]
+    fn test_logo_source_logo_url() {
+        // Arrange
+        let alias = ChannelAlias {
+            ch_id: 7,
+            mirakurun_service_id: Some(3_273_601_024),
+            epgstation_channel_id: None,
+            channel_type: Some(String::from("GR")),
+            name: None,
+        };
+
+        // Act
+        let mirakurun = LogoSource::Mirakurun("http://mirakurun:40772/").logo_url(&alias);
+        let epgstation = LogoSource::EpgStation("http://epgstation:8888").logo_url(&alias);
+
+        // Assert
+        assert_eq!(
+            mirakurun.as_deref(),
+            Some("http://mirakurun:40772/api/services/3273601024/logo")
+        );
+        assert_eq!(epgstation, None);
+    }
+
+    // ── tmdb images ────────────────────────────────────────────
+
+    fn tmdb_image(file_path: &str, language: Option<&str>) -> TmdbImage {
+        TmdbImage {
+            file_path: file_path.to_owned(),
+            width: 1000,
+            height: 1500,
+            iso_639_1: language.map(str::to_owned),
+            aspect_ratio: 0.667,
+            vote_average: 5.0,
+        }
+    }
+
+    #[test]
+    fn test_parse_count_range() {
+        // Arrange & Act & Assert
+        assert_eq!(
+            parse_count_range("13-24:2").unwrap(),
+            CountRange {
+                first: 13,
+                last: Some(24),
+                season: 2,
+            }
+        );
+        assert_eq!(parse_count_range("25-:3").unwrap().last, None);
+        assert!(parse_count_range("13:2").is_err());
+        assert!(parse_count_range("a-b:1").is_err());
+    }
+
+    #[test]
+    fn test_image_kind_pick_prefers_language_for_posters_and_textless_backdrops() {
+        // Arrange
+        let images = TmdbImagesResponse {
+            id: 1,
+            posters: vec![
+                tmdb_image("/en.jpg", Some("en")),
+                tmdb_image("/none.jpg", None),
+                tmdb_image("/ja.jpg", Some("ja")),
+            ],
+            backdrops: vec![
+                tmdb_image("/b-ja.jpg", Some("ja")),
+                tmdb_image("/b-none.jpg", None),
+            ],
+            logos: Vec::new(),
+        };
+
+        // Act
+        let poster = ImageKind::Poster.pick(&images, "ja").unwrap();
+        let backdrop = ImageKind::Backdrop.pick(&images, "ja").unwrap();
+        let fallback = ImageKind::Poster.pick(&images, "ko").unwrap();
 
-    #[test]
-    fn test_resolve_channel_name_from_arg() {
-        // Act & Assert
-        assert_eq!(resolve_channel_name(Some("NHK")), Some("NHK".to_owned()));
+        // Assert
+        assert_eq!(poster.file_path, "/ja.jpg");
+        assert_eq!(backdrop.file_path, "/b-none.jpg");
+        assert_eq!(fallback.file_path, "/none.jpg");
     }
 
-    #[test]
+    #[tokio::test]
     #[cfg_attr(miri, ignore)]
-    fn test_resolve_channel_name_none() {
-        // Arrange: clear env var to ensure None fallback
-        unsafe { std::env::remove_var(CHANNEL_ENV_VAR) };
+    async fn test_download_title_image_saves_file_and_hash() {
+        // Arrange: serve the image from a local file
+        let server = tempfile::tempdir().unwrap();
+        std::fs::write(server.path().join("p.png"), b"abc").unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let url = format!("file://{}/p.png", server.path().display());
 
-        // Act & Assert
-        assert_eq!(resolve_channel_name(None), None);
+        // Act
+        let record = download_title_image(
+            6309,
+            ImageKind::Poster,
+            &tmdb_image("/p.png", None),
+            &url,
+            out.path(),
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        assert_eq!(record.kind, "poster");
+        assert_eq!(record.file_path, "/p.png");
+        assert!(record.local_path.ends_with("poster.png"));
+        assert_eq!(std::fs::read(&record.local_path).unwrap(), b"abc");
+        assert_eq!(
+            record.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
     }
 
     // ── upsert_filtered_programs ───────────────────────────────
@@ -4307,6 +12768,7 @@ mod tests {
                 ch_id: 20,
                 ch_gid: None,
                 ch_name: "CH20".to_owned(),
+                ..CachedChannel::default()
             }],
         )
         .unwrap();
@@ -4327,6 +12789,7 @@ mod tests {
             &valid_tids,
             &valid_ch_ids,
             &all_fetched_tids,
+            DEFAULT_UPSERT_BATCH_SIZE,
         )
         .unwrap();
 
@@ -4356,6 +12819,7 @@ mod tests {
             &valid_tids,
             &valid_ch_ids,
             &all_fetched_tids,
+            DEFAULT_UPSERT_BATCH_SIZE,
         )
         .unwrap();
 
@@ -4434,6 +12898,30 @@ mod tests {
         assert_eq!(result.unwrap(), vec![1, 2]);
     }
 
+    // ── group_ch_ids ─────────────────────────────────────────
+
+    #[test]
+    fn test_group_ch_ids_filters_by_group_and_ch_ids() {
+        // Arrange
+        let channels: Vec<SyoboiChannel> = serde_json::from_str(
+            r#"[
+                {"ChID": 1, "ChGID": "1", "ChName": "NHK"},
+                {"ChID": 3, "ChGID": "1", "ChName": "Fuji"},
+                {"ChID": 9, "ChGID": "2", "ChName": "BS11"},
+                {"ChID": 20, "ChGID": "", "ChName": "Other"}
+            ]"#,
+        )
+        .unwrap();
+
+        // Act
+        let by_group = group_ch_ids(&channels, &[1, 2], None);
+        let narrowed = group_ch_ids(&channels, &[1], Some(&[3, 9]));
+
+        // Assert
+        assert_eq!(by_group, vec![1, 3, 9]);
+        assert_eq!(narrowed, vec![3]);
+    }
+
     // ── resolve_tmdb_language ────────────────────────────────
 
     #[test]
@@ -4455,6 +12943,509 @@ mod tests {
         assert_eq!(lang, "ja-JP");
     }
 
+    // ── resolve_tmdb_region ──────────────────────────────────
+
+    #[test]
+    fn test_resolve_tmdb_region_cli_arg() {
+        // Act
+        let region = resolve_tmdb_region(Some("US"), None);
+
+        // Assert
+        assert_eq!(region, "US");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_resolve_tmdb_region_fallback_default() {
+        // Act: template default region → "JP"
+        let region = resolve_tmdb_region(None, Some(&PathBuf::from("/nonexistent/path")));
+
+        // Assert
+        assert_eq!(region, "JP");
+    }
+
+    #[test]
+    fn test_resolve_tmdb_locale_completes_bare_language() {
+        // Act
+        let locale = resolve_tmdb_locale(Some("ja"), Some("JP"), None);
+
+        // Assert
+        assert_eq!(locale.language, "ja-JP");
+        assert_eq!(locale.region, "JP");
+    }
+
+    #[test]
+    fn test_tmdb_language_tag() {
+        // Act & Assert
+        assert_eq!(tmdb_language_tag("ja", "JP"), "ja-JP");
+        assert_eq!(tmdb_language_tag("en-US", "JP"), "en-US");
+        assert_eq!(tmdb_language_tag("ja", ""), "ja");
+    }
+
+    // ── jobs ─────────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_daemon_cycle_outcomes_do_not_set_exit_status() {
+        // Arrange: every cycle finds nothing to sync; shut down in the second
+        let cycles = &core::cell::Cell::new(0_u32);
+        let stop = &tokio::sync::Notify::new();
+
+        // Act
+        let ((), marked) = exit::scoped(run_daemon_loop(
+            move || async move {
+                cycles.set(cycles.get().saturating_add(1));
+                exit::mark(ExitStatus::NothingToDo, "no programs to sync");
+                if cycles.get() == 2 {
+                    stop.notify_one();
+                }
+            },
+            Duration::from_millis(1),
+            false,
+            stop.notified(),
+        ))
+        .await;
+
+        // Assert: a clean shutdown exits 0
+        assert_eq!(cycles.get(), 2);
+        assert_eq!(marked, None);
+        assert_eq!(exit::finish(&Ok(()), marked, false), ExitCode::SUCCESS);
+    }
+
+    #[test]
+    fn test_sync_job_payload_into_args() {
+        // Arrange
+        let payload = SyncJobPayload {
+            tids: vec![1, 2],
+            time_since: String::from("2024-01-01 00:00:00"),
+            time_until: String::from("2024-01-02 00:00:00"),
+            ch_ids: None,
+            categories: None,
+        };
+
+        // Act
+        let json = serde_json::to_string(&payload).unwrap();
+        let args = serde_json::from_str::<SyncJobPayload>(&json)
+            .unwrap()
+            .into_args();
+
+        // Assert
+        assert_eq!(args.tids, Some(vec![1, 2]));
+        assert_eq!(args.time_since.as_deref(), Some("2024-01-01 00:00:00"));
+        assert_eq!(args.time_until.as_deref(), Some("2024-01-02 00:00:00"));
+        assert!(args.ch_ids.is_none());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_defer_sync_remainder_queues_job() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let payload = SyncJobPayload {
+            tids: vec![42],
+            time_since: String::from("2024-01-01 00:00:00"),
+            time_until: String::from("2024-01-02 00:00:00"),
+            ch_ids: Some(vec![3]),
+            categories: Some(vec![7]),
+        };
+        let budget = RetryBudget::new(0, Duration::ZERO);
+
+        // Act
+        defer_sync_remainder(&conn, &payload, &budget).unwrap();
+
+        // Assert
+        let jobs = JobQueue::new(&conn).list(None).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].kind, JobKind::Sync);
+        let stored: SyncJobPayload =
+            serde_json::from_str(jobs[0].payload.as_deref().unwrap()).unwrap();
+        assert_eq!(stored, payload);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_advance_sync_cursor_uses_newest_last_update() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let scope = sync_scope(&[7, 1], Some(&[42]));
+        let mut older = make_syoboi_program(1, 42, 1);
+        older.last_update = Some(String::from("2024-04-01 10:00:00"));
+        let mut newer = make_syoboi_program(2, 42, 7);
+        newer.last_update = Some(String::from("2024-04-02 09:00:00"));
+
+        // Act
+        advance_sync_cursor(&conn, &scope, &[]).unwrap();
+        let before = load_sync_cursor(&conn, &scope).unwrap();
+        advance_sync_cursor(&conn, &scope, &[newer, older]).unwrap();
+
+        // Assert
+        assert_eq!(scope, "programs:ch=1,7;tid=42");
+        assert_eq!(scope, sync_scope(&[1, 7], Some(&[42])));
+        assert!(before.is_none());
+        assert_eq!(
+            load_sync_cursor(&conn, &scope).unwrap().as_deref(),
+            Some("2024-04-02 09:00:00")
+        );
+    }
+
+    #[test]
+    fn test_parse_prune_days() {
+        // Act & Assert
+        assert_eq!(
+            parse_prune_days(None).unwrap(),
+            DEFAULT_PRUNE_RETENTION_DAYS
+        );
+        assert_eq!(parse_prune_days(Some(" 30 ")).unwrap(), 30);
+        assert!(parse_prune_days(Some("abc")).is_err());
+        assert!(parse_prune_days(Some("-1")).is_err());
+    }
+
+    #[test]
+    fn test_prune_cutoff() {
+        // Arrange
+        let now = chrono::NaiveDate::from_ymd_opt(2024, 3, 10)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        // Act & Assert
+        assert_eq!(prune_cutoff(now, 10).unwrap(), "2024-02-29 12:00:00");
+        assert_eq!(prune_cutoff(now, 0).unwrap(), "2024-03-10 12:00:00");
+    }
+
+    #[test]
+    fn test_syoboi_local_time_is_jst() {
+        // Arrange
+        let utc = chrono::DateTime::parse_from_rfc3339("2024-01-01T20:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // Act
+        let local = syoboi_local_time(utc);
+
+        // Assert
+        assert_eq!(local.to_string(), "2024-01-02 05:00:00");
+    }
+
+    #[test]
+    fn test_display_broadcast_time_converts_from_jst() {
+        // Arrange
+        let value = "2024-04-06 01:30:00";
+
+        // Act
+        let jst = display_broadcast_time(value, SYOBOI_TIMEZONE);
+        let utc = display_broadcast_time(value, chrono_tz::UTC);
+        let paris = display_broadcast_time(value, chrono_tz::Europe::Paris);
+        let raw = display_broadcast_time("unknown", chrono_tz::UTC);
+
+        // Assert
+        assert_eq!(jst, "2024-04-06 01:30:00");
+        assert_eq!(utc, "2024-04-05 16:30:00 UTC");
+        assert_eq!(paris, "2024-04-05 18:30:00 CEST");
+        assert_eq!(raw, "unknown");
+    }
+
+    // ── format_watch_providers ───────────────────────────────
+
+    #[test]
+    fn test_format_watch_providers_sorted_by_priority() {
+        // Arrange
+        let providers = vec![
+            TmdbWatchProvider {
+                provider_id: 119,
+                provider_name: String::from("Amazon Prime Video"),
+                logo_path: None,
+                display_priority: 7,
+            },
+            TmdbWatchProvider {
+                provider_id: 8,
+                provider_name: String::from("Netflix"),
+                logo_path: None,
+                display_priority: 4,
+            },
+        ];
+
+        // Act
+        let result = format_watch_providers(&providers);
+
+        // Assert
+        assert_eq!(result, "Netflix, Amazon Prime Video");
+    }
+
+    #[test]
+    fn test_format_watch_providers_empty() {
+        // Act & Assert
+        assert_eq!(format_watch_providers(&[]), "-");
+    }
+
+    // ── tmdb find-by-external-id ─────────────────────────────
+
+    #[test]
+    fn test_check_external_mapping() {
+        // Act & Assert
+        assert_eq!(check_external_mapping(None, &[]), ExternalIdCheck::NotFound);
+        assert_eq!(
+            check_external_mapping(Some(1), &[1, 2]),
+            ExternalIdCheck::Matches(1)
+        );
+        assert_eq!(
+            check_external_mapping(Some(3), &[1]),
+            ExternalIdCheck::Mismatch(3)
+        );
+        assert_eq!(
+            check_external_mapping(None, &[7]),
+            ExternalIdCheck::Import(7)
+        );
+        assert_eq!(
+            check_external_mapping(None, &[1, 2]),
+            ExternalIdCheck::Ambiguous
+        );
+    }
+
+    // ── db stats ─────────────────────────────────────────────
+
+    #[test]
+    fn test_format_busiest_hours() {
+        // Act & Assert
+        assert_eq!(
+            format_busiest_hours(&[(1, 12), (23, 4)]),
+            "01:00(12),23:00(4)"
+        );
+        assert_eq!(format_busiest_hours(&[]), "-");
+    }
+
+    // ── report coverage ──────────────────────────────────────
+
+    #[test]
+    fn test_format_coverage_row() {
+        // Arrange
+        let mut row = SeasonCoverage {
+            tid: 100,
+            title: String::from("Title"),
+            season_number: Some(2),
+            episodes: 12,
+            aired: 4,
+            cached: 2,
+            missing: vec![1, 3],
+        };
+
+        // Act & Assert
+        assert_eq!(format_coverage_row(&row), "100\t2\t4\t2\t50%\tTitle\t1,3");
+        row.season_number = None;
+        row.missing.clear();
+        row.cached = 4;
+        assert_eq!(
+            format_coverage_row(&row),
+            "100\tgroup\t4\t4\t100%\tTitle\t-"
+        );
+    }
+
+    // ── library verify ───────────────────────────────────────
+
+    fn make_recording(name: &str, start_at: i64, end_at: i64) -> CachedRecordedItem {
+        CachedRecordedItem {
+            id: 1,
+            channel_id: 1,
+            name: String::from(name),
+            description: None,
+            extended: None,
+            start_at,
+            end_at,
+            is_recording: false,
+            is_encoding: false,
+            is_protected: false,
+            video_resolution: None,
+            video_type: None,
+            drop_cnt: 0,
+            error_cnt: 0,
+            scrambling_cnt: 0,
+            fetched_at: String::from("2024-01-01T00:00:00Z"),
+        }
+    }
+
+    #[test]
+    fn test_syoboi_time_to_unix_ms_is_jst() {
+        // Act & Assert: 2024-01-01 09:00 JST == 2024-01-01 00:00 UTC
+        assert_eq!(
+            syoboi_time_to_unix_ms("2024-01-01 09:00:00"),
+            Some(1_704_067_200_000)
+        );
+        assert_eq!(syoboi_time_to_unix_ms("invalid"), None);
+    }
+
+    #[test]
+    fn test_episode_status_classify() {
+        // Act & Assert
+        assert_eq!(EpisodeStatus::classify(0, 0), EpisodeStatus::Missing);
+        assert_eq!(EpisodeStatus::classify(2, 0), EpisodeStatus::Unhealthy);
+        assert_eq!(EpisodeStatus::classify(2, 1), EpisodeStatus::Ok);
+        assert_eq!(EpisodeStatus::classify(2, 2), EpisodeStatus::Duplicate);
+    }
+
+    #[test]
+    fn test_recording_matches_requires_name_and_overlap() {
+        // Arrange
+        let rec = make_recording("[新]葬送のフリーレン #1", 1_000, 2_000);
+
+        // Act & Assert
+        assert!(recording_matches(
+            &rec,
+            &["葬送のフリーレン"],
+            &[(1_500, 3_000)]
+        ));
+        assert!(!recording_matches(
+            &rec,
+            &["葬送のフリーレン"],
+            &[(2_000, 3_000)]
+        ));
+        assert!(!recording_matches(
+            &rec,
+            &["薬屋のひとりごと"],
+            &[(1_500, 3_000)]
+        ));
+    }
+
+    #[test]
+    fn test_inspect_cached_file_states() {
+        // Arrange
+        let mut vf = CachedVideoFile {
+            id: 10,
+            recorded_id: 1,
+            name: String::from("TS"),
+            filename: Some(String::from("a.ts")),
+            file_type: String::from("ts"),
+            size: 100,
+            file_exists: Some(true),
+            file_checked_at: None,
+        };
+
+        // Act & Assert
+        assert_eq!(inspect_cached_file(&vf), None);
+        vf.size = 0;
+        assert_eq!(inspect_cached_file(&vf), Some("empty"));
+        vf.file_exists = Some(false);
+        assert_eq!(inspect_cached_file(&vf), Some("missing"));
+        vf.file_exists = None;
+        assert_eq!(inspect_cached_file(&vf), Some("unchecked"));
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        // Act
+        let digest = sha256_hex(std::io::Cursor::new(b"abc")).unwrap();
+
+        // Assert
+        assert_eq!(
+            digest,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_render_verify_manifest() {
+        // Arrange
+        let title = make_cached_title(100, None, None);
+        let results = vec![
+            EpisodeVerification {
+                tmdb_episode_id: 501,
+                count: Some(1),
+                status: EpisodeStatus::Ok,
+                files: vec![VerifiedFile {
+                    recorded_id: 7,
+                    filename: String::from("ep1.ts"),
+                    size: 42,
+                    sha256: Some(String::from("abc")),
+                    problem: None,
+                }],
+            },
+            EpisodeVerification {
+                tmdb_episode_id: 502,
+                count: None,
+                status: EpisodeStatus::Missing,
+                files: Vec::new(),
+            },
+        ];
+
+        // Act
+        let manifest = render_verify_manifest(&title, &results, "2024-01-01T00:00:00Z");
+
+        // Assert
+        let lines: Vec<&str> = manifest.lines().collect();
+        assert_eq!(lines[1], "# tid: 100");
+        assert_eq!(lines[5], "501\t1\tok\t7\tep1.ts\t42\tabc\t-");
+        assert_eq!(lines[6], "502\t-\tmissing\t-\t-\t-\t-\t-");
+    }
+
+    // ── search ───────────────────────────────────────────────
+
+    #[test]
+    fn test_match_score_levels() {
+        // Arrange & Act & Assert
+        assert_eq!(match_score("lupin", &[Some("Lupin")]), 3);
+        assert_eq!(match_score("lupin", &[Some("Lupin the Third")]), 2);
+        assert_eq!(match_score("third", &[Some("Lupin the Third")]), 1);
+        assert_eq!(match_score("spy", &[Some("Lupin"), None]), 0);
+        assert_eq!(match_score("lupin", &[Some("the Lupin"), Some("lupin")]), 3);
+        assert_eq!(
+            match_score(
+                "SPY×FAMILY Season 2",
+                &[Some("ＳＰＹ×ＦＡＭＩＬＹ 第2クール")]
+            ),
+            3
+        );
+    }
+
+    #[test]
+    fn test_tmdb_search_hits_skips_mapped_series_and_people() {
+        // Arrange
+        let json = include_str!("../../../fixtures/tmdb/search_multi_lupin.json");
+        let response: dtvmgr_api::tmdb::TmdbSearchMultiResponse =
+            serde_json::from_str(json).unwrap();
+        let mapped = HashSet::from([31572]);
+
+        // Act
+        let hits = tmdb_search_hits(&response.results, "ルパン三世", &mapped);
+
+        // Assert: only the movie remains
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, 916_224);
+        assert_eq!(hits[0].source, SearchSource::Tmdb);
+        assert_eq!(hits[0].score, 2);
+        assert!(hits[0].detail.starts_with("movie "));
+    }
+
+    #[test]
+    fn test_rank_search_hits_orders_by_score_then_source() {
+        // Arrange
+        let mut title = make_cached_title(100, Some(42), None);
+        title.title = String::from("Lupin the Third Part 6");
+        let mut hits = local_search_hits(&[title], "lupin the third");
+        hits.push(SearchHit {
+            source: SearchSource::Tmdb,
+            id: 31572,
+            name: String::from("Lupin the Third"),
+            detail: String::from("tv -"),
+            score: 3,
+        });
+        hits.push(SearchHit {
+            source: SearchSource::Tmdb,
+            id: 1,
+            name: String::from("Lupin the Third Part 6"),
+            detail: String::from("tv -"),
+            score: 2,
+        });
+
+        // Act
+        let ranked = rank_search_hits(hits);
+
+        // Assert
+        assert_eq!(ranked[0].id, 31572);
+        assert_eq!(ranked[1].source, SearchSource::Local);
+        assert_eq!(ranked[1].detail, "tmdb:42");
+        assert_eq!(ranked[2].id, 1);
+    }
+
     // ── build_tui_groups ─────────────────────────────────────
 
     #[test]
@@ -4477,21 +13468,24 @@ mod tests {
                 ch_id: 10,
                 ch_gid: Some(1),
                 ch_name: String::from("Ch10"),
+                ..CachedChannel::default()
             },
             CachedChannel {
                 ch_id: 20,
                 ch_gid: Some(2),
                 ch_name: String::from("Ch20"),
+                ..CachedChannel::default()
             },
             CachedChannel {
                 ch_id: 11,
                 ch_gid: Some(1),
                 ch_name: String::from("Ch11"),
+                ..CachedChannel::default()
             },
         ];
 
         // Act
-        let result = build_tui_groups(&groups, &channels);
+        let result = build_tui_groups(&groups, &channels, &[]);
 
         // Assert
         assert_eq!(result.len(), 2);
@@ -4522,7 +13516,7 @@ mod tests {
         let channels: Vec<CachedChannel> = vec![];
 
         // Act
-        let result = build_tui_groups(&groups, &channels);
+        let result = build_tui_groups(&groups, &channels, &[]);
 
         // Assert: Group B (order=1) should come first
         assert_eq!(result[0].ch_gid, 2);
@@ -4542,16 +13536,18 @@ mod tests {
                 ch_id: 10,
                 ch_gid: Some(1),
                 ch_name: String::from("Ch10"),
+                ..CachedChannel::default()
             },
             CachedChannel {
                 ch_id: 99,
                 ch_gid: None,
                 ch_name: String::from("Ungrouped"),
+                ..CachedChannel::default()
             },
         ];
 
         // Act
-        let result = build_tui_groups(&groups, &channels);
+        let result = build_tui_groups(&groups, &channels, &[]);
 
         // Assert: only grouped channel included
         assert_eq!(result[0].channels.len(), 1);
@@ -4561,7 +13557,7 @@ mod tests {
     #[test]
     fn test_build_tui_groups_empty() {
         // Act
-        let result = build_tui_groups(&[], &[]);
+        let result = build_tui_groups(&[], &[], &[]);
 
         // Assert
         assert!(result.is_empty());
@@ -5378,9 +14374,15 @@ mod tests {
         let all_fetched_tids: HashSet<u32> = HashSet::new();
 
         // Act
-        let (inserted, changed) =
-            upsert_filtered_programs(&conn, &[], &valid_tids, &valid_ch_ids, &all_fetched_tids)
-                .unwrap();
+        let (inserted, changed) = upsert_filtered_programs(
+            &conn,
+            &[],
+            &valid_tids,
+            &valid_ch_ids,
+            &all_fetched_tids,
+            DEFAULT_UPSERT_BATCH_SIZE,
+        )
+        .unwrap();
 
         // Assert
         assert_eq!(inserted, 0);
@@ -5419,6 +14421,7 @@ mod tests {
                 ch_id: 20,
                 ch_gid: None,
                 ch_name: "CH20".to_owned(),
+                ..CachedChannel::default()
             }],
         )
         .unwrap();
@@ -5438,6 +14441,7 @@ mod tests {
             &valid_tids,
             &valid_ch_ids,
             &all_fetched_tids,
+            DEFAULT_UPSERT_BATCH_SIZE,
         )
         .unwrap();
 
@@ -5469,6 +14473,7 @@ mod tests {
                 ch_id: 20,
                 ch_gid: None,
                 ch_name: "CH20".to_owned(),
+                ..CachedChannel::default()
             }],
         )
         .unwrap();
@@ -5485,6 +14490,7 @@ mod tests {
             &valid_tids,
             &valid_ch_ids,
             &all_fetched_tids,
+            DEFAULT_UPSERT_BATCH_SIZE,
         )
         .unwrap();
         assert_eq!(inserted1, 1);
@@ -5497,6 +14503,7 @@ mod tests {
             &valid_tids,
             &valid_ch_ids,
             &all_fetched_tids,
+            DEFAULT_UPSERT_BATCH_SIZE,
         )
         .unwrap();
 
@@ -5655,10 +14662,9 @@ mod tests {
         // Act: no CLI arg, no config file → falls back to default
         let lang = resolve_tmdb_language(None, None);
 
-        // Assert: default is "ja-JP" (from config template) or "en-US" if
-        // config resolution fails entirely. Without a real config, the
-        // resolve_config_path(None) uses the default path which may or may not
-        // exist; the function falls back to "en-US" on error.
+        // Assert: default is "ja-JP" from the config template, or from
+        // DEFAULT_TMDB_LANGUAGE when config resolution fails entirely.
+        // A user config on the default path may override it.
         assert!(!lang.is_empty());
     }
 
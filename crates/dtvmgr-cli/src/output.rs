@@ -0,0 +1,37 @@
+//! Output format selection for query commands.
+
+use std::io::Write as _;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Output format of query command results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Tab-separated tables in the log output.
+    #[default]
+    Table,
+    /// One JSON document on stdout; logs move to stderr.
+    Json,
+}
+
+impl OutputFormat {
+    /// Returns `true` for JSON output.
+    #[must_use]
+    pub const fn is_json(self) -> bool {
+        matches!(self, Self::Json)
+    }
+}
+
+/// Writes `value` to stdout as pretty-printed JSON with a trailing newline.
+///
+/// # Errors
+///
+/// Returns an error if serialization or the write fails.
+pub fn write_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value).context("failed to write JSON output")?;
+    writeln!(stdout).context("failed to write JSON output")?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Terminal progress bars for `db sync`.
+
+use std::sync::Mutex;
+
+use dtvmgr_api::syoboi::{SyncProgress, SyncStage};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Template for stages with a known total (shows ETA).
+const BAR_TEMPLATE: &str =
+    "{prefix:>8} [{bar:30}] {pos}/{len} {msg} ({elapsed_precise}, ETA {eta})";
+
+/// Template for stages with an unknown total (pagination).
+const SPINNER_TEMPLATE: &str = "{spinner} {prefix:>8} {pos} {msg} ({elapsed_precise})";
+
+/// [`SyncProgress`] drawing one progress bar per stage on stderr.
+///
+/// Nothing is drawn when stderr is not a terminal, so logs and JSON output
+/// on stdout stay clean.
+#[derive(Debug, Default)]
+pub struct TerminalProgress {
+    /// Bar of the running stage and the records fetched in it.
+    current: Mutex<Option<(ProgressBar, usize)>>,
+}
+
+impl TerminalProgress {
+    /// Creates a reporter with no active stage.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Returns the progress bar style for a stage.
+fn style(has_total: bool) -> ProgressStyle {
+    let template = if has_total {
+        BAR_TEMPLATE
+    } else {
+        SPINNER_TEMPLATE
+    };
+    ProgressStyle::with_template(template).map_or_else(
+        |_| ProgressStyle::default_bar(),
+        |s| s.progress_chars("=> "),
+    )
+}
+
+impl SyncProgress for TerminalProgress {
+    fn begin(&self, stage: SyncStage, total: Option<u64>) {
+        let bar = ProgressBar::with_draw_target(total, ProgressDrawTarget::stderr());
+        bar.set_style(style(total.is_some()));
+        bar.set_prefix(stage.label());
+        bar.set_message(stage.unit());
+        if let Ok(mut current) = self.current.lock()
+            && let Some((previous, _)) = current.replace((bar, 0))
+        {
+            previous.finish_and_clear();
+        }
+    }
+
+    fn advance(&self, stage: SyncStage, delta: u64, items: usize) {
+        if let Ok(mut current) = self.current.lock()
+            && let Some((bar, fetched)) = current.as_mut()
+        {
+            *fetched = fetched.saturating_add(items);
+            bar.inc(delta);
+            bar.set_message(format!("{}, {fetched} records", stage.unit()));
+        }
+    }
+
+    fn finish(&self, _stage: SyncStage) {
+        if let Ok(mut current) = self.current.lock()
+            && let Some((bar, _)) = current.take()
+        {
+            bar.finish_and_clear();
+        }
+    }
+}
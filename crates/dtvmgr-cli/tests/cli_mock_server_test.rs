@@ -0,0 +1,238 @@
+#![allow(clippy::unwrap_used)]
+#![allow(missing_docs)]
+
+use std::path::Path;
+
+use assert_cmd::cargo_bin_cmd;
+use dtvmgr_server::mock::{MockOptions, serve_mock};
+use predicates::prelude::{PredicateBooleanExt, predicate};
+
+/// Serves the repository fixtures on a free port for the rest of the test
+/// process and returns the server's base URL.
+fn start_mock_server() -> String {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../fixtures");
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            tx.send(listener.local_addr().unwrap()).unwrap();
+            serve_mock(
+                listener,
+                MockOptions::new(fixtures),
+                core::future::pending::<()>(),
+            )
+            .await
+            .unwrap();
+        });
+    });
+    format!("http://{}", rx.recv().unwrap())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_syoboi_titles_against_mock_server() {
+    // Arrange
+    let base = start_mock_server();
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap(), "--output", "json"])
+        .args(["syoboi", "titles", "--tids", "6309"])
+        .env("DTVMGR_SYOBOI_URL", format!("{base}/db.php"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SPY×FAMILY"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_invalid_syoboi_url_override_fails() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert: no silent fallback to the real Syoboi Calendar
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap()])
+        .args(["syoboi", "titles", "--tids", "6309"])
+        .env("DTVMGR_SYOBOI_URL", "not a url")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid DTVMGR_SYOBOI_URL"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_tmdb_tv_details_against_mock_server() {
+    // Arrange
+    let base = start_mock_server();
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap(), "--output", "json"])
+        .args(["tmdb", "tv-details", "--id", "120089"])
+        .env("TMDB_API_TOKEN", "test")
+        .env("DTVMGR_TMDB_URL", format!("{base}/3/"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SPY×FAMILY"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_tmdb_missing_fixture_is_not_found() {
+    // Arrange
+    let base = start_mock_server();
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap()])
+        .args(["tmdb", "tv-details", "--id", "1"])
+        .env("TMDB_API_TOKEN", "test")
+        .env("DTVMGR_TMDB_URL", format!("{base}/3/"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("404"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_tmdb_suggest_and_apply_against_mock_server() {
+    // Arrange
+    let base = start_mock_server();
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO titles (tid, title, first_year, last_update)
+             VALUES (1, 'Lupin', 1971, '2024-01-01 00:00:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+    let file = dir.path().join("suggestions.toml");
+    let file_arg = file.to_str().unwrap();
+    let tmdb_url = format!("{base}/3/");
+
+    // Act & Assert: suggest writes the best candidate without approving it
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "tmdb", "suggest", "--out", file_arg])
+        .env("TMDB_API_TOKEN", "test")
+        .env("DTVMGR_TMDB_URL", &tmdb_url)
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(&file).unwrap();
+    assert!(content.contains("tmdb_id = 31572"), "{content}");
+    assert!(content.contains("approve = false"), "{content}");
+
+    // Act & Assert: apply stores the approved mapping
+    std::fs::write(&file, content.replace("approve = false", "approve = true")).unwrap();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "tmdb", "apply", file_arg])
+        .env("TMDB_API_TOKEN", "test")
+        .env("DTVMGR_TMDB_URL", &tmdb_url)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Applied 1 of 1 approved suggestion(s)",
+        ));
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    let series: Option<u64> = conn
+        .query_row("SELECT tmdb_series_id FROM titles WHERE tid = 1", [], |r| {
+            r.get(0)
+        })
+        .unwrap();
+    assert_eq!(series, Some(31572));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_syoboi_channels_import_validates_against_live_list() {
+    // Arrange
+    let base = start_mock_server();
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("dtvmgr.toml");
+    std::fs::write(&config_path, "[syoboi.channels]\nselected = [3]\n").unwrap();
+    let file = dir.path().join("channels.json");
+    std::fs::write(
+        &file,
+        r#"{"version": 1, "channels": [{"ch_id": 1, "ch_name": "NHK"}, {"ch_id": 99999}]}"#,
+    )
+    .unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap()])
+        .args(["syoboi", "channels", "import", "--dry-run"])
+        .arg(file.to_str().unwrap())
+        .env("DTVMGR_SYOBOI_URL", format!("{base}/db.php"))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Skipped 1 channel(s) not in the channel list")
+                .and(predicate::str::contains("Channel renamed: NHK -> NHK総合"))
+                .and(predicate::str::contains(
+                    "Selection now has 2 channel(s) (1 added, 0 removed) [dry run]",
+                )),
+        );
+    let config = std::fs::read_to_string(&config_path).unwrap();
+    assert_eq!(config, "[syoboi.channels]\nselected = [3]\n");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_bootstrap_syncs_only_updates_after_seed_cursor() {
+    use sha2::Digest as _;
+
+    // Arrange: a seed whose publisher synced up to 2024-04-01
+    let base = start_mock_server();
+    let dir = tempfile::tempdir().unwrap();
+    let seed_dir = dir.path().join("seed");
+    let seed = dtvmgr_db::open_db(Some(&seed_dir)).unwrap();
+    seed.execute_batch(
+        "INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');
+         INSERT INTO sync_state (scope, last_update) VALUES ('programs:ch=1', '2024-04-01 00:00:00');",
+    )
+    .unwrap();
+    drop(seed);
+    let raw = std::fs::read(seed_dir.join("dtvmgr.db")).unwrap();
+    let compressed = ruzstd::encoding::compress_to_vec(
+        raw.as_slice(),
+        ruzstd::encoding::CompressionLevel::Fastest,
+    );
+    let seed_path = dir.path().join("seed.sqlite.zst");
+    std::fs::write(&seed_path, &compressed).unwrap();
+    let sha = format!("{:x}", sha2::Sha256::digest(&compressed));
+    let data = dir.path().join("data");
+    std::fs::create_dir_all(&data).unwrap();
+    std::fs::write(
+        data.join("dtvmgr.toml"),
+        "[syoboi.channels]\nselected = [3]\n",
+    )
+    .unwrap();
+
+    // Act & Assert: the mock has no updated programs, so nothing to do (7)
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", data.to_str().unwrap(), "db", "bootstrap"])
+        .args(["--from-url", &format!("file://{}", seed_path.display())])
+        .args(["--sha256", &sha])
+        .env("DTVMGR_SYOBOI_URL", format!("{base}/db.php"))
+        .assert()
+        .code(7)
+        .stdout(
+            predicate::str::contains("programs updated since 2024-04-01 00:00:00")
+                .and(predicate::str::contains("Time range:").not()),
+        );
+    let conn = dtvmgr_db::open_db(Some(&data)).unwrap();
+    assert_eq!(
+        dtvmgr_db::load_sync_cursor(&conn, "programs:ch=3")
+            .unwrap()
+            .as_deref(),
+        Some("2024-04-01 00:00:00")
+    );
+}
@@ -6,17 +6,1372 @@ use std::path::PathBuf;
 use assert_cmd::cargo_bin_cmd;
 use predicates::prelude::{PredicateBooleanExt, predicate};
 
+// ── global options ─────────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_audit_log_is_global_option() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "sync", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--audit-log <FILE>"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_audit_log_unwritable_path_fails() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("missing").join("audit.jsonl");
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--audit-log", log_path.to_str().unwrap(), "jobs", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to open audit log"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_quiet_prints_only_summary_line() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir.path().to_str().unwrap(),
+            "--quiet",
+            "jobs",
+            "list",
+        ])
+        .assert()
+        .code(0)
+        .stdout("{\"status\":\"success\",\"exit_code\":0}\n");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_config_error_exits_with_config_code() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let dir_arg = dir.path().to_str().unwrap();
+    std::fs::write(dir.path().join("dtvmgr.toml"), "[syoboi\n").unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "config", "check"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("failed to parse"));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "-q", "config", "check"])
+        .assert()
+        .code(3)
+        .stdout(
+            predicate::str::starts_with("{\"status\":\"config_error\",\"exit_code\":3,")
+                .and(predicate::str::contains("failed to parse")),
+        );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_paths_with_dir_override() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("state");
+
+    // Act & Assert: every location lives under --dir, which is created
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", base.to_str().unwrap(), "paths"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("dtvmgr.toml")
+                .and(predicate::str::contains("dtvmgr.db"))
+                .and(predicate::str::contains("state/cache")),
+        );
+    assert!(base.is_dir());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_config_check_reports_unknown_keys_and_channels() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (1, 'ChA');")
+        .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+    let config_path = dir.path().join("dtvmgr.toml");
+
+    // Act & Assert: a typo is only a warning
+    std::fs::write(
+        &config_path,
+        "[syoboi.channels]\nselected = [1]\n\n[tmdb]\nlangauge = \"en\"\n",
+    )
+    .unwrap();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "config", "check"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "unknown key `tmdb.langauge` is ignored",
+        ));
+
+    // Act & Assert: an uncached channel ID fails the check
+    std::fs::write(&config_path, "[syoboi.channels]\nselected = [1, 42]\n").unwrap();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "config", "check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("unknown channel IDs 42"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_export_import_mappings_round_trip() {
+    // Arrange
+    let src = tempfile::tempdir().unwrap();
+    let dst = tempfile::tempdir().unwrap();
+    for dir in [&src, &dst] {
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update) VALUES (1, 'A', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+    }
+    let conn = dtvmgr_db::open_db(Some(&src.path().to_path_buf())).unwrap();
+    conn.execute(
+        "UPDATE titles SET tmdb_series_id = 100, tmdb_season_number = 2",
+        [],
+    )
+    .unwrap();
+    drop(conn);
+    let file = src.path().join("mappings.json");
+    let file_arg = file.to_str().unwrap();
+
+    // Act & Assert: export from the source DB
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            src.path().to_str().unwrap(),
+            "db",
+            "export-mappings",
+        ])
+        .args(["--file", file_arg])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 title mapping(s)"));
+
+    // Act & Assert: import into the other DB
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dst.path().to_str().unwrap(),
+            "db",
+            "import-mappings",
+        ])
+        .arg(file_arg)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Imported 1 title mapping(s), 0 unchanged",
+        ));
+    let conn = dtvmgr_db::open_db(Some(&dst.path().to_path_buf())).unwrap();
+    let series: Option<u64> = conn
+        .query_row("SELECT tmdb_series_id FROM titles WHERE tid = 1", [], |r| {
+            r.get(0)
+        })
+        .unwrap();
+    assert_eq!(series, Some(100));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_syoboi_channels_export_import_round_trip() {
+    // Arrange
+    let src = tempfile::tempdir().unwrap();
+    let dst = tempfile::tempdir().unwrap();
+    for dir in [&src, &dst] {
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channel_groups (ch_gid, ch_group_name, ch_group_order)
+                 VALUES (1, 'テレビ 関東', 100);
+             INSERT INTO channels (ch_id, ch_gid, ch_name)
+                 VALUES (1, 1, 'NHK総合'), (3, 1, 'フジテレビ'), (4, 1, '日本テレビ');",
+        )
+        .unwrap();
+    }
+    std::fs::write(
+        src.path().join("dtvmgr.toml"),
+        "[syoboi.channels]\nselected = [3, 1]\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dst.path().join("dtvmgr.toml"),
+        "[syoboi.channels]\nselected = [4]\n",
+    )
+    .unwrap();
+    let file = src.path().join("channels.toml");
+    let file_arg = file.to_str().unwrap();
+
+    // Act & Assert: export with names and groups
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", src.path().to_str().unwrap()])
+        .args(["syoboi", "channels", "export", "--file", file_arg])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 2 selected channel(s)"));
+    let exported = std::fs::read_to_string(&file).unwrap();
+    assert!(exported.contains("ch_name = \"フジテレビ\""));
+    assert!(exported.contains("ch_group_name = \"テレビ 関東\""));
+
+    // Act & Assert: merge into the other selection
+    let dst_arg = dst.path().to_str().unwrap();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dst_arg, "syoboi", "channels", "import", "--cached"])
+        .arg(file_arg)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Selection now has 3 channel(s) (2 added, 0 removed)",
+        ));
+
+    // Act & Assert: replace drops the channel not in the file
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dst_arg, "--output", "json"])
+        .args(["syoboi", "channels", "import", "--cached", "--replace"])
+        .arg(file_arg)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"removed\": [\n    4\n  ]"));
+    let config = std::fs::read_to_string(dst.path().join("dtvmgr.toml")).unwrap();
+    assert!(config.contains("selected = [1, 3]"), "{config}");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_titles_import_syoboi_requires_user() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir.path().to_str().unwrap(),
+            "titles",
+            "import-syoboi",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "[syoboi.account] user is not configured",
+        ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_paths_config_with_data_dir_env() {
+    // Arrange: config in one directory, data directory from the environment
+    let config_dir = tempfile::tempdir().unwrap();
+    let config_file = config_dir.path().join("custom.toml");
+    std::fs::write(&config_file, "").unwrap();
+    let data_dir = tempfile::tempdir().unwrap();
+    let data_path = std::fs::canonicalize(data_dir.path()).unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.env("DTVMGR_CONFIG", &config_file)
+        .env("DTVMGR_DATA_DIR", data_dir.path())
+        .env_remove("DTVMGR_DIR")
+        .arg("paths")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("custom.toml").and(predicate::str::contains(format!(
+                "database\t{}",
+                data_path.join("dtvmgr.db").display()
+            ))),
+        );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_paths_with_profile_data_dir() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("dtvmgr.toml"),
+        "[profile.home]\ndata_dir = \"home\"\n",
+    )
+    .unwrap();
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert: the profile data_dir replaces --dir for the database
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "--profile", "home", "paths"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("home/dtvmgr.db"));
+    assert!(dir.path().join("home").is_dir());
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "--profile", "vps", "paths"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown profile vps"));
+}
+
 // ── db subcommands ─────────────────────────────────────────────
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn test_db_sync_help() {
+fn test_db_prune_dry_run_then_prune_and_vacuum() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (1, 'ChA');
+         INSERT INTO titles (tid, title, last_update) VALUES (1, 'Old', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time)
+             VALUES (1, 1, 1, '2022-01-01 00:00:00', '2022-01-01 00:30:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "prune", "--before", "2023-01-01"])
+        .args(["--orphan-titles", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would prune 1 programs"));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "prune", "--before", "2023-01-01"])
+        .arg("--orphan-titles")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Pruned 1 programs ended before 2023-01-01 00:00:00 and 1 orphan titles",
+        ));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "vacuum"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Vacuumed database"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_snapshot_create_then_diff() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (1, 'ChA');
+         INSERT INTO titles (tid, title, last_update, followed)
+             VALUES (1, 'Followed', '2024-01-01 00:00:00', 1);
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time)
+             VALUES (1, 1, 1, '2024-01-01 00:00:00', '2024-01-01 00:30:00');",
+    )
+    .unwrap();
+    let dir_arg = dir.path().to_str().unwrap();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "snapshot", "create", "w1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Created snapshot 'w1' with 1 programs",
+        ));
+    conn.execute(
+        "INSERT INTO programs (pid, tid, ch_id, st_time, ed_time)
+             VALUES (2, 1, 1, '2024-01-08 00:00:00', '2024-01-08 00:30:00')",
+        [],
+    )
+    .unwrap();
+    drop(conn);
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "snapshot", "create", "w2"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "diff", "w1", "w2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "w1 -> w2: 1 added, 0 removed, 0 time-shifted",
+        ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_rules_run_lists_matches_as_json() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (1, 'NHK総合'), (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update) VALUES
+             (6309, 'SPY×FAMILY', '2022-01-01 00:00:00'),
+             (6310, 'Other', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, flag) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 2),
+             (101, 6309, 7, '2022-04-10 12:00:00', '2022-04-10 12:30:00', 8),
+             (102, 6310, 1, '2022-04-09 23:00:00', '2022-04-09 23:30:00', NULL);",
+    )
+    .unwrap();
+    drop(conn);
+    let config_path = dir.path().join("dtvmgr.toml");
+    std::fs::write(
+        &config_path,
+        "[[rules]]\nname = \"spy\"\nkeywords = [\"spy\"]\nexclude_flags = 8\n\n\
+         [[rules]]\nname = \"late\"\nstart_time = \"23:00\"\nend_time = \"02:00\"\n",
+    )
+    .unwrap();
+    let report = dir.path().join("matches.json");
+
+    // Act
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args(["--config", config_path.to_str().unwrap()])
+        .args(["--dir", dir.path().to_str().unwrap(), "--output", "json"])
+        .args(["rules", "run", "--time-since", "2022-04-01", "--file"])
+        .arg(&report)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 2);
+    assert_eq!(json[0]["pid"], 100);
+    assert_eq!(json[0]["title"], "SPY×FAMILY");
+    assert_eq!(json[0]["rules"], serde_json::json!(["spy", "late"]));
+    assert_eq!(json[1]["pid"], 102);
+    assert_eq!(json[1]["rules"], serde_json::json!(["late"]));
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(written, json);
+
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args(["--config", config_path.to_str().unwrap()])
+        .args(["--dir", dir.path().to_str().unwrap(), "--output", "json"])
+        .args(["rules", "run", "--time-since", "2022-04-01"])
+        .args(["--sort", "channel", "--limit", "1"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["pid"], 102);
+
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args(["--config", config_path.to_str().unwrap()])
+        .args(["--dir", dir.path().to_str().unwrap(), "--output", "json"])
+        .args(["rules", "run", "--time-since", "2022-04-01"])
+        .args(["--flag-filter", "rerun"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json, serde_json::json!([]));
+
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args(["--config", config_path.to_str().unwrap()])
+        .args(["--dir", dir.path().to_str().unwrap(), "--output", "json"])
+        .args(["rules", "run", "--time-since", "2022-04-01"])
+        .args(["--filter", "ch=1 OR title~spy AND flag~new"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let pids: Vec<_> = json.as_array().unwrap().iter().map(|m| &m["pid"]).collect();
+    assert_eq!(pids, [100, 102]);
+
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--config", config_path.to_str().unwrap()])
+        .args(["--dir", dir.path().to_str().unwrap()])
+        .args(["rules", "run", "--filter", "ch~7"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("`ch` does not support `~`"));
+
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--config", config_path.to_str().unwrap()])
+        .args(["--dir", dir.path().to_str().unwrap()])
+        .args(["rules", "run", "--sort", "title"])
+        .assert()
+        .failure();
+
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--config", config_path.to_str().unwrap()])
+        .args(["--dir", dir.path().to_str().unwrap()])
+        .args(["rules", "run", "--rules", "missing"])
+        .assert()
+        .failure();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_tag_and_note_round_trip() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time)
+             VALUES (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "tag", "100", "Recorded", "skip"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tagged program 100 as 'recorded'"));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "tag", "100", "skip", "--remove"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Removed tag 'skip' from program 100",
+        ));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "tag", "999", "failed"])
+        .assert()
+        .failure();
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "db",
+            "note",
+            "100",
+            "audio dropped at 12:30",
+        ])
+        .assert()
+        .success();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "note", "100"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("audio dropped at 12:30"));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "note", "100", "--clear"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deleted note of program 100"));
+
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    let annotations = dtvmgr_db::load_program_annotations(&conn).unwrap();
+    assert_eq!(
+        annotations.tags.get(&100),
+        Some(&vec![String::from("recorded")])
+    );
+    assert!(annotations.notes.is_empty());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_episode_group_set_show_clear() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO titles (tid, title, last_update, tmdb_series_id)
+             VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00', 120089);",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "episode-group", "6309"])
+        .args(["6310c3c1a4a9b9007e1f4f21"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "episode-group", "6309"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("6310c3c1a4a9b9007e1f4f21"));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "episode-group", "6309", "--clear"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "episode-group", "1", "x"])
+        .assert()
+        .failure();
+
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    let titles = dtvmgr_db::load_titles(&conn).unwrap();
+    assert!(titles.iter().all(|t| t.tmdb_episode_group_id.is_none()));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_show_prints_merged_title_view() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, cat, last_update)
+             VALUES (6309, 'SPY×FAMILY', 1, '2022-01-01 00:00:00');
+         INSERT INTO episodes (tid, count, sub_title) VALUES (6309, 1, 'オペレーション〈梟〉');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count)
+             VALUES (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1);
+         INSERT INTO title_staff (tid, kind, position, role, name) VALUES
+             (6309, 'staff', 0, '監督', '古橋一浩'),
+             (6309, 'cast', 0, 'ロイド・フォージャー', '江口拓也');
+         INSERT INTO title_links (tid, position, label, url)
+             VALUES (6309, 0, '公式', 'https://spy-family.net/');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "show", "6309"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("TID 6309: SPY×FAMILY")
+                .and(predicate::str::contains("Category: anime (1)"))
+                .and(predicate::str::contains("#1 オペレーション〈梟〉"))
+                .and(predicate::str::contains("テレビ東京 (7):"))
+                .and(predicate::str::contains("監督: 古橋一浩"))
+                .and(predicate::str::contains("ロイド・フォージャー: 江口拓也"))
+                .and(predicate::str::contains("公式: https://spy-family.net/")),
+        );
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "--output", "json", "db", "show", "6309"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"kind\": \"cast\""));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "show", "1"])
+        .assert()
+        .failure();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_history_prints_time_changes() {
+    // Arrange: a program delayed by 15 minutes
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count, revision)
+             VALUES (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1, 0);
+         UPDATE programs SET st_time = '2022-04-09 23:15:00', ed_time = '2022-04-09 23:45:00',
+             revision = 1 WHERE pid = 100;",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "history", "100"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("2022-04-09 23:00:00 - 2022-04-09 23:30:00 (rev 0)").and(
+                predicate::str::contains(
+                    "-> 2022-04-09 23:15:00 - 2022-04-09 23:45:00 (rev 1)\t+15m",
+                ),
+            ),
+        );
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "--output", "json", "db", "history", "100"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"new_revision\": 1"));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "history", "1"])
+        .assert()
+        .failure();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_sql_is_read_only_without_write() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');")
+        .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "db",
+            "sql",
+            "SELECT ch_id, ch_name, ch_gid FROM channels",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "ch_id\tch_name\tch_gid\n7\tテレビ東京\tNULL\n",
+        ));
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "--output",
+            "json",
+            "db",
+            "sql",
+            "SELECT ch_name FROM channels",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"テレビ東京\""));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "db", "sql", "DELETE FROM channels"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--write"));
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "db",
+            "sql",
+            "--write",
+            "DELETE FROM channels",
+        ])
+        .assert()
+        .success();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    assert!(dtvmgr_db::load_channels(&conn).unwrap().is_empty());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_report_gaps_lists_missing_and_duplicated_counts() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update) VALUES
+             (6309, 'SPY×FAMILY', '2023-10-01 00:00:00'),
+             (7000, 'Complete', '2023-10-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count, sub_title) VALUES
+             (1, 6309, 7, '2023-10-07 23:00:00', '2023-10-07 23:30:00', 1, 'A'),
+             (2, 6309, 7, '2023-10-21 23:00:00', '2023-10-21 23:30:00', 3, 'C'),
+             (3, 6309, 7, '2023-10-28 23:00:00', '2023-10-28 23:30:00', 3, 'D'),
+             (4, 7000, 7, '2023-10-07 22:00:00', '2023-10-07 22:30:00', 1, 'A');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir", dir_arg, "--output", "json", "report", "gaps", "--all",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"tid\": 6309"))
+        .stdout(predicate::str::contains("\"first\": 1"))
+        .stdout(predicate::str::contains("\"count\": 3"))
+        .stdout(predicate::str::contains("\"tid\": 7000").not());
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "report", "gaps", "--tids", "6309"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "6309\t1\t3\t2\t3(C|D)\tSPY×FAMILY",
+        ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_notify_digest_lists_followed_programs_of_the_day() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update, followed) VALUES
+             (6309, 'SPY×FAMILY', '2023-10-01 00:00:00', 1),
+             (7000, 'Unfollowed', '2023-10-01 00:00:00', 0);
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count, sub_title) VALUES
+             (1, 6309, 7, '2023-10-07 23:00:00', '2023-10-07 23:30:00', 1, '作戦'),
+             (2, 6309, 7, '2023-10-14 23:00:00', '2023-10-14 23:30:00', 2, NULL),
+             (3, 7000, 7, '2023-10-07 22:00:00', '2023-10-07 22:30:00', 1, NULL);",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "notify",
+            "digest",
+            "--date",
+            "2023-10-07",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Subject: dtvmgr: 1 programs on 2023-10-07\n\n23:00 テレビ東京 SPY×FAMILY #1 作戦\n",
+        ))
+        .stdout(predicate::str::contains("Unfollowed").not());
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "notify", "digest", "--date", "2023-10-07"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "[notify.email] host is not configured",
+        ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_sync_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "sync", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--time-since"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_sync_incremental_conflicts_with_time_range() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "sync", "--incremental", "--time-since", "2024-01-01"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_sync_season_conflicts_with_time_range() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "db",
+        "sync",
+        "--season",
+        "2024q2",
+        "--time-since",
+        "2024-01-01",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_syoboi_prog_rejects_invalid_season() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap()])
+        .args(["syoboi", "prog", "--season", "2024q5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid season"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_backfill_rejects_reversed_months() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap()])
+        .args(["db", "backfill", "--from", "2020-12", "--to", "2020-01"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is after --to"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_recompute_empty_db() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap(), "db", "recompute"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Recomputed 0 program(s), 0 updated",
+        ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_recompute_rejects_zero_batch_size() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "recompute", "--batch-size", "0"])
+        .assert()
+        .failure();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_tmdb_match_without_mapped_titles() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act & Assert: exits before building the TMDB client
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap(), "db", "tmdb-match"])
+        .env_remove("TMDB_API_TOKEN")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No titles with a TMDB series mapping",
+        ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_tmdb_auto_match_without_unmapped_titles() {
+    // Arrange: the only title is already mapped
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO titles (tid, title, last_update, tmdb_series_id)
+         VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00', 120089);",
+    )
+    .unwrap();
+    drop(conn);
+
+    // Act & Assert: exits before building the TMDB client
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir.path().to_str().unwrap(), "tmdb", "auto-match"])
+        .env_remove("TMDB_API_TOKEN")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No unmapped titles"));
+}
+
+/// Writes a zstd-compressed seed database with one title and returns its
+/// `file://` URL and SHA-256.
+fn write_seed(dir: &std::path::Path) -> (String, String) {
+    use sha2::Digest as _;
+
+    let seed_dir = dir.join("seed");
+    let conn = dtvmgr_db::open_db(Some(&seed_dir)).unwrap();
+    conn.execute_batch(
+        "INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let raw = std::fs::read(seed_dir.join("dtvmgr.db")).unwrap();
+    let compressed = ruzstd::encoding::compress_to_vec(
+        raw.as_slice(),
+        ruzstd::encoding::CompressionLevel::Fastest,
+    );
+    let path = dir.join("seed.sqlite.zst");
+    std::fs::write(&path, &compressed).unwrap();
+    let sha = format!("{:x}", sha2::Sha256::digest(&compressed));
+    (format!("file://{}", path.display()), sha)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_bootstrap_imports_verified_seed() {
+    // Arrange: checksum comes from the sidecar file
+    let dir = tempfile::tempdir().unwrap();
+    let (url, sha) = write_seed(dir.path());
+    std::fs::write(
+        dir.path().join("seed.sqlite.zst.sha256"),
+        format!("{sha}  seed.sqlite.zst\n"),
+    )
+    .unwrap();
+    let data = dir.path().join("data");
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "db",
+        "bootstrap",
+        "--from-url",
+        &url,
+        "--skip-sync",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("1 title(s)"));
+    assert!(!data.join("dtvmgr.seed").exists());
+
+    // Act & Assert: a second bootstrap refuses a populated database
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "db",
+        "bootstrap",
+        "--from-url",
+        &url,
+        "--skip-sync",
+    ])
+    .assert()
+    .failure();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_bootstrap_rejects_checksum_mismatch() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let (url, _) = write_seed(dir.path());
+    let data = dir.path().join("data");
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "db",
+        "bootstrap",
+        "--from-url",
+        &url,
+        "--sha256",
+        "0000",
+        "--skip-sync",
+    ])
+    .assert()
+    .failure();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_export_ics_writes_filtered_calendar() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    let conn = dtvmgr_db::open_db(Some(&data)).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1),
+             (101, 6309, 7, '2022-04-16 23:00:00', '2022-04-16 23:30:00', 2);",
+    )
+    .unwrap();
+    drop(conn);
+    let file = dir.path().join("out.ics");
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "db",
+        "export",
+        "ics",
+        "--file",
+        file.to_str().unwrap(),
+        "--ch-ids",
+        "7",
+        "--time-until",
+        "2022-04-10",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Exported 1 program(s)"));
+
+    // Assert
+    let ics = std::fs::read_to_string(&file).unwrap();
+    assert!(ics.contains("UID:100@dtvmgr"));
+    assert!(ics.contains("SUMMARY:SPY×FAMILY #1"));
+    assert!(!ics.contains("UID:101@dtvmgr"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_export_ics_filters_by_category() {
+    // Arrange: an anime (Cat=1) and an OVA (Cat=7)
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    let conn = dtvmgr_db::open_db(Some(&data)).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, cat, last_update) VALUES
+             (6309, 'SPY×FAMILY', 1, '2022-01-01 00:00:00'),
+             (6400, 'OVA title', 7, '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1),
+             (200, 6400, 7, '2022-04-10 23:00:00', '2022-04-10 23:30:00', 1);",
+    )
+    .unwrap();
+    drop(conn);
+    let file = dir.path().join("out.ics");
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "db",
+        "export",
+        "ics",
+        "--file",
+        file.to_str().unwrap(),
+        "--category",
+        "ova,movie",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Exported 1 program(s)"));
+
+    // Assert
+    let ics = std::fs::read_to_string(&file).unwrap();
+    assert!(ics.contains("UID:200@dtvmgr"));
+    assert!(!ics.contains("UID:100@dtvmgr"));
+
+    // Act & Assert: unknown labels are rejected by the argument parser
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "db",
+        "export",
+        "ics",
+        "--category",
+        "drama",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("unknown category"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_export_xmltv_writes_channels_and_programmes() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    let conn = dtvmgr_db::open_db(Some(&data)).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京'), (8, 'フジテレビ');
+         INSERT INTO titles (tid, title, cat, last_update) VALUES (6309, 'SPY×FAMILY', 1, '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1);",
+    )
+    .unwrap();
+    drop(conn);
+    let file = dir.path().join("guide.xml");
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "export",
+        "xmltv",
+        "--file",
+        file.to_str().unwrap(),
+        "--ch-ids",
+        "7,8",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Exported 1 program(s) on 1 channel(s)",
+    ));
+
+    // Assert
+    let xml = std::fs::read_to_string(&file).unwrap();
+    assert!(xml.contains("<display-name lang=\"ja\">テレビ東京</display-name>"));
+    assert!(!xml.contains("フジテレビ"));
+    assert!(xml.contains("start=\"20220409230000 +0900\""));
+    assert!(xml.contains("<category lang=\"en\">Animation</category>"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_export_csv_uses_shared_filters() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    let conn = dtvmgr_db::open_db(Some(&data)).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count, sub_title) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1, '作戦, 開始'),
+             (101, 6309, 7, '2022-04-16 23:00:00', '2022-04-16 23:30:00', 2, NULL);",
+    )
+    .unwrap();
+    drop(conn);
+    let file = dir.path().join("out.csv");
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "export",
+        "csv",
+        "--file",
+        file.to_str().unwrap(),
+        "--time-until",
+        "2022-04-10",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Exported 1 program(s)"));
+
+    // Assert
+    let csv = std::fs::read_to_string(&file).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with("100,6309,SPY×FAMILY,7,テレビ東京,1,\"作戦, 開始\","));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_export_csv_applies_filter_expression() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京'), (19, 'TOKYO MX');
+         INSERT INTO titles (tid, title, cat, last_update) VALUES
+             (6309, 'SPY×FAMILY', 1, '2022-01-01 00:00:00'),
+             (6310, 'Other', 8, '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count, flag) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1, 2),
+             (101, 6309, 19, '2022-04-10 23:00:00', '2022-04-10 23:30:00', 1, 10),
+             (102, 6309, 7, '2022-04-16 23:00:00', '2022-04-16 23:30:00', 2, NULL),
+             (103, 6310, 19, '2022-04-10 23:00:00', '2022-04-10 23:30:00', NULL, 2);",
+    )
+    .unwrap();
+    drop(conn);
+    let file = dir.path().join("out.csv");
+
+    // Act
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir.path().to_str().unwrap(),
+            "export",
+            "csv",
+            "--file",
+        ])
+        .arg(&file)
+        .args([
+            "--filter",
+            "cat=anime AND ch IN (7,19) AND flag~new AND NOT st_time=2022-04-09",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 program(s)"));
+
+    // Assert
+    let csv = std::fs::read_to_string(&file).unwrap();
+    assert!(csv.lines().nth(1).unwrap().starts_with("101,6309,"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_export_warns_when_channel_cache_is_stale() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    let conn = dtvmgr_db::open_db(Some(&data)).unwrap();
+    dtvmgr_db::mark_channels_refreshed(&conn, "2000-01-01T00:00:00Z").unwrap();
+    drop(conn);
+    let file = dir.path().join("out.json");
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        data.to_str().unwrap(),
+        "export",
+        "json",
+        "--file",
+        file.to_str().unwrap(),
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Channel cache was last refreshed at 2000-01-01T00:00:00Z",
+    ));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_syoboi_channels_sync_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["syoboi", "channels", "sync", "--help"])
+        .assert()
+        .success();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_export_list_formats() {
     // Arrange & Act & Assert
     let mut cmd = cargo_bin_cmd!("dtvmgr");
-    cmd.args(["db", "sync", "--help"])
+    cmd.args(["export", "list-formats"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("--time-since"));
+        .stdout(predicate::str::contains("ics\tdtvmgr.ics\t"))
+        .stdout(predicate::str::contains("nfo\tnfo\t"))
+        .stdout(predicate::str::contains("json\tdtvmgr.json\t"));
 }
 
 #[test]
@@ -46,6 +1401,597 @@ fn test_db_tmdb_lookup_help() {
         .stdout(predicate::str::contains("--tids"));
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_stats_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "stats", "--help"]).assert().success();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_db_stats_output_json() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update, cat) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00', 1);
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, duration_min) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 30);",
+    )
+    .unwrap();
+    drop(conn);
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    let out = cmd
+        .args([
+            "--dir",
+            dir.path().to_str().unwrap(),
+            "--output",
+            "json",
+            "db",
+            "stats",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert: stdout holds only the JSON document
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["summary"]["total_titles"], 1);
+    assert_eq!(json["summary"]["total_programs"], 1);
+    assert_eq!(json["categories"][0]["titles"], 1);
+    assert_eq!(json["channels"][0]["ch_name"], "テレビ東京");
+    assert_eq!(json["channels"][0]["total_minutes"], 30);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_db_bench_output_json_and_cleans_up() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    let out = cmd
+        .args([
+            "--dir",
+            dir.path().to_str().unwrap(),
+            "--output",
+            "json",
+            "db",
+            "bench",
+            "--programs",
+            "50",
+            "--batch-sizes",
+            "10,0",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert: three passes per batch size, no scratch files or real DB left
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let samples = json.as_array().unwrap();
+    assert_eq!(samples.len(), 6);
+    assert_eq!(samples[1]["pass"], "programs_insert");
+    assert_eq!(samples[1]["rows"], 50);
+    assert_eq!(samples[5]["batch_size"], 0);
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_db_conflicts_reports_watched_overlaps() {
+    // Arrange: watched 6309 overlaps unwatched 6310 and watched 6311
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (1, 'NHK総合'), (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update) VALUES
+             (6309, 'SPY×FAMILY', '2022-01-01 00:00:00'),
+             (6310, 'Unwatched', '2022-01-01 00:00:00'),
+             (6311, 'Other', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00'),
+             (101, 6310, 1, '2022-04-09 23:00:00', '2022-04-09 23:30:00'),
+             (102, 6311, 1, '2022-04-09 23:15:00', '2022-04-09 23:45:00');
+         INSERT INTO watchlist (tid) VALUES (6309), (6311);",
+    )
+    .unwrap();
+    drop(conn);
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    let out = cmd
+        .args([
+            "--dir",
+            dir.path().to_str().unwrap(),
+            "--output",
+            "json",
+            "db",
+            "conflicts",
+            "--time-since",
+            "2022-04-01",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["day"], "2022-04-09");
+    let conflict = &json[0]["conflicts"][0];
+    assert_eq!(conflict["peak"], 2);
+    assert_eq!(conflict["end"], "2022-04-09 23:45:00");
+    assert_eq!(conflict["programs"][0]["pid"], 100);
+    assert_eq!(conflict["programs"][0]["channel"], "テレビ東京");
+    assert_eq!(conflict["programs"][1]["title"], "Other");
+}
+
+// ── titles ─────────────────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_titles_follow_unfollow_and_list_followed() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO titles (tid, title, last_update) VALUES
+             (6309, 'SPY×FAMILY', '2022-01-01 00:00:00'),
+             (6310, 'Other', '2022-01-01 00:00:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "titles", "follow", "--tids", "6309,6310"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Followed 2 titles"));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "titles", "unfollow", "--tids", "6310"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unfollowed 1 titles"));
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "--output",
+            "json",
+            "titles",
+            "list-followed",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["tid"], 6309);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_titles_merge_relinks_programs_and_records_alias() {
+    // Arrange: 6309 was re-created by Syoboi as 7000
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update, tmdb_series_id, tmdb_season_number)
+             VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00', 120089, 1);
+         INSERT INTO titles (tid, title, last_update)
+             VALUES (7000, 'SPY×FAMILY', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "titles", "merge", "6309", "7000"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merged title 6309 into 7000: 1 programs re-linked, TMDB mapping copied",
+        ));
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "--output", "json", "titles", "aliases"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json[0]["old_tid"], 6309);
+    assert_eq!(json[0]["new_tid"], 7000);
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    let (tid, series): (u32, Option<u64>) = conn
+        .query_row(
+            "SELECT p.tid, t.tmdb_series_id FROM programs p JOIN titles t USING (tid)
+             WHERE p.pid = 100",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((tid, series), (7000, Some(120_089)));
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "titles", "merge", "7000", "9999"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("title 9999 is not cached"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_titles_split_seasons_detects_applies_and_overrides() {
+    // Arrange: counts 1-2 in spring 2022, 3 after a half-year break
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, first_year, last_update)
+             VALUES (6309, 'SPY×FAMILY', 2022, '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1),
+             (101, 6309, 7, '2022-04-16 23:00:00', '2022-04-16 23:30:00', 2),
+             (102, 6309, 7, '2023-10-07 23:00:00', '2023-10-07 23:30:00', 3);",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "titles", "split-seasons", "--apply"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("6309\t1-2:1,3-:2\t-"));
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "titles",
+            "split-seasons",
+            "--tid",
+            "6309",
+            "--range",
+            "1-12:1",
+            "--range",
+            "13-:2",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Saved season ranges of TID 6309: 1-12:1,13-:2",
+        ));
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "--output",
+            "json",
+            "titles",
+            "split-seasons",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json[0]["detected"].as_array().unwrap().len(), 2);
+    assert_eq!(json[0]["stored"][1]["first_count"], 13);
+    assert!(json[0]["stored"][1]["last_count"].is_null());
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "titles",
+            "split-seasons",
+            "--tid",
+            "6309",
+            "--range",
+            "1-12:1",
+            "--range",
+            "10-:2",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("overlaps the previous range"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_readonly_allows_reads_and_rejects_writes() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert
+    cargo_bin_cmd!("dtvmgr")
+        .args(["--dir", dir_arg, "--db-readonly", "titles", "list-followed"])
+        .assert()
+        .success();
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "--db-readonly",
+            "titles",
+            "follow",
+            "--tids",
+            "6309",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("readonly database"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_sync_followed_only_conflicts_with_tids() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "sync", "--followed-only", "--tids", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_sync_resume_rejects_succeeded_run() {
+    // Arrange
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO sync_runs (id, started_at, finished_at, status)
+         VALUES (3, '2024-04-01T00:00:00Z', '2024-04-01T00:01:00Z', 'succeeded');",
+    )
+    .unwrap();
+    drop(conn);
+
+    // Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        dir.path().to_str().unwrap(),
+        "db",
+        "sync",
+        "--resume",
+        "3",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("sync run #3 already succeeded"));
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "sync", "--resume", "3", "--tids", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+// ── channels ───────────────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::indexing_slicing)]
+fn test_channels_map_import_from_service_list_file() {
+    // Arrange: a Mirakurun-style service list matched by name
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');")
+        .unwrap();
+    drop(conn);
+    let services = dir.path().join("services.json");
+    std::fs::write(
+        &services,
+        r#"[{"id": 3273601024, "name": "テレビ東京", "channel": {"type": "GR"}},
+            {"id": 400101, "name": "NHK BS", "channel": {"type": "BS"}}]"#,
+    )
+    .unwrap();
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            dir_arg,
+            "channels",
+            "map",
+            "import",
+            services.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Mapped 1 channel(s) (1 changed), 1 unmatched",
+        ));
+    let out = cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir", dir_arg, "--output", "json", "channels", "map", "list",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    // Assert
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["ch_id"], 7);
+    assert_eq!(json[0]["mirakurun_service_id"], 3_273_601_024_u64);
+    assert_eq!(json[0]["channel_type"], "GR");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_channels_fetch_logos_saves_files_and_exports_icon() {
+    // Arrange: a local directory stands in for the Mirakurun server
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    let conn = dtvmgr_db::open_db(Some(&data)).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京'), (8, 'フジテレビ');
+         INSERT INTO channel_aliases (ch_id, mirakurun_service_id) VALUES (7, 3273601024);
+         INSERT INTO channel_aliases (ch_id, epgstation_channel_id) VALUES (8, 3273801032);
+         INSERT INTO titles (tid, title, last_update) VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00');
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00');",
+    )
+    .unwrap();
+    drop(conn);
+    let server = dir.path().join("mirakurun");
+    let logo_src = server.join("api/services/3273601024");
+    std::fs::create_dir_all(&logo_src).unwrap();
+    std::fs::write(logo_src.join("logo"), b"\x89PNG").unwrap();
+    let guide = dir.path().join("guide.xml");
+
+    // Act
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            data.to_str().unwrap(),
+            "channels",
+            "fetch-logos",
+            "--mirakurun",
+            server.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fetched 1 logo(s)"))
+        .stdout(predicate::str::contains("(1 without ID, 0 failed)"));
+    cargo_bin_cmd!("dtvmgr")
+        .args([
+            "--dir",
+            data.to_str().unwrap(),
+            "export",
+            "xmltv",
+            "--file",
+            guide.to_str().unwrap(),
+            "--ch-ids",
+            "7",
+        ])
+        .assert()
+        .success();
+
+    // Assert
+    assert_eq!(std::fs::read(data.join("logos/7.png")).unwrap(), b"\x89PNG");
+    let xml = std::fs::read_to_string(&guide).unwrap();
+    assert!(xml.contains("/api/services/3273601024/logo\" />"));
+}
+
+// ── export ─────────────────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[allow(clippy::literal_string_with_formatting_args)]
+fn test_export_nfo_offline_writes_show_and_episode_files() {
+    // Arrange: episode 1 airs twice; the rerun must not add a file
+    let dir = tempfile::tempdir().unwrap();
+    let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+    conn.execute_batch(
+        "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+         INSERT INTO titles (tid, title, last_update, first_year, tmdb_series_id, tmdb_season_number)
+             VALUES (6309, 'SPY×FAMILY', '2022-01-01 00:00:00', 2022, 120089, 1);
+         INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count, sub_title) VALUES
+             (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1, 'オペレーション〈梟〉'),
+             (101, 6309, 7, '2022-04-16 23:00:00', '2022-04-16 23:30:00', 2, NULL),
+             (102, 6309, 7, '2022-05-01 10:00:00', '2022-05-01 10:30:00', 1, NULL);",
+    )
+    .unwrap();
+    drop(conn);
+    let out_dir = dir.path().join("nfo");
+
+    // Act
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args([
+        "--dir",
+        dir.path().to_str().unwrap(),
+        "export",
+        "nfo",
+        "--offline",
+        "--out-dir",
+        out_dir.to_str().unwrap(),
+        "--episode-file",
+        "S{season:02}E{episode:02}.nfo",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Wrote 2 episode NFO(s) for 1 title(s)",
+    ));
+
+    // Assert
+    let show = std::fs::read_to_string(out_dir.join("SPY×FAMILY/tvshow.nfo")).unwrap();
+    assert!(show.contains("<title>SPY×FAMILY</title>"));
+    assert!(show.contains(r#"<uniqueid type="tmdb" default="true">120089</uniqueid>"#));
+    let ep1 = std::fs::read_to_string(out_dir.join("SPY×FAMILY/Season 01/S01E01.nfo")).unwrap();
+    assert!(ep1.contains("<title>オペレーション〈梟〉</title>"));
+    assert!(ep1.contains("<aired>2022-04-09</aired>"));
+    assert!(out_dir.join("SPY×FAMILY/Season 01/S01E02.nfo").exists());
+}
+
+// ── daemon ─────────────────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_daemon_once_records_failed_run() {
+    // Arrange: no channels configured, so the sync fails before any request
+    let dir = tempfile::tempdir().unwrap();
+    let dir_arg = dir.path().to_str().unwrap();
+
+    // Act & Assert: the daemon survives the failure and records it
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir_arg, "daemon", "--once"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Sync run #1 failed"));
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["--dir", dir_arg, "daemon", "--once"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Last sync run #1: failed"));
+}
+
 // ── tmdb subcommands ───────────────────────────────────────────
 
 #[test]
@@ -92,6 +2038,56 @@ fn test_tmdb_tv_season_help() {
         .stdout(predicate::str::contains("--season"));
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_tmdb_watch_providers_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["tmdb", "watch-providers", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--region"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_tmdb_find_by_external_id_rejects_unknown_id_format() {
+    // Arrange & Act & Assert: fails before building the TMDB client
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["tmdb", "find-by-external-id", "--id", "abc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --source"));
+}
+
+// ── search ─────────────────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_search_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["search", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--tmdb"))
+        .stdout(predicate::str::contains("<QUERY>"));
+}
+
+// ── watchlist ──────────────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_watchlist_add_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["watchlist", "add", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--notify"))
+        .stdout(predicate::str::contains("time-change"));
+}
+
 // ── jlse subcommands ───────────────────────────────────────────
 
 #[test]
@@ -252,3 +2248,69 @@ fn test_init_already_up_to_date() {
         .success()
         .stdout(predicate::str::contains("Config already up to date"));
 }
+
+// ── library subcommands ────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_library_verify_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["library", "verify", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--tid"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_library_verify_hash_requires_root() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["library", "verify", "--tid", "1", "--hash"])
+        .assert()
+        .failure();
+}
+
+// ── jobs subcommands ───────────────────────────────────────────
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_jobs_list_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["jobs", "list", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--state"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_jobs_add_rejects_unknown_kind() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["jobs", "add", "bogus"]).assert().failure();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_jobs_run_now_help() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["jobs", "run-now", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("<ID>"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_db_grid_rejects_invalid_date() {
+    // Arrange & Act & Assert
+    let mut cmd = cargo_bin_cmd!("dtvmgr");
+    cmd.args(["db", "grid", "--date", "2024-13-01"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--date"));
+}
@@ -0,0 +1,681 @@
+//! Heuristic TMDB series matching for unmapped titles.
+//!
+//! A title is searched by its English title, its Japanese title, and its
+//! romanized reading (`TitleYomi`). TV candidates are scored by name
+//! similarity, first-air year, and Japanese origin. The best candidate is
+//! accepted only when it clears the threshold and leads the runner-up by
+//! [`AMBIGUITY_MARGIN`]; everything else is left for manual review.
+//!
+//! [`SuggestionsFile`] carries the ranked candidates of many titles through
+//! a human review (`tmdb suggest` / `tmdb apply`).
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use dtvmgr_api::tmdb::{
+    LocalTmdbApi, SearchMultiParams, TmdbMultiSearchResult, TmdbTvSearchResult,
+};
+use dtvmgr_db::titles::CachedTitle;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::normalize::normalize_title;
+
+/// Default minimum score for a match to be applied automatically.
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// Default allowed difference between `FirstYear` and the first air year.
+pub const DEFAULT_YEAR_TOLERANCE: u32 = 1;
+
+/// Minimum lead of the best score over the runner-up.
+pub const AMBIGUITY_MARGIN: f64 = 0.1;
+
+/// Weight of the name similarity in the score.
+const NAME_WEIGHT: f64 = 0.6;
+
+/// Weight of the first-air year check in the score.
+const YEAR_WEIGHT: f64 = 0.25;
+
+/// Weight of the origin country (JP) check in the score.
+const COUNTRY_WEIGHT: f64 = 0.15;
+
+/// Candidates kept per title.
+const MAX_CANDIDATES: usize = 5;
+
+/// Scoring parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoMatchOptions {
+    /// Minimum score to accept the best candidate.
+    pub threshold: f64,
+    /// Allowed year difference for the full year score.
+    pub year_tolerance: u32,
+}
+
+impl Default for AutoMatchOptions {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            year_tolerance: DEFAULT_YEAR_TOLERANCE,
+        }
+    }
+}
+
+/// A scored TMDB TV series.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    /// TMDB series ID.
+    pub tmdb_id: u64,
+    /// Localized name.
+    pub name: String,
+    /// Original name.
+    pub original_name: String,
+    /// First air date (YYYY-MM-DD).
+    pub first_air_date: Option<String>,
+    /// Score in `0.0..=1.0`.
+    pub score: f64,
+}
+
+/// Outcome of matching one title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// The best candidate clears the threshold and the margin.
+    Matched,
+    /// Candidates exist but none is confident enough.
+    Ambiguous,
+    /// No TV candidate was found.
+    NotFound,
+}
+
+/// Ranked candidates and decision for one title.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AutoMatch {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Syoboi title.
+    pub title: String,
+    /// Outcome.
+    pub decision: Decision,
+    /// Candidates, best first.
+    pub candidates: Vec<Candidate>,
+}
+
+impl AutoMatch {
+    /// Returns the accepted candidate of a [`Decision::Matched`] title.
+    #[must_use]
+    pub fn matched(&self) -> Option<&Candidate> {
+        if self.decision == Decision::Matched {
+            self.candidates.first()
+        } else {
+            None
+        }
+    }
+}
+
+/// Reviewable candidates of one title in a [`SuggestionsFile`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Syoboi title (for readability).
+    pub title: String,
+    /// Outcome of the scoring.
+    pub decision: Decision,
+    /// Whether `tmdb_id` should be applied.
+    #[serde(default)]
+    pub approve: bool,
+    /// TMDB series ID to apply. Prefilled with the best candidate; 0 when
+    /// there is none.
+    #[serde(default)]
+    pub tmdb_id: u64,
+    /// Candidates, best first.
+    #[serde(default)]
+    pub candidates: Vec<Candidate>,
+}
+
+impl Suggestion {
+    /// Builds a suggestion from a ranked title. Scores are rounded to three
+    /// decimals; `approve_matched` pre-approves [`Decision::Matched`] titles.
+    #[must_use]
+    pub fn from_match(result: AutoMatch, approve_matched: bool) -> Self {
+        let candidates: Vec<Candidate> = result
+            .candidates
+            .into_iter()
+            .map(|c| Candidate {
+                score: (c.score * 1000.0).round() / 1000.0,
+                ..c
+            })
+            .collect();
+        Self {
+            tid: result.tid,
+            title: result.title,
+            decision: result.decision,
+            approve: approve_matched && result.decision == Decision::Matched,
+            tmdb_id: candidates.first().map_or(0, |c| c.tmdb_id),
+            candidates,
+        }
+    }
+
+    /// Returns the TMDB series ID to apply, if approved and set.
+    #[must_use]
+    pub const fn approved(&self) -> Option<u64> {
+        if self.approve && self.tmdb_id != 0 {
+            Some(self.tmdb_id)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the candidate chosen by `tmdb_id`, if it is one of the
+    /// listed candidates.
+    #[must_use]
+    pub fn chosen(&self) -> Option<&Candidate> {
+        self.candidates.iter().find(|c| c.tmdb_id == self.tmdb_id)
+    }
+}
+
+/// Suggestions file written by `tmdb suggest` and read by `tmdb apply`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SuggestionsFile {
+    /// One entry per title.
+    #[serde(default)]
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Searches TMDB with every query of `title` and ranks the TV results.
+///
+/// # Errors
+///
+/// Returns an error if a search request fails.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, fields(tid = title.tid), err(level = "warn"))]
+pub async fn auto_match_title<A: LocalTmdbApi>(
+    api: &A,
+    title: &CachedTitle,
+    language: &str,
+    options: AutoMatchOptions,
+) -> Result<AutoMatch> {
+    let queries = search_queries(title);
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    for query in &queries {
+        let params = SearchMultiParams::new(query).language(language);
+        let response = api
+            .search_multi(&params)
+            .await
+            .with_context(|| format!("TMDB search failed for {query}"))?;
+        for result in response.results {
+            if let TmdbMultiSearchResult::Tv(tv) = result
+                && seen.insert(tv.id)
+            {
+                results.push(tv);
+            }
+        }
+    }
+    Ok(rank_candidates(title, &queries, &results, options))
+}
+
+/// Scores `results` against `title` and decides whether the best one is
+/// confident enough.
+#[must_use]
+pub fn rank_candidates(
+    title: &CachedTitle,
+    queries: &[String],
+    results: &[TmdbTvSearchResult],
+    options: AutoMatchOptions,
+) -> AutoMatch {
+    let mut candidates: Vec<Candidate> = results
+        .iter()
+        .map(|tv| Candidate {
+            tmdb_id: tv.id,
+            name: tv.name.clone(),
+            original_name: tv.original_name.clone(),
+            first_air_date: tv.first_air_date.clone(),
+            score: score_candidate(queries, title.first_year, tv, options.year_tolerance),
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(MAX_CANDIDATES);
+    let decision = decide(&candidates, options.threshold);
+    AutoMatch {
+        tid: title.tid,
+        title: title.title.clone(),
+        decision,
+        candidates,
+    }
+}
+
+/// Returns the search queries for `title`: English title, Japanese title,
+/// and romanized reading, without duplicates.
+#[must_use]
+pub fn search_queries(title: &CachedTitle) -> Vec<String> {
+    let romanized = title.title_yomi.as_deref().map(romanize_kana);
+    let mut queries: Vec<String> = Vec::new();
+    for query in [title.title_en.clone(), Some(title.title.clone()), romanized]
+        .into_iter()
+        .flatten()
+    {
+        let query = query.trim().to_owned();
+        if !query.is_empty() && !queries.contains(&query) {
+            queries.push(query);
+        }
+    }
+    queries
+}
+
+/// Scores a TV result: best name similarity over all query/name pairs,
+/// first-air year within `year_tolerance`, and JP origin.
+///
+/// An unknown year on either side scores half.
+#[must_use]
+pub fn score_candidate(
+    queries: &[String],
+    first_year: Option<u32>,
+    tv: &TmdbTvSearchResult,
+    year_tolerance: u32,
+) -> f64 {
+    let name = queries
+        .iter()
+        .flat_map(|q| [similarity(q, &tv.name), similarity(q, &tv.original_name)])
+        .fold(0.0_f64, f64::max);
+    let air_year = tv
+        .first_air_date
+        .as_deref()
+        .and_then(|d| d.get(..4))
+        .and_then(|y| y.parse::<u32>().ok());
+    let year = match (first_year, air_year) {
+        (Some(a), Some(b)) if a.abs_diff(b) <= year_tolerance => 1.0,
+        (Some(_), Some(_)) => 0.0,
+        _ => 0.5,
+    };
+    let country = if tv.origin_country.iter().any(|c| c == "JP") {
+        1.0
+    } else {
+        0.0
+    };
+    NAME_WEIGHT.mul_add(name, YEAR_WEIGHT.mul_add(year, COUNTRY_WEIGHT * country))
+}
+
+/// Decides on candidates sorted best first.
+#[must_use]
+pub fn decide(candidates: &[Candidate], threshold: f64) -> Decision {
+    let Some(best) = candidates.first() else {
+        return Decision::NotFound;
+    };
+    let leads = candidates
+        .get(1)
+        .is_none_or(|second| best.score - second.score >= AMBIGUITY_MARGIN);
+    if best.score >= threshold && leads {
+        Decision::Matched
+    } else {
+        Decision::Ambiguous
+    }
+}
+
+/// Returns the Dice coefficient of the character bigrams of `a` and `b`
+/// after [`normalize_title`].
+#[must_use]
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = normalize_title(a).chars().collect();
+    let b: Vec<char> = normalize_title(b).chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    let bigrams = |s: &[char]| -> Vec<(char, char)> {
+        s.windows(2)
+            .filter_map(|w| Some((*w.first()?, *w.get(1)?)))
+            .collect()
+    };
+    let (a, b) = (bigrams(&a), bigrams(&b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let mut rest = b.clone();
+    let mut common: usize = 0;
+    for pair in &a {
+        if let Some(pos) = rest.iter().position(|p| p == pair) {
+            rest.swap_remove(pos);
+            common = common.saturating_add(1);
+        }
+    }
+    #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
+    let score = common.saturating_mul(2) as f64 / a.len().saturating_add(b.len()) as f64;
+    score
+}
+
+/// Romanizes hiragana / katakana (Hepburn, without long-vowel marks).
+/// Other characters are kept as is.
+#[must_use]
+pub fn romanize_kana(kana: &str) -> String {
+    let chars: Vec<char> = kana.nfkc().map(to_hiragana).collect();
+    let mut out = String::new();
+    let mut double_next = false;
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        i = i.saturating_add(1);
+        if c == 'っ' {
+            double_next = true;
+            continue;
+        }
+        let Some(base) = kana_syllable(c) else {
+            match c {
+                'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' => {
+                    // ふぁ -> fa, てぃ -> ti: replace the previous vowel.
+                    if out.ends_with(['a', 'i', 'u', 'e', 'o']) {
+                        out.pop();
+                    }
+                    out.push(small_vowel(c));
+                }
+                'ー' => {}
+                _ => out.extend(c.to_lowercase()),
+            }
+            double_next = false;
+            continue;
+        };
+        let mut syllable = base.to_owned();
+        if let Some(&next) = chars.get(i)
+            && let Some(vowel) = match next {
+                'ゃ' => Some('a'),
+                'ゅ' => Some('u'),
+                'ょ' => Some('o'),
+                _ => None,
+            }
+            && let Some(stem) = syllable.strip_suffix('i')
+        {
+            i = i.saturating_add(1);
+            syllable = if matches!(stem, "sh" | "ch" | "j") {
+                format!("{stem}{vowel}")
+            } else {
+                format!("{stem}y{vowel}")
+            };
+        }
+        if double_next {
+            if syllable.starts_with("ch") {
+                out.push('t');
+            } else if let Some(first) = syllable.chars().next()
+                && !"aiueon".contains(first)
+            {
+                out.push(first);
+            }
+            double_next = false;
+        }
+        out.push_str(&syllable);
+    }
+    out
+}
+
+/// Maps katakana to hiragana; other characters are returned unchanged.
+fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' => char::from_u32(u32::from(c).saturating_sub(0x60)).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Returns the vowel of a small vowel kana.
+const fn small_vowel(c: char) -> char {
+    match c {
+        'ぁ' => 'a',
+        'ぃ' => 'i',
+        'ぅ' => 'u',
+        'ぇ' => 'e',
+        _ => 'o',
+    }
+}
+
+/// Returns the Hepburn romanization of a single hiragana.
+#[allow(clippy::too_many_lines)]
+const fn kana_syllable(c: char) -> Option<&'static str> {
+    Some(match c {
+        'あ' => "a",
+        'い' => "i",
+        'う' => "u",
+        'え' => "e",
+        'お' => "o",
+        'か' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' => "ke",
+        'こ' => "ko",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'さ' => "sa",
+        'し' => "shi",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'ざ' => "za",
+        'じ' | 'ぢ' => "ji",
+        'ず' | 'づ' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'た' => "ta",
+        'ち' => "chi",
+        'つ' => "tsu",
+        'て' => "te",
+        'と' => "to",
+        'だ' => "da",
+        'で' => "de",
+        'ど' => "do",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'ふ' => "fu",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' | 'ゃ' => "ya",
+        'ゆ' | 'ゅ' => "yu",
+        'よ' | 'ょ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' => "wa",
+        'を' => "wo",
+        'ん' => "n",
+        'ゔ' => "vu",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn title(first_year: Option<u32>) -> CachedTitle {
+        CachedTitle {
+            tid: 6309,
+            tmdb_series_id: None,
+            tmdb_season_number: None,
+            tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
+            title: String::from("SPY×FAMILY"),
+            short_title: None,
+            title_yomi: Some(String::from("すぱいふぁみりー")),
+            title_en: Some(String::from("SPY x FAMILY")),
+            cat: Some(1),
+            title_flag: None,
+            first_year,
+            first_month: None,
+            keywords: Vec::new(),
+            last_update: String::from("2022-04-01 00:00:00"),
+            tmdb_original_name: None,
+            tmdb_name: None,
+            tmdb_alt_titles: None,
+            tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
+        }
+    }
+
+    fn tv(id: u64, name: &str, date: &str, country: &str) -> TmdbTvSearchResult {
+        TmdbTvSearchResult {
+            id,
+            name: name.to_owned(),
+            original_name: name.to_owned(),
+            original_language: String::from("ja"),
+            origin_country: vec![country.to_owned()],
+            first_air_date: Some(date.to_owned()),
+            overview: None,
+            popularity: 0.0,
+            vote_average: 0.0,
+            vote_count: 0,
+            genre_ids: Vec::new(),
+            adult: false,
+            poster_path: None,
+            backdrop_path: None,
+        }
+    }
+
+    #[test]
+    fn test_romanize_kana_hepburn() {
+        // Arrange & Act & Assert
+        assert_eq!(romanize_kana("すぱいふぁみりー"), "supaifamiri");
+        assert_eq!(romanize_kana("しんげきのきょじん"), "shingekinokyojin");
+        assert_eq!(romanize_kana("ぼっち・ざ・ろっく"), "botchi・za・rokku");
+        assert_eq!(romanize_kana("ジュジュツカイセン"), "jujutsukaisen");
+    }
+
+    #[test]
+    fn test_search_queries_dedupes_and_romanizes() {
+        // Arrange
+        let mut t = title(Some(2022));
+        t.title_en = Some(String::from("SPY×FAMILY"));
+
+        // Act
+        let queries = search_queries(&t);
+
+        // Assert
+        assert_eq!(queries, vec!["SPY×FAMILY", "supaifamiri"]);
+    }
+
+    #[test]
+    fn test_similarity_normalizes_width_and_symbols() {
+        // Arrange & Act & Assert
+        assert!((similarity("ＳＰＹ×ＦＡＭＩＬＹ", "spy family") - 1.0).abs() < f64::EPSILON);
+        assert!(similarity("SPY×FAMILY", "SPY x FAMILY") > 0.8);
+        assert!(similarity("SPY×FAMILY", "Dragon Ball") < 0.2);
+        assert!(similarity("", "x").abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_rank_candidates_matches_confident_result() {
+        // Arrange
+        let t = title(Some(2022));
+        let queries = search_queries(&t);
+        let results = vec![
+            tv(1, "Spy Kids", "2001-03-30", "US"),
+            tv(120_089, "SPY x FAMILY", "2022-04-09", "JP"),
+        ];
+
+        // Act
+        let result = rank_candidates(&t, &queries, &results, AutoMatchOptions::default());
+
+        // Assert
+        assert_eq!(result.decision, Decision::Matched);
+        assert_eq!(result.matched().unwrap().tmdb_id, 120_089);
+        assert_eq!(result.candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_suggestion_prefills_best_candidate() {
+        // Arrange
+        let results = [
+            tv(120_089, "SPY×FAMILY", "2022-04-09", "JP"),
+            tv(1, "Spy Kids", "2001-03-30", "US"),
+        ];
+        let queries = search_queries(&title(Some(2022)));
+        let ranked = rank_candidates(
+            &title(Some(2022)),
+            &queries,
+            &results,
+            AutoMatchOptions::default(),
+        );
+
+        // Act
+        let approved = Suggestion::from_match(ranked.clone(), true);
+        let pending = Suggestion::from_match(ranked, false);
+
+        // Assert
+        assert_eq!(approved.tmdb_id, 120_089);
+        assert_eq!(approved.approved(), Some(120_089));
+        assert_eq!(approved.chosen().unwrap().name, "SPY×FAMILY");
+        assert!(approved.candidates.iter().all(|c| {
+            let scaled = c.score * 1000.0;
+            (scaled - scaled.round()).abs() < 1e-9
+        }));
+        assert_eq!(pending.approved(), None);
+    }
+
+    #[test]
+    fn test_suggestion_without_candidates_is_never_approved() {
+        // Arrange
+        let ranked = rank_candidates(
+            &title(None),
+            &[String::from("SPY×FAMILY")],
+            &[],
+            AutoMatchOptions::default(),
+        );
+
+        // Act
+        let mut suggestion = Suggestion::from_match(ranked, true);
+        suggestion.approve = true;
+
+        // Assert
+        assert_eq!(suggestion.decision, Decision::NotFound);
+        assert_eq!(suggestion.tmdb_id, 0);
+        assert_eq!(suggestion.approved(), None);
+        assert!(suggestion.chosen().is_none());
+    }
+
+    #[test]
+    fn test_rank_candidates_flags_close_scores_as_ambiguous() {
+        // Arrange: two equally named JP series within the year tolerance
+        let t = title(Some(2022));
+        let queries = search_queries(&t);
+        let results = vec![
+            tv(1, "SPY x FAMILY", "2022-04-09", "JP"),
+            tv(2, "SPY x FAMILY", "2023-10-07", "JP"),
+        ];
+
+        // Act
+        let result = rank_candidates(&t, &queries, &results, AutoMatchOptions::default());
+        let none = rank_candidates(&t, &queries, &[], AutoMatchOptions::default());
+
+        // Assert
+        assert_eq!(result.decision, Decision::Ambiguous);
+        assert!(result.matched().is_none());
+        assert_eq!(none.decision, Decision::NotFound);
+    }
+}
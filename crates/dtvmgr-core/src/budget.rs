@@ -0,0 +1,114 @@
+//! Retry budget shared across all requests of one run.
+//!
+//! Per-request retries are bounded individually; the budget bounds their
+//! sum so a badly rate-limited run stops early instead of sleeping for
+//! tens of minutes.
+
+use std::time::Duration;
+
+/// Remaining retries and backoff sleep for a single run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RetryBudget {
+    /// Maximum number of retries.
+    max_retries: u32,
+    /// Maximum total backoff sleep.
+    max_sleep: Duration,
+    /// Retries spent so far.
+    retries: u32,
+    /// Backoff sleep spent so far.
+    slept: Duration,
+}
+
+impl RetryBudget {
+    /// Creates a budget allowing `max_retries` retries and `max_sleep` of
+    /// total backoff sleep.
+    #[must_use]
+    pub const fn new(max_retries: u32, max_sleep: Duration) -> Self {
+        Self {
+            max_retries,
+            max_sleep,
+            retries: 0,
+            slept: Duration::ZERO,
+        }
+    }
+
+    /// Reserves one retry that sleeps for `backoff`.
+    ///
+    /// Returns `false` without spending anything when the retry would exceed
+    /// either limit.
+    pub fn try_spend(&mut self, backoff: Duration) -> bool {
+        let slept = self.slept.saturating_add(backoff);
+        if self.retries >= self.max_retries || slept > self.max_sleep {
+            return false;
+        }
+        self.retries = self.retries.saturating_add(1);
+        self.slept = slept;
+        true
+    }
+
+    /// Returns the number of retries spent.
+    #[must_use]
+    pub const fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Returns the total backoff sleep spent.
+    #[must_use]
+    pub const fn slept(&self) -> Duration {
+        self.slept
+    }
+
+    /// Returns the retry limit.
+    #[must_use]
+    pub const fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Returns the sleep limit.
+    #[must_use]
+    pub const fn max_sleep(&self) -> Duration {
+        self.max_sleep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_spend_stops_at_retry_limit() {
+        // Arrange
+        let mut budget = RetryBudget::new(2, Duration::from_secs(1000));
+
+        // Act
+        let spent = [
+            budget.try_spend(Duration::from_secs(10)),
+            budget.try_spend(Duration::from_secs(10)),
+            budget.try_spend(Duration::from_secs(10)),
+        ];
+
+        // Assert
+        assert_eq!(spent, [true, true, false]);
+        assert_eq!(budget.retries(), 2);
+        assert_eq!(budget.slept(), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_try_spend_stops_at_sleep_limit_without_spending() {
+        // Arrange
+        let mut budget = RetryBudget::new(10, Duration::from_secs(30));
+
+        // Act
+        let first = budget.try_spend(Duration::from_secs(20));
+        let second = budget.try_spend(Duration::from_secs(20));
+        let third = budget.try_spend(Duration::from_secs(10));
+
+        // Assert: the rejected retry does not count; a smaller one still fits
+        assert!(first);
+        assert!(!second);
+        assert!(third);
+        assert_eq!(budget.retries(), 2);
+        assert_eq!(budget.slept(), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,809 @@
+//! Read-through cache of Syoboi lookups in the local DB.
+//!
+//! [`CachedSyoboiApi`] wraps any [`LocalSyoboiApi`] and implements the
+//! trait itself: lookups whose results were fetched within the TTL are
+//! answered from SQLite, the rest go to the wrapped API and their results
+//! are stored before being returned. Library consumers get a persistent
+//! cache without orchestrating `db sync` themselves.
+//!
+//! Served from the cache:
+//!
+//! - titles looked up by TID list, per TID;
+//! - programs looked up by time range, per distinct request (range,
+//!   channels, titles, `JOIN=SubTitles`);
+//! - channels and channel groups, all or by ID.
+//!
+//! Lookups restricted by `Fields`, `LastUpdate`, `PID`, `StTime`, or
+//! `Count` always go to the API. Fetch times are recorded in `fetch_log`.
+//! Cache errors are logged and fall back to the API; they never fail a
+//! lookup.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use dtvmgr_api::clock::{Clock, SystemClock};
+use dtvmgr_api::syoboi::{
+    LocalSyoboiApi, ProgLookupParams, SyoboiChannel, SyoboiChannelGroup, SyoboiProgram,
+    SyoboiTitle, TidSelector, TitleLookupParams,
+};
+use dtvmgr_db::channels::{CachedChannel, CachedChannelGroup};
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
+use dtvmgr_db::{
+    Connection, DEFAULT_UPSERT_BATCH_SIZE, Episode, ProgramFilter, load_channel_groups,
+    load_channels, load_episodes, load_fresh_keys, load_programs_filtered, load_titles_by_tids,
+    mark_fetched, replace_title_credits, upsert_channel_groups, upsert_channels, upsert_episodes,
+    upsert_titles,
+};
+
+use crate::sync::{
+    TITLE_LOOKUP_CHUNK_SIZE, fold_title_aliases, to_cached_title, to_episodes, to_title_credits,
+    upsert_filtered_programs,
+};
+
+/// Default age up to which cached lookups are served.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_hours(6);
+
+/// Format of `fetch_log.fetched_at`.
+const FETCHED_AT_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Format of the stored program start times.
+const ST_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Syoboi API answering from the local DB while its data is fresh.
+///
+/// Titles and programs fetched through it are stored like `db sync` stores
+/// them, so the DB stays usable by the CLI. Programs are returned with the
+/// TIDs of merged titles folded (see `titles merge`); `ProgComment` is not
+/// cached. Configured with builder-style setters:
+///
+/// ```no_run
+/// # async fn demo(client: dtvmgr_api::syoboi::SyoboiClient, conn: dtvmgr_db::Connection)
+/// # -> anyhow::Result<()> {
+/// use dtvmgr_api::syoboi::{LocalSyoboiApi, TitleLookupParams};
+/// use dtvmgr_core::cache::CachedSyoboiApi;
+///
+/// let api = CachedSyoboiApi::new(client, conn).ttl(std::time::Duration::from_hours(1));
+/// // The second lookup is answered from the DB.
+/// api.lookup_titles(&TitleLookupParams::from_tids(&[6309])).await?;
+/// api.lookup_titles(&TitleLookupParams::from_tids(&[6309])).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct CachedSyoboiApi<A> {
+    /// Wrapped API answering cache misses.
+    inner: A,
+    /// Cache database.
+    conn: Mutex<Connection>,
+    /// Age up to which cached lookups are served.
+    ttl: Duration,
+    /// Time source for fetch times.
+    clock: Arc<dyn Clock>,
+}
+
+impl<A> CachedSyoboiApi<A> {
+    /// Creates a cache over `inner` stored in `conn`, with
+    /// [`DEFAULT_CACHE_TTL`].
+    #[must_use]
+    pub fn new(inner: A, conn: Connection) -> Self {
+        Self {
+            inner,
+            conn: Mutex::new(conn),
+            ttl: DEFAULT_CACHE_TTL,
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Sets the age up to which cached lookups are served.
+    #[must_use]
+    pub const fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Replaces the time source (tests use a fake clock to expire entries).
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the wrapped API and the DB connection.
+    #[must_use]
+    pub fn into_parts(self) -> (A, Connection) {
+        let conn = self
+            .conn
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        (self.inner, conn)
+    }
+
+    /// Runs `f` on the DB. Errors are logged and turned into `None`.
+    fn with_conn<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Option<T> {
+        let conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        match f(&conn) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                tracing::warn!("Syoboi cache unavailable, using the API: {e:#}");
+                None
+            }
+        }
+    }
+
+    /// Current time as a `fetch_log` timestamp.
+    fn fetched_at(&self) -> String {
+        DateTime::<Utc>::from(self.clock.wall_now())
+            .format(FETCHED_AT_FORMAT)
+            .to_string()
+    }
+
+    /// Oldest `fetch_log` timestamp still served.
+    fn cutoff(&self) -> String {
+        let now = DateTime::<Utc>::from(self.clock.wall_now());
+        TimeDelta::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| now.checked_sub_signed(ttl))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+            .format(FETCHED_AT_FORMAT)
+            .to_string()
+    }
+
+    /// Returns the cached titles of `tids` fetched within the TTL.
+    fn fresh_titles(&self, tids: &[u32]) -> Vec<SyoboiTitle> {
+        let keys: Vec<String> = tids.iter().map(|&tid| fetch_key("title", tid)).collect();
+        let cutoff = self.cutoff();
+        self.with_conn(|conn| {
+            let fresh = load_fresh_keys(conn, &keys, &cutoff)?;
+            let fresh_tids: Vec<u32> = tids
+                .iter()
+                .copied()
+                .filter(|&tid| fresh.contains(&fetch_key("title", tid)))
+                .collect();
+            load_titles_by_tids(conn, &fresh_tids)?
+                .iter()
+                .map(|t| Ok(to_syoboi_title(t, &load_episodes(conn, t.tid)?)))
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Stores titles fetched with all fields and records their fetch.
+    fn store_titles(&self, titles: &[SyoboiTitle]) {
+        let fetched_at = self.fetched_at();
+        self.with_conn(|conn| {
+            let cached: Vec<CachedTitle> = titles.iter().map(to_cached_title).collect();
+            upsert_titles(conn, &cached)?;
+            for t in titles {
+                if let Some(episodes) = to_episodes(t) {
+                    upsert_episodes(conn, t.tid, &episodes)?;
+                }
+                let (credits, links) = to_title_credits(t);
+                replace_title_credits(conn, t.tid, &credits, &links)?;
+            }
+            let keys: Vec<String> = titles.iter().map(|t| fetch_key("title", t.tid)).collect();
+            mark_fetched(conn, &keys, &fetched_at)
+        });
+    }
+
+    /// Returns the cached programs of `params` when the request `key` was
+    /// fetched within the TTL.
+    fn fresh_programs(&self, key: &str, params: &ProgLookupParams) -> Option<Vec<SyoboiProgram>> {
+        let range = params.range.as_ref()?;
+        let since = range.start.format(ST_TIME_FORMAT).to_string();
+        let until = range.end.format(ST_TIME_FORMAT).to_string();
+        let keys = [key.to_owned()];
+        let cutoff = self.cutoff();
+        self.with_conn(|conn| {
+            if load_fresh_keys(conn, &keys, &cutoff)?.is_empty() {
+                return Ok(None);
+            }
+            let filter = ProgramFilter {
+                tids: params.tids.as_deref(),
+                ch_ids: params.ch_ids.as_deref(),
+                since: Some(&since),
+                until: Some(&until),
+                ..ProgramFilter::default()
+            };
+            let programs = load_programs_filtered(conn, &filter)?;
+            Ok(Some(
+                programs
+                    .iter()
+                    .map(|p| to_syoboi_program(p, params.join_sub_titles))
+                    .collect(),
+            ))
+        })
+        .flatten()
+    }
+
+    /// Returns whether the `ids` (all when `None`) of `kind` were fetched
+    /// within the TTL.
+    fn ids_fresh(&self, conn: &Connection, kind: &str, ids: Option<&[u32]>) -> Result<bool> {
+        let cutoff = self.cutoff();
+        let all = [format!("{kind}:*")];
+        if !load_fresh_keys(conn, &all, &cutoff)?.is_empty() {
+            return Ok(true);
+        }
+        let Some(ids) = ids else {
+            return Ok(false);
+        };
+        let keys: Vec<String> = ids.iter().map(|&id| fetch_key(kind, id)).collect();
+        let fresh = load_fresh_keys(conn, &keys, &cutoff)?;
+        Ok(keys.iter().all(|key| fresh.contains(key)))
+    }
+}
+
+#[allow(clippy::future_not_send)]
+impl<A: LocalSyoboiApi> CachedSyoboiApi<A> {
+    /// Stores fetched programs under the request `key`.
+    ///
+    /// Titles and channels the programs refer to are fetched first when not
+    /// cached. The request is recorded as fetched only when every program
+    /// could be stored.
+    async fn store_programs(&self, key: &str, programs: &mut [SyoboiProgram]) {
+        let Some((missing_tids, missing_ch_ids)) = self.with_conn(|conn| {
+            fold_title_aliases(conn, programs)?;
+            let (tids, ch_ids) = cached_references(conn, programs)?;
+            Ok((
+                unique(programs.iter().map(|p| p.tid).filter(|t| !tids.contains(t))),
+                unique(
+                    programs
+                        .iter()
+                        .map(|p| p.ch_id)
+                        .filter(|c| !ch_ids.contains(c)),
+                ),
+            ))
+        }) else {
+            return;
+        };
+
+        for chunk in missing_tids.chunks(TITLE_LOOKUP_CHUNK_SIZE) {
+            if let Err(e) = self
+                .lookup_titles(&TitleLookupParams::from_tids(chunk))
+                .await
+            {
+                tracing::warn!("Failed to fetch titles of cached programs: {e}");
+                return;
+            }
+        }
+        if !missing_ch_ids.is_empty()
+            && let Err(e) = self.lookup_channels(Some(&missing_ch_ids)).await
+        {
+            tracing::warn!("Failed to fetch channels of cached programs: {e}");
+            return;
+        }
+
+        let fetched_at = self.fetched_at();
+        self.with_conn(|conn| {
+            let (tids, ch_ids) = cached_references(conn, programs)?;
+            let (stored, _) = upsert_filtered_programs(
+                conn,
+                programs,
+                &tids,
+                &ch_ids,
+                &HashSet::new(),
+                DEFAULT_UPSERT_BATCH_SIZE,
+            )?;
+            if stored == programs.len() {
+                mark_fetched(conn, &[key.to_owned()], &fetched_at)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[allow(clippy::future_not_send)]
+impl<A: LocalSyoboiApi> LocalSyoboiApi for CachedSyoboiApi<A> {
+    async fn lookup_titles(
+        &self,
+        params: &TitleLookupParams,
+    ) -> dtvmgr_api::error::Result<Vec<SyoboiTitle>> {
+        if params.fields.is_some() {
+            return self.inner.lookup_titles(params).await;
+        }
+        let (mut titles, request) = match &params.tids {
+            TidSelector::List(tids) if params.last_update.is_none() => {
+                let cached = self.fresh_titles(tids);
+                let stale: Vec<u32> = tids
+                    .iter()
+                    .copied()
+                    .filter(|&tid| !cached.iter().any(|t| t.tid == tid))
+                    .collect();
+                if stale.is_empty() {
+                    return Ok(cached);
+                }
+                let request = TitleLookupParams {
+                    tids: TidSelector::List(stale),
+                    ..params.clone()
+                };
+                (cached, request)
+            }
+            _ => (Vec::new(), params.clone()),
+        };
+        let fetched = self.inner.lookup_titles(&request).await?;
+        self.store_titles(&fetched);
+        titles.extend(fetched);
+        Ok(titles)
+    }
+
+    async fn lookup_programs(
+        &self,
+        params: &ProgLookupParams,
+    ) -> dtvmgr_api::error::Result<Vec<SyoboiProgram>> {
+        let Some(key) = programs_key(params) else {
+            return self.inner.lookup_programs(params).await;
+        };
+        if let Some(programs) = self.fresh_programs(&key, params) {
+            return Ok(programs);
+        }
+        let mut programs = self.inner.lookup_programs(params).await?;
+        self.store_programs(&key, &mut programs).await;
+        Ok(programs)
+    }
+
+    async fn lookup_channels(
+        &self,
+        ch_ids: Option<&[u32]>,
+    ) -> dtvmgr_api::error::Result<Vec<SyoboiChannel>> {
+        let cached = self.with_conn(|conn| {
+            if !self.ids_fresh(conn, "channel", ch_ids)? {
+                return Ok(None);
+            }
+            let channels: Vec<SyoboiChannel> = load_channels(conn)?
+                .iter()
+                .filter(|c| ch_ids.is_none_or(|ids| ids.contains(&c.ch_id)))
+                .map(to_syoboi_channel)
+                .collect();
+            let complete = ch_ids
+                .is_none_or(|ids| ids.iter().all(|id| channels.iter().any(|c| c.ch_id == *id)));
+            Ok(complete.then_some(channels))
+        });
+        if let Some(channels) = cached.flatten() {
+            return Ok(channels);
+        }
+
+        let channels = self.inner.lookup_channels(ch_ids).await?;
+        let fetched_at = self.fetched_at();
+        self.with_conn(|conn| {
+            // Group IDs without a cached group would violate the foreign key.
+            let cached_gids: HashSet<u32> = load_channel_groups(conn)?
+                .into_iter()
+                .map(|g| g.ch_gid)
+                .collect();
+            let cached: Vec<CachedChannel> = channels
+                .iter()
+                .map(|ch| CachedChannel {
+                    ch_id: ch.ch_id,
+                    ch_gid: ch.ch_gid.filter(|gid| cached_gids.contains(gid)),
+                    ch_name: ch.ch_name.clone(),
+                    ch_url: ch.ch_url.clone(),
+                    ch_iepg_name: ch.ch_iepg_name.clone(),
+                    ch_comment: ch.ch_comment.clone(),
+                    logo_url: None,
+                })
+                .collect();
+            upsert_channels(conn, &cached)?;
+            let keys = fetched_keys("channel", ch_ids, channels.iter().map(|c| c.ch_id));
+            mark_fetched(conn, &keys, &fetched_at)
+        });
+        Ok(channels)
+    }
+
+    async fn lookup_channel_groups(
+        &self,
+        ch_gids: Option<&[u32]>,
+    ) -> dtvmgr_api::error::Result<Vec<SyoboiChannelGroup>> {
+        let cached = self.with_conn(|conn| {
+            if !self.ids_fresh(conn, "channel_group", ch_gids)? {
+                return Ok(None);
+            }
+            let groups: Vec<SyoboiChannelGroup> = load_channel_groups(conn)?
+                .into_iter()
+                .filter(|g| ch_gids.is_none_or(|ids| ids.contains(&g.ch_gid)))
+                .map(|g| SyoboiChannelGroup {
+                    ch_gid: g.ch_gid,
+                    ch_group_name: g.ch_group_name,
+                    ch_group_order: g.ch_group_order,
+                })
+                .collect();
+            let complete = ch_gids
+                .is_none_or(|ids| ids.iter().all(|id| groups.iter().any(|g| g.ch_gid == *id)));
+            Ok(complete.then_some(groups))
+        });
+        if let Some(groups) = cached.flatten() {
+            return Ok(groups);
+        }
+
+        let groups = self.inner.lookup_channel_groups(ch_gids).await?;
+        let fetched_at = self.fetched_at();
+        self.with_conn(|conn| {
+            let cached: Vec<CachedChannelGroup> = groups
+                .iter()
+                .map(|g| CachedChannelGroup {
+                    ch_gid: g.ch_gid,
+                    ch_group_name: g.ch_group_name.clone(),
+                    ch_group_order: g.ch_group_order,
+                })
+                .collect();
+            upsert_channel_groups(conn, &cached)?;
+            let keys = fetched_keys("channel_group", ch_gids, groups.iter().map(|g| g.ch_gid));
+            mark_fetched(conn, &keys, &fetched_at)
+        });
+        Ok(groups)
+    }
+}
+
+/// `fetch_log` key of one entity (`title:6309`).
+fn fetch_key(kind: &str, id: u32) -> String {
+    format!("{kind}:{id}")
+}
+
+/// `fetch_log` keys recording a lookup of `requested` (all when `None`)
+/// that returned `returned`.
+fn fetched_keys(
+    kind: &str,
+    requested: Option<&[u32]>,
+    returned: impl Iterator<Item = u32>,
+) -> Vec<String> {
+    let mut keys: Vec<String> = returned.map(|id| fetch_key(kind, id)).collect();
+    if requested.is_none() {
+        keys.push(format!("{kind}:*"));
+    }
+    keys
+}
+
+/// `fetch_log` key of a program lookup, or `None` when the lookup is not
+/// served from the cache.
+fn programs_key(params: &ProgLookupParams) -> Option<String> {
+    if params.pids.is_some()
+        || params.count.is_some()
+        || params.st_time.is_some()
+        || params.last_update.is_some()
+        || params.fields.is_some()
+    {
+        return None;
+    }
+    let range = params.range.as_ref()?;
+    let ids = |ids: Option<&Vec<u32>>| {
+        ids.map_or_else(
+            || String::from("*"),
+            |ids| {
+                let mut ids = ids.clone();
+                ids.sort_unstable();
+                ids.dedup();
+                ids.iter().map(u32::to_string).collect::<Vec<_>>().join(",")
+            },
+        )
+    };
+    Some(format!(
+        "programs:{}:ch={}:tid={}:join={}",
+        range.to_syoboi_format(),
+        ids(params.ch_ids.as_ref()),
+        ids(params.tids.as_ref()),
+        params.join_sub_titles,
+    ))
+}
+
+/// Returns the TIDs and channel IDs referenced by `programs` that are
+/// cached.
+fn cached_references(
+    conn: &Connection,
+    programs: &[SyoboiProgram],
+) -> Result<(HashSet<u32>, HashSet<u32>)> {
+    let tids = unique(programs.iter().map(|p| p.tid));
+    let titles = load_titles_by_tids(conn, &tids)?
+        .into_iter()
+        .map(|t| t.tid)
+        .collect();
+    let channels = load_channels(conn)?.into_iter().map(|c| c.ch_id).collect();
+    Ok((titles, channels))
+}
+
+/// Collects `ids` without duplicates, keeping their first order.
+fn unique(ids: impl Iterator<Item = u32>) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    ids.filter(|id| seen.insert(*id)).collect()
+}
+
+/// Rebuilds the API form of a cached title and its episodes.
+fn to_syoboi_title(t: &CachedTitle, episodes: &[Episode]) -> SyoboiTitle {
+    let sub_titles = (!episodes.is_empty()).then(|| {
+        episodes
+            .iter()
+            .map(|e| format!("*{:02}*{}", e.count, e.sub_title))
+            .collect::<Vec<_>>()
+            .join("\r\n")
+    });
+    SyoboiTitle {
+        tid: t.tid,
+        last_update: t.last_update.clone(),
+        title: t.title.clone(),
+        short_title: t.short_title.clone(),
+        title_yomi: t.title_yomi.clone(),
+        title_en: t.title_en.clone(),
+        comment: t.comment.clone(),
+        cat: t.cat,
+        title_flag: t.title_flag,
+        first_year: t.first_year,
+        first_month: t.first_month,
+        first_end_year: None,
+        first_end_month: None,
+        first_ch: None,
+        keywords: (!t.keywords.is_empty()).then(|| t.keywords.join(",")),
+        user_point: t.user_point,
+        user_point_rank: t.user_point_rank,
+        sub_titles,
+    }
+}
+
+/// Rebuilds the API form of a cached program.
+fn to_syoboi_program(p: &CachedProgram, join_sub_titles: bool) -> SyoboiProgram {
+    SyoboiProgram {
+        pid: p.pid,
+        tid: p.tid,
+        st_time: p.st_time.clone(),
+        st_offset: p.st_offset,
+        ed_time: p.ed_time.clone(),
+        count: p.count,
+        sub_title: p.sub_title.clone(),
+        prog_comment: None,
+        flag: p.flag,
+        deleted: p.deleted,
+        warn: p.warn,
+        ch_id: p.ch_id,
+        revision: p.revision,
+        last_update: p.last_update.clone(),
+        st_sub_title: p.st_sub_title.clone().filter(|_| join_sub_titles),
+    }
+}
+
+/// Rebuilds the API form of a cached channel.
+fn to_syoboi_channel(c: &CachedChannel) -> SyoboiChannel {
+    SyoboiChannel {
+        ch_id: c.ch_id,
+        ch_gid: c.ch_gid,
+        ch_name: c.ch_name.clone(),
+        ch_comment: c.ch_comment.clone(),
+        ch_url: c.ch_url.clone(),
+        last_update: None,
+        ch_iepg_name: c.ch_iepg_name.clone(),
+        ch_epg_url: None,
+        ch_number: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use chrono::NaiveDate;
+    use dtvmgr_api::clock::FakeClock;
+    use dtvmgr_api::error::ApiError;
+    use dtvmgr_api::syoboi::TimeRange;
+
+    use super::*;
+
+    /// Mock API counting the requests that reach it.
+    #[derive(Default)]
+    struct CountingApi {
+        titles: AtomicUsize,
+        programs: AtomicUsize,
+        channels: AtomicUsize,
+        /// TIDs of the last title lookup.
+        requested_tids: Mutex<Vec<u32>>,
+    }
+
+    impl LocalSyoboiApi for CountingApi {
+        async fn lookup_titles(
+            &self,
+            params: &TitleLookupParams,
+        ) -> Result<Vec<SyoboiTitle>, ApiError> {
+            self.titles.fetch_add(1, Ordering::SeqCst);
+            let TidSelector::List(tids) = &params.tids else {
+                return Ok(Vec::new());
+            };
+            tids.clone_into(&mut self.requested_tids.lock().unwrap());
+            Ok(tids.iter().map(|&tid| make_title(tid)).collect())
+        }
+
+        async fn lookup_programs(
+            &self,
+            _params: &ProgLookupParams,
+        ) -> Result<Vec<SyoboiProgram>, ApiError> {
+            self.programs.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![make_program(1, 100, 1), make_program(2, 200, 2)])
+        }
+
+        async fn lookup_channels(
+            &self,
+            ch_ids: Option<&[u32]>,
+        ) -> Result<Vec<SyoboiChannel>, ApiError> {
+            self.channels.fetch_add(1, Ordering::SeqCst);
+            Ok(ch_ids
+                .unwrap_or(&[1, 2, 3])
+                .iter()
+                .map(|&ch_id| SyoboiChannel {
+                    ch_id,
+                    ch_gid: Some(1),
+                    ch_name: format!("CH{ch_id}"),
+                    ch_comment: None,
+                    ch_url: None,
+                    last_update: None,
+                    ch_iepg_name: None,
+                    ch_epg_url: None,
+                    ch_number: None,
+                })
+                .collect())
+        }
+
+        async fn lookup_channel_groups(
+            &self,
+            _ch_gids: Option<&[u32]>,
+        ) -> Result<Vec<SyoboiChannelGroup>, ApiError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn make_title(tid: u32) -> SyoboiTitle {
+        SyoboiTitle {
+            tid,
+            last_update: "2024-01-01 00:00:00".to_owned(),
+            title: format!("Title {tid}"),
+            short_title: None,
+            title_yomi: None,
+            title_en: None,
+            comment: None,
+            cat: Some(1),
+            title_flag: None,
+            first_year: Some(2024),
+            first_month: Some(1),
+            first_end_year: None,
+            first_end_month: None,
+            first_ch: None,
+            keywords: Some(String::from("a,b")),
+            user_point: None,
+            user_point_rank: None,
+            sub_titles: Some(String::from("*01*一\r\n*02*二")),
+        }
+    }
+
+    fn make_program(pid: u32, tid: u32, ch_id: u32) -> SyoboiProgram {
+        SyoboiProgram {
+            pid,
+            tid,
+            st_time: "2024-01-15 20:00:00".to_owned(),
+            st_offset: None,
+            ed_time: "2024-01-15 20:30:00".to_owned(),
+            count: Some(1),
+            sub_title: None,
+            prog_comment: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            ch_id,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+        }
+    }
+
+    fn setup() -> (
+        CachedSyoboiApi<CountingApi>,
+        Arc<FakeClock>,
+        tempfile::TempDir,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let clock = Arc::new(FakeClock::new(std::time::SystemTime::UNIX_EPOCH));
+        let api = CachedSyoboiApi::new(CountingApi::default(), conn)
+            .ttl(Duration::from_hours(1))
+            .clock(clock.clone());
+        (api, clock, dir)
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_lookup_titles_serves_fresh_titles_from_cache() {
+        // Arrange
+        let (api, _clock, _dir) = setup();
+        let first = api
+            .lookup_titles(&TitleLookupParams::from_tids(&[100, 200]))
+            .await
+            .unwrap();
+
+        // Act: 100 and 200 are cached, only 300 is fetched
+        let second = api
+            .lookup_titles(&TitleLookupParams::from_tids(&[100, 300]))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(first.len(), 2);
+        assert_eq!(api.inner.titles.load(Ordering::SeqCst), 2);
+        assert_eq!(*api.inner.requested_tids.lock().unwrap(), vec![300]);
+        assert_eq!(second.len(), 2);
+        let cached = second.iter().find(|t| t.tid == 100).unwrap();
+        assert_eq!(cached.title, "Title 100");
+        assert_eq!(cached.keywords.as_deref(), Some("a,b"));
+        assert_eq!(cached.sub_titles.as_deref(), Some("*01*一\r\n*02*二"));
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_lookup_titles_refetches_after_ttl() {
+        // Arrange
+        let (api, clock, _dir) = setup();
+        let params = TitleLookupParams::from_tids(&[100]);
+        api.lookup_titles(&params).await.unwrap();
+        api.lookup_titles(&params).await.unwrap();
+        assert_eq!(api.inner.titles.load(Ordering::SeqCst), 1);
+
+        // Act
+        clock.advance(Duration::from_secs(3601));
+        let titles = api.lookup_titles(&params).await.unwrap();
+
+        // Assert
+        assert_eq!(titles.len(), 1);
+        assert_eq!(api.inner.titles.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_lookup_programs_stores_references_and_serves_range() {
+        // Arrange
+        let (api, _clock, _dir) = setup();
+        let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let params = ProgLookupParams {
+            range: Some(TimeRange::new(
+                day.and_hms_opt(0, 0, 0).unwrap(),
+                day.and_hms_opt(23, 59, 59).unwrap(),
+            )),
+            ..ProgLookupParams::default()
+        };
+        let fetched = api.lookup_programs(&params).await.unwrap();
+
+        // Act
+        let cached = api.lookup_programs(&params).await.unwrap();
+
+        // Assert: titles and channels were fetched once to satisfy the FKs
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(api.inner.programs.load(Ordering::SeqCst), 1);
+        assert_eq!(api.inner.titles.load(Ordering::SeqCst), 1);
+        assert_eq!(api.inner.channels.load(Ordering::SeqCst), 1);
+        let mut pids: Vec<u32> = cached.iter().map(|p| p.pid).collect();
+        pids.sort_unstable();
+        assert_eq!(pids, vec![1, 2]);
+        let narrowed = api
+            .lookup_programs(&ProgLookupParams {
+                ch_ids: Some(vec![2]),
+                ..params.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(narrowed.len(), 2, "a new request key goes to the API");
+        assert_eq!(api.inner.programs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_lookup_channels_by_id_served_after_full_fetch() {
+        // Arrange
+        let (api, _clock, _dir) = setup();
+        let all = api.lookup_channels(None).await.unwrap();
+
+        // Act
+        let some = api.lookup_channels(Some(&[2, 3])).await.unwrap();
+        let unknown = api.lookup_channels(Some(&[9])).await.unwrap();
+
+        // Assert: only the unknown channel reached the API again
+        assert_eq!(all.len(), 3);
+        assert_eq!(some.iter().map(|c| c.ch_id).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(api.inner.channels.load(Ordering::SeqCst), 2);
+    }
+}
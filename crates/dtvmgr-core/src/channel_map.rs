@@ -0,0 +1,182 @@
+//! Resolution of external channel lists into Syoboi channel aliases.
+//!
+//! Accepts a JSON array in either of two shapes:
+//!
+//! - dtvmgr mapping entries with an explicit `ch_id`
+//!   (`{"ch_id": 7, "mirakurun_service_id": 3273601024, "channel_type": "GR"}`)
+//! - service lists as returned by Mirakurun `/api/services` or `EPGStation`
+//!   `/api/channels` (`{"id": 3273601024, "name": "テレビ東京", ...}`),
+//!   matched to cached Syoboi channels by normalized name
+//!
+//! Both shapes may be mixed in one array.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use dtvmgr_db::channel_aliases::ChannelAlias;
+use dtvmgr_db::channels::CachedChannel;
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+
+/// One element of the input array.
+#[derive(Debug, Deserialize)]
+struct MapEntry {
+    /// Explicit Syoboi channel ID.
+    ch_id: Option<u32>,
+    /// Mirakurun service ID / `EPGStation` channel ID of a service list.
+    id: Option<u64>,
+    /// Explicit Mirakurun service ID.
+    mirakurun_service_id: Option<u64>,
+    /// Explicit `EPGStation` channel ID.
+    epgstation_channel_id: Option<u64>,
+    /// Broadcast type (`channelType` in `EPGStation`).
+    #[serde(alias = "channelType")]
+    channel_type: Option<String>,
+    /// Mirakurun channel object (`{"type": "GR", ...}`) or `EPGStation`
+    /// channel number.
+    channel: Option<serde_json::Value>,
+    /// Service name.
+    name: Option<String>,
+}
+
+/// Result of resolving an external channel list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelMapping {
+    /// Resolved aliases, one per Syoboi channel, ordered by `ch_id`.
+    pub aliases: Vec<ChannelAlias>,
+    /// Names of entries that matched no cached channel.
+    pub unmatched: Vec<String>,
+}
+
+/// Parses `json` and resolves each entry to a Syoboi channel.
+///
+/// Entries without `ch_id` are matched against `channels` by name after
+/// NFKC normalization, ignoring whitespace and case. When several entries
+/// resolve to the same channel, the first one wins.
+///
+/// # Errors
+///
+/// Returns an error if `json` is not an array of objects.
+#[allow(clippy::module_name_repetitions)]
+pub fn resolve_channel_map(json: &[u8], channels: &[CachedChannel]) -> Result<ChannelMapping> {
+    let entries: Vec<MapEntry> =
+        serde_json::from_slice(json).context("channel map must be a JSON array of objects")?;
+    let by_name: HashMap<String, u32> = channels
+        .iter()
+        .map(|c| (normalize_name(&c.ch_name), c.ch_id))
+        .collect();
+
+    let mut mapping = ChannelMapping::default();
+    let mut seen: HashSet<u32> = HashSet::new();
+    for entry in entries {
+        let ch_id = entry.ch_id.or_else(|| {
+            entry
+                .name
+                .as_deref()
+                .and_then(|name| by_name.get(&normalize_name(name)).copied())
+        });
+        let Some(ch_id) = ch_id else {
+            mapping
+                .unmatched
+                .push(entry.name.unwrap_or_else(|| String::from("(unnamed)")));
+            continue;
+        };
+        if !seen.insert(ch_id) {
+            continue;
+        }
+        let channel_type = entry.channel_type.or_else(|| {
+            entry
+                .channel
+                .as_ref()
+                .and_then(|c| c.get("type"))
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_owned)
+        });
+        mapping.aliases.push(ChannelAlias {
+            ch_id,
+            mirakurun_service_id: entry.mirakurun_service_id.or(entry.id),
+            epgstation_channel_id: entry.epgstation_channel_id.or(entry.id),
+            channel_type,
+            name: entry.name,
+        });
+    }
+    mapping.aliases.sort_by_key(|a| a.ch_id);
+    Ok(mapping)
+}
+
+/// Normalizes a channel name for matching (NFKC, no whitespace, lowercase).
+fn normalize_name(name: &str) -> String {
+    name.nfkc()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn channel(ch_id: u32, ch_name: &str) -> CachedChannel {
+        CachedChannel {
+            ch_id,
+            ch_gid: Some(1),
+            ch_name: ch_name.to_owned(),
+            ..CachedChannel::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_channel_map_matches_service_lists_by_name() {
+        // Arrange: a Mirakurun service, an EPGStation channel, and an unknown one
+        let channels = vec![channel(7, "テレビ東京"), channel(19, "TOKYO MX")];
+        let json = r#"[
+            {"id": 3273601024, "serviceId": 1024, "name": "テレビ東京",
+             "channel": {"type": "GR", "channel": "23"}},
+            {"id": 3239123608, "name": "ＴＯＫＹＯ　ＭＸ", "channelType": "GR", "channel": "16"},
+            {"id": 400101, "name": "NHK BS", "channelType": "BS"}
+        ]"#;
+
+        // Act
+        let mapping = resolve_channel_map(json.as_bytes(), &channels).unwrap();
+
+        // Assert
+        assert_eq!(mapping.aliases.len(), 2);
+        assert_eq!(mapping.aliases[0].ch_id, 7);
+        assert_eq!(mapping.aliases[0].mirakurun_service_id, Some(3_273_601_024));
+        assert_eq!(mapping.aliases[0].channel_type.as_deref(), Some("GR"));
+        assert_eq!(mapping.aliases[1].ch_id, 19);
+        assert_eq!(
+            mapping.aliases[1].epgstation_channel_id,
+            Some(3_239_123_608)
+        );
+        assert_eq!(mapping.unmatched, vec![String::from("NHK BS")]);
+    }
+
+    #[test]
+    fn test_resolve_channel_map_prefers_explicit_ids_and_first_entry() {
+        // Arrange
+        let json = r#"[
+            {"ch_id": 7, "mirakurun_service_id": 1, "epgstation_channel_id": 2},
+            {"ch_id": 7, "mirakurun_service_id": 3}
+        ]"#;
+
+        // Act
+        let mapping = resolve_channel_map(json.as_bytes(), &[]).unwrap();
+        let invalid = resolve_channel_map(br#"{"ch_id": 7}"#, &[]);
+
+        // Assert
+        assert_eq!(
+            mapping.aliases,
+            vec![ChannelAlias {
+                ch_id: 7,
+                mirakurun_service_id: Some(1),
+                epgstation_channel_id: Some(2),
+                channel_type: None,
+                name: None,
+            }]
+        );
+        assert!(invalid.is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! Detection of overlapping broadcasts for tuner planning.
+//!
+//! Programs are swept in start order and chained into groups while each one
+//! starts before the group's latest end. Every group of two or more programs
+//! is a conflict; its peak is the number of tuners needed to record all of
+//! them.
+
+use dtvmgr_db::programs::CachedProgram;
+
+/// Programs whose broadcast windows overlap, directly or through a chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict<'a> {
+    /// Earliest start in the group (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub start: &'a str,
+    /// Latest end in the group (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub end: &'a str,
+    /// Maximum number of programs on air at the same time.
+    pub peak: usize,
+    /// Programs in start order.
+    pub programs: Vec<&'a CachedProgram>,
+}
+
+impl<'a> Conflict<'a> {
+    /// Returns the broadcast day (`YYYY-MM-DD`) the group starts on.
+    #[must_use]
+    pub fn day(&self) -> &'a str {
+        let start: &'a str = self.start;
+        start.get(..10).unwrap_or(start)
+    }
+}
+
+/// Finds groups of overlapping programs, ordered by start time.
+///
+/// End times are exclusive, so back-to-back programs do not conflict.
+/// Programs whose end is not after their start are ignored.
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub fn find_conflicts(programs: &[CachedProgram]) -> Vec<Conflict<'_>> {
+    let mut sorted: Vec<&CachedProgram> =
+        programs.iter().filter(|p| p.ed_time > p.st_time).collect();
+    sorted.sort_by(|a, b| a.st_time.cmp(&b.st_time).then(a.pid.cmp(&b.pid)));
+
+    let mut conflicts = Vec::new();
+    let mut group: Vec<&CachedProgram> = Vec::new();
+    let mut group_end = "";
+    for p in sorted {
+        if !group.is_empty() && p.st_time.as_str() >= group_end {
+            push_conflict(&mut conflicts, core::mem::take(&mut group), group_end);
+        }
+        if group.is_empty() || p.ed_time.as_str() > group_end {
+            group_end = &p.ed_time;
+        }
+        group.push(p);
+    }
+    push_conflict(&mut conflicts, group, group_end);
+    conflicts
+}
+
+/// Appends `group` as a conflict when it holds more than one program.
+fn push_conflict<'a>(
+    conflicts: &mut Vec<Conflict<'a>>,
+    group: Vec<&'a CachedProgram>,
+    end: &'a str,
+) {
+    let Some(first) = group.first() else {
+        return;
+    };
+    if group.len() < 2 {
+        return;
+    }
+    // The peak is always reached at some program's start.
+    let peak = group
+        .iter()
+        .map(|p| {
+            let at = p.st_time.as_str();
+            group
+                .iter()
+                .filter(|q| q.st_time.as_str() <= at && at < q.ed_time.as_str())
+                .count()
+        })
+        .max()
+        .unwrap_or(1);
+    conflicts.push(Conflict {
+        start: &first.st_time,
+        end,
+        peak,
+        programs: group,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn program(pid: u32, ch_id: u32, st_time: &str, ed_time: &str) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid: 100,
+            ch_id,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: ed_time.to_owned(),
+            count: None,
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts_groups_chained_overlaps() {
+        // Arrange: 1-2-3 chain on one evening, 4 back-to-back with 3, 5-6 the next day
+        let programs = vec![
+            program(3, 3, "2024-04-05 23:20:00", "2024-04-05 23:50:00"),
+            program(1, 1, "2024-04-05 23:00:00", "2024-04-05 23:30:00"),
+            program(2, 2, "2024-04-05 23:10:00", "2024-04-05 23:40:00"),
+            program(4, 1, "2024-04-05 23:50:00", "2024-04-06 00:20:00"),
+            program(5, 1, "2024-04-07 01:00:00", "2024-04-07 01:30:00"),
+            program(6, 2, "2024-04-07 01:00:00", "2024-04-07 01:30:00"),
+        ];
+
+        // Act
+        let conflicts = find_conflicts(&programs);
+
+        // Assert
+        assert_eq!(conflicts.len(), 2);
+        let pids: Vec<u32> = conflicts[0].programs.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1, 2, 3]);
+        assert_eq!(conflicts[0].start, "2024-04-05 23:00:00");
+        assert_eq!(conflicts[0].end, "2024-04-05 23:50:00");
+        assert_eq!(conflicts[0].peak, 3);
+        assert_eq!(conflicts[0].day(), "2024-04-05");
+        assert_eq!(conflicts[1].programs.len(), 2);
+        assert_eq!(conflicts[1].peak, 2);
+    }
+
+    #[test]
+    fn test_find_conflicts_peak_counts_simultaneous_programs_only() {
+        // Arrange: a long program overlapping two that do not overlap each other
+        let programs = vec![
+            program(1, 1, "2024-04-05 20:00:00", "2024-04-05 22:00:00"),
+            program(2, 2, "2024-04-05 20:00:00", "2024-04-05 21:00:00"),
+            program(3, 3, "2024-04-05 21:00:00", "2024-04-05 22:00:00"),
+            program(4, 3, "2024-04-05 23:00:00", "2024-04-05 22:00:00"),
+        ];
+
+        // Act
+        let conflicts = find_conflicts(&programs);
+
+        // Assert
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].programs.len(), 3);
+        assert_eq!(conflicts[0].peak, 2);
+    }
+}
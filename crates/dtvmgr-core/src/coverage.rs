@@ -0,0 +1,304 @@
+//! Episode coverage of mapped titles: TMDB episodes vs cached programs.
+//!
+//! For each TMDB season mapped to a title, the aired episodes (air date on
+//! or before today) are compared with the cached programs. An episode counts
+//! as cached when a program is already mapped to it (`tmdb_episode_id`) or
+//! when [`match_programs`] would map one to it, so unmapped programs are
+//! still credited. Titles with season ranges report one row per range, and
+//! episode group mappings one row for the whole group.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use dtvmgr_api::tmdb::{LocalTmdbApi, TmdbEpisode};
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::season_ranges::SeasonRange;
+use dtvmgr_db::titles::CachedTitle;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::matcher::{match_programs, rebase_counts};
+
+/// Coverage of one TMDB season (or episode group) of a title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SeasonCoverage {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name.
+    pub title: String,
+    /// TMDB season number (`None` for an episode group).
+    pub season_number: Option<u32>,
+    /// Episodes listed by TMDB.
+    pub episodes: usize,
+    /// Episodes aired on or before today.
+    pub aired: usize,
+    /// Aired episodes with at least one cached program.
+    pub cached: usize,
+    /// Episode numbers of aired episodes without a cached program.
+    pub missing: Vec<u32>,
+}
+
+impl SeasonCoverage {
+    /// Returns the cached share of aired episodes in percent (100 when
+    /// nothing has aired).
+    #[must_use]
+    pub fn percent(&self) -> u32 {
+        let pct = self
+            .cached
+            .saturating_mul(100)
+            .checked_div(self.aired)
+            .unwrap_or(100);
+        u32::try_from(pct).unwrap_or(100)
+    }
+}
+
+/// TMDB episodes of one season (or episode group) mapped to a title.
+#[derive(Debug, Clone)]
+pub struct MappedEpisodes {
+    /// TMDB season number (`None` for an episode group).
+    pub season_number: Option<u32>,
+    /// Season range the season is mapped through, if any.
+    pub range: Option<SeasonRange>,
+    /// Episodes of the season (absolute order for an episode group).
+    pub episodes: Vec<TmdbEpisode>,
+}
+
+/// Fetches the TMDB episodes mapped to `title`: its episode group, one
+/// season per season range, or the mapped season (season 1 when unset).
+/// Titles without `tmdb_series_id` yield nothing.
+///
+/// # Errors
+///
+/// Returns an error if a season or episode group request fails.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, fields(tid = title.tid), err(level = "error"))]
+pub async fn fetch_mapped_episodes<A: LocalTmdbApi>(
+    api: &A,
+    title: &CachedTitle,
+    ranges: &[SeasonRange],
+    language: &str,
+) -> Result<Vec<MappedEpisodes>> {
+    let Some(series_id) = title.tmdb_series_id else {
+        return Ok(Vec::new());
+    };
+    if let Some(group_id) = title.tmdb_episode_group_id.as_deref() {
+        let group = api
+            .episode_group(group_id, language)
+            .await
+            .with_context(|| format!("failed to fetch episode group {group_id}"))?;
+        return Ok(vec![MappedEpisodes {
+            season_number: None,
+            range: None,
+            episodes: group.absolute_episodes(),
+        }]);
+    }
+    let ranges: Vec<&SeasonRange> = ranges.iter().filter(|r| r.tid == title.tid).collect();
+    let seasons: Vec<(u32, Option<SeasonRange>)> = if ranges.is_empty() {
+        vec![(title.tmdb_season_number.unwrap_or(1), None)]
+    } else {
+        ranges
+            .into_iter()
+            .map(|r| (r.season_number, Some(*r)))
+            .collect()
+    };
+    let mut mapped = Vec::with_capacity(seasons.len());
+    for (season_number, range) in seasons {
+        let season = api
+            .tv_season(series_id, season_number, language)
+            .await
+            .with_context(|| {
+                format!("failed to fetch season {season_number} of series {series_id}")
+            })?;
+        mapped.push(MappedEpisodes {
+            season_number: Some(season_number),
+            range,
+            episodes: season.episodes,
+        });
+    }
+    Ok(mapped)
+}
+
+/// Fetches the TMDB episodes mapped to `title` and compares them with the
+/// cached `programs`.
+///
+/// `today` is the current date (`YYYY-MM-DD`, JST). Titles without
+/// `tmdb_series_id` yield no rows; a missing season number defaults to
+/// season 1.
+///
+/// # Errors
+///
+/// Returns an error if a season or episode group request fails.
+#[allow(clippy::future_not_send)]
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, fields(tid = title.tid), err(level = "error"))]
+pub async fn title_coverage<A: LocalTmdbApi>(
+    api: &A,
+    title: &CachedTitle,
+    programs: &[CachedProgram],
+    ranges: &[SeasonRange],
+    language: &str,
+    today: &str,
+) -> Result<Vec<SeasonCoverage>> {
+    let own: Vec<CachedProgram> = programs
+        .iter()
+        .filter(|p| p.tid == title.tid)
+        .cloned()
+        .collect();
+    let mapped = fetch_mapped_episodes(api, title, ranges, language).await?;
+    Ok(mapped
+        .iter()
+        .map(|m| {
+            let season_programs = m
+                .range
+                .as_ref()
+                .map_or_else(|| own.clone(), |r| rebase_counts(title.tid, &own, r));
+            season_coverage(title, m.season_number, &season_programs, &m.episodes, today)
+        })
+        .collect())
+}
+
+/// Compares the episodes of one season with the programs mapped to it.
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub fn season_coverage(
+    title: &CachedTitle,
+    season_number: Option<u32>,
+    programs: &[CachedProgram],
+    episodes: &[TmdbEpisode],
+    today: &str,
+) -> SeasonCoverage {
+    let covered: HashSet<u64> = programs
+        .iter()
+        .filter_map(|p| p.tmdb_episode_id)
+        .chain(
+            match_programs(programs, episodes)
+                .into_iter()
+                .map(|m| m.tmdb_episode_id),
+        )
+        .collect();
+    let mut aired: Vec<&TmdbEpisode> = episodes
+        .iter()
+        .filter(|e| e.air_date.as_deref().is_some_and(|d| d <= today))
+        .collect();
+    aired.sort_by_key(|e| e.episode_number);
+    let missing: Vec<u32> = aired
+        .iter()
+        .filter(|e| !covered.contains(&e.id))
+        .map(|e| e.episode_number)
+        .collect();
+    SeasonCoverage {
+        tid: title.tid,
+        title: title.title.clone(),
+        season_number,
+        episodes: episodes.len(),
+        aired: aired.len(),
+        cached: aired.len().saturating_sub(missing.len()),
+        missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn program(pid: u32, count: Option<u32>, tmdb_episode_id: Option<u64>) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid: 100,
+            ch_id: 1,
+            tmdb_episode_id,
+            st_time: String::from("2024-01-01 23:00:00"),
+            st_offset: None,
+            ed_time: String::from("2024-01-01 23:30:00"),
+            count,
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    fn episode(id: u64, episode_number: u32, air_date: Option<&str>) -> TmdbEpisode {
+        TmdbEpisode {
+            id,
+            episode_number,
+            name: format!("Episode {episode_number}"),
+            overview: None,
+            air_date: air_date.map(str::to_owned),
+            season_number: 1,
+            show_id: 1,
+            runtime: None,
+            vote_average: 0.0,
+            episode_type: None,
+        }
+    }
+
+    fn title() -> CachedTitle {
+        CachedTitle {
+            tid: 100,
+            tmdb_series_id: Some(1),
+            tmdb_season_number: Some(1),
+            tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
+            title: String::from("Title"),
+            short_title: None,
+            title_yomi: None,
+            title_en: None,
+            cat: Some(1),
+            title_flag: None,
+            first_year: None,
+            first_month: None,
+            keywords: Vec::new(),
+            last_update: String::new(),
+            tmdb_original_name: None,
+            tmdb_name: None,
+            tmdb_alt_titles: None,
+            tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
+        }
+    }
+
+    #[test]
+    fn test_season_coverage_reports_missing_aired_episodes() {
+        // Arrange: episode 2 is mapped, episode 1 matches by count, episode 3
+        // has no program, and episode 4 has not aired yet
+        let episodes = vec![
+            episode(501, 1, Some("2024-01-05")),
+            episode(502, 2, Some("2024-01-12")),
+            episode(503, 3, Some("2024-01-19")),
+            episode(504, 4, Some("2024-01-26")),
+            episode(505, 5, None),
+        ];
+        let programs = vec![program(1, Some(1), None), program(2, None, Some(502))];
+
+        // Act
+        let coverage = season_coverage(&title(), Some(1), &programs, &episodes, "2024-01-20");
+
+        // Assert
+        assert_eq!(coverage.episodes, 5);
+        assert_eq!(coverage.aired, 3);
+        assert_eq!(coverage.cached, 2);
+        assert_eq!(coverage.missing, vec![3]);
+        assert_eq!(coverage.percent(), 66);
+    }
+
+    #[test]
+    fn test_percent_is_full_when_nothing_aired() {
+        // Act
+        let coverage = season_coverage(&title(), Some(1), &[], &[], "2024-01-20");
+
+        // Assert
+        assert_eq!(coverage.percent(), 100);
+        assert!(coverage.missing.is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! CSV (RFC 4180) rendering of cached programs.
+
+use core::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+
+use super::{ExportData, Exporter, ProgramRow};
+
+/// Header row, in column order.
+const HEADER: &str = "pid,tid,title,ch_id,channel,count,sub_title,st_time,ed_time,duration_min";
+
+/// Writes the selected programs as one CSV row each.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct CsvExporter;
+
+impl Exporter for CsvExporter {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn description(&self) -> &'static str {
+        "CSV program list with a header row"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "dtvmgr.csv"
+    }
+
+    fn export(&self, data: &ExportData<'_>, output: &Path) -> Result<String> {
+        let rows = data.rows();
+        std::fs::write(output, render_rows(&rows))
+            .with_context(|| format!("failed to write {}", output.display()))?;
+        Ok(format!(
+            "Exported {} program(s) to {}",
+            rows.len(),
+            output.display()
+        ))
+    }
+}
+
+/// Renders `rows` as CSV with a header and CRLF line endings.
+#[must_use]
+pub fn render_rows(rows: &[ProgramRow<'_>]) -> String {
+    let mut out = format!("{HEADER}\r\n");
+    let number = |n: Option<u32>| n.map_or_else(String::new, |n| n.to_string());
+    for row in rows {
+        let _ = write!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{}\r\n",
+            row.pid,
+            row.tid,
+            escape_field(row.title),
+            row.ch_id,
+            escape_field(row.channel),
+            number(row.count),
+            escape_field(row.sub_title.unwrap_or("")),
+            row.st_time,
+            row.ed_time,
+            number(row.duration_min),
+        );
+    }
+    out
+}
+
+/// Quotes a field containing a comma, quote, or line break, doubling
+/// embedded quotes.
+fn escape_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_rows_quotes_special_fields() {
+        // Arrange
+        let rows = [ProgramRow {
+            pid: 100,
+            tid: 6309,
+            title: "SPY×FAMILY",
+            ch_id: 7,
+            channel: "テレビ東京",
+            count: Some(1),
+            sub_title: Some("作戦, \"前編\""),
+            st_time: "2022-04-09 23:00:00",
+            ed_time: "2022-04-09 23:30:00",
+            duration_min: None,
+        }];
+
+        // Act
+        let csv = render_rows(&rows);
+
+        // Assert
+        assert_eq!(
+            csv,
+            format!(
+                "{HEADER}\r\n100,6309,SPY×FAMILY,7,テレビ東京,1,\"作戦, \"\"前編\"\"\",\
+                 2022-04-09 23:00:00,2022-04-09 23:30:00,\r\n"
+            )
+        );
+    }
+}
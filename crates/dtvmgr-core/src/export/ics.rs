@@ -0,0 +1,266 @@
+//! iCalendar (RFC 5545) rendering of cached programs.
+
+use core::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use dtvmgr_api::syoboi::{SYOBOI_BASE_URL, SYOBOI_TIMEZONE};
+
+use super::{ExportData, Exporter};
+
+/// Maximum content line length in octets, excluding the CRLF.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// One broadcast rendered as a `VEVENT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramEvent<'a> {
+    /// Syoboi program ID (used for the UID).
+    pub pid: u32,
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name.
+    pub title: &'a str,
+    /// Episode number.
+    pub count: Option<u32>,
+    /// Episode subtitle.
+    pub sub_title: Option<&'a str>,
+    /// Channel name.
+    pub channel: &'a str,
+    /// Channel logo URL, written as an RFC 7986 `IMAGE` badge.
+    pub channel_logo: Option<&'a str>,
+    /// Broadcast start (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub st_time: &'a str,
+    /// Broadcast end (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub ed_time: &'a str,
+    /// Duration in minutes.
+    pub duration_min: Option<u32>,
+}
+
+/// Renders `events` as a complete `VCALENDAR` with CRLF line endings.
+///
+/// Events whose times cannot be parsed are skipped. `stamp` is written as
+/// every event's `DTSTAMP`.
+#[must_use]
+pub fn render_calendar(events: &[ProgramEvent<'_>], stamp: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let dtstamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(
+        &mut out,
+        concat!(
+            "PRODID:-//dtvmgr//dtvmgr ",
+            env!("CARGO_PKG_VERSION"),
+            "//JA"
+        ),
+    );
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, "X-WR-CALNAME:dtvmgr");
+    for event in events {
+        let (Some(start), Some(end)) = (to_utc(event.st_time), to_utc(event.ed_time)) else {
+            continue;
+        };
+        let mut summary = String::from(event.title);
+        if let Some(count) = event.count {
+            let _ = write!(summary, " #{count}");
+        }
+        if let Some(sub) = event.sub_title.filter(|s| !s.is_empty()) {
+            let _ = write!(summary, " 「{sub}」");
+        }
+        let mut description = format!("{}\n{}", event.channel, event.st_time);
+        if let Some(min) = event.duration_min {
+            let _ = write!(description, " ({min} min)");
+        }
+
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}@dtvmgr", event.pid));
+        push_line(&mut out, &format!("DTSTAMP:{dtstamp}"));
+        push_line(&mut out, &format!("DTSTART:{start}"));
+        push_line(&mut out, &format!("DTEND:{end}"));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&summary)));
+        push_line(
+            &mut out,
+            &format!("LOCATION:{}", escape_text(event.channel)),
+        );
+        push_line(
+            &mut out,
+            &format!("DESCRIPTION:{}", escape_text(&description)),
+        );
+        push_line(
+            &mut out,
+            &format!("URL:{SYOBOI_BASE_URL}/tid/{}#{}", event.tid, event.pid),
+        );
+        if let Some(logo) = event.channel_logo {
+            push_line(&mut out, &format!("IMAGE;VALUE=URI;DISPLAY=BADGE:{logo}"));
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Writes the selected programs as one `.ics` calendar.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct IcsExporter;
+
+impl Exporter for IcsExporter {
+    fn name(&self) -> &'static str {
+        "ics"
+    }
+
+    fn description(&self) -> &'static str {
+        "iCalendar (.ics) schedule, one event per program"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "dtvmgr.ics"
+    }
+
+    fn export(&self, data: &ExportData<'_>, output: &Path) -> Result<String> {
+        let events: Vec<ProgramEvent<'_>> = data
+            .programs
+            .iter()
+            .map(|p| ProgramEvent {
+                pid: p.pid,
+                tid: p.tid,
+                title: data.title_name(p.tid),
+                count: p.count,
+                sub_title: p.st_sub_title.as_deref().or(p.sub_title.as_deref()),
+                channel: data.channel_name(p.ch_id),
+                channel_logo: data
+                    .channels
+                    .get(&p.ch_id)
+                    .and_then(|c| c.logo_url.as_deref()),
+                st_time: &p.st_time,
+                ed_time: &p.ed_time,
+                duration_min: p.duration_min,
+            })
+            .collect();
+        std::fs::write(output, render_calendar(&events, data.generated_at))
+            .with_context(|| format!("failed to write {}", output.display()))?;
+        Ok(format!(
+            "Exported {} program(s) to {}",
+            events.len(),
+            output.display()
+        ))
+    }
+}
+
+/// Converts a JST `YYYY-MM-DD HH:MM:SS` time to iCalendar UTC form.
+fn to_utc(value: &str) -> Option<String> {
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()?;
+    let local = naive.and_local_timezone(SYOBOI_TIMEZONE).single()?;
+    Some(
+        local
+            .with_timezone(&Utc)
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string(),
+    )
+}
+
+/// Escapes a TEXT value (backslash, semicolon, comma, newline).
+fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Appends `line` folded at 75 octets (never inside a UTF-8 sequence),
+/// terminated by CRLF.
+fn push_line(out: &mut String, line: &str) {
+    let mut width: usize = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width.saturating_add(len) > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The leading space of a continuation line counts toward its length.
+            width = 1;
+        }
+        out.push(c);
+        width = width.saturating_add(len);
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn event(sub_title: Option<&str>) -> ProgramEvent<'_> {
+        ProgramEvent {
+            pid: 100,
+            tid: 6309,
+            title: "SPY×FAMILY",
+            count: Some(1),
+            sub_title,
+            channel: "テレビ東京",
+            channel_logo: Some("http://epgstation:8888/api/channels/3273601024/logo"),
+            st_time: "2022-04-09 23:00:00",
+            ed_time: "2022-04-09 23:30:00",
+            duration_min: Some(30),
+        }
+    }
+
+    #[test]
+    fn test_render_calendar_converts_jst_to_utc_and_escapes() {
+        // Arrange
+        let stamp = DateTime::from_timestamp(0, 0).unwrap();
+
+        // Act
+        let ics = render_calendar(&[event(Some("作戦; 開始, 前編"))], stamp);
+
+        // Assert
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:100@dtvmgr\r\n"));
+        assert!(ics.contains("DTSTAMP:19700101T000000Z\r\n"));
+        assert!(ics.contains("DTSTART:20220409T140000Z\r\n"));
+        assert!(ics.contains("DTEND:20220409T143000Z\r\n"));
+        assert!(ics.contains("SUMMARY:SPY×FAMILY #1 「作戦\\; 開始\\, 前編」\r\n"));
+        assert!(ics.contains("DESCRIPTION:テレビ東京\\n2022-04-09 23:00:00 (30 min)\r\n"));
+        assert!(ics.contains("IMAGE;VALUE=URI;DISPLAY=BADGE:http://epgstation:8888/api/c"));
+    }
+
+    #[test]
+    fn test_render_calendar_folds_long_lines_on_char_boundaries() {
+        // Arrange: 40 three-byte characters exceed 75 octets
+        let long = "あ".repeat(40);
+        let stamp = DateTime::from_timestamp(0, 0).unwrap();
+
+        // Act
+        let ics = render_calendar(&[event(Some(&long))], stamp);
+
+        // Assert
+        assert!(ics.lines().all(|l| l.trim_end_matches('\r').len() <= 75));
+        assert!(ics.contains("\r\n "));
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("「{long}」")));
+    }
+
+    #[test]
+    fn test_render_calendar_skips_unparsable_times() {
+        // Arrange
+        let mut bad = event(None);
+        bad.st_time = "not a time";
+
+        // Act
+        let ics = render_calendar(&[bad], DateTime::from_timestamp(0, 0).unwrap());
+
+        // Assert
+        assert!(!ics.contains("BEGIN:VEVENT"));
+    }
+}
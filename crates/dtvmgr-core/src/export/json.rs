@@ -0,0 +1,106 @@
+//! JSON rendering of cached programs.
+
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+
+use super::{ExportData, Exporter};
+
+/// Writes the selected programs as a pretty-printed JSON array of
+/// [`ProgramRow`](super::ProgramRow) objects.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn description(&self) -> &'static str {
+        "JSON array of programs with title and channel names"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "dtvmgr.json"
+    }
+
+    fn export(&self, data: &ExportData<'_>, output: &Path) -> Result<String> {
+        let rows = data.rows();
+        let mut json = serde_json::to_string_pretty(&rows).context("failed to encode programs")?;
+        json.push('\n');
+        std::fs::write(output, json)
+            .with_context(|| format!("failed to write {}", output.display()))?;
+        Ok(format!(
+            "Exported {} program(s) to {}",
+            rows.len(),
+            output.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use std::collections::HashMap;
+
+    use chrono::DateTime;
+    use dtvmgr_db::channels::CachedChannel;
+    use dtvmgr_db::localizations::TitleLocalization;
+
+    use super::*;
+    use crate::export::test_program;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_json_export_writes_rows_with_names() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.json");
+        let programs = [test_program(
+            100,
+            "2022-04-09 23:00:00",
+            "2022-04-09 23:30:00",
+        )];
+        let channels = HashMap::from([(
+            7,
+            CachedChannel {
+                ch_id: 7,
+                ch_name: String::from("テレビ東京"),
+                ..CachedChannel::default()
+            },
+        )]);
+        let localized = HashMap::from([(
+            6309,
+            TitleLocalization {
+                tid: 6309,
+                language: String::from("en-US"),
+                name: String::from("SPY x FAMILY"),
+                overview: None,
+            },
+        )]);
+        let data = ExportData {
+            programs: &programs,
+            titles: &HashMap::new(),
+            channels: &channels,
+            localized: &localized,
+            lang: "en",
+            tmdb_episodes: &HashMap::new(),
+            images: &[],
+            generated_at: DateTime::from_timestamp(0, 0).unwrap(),
+        };
+
+        // Act
+        let summary = JsonExporter.export(&data, &output).unwrap();
+
+        // Assert
+        assert!(summary.starts_with("Exported 1 program(s)"));
+        let value: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(value[0]["pid"], 100);
+        assert_eq!(value[0]["title"], "SPY x FAMILY");
+        assert_eq!(value[0]["channel"], "テレビ東京");
+        assert_eq!(value[0]["st_time"], "2022-04-09 23:00:00");
+    }
+}
@@ -0,0 +1,203 @@
+//! Export of cached data to external file formats.
+//!
+//! Every format implements [`Exporter`] in its own module and is listed in
+//! [`EXPORTERS`]. Callers select the programs once with the shared filters,
+//! bundle them with their lookup tables into [`ExportData`], and hand that
+//! to the exporter of the requested format.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dtvmgr_api::tmdb::TmdbEpisode;
+use dtvmgr_db::channels::CachedChannel;
+use dtvmgr_db::images::TitleImage;
+use dtvmgr_db::localizations::TitleLocalization;
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
+use serde::Serialize;
+
+/// Flat CSV program list.
+pub mod csv;
+/// iCalendar (RFC 5545) schedule export.
+pub mod ics;
+/// JSON program list.
+pub mod json;
+/// Kodi / Jellyfin NFO metadata export.
+pub mod nfo;
+/// XMLTV electronic program guide export.
+pub mod xmltv;
+
+/// Every registered export format, in `export list-formats` order.
+pub static EXPORTERS: [&dyn Exporter; 5] = [
+    &ics::IcsExporter,
+    &xmltv::XmltvExporter,
+    &nfo::NfoExporter::DEFAULT,
+    &csv::CsvExporter,
+    &json::JsonExporter,
+];
+
+/// Returns the registered exporter named `name`.
+#[must_use]
+pub fn find_exporter(name: &str) -> Option<&'static dyn Exporter> {
+    EXPORTERS.iter().copied().find(|e| e.name() == name)
+}
+
+/// An output format of cached programs.
+pub trait Exporter: Sync {
+    /// Format name used on the command line (e.g. "ics").
+    fn name(&self) -> &'static str;
+
+    /// One-line description shown by `export list-formats`.
+    fn description(&self) -> &'static str;
+
+    /// Output path used when none is given.
+    fn default_output(&self) -> &'static str;
+
+    /// Whether [`ExportData::tmdb_episodes`] is read, so callers can skip
+    /// fetching TMDB seasons for other formats.
+    fn uses_tmdb_episodes(&self) -> bool {
+        false
+    }
+
+    /// Writes `data` to `output` and returns a one-line summary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file or directory cannot be written.
+    fn export(&self, data: &ExportData<'_>, output: &Path) -> Result<String>;
+}
+
+/// Programs selected by the shared export filters and their lookup tables.
+#[derive(Debug, Clone, Copy)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ExportData<'a> {
+    /// Selected programs, ordered by start time and PID.
+    pub programs: &'a [CachedProgram],
+    /// Cached titles keyed by TID.
+    pub titles: &'a HashMap<u32, CachedTitle>,
+    /// Cached channels keyed by channel ID.
+    pub channels: &'a HashMap<u32, CachedChannel>,
+    /// Stored TMDB names and overviews keyed by TID; empty to keep the
+    /// Syoboi titles.
+    pub localized: &'a HashMap<u32, TitleLocalization>,
+    /// Language of `localized` (e.g. "ja").
+    pub lang: &'a str,
+    /// TMDB season episodes keyed by TID; empty when not fetched.
+    pub tmdb_episodes: &'a HashMap<u32, Vec<TmdbEpisode>>,
+    /// Downloaded title images.
+    pub images: &'a [TitleImage],
+    /// Time the export runs.
+    pub generated_at: DateTime<Utc>,
+}
+
+impl<'a> ExportData<'a> {
+    /// Returns the display name of `tid`: the localized name when stored,
+    /// else the Syoboi title, else an empty string.
+    #[must_use]
+    pub fn title_name(&self, tid: u32) -> &'a str {
+        self.localized.get(&tid).map_or_else(
+            || self.titles.get(&tid).map_or("", |t| t.title.as_str()),
+            |l| l.name.as_str(),
+        )
+    }
+
+    /// Returns the name of channel `ch_id`, or an empty string.
+    #[must_use]
+    pub fn channel_name(&self, ch_id: u32) -> &'a str {
+        self.channels.get(&ch_id).map_or("", |c| c.ch_name.as_str())
+    }
+
+    /// Returns one flat row per selected program.
+    #[must_use]
+    pub fn rows(&self) -> Vec<ProgramRow<'a>> {
+        self.programs
+            .iter()
+            .map(|p| ProgramRow {
+                pid: p.pid,
+                tid: p.tid,
+                title: self.title_name(p.tid),
+                ch_id: p.ch_id,
+                channel: self.channel_name(p.ch_id),
+                count: p.count,
+                sub_title: p.st_sub_title.as_deref().or(p.sub_title.as_deref()),
+                st_time: &p.st_time,
+                ed_time: &p.ed_time,
+                duration_min: p.duration_min,
+            })
+            .collect()
+    }
+}
+
+/// One program of the flat list formats (CSV, JSON).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProgramRow<'a> {
+    /// Syoboi program ID.
+    pub pid: u32,
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name.
+    pub title: &'a str,
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Channel name.
+    pub channel: &'a str,
+    /// Episode number.
+    pub count: Option<u32>,
+    /// Episode subtitle.
+    pub sub_title: Option<&'a str>,
+    /// Broadcast start (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub st_time: &'a str,
+    /// Broadcast end (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub ed_time: &'a str,
+    /// Duration in minutes.
+    pub duration_min: Option<u32>,
+}
+
+/// Builds a program for exporter tests.
+#[cfg(test)]
+pub(crate) fn test_program(pid: u32, st_time: &str, ed_time: &str) -> CachedProgram {
+    CachedProgram {
+        pid,
+        tid: 6309,
+        ch_id: 7,
+        tmdb_episode_id: None,
+        st_time: st_time.to_owned(),
+        st_offset: None,
+        ed_time: ed_time.to_owned(),
+        count: Some(1),
+        sub_title: None,
+        flag: None,
+        deleted: None,
+        warn: None,
+        revision: None,
+        last_update: None,
+        st_sub_title: None,
+        duration_min: Some(30),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_find_exporter_resolves_every_registered_name() {
+        // Arrange
+        let names: Vec<&str> = EXPORTERS.iter().map(|e| e.name()).collect();
+
+        // Act
+        let found: Vec<&str> = names
+            .iter()
+            .map(|n| find_exporter(n).unwrap().name())
+            .collect();
+
+        // Assert
+        assert_eq!(names, vec!["ics", "xmltv", "nfo", "csv", "json"]);
+        assert_eq!(found, names);
+        assert!(find_exporter("pdf").is_none());
+    }
+}
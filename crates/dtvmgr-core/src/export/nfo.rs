@@ -0,0 +1,613 @@
+//! Kodi / Jellyfin NFO rendering of cached titles and programs.
+//!
+//! Writes `<tvshow>` and `<episodedetails>` documents following the Kodi
+//! schema. Episode numbers come from the mapped TMDB episode when known,
+//! otherwise from the Syoboi program count.
+
+use core::fmt::Write as _;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use dtvmgr_api::tmdb::TmdbEpisode;
+use dtvmgr_db::images::TitleImage;
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
+
+use super::{ExportData, Exporter};
+
+/// XML declaration written at the top of every NFO.
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+
+/// Default show directory template (holds `tvshow.nfo`).
+pub const DEFAULT_SHOW_DIR: &str = "{title}";
+/// Default season directory template, relative to the show directory.
+pub const DEFAULT_SEASON_DIR: &str = "Season {season:02}";
+/// Default episode NFO file name template.
+pub const DEFAULT_EPISODE_FILE: &str = "{title} S{season:02}E{episode:02}.nfo";
+
+/// Writes `tvshow.nfo` and one episode NFO per episode number for every
+/// title of the selected programs, below the output directory.
+///
+/// Path templates accept the placeholders of [`render_path_template`].
+/// Episode numbers, names, and synopses come from
+/// [`ExportData::tmdb_episodes`] when present, else from Syoboi counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct NfoExporter<'a> {
+    /// Show directory template.
+    pub show_dir: &'a str,
+    /// Season directory template.
+    pub season_dir: &'a str,
+    /// Episode NFO file name template.
+    pub episode_file: &'a str,
+    /// Skip titles without a TMDB series mapping.
+    pub mapped_only: bool,
+}
+
+impl NfoExporter<'static> {
+    /// Default templates, exporting mapped titles only.
+    pub const DEFAULT: Self = Self {
+        show_dir: DEFAULT_SHOW_DIR,
+        season_dir: DEFAULT_SEASON_DIR,
+        episode_file: DEFAULT_EPISODE_FILE,
+        mapped_only: true,
+    };
+}
+
+impl Exporter for NfoExporter<'_> {
+    fn name(&self) -> &'static str {
+        "nfo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Kodi/Jellyfin tvshow.nfo and episode NFO files (directory)"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "nfo"
+    }
+
+    fn uses_tmdb_episodes(&self) -> bool {
+        true
+    }
+
+    fn export(&self, data: &ExportData<'_>, output: &Path) -> Result<String> {
+        let tids: BTreeSet<u32> = data.programs.iter().map(|p| p.tid).collect();
+        let titles: Vec<&CachedTitle> = tids
+            .iter()
+            .filter_map(|tid| data.titles.get(tid))
+            .filter(|t| !self.mapped_only || t.tmdb_series_id.is_some())
+            .collect();
+        if titles.is_empty() {
+            return Ok(String::from("No titles to export"));
+        }
+        let mut written: usize = 0;
+        for title in &titles {
+            let own: Vec<CachedProgram> = data
+                .programs
+                .iter()
+                .filter(|p| p.tid == title.tid)
+                .cloned()
+                .collect();
+            let episodes = data
+                .tmdb_episodes
+                .get(&title.tid)
+                .map_or(&[][..], Vec::as_slice);
+            let count = self.write_title(data, output, title, &own, episodes)?;
+            written = written.saturating_add(count);
+        }
+        Ok(format!(
+            "Wrote {written} episode NFO(s) for {} title(s) to {}",
+            titles.len(),
+            output.display()
+        ))
+    }
+}
+
+impl NfoExporter<'_> {
+    /// Writes `tvshow.nfo` and the episode NFOs of one title. Returns the
+    /// number of episode NFOs written.
+    ///
+    /// Downloaded images are copied next to `tvshow.nfo` as `poster.*` /
+    /// `fanart.*` and referenced from it. A stored localization replaces
+    /// the Syoboi title and adds the plot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory or file cannot be written.
+    fn write_title(
+        &self,
+        data: &ExportData<'_>,
+        out_dir: &Path,
+        title: &CachedTitle,
+        programs: &[CachedProgram],
+        tmdb_episodes: &[TmdbEpisode],
+    ) -> Result<usize> {
+        let season = title.tmdb_season_number.unwrap_or(1);
+        let localized = data.localized.get(&title.tid);
+        let show_dir = out_dir.join(render_path_template(
+            self.show_dir,
+            &title.title,
+            title.tid,
+            season,
+            0,
+        ));
+        std::fs::create_dir_all(&show_dir)
+            .with_context(|| format!("failed to create {}", show_dir.display()))?;
+        let poster = copy_title_image(data.images, title.tid, "poster", &show_dir, "poster")?;
+        let fanart = copy_title_image(data.images, title.tid, "backdrop", &show_dir, "fanart")?;
+        let show = TvShowNfo {
+            tid: title.tid,
+            title: localized.map_or(title.title.as_str(), |l| l.name.as_str()),
+            original_title: title.tmdb_original_name.as_deref(),
+            sort_title: title.title_yomi.as_deref(),
+            year: title.first_year,
+            plot: localized.and_then(|l| l.overview.as_deref()),
+            tmdb_series_id: title.tmdb_series_id,
+            poster: poster.as_deref(),
+            fanart: fanart.as_deref(),
+        };
+        let show_path = show_dir.join("tvshow.nfo");
+        std::fs::write(&show_path, render_tvshow(&show))
+            .with_context(|| format!("failed to write {}", show_path.display()))?;
+
+        let mut written: usize = 0;
+        for ep in collect_episodes(programs, tmdb_episodes) {
+            let season_dir = show_dir.join(render_path_template(
+                self.season_dir,
+                &title.title,
+                title.tid,
+                season,
+                ep.number,
+            ));
+            std::fs::create_dir_all(&season_dir)
+                .with_context(|| format!("failed to create {}", season_dir.display()))?;
+            let nfo = EpisodeNfo {
+                pid: ep.program.pid,
+                show_title: &title.title,
+                title: ep.tmdb.map(|e| e.name.as_str()).or_else(|| {
+                    ep.program
+                        .st_sub_title
+                        .as_deref()
+                        .or(ep.program.sub_title.as_deref())
+                }),
+                season,
+                episode: ep.number,
+                aired: ep
+                    .tmdb
+                    .and_then(|e| e.air_date.as_deref())
+                    .or_else(|| ep.program.st_time.get(..10)),
+                plot: ep.tmdb.and_then(|e| e.overview.as_deref()),
+                tmdb_episode_id: ep.tmdb.map(|e| e.id),
+            };
+            let path = season_dir.join(render_path_template(
+                self.episode_file,
+                &title.title,
+                title.tid,
+                season,
+                ep.number,
+            ));
+            std::fs::write(&path, render_episode(&nfo))
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            written = written.saturating_add(1);
+        }
+        Ok(written)
+    }
+}
+
+/// Copies the downloaded `kind` image of `tid` into `show_dir` as
+/// `<name>.<ext>` and returns the file name, or `None` when no image is
+/// stored on disk.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be copied.
+fn copy_title_image(
+    images: &[TitleImage],
+    tid: u32,
+    kind: &str,
+    show_dir: &Path,
+    name: &str,
+) -> Result<Option<String>> {
+    let Some(image) = images.iter().find(|i| i.tid == tid && i.kind == kind) else {
+        return Ok(None);
+    };
+    let src = Path::new(&image.local_path);
+    if !src.exists() {
+        return Ok(None);
+    }
+    let ext = src.extension().and_then(|e| e.to_str()).unwrap_or("jpg");
+    let file_name = format!("{name}.{ext}");
+    std::fs::copy(src, show_dir.join(&file_name))
+        .with_context(|| format!("failed to copy {}", src.display()))?;
+    Ok(Some(file_name))
+}
+
+/// Show-level metadata rendered as `tvshow.nfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TvShowNfo<'a> {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Display title.
+    pub title: &'a str,
+    /// Original title (TMDB original name).
+    pub original_title: Option<&'a str>,
+    /// Sort title (reading).
+    pub sort_title: Option<&'a str>,
+    /// First broadcast year.
+    pub year: Option<u32>,
+    /// Series overview.
+    pub plot: Option<&'a str>,
+    /// TMDB series ID.
+    pub tmdb_series_id: Option<u64>,
+    /// Poster image path, relative to the NFO (e.g. "poster.jpg").
+    pub poster: Option<&'a str>,
+    /// Fanart (backdrop) image path, relative to the NFO.
+    pub fanart: Option<&'a str>,
+}
+
+/// Episode-level metadata rendered as an episode NFO.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EpisodeNfo<'a> {
+    /// Syoboi program ID of the first broadcast.
+    pub pid: u32,
+    /// Show title.
+    pub show_title: &'a str,
+    /// Episode title.
+    pub title: Option<&'a str>,
+    /// Season number.
+    pub season: u32,
+    /// Episode number within the season.
+    pub episode: u32,
+    /// First air date (`YYYY-MM-DD`).
+    pub aired: Option<&'a str>,
+    /// Episode synopsis.
+    pub plot: Option<&'a str>,
+    /// TMDB episode ID.
+    pub tmdb_episode_id: Option<u64>,
+}
+
+/// One episode to export: its number, first broadcast, and TMDB data.
+#[derive(Debug, Clone, Copy)]
+#[allow(clippy::module_name_repetitions)]
+pub struct NfoEpisode<'a> {
+    /// Episode number within the season.
+    pub number: u32,
+    /// Earliest broadcast of the episode.
+    pub program: &'a CachedProgram,
+    /// Matching TMDB episode, when the program is mapped.
+    pub tmdb: Option<&'a TmdbEpisode>,
+}
+
+/// Renders `tvshow.nfo`.
+#[must_use]
+pub fn render_tvshow(show: &TvShowNfo<'_>) -> String {
+    let mut out = format!("{XML_HEADER}\n<tvshow>\n");
+    push_element(&mut out, "title", show.title);
+    if let Some(original) = show.original_title {
+        push_element(&mut out, "originaltitle", original);
+    }
+    if let Some(sort) = show.sort_title {
+        push_element(&mut out, "sorttitle", sort);
+    }
+    if let Some(year) = show.year {
+        push_element(&mut out, "year", &year.to_string());
+    }
+    if let Some(plot) = show.plot {
+        push_element(&mut out, "plot", plot);
+    }
+    if let Some(poster) = show.poster {
+        let _ = writeln!(
+            out,
+            r#"  <thumb aspect="poster">{}</thumb>"#,
+            escape_xml(poster)
+        );
+    }
+    if let Some(fanart) = show.fanart {
+        let _ = writeln!(
+            out,
+            "  <fanart>\n    <thumb>{}</thumb>\n  </fanart>",
+            escape_xml(fanart)
+        );
+    }
+    if let Some(id) = show.tmdb_series_id {
+        let _ = writeln!(
+            out,
+            r#"  <uniqueid type="tmdb" default="true">{id}</uniqueid>"#
+        );
+    }
+    let _ = writeln!(out, r#"  <uniqueid type="syoboi">{}</uniqueid>"#, show.tid);
+    out.push_str("</tvshow>\n");
+    out
+}
+
+/// Renders an `<episodedetails>` NFO.
+#[must_use]
+pub fn render_episode(episode: &EpisodeNfo<'_>) -> String {
+    let mut out = format!("{XML_HEADER}\n<episodedetails>\n");
+    let fallback = format!("第{}話", episode.episode);
+    push_element(
+        &mut out,
+        "title",
+        episode.title.filter(|t| !t.is_empty()).unwrap_or(&fallback),
+    );
+    push_element(&mut out, "showtitle", episode.show_title);
+    push_element(&mut out, "season", &episode.season.to_string());
+    push_element(&mut out, "episode", &episode.episode.to_string());
+    if let Some(aired) = episode.aired {
+        push_element(&mut out, "aired", aired);
+    }
+    if let Some(plot) = episode.plot.filter(|p| !p.is_empty()) {
+        push_element(&mut out, "plot", plot);
+    }
+    if let Some(id) = episode.tmdb_episode_id {
+        let _ = writeln!(
+            out,
+            r#"  <uniqueid type="tmdb" default="true">{id}</uniqueid>"#
+        );
+    }
+    let _ = writeln!(
+        out,
+        r#"  <uniqueid type="syoboi">{}</uniqueid>"#,
+        episode.pid
+    );
+    out.push_str("</episodedetails>\n");
+    out
+}
+
+/// Picks one entry per episode number from `programs`.
+///
+/// A program mapped to one of `episodes` takes that episode's number;
+/// otherwise its count is used. Programs with neither are skipped, and
+/// reruns collapse onto their earliest broadcast.
+#[must_use]
+pub fn collect_episodes<'a>(
+    programs: &'a [CachedProgram],
+    episodes: &'a [TmdbEpisode],
+) -> Vec<NfoEpisode<'a>> {
+    let by_id: HashMap<u64, &TmdbEpisode> = episodes.iter().map(|e| (e.id, e)).collect();
+    let mut picked: BTreeMap<u32, NfoEpisode<'a>> = BTreeMap::new();
+    for program in programs {
+        let tmdb = program
+            .tmdb_episode_id
+            .and_then(|id| by_id.get(&id).copied());
+        let Some(number) = tmdb.map(|e| e.episode_number).or(program.count) else {
+            continue;
+        };
+        let candidate = NfoEpisode {
+            number,
+            program,
+            tmdb,
+        };
+        picked
+            .entry(number)
+            .and_modify(|current| {
+                if program.st_time < current.program.st_time {
+                    *current = NfoEpisode {
+                        tmdb: current.tmdb.or(tmdb),
+                        ..candidate
+                    };
+                }
+            })
+            .or_insert(candidate);
+    }
+    picked.into_values().collect()
+}
+
+/// Expands a path template.
+///
+/// Supported placeholders are `{title}`, `{tid}`, `{season}`, and
+/// `{episode}`; numbers accept a zero-padded width such as `{season:02}`.
+/// `{title}` is made safe for use as a single path component. Unknown
+/// placeholders are kept as written.
+#[must_use]
+pub fn render_path_template(
+    template: &str,
+    title: &str,
+    tid: u32,
+    season: u32,
+    episode: u32,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let (before, tail) = rest.split_at(open);
+        out.push_str(before);
+        let Some(close) = tail.find('}') else {
+            rest = tail;
+            break;
+        };
+        let (placeholder, after) = tail.split_at(close.saturating_add(1));
+        rest = after;
+        let inner = placeholder.trim_start_matches('{').trim_end_matches('}');
+        let (name, width) = inner.split_once(':').unwrap_or((inner, ""));
+        let width: usize = width.parse().unwrap_or(0);
+        let number = match name {
+            "title" => {
+                out.push_str(&sanitize_component(title));
+                continue;
+            }
+            "tid" => tid,
+            "season" => season,
+            "episode" => episode,
+            _ => {
+                out.push_str(placeholder);
+                continue;
+            }
+        };
+        let _ = write!(out, "{number:0width$}");
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replaces characters that are invalid in file names on common platforms.
+#[must_use]
+pub fn sanitize_component(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Trailing dots and spaces are stripped by Windows.
+    let trimmed = cleaned.trim_end_matches(['.', ' ']).trim_start();
+    if trimmed.is_empty() {
+        String::from("_")
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+/// Appends `  <tag>escaped value</tag>\n`.
+fn push_element(out: &mut String, tag: &str, value: &str) {
+    let _ = writeln!(out, "  <{tag}>{}</{tag}>", escape_xml(value));
+}
+
+/// Escapes XML special characters and drops characters XML 1.0 forbids.
+fn escape_xml(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn program(pid: u32, count: Option<u32>, st_time: &str, episode: Option<u64>) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid: 6309,
+            ch_id: 7,
+            tmdb_episode_id: episode,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: st_time.to_owned(),
+            count,
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    fn episode(id: u64, episode_number: u32) -> TmdbEpisode {
+        TmdbEpisode {
+            id,
+            episode_number,
+            name: format!("Episode {episode_number}"),
+            overview: None,
+            air_date: None,
+            season_number: 2,
+            show_id: 1,
+            runtime: None,
+            vote_average: 0.0,
+            episode_type: None,
+        }
+    }
+
+    #[test]
+    fn test_render_tvshow_and_episode_escape_values() {
+        // Arrange
+        let show = TvShowNfo {
+            tid: 6309,
+            title: "A & B <test>",
+            original_title: Some("SPY×FAMILY"),
+            sort_title: None,
+            year: Some(2022),
+            plot: Some("Spy & family"),
+            tmdb_series_id: Some(120_089),
+            poster: Some("poster.jpg"),
+            fanart: None,
+        };
+        let ep = EpisodeNfo {
+            pid: 100,
+            show_title: "A & B <test>",
+            title: None,
+            season: 1,
+            episode: 3,
+            aired: Some("2022-04-23"),
+            plot: Some("\u{1}plot"),
+            tmdb_episode_id: None,
+        };
+
+        // Act
+        let tvshow = render_tvshow(&show);
+        let episode = render_episode(&ep);
+
+        // Assert
+        assert!(tvshow.starts_with(XML_HEADER));
+        assert!(tvshow.contains("  <title>A &amp; B &lt;test&gt;</title>\n"));
+        assert!(tvshow.contains("  <year>2022</year>\n  <plot>Spy &amp; family</plot>\n"));
+        assert!(tvshow.contains(r#"<uniqueid type="tmdb" default="true">120089</uniqueid>"#));
+        assert!(!tvshow.contains("sorttitle"));
+        assert!(tvshow.contains(r#"  <thumb aspect="poster">poster.jpg</thumb>"#));
+        assert!(!tvshow.contains("<fanart>"));
+        assert!(episode.contains("  <title>第3話</title>\n"));
+        assert!(episode.contains("  <season>1</season>\n  <episode>3</episode>\n"));
+        assert!(episode.contains("  <plot>plot</plot>\n"));
+        assert!(episode.contains(r#"<uniqueid type="syoboi">100</uniqueid>"#));
+    }
+
+    #[test]
+    fn test_collect_episodes_prefers_tmdb_numbers_and_first_broadcast() {
+        // Arrange: counts 13/14 map to TMDB episodes 1/2; pid 3 is a rerun of 13
+        let episodes = vec![episode(501, 1), episode(502, 2)];
+        let programs = vec![
+            program(3, Some(13), "2024-04-13 22:00:00", None),
+            program(1, Some(13), "2024-04-06 01:30:00", Some(501)),
+            program(2, Some(14), "2024-04-13 01:30:00", Some(502)),
+            program(4, None, "2024-04-20 01:30:00", None),
+        ];
+
+        // Act
+        let picked = collect_episodes(&programs, &episodes);
+
+        // Assert
+        let summary: Vec<(u32, u32)> = picked.iter().map(|e| (e.number, e.program.pid)).collect();
+        assert_eq!(summary, vec![(1, 1), (2, 2), (13, 3)]);
+        assert_eq!(picked[0].tmdb.unwrap().id, 501);
+        assert!(picked[2].tmdb.is_none());
+    }
+
+    #[test]
+    #[allow(clippy::literal_string_with_formatting_args)]
+    fn test_render_path_template_pads_and_sanitizes() {
+        // Act
+        let dir = render_path_template("{title}/Season {season:02}", "Re:ゼロ?", 1, 2, 5);
+        let file = render_path_template(
+            "S{season:02}E{episode:03} {tid} {unknown}.nfo",
+            "",
+            42,
+            1,
+            7,
+        );
+
+        // Assert
+        assert_eq!(dir, "Re_ゼロ_/Season 02");
+        assert_eq!(file, "S01E007 42 {unknown}.nfo");
+    }
+}
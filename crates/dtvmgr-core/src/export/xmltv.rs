@@ -0,0 +1,350 @@
+//! XMLTV rendering of cached channels and programs.
+
+use core::fmt::Write as _;
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use chrono::NaiveDateTime;
+use dtvmgr_api::syoboi::{SYOBOI_BASE_URL, TitleCategory};
+
+use super::{ExportData, Exporter};
+
+/// UTC offset appended to every programme time (Syoboi times are JST).
+const JST_OFFSET: &str = "+0900";
+
+/// A channel rendered as a `<channel>` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuideChannel<'a> {
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Display name.
+    pub name: &'a str,
+    /// Logo image URL.
+    pub icon: Option<&'a str>,
+    /// Broadcaster website.
+    pub url: Option<&'a str>,
+}
+
+/// A broadcast rendered as a `<programme>` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuideProgramme<'a> {
+    /// Syoboi program ID.
+    pub pid: u32,
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Title name.
+    pub title: &'a str,
+    /// Language of `title` and `desc` (e.g. "ja").
+    pub lang: &'a str,
+    /// Title overview.
+    pub desc: Option<&'a str>,
+    /// Episode subtitle.
+    pub sub_title: Option<&'a str>,
+    /// Episode number.
+    pub count: Option<u32>,
+    /// Syoboi title category code.
+    pub cat: Option<u32>,
+    /// Broadcast start (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub st_time: &'a str,
+    /// Broadcast end (`YYYY-MM-DD HH:MM:SS`, JST).
+    pub ed_time: &'a str,
+}
+
+/// Returns the XMLTV channel ID of a Syoboi channel (`<ch_id>.syoboi.jp`).
+#[must_use]
+pub fn channel_id(ch_id: u32) -> String {
+    format!("{ch_id}.syoboi.jp")
+}
+
+/// Writes the selected programs as an XMLTV guide. Only channels with at
+/// least one selected program are listed.
+#[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::module_name_repetitions)]
+pub struct XmltvExporter;
+
+impl Exporter for XmltvExporter {
+    fn name(&self) -> &'static str {
+        "xmltv"
+    }
+
+    fn description(&self) -> &'static str {
+        "XMLTV guide for DVR software (channels and programmes)"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "dtvmgr.xml"
+    }
+
+    fn export(&self, data: &ExportData<'_>, output: &Path) -> Result<String> {
+        let used_ch_ids: HashSet<u32> = data.programs.iter().map(|p| p.ch_id).collect();
+        let mut channels: Vec<GuideChannel<'_>> = data
+            .channels
+            .values()
+            .filter(|c| used_ch_ids.contains(&c.ch_id))
+            .map(|c| GuideChannel {
+                ch_id: c.ch_id,
+                name: &c.ch_name,
+                icon: c.logo_url.as_deref(),
+                url: c.ch_url.as_deref(),
+            })
+            .collect();
+        channels.sort_by_key(|c| c.ch_id);
+        let programmes: Vec<GuideProgramme<'_>> = data
+            .programs
+            .iter()
+            .map(|p| {
+                let loc = data.localized.get(&p.tid);
+                GuideProgramme {
+                    pid: p.pid,
+                    tid: p.tid,
+                    ch_id: p.ch_id,
+                    title: data.title_name(p.tid),
+                    lang: if loc.is_some() { data.lang } else { "ja" },
+                    desc: loc.and_then(|l| l.overview.as_deref()),
+                    sub_title: p.st_sub_title.as_deref().or(p.sub_title.as_deref()),
+                    count: p.count,
+                    cat: data.titles.get(&p.tid).and_then(|t| t.cat),
+                    st_time: &p.st_time,
+                    ed_time: &p.ed_time,
+                }
+            })
+            .collect();
+        std::fs::write(output, render_guide(&channels, &programmes))
+            .with_context(|| format!("failed to write {}", output.display()))?;
+        Ok(format!(
+            "Exported {} program(s) on {} channel(s) to {}",
+            programmes.len(),
+            channels.len(),
+            output.display()
+        ))
+    }
+}
+
+/// Renders `channels` and `programmes` as a complete XMLTV document.
+///
+/// Programmes whose times cannot be parsed are skipped.
+#[must_use]
+pub fn render_guide(channels: &[GuideChannel<'_>], programmes: &[GuideProgramme<'_>]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<!DOCTYPE tv SYSTEM \"xmltv.dtd\">\n");
+    let _ = writeln!(
+        out,
+        "<tv generator-info-name=\"dtvmgr/{}\" source-info-url=\"{SYOBOI_BASE_URL}/\">",
+        env!("CARGO_PKG_VERSION")
+    );
+    for channel in channels {
+        let _ = writeln!(out, "  <channel id=\"{}\">", channel_id(channel.ch_id));
+        let _ = writeln!(
+            out,
+            "    <display-name lang=\"ja\">{}</display-name>",
+            escape_xml(channel.name)
+        );
+        if let Some(icon) = channel.icon {
+            let _ = writeln!(out, "    <icon src=\"{}\" />", escape_xml(icon));
+        }
+        if let Some(url) = channel.url {
+            let _ = writeln!(out, "    <url>{}</url>", escape_xml(url));
+        }
+        let _ = writeln!(out, "  </channel>");
+    }
+    for programme in programmes {
+        push_programme(&mut out, programme);
+    }
+    out.push_str("</tv>\n");
+    out
+}
+
+/// Appends one `<programme>` element, or nothing if its times are invalid.
+fn push_programme(out: &mut String, programme: &GuideProgramme<'_>) {
+    let (Some(start), Some(stop)) = (
+        to_xmltv_time(programme.st_time),
+        to_xmltv_time(programme.ed_time),
+    ) else {
+        return;
+    };
+    let _ = writeln!(
+        out,
+        "  <programme start=\"{start}\" stop=\"{stop}\" channel=\"{}\">",
+        channel_id(programme.ch_id)
+    );
+    let _ = writeln!(
+        out,
+        "    <title lang=\"{}\">{}</title>",
+        programme.lang,
+        escape_xml(programme.title)
+    );
+    if let Some(sub) = programme.sub_title.filter(|s| !s.is_empty()) {
+        let _ = writeln!(
+            out,
+            "    <sub-title lang=\"ja\">{}</sub-title>",
+            escape_xml(sub)
+        );
+    }
+    if let Some(desc) = programme.desc.filter(|s| !s.is_empty()) {
+        let _ = writeln!(
+            out,
+            "    <desc lang=\"{}\">{}</desc>",
+            programme.lang,
+            escape_xml(desc)
+        );
+    }
+    if let Some(category) = category_name(programme.cat) {
+        let _ = writeln!(out, "    <category lang=\"en\">{category}</category>");
+    }
+    if let Some(count) = programme.count.filter(|c| *c > 0) {
+        // xmltv_ns is zero-based: ".<episode - 1>." without season or part.
+        let _ = writeln!(
+            out,
+            "    <episode-num system=\"xmltv_ns\">.{}.</episode-num>",
+            count.saturating_sub(1)
+        );
+        let _ = writeln!(
+            out,
+            "    <episode-num system=\"onscreen\">#{count}</episode-num>"
+        );
+    }
+    let _ = writeln!(
+        out,
+        "    <url>{SYOBOI_BASE_URL}/tid/{}#{}</url>",
+        programme.tid, programme.pid
+    );
+    out.push_str("  </programme>\n");
+}
+
+/// Maps a Syoboi category to an XMLTV (English) category name.
+const fn category_name(cat: Option<u32>) -> Option<&'static str> {
+    match TitleCategory::from_cat(cat) {
+        TitleCategory::Anime | TitleCategory::AnimeEnded | TitleCategory::AnimeRelated => {
+            Some("Animation")
+        }
+        TitleCategory::Ova => Some("OVA"),
+        TitleCategory::Movie => Some("Movie"),
+        TitleCategory::Tokusatsu => Some("Tokusatsu"),
+        TitleCategory::Radio => Some("Radio"),
+        TitleCategory::Tv => Some("TV"),
+        TitleCategory::Other | TitleCategory::Memo | TitleCategory::Unknown(_) => None,
+    }
+}
+
+/// Converts a JST `YYYY-MM-DD HH:MM:SS` time to `YYYYMMDDhhmmss +0900`.
+fn to_xmltv_time(value: &str) -> Option<String> {
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some(format!("{} {JST_OFFSET}", naive.format("%Y%m%d%H%M%S")))
+}
+
+/// Escapes XML special characters and drops characters XML 1.0 forbids.
+fn escape_xml(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn programme<'a>(st_time: &'a str, sub_title: Option<&'a str>) -> GuideProgramme<'a> {
+        GuideProgramme {
+            pid: 560_001,
+            tid: 6309,
+            ch_id: 7,
+            title: "SPY×FAMILY",
+            lang: "ja",
+            desc: None,
+            sub_title,
+            count: Some(1),
+            cat: Some(1),
+            st_time,
+            ed_time: "2022-04-09 23:30:00",
+        }
+    }
+
+    #[test]
+    fn test_render_guide_channels_and_programmes() {
+        // Arrange
+        let channels = [GuideChannel {
+            ch_id: 7,
+            name: "テレビ東京",
+            icon: Some("http://mirakurun:40772/api/services/3273601024/logo"),
+            url: None,
+        }];
+        let programmes = [programme(
+            "2022-04-09 23:00:00",
+            Some("オペレーション<梟> & 前編"),
+        )];
+
+        // Act
+        let xml = render_guide(&channels, &programmes);
+
+        // Assert
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.ends_with("</tv>\n"));
+        assert!(xml.contains(
+            "<channel id=\"7.syoboi.jp\">\n    <display-name lang=\"ja\">テレビ東京</display-name>"
+        ));
+        assert!(xml.contains(
+            "<icon src=\"http://mirakurun:40772/api/services/3273601024/logo\" />\n  </channel>"
+        ));
+        assert!(xml.contains(
+            "<programme start=\"20220409230000 +0900\" stop=\"20220409233000 +0900\" channel=\"7.syoboi.jp\">"
+        ));
+        assert!(
+            xml.contains("<sub-title lang=\"ja\">オペレーション&lt;梟&gt; &amp; 前編</sub-title>")
+        );
+        assert!(xml.contains("<category lang=\"en\">Animation</category>"));
+        assert!(xml.contains("<episode-num system=\"xmltv_ns\">.0.</episode-num>"));
+        assert!(xml.contains("<url>https://cal.syoboi.jp/tid/6309#560001</url>"));
+    }
+
+    #[test]
+    fn test_render_guide_skips_invalid_times_and_empty_fields() {
+        // Arrange
+        let mut plain = programme("2022-04-16 23:00:00", Some(""));
+        plain.count = None;
+        plain.cat = None;
+        let programmes = [programme("invalid", None), plain];
+
+        // Act
+        let xml = render_guide(&[], &programmes);
+
+        // Assert
+        assert_eq!(xml.matches("<programme ").count(), 1);
+        assert!(!xml.contains("<sub-title"));
+        assert!(!xml.contains("<category"));
+        assert!(!xml.contains("<episode-num"));
+        assert!(!xml.contains("<desc"));
+    }
+
+    #[test]
+    fn test_render_guide_uses_metadata_language() {
+        // Arrange
+        let mut english = programme("2022-04-09 23:00:00", None);
+        english.title = "SPY x FAMILY";
+        english.lang = "en";
+        english.desc = Some("A spy & an assassin");
+
+        // Act
+        let xml = render_guide(&[], &[english]);
+
+        // Assert
+        assert!(xml.contains("<title lang=\"en\">SPY x FAMILY</title>"));
+        assert!(xml.contains("<desc lang=\"en\">A spy &amp; an assassin</desc>"));
+    }
+}
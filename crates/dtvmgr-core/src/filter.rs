@@ -0,0 +1,689 @@
+//! Program filter expressions (`--filter`).
+//!
+//! A small expression language over cached programs, compiled to an SQL
+//! condition for [`ProgramFilter`](dtvmgr_db::ProgramFilter):
+//!
+//! ```text
+//! tid=6309 AND ch IN (7,19) AND flag~new AND st_time>=2024-04-01
+//! ```
+//!
+//! ```text
+//! expr  := and ("OR" and)*
+//! and   := unary ("AND" unary)*
+//! unary := "NOT" unary | "(" expr ")" | field op value
+//!        | field "IN" "(" value ("," value)* ")"
+//! op    := "=" | "!=" | "<" | "<=" | ">" | ">=" | "~"
+//! ```
+//!
+//! Keywords are case-insensitive. Values with spaces or operator characters
+//! are written in double quotes (`sub_title~"第 1 話"`).
+//!
+//! | Field                                   | Operators                  | Values                                  |
+//! |-----------------------------------------|----------------------------|-----------------------------------------|
+//! | `pid`, `tid`, `ch`, `count`, `duration` | `= != < <= > >= IN`        | Integers (`duration` in minutes)        |
+//! | `st_time`, `ed_time`                    | `= != < <= > >=`           | Date or datetime (`=` on a date: that day) |
+//! | `title`, `sub_title`                    | `= != ~ IN`                | Text; `~` matches a substring           |
+//! | `cat`                                   | `= != IN`                  | Category label (`anime`) or `Cat` code  |
+//! | `flag`                                  | `~`                        | `new`, `final`, `rerun`, `caution`, or bits |
+
+use core::fmt::{self, Write as _};
+use core::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use dtvmgr_api::syoboi::{
+    ProgramFlags, TitleCategory, to_naive_datetime_since, to_naive_datetime_until,
+};
+use dtvmgr_db::SqlCondition;
+use rusqlite::types::Value;
+
+/// Stored `st_time` / `ed_time` format.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Program field a condition tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Pid,
+    Tid,
+    Ch,
+    Count,
+    Duration,
+    StTime,
+    EdTime,
+    Title,
+    SubTitle,
+    Cat,
+    Flag,
+}
+
+/// Value type of a [`Field`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Time,
+    Text,
+    Category,
+    Flags,
+}
+
+impl Field {
+    const ALL: [Self; 11] = [
+        Self::Pid,
+        Self::Tid,
+        Self::Ch,
+        Self::Count,
+        Self::Duration,
+        Self::StTime,
+        Self::EdTime,
+        Self::Title,
+        Self::SubTitle,
+        Self::Cat,
+        Self::Flag,
+    ];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Pid => "pid",
+            Self::Tid => "tid",
+            Self::Ch => "ch",
+            Self::Count => "count",
+            Self::Duration => "duration",
+            Self::StTime => "st_time",
+            Self::EdTime => "ed_time",
+            Self::Title => "title",
+            Self::SubTitle => "sub_title",
+            Self::Cat => "cat",
+            Self::Flag => "flag",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        match name.as_str() {
+            "ch_id" => Some(Self::Ch),
+            "duration_min" => Some(Self::Duration),
+            _ => Self::ALL.into_iter().find(|f| f.name() == name),
+        }
+    }
+
+    const fn kind(self) -> Kind {
+        match self {
+            Self::Pid | Self::Tid | Self::Ch | Self::Count | Self::Duration => Kind::Number,
+            Self::StTime | Self::EdTime => Kind::Time,
+            Self::Title | Self::SubTitle => Kind::Text,
+            Self::Cat => Kind::Category,
+            Self::Flag => Kind::Flags,
+        }
+    }
+
+    /// SQL expression of the field. Title fields are read through
+    /// [`Self::title_subquery`].
+    const fn column(self) -> &'static str {
+        match self {
+            Self::Pid => "pid",
+            Self::Tid => "tid",
+            Self::Ch => "ch_id",
+            Self::Count => "count",
+            Self::Duration => "duration_min",
+            Self::StTime => "st_time",
+            Self::EdTime => "ed_time",
+            Self::Title => "title",
+            Self::SubTitle => {
+                "COALESCE(st_sub_title, (SELECT e.sub_title FROM episodes e \
+                 WHERE e.tid = programs.tid AND e.count = programs.count), sub_title)"
+            }
+            Self::Cat => "COALESCE(cat, 0)",
+            Self::Flag => "COALESCE(flag, 0)",
+        }
+    }
+
+    /// Returns `true` for fields of the program's title.
+    const fn title_subquery(self) -> bool {
+        matches!(self, Self::Title | Self::Cat)
+    }
+}
+
+/// Comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+    In,
+}
+
+impl Op {
+    const fn symbol(self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Like => "~",
+            Self::In => "IN",
+        }
+    }
+
+    /// Returns `true` if fields of `kind` support the operator.
+    const fn supports(self, kind: Kind) -> bool {
+        match kind {
+            Kind::Number => !matches!(self, Self::Like),
+            Kind::Time => !matches!(self, Self::Like | Self::In),
+            Kind::Text => matches!(self, Self::Eq | Self::Ne | Self::Like | Self::In),
+            Kind::Category => matches!(self, Self::Eq | Self::Ne | Self::In),
+            Kind::Flags => matches!(self, Self::Like),
+        }
+    }
+}
+
+/// A parsed condition value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand {
+    Int(i64),
+    Text(String),
+    /// Inclusive range of stored times (equal bounds for a datetime).
+    Time {
+        start: String,
+        end: String,
+    },
+}
+
+/// Parsed expression tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    And(Box<Self>, Box<Self>),
+    Or(Box<Self>, Box<Self>),
+    Not(Box<Self>),
+    Compare {
+        field: Field,
+        op: Op,
+        values: Vec<Operand>,
+    },
+}
+
+/// A parsed `--filter` expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct FilterExpr {
+    source: String,
+    node: Node,
+}
+
+impl FilterExpr {
+    /// Parses an expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the position of the first syntax error,
+    /// unknown field, unsupported operator, or invalid value.
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: source.chars().count().saturating_add(1),
+        };
+        let node = parser.or()?;
+        if let Some((col, token)) = parser.peek() {
+            bail!("invalid filter at column {col}: unexpected {token}");
+        }
+        Ok(Self {
+            source: source.to_owned(),
+            node,
+        })
+    }
+
+    /// Compiles the expression to an SQL condition over the `programs`
+    /// table.
+    #[must_use]
+    pub fn to_sql(&self) -> SqlCondition {
+        let mut condition = SqlCondition::default();
+        write_sql(&self.node, &mut condition);
+        condition
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+/// Lexical token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Self::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(w) => write!(f, "`{w}`"),
+            Self::Quoted(q) => write!(f, "\"{q}\""),
+            Self::Op(op) => write!(f, "`{}`", op.symbol()),
+            Self::LParen => f.write_str("`(`"),
+            Self::RParen => f.write_str("`)`"),
+            Self::Comma => f.write_str("`,`"),
+        }
+    }
+}
+
+/// Splits `source` into tokens paired with their 1-based column.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().enumerate().peekable();
+    while let Some((i, c)) = chars.next() {
+        let col = i.saturating_add(1);
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            '~' => Token::Op(Op::Like),
+            '=' => Token::Op(Op::Eq),
+            '!' if chars.next_if(|(_, c)| *c == '=').is_some() => Token::Op(Op::Ne),
+            '<' if chars.next_if(|(_, c)| *c == '=').is_some() => Token::Op(Op::Le),
+            '<' => Token::Op(Op::Lt),
+            '>' if chars.next_if(|(_, c)| *c == '=').is_some() => Token::Op(Op::Ge),
+            '>' => Token::Op(Op::Gt),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => {
+                            if let Some((_, escaped)) = chars.next() {
+                                text.push(escaped);
+                            }
+                        }
+                        Some((_, c)) => text.push(c),
+                        None => bail!("invalid filter at column {col}: unterminated string"),
+                    }
+                }
+                Token::Quoted(text)
+            }
+            c if is_word_char(c) => {
+                let mut word = String::from(c);
+                while let Some((_, c)) = chars.next_if(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+            c => bail!("invalid filter at column {col}: unexpected `{c}`"),
+        };
+        tokens.push((col, token));
+    }
+    Ok(tokens)
+}
+
+/// Returns `true` for characters of unquoted words.
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !"()=,!<>~\"".contains(c)
+}
+
+/// Recursive-descent parser over the tokens.
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    pos: usize,
+    /// Column reported for errors at the end of the input.
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<(usize, &Token)> {
+        self.tokens.get(self.pos).map(|(col, t)| (*col, t))
+    }
+
+    fn next(&mut self) -> Option<(usize, &Token)> {
+        let token = self.tokens.get(self.pos).map(|(col, t)| (*col, t));
+        self.pos = self.pos.saturating_add(1);
+        token
+    }
+
+    /// Consumes the next token if it is the keyword.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|(_, t)| t.is_keyword(keyword));
+        if found {
+            self.pos = self.pos.saturating_add(1);
+        }
+        found
+    }
+
+    /// Consumes the next token, failing unless it equals `expected`.
+    fn expect(&mut self, expected: &Token) -> Result<()> {
+        let end = self.end;
+        match self.next() {
+            Some((_, t)) if t == expected => Ok(()),
+            Some((col, t)) => bail!("invalid filter at column {col}: expected {expected}, got {t}"),
+            None => bail!("invalid filter at column {end}: expected {expected}"),
+        }
+    }
+
+    fn or(&mut self) -> Result<Node> {
+        let mut node = self.and()?;
+        while self.keyword("OR") {
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        let mut node = self.unary()?;
+        while self.keyword("AND") {
+            node = Node::And(Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        if self.keyword("NOT") {
+            return Ok(Node::Not(Box::new(self.unary()?)));
+        }
+        if self.peek().is_some_and(|(_, t)| *t == Token::LParen) {
+            self.pos = self.pos.saturating_add(1);
+            let node = self.or()?;
+            self.expect(&Token::RParen)?;
+            return Ok(node);
+        }
+        self.compare()
+    }
+
+    fn compare(&mut self) -> Result<Node> {
+        let end = self.end;
+        let field = match self.next() {
+            Some((col, Token::Word(name))) => Field::from_name(name).with_context(|| {
+                let names: Vec<&str> = Field::ALL.iter().map(|f| f.name()).collect();
+                format!(
+                    "invalid filter at column {col}: unknown field `{name}` ({})",
+                    names.join(", ")
+                )
+            })?,
+            Some((col, t)) => bail!("invalid filter at column {col}: expected a field, got {t}"),
+            None => bail!("invalid filter at column {end}: expected a field"),
+        };
+        let (col, op) = match self.next() {
+            Some((col, Token::Op(op))) => (col, *op),
+            Some((col, t)) if t.is_keyword("IN") => (col, Op::In),
+            Some((col, t)) => {
+                bail!("invalid filter at column {col}: expected an operator, got {t}")
+            }
+            None => bail!("invalid filter at column {end}: expected an operator"),
+        };
+        if !op.supports(field.kind()) {
+            bail!(
+                "invalid filter at column {col}: `{}` does not support `{}`",
+                field.name(),
+                op.symbol()
+            );
+        }
+        let values = if op == Op::In {
+            self.expect(&Token::LParen)?;
+            let mut values = vec![self.value(field, op)?];
+            while self.peek().is_some_and(|(_, t)| *t == Token::Comma) {
+                self.pos = self.pos.saturating_add(1);
+                values.push(self.value(field, op)?);
+            }
+            self.expect(&Token::RParen)?;
+            values
+        } else {
+            vec![self.value(field, op)?]
+        };
+        Ok(Node::Compare { field, op, values })
+    }
+
+    /// Parses the next token as a value of `field`.
+    fn value(&mut self, field: Field, op: Op) -> Result<Operand> {
+        let end = self.end;
+        let (col, raw) = match self.next() {
+            Some((col, Token::Word(w) | Token::Quoted(w))) => (col, w.as_str()),
+            Some((col, t)) => bail!("invalid filter at column {col}: expected a value, got {t}"),
+            None => bail!("invalid filter at column {end}: expected a value"),
+        };
+        let operand = match field.kind() {
+            Kind::Number => raw.parse::<i64>().ok().map(Operand::Int),
+            Kind::Text => Some(Operand::Text(raw.to_owned())),
+            Kind::Category => raw
+                .parse::<TitleCategory>()
+                .ok()
+                .map(|c| Operand::Int(c.code().into())),
+            Kind::Flags => parse_flags(raw).map(|f| Operand::Int(f.bits().into())),
+            Kind::Time => {
+                let start = to_naive_datetime_since(raw).ok();
+                let end = to_naive_datetime_until(raw).ok();
+                start.zip(end).map(|(start, end)| Operand::Time {
+                    start: start.format(TIME_FORMAT).to_string(),
+                    end: end.format(TIME_FORMAT).to_string(),
+                })
+            }
+        };
+        operand.with_context(|| {
+            format!(
+                "invalid filter at column {col}: invalid value `{raw}` for `{} {}`",
+                field.name(),
+                op.symbol()
+            )
+        })
+    }
+}
+
+/// Parses a flag name or a numeric bitmask.
+fn parse_flags(raw: &str) -> Option<ProgramFlags> {
+    if let Ok(bits) = raw.parse::<u32>() {
+        return Some(ProgramFlags::from_bits_retain(bits));
+    }
+    match raw.to_ascii_lowercase().as_str() {
+        "caution" => Some(ProgramFlags::CAUTION),
+        "new" => Some(ProgramFlags::NEW),
+        "final" => Some(ProgramFlags::FINAL),
+        "rerun" => Some(ProgramFlags::RERUN),
+        _ => None,
+    }
+}
+
+/// Appends the SQL of `node` to `out`.
+fn write_sql(node: &Node, out: &mut SqlCondition) {
+    match node {
+        Node::And(lhs, rhs) | Node::Or(lhs, rhs) => {
+            let joiner = if matches!(node, Node::And(..)) {
+                " AND "
+            } else {
+                " OR "
+            };
+            out.sql.push('(');
+            write_sql(lhs, out);
+            out.sql.push_str(joiner);
+            write_sql(rhs, out);
+            out.sql.push(')');
+        }
+        Node::Not(inner) => {
+            out.sql.push_str("NOT (");
+            write_sql(inner, out);
+            out.sql.push(')');
+        }
+        Node::Compare { field, op, values } => {
+            if field.title_subquery() {
+                out.sql.push_str("tid IN (SELECT tid FROM titles WHERE ");
+                write_compare(*field, *op, values, out);
+                out.sql.push(')');
+            } else {
+                write_compare(*field, *op, values, out);
+            }
+        }
+    }
+}
+
+/// Appends one comparison of `field` to `out`.
+fn write_compare(field: Field, op: Op, values: &[Operand], out: &mut SqlCondition) {
+    let column = field.column();
+    if op == Op::In {
+        let placeholders: Vec<&str> = values.iter().map(|_| "?").collect();
+        let _ = write!(out.sql, "{column} IN ({})", placeholders.join(", "));
+        out.params.extend(values.iter().map(operand_value));
+        return;
+    }
+    let Some(value) = values.first() else {
+        return;
+    };
+    match (value, op) {
+        (Operand::Time { start, end }, Op::Eq | Op::Ne) => {
+            let not = if op == Op::Ne { "NOT " } else { "" };
+            let _ = write!(out.sql, "{column} {not}BETWEEN ? AND ?");
+            out.params.push(Value::Text(start.clone()));
+            out.params.push(Value::Text(end.clone()));
+        }
+        (Operand::Time { start, end }, _) => {
+            // A date-only bound covers the whole day.
+            let bound = if matches!(op, Op::Le | Op::Gt) {
+                end
+            } else {
+                start
+            };
+            let _ = write!(out.sql, "{column} {} ?", op.symbol());
+            out.params.push(Value::Text(bound.clone()));
+        }
+        (Operand::Int(bits), Op::Like) => {
+            let _ = write!(out.sql, "({column} & ?) = ?");
+            out.params.push(Value::Integer(*bits));
+            out.params.push(Value::Integer(*bits));
+        }
+        (Operand::Text(text), Op::Like) => {
+            let _ = write!(out.sql, "{column} LIKE ? ESCAPE '\\'");
+            out.params
+                .push(Value::Text(format!("%{}%", escape_like(text))));
+        }
+        _ => {
+            let _ = write!(out.sql, "{column} {} ?", op.symbol());
+            out.params.push(operand_value(value));
+        }
+    }
+}
+
+/// Returns the SQL parameter of a non-time operand.
+fn operand_value(operand: &Operand) -> Value {
+    match operand {
+        Operand::Int(n) => Value::Integer(*n),
+        Operand::Text(text) => Value::Text(text.clone()),
+        Operand::Time { start, .. } => Value::Text(start.clone()),
+    }
+}
+
+/// Escapes `LIKE` wildcards with `\`.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_parse_compiles_conditions_to_sql() {
+        // Arrange
+        let expr =
+            FilterExpr::parse("tid=6309 AND ch IN (7,19) AND flag~new AND st_time>=2024-04-01")
+                .unwrap();
+
+        // Act
+        let condition = expr.to_sql();
+
+        // Assert
+        assert_eq!(
+            condition.sql,
+            "(((tid = ? AND ch_id IN (?, ?)) AND (COALESCE(flag, 0) & ?) = ?) AND st_time >= ?)"
+        );
+        assert_eq!(
+            condition.params,
+            vec![
+                Value::Integer(6309),
+                Value::Integer(7),
+                Value::Integer(19),
+                Value::Integer(2),
+                Value::Integer(2),
+                Value::Text(String::from("2024-04-01 00:00:00")),
+            ]
+        );
+        assert_eq!(
+            expr.to_string(),
+            "tid=6309 AND ch IN (7,19) AND flag~new AND st_time>=2024-04-01"
+        );
+    }
+
+    #[test]
+    fn test_parse_handles_precedence_titles_and_dates() {
+        // Arrange
+        let expr =
+            FilterExpr::parse(r#"not cat=movie or title~"100%" and st_time<=2024-04-01"#).unwrap();
+
+        // Act
+        let condition = expr.to_sql();
+
+        // Assert: AND binds tighter than OR; a date-only `<=` covers the day
+        assert_eq!(
+            condition.sql,
+            "(NOT (tid IN (SELECT tid FROM titles WHERE COALESCE(cat, 0) = ?)) OR \
+             (tid IN (SELECT tid FROM titles WHERE title LIKE ? ESCAPE '\\') AND st_time <= ?))"
+        );
+        assert_eq!(
+            condition.params,
+            vec![
+                Value::Integer(8),
+                Value::Text(String::from("%100\\%%")),
+                Value::Text(String::from("2024-04-01 23:59:59")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_expressions() {
+        // Arrange
+        let cases = [
+            ("tid=", "column 5: expected a value"),
+            ("foo=1", "unknown field `foo`"),
+            ("flag=new", "`flag` does not support `=`"),
+            ("tid=abc", "invalid value `abc` for `tid =`"),
+            ("st_time>=2024-13-01", "invalid value `2024-13-01`"),
+            ("(tid=1", "expected `)`"),
+            ("tid=1 tid=2", "column 7: unexpected `tid`"),
+            ("title=\"open", "unterminated string"),
+        ];
+
+        // Act & Assert
+        for (source, expected) in cases {
+            let err = FilterExpr::parse(source).unwrap_err().to_string();
+            assert!(err.contains(expected), "{source}: {err}");
+        }
+    }
+}
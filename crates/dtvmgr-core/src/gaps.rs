@@ -0,0 +1,200 @@
+//! Episode count gaps of cached titles.
+//!
+//! Works from the cached programs alone (no TMDB request): for each title,
+//! the `count` sequence between its lowest and highest episode number is
+//! checked for numbers without a program, and counts shared by programs
+//! with different subtitles are flagged. Rebroadcasts of an episode on
+//! several channels share its subtitle and are not flagged. Gaps usually
+//! mean a `db sync` window was skipped.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
+use serde::Serialize;
+
+/// An episode number used by programs with different subtitles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateCount {
+    /// Episode number.
+    pub count: u32,
+    /// Distinct subtitles of the programs with this number, sorted.
+    pub sub_titles: Vec<String>,
+}
+
+/// Count gaps and duplicated counts of one title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TitleGaps {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name.
+    pub title: String,
+    /// Lowest cached episode number.
+    pub first: Option<u32>,
+    /// Highest cached episode number.
+    pub last: Option<u32>,
+    /// Episode numbers between `first` and `last` without a program.
+    pub missing: Vec<u32>,
+    /// Episode numbers shared by programs with different subtitles.
+    pub duplicates: Vec<DuplicateCount>,
+}
+
+impl TitleGaps {
+    /// Returns `true` when the title has missing or duplicated counts.
+    #[must_use]
+    pub const fn has_issues(&self) -> bool {
+        !self.missing.is_empty() || !self.duplicates.is_empty()
+    }
+}
+
+/// Inspects the `count` sequence of `title` in `programs`.
+///
+/// Deleted programs and programs without a count are ignored. Subtitles
+/// are compared after trimming; programs without one never make a count a
+/// duplicate.
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub fn title_gaps(title: &CachedTitle, programs: &[CachedProgram]) -> TitleGaps {
+    let mut sub_titles: BTreeMap<u32, BTreeSet<String>> = BTreeMap::new();
+    for p in programs
+        .iter()
+        .filter(|p| p.tid == title.tid && p.deleted.is_none_or(|d| d == 0))
+    {
+        let Some(count) = p.count else {
+            continue;
+        };
+        let names = sub_titles.entry(count).or_default();
+        if let Some(sub_title) = p.sub_title.as_deref().map(str::trim)
+            && !sub_title.is_empty()
+        {
+            names.insert(sub_title.to_owned());
+        }
+    }
+
+    let first = sub_titles.keys().next().copied();
+    let last = sub_titles.keys().next_back().copied();
+    let missing = match (first, last) {
+        (Some(first), Some(last)) => (first..=last)
+            .filter(|c| !sub_titles.contains_key(c))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let duplicates = sub_titles
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(count, names)| DuplicateCount {
+            count,
+            sub_titles: names.into_iter().collect(),
+        })
+        .collect();
+
+    TitleGaps {
+        tid: title.tid,
+        title: title.title.clone(),
+        first,
+        last,
+        missing,
+        duplicates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(pid: u32, count: Option<u32>, sub_title: Option<&str>) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid: 100,
+            ch_id: 1,
+            tmdb_episode_id: None,
+            st_time: String::from("2024-01-01 23:00:00"),
+            st_offset: None,
+            ed_time: String::from("2024-01-01 23:30:00"),
+            count,
+            sub_title: sub_title.map(str::to_owned),
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    fn title() -> CachedTitle {
+        CachedTitle {
+            tid: 100,
+            tmdb_series_id: None,
+            tmdb_season_number: None,
+            tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
+            title: String::from("Title"),
+            short_title: None,
+            title_yomi: None,
+            title_en: None,
+            cat: Some(1),
+            title_flag: None,
+            first_year: None,
+            first_month: None,
+            keywords: Vec::new(),
+            last_update: String::new(),
+            tmdb_original_name: None,
+            tmdb_name: None,
+            tmdb_alt_titles: None,
+            tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
+        }
+    }
+
+    #[test]
+    fn test_title_gaps_flags_missing_and_duplicated_counts() {
+        // Arrange: 3 and 5 are missing, 2 is rebroadcast with the same
+        // subtitle, 4 has two different subtitles, and 6 is deleted
+        let mut deleted = program(9, Some(6), Some("六"));
+        deleted.deleted = Some(1);
+        let programs = vec![
+            program(1, Some(1), Some("一")),
+            program(2, Some(2), Some("二")),
+            program(3, Some(2), Some(" 二 ")),
+            program(4, Some(4), Some("四")),
+            program(5, Some(4), Some("別の四")),
+            program(6, Some(4), None),
+            program(7, None, Some("特番")),
+            program(8, Some(7), None),
+            deleted,
+        ];
+
+        // Act
+        let gaps = title_gaps(&title(), &programs);
+
+        // Assert
+        assert_eq!(gaps.first, Some(1));
+        assert_eq!(gaps.last, Some(7));
+        assert_eq!(gaps.missing, vec![3, 5, 6]);
+        assert_eq!(
+            gaps.duplicates,
+            vec![DuplicateCount {
+                count: 4,
+                sub_titles: vec![String::from("別の四"), String::from("四")],
+            }]
+        );
+        assert!(gaps.has_issues());
+    }
+
+    #[test]
+    fn test_title_gaps_without_counts_has_no_issues() {
+        // Act
+        let gaps = title_gaps(&title(), &[program(1, None, Some("特番"))]);
+
+        // Assert
+        assert_eq!(gaps.first, None);
+        assert!(gaps.missing.is_empty());
+        assert!(!gaps.has_issues());
+    }
+}
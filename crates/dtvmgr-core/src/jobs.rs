@@ -0,0 +1,509 @@
+//! Background job queue.
+//!
+//! Jobs are persisted in the `jobs` table so queued work, retries, and
+//! failures survive restarts and can be inspected from the CLI.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use dtvmgr_db::Connection;
+use dtvmgr_db::jobs::{JobRecord, insert_job, load_job, load_jobs, load_next_due_job, update_job};
+use tracing::instrument;
+
+/// Timestamp format stored in the `jobs` table (sortable as text).
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Kind of background work a job performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobKind {
+    /// Sync Syoboi data into the local cache.
+    Sync,
+    /// Prefetch TMDB mappings for unmapped titles.
+    Prefetch,
+    /// Delete cached programs past the retention period.
+    Prune,
+    /// Report upcoming programs of watched titles.
+    Notify,
+}
+
+impl JobKind {
+    /// All job kinds.
+    pub const ALL: [Self; 4] = [Self::Sync, Self::Prefetch, Self::Prune, Self::Notify];
+
+    /// Returns the stored name of the kind.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Sync => "sync",
+            Self::Prefetch => "prefetch",
+            Self::Prune => "prune",
+            Self::Notify => "notify",
+        }
+    }
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .with_context(|| format!("unknown job kind: {s}"))
+    }
+}
+
+/// Lifecycle state of a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JobState {
+    /// Waiting for its scheduled time.
+    Queued,
+    /// Currently executing.
+    Running,
+    /// Finished successfully.
+    Succeeded,
+    /// Exhausted all attempts.
+    Failed,
+    /// Cancelled by the user.
+    Cancelled,
+}
+
+impl JobState {
+    /// All job states.
+    pub const ALL: [Self; 5] = [
+        Self::Queued,
+        Self::Running,
+        Self::Succeeded,
+        Self::Failed,
+        Self::Cancelled,
+    ];
+
+    /// Returns the stored name of the state.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Returns `true` for states that will not change without user action.
+    #[must_use]
+    pub const fn is_terminal(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+}
+
+impl fmt::Display for JobState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobState {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|st| st.as_str() == s)
+            .with_context(|| format!("unknown job state: {s}"))
+    }
+}
+
+/// A typed view of a persisted job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    /// Job ID.
+    pub id: i64,
+    /// Job kind.
+    pub kind: JobKind,
+    /// Current state.
+    pub state: JobState,
+    /// Optional kind-specific payload.
+    pub payload: Option<String>,
+    /// Number of attempts started so far.
+    pub attempts: u32,
+    /// Maximum number of attempts.
+    pub max_attempts: u32,
+    /// Error message of the last failed attempt.
+    pub last_error: Option<String>,
+    /// Earliest time the job may run.
+    pub scheduled_at: String,
+    /// Creation timestamp.
+    pub created_at: String,
+    /// Last update timestamp.
+    pub updated_at: String,
+}
+
+impl TryFrom<JobRecord> for Job {
+    type Error = anyhow::Error;
+
+    fn try_from(r: JobRecord) -> Result<Self> {
+        Ok(Self {
+            id: r.id,
+            kind: r.kind.parse()?,
+            state: r.state.parse()?,
+            payload: r.payload,
+            attempts: r.attempts,
+            max_attempts: r.max_attempts,
+            last_error: r.last_error,
+            scheduled_at: r.scheduled_at,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        })
+    }
+}
+
+impl Job {
+    /// Converts back into a storable record.
+    fn to_record(&self) -> JobRecord {
+        JobRecord {
+            id: self.id,
+            kind: self.kind.as_str().to_owned(),
+            state: self.state.as_str().to_owned(),
+            payload: self.payload.clone(),
+            attempts: self.attempts,
+            max_attempts: self.max_attempts,
+            last_error: self.last_error.clone(),
+            scheduled_at: self.scheduled_at.clone(),
+            created_at: self.created_at.clone(),
+            updated_at: self.updated_at.clone(),
+        }
+    }
+}
+
+/// Retry behaviour for failed jobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts allowed per job (including the first).
+    pub max_attempts: u32,
+    /// Delay before the first retry, in seconds. Doubles for each retry.
+    pub base_delay_secs: u32,
+    /// Upper bound for the retry delay, in seconds.
+    pub max_delay_secs: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_secs: 60,
+            max_delay_secs: 3600,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay before retrying after `attempt` (1-based) failed.
+    #[must_use]
+    pub fn delay_after(&self, attempt: u32) -> TimeDelta {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let secs = self
+            .base_delay_secs
+            .saturating_mul(factor)
+            .min(self.max_delay_secs);
+        TimeDelta::seconds(i64::from(secs))
+    }
+}
+
+/// Formats a timestamp for storage.
+fn format_ts(ts: DateTime<Utc>) -> String {
+    ts.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Persistent job queue backed by the local database.
+#[derive(Debug)]
+pub struct JobQueue<'c> {
+    /// Database connection.
+    conn: &'c Connection,
+    /// Retry policy applied to new jobs and failures.
+    policy: RetryPolicy,
+}
+
+impl<'c> JobQueue<'c> {
+    /// Creates a queue with the default retry policy.
+    #[must_use]
+    pub fn new(conn: &'c Connection) -> Self {
+        Self {
+            conn,
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Overrides the retry policy.
+    #[must_use]
+    pub const fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Enqueues a job to run at `run_at` and returns its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    #[instrument(skip_all, fields(kind = %kind), err(level = "error"))]
+    pub fn enqueue(
+        &self,
+        kind: JobKind,
+        payload: Option<&str>,
+        run_at: DateTime<Utc>,
+    ) -> Result<i64> {
+        insert_job(
+            self.conn,
+            kind.as_str(),
+            payload,
+            self.policy.max_attempts,
+            &format_ts(run_at),
+        )
+    }
+
+    /// Lists jobs (newest first), optionally filtered by state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails or a row has an unknown kind/state.
+    pub fn list(&self, state: Option<JobState>) -> Result<Vec<Job>> {
+        load_jobs(self.conn, state.map(JobState::as_str))?
+            .into_iter()
+            .map(Job::try_from)
+            .collect()
+    }
+
+    /// Finds a job by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails or the row is invalid.
+    pub fn find(&self, id: i64) -> Result<Option<Job>> {
+        load_job(self.conn, id)?.map(Job::try_from).transpose()
+    }
+
+    /// Claims the next due job, marking it running and counting the attempt.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn claim_next_due(&self, now: DateTime<Utc>) -> Result<Option<Job>> {
+        let Some(record) = load_next_due_job(self.conn, &format_ts(now))? else {
+            return Ok(None);
+        };
+        let job = Job::try_from(record)?;
+        self.start(job, now).map(Some)
+    }
+
+    /// Claims a specific job immediately, regardless of its schedule.
+    ///
+    /// Queued, failed, and cancelled jobs can be run; failed and cancelled
+    /// jobs get a fresh attempt budget.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job does not exist, is running or already
+    /// succeeded, or the database operation fails.
+    #[instrument(skip(self, now), err(level = "error"))]
+    pub fn claim_now(&self, id: i64, now: DateTime<Utc>) -> Result<Job> {
+        let mut job = self
+            .find(id)?
+            .with_context(|| format!("job {id} not found"))?;
+        match job.state {
+            JobState::Queued => {}
+            JobState::Failed | JobState::Cancelled => {
+                job.attempts = 0;
+                job.max_attempts = self.policy.max_attempts;
+            }
+            JobState::Running | JobState::Succeeded => {
+                bail!("job {id} is {} and cannot be run now", job.state);
+            }
+        }
+        self.start(job, now)
+    }
+
+    /// Marks a job as succeeded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job does not exist or the update fails.
+    pub fn complete(&self, id: i64, now: DateTime<Utc>) -> Result<Job> {
+        self.transition(id, now, |job| {
+            job.state = JobState::Succeeded;
+            job.last_error = None;
+        })
+    }
+
+    /// Records a failed attempt. Reschedules with backoff while attempts
+    /// remain, otherwise marks the job failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job does not exist or the update fails.
+    pub fn fail(&self, id: i64, error: &str, now: DateTime<Utc>) -> Result<Job> {
+        let policy = self.policy;
+        self.transition(id, now, |job| {
+            job.last_error = Some(error.to_owned());
+            if job.attempts < job.max_attempts {
+                job.state = JobState::Queued;
+                let delay = policy.delay_after(job.attempts);
+                job.scheduled_at = format_ts(now.checked_add_signed(delay).unwrap_or(now));
+            } else {
+                job.state = JobState::Failed;
+            }
+        })
+    }
+
+    /// Cancels a queued or running job. Returns `false` if it had already
+    /// reached a terminal state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the job does not exist or the update fails.
+    pub fn cancel(&self, id: i64, now: DateTime<Utc>) -> Result<bool> {
+        let job = self
+            .find(id)?
+            .with_context(|| format!("job {id} not found"))?;
+        if job.state.is_terminal() {
+            return Ok(false);
+        }
+        self.transition(id, now, |job| job.state = JobState::Cancelled)?;
+        Ok(true)
+    }
+
+    /// Marks a job running and counts the attempt.
+    fn start(&self, mut job: Job, now: DateTime<Utc>) -> Result<Job> {
+        job.state = JobState::Running;
+        job.attempts = job.attempts.saturating_add(1);
+        job.updated_at = format_ts(now);
+        update_job(self.conn, &job.to_record())?;
+        Ok(job)
+    }
+
+    /// Loads a job, applies `change`, and persists it.
+    fn transition(
+        &self,
+        id: i64,
+        now: DateTime<Utc>,
+        change: impl FnOnce(&mut Job),
+    ) -> Result<Job> {
+        let mut job = self
+            .find(id)?
+            .with_context(|| format!("job {id} not found"))?;
+        change(&mut job);
+        job.updated_at = format_ts(now);
+        update_job(self.conn, &job.to_record())?;
+        Ok(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use chrono::TimeZone as _;
+    use dtvmgr_db::open_db;
+
+    use super::*;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        (conn, dir)
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn job_kind_and_state_round_trip() {
+        // Arrange & Act & Assert
+        for kind in JobKind::ALL {
+            assert_eq!(kind.as_str().parse::<JobKind>().unwrap(), kind);
+        }
+        for state in JobState::ALL {
+            assert_eq!(state.as_str().parse::<JobState>().unwrap(), state);
+        }
+        assert!("bogus".parse::<JobKind>().is_err());
+    }
+
+    #[test]
+    fn retry_delay_doubles_and_caps() {
+        // Arrange
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay_secs: 60,
+            max_delay_secs: 200,
+        };
+
+        // Act & Assert
+        assert_eq!(policy.delay_after(1), TimeDelta::seconds(60));
+        assert_eq!(policy.delay_after(2), TimeDelta::seconds(120));
+        assert_eq!(policy.delay_after(3), TimeDelta::seconds(200));
+        assert_eq!(policy.delay_after(40), TimeDelta::seconds(200));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn failed_job_retries_with_backoff_then_fails() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let queue = JobQueue::new(&conn).with_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay_secs: 3600,
+            max_delay_secs: 3600,
+        });
+        let id = queue.enqueue(JobKind::Sync, None, at(0)).unwrap();
+
+        // Act: first attempt fails and is rescheduled one hour later
+        let first = queue.claim_next_due(at(0)).unwrap().unwrap();
+        let retried = queue.fail(id, "boom", at(0)).unwrap();
+        let not_yet = queue.claim_next_due(at(0)).unwrap();
+        let second = queue.claim_next_due(at(1)).unwrap().unwrap();
+        let failed = queue.fail(id, "boom again", at(1)).unwrap();
+
+        // Assert
+        assert_eq!(first.attempts, 1);
+        assert_eq!(retried.state, JobState::Queued);
+        assert_eq!(retried.scheduled_at, "2024-01-01T01:00:00Z");
+        assert!(not_yet.is_none());
+        assert_eq!(second.attempts, 2);
+        assert_eq!(failed.state, JobState::Failed);
+        assert_eq!(failed.last_error.as_deref(), Some("boom again"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn cancel_and_run_now() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let queue = JobQueue::new(&conn);
+        let id = queue.enqueue(JobKind::Prune, Some("90"), at(5)).unwrap();
+
+        // Act
+        let cancelled = queue.cancel(id, at(0)).unwrap();
+        let cancelled_again = queue.cancel(id, at(0)).unwrap();
+        let claimed = queue.claim_now(id, at(0)).unwrap();
+        let done = queue.complete(id, at(0)).unwrap();
+        let rerun = queue.claim_now(id, at(0));
+
+        // Assert
+        assert!(cancelled);
+        assert!(!cancelled_again);
+        assert_eq!(claimed.state, JobState::Running);
+        assert_eq!(claimed.payload.as_deref(), Some("90"));
+        assert_eq!(done.state, JobState::Succeeded);
+        assert!(rerun.is_err());
+        assert_eq!(queue.list(Some(JobState::Succeeded)).unwrap().len(), 1);
+    }
+}
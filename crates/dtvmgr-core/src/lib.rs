@@ -0,0 +1,39 @@
+//! Shared application services for dtvmgr.
+//!
+//! Hosts logic that sits between the API/DB layers and the CLI, so it can be
+//! reused by long-running modes (daemon, server).
+
+/// Heuristic TMDB series matching for unmapped titles.
+pub mod automatch;
+/// Retry budget shared across a whole run.
+pub mod budget;
+/// Read-through cache of Syoboi lookups in the local DB.
+pub mod cache;
+/// Resolution of Mirakurun / `EPGStation` channel lists to Syoboi channels.
+pub mod channel_map;
+/// Overlapping broadcast detection.
+pub mod conflicts;
+/// Episode coverage of mapped titles against TMDB.
+pub mod coverage;
+/// Export of cached data to external file formats.
+pub mod export;
+/// Program filter expressions (`--filter`) compiled to SQL.
+pub mod filter;
+/// Episode count gaps and duplicated counts of cached titles.
+pub mod gaps;
+/// Background job queue with retries and persistence.
+pub mod jobs;
+/// Sorting, flag filtering, and limiting of program listings.
+pub mod listing;
+/// TMDB episode matching for cached programs.
+pub mod matcher;
+/// Fuzzy normalization of Japanese title names.
+pub mod normalize;
+/// Webhook notifications for new and rescheduled programs.
+pub mod notify;
+/// Keyword / channel / time recording rules.
+pub mod rules;
+/// Season splitting of long-running titles.
+pub mod seasons;
+/// Syoboi-to-DB sync engine.
+pub mod sync;
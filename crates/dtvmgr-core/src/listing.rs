@@ -0,0 +1,309 @@
+//! Sorting, flag filtering, and limiting of program listings.
+//!
+//! Shared by `syoboi prog` (API programs) and the DB-backed listings so the
+//! same options behave identically regardless of where the programs come
+//! from.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use dtvmgr_api::syoboi::{ProgramFlags, SyoboiProgram};
+use dtvmgr_db::programs::CachedProgram;
+
+use crate::rules::RuleMatch;
+
+/// Fields of a program used for sorting and filtering.
+pub trait ProgramRecord {
+    /// Syoboi program ID.
+    fn pid(&self) -> u32;
+    /// Syoboi title ID.
+    fn tid(&self) -> u32;
+    /// Channel ID.
+    fn ch_id(&self) -> u32;
+    /// Broadcast start.
+    fn st_time(&self) -> &str;
+    /// Syoboi flag bitmask.
+    fn flags(&self) -> ProgramFlags;
+}
+
+impl ProgramRecord for SyoboiProgram {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+    fn tid(&self) -> u32 {
+        self.tid
+    }
+    fn ch_id(&self) -> u32 {
+        self.ch_id
+    }
+    fn st_time(&self) -> &str {
+        &self.st_time
+    }
+    fn flags(&self) -> ProgramFlags {
+        Self::flags(self)
+    }
+}
+
+impl ProgramRecord for CachedProgram {
+    fn pid(&self) -> u32 {
+        self.pid
+    }
+    fn tid(&self) -> u32 {
+        self.tid
+    }
+    fn ch_id(&self) -> u32 {
+        self.ch_id
+    }
+    fn st_time(&self) -> &str {
+        &self.st_time
+    }
+    fn flags(&self) -> ProgramFlags {
+        ProgramFlags::from_flag(self.flag)
+    }
+}
+
+impl ProgramRecord for RuleMatch<'_> {
+    fn pid(&self) -> u32 {
+        self.program.pid
+    }
+    fn tid(&self) -> u32 {
+        self.program.tid
+    }
+    fn ch_id(&self) -> u32 {
+        self.program.ch_id
+    }
+    fn st_time(&self) -> &str {
+        &self.program.st_time
+    }
+    fn flags(&self) -> ProgramFlags {
+        ProgramFlags::from_flag(self.program.flag)
+    }
+}
+
+/// Sort key of a program listing. Ties are broken by start time and PID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramSort {
+    /// Broadcast start.
+    StTime,
+    /// Title ID.
+    Tid,
+    /// Channel ID.
+    Channel,
+}
+
+impl ProgramSort {
+    /// All sort keys.
+    pub const ALL: [Self; 3] = [Self::StTime, Self::Tid, Self::Channel];
+
+    /// Returns the option value of the key.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::StTime => "st_time",
+            Self::Tid => "tid",
+            Self::Channel => "channel",
+        }
+    }
+}
+
+impl fmt::Display for ProgramSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProgramSort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .with_context(|| format!("unknown sort key: {s} (st_time, tid, channel)"))
+    }
+}
+
+/// Syoboi flag a listed program must carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagFilter {
+    /// New series or season (新, bit 2).
+    New,
+    /// Final episode (終, bit 4).
+    Final,
+    /// Rerun (再, bit 8).
+    Rerun,
+}
+
+impl FlagFilter {
+    /// All flag filters.
+    pub const ALL: [Self; 3] = [Self::New, Self::Final, Self::Rerun];
+
+    /// Returns the option value of the filter.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Final => "final",
+            Self::Rerun => "rerun",
+        }
+    }
+
+    /// Returns the Syoboi flag of the filter.
+    #[must_use]
+    pub const fn flags(self) -> ProgramFlags {
+        match self {
+            Self::New => ProgramFlags::NEW,
+            Self::Final => ProgramFlags::FINAL,
+            Self::Rerun => ProgramFlags::RERUN,
+        }
+    }
+}
+
+impl fmt::Display for FlagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FlagFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .with_context(|| format!("unknown flag filter: {s} (new, final, rerun)"))
+    }
+}
+
+/// Sort, filter, and limit options of a program listing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgramQuery {
+    /// Sort key (`None` keeps the source order).
+    pub sort: Option<ProgramSort>,
+    /// Required flag.
+    pub flag: Option<FlagFilter>,
+    /// Maximum number of programs, applied after sorting and filtering.
+    pub limit: Option<usize>,
+}
+
+impl ProgramQuery {
+    /// Filters, sorts, and truncates `programs`.
+    #[must_use]
+    pub fn apply<T: ProgramRecord>(&self, mut programs: Vec<T>) -> Vec<T> {
+        if let Some(flag) = self.flag {
+            programs.retain(|p| p.flags().contains(flag.flags()));
+        }
+        if let Some(sort) = self.sort {
+            programs.sort_by(|a, b| {
+                let key = match sort {
+                    ProgramSort::StTime => core::cmp::Ordering::Equal,
+                    ProgramSort::Tid => a.tid().cmp(&b.tid()),
+                    ProgramSort::Channel => a.ch_id().cmp(&b.ch_id()),
+                };
+                key.then_with(|| a.st_time().cmp(b.st_time()))
+                    .then_with(|| a.pid().cmp(&b.pid()))
+            });
+        }
+        if let Some(limit) = self.limit {
+            programs.truncate(limit);
+        }
+        programs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn program(pid: u32, tid: u32, ch_id: u32, st_time: &str, flag: Option<u32>) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid,
+            ch_id,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: st_time.to_owned(),
+            count: None,
+            sub_title: None,
+            flag,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    fn programs() -> Vec<CachedProgram> {
+        vec![
+            program(1, 20, 7, "2024-04-02 23:00:00", Some(2)),
+            program(2, 10, 1, "2024-04-03 23:00:00", Some(8)),
+            program(3, 20, 1, "2024-04-01 23:00:00", Some(6)),
+            program(4, 10, 7, "2024-04-01 22:00:00", None),
+        ]
+    }
+
+    fn pids(programs: &[CachedProgram]) -> Vec<u32> {
+        programs.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn test_apply_sorts_by_key_then_start() {
+        // Arrange
+        let by = |sort| ProgramQuery {
+            sort: Some(sort),
+            ..ProgramQuery::default()
+        };
+
+        // Act & Assert
+        assert_eq!(
+            pids(&by(ProgramSort::StTime).apply(programs())),
+            [4, 3, 1, 2]
+        );
+        assert_eq!(pids(&by(ProgramSort::Tid).apply(programs())), [4, 2, 3, 1]);
+        assert_eq!(
+            pids(&by(ProgramSort::Channel).apply(programs())),
+            [3, 2, 4, 1]
+        );
+        assert_eq!(
+            pids(&ProgramQuery::default().apply(programs())),
+            [1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_apply_filters_flags_and_limits() {
+        // Arrange
+        let query = ProgramQuery {
+            sort: Some(ProgramSort::StTime),
+            flag: Some(FlagFilter::New),
+            limit: Some(1),
+        };
+        let finals = ProgramQuery {
+            flag: Some(FlagFilter::Final),
+            ..ProgramQuery::default()
+        };
+
+        // Act & Assert
+        assert_eq!(pids(&query.apply(programs())), [3]);
+        assert_eq!(pids(&finals.apply(programs())), [3]);
+    }
+
+    #[test]
+    fn test_parse_sort_and_flag_filter() {
+        // Arrange & Act & Assert
+        assert_eq!(
+            "channel".parse::<ProgramSort>().unwrap(),
+            ProgramSort::Channel
+        );
+        assert_eq!("rerun".parse::<FlagFilter>().unwrap(), FlagFilter::Rerun);
+        assert!("title".parse::<ProgramSort>().is_err());
+        assert!("repeat".parse::<FlagFilter>().is_err());
+    }
+}
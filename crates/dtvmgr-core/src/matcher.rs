@@ -0,0 +1,387 @@
+//! Automatic TMDB episode matching for cached programs.
+//!
+//! Programs are mapped to the episodes of the title's TMDB season by
+//! episode number (`count`), falling back to the broadcast date when the
+//! count is missing or out of range. Syoboi counts keep running across
+//! cours while TMDB restarts numbering per season, so the count offset is
+//! inferred from programs whose broadcast date matches exactly one episode.
+//! Titles with season ranges are matched range by range instead, with the
+//! counts rebased to the start of each range. Titles mapped to a TMDB
+//! episode group are matched against the group's episodes in absolute
+//! order, which usually follows Syoboi counts directly.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use dtvmgr_api::tmdb::{LocalTmdbApi, TmdbEpisode};
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::season_ranges::SeasonRange;
+use dtvmgr_db::titles::CachedTitle;
+use tracing::instrument;
+
+/// Hour (JST) before which a broadcast may be listed under the previous day.
+const LATE_NIGHT_END_HOUR: u32 = 5;
+
+/// How a program was matched to an episode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMethod {
+    /// Episode number equals the program count (after the season offset).
+    Count,
+    /// Episode air date equals the broadcast date.
+    AirDate,
+}
+
+/// A program mapped to a TMDB episode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpisodeMatch {
+    /// Syoboi program ID.
+    pub pid: u32,
+    /// Matched TMDB episode ID.
+    pub tmdb_episode_id: u64,
+    /// How the match was made.
+    pub method: MatchMethod,
+}
+
+/// Fetches the TMDB season mapped to `title` and matches `programs` against
+/// its episodes.
+///
+/// Titles without `tmdb_series_id` yield no matches; a missing season number
+/// defaults to season 1. Programs and season ranges of other titles are
+/// ignored. When the title has season ranges, each range is matched against
+/// its own season and programs without a count are left unmatched. An
+/// episode group mapping takes precedence over both.
+///
+/// # Errors
+///
+/// Returns an error if a season or episode group request fails.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, fields(tid = title.tid), err(level = "error"))]
+pub async fn match_title<A: LocalTmdbApi>(
+    api: &A,
+    title: &CachedTitle,
+    programs: &[CachedProgram],
+    ranges: &[SeasonRange],
+    language: &str,
+) -> Result<Vec<EpisodeMatch>> {
+    let Some(series_id) = title.tmdb_series_id else {
+        return Ok(Vec::new());
+    };
+    let own = || -> Vec<CachedProgram> {
+        programs
+            .iter()
+            .filter(|p| p.tid == title.tid)
+            .cloned()
+            .collect()
+    };
+    if let Some(group_id) = title.tmdb_episode_group_id.as_deref() {
+        let group = api
+            .episode_group(group_id, language)
+            .await
+            .with_context(|| format!("failed to fetch episode group {group_id}"))?;
+        return Ok(match_programs(&own(), &group.absolute_episodes()));
+    }
+    let ranges: Vec<&SeasonRange> = ranges.iter().filter(|r| r.tid == title.tid).collect();
+    if !ranges.is_empty() {
+        let mut matches = Vec::new();
+        for range in ranges {
+            let season_number = range.season_number;
+            let season = api
+                .tv_season(series_id, season_number, language)
+                .await
+                .with_context(|| {
+                    format!("failed to fetch season {season_number} of series {series_id}")
+                })?;
+            matches.extend(match_programs(
+                &rebase_counts(title.tid, programs, range),
+                &season.episodes,
+            ));
+        }
+        return Ok(matches);
+    }
+    let season_number = title.tmdb_season_number.unwrap_or(1);
+    let season = api
+        .tv_season(series_id, season_number, language)
+        .await
+        .with_context(|| format!("failed to fetch season {season_number} of series {series_id}"))?;
+    Ok(match_programs(&own(), &season.episodes))
+}
+
+/// Matches a single program with one TMDB episode request instead of
+/// fetching the whole season.
+///
+/// The program count is used as the episode number, rebased to the season
+/// range containing it; unlike [`match_title`] no count offset is inferred.
+/// Programs without a count and episodes unknown to TMDB (HTTP 404) yield
+/// `None`. Titles mapped to an episode group fall back to [`match_title`].
+///
+/// # Errors
+///
+/// Returns an error if the episode or episode group request fails.
+#[allow(clippy::future_not_send)]
+#[instrument(skip_all, fields(tid = title.tid, pid = program.pid), err(level = "error"))]
+pub async fn match_program<A: LocalTmdbApi>(
+    api: &A,
+    title: &CachedTitle,
+    program: &CachedProgram,
+    ranges: &[SeasonRange],
+    language: &str,
+) -> Result<Option<EpisodeMatch>> {
+    let Some(series_id) = title.tmdb_series_id else {
+        return Ok(None);
+    };
+    if title.tmdb_episode_group_id.is_some() {
+        let matches =
+            match_title(api, title, core::slice::from_ref(program), ranges, language).await?;
+        return Ok(matches.into_iter().next());
+    }
+    let Some(count) = program.count else {
+        return Ok(None);
+    };
+    let (season_number, episode_number) = ranges
+        .iter()
+        .find(|r| r.tid == title.tid && r.contains(count))
+        .map_or_else(
+            || (title.tmdb_season_number.unwrap_or(1), count),
+            |r| {
+                (
+                    r.season_number,
+                    count.saturating_sub(r.first_count).saturating_add(1),
+                )
+            },
+        );
+    match api
+        .tv_episode(series_id, season_number, episode_number, language)
+        .await
+    {
+        Ok(episode) => Ok(Some(EpisodeMatch {
+            pid: program.pid,
+            tmdb_episode_id: episode.id,
+            method: MatchMethod::Count,
+        })),
+        Err(e) if e.status() == Some(404) => Ok(None),
+        Err(e) => Err(e).with_context(|| {
+            format!(
+                "failed to fetch episode {episode_number} of season {season_number} of series {series_id}"
+            )
+        }),
+    }
+}
+
+/// Returns the programs of `tid` whose count falls into `range`, with the
+/// count rebased so that `range.first_count` becomes 1.
+pub(crate) fn rebase_counts(
+    tid: u32,
+    programs: &[CachedProgram],
+    range: &SeasonRange,
+) -> Vec<CachedProgram> {
+    programs
+        .iter()
+        .filter(|p| p.tid == tid)
+        .filter_map(|p| {
+            let count = p.count.filter(|c| range.contains(*c))?;
+            let mut rebased = p.clone();
+            rebased.count = Some(count.saturating_sub(range.first_count).saturating_add(1));
+            Some(rebased)
+        })
+        .collect()
+}
+
+/// Matches programs to episodes by count, then by broadcast date.
+///
+/// Programs that match nothing are left out of the result.
+#[must_use]
+pub fn match_programs(programs: &[CachedProgram], episodes: &[TmdbEpisode]) -> Vec<EpisodeMatch> {
+    let by_number: HashMap<u32, u64> = episodes.iter().map(|e| (e.episode_number, e.id)).collect();
+    let mut by_date: HashMap<&str, Vec<u64>> = HashMap::new();
+    for e in episodes {
+        if let Some(date) = e.air_date.as_deref() {
+            by_date.entry(date).or_default().push(e.id);
+        }
+    }
+    let number_of: HashMap<u64, u32> = episodes.iter().map(|e| (e.id, e.episode_number)).collect();
+    let date_match = |p: &CachedProgram| -> Option<u64> {
+        broadcast_dates(&p.st_time).into_iter().find_map(|date| {
+            match by_date.get(date.as_str())?.as_slice() {
+                [only] => Some(*only),
+                _ => None,
+            }
+        })
+    };
+
+    // Most common (count - episode_number) among date-anchored programs.
+    let mut offsets: BTreeMap<i64, usize> = BTreeMap::new();
+    for p in programs {
+        if let (Some(count), Some(id)) = (p.count, date_match(p))
+            && let Some(&number) = number_of.get(&id)
+        {
+            let diff = i64::from(count).saturating_sub(i64::from(number));
+            let n = offsets.entry(diff).or_default();
+            *n = n.saturating_add(1);
+        }
+    }
+    let offset = offsets
+        .iter()
+        .max_by_key(|&(diff, n)| (*n, core::cmp::Reverse(diff.abs())))
+        .map_or(0, |(diff, _)| *diff);
+
+    programs
+        .iter()
+        .filter_map(|p| {
+            let by_count = p.count.and_then(|count| {
+                let number = i64::from(count).checked_sub(offset)?;
+                by_number.get(&u32::try_from(number).ok()?).copied()
+            });
+            let (tmdb_episode_id, method) = by_count
+                .map(|id| (id, MatchMethod::Count))
+                .or_else(|| date_match(p).map(|id| (id, MatchMethod::AirDate)))?;
+            Some(EpisodeMatch {
+                pid: p.pid,
+                tmdb_episode_id,
+                method,
+            })
+        })
+        .collect()
+}
+
+/// Returns the candidate air dates (`YYYY-MM-DD`) of a broadcast starting at
+/// `st_time` (`YYYY-MM-DD HH:MM:SS`, JST).
+///
+/// Late-night broadcasts are often listed under the previous day, which is
+/// tried second.
+fn broadcast_dates(st_time: &str) -> Vec<String> {
+    let Ok(start) = chrono::NaiveDateTime::parse_from_str(st_time, "%Y-%m-%d %H:%M:%S") else {
+        return Vec::new();
+    };
+    let date = start.date();
+    let mut dates = vec![date.format("%Y-%m-%d").to_string()];
+    if chrono::Timelike::hour(&start) < LATE_NIGHT_END_HOUR
+        && let Some(prev) = date.pred_opt()
+    {
+        dates.push(prev.format("%Y-%m-%d").to_string());
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn program(pid: u32, count: Option<u32>, st_time: &str) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid: 100,
+            ch_id: 1,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: st_time.to_owned(),
+            count,
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    fn episode(id: u64, episode_number: u32, air_date: &str) -> TmdbEpisode {
+        TmdbEpisode {
+            id,
+            episode_number,
+            name: format!("Episode {episode_number}"),
+            overview: None,
+            air_date: Some(air_date.to_owned()),
+            season_number: 2,
+            show_id: 1,
+            runtime: None,
+            vote_average: 0.0,
+            episode_type: None,
+        }
+    }
+
+    #[test]
+    fn test_match_programs_by_count_and_air_date() {
+        // Arrange: the second program has no count
+        let episodes = vec![episode(501, 1, "2024-01-05"), episode(502, 2, "2024-01-12")];
+        let programs = vec![
+            program(1, Some(1), "2024-01-05 23:00:00"),
+            program(2, None, "2024-01-12 23:00:00"),
+            program(3, Some(9), "2024-03-01 23:00:00"),
+        ];
+
+        // Act
+        let matches = match_programs(&programs, &episodes);
+
+        // Assert
+        assert_eq!(
+            matches,
+            vec![
+                EpisodeMatch {
+                    pid: 1,
+                    tmdb_episode_id: 501,
+                    method: MatchMethod::Count,
+                },
+                EpisodeMatch {
+                    pid: 2,
+                    tmdb_episode_id: 502,
+                    method: MatchMethod::AirDate,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_programs_infers_count_offset_from_late_night_dates() {
+        // Arrange: second cour counts 13.. against season 2 episodes 1..,
+        // broadcast after midnight and listed on the previous day
+        let episodes = vec![
+            episode(601, 1, "2024-04-05"),
+            episode(602, 2, "2024-04-12"),
+            episode(603, 3, "2024-04-19"),
+        ];
+        let programs = vec![
+            program(1, Some(13), "2024-04-06 01:30:00"),
+            program(2, Some(14), "2024-04-13 01:30:00"),
+            // Rerun on another channel a week later: matched by count only
+            program(3, Some(13), "2024-04-13 22:00:00"),
+            program(4, Some(15), "2024-04-20 01:30:00"),
+        ];
+
+        // Act
+        let matches = match_programs(&programs, &episodes);
+
+        // Assert
+        let ids: Vec<(u32, u64)> = matches.iter().map(|m| (m.pid, m.tmdb_episode_id)).collect();
+        assert_eq!(ids, vec![(1, 601), (2, 602), (3, 601), (4, 603)]);
+        assert!(matches.iter().all(|m| m.method == MatchMethod::Count));
+    }
+
+    #[test]
+    fn test_rebase_counts_keeps_programs_in_range() {
+        // Arrange
+        let programs = vec![
+            program(1, Some(12), "2024-01-05 23:00:00"),
+            program(2, Some(13), "2024-04-05 23:00:00"),
+            program(3, None, "2024-04-12 23:00:00"),
+            program(4, Some(14), "2024-04-12 23:00:00"),
+        ];
+        let range = SeasonRange {
+            tid: 100,
+            first_count: 13,
+            last_count: None,
+            season_number: 2,
+        };
+
+        // Act
+        let rebased = rebase_counts(100, &programs, &range);
+
+        // Assert
+        let counts: Vec<(u32, Option<u32>)> = rebased.iter().map(|p| (p.pid, p.count)).collect();
+        assert_eq!(counts, vec![(2, Some(1)), (4, Some(2))]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Fuzzy normalization of Japanese title names.
+//!
+//! Syoboi, TMDB, and user queries spell the same series differently:
+//! full-width vs half-width letters, katakana vs hiragana, and season
+//! markers such as "第2期" or "Season 2". [`normalize_title`] folds these
+//! away so names can be compared with plain string equality or containment.
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use anyhow::{Context, Result};
+use dtvmgr_db::Connection;
+use dtvmgr_db::titles::CachedTitle;
+use regex::Regex;
+use unicode_normalization::UnicodeNormalization;
+
+/// Season markers ("第2期", "第二クール", "Season 2", "2nd Season", "2期"),
+/// matched after NFKC and lowercasing.
+#[allow(clippy::expect_used)]
+static SEASON_MARKER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"第\s*[0-9〇一二三四五六七八九十]+\s*(?:期|クール|シーズン|シリーズ|部|章)|(?:season|シーズン)\s*[0-9]+|[0-9]+\s*(?:st|nd|rd|th)\s*(?:season|シーズン|クール)|[0-9]+期",
+    )
+    .expect("failed to compile season marker regex")
+});
+
+/// Normalizes a title name for fuzzy comparison.
+///
+/// Applies NFKC (full-width / half-width folding), lowercases, removes
+/// season markers, maps katakana to hiragana, and keeps only letters and
+/// digits, so brackets, spaces, and symbols such as "×" disappear. When a
+/// name consists only of a season marker, the marker is kept.
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub fn normalize_title(name: &str) -> String {
+    let folded: String = name.nfkc().flat_map(char::to_lowercase).collect();
+    let stripped = SEASON_MARKER_RE.replace_all(&folded, " ");
+    let key = fold_chars(&stripped);
+    if key.is_empty() {
+        fold_chars(&folded)
+    } else {
+        key
+    }
+}
+
+/// Returns `true` when `a` and `b` normalize to the same non-empty name.
+#[must_use]
+pub fn titles_match(a: &str, b: &str) -> bool {
+    let a = normalize_title(a);
+    !a.is_empty() && a == normalize_title(b)
+}
+
+/// Maps katakana to hiragana and drops everything but letters and digits.
+fn fold_chars(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| match c {
+            'ァ'..='ヶ' => char::from_u32(u32::from(c).saturating_sub(0x60)).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Searches cached titles like [`dtvmgr_db::search_titles`], then fills the
+/// remaining `limit` with titles whose normalized name, reading, English
+/// title, or TMDB name contains the normalized query.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+pub fn search_titles_normalized(
+    conn: &Connection,
+    query: &str,
+    limit: u32,
+) -> Result<Vec<CachedTitle>> {
+    let mut found =
+        dtvmgr_db::search_titles(conn, query, limit).context("failed to search titles")?;
+    let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+    let key = normalize_title(query);
+    if found.len() >= limit || key.is_empty() {
+        return Ok(found);
+    }
+
+    let seen: HashSet<u32> = found.iter().map(|t| t.tid).collect();
+    for title in dtvmgr_db::load_titles(conn).context("failed to load titles")? {
+        if found.len() >= limit {
+            break;
+        }
+        let names = [
+            Some(title.title.as_str()),
+            title.short_title.as_deref(),
+            title.title_yomi.as_deref(),
+            title.title_en.as_deref(),
+            title.tmdb_name.as_deref(),
+            title.tmdb_original_name.as_deref(),
+        ];
+        if !seen.contains(&title.tid)
+            && names
+                .into_iter()
+                .flatten()
+                .any(|name| normalize_title(name).contains(&key))
+        {
+            found.push(title);
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_title_folds_width_kana_and_symbols() {
+        // Arrange & Act & Assert
+        assert_eq!(normalize_title("ＳＰＹ×ＦＡＭＩＬＹ"), "spyfamily");
+        assert_eq!(normalize_title("ｽﾊﾟｲﾌｧﾐﾘｰ"), "すぱいふぁみりー");
+        assert_eq!(normalize_title("【推しの子】"), "推しの子");
+        assert_eq!(normalize_title("ゆるキャン△"), "ゆるきゃん");
+    }
+
+    #[test]
+    fn test_normalize_title_removes_season_markers() {
+        // Arrange & Act & Assert
+        assert!(titles_match("SPY×FAMILY Season 2", "SPY×FAMILY 第2クール"));
+        assert!(titles_match("進撃の巨人 第二期", "進撃の巨人"));
+        assert!(titles_match(
+            "ゆるキャン△ 2nd Season",
+            "ゆるキャン△ SEASON2"
+        ));
+        assert!(titles_match("薬屋のひとりごと 2期", "薬屋のひとりごと"));
+        assert_eq!(normalize_title("第2期"), "第2期");
+        assert!(!titles_match("SPY×FAMILY", "Dragon Ball"));
+        assert!(!titles_match("", ""));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_search_titles_normalized_matches_season_variant() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update) VALUES
+                 (6309, 'SPY×FAMILY 第2クール', '2023-10-01 00:00:00'),
+                 (7000, 'Dragon Ball', '2023-10-01 00:00:00');",
+        )
+        .unwrap();
+
+        // Act
+        let raw = dtvmgr_db::search_titles(&conn, "ＳＰＹ×ＦＡＭＩＬＹ Season 2", 10).unwrap();
+        let found = search_titles_normalized(&conn, "ＳＰＹ×ＦＡＭＩＬＹ Season 2", 10).unwrap();
+
+        // Assert
+        assert!(raw.is_empty());
+        let tids: Vec<u32> = found.iter().map(|t| t.tid).collect();
+        assert_eq!(tids, vec![6309]);
+    }
+}
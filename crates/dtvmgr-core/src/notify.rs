@@ -0,0 +1,469 @@
+//! Webhook notifications for program changes found during sync, and the
+//! daily email digest.
+//!
+//! Freshly fetched programs of followed titles are compared with the cached
+//! ones; new programs and moved start/end times become [`ProgramChange`]s.
+//! Each change is rendered with a line template and the summary is wrapped
+//! in a generic JSON or Discord embed payload.
+//!
+//! [`build_digest`] lists the cached programs of followed titles that start
+//! on one day as a plain-text email.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use dtvmgr_db::programs::CachedProgram;
+use serde::{Deserialize, Serialize};
+
+/// Default line template for one change.
+pub const DEFAULT_TEMPLATE: &str = "[{kind}] {title} #{count} {sub_title} {when} ({channel})";
+
+/// Maximum length (characters) of a Discord embed description.
+const DISCORD_DESCRIPTION_LIMIT: usize = 4096;
+
+/// Discord embed color (`0x4f8bd6`).
+const DISCORD_COLOR: u32 = 0x004f_8bd6;
+
+/// Payload format of the webhook request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// `{"text": ..., "changes": [...]}`.
+    #[default]
+    Json,
+    /// Discord webhook with one embed.
+    Discord,
+}
+
+/// Connection security of the SMTP server used for digest emails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain connection upgraded with `STARTTLS` (port 587).
+    #[default]
+    StartTls,
+    /// Implicit TLS from the start (port 465).
+    Tls,
+    /// Unencrypted connection (port 25), e.g. a local relay.
+    Plain,
+}
+
+impl SmtpTls {
+    /// Returns the conventional port of the mode.
+    #[must_use]
+    pub const fn default_port(self) -> u16 {
+        match self {
+            Self::StartTls => 587,
+            Self::Tls => 465,
+            Self::Plain => 25,
+        }
+    }
+
+    /// Returns the configuration name of the mode.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::StartTls => "starttls",
+            Self::Tls => "tls",
+            Self::Plain => "plain",
+        }
+    }
+}
+
+/// Kind of program change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// A program not cached before.
+    New,
+    /// A cached program whose start or end time moved.
+    TimeChange,
+}
+
+impl ChangeKind {
+    /// Returns the label used in messages.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::TimeChange => "time_change",
+        }
+    }
+}
+
+/// A new or rescheduled program of a followed title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProgramChange {
+    /// Kind of change.
+    pub kind: ChangeKind,
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name (empty when unknown).
+    pub title: String,
+    /// Syoboi program ID.
+    pub pid: u32,
+    /// Channel ID.
+    pub ch_id: u32,
+    /// Channel name (empty when unknown).
+    pub channel: String,
+    /// Episode number.
+    pub count: Option<u32>,
+    /// Episode subtitle.
+    pub sub_title: Option<String>,
+    /// Current start time.
+    pub st_time: String,
+    /// Previous start time (time changes only).
+    pub old_st_time: Option<String>,
+}
+
+/// Compares `current` programs of the titles in `tids` with the `previous`
+/// cached ones and returns the new and time-changed programs in start order.
+///
+/// Title and channel names are resolved from `titles` and `channels`.
+#[must_use]
+#[allow(clippy::implicit_hasher)]
+pub fn diff_programs(
+    tids: &HashSet<u32>,
+    previous: &[CachedProgram],
+    current: &[CachedProgram],
+    titles: &HashMap<u32, String>,
+    channels: &HashMap<u32, String>,
+) -> Vec<ProgramChange> {
+    let previous: HashMap<u32, &CachedProgram> = previous.iter().map(|p| (p.pid, p)).collect();
+    let mut changes: Vec<ProgramChange> = current
+        .iter()
+        .filter(|p| tids.contains(&p.tid) && p.deleted.is_none_or(|d| d == 0))
+        .filter_map(|p| {
+            let (kind, old_st_time) = match previous.get(&p.pid) {
+                None => (ChangeKind::New, None),
+                Some(old) if old.st_time != p.st_time || old.ed_time != p.ed_time => {
+                    (ChangeKind::TimeChange, Some(old.st_time.clone()))
+                }
+                Some(_) => return None,
+            };
+            Some(ProgramChange {
+                kind,
+                tid: p.tid,
+                title: titles.get(&p.tid).cloned().unwrap_or_default(),
+                pid: p.pid,
+                ch_id: p.ch_id,
+                channel: channels.get(&p.ch_id).cloned().unwrap_or_default(),
+                count: p.count,
+                sub_title: p.st_sub_title.clone().or_else(|| p.sub_title.clone()),
+                st_time: p.st_time.clone(),
+                old_st_time,
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| a.st_time.cmp(&b.st_time).then(a.pid.cmp(&b.pid)));
+    changes
+}
+
+/// Renders one change with `template`.
+///
+/// Placeholders: `{kind}`, `{tid}`, `{title}`, `{pid}`, `{channel}`,
+/// `{count}`, `{sub_title}`, `{st_time}`, `{old_st_time}`, and `{when}`
+/// (`old -> new` for time changes, the start time otherwise). Unknown
+/// values render as `-`.
+#[must_use]
+pub fn render_change(template: &str, change: &ProgramChange) -> String {
+    let dash = |s: &str| {
+        if s.is_empty() {
+            String::from("-")
+        } else {
+            s.to_owned()
+        }
+    };
+    let when = change.old_st_time.as_ref().map_or_else(
+        || change.st_time.clone(),
+        |old| format!("{old} -> {}", change.st_time),
+    );
+    [
+        ("kind", change.kind.as_str().to_owned()),
+        ("tid", change.tid.to_string()),
+        ("title", dash(&change.title)),
+        ("pid", change.pid.to_string()),
+        ("channel", dash(&change.channel)),
+        (
+            "count",
+            change
+                .count
+                .map_or_else(|| String::from("-"), |c| c.to_string()),
+        ),
+        ("sub_title", dash(change.sub_title.as_deref().unwrap_or(""))),
+        ("st_time", change.st_time.clone()),
+        (
+            "old_st_time",
+            dash(change.old_st_time.as_deref().unwrap_or("")),
+        ),
+        ("when", when),
+    ]
+    .iter()
+    .fold(template.to_owned(), |text, (key, value)| {
+        text.replace(&format!("{{{key}}}"), value)
+    })
+}
+
+/// Builds the webhook request body for `changes`.
+#[must_use]
+pub fn build_payload(
+    format: WebhookFormat,
+    template: &str,
+    changes: &[ProgramChange],
+) -> serde_json::Value {
+    let summary = format!("dtvmgr: {} program changes", changes.len());
+    let body = changes
+        .iter()
+        .map(|c| render_change(template, c))
+        .collect::<Vec<_>>()
+        .join("\n");
+    match format {
+        WebhookFormat::Json => serde_json::json!({
+            "text": format!("{summary}\n{body}"),
+            "changes": changes,
+        }),
+        WebhookFormat::Discord => serde_json::json!({
+            "username": "dtvmgr",
+            "embeds": [{
+                "title": summary,
+                "description": truncate(&body, DISCORD_DESCRIPTION_LIMIT),
+                "color": DISCORD_COLOR,
+            }],
+        }),
+    }
+}
+
+/// Subject and plain-text body of a daily digest email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    /// Email subject.
+    pub subject: String,
+    /// Email body, one line per program.
+    pub body: String,
+    /// Number of programs listed.
+    pub programs: usize,
+}
+
+/// Builds the digest of programs of the titles in `tids` that start on
+/// `date` (`YYYY-MM-DD`, Syoboi local time), in start order.
+///
+/// Each line reads `HH:MM channel title #count subtitle`; title and channel
+/// names are resolved from `titles` and `channels`. Deleted programs are
+/// skipped.
+#[must_use]
+#[allow(clippy::implicit_hasher)]
+pub fn build_digest(
+    date: &str,
+    tids: &HashSet<u32>,
+    programs: &[CachedProgram],
+    titles: &HashMap<u32, String>,
+    channels: &HashMap<u32, String>,
+) -> Digest {
+    let mut today: Vec<&CachedProgram> = programs
+        .iter()
+        .filter(|p| {
+            tids.contains(&p.tid)
+                && p.deleted.is_none_or(|d| d == 0)
+                && p.st_time.get(..10) == Some(date)
+        })
+        .collect();
+    today.sort_by(|a, b| a.st_time.cmp(&b.st_time).then(a.pid.cmp(&b.pid)));
+
+    let lines: Vec<String> = today
+        .iter()
+        .map(|p| {
+            let mut line = format!(
+                "{} {} {}",
+                p.st_time.get(11..16).unwrap_or(&p.st_time),
+                channels.get(&p.ch_id).map_or("-", String::as_str),
+                titles.get(&p.tid).map_or("-", String::as_str),
+            );
+            if let Some(count) = p.count {
+                let _ = write!(line, " #{count}");
+            }
+            if let Some(sub_title) = p.st_sub_title.as_deref().or(p.sub_title.as_deref()) {
+                line.push(' ');
+                line.push_str(sub_title);
+            }
+            line
+        })
+        .collect();
+    let body = if lines.is_empty() {
+        format!("No programs of followed titles on {date}.\n")
+    } else {
+        format!("{}\n", lines.join("\n"))
+    };
+    Digest {
+        subject: format!("dtvmgr: {} programs on {date}", lines.len()),
+        body,
+        programs: lines.len(),
+    }
+}
+
+/// Truncates `text` to at most `limit` characters, ending with `…` when cut.
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_owned();
+    }
+    let mut out: String = text.chars().take(limit.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn program(pid: u32, tid: u32, st_time: &str, ed_time: &str) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid,
+            ch_id: 7,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: ed_time.to_owned(),
+            count: Some(pid),
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    fn changes() -> Vec<ProgramChange> {
+        let previous = vec![
+            program(1, 100, "2024-04-06 23:00:00", "2024-04-06 23:30:00"),
+            program(2, 100, "2024-04-13 23:00:00", "2024-04-13 23:30:00"),
+        ];
+        let current = vec![
+            program(1, 100, "2024-04-06 23:00:00", "2024-04-06 23:30:00"),
+            program(2, 100, "2024-04-13 23:15:00", "2024-04-13 23:45:00"),
+            program(3, 100, "2024-04-20 23:00:00", "2024-04-20 23:30:00"),
+            program(4, 200, "2024-04-20 23:00:00", "2024-04-20 23:30:00"),
+        ];
+        let titles = HashMap::from([(100, String::from("SPY×FAMILY"))]);
+        let channels = HashMap::from([(7, String::from("テレビ東京"))]);
+        diff_programs(
+            &HashSet::from([100]),
+            &previous,
+            &current,
+            &titles,
+            &channels,
+        )
+    }
+
+    #[test]
+    fn test_diff_programs_reports_new_and_time_changed() {
+        // Arrange & Act
+        let changes = changes();
+
+        // Assert
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].kind, ChangeKind::TimeChange);
+        assert_eq!(changes[0].pid, 2);
+        assert_eq!(
+            changes[0].old_st_time.as_deref(),
+            Some("2024-04-13 23:00:00")
+        );
+        assert_eq!(changes[1].kind, ChangeKind::New);
+        assert_eq!(changes[1].pid, 3);
+        assert_eq!(changes[1].channel, "テレビ東京");
+    }
+
+    #[test]
+    fn test_render_change_fills_placeholders() {
+        // Arrange
+        let changes = changes();
+
+        // Act
+        let rescheduled = render_change(DEFAULT_TEMPLATE, &changes[0]);
+        let custom = render_change(
+            "{title} ep{count} on {channel} [{old_st_time}]",
+            &changes[1],
+        );
+
+        // Assert
+        assert_eq!(
+            rescheduled,
+            "[time_change] SPY×FAMILY #2 - 2024-04-13 23:00:00 -> 2024-04-13 23:15:00 (テレビ東京)"
+        );
+        assert_eq!(custom, "SPY×FAMILY ep3 on テレビ東京 [-]");
+    }
+
+    #[test]
+    fn test_build_payload_formats() {
+        // Arrange
+        let changes = changes();
+
+        // Act
+        let json = build_payload(WebhookFormat::Json, "{pid}", &changes);
+        let discord = build_payload(WebhookFormat::Discord, "{pid}", &changes);
+
+        // Assert
+        assert_eq!(json["text"], "dtvmgr: 2 program changes\n2\n3");
+        assert_eq!(json["changes"][1]["kind"], "new");
+        assert_eq!(discord["embeds"][0]["title"], "dtvmgr: 2 program changes");
+        assert_eq!(discord["embeds"][0]["description"], "2\n3");
+    }
+
+    #[test]
+    fn test_build_digest_lists_programs_of_the_day() {
+        // Arrange
+        let mut with_sub_title = program(3, 100, "2024-04-20 23:00:00", "2024-04-20 23:30:00");
+        with_sub_title.sub_title = Some(String::from("作戦"));
+        let mut deleted = program(5, 100, "2024-04-20 22:00:00", "2024-04-20 22:30:00");
+        deleted.deleted = Some(1);
+        let programs = vec![
+            with_sub_title,
+            program(1, 100, "2024-04-20 01:30:00", "2024-04-20 02:00:00"),
+            program(2, 100, "2024-04-21 23:00:00", "2024-04-21 23:30:00"),
+            program(4, 200, "2024-04-20 23:00:00", "2024-04-20 23:30:00"),
+            deleted,
+        ];
+        let titles = HashMap::from([(100, String::from("SPY×FAMILY"))]);
+        let channels = HashMap::from([(7, String::from("テレビ東京"))]);
+
+        // Act
+        let digest = build_digest(
+            "2024-04-20",
+            &HashSet::from([100]),
+            &programs,
+            &titles,
+            &channels,
+        );
+        let empty = build_digest(
+            "2024-04-22",
+            &HashSet::from([100]),
+            &programs,
+            &titles,
+            &channels,
+        );
+
+        // Assert
+        assert_eq!(digest.programs, 2);
+        assert_eq!(digest.subject, "dtvmgr: 2 programs on 2024-04-20");
+        assert_eq!(
+            digest.body,
+            "01:30 テレビ東京 SPY×FAMILY #1\n23:00 テレビ東京 SPY×FAMILY #3 作戦\n"
+        );
+        assert_eq!(empty.programs, 0);
+        assert_eq!(
+            empty.body,
+            "No programs of followed titles on 2024-04-22.\n"
+        );
+    }
+
+    #[test]
+    fn test_truncate_limits_characters() {
+        // Arrange & Act & Assert
+        assert_eq!(truncate("あいうえお", 5), "あいうえお");
+        assert_eq!(truncate("あいうえお", 3), "あい…");
+    }
+}
@@ -0,0 +1,380 @@
+//! Recording rules evaluated against cached programs.
+//!
+//! A rule selects programs by keyword (title or subtitle), channel, time of
+//! day, and Syoboi flag bits. Every condition that is set must hold; unset
+//! conditions match everything. Matches are returned per program with the
+//! names of all rules that selected it.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use chrono::NaiveTime;
+use dtvmgr_api::syoboi::ProgramFlags;
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// A recording rule (`[[rules]]` in the config file).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    /// Rule name, reported with each match.
+    pub name: String,
+    /// Keywords matched against title, short title, and subtitle
+    /// (NFKC, case-insensitive). Any keyword matches; empty matches all.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Channel IDs (Syoboi `ChID`). Empty matches all channels.
+    #[serde(default)]
+    pub ch_ids: Vec<u32>,
+    /// Earliest start time of day (`HH:MM`, JST).
+    #[serde(default)]
+    pub start_time: Option<String>,
+    /// Start time of day before which the program must start (`HH:MM`,
+    /// JST, exclusive). A value before `start_time` wraps past midnight.
+    #[serde(default)]
+    pub end_time: Option<String>,
+    /// Flag bits that must all be set (1: 注, 2: 新, 4: 終, 8: 再).
+    #[serde(default)]
+    pub include_flags: u32,
+    /// Flag bits of which none may be set (e.g. 8 skips repeats).
+    #[serde(default)]
+    pub exclude_flags: u32,
+}
+
+/// A program selected by one or more rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct RuleMatch<'a> {
+    /// The matched program.
+    pub program: &'a CachedProgram,
+    /// Names of the matching rules, in rule order.
+    pub rules: Vec<&'a str>,
+}
+
+/// A rule with its keywords normalized and its time window parsed.
+struct CompiledRule<'a> {
+    /// Rule name.
+    name: &'a str,
+    /// Normalized keywords.
+    keywords: Vec<String>,
+    /// Source rule.
+    rule: &'a Rule,
+    /// Parsed start of the time window.
+    start: Option<NaiveTime>,
+    /// Parsed end of the time window.
+    end: Option<NaiveTime>,
+    /// Flags that must all be set.
+    include: ProgramFlags,
+    /// Flags of which none may be set.
+    exclude: ProgramFlags,
+}
+
+impl CompiledRule<'_> {
+    /// Returns whether `program` of a title with `names` satisfies the rule.
+    fn matches(&self, program: &CachedProgram, names: &[String]) -> bool {
+        let rule = self.rule;
+        if !rule.ch_ids.is_empty() && !rule.ch_ids.contains(&program.ch_id) {
+            return false;
+        }
+        let flags = ProgramFlags::from_flag(program.flag);
+        if !flags.contains(self.include) || flags.intersects(self.exclude) {
+            return false;
+        }
+        if !self.in_window(&program.st_time) {
+            return false;
+        }
+        if self.keywords.is_empty() {
+            return true;
+        }
+        let sub_titles = [
+            program.sub_title.as_deref(),
+            program.st_sub_title.as_deref(),
+        ];
+        let sub_titles: Vec<String> = sub_titles.into_iter().flatten().map(fold).collect();
+        self.keywords.iter().any(|k| {
+            names
+                .iter()
+                .chain(sub_titles.iter())
+                .any(|text| text.contains(k.as_str()))
+        })
+    }
+
+    /// Returns whether a program starting at `st_time` falls into the time
+    /// window.
+    fn in_window(&self, st_time: &str) -> bool {
+        if self.start.is_none() && self.end.is_none() {
+            return true;
+        }
+        let Some(time) = st_time
+            .get(11..16)
+            .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok())
+        else {
+            return false;
+        };
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if end < start => time >= start || time < end,
+            (start, end) => start.is_none_or(|s| time >= s) && end.is_none_or(|e| time < e),
+        }
+    }
+}
+
+/// Checks that every rule has a unique, non-empty name and valid times.
+///
+/// # Errors
+///
+/// Returns an error describing the first invalid rule.
+#[allow(clippy::module_name_repetitions)]
+pub fn validate_rules(rules: &[Rule]) -> Result<()> {
+    compile(rules).map(|_| ())
+}
+
+/// Evaluates `rules` against `programs` and returns the matching programs
+/// ordered by start time. Deleted programs never match.
+///
+/// # Errors
+///
+/// Returns an error if a rule is invalid (see [`validate_rules`]).
+#[allow(clippy::module_name_repetitions)]
+pub fn evaluate_rules<'a>(
+    rules: &'a [Rule],
+    titles: &[CachedTitle],
+    programs: &'a [CachedProgram],
+) -> Result<Vec<RuleMatch<'a>>> {
+    let compiled = compile(rules)?;
+    let names: HashMap<u32, Vec<String>> = titles
+        .iter()
+        .map(|t| {
+            let names = core::iter::once(t.title.as_str())
+                .chain(t.short_title.as_deref())
+                .map(fold)
+                .collect();
+            (t.tid, names)
+        })
+        .collect();
+
+    let mut matches: Vec<RuleMatch<'a>> = programs
+        .iter()
+        .filter(|p| p.deleted.is_none_or(|d| d == 0))
+        .filter_map(|p| {
+            let title_names = names.get(&p.tid).map_or(&[][..], Vec::as_slice);
+            let matched: Vec<&str> = compiled
+                .iter()
+                .filter(|r| r.matches(p, title_names))
+                .map(|r| r.name)
+                .collect();
+            (!matched.is_empty()).then_some(RuleMatch {
+                program: p,
+                rules: matched,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        a.program
+            .st_time
+            .cmp(&b.program.st_time)
+            .then(a.program.pid.cmp(&b.program.pid))
+    });
+    Ok(matches)
+}
+
+/// Validates and prepares `rules` for matching.
+fn compile(rules: &[Rule]) -> Result<Vec<CompiledRule<'_>>> {
+    let mut compiled: Vec<CompiledRule<'_>> = Vec::with_capacity(rules.len());
+    for rule in rules {
+        let name = rule.name.trim();
+        if name.is_empty() {
+            bail!("rule names must not be empty");
+        }
+        if compiled.iter().any(|r| r.name == name) {
+            bail!("duplicate rule name {name}");
+        }
+        compiled.push(CompiledRule {
+            name,
+            keywords: rule
+                .keywords
+                .iter()
+                .map(|k| fold(k))
+                .filter(|k| !k.is_empty())
+                .collect(),
+            rule,
+            start: parse_time(rule.start_time.as_deref())
+                .with_context(|| format!("invalid start_time in rule {name}"))?,
+            end: parse_time(rule.end_time.as_deref())
+                .with_context(|| format!("invalid end_time in rule {name}"))?,
+            include: ProgramFlags::from_bits_retain(rule.include_flags),
+            exclude: ProgramFlags::from_bits_retain(rule.exclude_flags),
+        });
+    }
+    Ok(compiled)
+}
+
+/// Parses an optional `HH:MM` time of day.
+fn parse_time(value: Option<&str>) -> Result<Option<NaiveTime>> {
+    value
+        .map(|v| {
+            NaiveTime::parse_from_str(v.trim(), "%H:%M")
+                .with_context(|| format!("expected HH:MM, got {v:?}"))
+        })
+        .transpose()
+}
+
+/// Folds text for keyword matching (NFKC, lowercase).
+fn fold(text: &str) -> String {
+    text.nfkc().flat_map(char::to_lowercase).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn title(tid: u32, name: &str) -> CachedTitle {
+        CachedTitle {
+            tid,
+            tmdb_series_id: None,
+            tmdb_season_number: None,
+            tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
+            title: name.to_owned(),
+            short_title: None,
+            title_yomi: None,
+            title_en: None,
+            cat: Some(1),
+            title_flag: None,
+            first_year: None,
+            first_month: None,
+            keywords: Vec::new(),
+            last_update: String::from("2023-01-01 00:00:00"),
+            tmdb_original_name: None,
+            tmdb_name: None,
+            tmdb_alt_titles: None,
+            tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
+        }
+    }
+
+    fn program(pid: u32, tid: u32, ch_id: u32, st_time: &str, flag: Option<u32>) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid,
+            ch_id,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: st_time.to_owned(),
+            count: None,
+            sub_title: None,
+            flag,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    fn rule(name: &str) -> Rule {
+        Rule {
+            name: name.to_owned(),
+            ..Rule::default()
+        }
+    }
+
+    #[test]
+    fn test_evaluate_rules_combines_conditions() {
+        // Arrange
+        let titles = vec![title(1, "ＳＰＹ×ＦＡＭＩＬＹ"), title(2, "Other Show")];
+        let mut deleted = program(5, 1, 7, "2023-04-01 23:00:00", None);
+        deleted.deleted = Some(1);
+        let programs = vec![
+            program(1, 1, 7, "2023-04-08 23:00:00", Some(2)),
+            program(2, 1, 7, "2023-04-09 12:00:00", Some(8)),
+            program(3, 1, 1, "2023-04-08 23:30:00", None),
+            program(4, 2, 7, "2023-04-08 22:00:00", None),
+            deleted,
+        ];
+        let rules = vec![
+            Rule {
+                keywords: vec![String::from("spy")],
+                ch_ids: vec![7],
+                exclude_flags: 8,
+                ..rule("spy-tx")
+            },
+            Rule {
+                include_flags: 2,
+                ..rule("premieres")
+            },
+        ];
+
+        // Act
+        let matches = evaluate_rules(&rules, &titles, &programs).unwrap();
+
+        // Assert
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].program.pid, 1);
+        assert_eq!(matches[0].rules, vec!["spy-tx", "premieres"]);
+    }
+
+    #[test]
+    fn test_evaluate_rules_time_window_wraps_midnight() {
+        // Arrange
+        let titles = vec![title(1, "Late Night")];
+        let programs = vec![
+            program(1, 1, 7, "2023-04-08 23:30:00", None),
+            program(2, 1, 7, "2023-04-09 01:00:00", None),
+            program(3, 1, 7, "2023-04-09 02:00:00", None),
+            program(4, 1, 7, "2023-04-09 19:00:00", None),
+        ];
+        let rules = vec![Rule {
+            start_time: Some(String::from("23:00")),
+            end_time: Some(String::from("02:00")),
+            ..rule("late")
+        }];
+
+        // Act
+        let matches = evaluate_rules(&rules, &titles, &programs).unwrap();
+
+        // Assert
+        let pids: Vec<u32> = matches.iter().map(|m| m.program.pid).collect();
+        assert_eq!(pids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_evaluate_rules_matches_subtitles() {
+        // Arrange
+        let titles = vec![title(1, "Anthology")];
+        let mut special = program(1, 1, 7, "2023-04-08 23:00:00", None);
+        special.st_sub_title = Some(String::from("Beach Episode"));
+        let programs = vec![special, program(2, 1, 7, "2023-04-15 23:00:00", None)];
+        let rules = vec![Rule {
+            keywords: vec![String::from("BEACH")],
+            ..rule("beach")
+        }];
+
+        // Act
+        let matches = evaluate_rules(&rules, &titles, &programs).unwrap();
+
+        // Assert
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].program.pid, 1);
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        // Arrange & Act & Assert
+        validate_rules(&[rule("a"), rule("b")]).unwrap();
+        assert!(validate_rules(&[rule("a"), rule("a")]).is_err());
+        assert!(validate_rules(&[rule(" ")]).is_err());
+        let bad_time = Rule {
+            start_time: Some(String::from("25:00")),
+            ..rule("bad")
+        };
+        assert!(validate_rules(&[bad_time]).is_err());
+    }
+}
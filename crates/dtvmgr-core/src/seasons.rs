@@ -0,0 +1,243 @@
+//! Season splitting of long-running Syoboi titles.
+//!
+//! Syoboi keeps one title (and one running `count`) for shows that TMDB
+//! splits into several seasons. A title is treated as cross-season when its
+//! broadcasts continue past `FirstYear` and the first airings of consecutive
+//! counts are separated by a long break; each run between breaks becomes a
+//! suggested TMDB season.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+use chrono::{Datelike, NaiveDateTime};
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::season_ranges::SeasonRange;
+use dtvmgr_db::titles::CachedTitle;
+
+/// Minimum break (days) between two consecutive counts that starts a new
+/// season.
+pub const SEASON_GAP_DAYS: i64 = 60;
+
+/// Suggests season ranges for `title` from its broadcasts.
+///
+/// Only the first airing of each count is considered; deleted programs and
+/// programs of other titles are ignored. Returns an empty list when the
+/// title does not run past `FirstYear` or has no break of at least
+/// [`SEASON_GAP_DAYS`]. The first range starts at count 1 and the last one
+/// is open-ended.
+#[must_use]
+pub fn detect_season_ranges(title: &CachedTitle, programs: &[CachedProgram]) -> Vec<SeasonRange> {
+    let mut first_airing: BTreeMap<u32, NaiveDateTime> = BTreeMap::new();
+    for p in programs {
+        if p.tid != title.tid || p.deleted.is_some_and(|d| d != 0) {
+            continue;
+        }
+        let (Some(count), Ok(start)) = (
+            p.count,
+            NaiveDateTime::parse_from_str(&p.st_time, "%Y-%m-%d %H:%M:%S"),
+        ) else {
+            continue;
+        };
+        first_airing
+            .entry(count)
+            .and_modify(|t| *t = (*t).min(start))
+            .or_insert(start);
+    }
+    let last_year = first_airing.values().map(Datelike::year).max();
+    let runs_past_first_year = match (title.first_year, last_year) {
+        (Some(first), Some(last)) => i64::from(last) > i64::from(first),
+        _ => false,
+    };
+    if !runs_past_first_year {
+        return Vec::new();
+    }
+
+    // Counts whose first airing follows a long break start a new season.
+    let mut starts: Vec<u32> = Vec::new();
+    let mut previous: Option<NaiveDateTime> = None;
+    for (&count, &start) in &first_airing {
+        if let Some(prev) = previous
+            && start.signed_duration_since(prev).num_days() >= SEASON_GAP_DAYS
+        {
+            starts.push(count);
+        }
+        previous = Some(start);
+    }
+    if starts.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ranges = Vec::with_capacity(starts.len().saturating_add(1));
+    let mut first_count = 1;
+    for (index, &start) in starts.iter().enumerate() {
+        ranges.push(SeasonRange {
+            tid: title.tid,
+            first_count,
+            last_count: Some(start.saturating_sub(1)),
+            season_number: season_number(index),
+        });
+        first_count = start;
+    }
+    ranges.push(SeasonRange {
+        tid: title.tid,
+        first_count,
+        last_count: None,
+        season_number: season_number(starts.len()),
+    });
+    ranges
+}
+
+/// Returns the 1-based season number of the range at `index`.
+fn season_number(index: usize) -> u32 {
+    u32::try_from(index).map_or(u32::MAX, |i| i.saturating_add(1))
+}
+
+/// Checks that `ranges` are sorted by first count, non-empty, do not
+/// overlap, and that only the last range is open-ended.
+///
+/// # Errors
+///
+/// Returns an error describing the first invalid range.
+pub fn validate_season_ranges(ranges: &[SeasonRange]) -> Result<()> {
+    let mut next_first: u32 = 1;
+    for (index, range) in ranges.iter().enumerate() {
+        if range.first_count < next_first {
+            bail!(
+                "range starting at count {} overlaps the previous range",
+                range.first_count
+            );
+        }
+        match range.last_count {
+            Some(last) if last < range.first_count => {
+                bail!("range {}-{last} ends before it starts", range.first_count)
+            }
+            Some(last) => next_first = last.saturating_add(1),
+            None if index.saturating_add(1) < ranges.len() => bail!(
+                "only the last range may be open-ended (count {})",
+                range.first_count
+            ),
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn title(first_year: Option<u32>) -> CachedTitle {
+        CachedTitle {
+            tid: 100,
+            tmdb_series_id: None,
+            tmdb_season_number: None,
+            tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
+            title: String::from("Long Runner"),
+            short_title: None,
+            title_yomi: None,
+            title_en: None,
+            cat: Some(1),
+            title_flag: None,
+            first_year,
+            first_month: None,
+            keywords: Vec::new(),
+            last_update: String::from("2023-01-01 00:00:00"),
+            tmdb_original_name: None,
+            tmdb_name: None,
+            tmdb_alt_titles: None,
+            tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
+        }
+    }
+
+    fn program(pid: u32, count: u32, st_time: &str) -> CachedProgram {
+        CachedProgram {
+            pid,
+            tid: 100,
+            ch_id: 1,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: st_time.to_owned(),
+            count: Some(count),
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    const fn range(first_count: u32, last_count: Option<u32>, season_number: u32) -> SeasonRange {
+        SeasonRange {
+            tid: 100,
+            first_count,
+            last_count,
+            season_number,
+        }
+    }
+
+    #[test]
+    fn test_detect_season_ranges_splits_at_long_breaks() {
+        // Arrange: counts 1-2 in 2023, 3-4 after a half-year break (with a
+        // rerun of 2 in between), 5 after another break
+        let programs = vec![
+            program(1, 1, "2023-04-01 23:00:00"),
+            program(2, 2, "2023-04-08 23:00:00"),
+            program(3, 2, "2023-08-01 12:00:00"),
+            program(4, 3, "2023-10-07 23:00:00"),
+            program(5, 4, "2023-10-14 23:00:00"),
+            program(6, 5, "2024-07-06 23:00:00"),
+        ];
+
+        // Act
+        let ranges = detect_season_ranges(&title(Some(2023)), &programs);
+
+        // Assert
+        assert_eq!(
+            ranges,
+            vec![
+                range(1, Some(2), 1),
+                range(3, Some(4), 2),
+                range(5, None, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_season_ranges_ignores_single_year_and_continuous_titles() {
+        // Arrange
+        let continuous = vec![
+            program(1, 1, "2023-12-23 23:00:00"),
+            program(2, 2, "2023-12-30 23:00:00"),
+            program(3, 3, "2024-01-06 23:00:00"),
+        ];
+        let one_year = vec![
+            program(1, 1, "2023-01-07 23:00:00"),
+            program(2, 2, "2023-07-01 23:00:00"),
+        ];
+
+        // Act & Assert
+        assert!(detect_season_ranges(&title(Some(2023)), &continuous).is_empty());
+        assert!(detect_season_ranges(&title(Some(2023)), &one_year).is_empty());
+        assert!(detect_season_ranges(&title(None), &continuous).is_empty());
+    }
+
+    #[test]
+    fn test_validate_season_ranges() {
+        // Arrange & Act & Assert
+        validate_season_ranges(&[range(1, Some(12), 1), range(13, None, 2)]).unwrap();
+        assert!(validate_season_ranges(&[range(1, Some(12), 1), range(12, None, 2)]).is_err());
+        assert!(validate_season_ranges(&[range(5, Some(3), 1)]).is_err());
+        assert!(validate_season_ranges(&[range(1, None, 1), range(13, None, 2)]).is_err());
+    }
+}
@@ -0,0 +1,1466 @@
+//! Synchronization of Syoboi programs, titles, and channels into the local DB.
+//!
+//! [`SyncEngine`] drives the whole fetch-and-store pipeline behind
+//! `dtvmgr db sync`. The API client and DB connection are injected, so other
+//! programs can embed the sync (or test it against a mock API) without
+//! going through the CLI.
+
+use core::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDateTime;
+use dtvmgr_api::clock::{Clock, SystemClock};
+use dtvmgr_api::syoboi::{
+    LocalSyoboiApi, NoProgress, PROGRAM_FIELDS, ProgLookupParams, REQUIRED_PROGRAM_FIELDS,
+    REQUIRED_TITLE_FIELDS, SyncProgress, SyncStage, SyoboiProgram, SyoboiTitle, TITLE_FIELDS,
+    TimeRange, TitleLookupParams, lookup_all_programs_with_progress,
+    lookup_updated_programs_with_progress, parse_comment, parse_sub_titles,
+};
+use dtvmgr_db::Connection;
+use dtvmgr_db::channels::CachedChannel;
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
+use dtvmgr_db::{
+    CreditKind, DEFAULT_UPSERT_BATCH_SIZE, Episode, TitleCredit, TitleLink,
+    delete_programs_by_tids_not_in, delete_titles_by_cat_not_in, load_title_aliases, load_titles,
+    load_titles_by_tids, replace_title_credits, save_programs_fetched, save_title_chunk,
+    upsert_channels, upsert_episodes, upsert_programs_batched, upsert_titles_batched,
+};
+use tracing::instrument;
+
+use crate::budget::RetryBudget;
+
+/// Title lookup chunk size for Syoboi API.
+pub const TITLE_LOOKUP_CHUNK_SIZE: usize = 50;
+
+/// Max retries per title chunk (rate-limit empty-response recovery).
+///
+/// Cloudflare rate-limit on cal.syoboi.jp typically lasts ~30-35s.
+/// With initial backoff 10s: 10+20+40+80+160 = 310s theoretical max,
+/// but usually resolves by retry 2-3 (cumulative 30-70s).
+pub const TITLE_CHUNK_MAX_RETRIES: u32 = 5;
+
+/// Initial backoff before retrying a title chunk (doubles each retry).
+pub const TITLE_CHUNK_INITIAL_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Fields to request from `TitleLookup` during a sync.
+///
+/// Limited to the fields stored by [`to_cached_title`] and
+/// [`to_title_credits`]. `Comment` often contains unescaped `&` in URLs, so
+/// XML clients must repair malformed XML (`sanitize_xml`) to parse it.
+pub const TITLE_SYNC_FIELDS: &[&str] = &[
+    "TID",
+    "LastUpdate",
+    "Title",
+    "ShortTitle",
+    "TitleYomi",
+    "TitleEN",
+    "Comment",
+    "Cat",
+    "TitleFlag",
+    "FirstYear",
+    "FirstMonth",
+    "Keywords",
+    "SubTitles",
+];
+
+/// Checks a `TitleLookup` field selection (`[syoboi.sync] title_fields`).
+///
+/// # Errors
+///
+/// Returns an error naming the fields that are not in [`TITLE_FIELDS`], or
+/// the [`REQUIRED_TITLE_FIELDS`] missing from `fields`.
+pub fn validate_title_fields(fields: &[String]) -> Result<()> {
+    validate_fields("title_fields", fields, TITLE_FIELDS, REQUIRED_TITLE_FIELDS)
+}
+
+/// Checks a `ProgLookup` field selection (`[syoboi.sync] program_fields`).
+///
+/// # Errors
+///
+/// Returns an error naming the fields that are not in [`PROGRAM_FIELDS`],
+/// or the [`REQUIRED_PROGRAM_FIELDS`] missing from `fields`.
+pub fn validate_program_fields(fields: &[String]) -> Result<()> {
+    validate_fields(
+        "program_fields",
+        fields,
+        PROGRAM_FIELDS,
+        REQUIRED_PROGRAM_FIELDS,
+    )
+}
+
+fn validate_fields(key: &str, fields: &[String], known: &[&str], required: &[&str]) -> Result<()> {
+    let unknown: Vec<&str> = fields
+        .iter()
+        .map(String::as_str)
+        .filter(|f| !known.contains(f))
+        .collect();
+    if !unknown.is_empty() {
+        bail!(
+            "{key}: unknown field(s) {} (known: {})",
+            unknown.join(", "),
+            known.join(", ")
+        );
+    }
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|r| !fields.iter().any(|f| f == r))
+        .collect();
+    if !missing.is_empty() {
+        bail!("{key}: required field(s) {} missing", missing.join(", "));
+    }
+    Ok(())
+}
+
+/// Outcome of [`SyncEngine::sync_titles`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TitleSync {
+    /// Titles stored (after the category filter).
+    pub titles: Vec<CachedTitle>,
+    /// Titles inserted or changed.
+    pub changed: usize,
+    /// TIDs returned by the API, including category-filtered ones.
+    pub fetched_tids: HashSet<u32>,
+    /// TIDs not fetched because the retry budget ran out.
+    pub deferred_tids: Vec<u32>,
+}
+
+/// Outcome of [`SyncEngine::store_programs`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramSync {
+    /// Programs stored.
+    pub programs: usize,
+    /// Programs inserted or changed.
+    pub changed: usize,
+    /// Channels fetched for the programs.
+    pub channels: usize,
+    /// Channels inserted or changed.
+    pub channels_changed: usize,
+}
+
+/// Outcome of [`SyncEngine::sync_programs`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SyncReport {
+    /// Titles stage result.
+    pub titles: TitleSync,
+    /// Programs and channels stage result.
+    pub programs: ProgramSync,
+}
+
+/// Fetches Syoboi data through an injected API and stores it in a DB.
+///
+/// Owns the DB connection and the run's [`RetryBudget`], so all chunk
+/// retries of one engine share it. Titles are stored chunk by chunk; with
+/// [`Self::checkpoint`] each completed chunk is also recorded in
+/// `sync_runs`, so an interrupted run can be resumed. Async methods take `&mut self`, keeping
+/// their futures `Send` although `Connection` is not `Sync`. The connection
+/// is anything that borrows as one, such as an owned `Connection` or a
+/// `dtvmgr_db::PooledConnection`. Configured with builder-style setters:
+///
+/// ```no_run
+/// # async fn demo(client: &dtvmgr_api::syoboi::SyoboiClient, conn: dtvmgr_db::Connection)
+/// # -> anyhow::Result<()> {
+/// use dtvmgr_core::sync::SyncEngine;
+///
+/// let range = dtvmgr_api::syoboi::resolve_time_range(None, None)?;
+/// let report = SyncEngine::new(client, conn)
+///     .allowed_cats([1, 10].into())
+///     .sync_programs(range, &[1, 2])
+///     .await?;
+/// println!("{} programs", report.programs.programs);
+/// # Ok(())
+/// # }
+/// ```
+#[allow(clippy::module_name_repetitions, missing_debug_implementations)]
+pub struct SyncEngine<'a, A, C = Connection> {
+    /// Syoboi API client.
+    api: &'a A,
+    /// Destination database.
+    conn: C,
+    /// Title categories to keep; `None` keeps all.
+    allowed_cats: Option<HashSet<u32>>,
+    /// Title categories stored by this run; `None` stores all.
+    run_cats: Option<HashSet<u32>>,
+    /// Receiver of fetch progress.
+    progress: &'a dyn SyncProgress,
+    /// Retry budget for the whole run.
+    budget: RetryBudget,
+    /// `sync_runs` row receiving checkpoints.
+    run_id: Option<i64>,
+    /// TIDs completed by an earlier attempt of the run.
+    done_tids: HashSet<u32>,
+    /// Time source for retry backoff.
+    clock: Arc<dyn Clock>,
+    /// Rows written per transaction when storing titles and programs.
+    batch_size: usize,
+    /// Fields requested from `TitleLookup`.
+    title_fields: Vec<String>,
+}
+
+impl<'a, A: LocalSyoboiApi + Sync, C: Borrow<Connection>> SyncEngine<'a, A, C> {
+    /// Creates an engine with no category filter, no progress reporting, and
+    /// an unlimited retry budget.
+    #[must_use]
+    pub fn new(api: &'a A, conn: C) -> Self {
+        Self {
+            api,
+            conn,
+            allowed_cats: None,
+            run_cats: None,
+            progress: &NoProgress,
+            budget: RetryBudget::new(u32::MAX, Duration::MAX),
+            run_id: None,
+            done_tids: HashSet::new(),
+            clock: SystemClock::shared(),
+            batch_size: DEFAULT_UPSERT_BATCH_SIZE,
+            title_fields: TITLE_SYNC_FIELDS.iter().map(|f| (*f).to_owned()).collect(),
+        }
+    }
+
+    /// Keeps only titles (and their programs) in `cats`; other titles are
+    /// skipped and removed from the DB by [`Self::store_programs`].
+    #[must_use]
+    pub fn allowed_cats(mut self, cats: HashSet<u32>) -> Self {
+        self.allowed_cats = Some(cats);
+        self
+    }
+
+    /// Stores only titles (and their programs) in `cats` during this run.
+    /// Unlike [`Self::allowed_cats`], cached titles of other categories are
+    /// kept. A missing `cat` counts as `0`.
+    #[must_use]
+    pub fn run_cats(mut self, cats: HashSet<u32>) -> Self {
+        self.run_cats = Some(cats);
+        self
+    }
+
+    /// Reports fetch progress to `progress`.
+    #[must_use]
+    pub fn progress(mut self, progress: &'a dyn SyncProgress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Bounds the total retries of the run with `budget`.
+    #[must_use]
+    pub const fn budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Sleeps retry backoff on `clock` instead of the system clock.
+    #[must_use]
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Writes titles and programs in transactions of `batch_size` rows
+    /// (`0` = one transaction per store).
+    #[must_use]
+    pub const fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Requests `fields` from `TitleLookup` instead of
+    /// [`TITLE_SYNC_FIELDS`]; check them with [`validate_title_fields`].
+    #[must_use]
+    pub fn title_fields(mut self, fields: Vec<String>) -> Self {
+        self.title_fields = fields;
+        self
+    }
+
+    /// Records progress in the `sync_runs` row `run_id` and skips fetching
+    /// `done_tids`, the titles an earlier attempt of the run already stored.
+    #[must_use]
+    pub fn checkpoint(mut self, run_id: i64, done_tids: HashSet<u32>) -> Self {
+        self.run_id = Some(run_id);
+        self.done_tids = done_tids;
+        self
+    }
+
+    /// Returns the destination database.
+    #[must_use]
+    pub fn conn(&self) -> &Connection {
+        self.conn.borrow()
+    }
+
+    /// Consumes the engine, returning the destination database.
+    #[must_use]
+    pub fn into_conn(self) -> C {
+        self.conn
+    }
+
+    /// Returns the retry budget spent so far.
+    #[must_use]
+    pub const fn retry_budget(&self) -> &RetryBudget {
+        &self.budget
+    }
+
+    /// Runs a full sync of `ch_ids` over `range`: fetches programs, then
+    /// syncs their titles and channels and stores the programs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an API request or DB operation fails.
+    #[allow(clippy::future_not_send)]
+    #[instrument(skip_all, err(level = "error"))]
+    pub async fn sync_programs(&mut self, range: TimeRange, ch_ids: &[u32]) -> Result<SyncReport> {
+        let params = ProgLookupParams {
+            ch_ids: Some(ch_ids.to_vec()),
+            range: Some(range),
+            ..ProgLookupParams::default()
+        };
+        let programs = self.fetch_programs(&params).await?;
+        let tids: Vec<u32> = programs
+            .iter()
+            .map(|p| p.tid)
+            .collect::<HashSet<u32>>()
+            .into_iter()
+            .collect();
+        let titles = self.sync_titles(&tids).await?;
+        let stored = self.store_programs(&programs, &titles).await?;
+        Ok(SyncReport {
+            titles,
+            programs: stored,
+        })
+    }
+
+    /// Fetches all programs matching `params`, paginating over its range.
+    ///
+    /// Programs of TIDs merged by `titles merge` are folded into the title
+    /// they were merged into.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `params.range` is `None` or a request fails.
+    #[allow(clippy::future_not_send)]
+    pub async fn fetch_programs(
+        &mut self,
+        params: &ProgLookupParams,
+    ) -> Result<Vec<SyoboiProgram>> {
+        let mut programs = lookup_all_programs_with_progress(self.api, params, self.progress)
+            .await
+            .context("failed to fetch programs")?;
+        fold_title_aliases(self.conn.borrow(), &mut programs)?;
+        self.save_programs_checkpoint(programs.len())?;
+        Ok(programs)
+    }
+
+    /// Fetches the programs matching `params` updated since `since`.
+    ///
+    /// Aliased TIDs are folded as in [`Self::fetch_programs`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request fails.
+    #[allow(clippy::future_not_send)]
+    pub async fn fetch_updated_programs(
+        &mut self,
+        params: &ProgLookupParams,
+        since: NaiveDateTime,
+    ) -> Result<Vec<SyoboiProgram>> {
+        let mut programs =
+            lookup_updated_programs_with_progress(self.api, params, since, self.progress)
+                .await
+                .context("failed to fetch updated programs")?;
+        fold_title_aliases(self.conn.borrow(), &mut programs)?;
+        self.save_programs_checkpoint(programs.len())?;
+        Ok(programs)
+    }
+
+    /// Records the fetched program count when checkpointing.
+    fn save_programs_checkpoint(&self, programs: usize) -> Result<()> {
+        if let Some(run_id) = self.run_id {
+            let programs = u32::try_from(programs).unwrap_or(u32::MAX);
+            save_programs_fetched(self.conn.borrow(), run_id, programs)
+                .context("failed to save programs checkpoint")?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the titles `tids` and upserts those passing the category
+    /// filter, one chunk at a time.
+    ///
+    /// TIDs completed by an earlier attempt (see [`Self::checkpoint`]) are
+    /// loaded from the DB instead of fetched. TIDs left unfetched once the
+    /// retry budget is exhausted are returned in [`TitleSync::deferred_tids`]
+    /// so the caller can retry them later.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request or the upsert fails.
+    #[allow(clippy::future_not_send)]
+    #[instrument(skip_all, err(level = "error"))]
+    pub async fn sync_titles(&mut self, tids: &[u32]) -> Result<TitleSync> {
+        let (done, pending): (Vec<u32>, Vec<u32>) =
+            tids.iter().partition(|tid| self.done_tids.contains(tid));
+        let mut sync = TitleSync::default();
+        if !done.is_empty() {
+            tracing::info!("Resuming: {} titles already synced", done.len());
+            sync.titles = load_titles_by_tids(self.conn.borrow(), &done)
+                .context("failed to load already synced titles")?;
+        }
+
+        tracing::info!("Fetching titles for {} unique TIDs...", pending.len());
+        let (fetched, cat_filtered) = self
+            .fetch_titles_chunked(&pending, &mut sync)
+            .await
+            .context("failed to fetch titles in chunks")?;
+        tracing::info!("Fetched {fetched} titles total");
+        if cat_filtered > 0 {
+            tracing::info!(
+                filtered = cat_filtered,
+                remaining = fetched.saturating_sub(cat_filtered),
+                "Filtered titles by category"
+            );
+        }
+        tracing::info!(
+            changed = sync.changed,
+            unchanged = sync.titles.len().saturating_sub(sync.changed),
+            "Titles upsert complete"
+        );
+        sync.fetched_tids = tids
+            .iter()
+            .copied()
+            .filter(|tid| !sync.deferred_tids.contains(tid))
+            .collect();
+        Ok(sync)
+    }
+
+    /// Stores `programs` whose title was synced in `titles`, after upserting
+    /// the channels they reference.
+    ///
+    /// With a category filter, titles and programs of other categories are
+    /// removed from the DB afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the channel request or a DB operation fails.
+    #[allow(clippy::future_not_send)]
+    #[instrument(skip_all, err(level = "error"))]
+    pub async fn store_programs(
+        &mut self,
+        programs: &[SyoboiProgram],
+        titles: &TitleSync,
+    ) -> Result<ProgramSync> {
+        // Ensure channels referenced by programs exist in DB
+        let unique_ch_ids: Vec<u32> = programs
+            .iter()
+            .map(|p| p.ch_id)
+            .collect::<HashSet<u32>>()
+            .into_iter()
+            .collect();
+        tracing::info!(
+            "Fetching channels for {} unique ch_ids...",
+            unique_ch_ids.len()
+        );
+        let api_channels = self
+            .api
+            .lookup_channels(Some(&unique_ch_ids))
+            .await
+            .context("failed to fetch channels")?;
+        let cached_channels: Vec<CachedChannel> = api_channels
+            .iter()
+            .map(|ch| CachedChannel {
+                ch_id: ch.ch_id,
+                ch_gid: None,
+                ch_name: ch.ch_name.clone(),
+                ch_url: ch.ch_url.clone(),
+                ch_iepg_name: ch.ch_iepg_name.clone(),
+                ch_comment: ch.ch_comment.clone(),
+                logo_url: None,
+            })
+            .collect();
+        let channels_changed = upsert_channels(self.conn.borrow(), &cached_channels)
+            .context("failed to upsert channels")?;
+        tracing::info!(
+            fetched = cached_channels.len(),
+            changed = channels_changed,
+            "Channels upsert complete"
+        );
+
+        let valid_tids: HashSet<u32> = titles.titles.iter().map(|t| t.tid).collect();
+        let valid_ch_ids: HashSet<u32> = cached_channels.iter().map(|ch| ch.ch_id).collect();
+        let (stored, changed) = upsert_filtered_programs(
+            self.conn.borrow(),
+            programs,
+            &valid_tids,
+            &valid_ch_ids,
+            &titles.fetched_tids,
+            self.batch_size,
+        )
+        .context("failed to upsert filtered programs")?;
+
+        if let Some(cats) = &self.allowed_cats {
+            cleanup_disallowed_cats(self.conn.borrow(), cats)
+                .context("failed to clean up disallowed categories")?;
+        }
+
+        Ok(ProgramSync {
+            programs: stored,
+            changed,
+            channels: cached_channels.len(),
+            channels_changed,
+        })
+    }
+
+    /// Fetches titles in chunks with retry + exponential backoff for empty
+    /// responses.
+    ///
+    /// When the API returns an empty response for a non-empty chunk (likely
+    /// rate-limited), retries up to [`TITLE_CHUNK_MAX_RETRIES`] times with
+    /// exponential backoff starting at [`TITLE_CHUNK_INITIAL_BACKOFF`].
+    /// [`ApiError::RateLimited`](dtvmgr_api::error::ApiError::RateLimited)
+    /// is retried the same way, waiting for the
+    /// server's `Retry-After` when it sent one.
+    ///
+    /// Every retry is charged to the budget. Once it is exhausted, or the
+    /// client's circuit breaker is open, fetching stops and the TIDs of the
+    /// current and all later chunks are stored in `sync.deferred_tids`.
+    ///
+    /// Each completed chunk is stored via [`Self::store_title_chunk`] and
+    /// reported as [`SyncStage::Titles`]. Returns the number of titles
+    /// fetched and the number dropped by the category filter.
+    #[allow(
+        clippy::arithmetic_side_effects,
+        clippy::future_not_send,
+        clippy::too_many_lines
+    )]
+    async fn fetch_titles_chunked(
+        &mut self,
+        unique_tids: &[u32],
+        sync: &mut TitleSync,
+    ) -> Result<(usize, usize)> {
+        let (mut fetched, mut cat_filtered) = (0_usize, 0_usize);
+        let chunks: Vec<&[u32]> = unique_tids.chunks(TITLE_LOOKUP_CHUNK_SIZE).collect();
+        let total_chunks = chunks.len();
+        self.progress.begin(
+            SyncStage::Titles,
+            Some(u64::try_from(total_chunks).unwrap_or(u64::MAX)),
+        );
+
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            tracing::debug!(?chunk, "TitleLookup requesting TIDs");
+
+            let mut titles = Vec::new();
+            for retry in 0..=TITLE_CHUNK_MAX_RETRIES {
+                let default_backoff = TITLE_CHUNK_INITIAL_BACKOFF * 2u32.pow(retry);
+                let params = TitleLookupParams {
+                    fields: Some(self.title_fields.clone()),
+                    ..TitleLookupParams::from_tids(chunk)
+                };
+                let (backoff, reason) = match self.api.lookup_titles(&params).await {
+                    Ok(result) if !result.is_empty() || chunk.is_empty() => {
+                        titles = result;
+                        break;
+                    }
+                    // Empty response for a non-empty chunk — likely rate-limited.
+                    Ok(_) => (
+                        default_backoff,
+                        "TitleLookup returned 0 titles for non-empty chunk",
+                    ),
+                    Err(e) if e.is_rate_limited() && retry < TITLE_CHUNK_MAX_RETRIES => (
+                        e.retry_after().unwrap_or(default_backoff),
+                        "TitleLookup rate limited",
+                    ),
+                    Err(e) if e.is_circuit_open() => {
+                        tracing::warn!(
+                            chunk = i + 1,
+                            total_chunks,
+                            error = %e,
+                            "Syoboi API unavailable, deferring remaining chunks"
+                        );
+                        sync.deferred_tids = unique_tids
+                            .get(i * TITLE_LOOKUP_CHUNK_SIZE..)
+                            .unwrap_or_default()
+                            .to_vec();
+                        self.progress.finish(SyncStage::Titles);
+                        return Ok((fetched, cat_filtered));
+                    }
+                    Err(e) => {
+                        return Err(anyhow::Error::new(e).context(format!(
+                            "failed to fetch titles for chunk of {} TIDs",
+                            chunk.len()
+                        )));
+                    }
+                };
+
+                if retry < TITLE_CHUNK_MAX_RETRIES {
+                    if !self.budget.try_spend(backoff) {
+                        tracing::warn!(
+                            chunk = i + 1,
+                            total_chunks,
+                            retries = self.budget.retries(),
+                            slept_secs = self.budget.slept().as_secs(),
+                            "Sync retry budget exhausted, deferring remaining chunks"
+                        );
+                        let offset = i * TITLE_LOOKUP_CHUNK_SIZE;
+                        sync.deferred_tids = unique_tids.get(offset..).unwrap_or_default().to_vec();
+                        self.progress.finish(SyncStage::Titles);
+                        return Ok((fetched, cat_filtered));
+                    }
+                    tracing::warn!(
+                        chunk = i + 1,
+                        total_chunks,
+                        retry = retry + 1,
+                        max_retries = TITLE_CHUNK_MAX_RETRIES,
+                        backoff_secs = backoff.as_secs(),
+                        "{reason}, retrying after backoff"
+                    );
+                    self.clock.sleep(backoff).await;
+                } else {
+                    tracing::warn!(
+                        chunk = i + 1,
+                        total_chunks,
+                        requested = chunk.len(),
+                        "TitleLookup returned 0 titles after all retries, skipping chunk"
+                    );
+                }
+            }
+
+            if titles.is_empty() {
+                tracing::warn!(
+                    chunk = i + 1,
+                    total_chunks,
+                    fetched = 0,
+                    "TitleLookup chunk completed"
+                );
+            } else {
+                tracing::debug!(
+                    chunk = i + 1,
+                    total_chunks,
+                    fetched = titles.len(),
+                    "TitleLookup chunk completed"
+                );
+                let done_chunks = self.done_tids.len().div_ceil(TITLE_LOOKUP_CHUNK_SIZE);
+                cat_filtered +=
+                    self.store_title_chunk(chunk, &titles, done_chunks + total_chunks, sync)?;
+            }
+            self.progress.advance(SyncStage::Titles, 1, titles.len());
+            fetched += titles.len();
+        }
+
+        self.progress.finish(SyncStage::Titles);
+        Ok((fetched, cat_filtered))
+    }
+
+    /// Upserts the titles of a completed chunk that pass the category
+    /// filter, adds them to `sync`, and checkpoints the chunk. Returns the
+    /// number of titles dropped by the filter.
+    fn store_title_chunk(
+        &self,
+        chunk: &[u32],
+        titles: &[SyoboiTitle],
+        total_chunks: usize,
+        sync: &mut TitleSync,
+    ) -> Result<usize> {
+        let kept_src: Vec<&SyoboiTitle> = titles
+            .iter()
+            .filter(|t| {
+                self.allowed_cats
+                    .as_ref()
+                    .is_none_or(|cats| t.cat.is_some_and(|c| cats.contains(&c)))
+                    && self
+                        .run_cats
+                        .as_ref()
+                        .is_none_or(|cats| cats.contains(&t.cat.unwrap_or(0)))
+            })
+            .collect();
+        let kept: Vec<CachedTitle> = kept_src.iter().map(|t| to_cached_title(t)).collect();
+        let dropped = titles.len().saturating_sub(kept.len());
+        let changed = upsert_titles_batched(self.conn.borrow(), &kept, self.batch_size)
+            .context("failed to upsert titles")?;
+        for title in kept_src {
+            let (credits, links) = to_title_credits(title);
+            replace_title_credits(self.conn.borrow(), title.tid, &credits, &links)
+                .with_context(|| format!("failed to store staff of title {}", title.tid))?;
+            if let Some(episodes) = to_episodes(title) {
+                upsert_episodes(self.conn.borrow(), title.tid, &episodes).with_context(|| {
+                    format!("failed to store sub-titles of title {}", title.tid)
+                })?;
+            }
+        }
+        sync.changed = sync.changed.saturating_add(changed);
+        sync.titles.extend(kept);
+        if let Some(run_id) = self.run_id {
+            let total = u32::try_from(total_chunks).unwrap_or(u32::MAX);
+            save_title_chunk(self.conn.borrow(), run_id, chunk, total)
+                .context("failed to save title chunk checkpoint")?;
+        }
+        Ok(dropped)
+    }
+}
+
+/// Converts a `SyoboiTitle` to a `CachedTitle` for DB storage.
+#[must_use]
+pub fn to_cached_title(t: &SyoboiTitle) -> CachedTitle {
+    CachedTitle {
+        tid: t.tid,
+        tmdb_series_id: None,
+        tmdb_season_number: None,
+        tmdb_season_id: None,
+        tmdb_movie_id: None,
+        tmdb_episode_group_id: None,
+        title: t.title.clone(),
+        short_title: t.short_title.clone(),
+        title_yomi: t.title_yomi.clone(),
+        title_en: t.title_en.clone(),
+        cat: t.cat,
+        title_flag: t.title_flag,
+        first_year: t.first_year,
+        first_month: t.first_month,
+        keywords: dtvmgr_db::parse_keywords(t.keywords.clone()),
+        last_update: t.last_update.clone(),
+        comment: t.comment.clone(),
+        user_point: t.user_point,
+        user_point_rank: t.user_point_rank,
+        tmdb_original_name: None,
+        tmdb_name: None,
+        tmdb_alt_titles: None,
+        tmdb_last_updated: None,
+    }
+}
+
+/// Parses a title's `SubTitles` into episode rows for DB storage.
+///
+/// Returns `None` when `SubTitles` was not fetched, so the stored episodes
+/// are kept.
+#[must_use]
+pub fn to_episodes(t: &SyoboiTitle) -> Option<Vec<Episode>> {
+    let raw = t.sub_titles.as_deref()?;
+    Some(
+        parse_sub_titles(raw)
+            .into_iter()
+            .map(|(count, sub_title)| Episode {
+                tid: t.tid,
+                count,
+                sub_title,
+            })
+            .collect(),
+    )
+}
+
+/// Parses the staff, cast, and links sections of a title's `Comment` into
+/// rows for DB storage (empty when the comment is missing).
+#[must_use]
+pub fn to_title_credits(t: &SyoboiTitle) -> (Vec<TitleCredit>, Vec<TitleLink>) {
+    let Some(raw) = t.comment.as_deref() else {
+        return (Vec::new(), Vec::new());
+    };
+    let comment = parse_comment(raw);
+    let credits = comment
+        .staff
+        .into_iter()
+        .map(|c| (CreditKind::Staff, c))
+        .chain(comment.cast.into_iter().map(|c| (CreditKind::Cast, c)))
+        .map(|(kind, c)| TitleCredit {
+            tid: t.tid,
+            kind,
+            role: c.role,
+            name: c.name,
+        })
+        .collect();
+    let links = comment
+        .links
+        .into_iter()
+        .map(|l| TitleLink {
+            tid: t.tid,
+            label: l.label,
+            url: l.url,
+        })
+        .collect();
+    (credits, links)
+}
+
+/// Converts a `SyoboiProgram` to a `CachedProgram` for DB storage.
+#[must_use]
+pub fn to_cached_program(p: &SyoboiProgram) -> CachedProgram {
+    CachedProgram {
+        pid: p.pid,
+        tid: p.tid,
+        ch_id: p.ch_id,
+        tmdb_episode_id: None,
+        st_time: p.st_time.clone(),
+        st_offset: p.st_offset,
+        ed_time: p.ed_time.clone(),
+        count: p.count,
+        sub_title: p.sub_title.clone(),
+        flag: p.flag,
+        deleted: p.deleted,
+        warn: p.warn,
+        revision: p.revision,
+        last_update: p.last_update.clone(),
+        st_sub_title: p.st_sub_title.clone(),
+        duration_min: None,
+    }
+}
+
+/// Rewrites the TID of programs whose title was merged into another (see
+/// `title_aliases`).
+///
+/// # Errors
+///
+/// Returns an error if the aliases cannot be loaded.
+pub fn fold_title_aliases(conn: &Connection, programs: &mut [SyoboiProgram]) -> Result<()> {
+    let aliases: HashMap<u32, u32> = load_title_aliases(conn)
+        .context("failed to load title aliases")?
+        .into_iter()
+        .map(|a| (a.old_tid, a.new_tid))
+        .collect();
+    if aliases.is_empty() {
+        return Ok(());
+    }
+    let mut folded: usize = 0;
+    for program in programs.iter_mut() {
+        if let Some(&new_tid) = aliases.get(&program.tid) {
+            program.tid = new_tid;
+            folded = folded.saturating_add(1);
+        }
+    }
+    if folded > 0 {
+        tracing::info!("Folded {folded} programs of merged titles");
+    }
+    Ok(())
+}
+
+/// Filters and upserts programs, skipping those with missing FK references.
+///
+/// `all_fetched_tids` contains TIDs from all API-fetched titles (before cat
+/// filtering) and is used to distinguish cat-filtered skips from genuine
+/// FK misses. Programs are written in transactions of `batch_size` rows.
+/// Returns the number of programs stored and changed.
+///
+/// # Errors
+///
+/// Returns an error if the upsert fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::implicit_hasher)]
+pub fn upsert_filtered_programs(
+    conn: &Connection,
+    programs: &[SyoboiProgram],
+    valid_tids: &HashSet<u32>,
+    valid_ch_ids: &HashSet<u32>,
+    all_fetched_tids: &HashSet<u32>,
+    batch_size: usize,
+) -> Result<(usize, usize)> {
+    let mut cat_filtered: usize = 0;
+    let mut fk_missing: usize = 0;
+    let cached: Vec<CachedProgram> = programs
+        .iter()
+        .filter(|p| {
+            if valid_tids.contains(&p.tid) && valid_ch_ids.contains(&p.ch_id) {
+                return true;
+            }
+            if all_fetched_tids.contains(&p.tid) && !valid_tids.contains(&p.tid) {
+                cat_filtered = cat_filtered.saturating_add(1);
+            } else {
+                fk_missing = fk_missing.saturating_add(1);
+            }
+            false
+        })
+        .map(to_cached_program)
+        .collect();
+    if cat_filtered > 0 {
+        tracing::info!(
+            skipped = cat_filtered,
+            "Skipped programs (title excluded by cat filter)"
+        );
+    }
+    if fk_missing > 0 {
+        tracing::warn!(
+            skipped = fk_missing,
+            "Skipped programs with missing FK references"
+        );
+    }
+    let changed =
+        upsert_programs_batched(conn, &cached, batch_size).context("failed to upsert programs")?;
+    tracing::info!(
+        changed,
+        unchanged = cached.len().saturating_sub(changed),
+        "Programs upsert complete"
+    );
+    Ok((cached.len(), changed))
+}
+
+/// Deletes titles and programs whose categories are not in the allowed set.
+///
+/// # Errors
+///
+/// Returns an error if a DB operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::implicit_hasher)]
+pub fn cleanup_disallowed_cats(conn: &Connection, allowed_cats: &HashSet<u32>) -> Result<()> {
+    let allowed_cats_vec: Vec<u32> = allowed_cats.iter().copied().collect();
+    let titles_deleted = delete_titles_by_cat_not_in(conn, &allowed_cats_vec)
+        .context("failed to delete titles by cat filter")?;
+    if titles_deleted > 0 {
+        tracing::info!(
+            deleted = titles_deleted,
+            "Deleted titles with non-allowed categories"
+        );
+    }
+
+    let remaining_titles = load_titles(conn).context("failed to load titles after cleanup")?;
+    let remaining_tids: Vec<u32> = remaining_titles.iter().map(|t| t.tid).collect();
+    let programs_deleted = delete_programs_by_tids_not_in(conn, &remaining_tids)
+        .context("failed to delete programs by tid filter")?;
+    if programs_deleted > 0 {
+        tracing::info!(deleted = programs_deleted, "Deleted orphaned programs");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use chrono::NaiveDate;
+    use dtvmgr_api::error::ApiError;
+    use dtvmgr_api::syoboi::{SyoboiChannel, SyoboiChannelGroup, TidSelector};
+
+    use super::*;
+
+    /// Mock API serving fixed programs, titles, and channels.
+    struct MockSyoboiApi {
+        programs: Vec<SyoboiProgram>,
+        titles: Vec<SyoboiTitle>,
+    }
+
+    impl LocalSyoboiApi for MockSyoboiApi {
+        async fn lookup_titles(
+            &self,
+            params: &TitleLookupParams,
+        ) -> Result<Vec<SyoboiTitle>, ApiError> {
+            let TidSelector::List(tids) = &params.tids else {
+                return Ok(Vec::new());
+            };
+            Ok(self
+                .titles
+                .iter()
+                .filter(|t| tids.contains(&t.tid))
+                .cloned()
+                .collect())
+        }
+
+        async fn lookup_programs(
+            &self,
+            _params: &ProgLookupParams,
+        ) -> Result<Vec<SyoboiProgram>, ApiError> {
+            Ok(self.programs.clone())
+        }
+
+        async fn lookup_channels(
+            &self,
+            ch_ids: Option<&[u32]>,
+        ) -> Result<Vec<SyoboiChannel>, ApiError> {
+            Ok(ch_ids
+                .unwrap_or_default()
+                .iter()
+                .map(|&ch_id| SyoboiChannel {
+                    ch_id,
+                    ch_gid: None,
+                    ch_name: format!("CH{ch_id}"),
+                    ch_comment: None,
+                    ch_url: None,
+                    last_update: None,
+                    ch_iepg_name: None,
+                    ch_epg_url: None,
+                    ch_number: None,
+                })
+                .collect())
+        }
+
+        async fn lookup_channel_groups(
+            &self,
+            _ch_gids: Option<&[u32]>,
+        ) -> Result<Vec<SyoboiChannelGroup>, ApiError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn make_title(tid: u32, cat: u32) -> SyoboiTitle {
+        SyoboiTitle {
+            tid,
+            last_update: "2024-01-01 00:00:00".to_owned(),
+            title: format!("Title {tid}"),
+            short_title: None,
+            title_yomi: None,
+            title_en: None,
+            comment: None,
+            cat: Some(cat),
+            title_flag: None,
+            first_year: None,
+            first_month: None,
+            first_end_year: None,
+            first_end_month: None,
+            first_ch: None,
+            keywords: None,
+            user_point: None,
+            user_point_rank: None,
+            sub_titles: None,
+        }
+    }
+
+    fn make_program(pid: u32, tid: u32, ch_id: u32) -> SyoboiProgram {
+        SyoboiProgram {
+            pid,
+            tid,
+            st_time: "2024-01-15 20:00:00".to_owned(),
+            st_offset: None,
+            ed_time: "2024-01-15 20:30:00".to_owned(),
+            count: Some(1),
+            sub_title: None,
+            prog_comment: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            ch_id,
+            revision: None,
+            last_update: None,
+            st_sub_title: None,
+        }
+    }
+
+    fn make_range() -> TimeRange {
+        TimeRange::new(
+            NaiveDate::from_ymd_opt(2024, 1, 15)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 16)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_programs_stores_allowed_titles_and_programs() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let api = MockSyoboiApi {
+            programs: vec![make_program(1, 10, 5), make_program(2, 20, 5)],
+            titles: vec![make_title(10, 1), make_title(20, 7)],
+        };
+
+        // Act
+        let mut engine = SyncEngine::new(&api, conn).allowed_cats([1].into());
+        let report = engine.sync_programs(make_range(), &[5]).await.unwrap();
+
+        // Assert: tid 20 (cat 7) is filtered out along with its program
+        assert_eq!(report.titles.titles.len(), 1);
+        assert_eq!(report.titles.fetched_tids, [10, 20].into());
+        assert!(report.titles.deferred_tids.is_empty());
+        assert_eq!(report.programs.programs, 1);
+        assert_eq!(report.programs.changed, 1);
+        assert_eq!(report.programs.channels, 1);
+        let stored = dtvmgr_db::load_programs(engine.conn()).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].pid, 1);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_programs_stores_episodes_for_program_sub_titles() {
+        // Arrange: the program has count 1 but no subtitle of its own
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let mut title = make_title(10, 1);
+        title.sub_titles = Some(String::from("*01*始まり\r\n*02*続き"));
+        let api = MockSyoboiApi {
+            programs: vec![make_program(1, 10, 5)],
+            titles: vec![title],
+        };
+
+        // Act
+        let mut engine = SyncEngine::new(&api, conn);
+        engine.sync_programs(make_range(), &[5]).await.unwrap();
+
+        // Assert
+        let episodes = dtvmgr_db::load_episodes(engine.conn(), 10).unwrap();
+        assert_eq!(episodes.len(), 2);
+        let program = dtvmgr_db::load_program(engine.conn(), 1).unwrap().unwrap();
+        assert_eq!(program.st_sub_title.as_deref(), Some("始まり"));
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_programs_folds_merged_titles() {
+        // Arrange: tid 10 was merged into tid 20
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        dtvmgr_db::upsert_titles(
+            &conn,
+            &[
+                to_cached_title(&make_title(10, 1)),
+                to_cached_title(&make_title(20, 1)),
+            ],
+        )
+        .unwrap();
+        dtvmgr_db::merge_titles(&conn, 10, 20).unwrap();
+        let api = MockSyoboiApi {
+            programs: vec![make_program(1, 10, 5), make_program(2, 20, 5)],
+            titles: vec![make_title(10, 1), make_title(20, 1)],
+        };
+
+        // Act
+        let mut engine = SyncEngine::new(&api, conn).allowed_cats([1].into());
+        let report = engine.sync_programs(make_range(), &[5]).await.unwrap();
+
+        // Assert: the old title is not re-created
+        assert_eq!(report.titles.fetched_tids, [20].into());
+        let stored = dtvmgr_db::load_programs(engine.conn()).unwrap();
+        assert!(stored.iter().all(|p| p.tid == 20));
+        assert_eq!(stored.len(), 2);
+        let tids: Vec<u32> = dtvmgr_db::load_titles(engine.conn())
+            .unwrap()
+            .iter()
+            .map(|t| t.tid)
+            .collect();
+        assert_eq!(tids, vec![20]);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_programs_run_cats_keeps_cached_titles() {
+        // Arrange: tid 30 (cat 1) is already cached
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        dtvmgr_db::upsert_titles(&conn, &[to_cached_title(&make_title(30, 1))]).unwrap();
+        let api = MockSyoboiApi {
+            programs: vec![make_program(1, 10, 5), make_program(2, 20, 5)],
+            titles: vec![make_title(10, 1), make_title(20, 7)],
+        };
+
+        // Act
+        let mut engine = SyncEngine::new(&api, conn)
+            .allowed_cats([1, 7].into())
+            .run_cats([7].into());
+        let report = engine.sync_programs(make_range(), &[5]).await.unwrap();
+
+        // Assert: only the OVA is stored, and the cached anime survives
+        assert_eq!(report.titles.titles.len(), 1);
+        assert_eq!(report.titles.titles[0].tid, 20);
+        assert_eq!(report.programs.programs, 1);
+        let tids: Vec<u32> = dtvmgr_db::load_titles(engine.conn())
+            .unwrap()
+            .iter()
+            .map(|t| t.tid)
+            .collect();
+        assert_eq!(tids, vec![20, 30]);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_stores_comment_credits() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let mut title = make_title(10, 1);
+        title.comment = Some(String::from(
+            "*リンク\n-[[公式 https://example.com/]]\n*スタッフ\n:監督:古橋一浩\n*キャスト\n:ロイド:江口拓也",
+        ));
+        let api = MockSyoboiApi {
+            programs: Vec::new(),
+            titles: vec![title],
+        };
+        let mut engine = SyncEngine::new(&api, conn);
+
+        // Act
+        engine.sync_titles(&[10]).await.unwrap();
+
+        // Assert
+        let credits = dtvmgr_db::load_title_credits(engine.conn(), 10).unwrap();
+        let names: Vec<(CreditKind, &str)> =
+            credits.iter().map(|c| (c.kind, c.name.as_str())).collect();
+        assert_eq!(
+            names,
+            vec![
+                (CreditKind::Staff, "古橋一浩"),
+                (CreditKind::Cast, "江口拓也")
+            ]
+        );
+        let links = dtvmgr_db::load_title_links(engine.conn(), 10).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].url, "https://example.com/");
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_stores_user_points() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let mut title = make_title(10, 1);
+        title.user_point = Some(42);
+        title.user_point_rank = Some(7);
+        let api = MockSyoboiApi {
+            programs: Vec::new(),
+            titles: vec![title],
+        };
+        let fields: Vec<String> = TITLE_SYNC_FIELDS
+            .iter()
+            .chain(&["UserPoint", "UserPointRank"])
+            .map(|f| (*f).to_owned())
+            .collect();
+        validate_title_fields(&fields).unwrap();
+        let mut engine = SyncEngine::new(&api, conn).title_fields(fields);
+
+        // Act
+        engine.sync_titles(&[10]).await.unwrap();
+
+        // Assert
+        let stored = dtvmgr_db::load_titles(engine.conn()).unwrap();
+        assert_eq!(stored[0].user_point, Some(42));
+        assert_eq!(stored[0].user_point_rank, Some(7));
+    }
+
+    #[test]
+    fn test_validate_fields_rejects_unknown_and_missing_fields() {
+        // Arrange
+        let unknown = vec![
+            String::from("TID"),
+            String::from("LastUpdate"),
+            String::from("Title"),
+            String::from("Points"),
+        ];
+        let missing = vec![String::from("PID"), String::from("Count")];
+
+        // Act
+        let unknown_err = validate_title_fields(&unknown).unwrap_err().to_string();
+        let missing_err = validate_program_fields(&missing).unwrap_err().to_string();
+
+        // Assert
+        assert!(unknown_err.starts_with("title_fields: unknown field(s) Points (known: TID, "));
+        assert_eq!(
+            missing_err,
+            "program_fields: required field(s) TID, StTime, EdTime, ChID missing"
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_defers_when_budget_exhausted() {
+        // Arrange: the API returns nothing, and no retry is allowed
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let api = MockSyoboiApi {
+            programs: Vec::new(),
+            titles: Vec::new(),
+        };
+        let mut engine = SyncEngine::new(&api, conn).budget(RetryBudget::new(0, Duration::ZERO));
+
+        // Act
+        let result = engine.sync_titles(&[10, 20]).await.unwrap();
+
+        // Assert
+        assert!(result.titles.is_empty());
+        assert_eq!(result.deferred_tids, vec![10, 20]);
+        assert!(result.fetched_tids.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_backoff_uses_injected_clock() {
+        // Arrange: the API returns nothing, so every retry backs off
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let api = MockSyoboiApi {
+            programs: Vec::new(),
+            titles: Vec::new(),
+        };
+        let clock = Arc::new(dtvmgr_api::clock::FakeClock::new(
+            std::time::SystemTime::UNIX_EPOCH,
+        ));
+        let mut engine = SyncEngine::new(&api, conn).clock(clock.clone());
+
+        // Act
+        let result = engine.sync_titles(&[10]).await.unwrap();
+
+        // Assert: 10 + 20 + 40 + 80 + 160 seconds, without real sleeps
+        assert!(result.titles.is_empty());
+        assert!(result.deferred_tids.is_empty());
+        assert_eq!(clock.elapsed(), Duration::from_secs(310));
+    }
+
+    /// Mock API whose first title lookup fails with `error()`.
+    struct FailsOnceApi {
+        inner: MockSyoboiApi,
+        error: fn() -> ApiError,
+        failed: std::sync::atomic::AtomicBool,
+    }
+
+    impl LocalSyoboiApi for FailsOnceApi {
+        async fn lookup_titles(
+            &self,
+            params: &TitleLookupParams,
+        ) -> Result<Vec<SyoboiTitle>, ApiError> {
+            if !self.failed.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                return Err((self.error)());
+            }
+            self.inner.lookup_titles(params).await
+        }
+
+        async fn lookup_programs(
+            &self,
+            params: &ProgLookupParams,
+        ) -> Result<Vec<SyoboiProgram>, ApiError> {
+            self.inner.lookup_programs(params).await
+        }
+
+        async fn lookup_channels(
+            &self,
+            ch_ids: Option<&[u32]>,
+        ) -> Result<Vec<SyoboiChannel>, ApiError> {
+            self.inner.lookup_channels(ch_ids).await
+        }
+
+        async fn lookup_channel_groups(
+            &self,
+            ch_gids: Option<&[u32]>,
+        ) -> Result<Vec<SyoboiChannelGroup>, ApiError> {
+            self.inner.lookup_channel_groups(ch_gids).await
+        }
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_waits_retry_after_on_rate_limit() {
+        // Arrange: the first lookup is rate limited with Retry-After: 42
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let api = FailsOnceApi {
+            inner: MockSyoboiApi {
+                programs: Vec::new(),
+                titles: vec![make_title(10, 1)],
+            },
+            error: || ApiError::RateLimited {
+                message: String::from("rate limited"),
+                retry_after: Some(Duration::from_secs(42)),
+            },
+            failed: std::sync::atomic::AtomicBool::new(false),
+        };
+        let clock = Arc::new(dtvmgr_api::clock::FakeClock::new(
+            std::time::SystemTime::UNIX_EPOCH,
+        ));
+        let mut engine = SyncEngine::new(&api, conn).clock(clock.clone());
+
+        // Act
+        let result = engine.sync_titles(&[10]).await.unwrap();
+
+        // Assert: the retry waited for the server-requested duration
+        assert_eq!(result.titles.len(), 1);
+        assert_eq!(clock.elapsed(), Duration::from_secs(42));
+        assert_eq!(engine.retry_budget().retries(), 1);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_defers_when_circuit_open() {
+        // Arrange: the client rejects the first lookup with an open circuit
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let api = FailsOnceApi {
+            inner: MockSyoboiApi {
+                programs: Vec::new(),
+                titles: vec![make_title(10, 1)],
+            },
+            error: || ApiError::CircuitOpen {
+                message: String::from("circuit open"),
+                retry_after: Duration::from_mins(5),
+            },
+            failed: std::sync::atomic::AtomicBool::new(false),
+        };
+        let mut engine = SyncEngine::new(&api, conn);
+
+        // Act
+        let result = engine.sync_titles(&[10, 20]).await.unwrap();
+
+        // Assert: nothing fetched, no backoff spent, everything deferred
+        assert!(result.titles.is_empty());
+        assert_eq!(result.deferred_tids, vec![10, 20]);
+        assert_eq!(engine.retry_budget().retries(), 0);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_checkpoints_and_resumes() {
+        // Arrange: a first attempt stores title 10 under run `run_id`
+        let dir = tempfile::tempdir().unwrap();
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let run_id = dtvmgr_db::start_sync_run(&conn, "2024-01-15T00:00:00Z").unwrap();
+        let first = MockSyoboiApi {
+            programs: Vec::new(),
+            titles: vec![make_title(10, 1)],
+        };
+        let mut engine = SyncEngine::new(&first, conn).checkpoint(run_id, HashSet::new());
+        engine.sync_titles(&[10]).await.unwrap();
+        let conn = engine.into_conn();
+        let done: HashSet<u32> = dtvmgr_db::load_sync_run(&conn, run_id)
+            .unwrap()
+            .unwrap()
+            .done_tids
+            .into_iter()
+            .collect();
+
+        // Act: the resumed attempt's API no longer serves title 10
+        let second = MockSyoboiApi {
+            programs: Vec::new(),
+            titles: vec![make_title(20, 1)],
+        };
+        let mut engine = SyncEngine::new(&second, conn).checkpoint(run_id, done);
+        let result = engine.sync_titles(&[10, 20]).await.unwrap();
+
+        // Assert: title 10 comes from the DB, title 20 from the API
+        let mut tids: Vec<u32> = result.titles.iter().map(|t| t.tid).collect();
+        tids.sort_unstable();
+        assert_eq!(tids, vec![10, 20]);
+        assert_eq!(result.fetched_tids, [10, 20].into());
+        let run = dtvmgr_db::load_sync_run(engine.conn(), run_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.title_chunks_done, 2);
+        assert_eq!(run.done_tids, vec![10, 20]);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn test_sync_titles_with_pooled_connection() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let pool = dtvmgr_db::DbPool::new(
+            Some(dir.path().to_path_buf()),
+            dtvmgr_db::DbOptions::default(),
+            1,
+        );
+        let api = MockSyoboiApi {
+            programs: Vec::new(),
+            titles: vec![make_title(10, 1)],
+        };
+        let mut engine = SyncEngine::new(&api, pool.get().unwrap());
+
+        // Act
+        engine.sync_titles(&[10]).await.unwrap();
+        drop(engine);
+
+        // Assert: the connection went back to the pool with the title stored
+        let conn = pool.get().unwrap();
+        assert_eq!(pool.open_connections(), 1);
+        assert_eq!(
+            dtvmgr_db::load_titles_by_tids(&conn, &[10]).unwrap().len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! User tags and notes attached to programs.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Tags and notes of all annotated programs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramAnnotations {
+    /// Tags per PID, sorted.
+    pub tags: HashMap<u32, Vec<String>>,
+    /// Note per PID.
+    pub notes: HashMap<u32, String>,
+}
+
+/// Adds `tag` to a program. Returns `false` if the program already had it.
+///
+/// # Errors
+///
+/// Returns an error if the program is not cached or the insert fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn add_program_tag(db: &impl DbHandle, pid: u32, tag: &str, created_at: &str) -> Result<bool> {
+    let conn = &*db.conn()?;
+    let changed = conn
+        .execute(
+            "INSERT INTO program_tags (pid, tag, created_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(pid, tag) DO NOTHING",
+            rusqlite::params![pid, tag, created_at],
+        )
+        .with_context(|| format!("failed to tag program {pid}"))?;
+    Ok(changed > 0)
+}
+
+/// Removes `tag` from a program. Returns `false` if it was not set.
+///
+/// # Errors
+///
+/// Returns an error if the delete fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn remove_program_tag(db: &impl DbHandle, pid: u32, tag: &str) -> Result<bool> {
+    let conn = &*db.conn()?;
+    let changed = conn
+        .execute(
+            "DELETE FROM program_tags WHERE pid = ?1 AND tag = ?2",
+            rusqlite::params![pid, tag],
+        )
+        .with_context(|| format!("failed to untag program {pid}"))?;
+    Ok(changed > 0)
+}
+
+/// Sets (or replaces) the note of a program.
+///
+/// # Errors
+///
+/// Returns an error if the program is not cached or the upsert fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn set_program_note(db: &impl DbHandle, pid: u32, body: &str, updated_at: &str) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO notes (pid, body, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(pid) DO UPDATE SET body = excluded.body, updated_at = excluded.updated_at",
+        rusqlite::params![pid, body, updated_at],
+    )
+    .with_context(|| format!("failed to save note of program {pid}"))?;
+    Ok(())
+}
+
+/// Deletes the note of a program. Returns `false` if it had none.
+///
+/// # Errors
+///
+/// Returns an error if the delete fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn delete_program_note(db: &impl DbHandle, pid: u32) -> Result<bool> {
+    let conn = &*db.conn()?;
+    let changed = conn
+        .execute("DELETE FROM notes WHERE pid = ?1", [pid])
+        .with_context(|| format!("failed to delete note of program {pid}"))?;
+    Ok(changed > 0)
+}
+
+/// Loads the tags and notes of all programs.
+///
+/// # Errors
+///
+/// Returns an error if a query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_program_annotations(db: &impl DbHandle) -> Result<ProgramAnnotations> {
+    let conn = &*db.conn()?;
+    let mut annotations = ProgramAnnotations::default();
+
+    let mut stmt = conn
+        .prepare("SELECT pid, tag FROM program_tags ORDER BY pid, tag")
+        .context("failed to prepare program tags query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))
+        })
+        .context("failed to query program tags")?;
+    for row in rows {
+        let (pid, tag) = row.context("failed to read program tag row")?;
+        annotations.tags.entry(pid).or_default().push(tag);
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT pid, body FROM notes")
+        .context("failed to prepare notes query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))
+        })
+        .context("failed to query notes")?;
+    for row in rows {
+        let (pid, body) = row.context("failed to read note row")?;
+        annotations.notes.insert(pid, body);
+    }
+    Ok(annotations)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use rusqlite::Connection;
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn seeded_db(dir: &tempfile::TempDir) -> Connection {
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+             INSERT INTO titles (tid, title, last_update) VALUES (6309, 'Test', '2024-01-01 00:00:00');
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time)
+                 VALUES (100, 6309, 7, '2024-01-01 23:00:00', '2024-01-01 23:30:00');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_program_tags_and_notes_round_trip() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+        let now = "2024-01-02T00:00:00Z";
+
+        // Act
+        assert!(add_program_tag(&conn, 100, "skip", now).unwrap());
+        assert!(add_program_tag(&conn, 100, "recorded", now).unwrap());
+        assert!(!add_program_tag(&conn, 100, "skip", now).unwrap());
+        assert!(remove_program_tag(&conn, 100, "skip").unwrap());
+        set_program_note(&conn, 100, "first", now).unwrap();
+        set_program_note(&conn, 100, "dropped frames at 12:30", now).unwrap();
+        let annotations = load_program_annotations(&conn).unwrap();
+
+        // Assert
+        assert_eq!(annotations.tags[&100], vec![String::from("recorded")]);
+        assert_eq!(annotations.notes[&100], "dropped frames at 12:30");
+        assert!(delete_program_note(&conn, 100).unwrap());
+        assert!(!delete_program_note(&conn, 100).unwrap());
+        assert!(add_program_tag(&conn, 999, "skip", now).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_annotations_are_removed_with_their_program() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+        add_program_tag(&conn, 100, "failed", "2024-01-02T00:00:00Z").unwrap();
+        set_program_note(&conn, 100, "retry", "2024-01-02T00:00:00Z").unwrap();
+
+        // Act
+        conn.execute("DELETE FROM programs WHERE pid = 100", [])
+            .unwrap();
+        let annotations = load_program_annotations(&conn).unwrap();
+
+        // Assert
+        assert_eq!(annotations, ProgramAnnotations::default());
+    }
+}
@@ -0,0 +1,242 @@
+//! Upsert throughput benchmark on a scratch database.
+//!
+//! Generates synthetic channels, titles, and programs and times the batched
+//! upserts used by `db sync`, so batch sizes can be compared on the target
+//! disk. Run it against an empty database; it never reads real cache data.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::channels::{CachedChannel, upsert_channels};
+use crate::programs::{CachedProgram, upsert_programs_batched};
+use crate::titles::{CachedTitle, upsert_titles_batched};
+
+/// Number of synthetic channels.
+const CHANNELS: u32 = 20;
+
+/// Programs generated per synthetic title.
+const PROGRAMS_PER_TITLE: usize = 10;
+
+/// Timing of one upsert pass.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BenchSample {
+    /// Rows per transaction (`0` = all rows in one transaction).
+    pub batch_size: usize,
+    /// Pass name: `titles`, `programs_insert`, or `programs_update`.
+    pub pass: &'static str,
+    /// Rows written.
+    pub rows: usize,
+    /// Rows reported as changed.
+    pub changed: usize,
+    /// Wall-clock time of the pass, in seconds.
+    pub secs: f64,
+}
+
+impl BenchSample {
+    /// Creates a sample from the measured `elapsed` time.
+    const fn new(
+        batch_size: usize,
+        pass: &'static str,
+        rows: usize,
+        changed: usize,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            batch_size,
+            pass,
+            rows,
+            changed,
+            secs: elapsed.as_secs_f64(),
+        }
+    }
+
+    /// Returns the throughput in rows per second.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
+    pub fn rows_per_sec(&self) -> f64 {
+        if self.secs > 0.0 {
+            self.rows as f64 / self.secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Inserts `programs` synthetic programs (and one title per ten programs)
+/// into `conn` with `batch_size`, then rewrites every program with a new
+/// `last_update`. Returns one sample per pass.
+///
+/// `conn` should be an empty, migrated database.
+///
+/// # Errors
+///
+/// Returns an error if an upsert fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, fields(programs, batch_size), err(level = "error"))]
+pub fn bench_upserts(
+    conn: &Connection,
+    programs: usize,
+    batch_size: usize,
+) -> Result<Vec<BenchSample>> {
+    let channels: Vec<CachedChannel> = (1..=CHANNELS).map(synthetic_channel).collect();
+    upsert_channels(conn, &channels).context("failed to insert channels")?;
+
+    let title_count = programs.div_ceil(PROGRAMS_PER_TITLE).max(1);
+    let titles: Vec<CachedTitle> = (0..title_count).map(synthetic_title).collect();
+    let mut rows: Vec<CachedProgram> = (0..programs)
+        .map(|i| synthetic_program(i, title_count))
+        .collect();
+
+    let mut samples = Vec::with_capacity(3);
+    let started = Instant::now();
+    let changed =
+        upsert_titles_batched(conn, &titles, batch_size).context("failed to insert titles")?;
+    samples.push(BenchSample::new(
+        batch_size,
+        "titles",
+        titles.len(),
+        changed,
+        started.elapsed(),
+    ));
+
+    let started = Instant::now();
+    let changed =
+        upsert_programs_batched(conn, &rows, batch_size).context("failed to insert programs")?;
+    samples.push(BenchSample::new(
+        batch_size,
+        "programs_insert",
+        rows.len(),
+        changed,
+        started.elapsed(),
+    ));
+
+    for p in &mut rows {
+        p.last_update = Some(String::from("2024-02-01 00:00:00"));
+    }
+    let started = Instant::now();
+    let changed =
+        upsert_programs_batched(conn, &rows, batch_size).context("failed to update programs")?;
+    samples.push(BenchSample::new(
+        batch_size,
+        "programs_update",
+        rows.len(),
+        changed,
+        started.elapsed(),
+    ));
+    Ok(samples)
+}
+
+/// Returns synthetic channel `ch_id`.
+fn synthetic_channel(ch_id: u32) -> CachedChannel {
+    CachedChannel {
+        ch_id,
+        ch_gid: None,
+        ch_name: format!("Bench {ch_id}"),
+        ch_url: None,
+        ch_iepg_name: None,
+        ch_comment: None,
+        logo_url: None,
+    }
+}
+
+/// Returns the synthetic title with index `i` (TID `i + 1`).
+fn synthetic_title(i: usize) -> CachedTitle {
+    CachedTitle {
+        tid: u32::try_from(i).unwrap_or(u32::MAX).saturating_add(1),
+        tmdb_series_id: None,
+        tmdb_season_number: None,
+        tmdb_season_id: None,
+        tmdb_movie_id: None,
+        tmdb_episode_group_id: None,
+        title: format!("Bench title {i}"),
+        short_title: None,
+        title_yomi: None,
+        title_en: None,
+        cat: Some(1),
+        title_flag: None,
+        first_year: Some(2024),
+        first_month: Some(1),
+        keywords: Vec::new(),
+        last_update: String::from("2024-01-01 00:00:00"),
+        tmdb_original_name: None,
+        tmdb_name: None,
+        tmdb_alt_titles: None,
+        tmdb_last_updated: None,
+        comment: None,
+        user_point: None,
+        user_point_rank: None,
+    }
+}
+
+/// Returns the synthetic program with index `i`: half-hour slots spread
+/// over 28 days, titles and channels assigned round-robin.
+// All operands are small: remainders of constants and indices below `u32::MAX`.
+#[allow(clippy::arithmetic_side_effects)]
+fn synthetic_program(i: usize, titles: usize) -> CachedProgram {
+    let slot = i % 48;
+    let day = (i / 48) % 28 + 1;
+    let (hour, minute) = (slot / 2, (slot % 2) * 30);
+    let index = u32::try_from(i).unwrap_or(u32::MAX);
+    CachedProgram {
+        pid: index.saturating_add(1),
+        tid: u32::try_from(i % titles)
+            .unwrap_or(u32::MAX)
+            .saturating_add(1),
+        ch_id: index % CHANNELS + 1,
+        tmdb_episode_id: None,
+        st_time: format!("2024-01-{day:02} {hour:02}:{minute:02}:00"),
+        st_offset: None,
+        ed_time: format!("2024-01-{day:02} {hour:02}:{:02}:00", minute + 29),
+        count: Some(index / u32::try_from(titles).unwrap_or(u32::MAX) + 1),
+        sub_title: None,
+        flag: None,
+        deleted: None,
+        warn: None,
+        revision: None,
+        last_update: Some(String::from("2024-01-01 00:00:00")),
+        st_sub_title: None,
+        duration_min: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_bench_upserts_reports_each_pass() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+
+        // Act
+        let samples = bench_upserts(&conn, 250, 100).unwrap();
+
+        // Assert: every row is inserted, then every row is updated
+        let passes: Vec<(&str, usize, usize)> = samples
+            .iter()
+            .map(|s| (s.pass, s.rows, s.changed))
+            .collect();
+        assert_eq!(
+            passes,
+            vec![
+                ("titles", 25, 25),
+                ("programs_insert", 250, 250),
+                ("programs_update", 250, 250),
+            ]
+        );
+        let count: usize = conn
+            .query_row("SELECT COUNT(*) FROM programs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 250);
+    }
+}
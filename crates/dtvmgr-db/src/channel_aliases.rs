@@ -0,0 +1,161 @@
+//! Mapping of Syoboi channels to external tuner identifiers.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// External identifiers of one Syoboi channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ChannelAlias {
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Mirakurun service ID (`id` of `/api/services`).
+    pub mirakurun_service_id: Option<u64>,
+    /// `EPGStation` channel ID.
+    pub epgstation_channel_id: Option<u64>,
+    /// Broadcast type (`GR`, `BS`, `CS`, `SKY`).
+    pub channel_type: Option<String>,
+    /// Service name on the tuner side.
+    pub name: Option<String>,
+}
+
+/// Inserts or replaces channel aliases. Returns the number of rows changed.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn upsert_channel_aliases(db: &impl DbHandle, aliases: &[ChannelAlias]) -> Result<usize> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+
+    let mut stmt = tx
+        .prepare(
+            "INSERT INTO channel_aliases
+                 (ch_id, mirakurun_service_id, epgstation_channel_id, channel_type, name)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(ch_id) DO UPDATE SET
+                 mirakurun_service_id  = excluded.mirakurun_service_id,
+                 epgstation_channel_id = excluded.epgstation_channel_id,
+                 channel_type          = excluded.channel_type,
+                 name                  = excluded.name,
+                 updated_at            = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE channel_aliases.mirakurun_service_id  IS NOT excluded.mirakurun_service_id
+                OR channel_aliases.epgstation_channel_id IS NOT excluded.epgstation_channel_id
+                OR channel_aliases.channel_type          IS NOT excluded.channel_type
+                OR channel_aliases.name                  IS NOT excluded.name",
+        )
+        .context("failed to prepare channel alias upsert")?;
+
+    let mut changed: usize = 0;
+    for a in aliases {
+        let rows = stmt
+            .execute(rusqlite::params![
+                a.ch_id,
+                a.mirakurun_service_id,
+                a.epgstation_channel_id,
+                a.channel_type,
+                a.name,
+            ])
+            .with_context(|| format!("failed to upsert channel alias {}", a.ch_id))?;
+        changed = changed.saturating_add(rows);
+    }
+    drop(stmt);
+
+    tx.commit().context("failed to commit transaction")?;
+    Ok(changed)
+}
+
+/// Loads all channel aliases ordered by Syoboi channel ID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_channel_aliases(db: &impl DbHandle) -> Result<Vec<ChannelAlias>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT ch_id, mirakurun_service_id, epgstation_channel_id, channel_type, name
+             FROM channel_aliases
+             ORDER BY ch_id",
+        )
+        .context("failed to prepare channel aliases query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ChannelAlias {
+                ch_id: row.get(0)?,
+                mirakurun_service_id: row.get(1)?,
+                epgstation_channel_id: row.get(2)?,
+                channel_type: row.get(3)?,
+                name: row.get(4)?,
+            })
+        })
+        .context("failed to query channel aliases")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read channel alias rows")
+}
+
+/// Deletes the aliases of `ch_ids`. Returns the number of rows deleted.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn delete_channel_aliases(db: &impl DbHandle, ch_ids: &[u32]) -> Result<usize> {
+    let conn = &*db.conn()?;
+    let mut deleted: usize = 0;
+    for ch_id in ch_ids {
+        let rows = conn
+            .execute("DELETE FROM channel_aliases WHERE ch_id = ?1", [ch_id])
+            .with_context(|| format!("failed to delete channel alias {ch_id}"))?;
+        deleted = deleted.saturating_add(rows);
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn alias(ch_id: u32, service: u64) -> ChannelAlias {
+        ChannelAlias {
+            ch_id,
+            mirakurun_service_id: Some(service),
+            epgstation_channel_id: Some(service),
+            channel_type: Some(String::from("GR")),
+            name: None,
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_load_and_delete_channel_aliases() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        upsert_channel_aliases(&conn, &[alias(7, 3_273_601_024), alias(1, 3_273_701_032)]).unwrap();
+
+        // Act: one unchanged, one changed
+        let changed =
+            upsert_channel_aliases(&conn, &[alias(7, 3_273_601_024), alias(1, 400_101)]).unwrap();
+        let deleted = delete_channel_aliases(&conn, &[7, 99]).unwrap();
+        let loaded = load_channel_aliases(&conn).unwrap();
+
+        // Assert
+        assert_eq!(changed, 1);
+        assert_eq!(deleted, 1);
+        assert_eq!(loaded, vec![alias(1, 400_101)]);
+    }
+}
@@ -1,11 +1,14 @@
 //! Channel and channel group cache CRUD operations.
 
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::OptionalExtension as _;
+use serde::Serialize;
 use tracing::instrument;
 
+use crate::pool::DbHandle;
+
 /// A cached channel group.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CachedChannelGroup {
     /// Channel group ID.
     pub ch_gid: u32,
@@ -16,7 +19,7 @@ pub struct CachedChannelGroup {
 }
 
 /// A cached channel.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 pub struct CachedChannel {
     /// Channel ID.
     pub ch_id: u32,
@@ -24,6 +27,14 @@ pub struct CachedChannel {
     pub ch_gid: Option<u32>,
     /// Channel name.
     pub ch_name: String,
+    /// Broadcaster website (`ChURL`).
+    pub ch_url: Option<String>,
+    /// iEPG station name (`ChiEPGName`).
+    pub ch_iepg_name: Option<String>,
+    /// Channel comment (`ChComment`).
+    pub ch_comment: Option<String>,
+    /// Logo image URL, set by `channels fetch-logos`.
+    pub logo_url: Option<String>,
 }
 
 /// Upserts channel groups into the cache. Returns the number of rows changed.
@@ -35,7 +46,8 @@ pub struct CachedChannel {
 ///
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn upsert_channel_groups(conn: &Connection, groups: &[CachedChannelGroup]) -> Result<usize> {
+pub fn upsert_channel_groups(db: &impl DbHandle, groups: &[CachedChannelGroup]) -> Result<usize> {
+    let conn = &*db.conn()?;
     let tx = conn
         .unchecked_transaction()
         .context("failed to begin transaction")?;
@@ -75,7 +87,8 @@ pub fn upsert_channel_groups(conn: &Connection, groups: &[CachedChannelGroup]) -
 ///
 /// Returns an error if the database query fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn load_channel_groups(conn: &Connection) -> Result<Vec<CachedChannelGroup>> {
+pub fn load_channel_groups(db: &impl DbHandle) -> Result<Vec<CachedChannelGroup>> {
+    let conn = &*db.conn()?;
     let mut stmt = conn
         .prepare("SELECT ch_gid, ch_group_name, ch_group_order FROM channel_groups ORDER BY ch_group_order")
         .context("failed to prepare channel_groups query")?;
@@ -97,35 +110,50 @@ pub fn load_channel_groups(conn: &Connection) -> Result<Vec<CachedChannelGroup>>
 /// Upserts channels into the cache. Returns the number of rows changed.
 ///
 /// Uses `INSERT ... ON CONFLICT(ch_id) DO UPDATE SET` to update existing rows.
-/// When `ch_gid` is `None`, existing `ch_gid` is preserved via `COALESCE`.
-/// Only updates when a value has actually changed.
+/// When `ch_gid` or a metadata field is `None`, the existing value is
+/// preserved via `COALESCE`. `logo_url` is left untouched (see
+/// [`update_channel_logo`]). Only updates when a value has actually changed.
 ///
 /// # Errors
 ///
 /// Returns an error if the database operation fails.
 #[allow(clippy::module_name_repetitions)]
 #[instrument(skip_all, err(level = "error"))]
-pub fn upsert_channels(conn: &Connection, channels: &[CachedChannel]) -> Result<usize> {
+pub fn upsert_channels(db: &impl DbHandle, channels: &[CachedChannel]) -> Result<usize> {
+    let conn = &*db.conn()?;
     let tx = conn
         .unchecked_transaction()
         .context("failed to begin transaction")?;
 
     let mut stmt = tx
         .prepare(
-            "INSERT INTO channels (ch_id, ch_gid, ch_name)
-            VALUES (?1, ?2, ?3)
+            "INSERT INTO channels (ch_id, ch_gid, ch_name, ch_url, ch_iepg_name, ch_comment)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             ON CONFLICT(ch_id) DO UPDATE SET
-                ch_gid  = COALESCE(excluded.ch_gid, channels.ch_gid),
-                ch_name = excluded.ch_name
+                ch_gid       = COALESCE(excluded.ch_gid, channels.ch_gid),
+                ch_name      = excluded.ch_name,
+                ch_url       = COALESCE(excluded.ch_url, channels.ch_url),
+                ch_iepg_name = COALESCE(excluded.ch_iepg_name, channels.ch_iepg_name),
+                ch_comment   = COALESCE(excluded.ch_comment, channels.ch_comment)
             WHERE channels.ch_name != excluded.ch_name
-               OR COALESCE(channels.ch_gid, -1) != COALESCE(excluded.ch_gid, channels.ch_gid, -1)",
+               OR COALESCE(channels.ch_gid, -1) != COALESCE(excluded.ch_gid, channels.ch_gid, -1)
+               OR channels.ch_url       IS NOT COALESCE(excluded.ch_url, channels.ch_url)
+               OR channels.ch_iepg_name IS NOT COALESCE(excluded.ch_iepg_name, channels.ch_iepg_name)
+               OR channels.ch_comment   IS NOT COALESCE(excluded.ch_comment, channels.ch_comment)",
         )
         .context("failed to prepare channels upsert")?;
 
     let mut changed: usize = 0;
     for ch in channels {
         let rows = stmt
-            .execute(rusqlite::params![ch.ch_id, ch.ch_gid, ch.ch_name])
+            .execute(rusqlite::params![
+                ch.ch_id,
+                ch.ch_gid,
+                ch.ch_name,
+                ch.ch_url,
+                ch.ch_iepg_name,
+                ch.ch_comment
+            ])
             .with_context(|| format!("failed to upsert channel {}", ch.ch_id))?;
         changed = changed.saturating_add(rows);
     }
@@ -142,9 +170,13 @@ pub fn upsert_channels(conn: &Connection, channels: &[CachedChannel]) -> Result<
 /// Returns an error if the database query fails.
 #[allow(clippy::module_name_repetitions)]
 #[instrument(skip_all, err(level = "error"))]
-pub fn load_channels(conn: &Connection) -> Result<Vec<CachedChannel>> {
+pub fn load_channels(db: &impl DbHandle) -> Result<Vec<CachedChannel>> {
+    let conn = &*db.conn()?;
     let mut stmt = conn
-        .prepare("SELECT ch_id, ch_gid, ch_name FROM channels ORDER BY ch_id")
+        .prepare(
+            "SELECT ch_id, ch_gid, ch_name, ch_url, ch_iepg_name, ch_comment, logo_url
+             FROM channels ORDER BY ch_id",
+        )
         .context("failed to prepare channels query")?;
 
     let rows = stmt
@@ -153,6 +185,10 @@ pub fn load_channels(conn: &Connection) -> Result<Vec<CachedChannel>> {
                 ch_id: row.get(0)?,
                 ch_gid: row.get(1)?,
                 ch_name: row.get(2)?,
+                ch_url: row.get(3)?,
+                ch_iepg_name: row.get(4)?,
+                ch_comment: row.get(5)?,
+                logo_url: row.get(6)?,
             })
         })
         .context("failed to query channels")?;
@@ -161,11 +197,118 @@ pub fn load_channels(conn: &Connection) -> Result<Vec<CachedChannel>> {
         .context("failed to read channels rows")
 }
 
+/// Cached program count and freshness of one channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ChannelProgramStats {
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Number of cached programs on the channel.
+    pub program_count: u32,
+    /// Newest Syoboi `LastUpdate` among those programs, i.e. the most recent
+    /// change a sync has pulled for the channel.
+    pub last_update: Option<String>,
+}
+
+/// Loads program counts and the newest `LastUpdate` per channel, ordered by
+/// channel ID. Channels without cached programs are omitted.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_channel_program_stats(db: &impl DbHandle) -> Result<Vec<ChannelProgramStats>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT ch_id, COUNT(*), MAX(last_update)
+             FROM programs GROUP BY ch_id ORDER BY ch_id",
+        )
+        .context("failed to prepare channel program stats query")?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ChannelProgramStats {
+                ch_id: row.get(0)?,
+                program_count: row.get(1)?,
+                last_update: row.get(2)?,
+            })
+        })
+        .context("failed to query channel program stats")?;
+
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read channel program stats rows")
+}
+
+/// Sets (or clears) the logo URL of a channel. Returns `false` if the
+/// channel does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, fields(ch_id), err(level = "error"))]
+pub fn update_channel_logo(db: &impl DbHandle, ch_id: u32, logo_url: Option<&str>) -> Result<bool> {
+    let conn = &*db.conn()?;
+    let rows = conn
+        .execute(
+            "UPDATE channels SET logo_url = ?2 WHERE ch_id = ?1",
+            rusqlite::params![ch_id, logo_url],
+        )
+        .with_context(|| format!("failed to update logo of channel {ch_id}"))?;
+    Ok(rows > 0)
+}
+
+/// `sync_state` scope recording when the channel cache was last refreshed.
+const CHANNELS_REFRESH_SCOPE: &str = "channels:refreshed";
+
+/// Records `refreshed_at` (`YYYY-MM-DDTHH:MM:SSZ`) as the time channels and
+/// channel groups were last fetched from the API.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn mark_channels_refreshed(db: &impl DbHandle, refreshed_at: &str) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO sync_state (scope, last_update, synced_at) VALUES (?1, ?2, ?2)
+        ON CONFLICT(scope) DO UPDATE SET
+            last_update = excluded.last_update,
+            synced_at   = excluded.synced_at",
+        [CHANNELS_REFRESH_SCOPE, refreshed_at],
+    )
+    .context("failed to record channel refresh time")?;
+    Ok(())
+}
+
+/// Loads the time the channel cache was last refreshed.
+///
+/// Returns `None` when channels were never fetched (or were cached before
+/// refresh times were recorded).
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_channels_refreshed_at(db: &impl DbHandle) -> Result<Option<String>> {
+    let conn = &*db.conn()?;
+    conn.query_row(
+        "SELECT last_update FROM sync_state WHERE scope = ?1",
+        [CHANNELS_REFRESH_SCOPE],
+        |row| row.get(0),
+    )
+    .optional()
+    .context("failed to load channel refresh time")
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
     #![allow(clippy::indexing_slicing)]
 
+    use rusqlite::Connection;
+
     use super::*;
     use crate::connection::open_db;
     use crate::programs::{CachedProgram, upsert_programs};
@@ -223,11 +366,13 @@ mod tests {
                 ch_id: 3,
                 ch_gid: Some(1),
                 ch_name: String::from("フジテレビ"),
+                ..CachedChannel::default()
             },
             CachedChannel {
                 ch_id: 1,
                 ch_gid: Some(1),
                 ch_name: String::from("NHK総合"),
+                ..CachedChannel::default()
             },
         ];
 
@@ -315,6 +460,7 @@ mod tests {
             ch_id: 10,
             ch_gid: Some(1),
             ch_name: String::from("Old Name"),
+            ..CachedChannel::default()
         }];
         upsert_channels(&conn, &channels).unwrap();
 
@@ -323,6 +469,7 @@ mod tests {
             ch_id: 10,
             ch_gid: Some(1),
             ch_name: String::from("New Name"),
+            ..CachedChannel::default()
         }];
         let changed = upsert_channels(&conn, &updated).unwrap();
         let loaded = load_channels(&conn).unwrap();
@@ -349,6 +496,7 @@ mod tests {
             ch_id: 10,
             ch_gid: Some(1),
             ch_name: String::from("TestCh"),
+            ..CachedChannel::default()
         }];
         upsert_channels(&conn, &channels).unwrap();
 
@@ -358,6 +506,8 @@ mod tests {
             tmdb_series_id: None,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: String::from("Test Title"),
             short_title: None,
             title_yomi: None,
@@ -367,12 +517,14 @@ mod tests {
             first_year: None,
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::from("2025-01-01 00:00:00"),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         }];
         crate::titles::upsert_titles(&conn, &titles).unwrap();
 
@@ -401,6 +553,7 @@ mod tests {
             ch_id: 10,
             ch_gid: Some(1),
             ch_name: String::from("Updated Name"),
+            ..CachedChannel::default()
         }];
         let result = upsert_channels(&conn, &updated);
 
@@ -426,6 +579,7 @@ mod tests {
             ch_id: 10,
             ch_gid: Some(1),
             ch_name: String::from("TestCh"),
+            ..CachedChannel::default()
         }];
         upsert_channels(&conn, &channels).unwrap();
 
@@ -434,6 +588,7 @@ mod tests {
             ch_id: 10,
             ch_gid: None,
             ch_name: String::from("TestCh"),
+            ..CachedChannel::default()
         }];
         upsert_channels(&conn, &updated).unwrap();
         let loaded = load_channels(&conn).unwrap();
@@ -443,6 +598,158 @@ mod tests {
         assert_eq!(loaded[0].ch_gid, Some(1));
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_channels_metadata_and_logo() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let channel = CachedChannel {
+            ch_id: 7,
+            ch_name: String::from("テレビ東京"),
+            ch_url: Some(String::from("https://www.tv-tokyo.co.jp/")),
+            ch_iepg_name: Some(String::from("テレビ東京")),
+            ch_comment: Some(String::from("地上波")),
+            ..CachedChannel::default()
+        };
+        upsert_channels(&conn, std::slice::from_ref(&channel)).unwrap();
+        let logo = "http://mirakurun:40772/api/services/3273601024/logo";
+
+        // Act: set the logo, then re-upsert without metadata
+        let updated = update_channel_logo(&conn, 7, Some(logo)).unwrap();
+        let missing = update_channel_logo(&conn, 99, Some(logo)).unwrap();
+        let changed = upsert_channels(
+            &conn,
+            &[CachedChannel {
+                ch_id: 7,
+                ch_name: String::from("テレビ東京"),
+                ..CachedChannel::default()
+            }],
+        )
+        .unwrap();
+        let loaded = load_channels(&conn).unwrap();
+
+        // Assert: metadata and logo survive an upsert without them
+        assert!(updated);
+        assert!(!missing);
+        assert_eq!(changed, 0);
+        assert_eq!(
+            loaded[0],
+            CachedChannel {
+                logo_url: Some(logo.to_owned()),
+                ..channel
+            }
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_load_channel_program_stats() {
+        // Arrange: two programs on channel 1, one on channel 3, none on 7
+        let (conn, _dir) = setup_db();
+        let channels: Vec<CachedChannel> = [1, 3, 7]
+            .into_iter()
+            .map(|ch_id| CachedChannel {
+                ch_id,
+                ch_name: format!("ch{ch_id}"),
+                ..CachedChannel::default()
+            })
+            .collect();
+        upsert_channels(&conn, &channels).unwrap();
+        crate::titles::upsert_titles(
+            &conn,
+            &[crate::titles::CachedTitle {
+                tid: 100,
+                tmdb_series_id: None,
+                tmdb_season_number: None,
+                tmdb_season_id: None,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
+                title: String::from("Test Title"),
+                short_title: None,
+                title_yomi: None,
+                title_en: None,
+                cat: None,
+                title_flag: None,
+                first_year: None,
+                first_month: None,
+                keywords: Vec::new(),
+                last_update: String::from("2025-01-01 00:00:00"),
+                tmdb_original_name: None,
+                tmdb_name: None,
+                tmdb_alt_titles: None,
+                tmdb_last_updated: None,
+                comment: None,
+                user_point: None,
+                user_point_rank: None,
+            }],
+        )
+        .unwrap();
+        let program = |pid: u32, ch_id: u32, last_update: Option<&str>| CachedProgram {
+            pid,
+            tid: 100,
+            ch_id,
+            tmdb_episode_id: None,
+            st_time: String::from("2025-01-01 00:00:00"),
+            st_offset: None,
+            ed_time: String::from("2025-01-01 00:30:00"),
+            count: None,
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: None,
+            last_update: last_update.map(str::to_owned),
+            st_sub_title: None,
+            duration_min: None,
+        };
+        upsert_programs(
+            &conn,
+            &[
+                program(1, 1, Some("2025-01-02 00:00:00")),
+                program(2, 1, Some("2025-01-05 00:00:00")),
+                program(3, 3, None),
+            ],
+        )
+        .unwrap();
+
+        // Act
+        let stats = load_channel_program_stats(&conn).unwrap();
+
+        // Assert
+        assert_eq!(
+            stats,
+            vec![
+                ChannelProgramStats {
+                    ch_id: 1,
+                    program_count: 2,
+                    last_update: Some(String::from("2025-01-05 00:00:00")),
+                },
+                ChannelProgramStats {
+                    ch_id: 3,
+                    program_count: 1,
+                    last_update: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_mark_channels_refreshed_overwrites_time() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+
+        // Act
+        let missing = load_channels_refreshed_at(&conn).unwrap();
+        mark_channels_refreshed(&conn, "2024-04-02T10:00:00Z").unwrap();
+        mark_channels_refreshed(&conn, "2024-04-01T10:00:00Z").unwrap();
+        let loaded = load_channels_refreshed_at(&conn).unwrap();
+
+        // Assert
+        assert!(missing.is_none());
+        assert_eq!(loaded.as_deref(), Some("2024-04-01T10:00:00Z"));
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_load_empty_tables() {
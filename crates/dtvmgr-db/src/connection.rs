@@ -1,32 +1,146 @@
 //! Database connection management.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags, Transaction};
 use tracing::instrument;
 
 use super::migrations::run_migrations;
+use super::platform::PlatformDirs;
 
-/// Opens (or creates) the database and runs migrations.
+/// Database file name under the data directory.
+pub const DB_FILE_NAME: &str = "dtvmgr.db";
+
+/// Default time a connection waits for a lock held by another process.
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default number of rows written per transaction by the batched upserts.
+pub const DEFAULT_UPSERT_BATCH_SIZE: usize = 1000;
+
+/// `PRAGMA journal_mode` of the database file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JournalMode {
+    /// Rollback journal (`DELETE`); writers block readers.
+    Delete,
+    /// Write-ahead log; readers and one writer can run concurrently.
+    #[default]
+    Wal,
+}
+
+impl JournalMode {
+    /// Returns the pragma value.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "DELETE",
+            Self::Wal => "WAL",
+        }
+    }
+}
+
+/// `PRAGMA synchronous` level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Synchronous {
+    /// No syncs; fastest, may corrupt on power loss.
+    Off,
+    /// Sync at checkpoints; safe with WAL.
+    #[default]
+    Normal,
+    /// Sync on every commit.
+    Full,
+}
+
+impl Synchronous {
+    /// Returns the pragma value.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Normal => "NORMAL",
+            Self::Full => "FULL",
+        }
+    }
+}
+
+/// Connection settings for [`open_db_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbOptions {
+    /// Journal mode set on read-write connections.
+    pub journal_mode: JournalMode,
+    /// How long to wait for locks before failing with `database is locked`.
+    pub busy_timeout: Duration,
+    /// Synchronous level.
+    pub synchronous: Synchronous,
+    /// Open the database read-only. The file must exist and be migrated.
+    pub read_only: bool,
+}
+
+impl Default for DbOptions {
+    fn default() -> Self {
+        Self {
+            journal_mode: JournalMode::default(),
+            busy_timeout: DEFAULT_BUSY_TIMEOUT,
+            synchronous: Synchronous::default(),
+            read_only: false,
+        }
+    }
+}
+
+/// Opens (or creates) the database with the default [`DbOptions`] and runs
+/// migrations.
 ///
 /// - If `dir` is `Some`, uses `{dir}/dtvmgr.db`.
-/// - Otherwise uses `~/.local/share/dtvmgr/dtvmgr.db`.
+/// - Otherwise uses `dtvmgr.db` in the platform data directory
+///   ([`PlatformDirs`]).
 ///
 /// # Errors
 ///
 /// Returns an error if the database cannot be opened or migrations fail.
-#[instrument(skip_all, err(level = "error"))]
 pub fn open_db(dir: Option<&PathBuf>) -> Result<Connection> {
+    open_db_with_options(dir, &DbOptions::default())
+}
+
+/// Opens the database with `options` and runs migrations.
+///
+/// Read-only connections neither create the file nor change its journal
+/// mode; migrations only succeed if the schema is already current.
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened, a pragma fails, or
+/// migrations fail.
+#[instrument(skip_all, err(level = "error"))]
+pub fn open_db_with_options(dir: Option<&PathBuf>, options: &DbOptions) -> Result<Connection> {
     let db_path = resolve_db_path(dir)?;
 
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)
-            .with_context(|| format!("failed to create directory {}", parent.display()))?;
+    let conn = if options.read_only {
+        Connection::open_with_flags(
+            &db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+    } else {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory {}", parent.display()))?;
+        }
+        Connection::open(&db_path)
     }
+    .with_context(|| format!("failed to open database {}", db_path.display()))?;
 
-    let conn = Connection::open(&db_path)
-        .with_context(|| format!("failed to open database {}", db_path.display()))?;
+    conn.busy_timeout(options.busy_timeout)
+        .context("failed to set busy timeout")?;
+    if !options.read_only {
+        let mode: String = conn
+            .pragma_update_and_check(None, "journal_mode", options.journal_mode.as_str(), |row| {
+                row.get(0)
+            })
+            .context("failed to set journal mode")?;
+        tracing::debug!(journal_mode = mode, "Database journal mode");
+    }
+    conn.pragma_update(None, "synchronous", options.synchronous.as_str())
+        .context("failed to set synchronous level")?;
 
     run_migrations(&conn).context("database migration failed")?;
 
@@ -36,18 +150,104 @@ pub fn open_db(dir: Option<&PathBuf>) -> Result<Connection> {
     Ok(conn)
 }
 
-/// Resolves the database file path.
-fn resolve_db_path(dir: Option<&PathBuf>) -> Result<PathBuf> {
+/// Resolves the database file path: `{dir}/dtvmgr.db`, or `dtvmgr.db` in
+/// the platform data directory ([`PlatformDirs`]) when `dir` is `None`.
+///
+/// # Errors
+///
+/// Returns an error if `dir` is `None` and the platform data directory
+/// cannot be determined (no `HOME`, or `APPDATA` / `USERPROFILE` on
+/// Windows).
+pub fn resolve_db_path(dir: Option<&PathBuf>) -> Result<PathBuf> {
     if let Some(d) = dir {
-        return Ok(d.join("dtvmgr.db"));
+        return Ok(d.join(DB_FILE_NAME));
     }
+    let data = PlatformDirs::detect()
+        .data
+        .context("failed to determine the platform data directory")?;
+    Ok(data.join(DB_FILE_NAME))
+}
 
-    let home = std::env::var("HOME").context("HOME environment variable is not set")?;
-    Ok(PathBuf::from(home)
-        .join(".local")
-        .join("share")
-        .join("dtvmgr")
-        .join("dtvmgr.db"))
+/// Prepares the database file at `db_path` to be moved on its own.
+///
+/// Checkpoints the write-ahead log into the main file and switches to the
+/// rollback journal, so no `-wal` / `-shm` side files are left once the
+/// connection closes. The next [`open_db_with_options`] restores WAL mode.
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened, another connection
+/// has it open (e.g. a running `daemon` or `serve`), or the checkpoint
+/// leaves a non-empty `-wal` file behind.
+#[instrument(skip_all, err(level = "error"))]
+pub fn detach_wal(db_path: &Path) -> Result<()> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_WRITE)
+        .with_context(|| format!("failed to open database {}", db_path.display()))?;
+    let in_use = || {
+        format!(
+            "database {} is in use by another process",
+            db_path.display()
+        )
+    };
+
+    let busy: i64 = conn
+        .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))
+        .with_context(in_use)?;
+    if busy != 0 {
+        anyhow::bail!(in_use());
+    }
+    // Leaving WAL mode needs exclusive access, so this also fails while
+    // another connection has the database open.
+    let mode: String = conn
+        .pragma_update_and_check(None, "journal_mode", "DELETE", |row| row.get(0))
+        .with_context(in_use)?;
+    if !mode.eq_ignore_ascii_case("delete") {
+        anyhow::bail!(in_use());
+    }
+    conn.close()
+        .map_err(|(_, e)| e)
+        .with_context(|| format!("failed to close database {}", db_path.display()))?;
+
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    if std::fs::metadata(&wal).is_ok_and(|m| m.len() > 0) {
+        anyhow::bail!(
+            "write-ahead log of {} was not checkpointed",
+            db_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Calls `f` on `rows` in chunks of `batch_size` (`0` means one chunk),
+/// each in its own transaction. Returns the sum of the returned counts.
+///
+/// A failing chunk is rolled back; earlier chunks stay committed.
+///
+/// # Errors
+///
+/// Returns an error if a transaction cannot be started or committed, or
+/// `f` fails.
+pub fn in_batches<T>(
+    conn: &Connection,
+    rows: &[T],
+    batch_size: usize,
+    mut f: impl FnMut(&Transaction<'_>, &[T]) -> Result<usize>,
+) -> Result<usize> {
+    let size = if batch_size == 0 {
+        rows.len().max(1)
+    } else {
+        batch_size
+    };
+    let mut changed: usize = 0;
+    for chunk in rows.chunks(size) {
+        let tx = conn
+            .unchecked_transaction()
+            .context("failed to begin transaction")?;
+        changed = changed.saturating_add(f(&tx, chunk)?);
+        tx.commit().context("failed to commit batch")?;
+    }
+    Ok(changed)
 }
 
 #[cfg(test)]
@@ -90,6 +290,55 @@ mod tests {
         assert_eq!(fk, 1);
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_open_db_uses_wal_by_default() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().to_path_buf();
+
+        // Act
+        let conn = open_db(Some(&dir_path)).unwrap();
+        let mode: String = conn
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .unwrap();
+        let timeout: i64 = conn
+            .pragma_query_value(None, "busy_timeout", |row| row.get(0))
+            .unwrap();
+
+        // Assert
+        assert_eq!(mode, "wal");
+        assert_eq!(timeout, 5000);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_open_db_read_only_rejects_writes_and_missing_file() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().to_path_buf();
+        let options = DbOptions {
+            read_only: true,
+            ..DbOptions::default()
+        };
+        assert!(open_db_with_options(Some(&dir_path), &options).is_err());
+        drop(open_db(Some(&dir_path)).unwrap());
+
+        // Act
+        let conn = open_db_with_options(Some(&dir_path), &options).unwrap();
+        let result = conn.execute(
+            "INSERT INTO titles (tid, title, last_update) VALUES (1, 'T', '2024-01-01 00:00:00')",
+            [],
+        );
+
+        // Assert
+        assert!(result.is_err());
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM titles", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_resolve_db_path_with_dir() {
@@ -106,10 +355,41 @@ mod tests {
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_resolve_db_path_default() {
-        // Arrange & Act
+        // Arrange
+        let data = PlatformDirs::detect().data.unwrap();
+
+        // Act
         let path = resolve_db_path(None).unwrap();
 
-        // Assert
-        assert!(path.ends_with(".local/share/dtvmgr/dtvmgr.db"));
+        // Assert: the same directory the CLI resolves
+        assert_eq!(path, data.join(DB_FILE_NAME));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_detach_wal_checkpoints_into_main_file() {
+        // Arrange: a committed row left in the WAL of an open connection
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().to_path_buf();
+        let conn = open_db(Some(&dir_path)).unwrap();
+        conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (1, 'A');")
+            .unwrap();
+        let db_path = resolve_db_path(Some(&dir_path)).unwrap();
+
+        // Act & Assert: refused while the other connection is open
+        assert!(detach_wal(&db_path).is_err());
+        drop(conn);
+        detach_wal(&db_path).unwrap();
+
+        // Assert: the main file alone holds the data
+        assert!(!dir_path.join("dtvmgr.db-wal").exists());
+        let moved = dir_path.join("moved");
+        std::fs::create_dir_all(&moved).unwrap();
+        std::fs::rename(&db_path, moved.join(DB_FILE_NAME)).unwrap();
+        let conn = open_db(Some(&moved)).unwrap();
+        let count: u32 = conn
+            .query_row("SELECT COUNT(*) FROM channels", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
     }
 }
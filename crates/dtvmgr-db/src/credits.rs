@@ -0,0 +1,280 @@
+//! Staff, cast, and links parsed from Syoboi title comments.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Section of the title comment a credit comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CreditKind {
+    /// Staff (`*スタッフ`): role is the position.
+    Staff,
+    /// Cast (`*キャスト`): role is the character.
+    Cast,
+}
+
+impl CreditKind {
+    /// Value stored in `title_staff.kind`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Staff => "staff",
+            Self::Cast => "cast",
+        }
+    }
+
+    /// Parses a stored `title_staff.kind` value.
+    fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "staff" => Some(Self::Staff),
+            "cast" => Some(Self::Cast),
+            _ => None,
+        }
+    }
+}
+
+/// One staff or cast entry of a title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TitleCredit {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Staff or cast.
+    pub kind: CreditKind,
+    /// Position or character name (may be empty).
+    pub role: String,
+    /// Person or company name.
+    pub name: String,
+}
+
+/// One link of a title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TitleLink {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Link label (may be empty).
+    pub label: String,
+    /// Link URL.
+    pub url: String,
+}
+
+/// Replaces all credits and links of `tid` in one transaction, keeping the
+/// given order. Empty slices clear the title's entries.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn replace_title_credits(
+    db: &impl DbHandle,
+    tid: u32,
+    credits: &[TitleCredit],
+    links: &[TitleLink],
+) -> Result<()> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    tx.execute("DELETE FROM title_staff WHERE tid = ?1", [tid])
+        .with_context(|| format!("failed to clear staff of title {tid}"))?;
+    tx.execute("DELETE FROM title_links WHERE tid = ?1", [tid])
+        .with_context(|| format!("failed to clear links of title {tid}"))?;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO title_staff (tid, kind, position, role, name)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .context("failed to prepare staff insert")?;
+        for kind in [CreditKind::Staff, CreditKind::Cast] {
+            for (position, credit) in credits.iter().filter(|c| c.kind == kind).enumerate() {
+                stmt.execute(rusqlite::params![
+                    tid,
+                    kind.as_str(),
+                    position,
+                    credit.role,
+                    credit.name,
+                ])
+                .with_context(|| format!("failed to insert staff of title {tid}"))?;
+            }
+        }
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO title_links (tid, position, label, url)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .context("failed to prepare link insert")?;
+        for (position, link) in links.iter().enumerate() {
+            stmt.execute(rusqlite::params![tid, position, link.label, link.url])
+                .with_context(|| format!("failed to insert link of title {tid}"))?;
+        }
+    }
+    tx.commit().context("failed to commit title credits")?;
+    Ok(())
+}
+
+/// Loads the credits of `tid`, staff before cast, each in comment order.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_title_credits(db: &impl DbHandle, tid: u32) -> Result<Vec<TitleCredit>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT kind, role, name
+             FROM title_staff
+             WHERE tid = ?1
+             ORDER BY CASE kind WHEN 'staff' THEN 0 ELSE 1 END, position",
+        )
+        .context("failed to prepare title staff query")?;
+    let rows = stmt
+        .query_map([tid], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .context("failed to query title staff")?;
+    let mut credits = Vec::new();
+    for row in rows {
+        let (kind, role, name) = row.context("failed to read title staff row")?;
+        let Some(kind) = CreditKind::from_db(&kind) else {
+            tracing::warn!(tid, kind = %kind, "Skipping staff row of unknown kind");
+            continue;
+        };
+        credits.push(TitleCredit {
+            tid,
+            kind,
+            role,
+            name,
+        });
+    }
+    Ok(credits)
+}
+
+/// Loads the links of `tid` in comment order.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_title_links(db: &impl DbHandle, tid: u32) -> Result<Vec<TitleLink>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT label, url
+             FROM title_links
+             WHERE tid = ?1
+             ORDER BY position",
+        )
+        .context("failed to prepare title links query")?;
+    let rows = stmt
+        .query_map([tid], |row| {
+            Ok(TitleLink {
+                tid,
+                label: row.get(0)?,
+                url: row.get(1)?,
+            })
+        })
+        .context("failed to query title links")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read title links rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn credit(kind: CreditKind, role: &str, name: &str) -> TitleCredit {
+        TitleCredit {
+            tid: 6309,
+            kind,
+            role: role.to_owned(),
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_replace_title_credits_overwrites_and_keeps_order() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update) VALUES (6309, 'Test', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+        replace_title_credits(
+            &conn,
+            6309,
+            &[credit(CreditKind::Staff, "監督", "旧監督")],
+            &[],
+        )
+        .unwrap();
+        let link = TitleLink {
+            tid: 6309,
+            label: String::from("公式"),
+            url: String::from("https://example.com/"),
+        };
+
+        // Act
+        replace_title_credits(
+            &conn,
+            6309,
+            &[
+                credit(CreditKind::Cast, "ロイド", "江口拓也"),
+                credit(CreditKind::Staff, "原作", "遠藤達哉"),
+                credit(CreditKind::Staff, "監督", "古橋一浩"),
+            ],
+            std::slice::from_ref(&link),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(
+            load_title_credits(&conn, 6309).unwrap(),
+            vec![
+                credit(CreditKind::Staff, "原作", "遠藤達哉"),
+                credit(CreditKind::Staff, "監督", "古橋一浩"),
+                credit(CreditKind::Cast, "ロイド", "江口拓也"),
+            ]
+        );
+        assert_eq!(load_title_links(&conn, 6309).unwrap(), vec![link]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_title_credits_deleted_with_title() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update) VALUES (6309, 'Test', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+        replace_title_credits(
+            &conn,
+            6309,
+            &[credit(CreditKind::Staff, "監督", "古橋一浩")],
+            &[],
+        )
+        .unwrap();
+
+        // Act
+        conn.execute("DELETE FROM titles WHERE tid = 6309", [])
+            .unwrap();
+
+        // Assert
+        assert!(load_title_credits(&conn, 6309).unwrap().is_empty());
+    }
+}
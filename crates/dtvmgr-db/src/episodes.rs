@@ -0,0 +1,172 @@
+//! Episode subtitles of titles, parsed from Syoboi `SubTitles`.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Subtitle of one episode of a title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Episode {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Episode number.
+    pub count: u32,
+    /// Episode subtitle.
+    pub sub_title: String,
+}
+
+/// Stores the episode subtitles of title `tid`. Returns the number of rows
+/// changed.
+///
+/// `episodes` is the complete list for the title: new counts are inserted,
+/// changed subtitles updated, and counts no longer listed deleted.
+/// Unchanged rows are not rewritten. Episodes of other titles in
+/// `episodes` are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn upsert_episodes(db: &impl DbHandle, tid: u32, episodes: &[Episode]) -> Result<usize> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+
+    let mut stmt = tx
+        .prepare(
+            "INSERT INTO episodes (tid, count, sub_title) VALUES (?1, ?2, ?3)
+             ON CONFLICT(tid, count) DO UPDATE SET sub_title = excluded.sub_title
+             WHERE episodes.sub_title != excluded.sub_title",
+        )
+        .context("failed to prepare episode upsert")?;
+    let mut changed: usize = 0;
+    let mut counts: Vec<u32> = Vec::with_capacity(episodes.len());
+    for e in episodes.iter().filter(|e| e.tid == tid) {
+        let rows = stmt
+            .execute(rusqlite::params![tid, e.count, e.sub_title])
+            .with_context(|| format!("failed to upsert episode {tid}#{}", e.count))?;
+        changed = changed.saturating_add(rows);
+        counts.push(e.count);
+    }
+    drop(stmt);
+
+    let placeholders: Vec<String> = (0..counts.len())
+        .map(|i| format!("?{}", i.saturating_add(2)))
+        .collect();
+    let sql = if counts.is_empty() {
+        String::from("DELETE FROM episodes WHERE tid = ?1")
+    } else {
+        format!(
+            "DELETE FROM episodes WHERE tid = ?1 AND count NOT IN ({})",
+            placeholders.join(", ")
+        )
+    };
+    let params = core::iter::once(tid).chain(counts.iter().copied());
+    let deleted = tx
+        .execute(&sql, rusqlite::params_from_iter(params))
+        .with_context(|| format!("failed to delete stale episodes of title {tid}"))?;
+    changed = changed.saturating_add(deleted);
+
+    tx.commit().context("failed to commit episodes")?;
+    Ok(changed)
+}
+
+/// Loads the episode subtitles of title `tid` ordered by count.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_episodes(db: &impl DbHandle, tid: u32) -> Result<Vec<Episode>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare("SELECT tid, count, sub_title FROM episodes WHERE tid = ?1 ORDER BY count")
+        .context("failed to prepare episodes query")?;
+    let rows = stmt
+        .query_map([tid], |row| {
+            Ok(Episode {
+                tid: row.get(0)?,
+                count: row.get(1)?,
+                sub_title: row.get(2)?,
+            })
+        })
+        .with_context(|| format!("failed to query episodes of title {tid}"))?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to read episode rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::indexing_slicing)]
+
+    use rusqlite::Connection;
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch("INSERT INTO titles (tid, title, last_update) VALUES (100, 'A', '');")
+            .unwrap();
+        (conn, dir)
+    }
+
+    fn episode(count: u32, sub_title: &str) -> Episode {
+        Episode {
+            tid: 100,
+            count,
+            sub_title: sub_title.to_owned(),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_episodes_inserts_updates_and_deletes() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_episodes(
+            &conn,
+            100,
+            &[episode(1, "一"), episode(2, "二"), episode(3, "三")],
+        )
+        .unwrap();
+
+        // Act: 1 unchanged, 2 renamed, 3 dropped, 4 added
+        let changed = upsert_episodes(
+            &conn,
+            100,
+            &[episode(1, "一"), episode(2, "二話"), episode(4, "四")],
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(changed, 3);
+        let loaded = load_episodes(&conn, 100).unwrap();
+        assert_eq!(
+            loaded,
+            vec![episode(1, "一"), episode(2, "二話"), episode(4, "四")]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_episodes_empty_list_clears_title() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_episodes(&conn, 100, &[episode(1, "一")]).unwrap();
+
+        // Act
+        let changed = upsert_episodes(&conn, 100, &[]).unwrap();
+
+        // Assert
+        assert_eq!(changed, 1);
+        assert!(load_episodes(&conn, 100).unwrap().is_empty());
+    }
+}
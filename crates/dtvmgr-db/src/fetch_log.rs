@@ -0,0 +1,95 @@
+//! Fetch times of API requests answered by the read-through cache.
+//!
+//! Keys are chosen by the caller (e.g. `title:6309`); a request is fresh
+//! when its key was fetched at or after a cutoff.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Records `fetched_at` (`YYYY-MM-DDTHH:MM:SSZ`) as the fetch time of
+/// `keys`.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn mark_fetched(db: &impl DbHandle, keys: &[String], fetched_at: &str) -> Result<()> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    let mut stmt = tx
+        .prepare(
+            "INSERT INTO fetch_log (key, fetched_at) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET fetched_at = excluded.fetched_at",
+        )
+        .context("failed to prepare fetch log upsert")?;
+    for key in keys {
+        stmt.execute([key.as_str(), fetched_at])
+            .with_context(|| format!("failed to record fetch of {key}"))?;
+    }
+    drop(stmt);
+    tx.commit().context("failed to commit fetch log")?;
+    Ok(())
+}
+
+/// Returns the subset of `keys` fetched at or after `since`
+/// (`YYYY-MM-DDTHH:MM:SSZ`).
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_fresh_keys(
+    db: &impl DbHandle,
+    keys: &[String],
+    since: &str,
+) -> Result<HashSet<String>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare_cached("SELECT 1 FROM fetch_log WHERE key = ?1 AND fetched_at >= ?2")
+        .context("failed to prepare fetch log query")?;
+    let mut fresh = HashSet::new();
+    for key in keys {
+        if stmt
+            .exists([key.as_str(), since])
+            .with_context(|| format!("failed to look up fetch of {key}"))?
+        {
+            fresh.insert(key.clone());
+        }
+    }
+    Ok(fresh)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_load_fresh_keys_compares_fetch_times() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let keys = vec![
+            String::from("title:1"),
+            String::from("title:2"),
+            String::from("title:3"),
+        ];
+        mark_fetched(&conn, &keys[..1], "2024-01-01T00:00:00Z").unwrap();
+        mark_fetched(&conn, &keys[1..2], "2024-01-02T00:00:00Z").unwrap();
+
+        // Act
+        let fresh = load_fresh_keys(&conn, &keys, "2024-01-01T12:00:00Z").unwrap();
+
+        // Assert
+        assert_eq!(fresh, HashSet::from([String::from("title:2")]));
+    }
+}
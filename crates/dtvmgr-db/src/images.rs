@@ -0,0 +1,125 @@
+//! Downloaded TMDB artwork per title.
+
+use anyhow::{Context, Result};
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// A downloaded title image (poster, backdrop, ...).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleImage {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Image kind (`poster` or `backdrop`).
+    pub kind: String,
+    /// TMDB image path (e.g. "/abc.jpg").
+    pub file_path: String,
+    /// Local file path the image was saved to.
+    pub local_path: String,
+    /// SHA-256 of the saved file (lowercase hex).
+    pub sha256: String,
+    /// Download time (`%Y-%m-%dT%H:%M:%SZ`).
+    pub downloaded_at: String,
+}
+
+/// Inserts or replaces the image of a title for its kind.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn upsert_title_image(db: &impl DbHandle, image: &TitleImage) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO title_images (tid, kind, file_path, local_path, sha256, downloaded_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(tid, kind) DO UPDATE SET
+             file_path = excluded.file_path,
+             local_path = excluded.local_path,
+             sha256 = excluded.sha256,
+             downloaded_at = excluded.downloaded_at",
+        rusqlite::params![
+            image.tid,
+            image.kind,
+            image.file_path,
+            image.local_path,
+            image.sha256,
+            image.downloaded_at,
+        ],
+    )
+    .context("failed to upsert title image")?;
+    Ok(())
+}
+
+/// Loads all downloaded title images ordered by TID and kind.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_title_images(db: &impl DbHandle) -> Result<Vec<TitleImage>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT tid, kind, file_path, local_path, sha256, downloaded_at
+             FROM title_images
+             ORDER BY tid, kind",
+        )
+        .context("failed to prepare title images query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TitleImage {
+                tid: row.get(0)?,
+                kind: row.get(1)?,
+                file_path: row.get(2)?,
+                local_path: row.get(3)?,
+                sha256: row.get(4)?,
+                downloaded_at: row.get(5)?,
+            })
+        })
+        .context("failed to query title images")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read title image rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn image(kind: &str, file_path: &str) -> TitleImage {
+        TitleImage {
+            tid: 6309,
+            kind: kind.to_owned(),
+            file_path: file_path.to_owned(),
+            local_path: format!("/data/assets/6309/{kind}.jpg"),
+            sha256: String::from("00ff"),
+            downloaded_at: String::from("2024-04-01T00:00:00Z"),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_title_image_replaces_same_kind() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch("INSERT INTO titles (tid, title, last_update) VALUES (6309, 'Test', '2024-01-01 00:00:00');")
+            .unwrap();
+
+        // Act
+        upsert_title_image(&conn, &image("poster", "/old.jpg")).unwrap();
+        upsert_title_image(&conn, &image("backdrop", "/b.jpg")).unwrap();
+        upsert_title_image(&conn, &image("poster", "/new.jpg")).unwrap();
+        let loaded = load_title_images(&conn).unwrap();
+
+        // Assert
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].kind, "backdrop");
+        assert_eq!(loaded[1].kind, "poster");
+        assert_eq!(loaded[1].file_path, "/new.jpg");
+    }
+}
@@ -0,0 +1,226 @@
+//! Background job persistence.
+
+use anyhow::{Context, Result};
+use rusqlite::OptionalExtension;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// A persisted job row. `kind` and `state` are stored as plain strings;
+/// typed handling lives in `dtvmgr-core`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobRecord {
+    /// Job ID.
+    pub id: i64,
+    /// Job kind (e.g. "sync").
+    pub kind: String,
+    /// Job state (e.g. "queued").
+    pub state: String,
+    /// Optional kind-specific payload.
+    pub payload: Option<String>,
+    /// Number of attempts started so far.
+    pub attempts: u32,
+    /// Maximum number of attempts before the job is marked failed.
+    pub max_attempts: u32,
+    /// Error message of the last failed attempt.
+    pub last_error: Option<String>,
+    /// Earliest time the job may run (`%Y-%m-%dT%H:%M:%SZ`).
+    pub scheduled_at: String,
+    /// Creation timestamp.
+    pub created_at: String,
+    /// Last update timestamp.
+    pub updated_at: String,
+}
+
+/// Inserts a new queued job and returns its ID.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn insert_job(
+    db: &impl DbHandle,
+    kind: &str,
+    payload: Option<&str>,
+    max_attempts: u32,
+    scheduled_at: &str,
+) -> Result<i64> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO jobs (kind, state, payload, max_attempts, scheduled_at, created_at, updated_at)
+         VALUES (?1, 'queued', ?2, ?3, ?4, ?4, ?4)",
+        rusqlite::params![kind, payload, max_attempts, scheduled_at],
+    )
+    .with_context(|| format!("failed to insert {kind} job"))?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Loads jobs (newest first), optionally filtered by state.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_jobs(db: &impl DbHandle, state: Option<&str>) -> Result<Vec<JobRecord>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, kind, state, payload, attempts, max_attempts, last_error,
+                    scheduled_at, created_at, updated_at
+             FROM jobs
+             WHERE ?1 IS NULL OR state = ?1
+             ORDER BY id DESC",
+        )
+        .context("failed to prepare jobs query")?;
+    let rows = stmt
+        .query_map([state], map_job_row)
+        .context("failed to query jobs")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read job rows")
+}
+
+/// Loads a single job by ID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_job(db: &impl DbHandle, id: i64) -> Result<Option<JobRecord>> {
+    let conn = &*db.conn()?;
+    conn.query_row(
+        "SELECT id, kind, state, payload, attempts, max_attempts, last_error,
+                scheduled_at, created_at, updated_at
+         FROM jobs WHERE id = ?1",
+        [id],
+        map_job_row,
+    )
+    .optional()
+    .with_context(|| format!("failed to load job {id}"))
+}
+
+/// Loads the oldest queued job whose `scheduled_at` is not after `now`.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_next_due_job(db: &impl DbHandle, now: &str) -> Result<Option<JobRecord>> {
+    let conn = &*db.conn()?;
+    conn.query_row(
+        "SELECT id, kind, state, payload, attempts, max_attempts, last_error,
+                scheduled_at, created_at, updated_at
+         FROM jobs
+         WHERE state = 'queued' AND scheduled_at <= ?1
+         ORDER BY scheduled_at, id
+         LIMIT 1",
+        [now],
+        map_job_row,
+    )
+    .optional()
+    .context("failed to load next due job")
+}
+
+/// Writes the mutable fields (state, attempts, error, schedule, `updated_at`) of a job.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn update_job(db: &impl DbHandle, job: &JobRecord) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "UPDATE jobs
+         SET state = ?1, attempts = ?2, last_error = ?3, scheduled_at = ?4, updated_at = ?5
+         WHERE id = ?6",
+        rusqlite::params![
+            job.state,
+            job.attempts,
+            job.last_error,
+            job.scheduled_at,
+            job.updated_at,
+            job.id
+        ],
+    )
+    .with_context(|| format!("failed to update job {}", job.id))?;
+    Ok(())
+}
+
+/// Maps a database row to a `JobRecord`.
+fn map_job_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<JobRecord> {
+    Ok(JobRecord {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        state: row.get(2)?,
+        payload: row.get(3)?,
+        attempts: row.get(4)?,
+        max_attempts: row.get(5)?,
+        last_error: row.get(6)?,
+        scheduled_at: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use rusqlite::Connection;
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        (conn, dir)
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_insert_and_load_jobs() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+
+        // Act
+        let id = insert_job(&conn, "sync", None, 3, "2024-01-01T00:00:00Z").unwrap();
+        insert_job(&conn, "prune", Some("30"), 1, "2024-01-01T00:00:00Z").unwrap();
+        let all = load_jobs(&conn, None).unwrap();
+        let queued = load_jobs(&conn, Some("queued")).unwrap();
+        let running = load_jobs(&conn, Some("running")).unwrap();
+        let one = load_job(&conn, id).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].kind, "prune");
+        assert_eq!(queued.len(), 2);
+        assert!(running.is_empty());
+        assert_eq!(one.state, "queued");
+        assert_eq!(one.attempts, 0);
+        assert_eq!(one.max_attempts, 3);
+        assert!(load_job(&conn, 999).unwrap().is_none());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_next_due_job_respects_schedule_and_state() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let later = insert_job(&conn, "sync", None, 3, "2024-01-02T00:00:00Z").unwrap();
+        let due = insert_job(&conn, "notify", None, 3, "2024-01-01T00:00:00Z").unwrap();
+
+        // Act
+        let first = load_next_due_job(&conn, "2024-01-01T12:00:00Z").unwrap();
+        let mut job = first.clone().unwrap();
+        job.state = String::from("running");
+        update_job(&conn, &job).unwrap();
+        let second = load_next_due_job(&conn, "2024-01-01T12:00:00Z").unwrap();
+        let third = load_next_due_job(&conn, "2024-01-02T00:00:00Z").unwrap();
+
+        // Assert
+        assert_eq!(first.unwrap().id, due);
+        assert!(second.is_none());
+        assert_eq!(third.unwrap().id, later);
+    }
+}
@@ -3,30 +3,133 @@
 //! Uses `rusqlite` (bundled `SQLite`) to cache channel, title,
 //! and program data from the Syoboi Calendar API.
 
+/// Program tags and notes CRUD operations.
+pub mod annotations;
+/// Upsert throughput benchmark on a scratch database.
+pub mod bench;
+/// Channel alias (Mirakurun / `EPGStation`) CRUD operations.
+pub mod channel_aliases;
 /// Channel cache CRUD operations.
 pub mod channels;
 mod connection;
+/// Staff, cast, and links parsed from title comments.
+pub mod credits;
+/// Episode subtitles of titles.
+pub mod episodes;
+/// Fetch times of the read-through API cache.
+pub mod fetch_log;
+/// Downloaded TMDB artwork tracking.
+pub mod images;
+/// Background job persistence.
+pub mod jobs;
+/// Localized TMDB names and overviews of titles.
+pub mod localizations;
+/// Retention (prune) and compaction (vacuum).
+pub mod maintenance;
+/// Export and import of TMDB mappings.
+pub mod mappings;
 mod migrations;
+/// Platform-native config, data, and cache directories.
+pub mod platform;
+/// Bounded pool of shared connections.
+pub mod pool;
+/// Previous broadcast times of programs.
+pub mod program_history;
 /// Program cache CRUD operations.
 pub mod programs;
 /// EPGStation recorded items cache CRUD operations.
 pub mod recorded;
+/// TMDB season mapping of episode count ranges.
+pub mod season_ranges;
+/// Seed database import.
+pub mod seed;
+/// Named schedule snapshots and diffs between them.
+pub mod snapshots;
+/// Ad-hoc SQL against the cache.
+pub mod sql;
+/// Aggregate statistics over the cache.
+pub mod stats;
+/// Sync run summaries and checkpoints.
+pub mod sync_runs;
+/// Incremental sync cursors.
+pub mod sync_state;
+/// Merged (aliased) Syoboi titles.
+pub mod title_aliases;
 /// Title cache CRUD operations.
 pub mod titles;
+/// Watchlist CRUD operations.
+pub mod watchlist;
 
+pub use annotations::{
+    ProgramAnnotations, add_program_tag, delete_program_note, load_program_annotations,
+    remove_program_tag, set_program_note,
+};
+#[allow(clippy::module_name_repetitions)]
+pub use channel_aliases::{
+    ChannelAlias, delete_channel_aliases, load_channel_aliases, upsert_channel_aliases,
+};
 #[allow(clippy::module_name_repetitions)]
-pub use channels::{load_channel_groups, load_channels, upsert_channel_groups, upsert_channels};
+pub use channels::{
+    ChannelProgramStats, load_channel_groups, load_channel_program_stats, load_channels,
+    load_channels_refreshed_at, mark_channels_refreshed, update_channel_logo,
+    upsert_channel_groups, upsert_channels,
+};
 #[allow(clippy::module_name_repetitions)]
-pub use connection::open_db;
+pub use connection::{
+    DB_FILE_NAME, DEFAULT_UPSERT_BATCH_SIZE, DbOptions, JournalMode, Synchronous, detach_wal,
+    open_db, open_db_with_options, resolve_db_path,
+};
+pub use credits::{
+    CreditKind, TitleCredit, TitleLink, load_title_credits, load_title_links, replace_title_credits,
+};
+pub use episodes::{Episode, load_episodes, upsert_episodes};
+pub use fetch_log::{load_fresh_keys, mark_fetched};
+pub use images::{TitleImage, load_title_images, upsert_title_image};
+pub use jobs::{JobRecord, insert_job, load_job, load_jobs, load_next_due_job, update_job};
+pub use localizations::{TitleLocalization, load_title_localizations, upsert_title_localization};
+pub use maintenance::{PruneReport, VacuumReport, prune_programs, vacuum};
+pub use mappings::{
+    MAPPINGS_FORMAT_VERSION, MappingImport, MappingsDocument, TitleMapping, export_mappings,
+    import_mappings,
+};
+#[allow(clippy::module_name_repetitions)]
+pub use pool::{DEFAULT_POOL_SIZE, DbConn, DbHandle, DbPool, PooledConnection};
+pub use program_history::{ProgramHistoryEntry, load_program_history};
 pub use programs::{
-    delete_programs_by_tids_not_in, load_programs, load_programs_by_tids, upsert_programs,
+    ProgramFilter, RecomputeProgress, SqlCondition, delete_programs_by_tids_not_in, iter_programs,
+    load_program, load_programs, load_programs_by_tids, load_programs_filtered,
+    recompute_program_columns, update_tmdb_episode_mapping, upsert_programs,
+    upsert_programs_batched,
 };
 pub use recorded::{
     delete_recorded_items_not_in, invalidate_file_exists, load_recorded_items,
-    load_recorded_items_page, newest_start_at, update_file_exists, upsert_recorded_items,
+    load_recorded_items_page, load_video_file_hashes, newest_start_at, update_file_exists,
+    upsert_recorded_items, upsert_video_file_hash,
 };
 pub use rusqlite::Connection;
+pub use season_ranges::{SeasonRange, load_season_ranges, replace_season_ranges};
+pub use seed::{SeedImport, import_seed};
+#[allow(clippy::module_name_repetitions)]
+pub use snapshots::{
+    ProgramChange, ProgramChangeKind, SnapshotInfo, create_snapshot, delete_snapshot,
+    diff_snapshots, load_snapshots,
+};
+#[allow(clippy::module_name_repetitions)]
+pub use sql::{SqlOutput, SqlValue, run_sql};
+pub use stats::{
+    ChannelUsage, DbSummary, load_category_counts, load_channel_usage, load_db_summary,
+};
+pub use sync_runs::{
+    SyncRunRecord, finish_sync_run, insert_sync_run, load_sync_run, load_sync_runs,
+    save_programs_fetched, save_sync_params, save_title_chunk, start_sync_run,
+};
+pub use sync_state::{load_sync_cursor, save_sync_cursor};
+pub use title_aliases::{TitleAlias, TitleMerge, load_title_aliases, merge_titles};
 pub use titles::{
-    delete_titles_by_cat_not_in, filter_keywords, load_titles, load_titles_by_tids, parse_keywords,
-    update_tmdb_last_updated, update_tmdb_mapping, update_tmdb_search_result, upsert_titles,
+    ExternalIds, delete_titles_by_cat_not_in, filter_keywords, load_external_ids,
+    load_followed_tids, load_titles, load_titles_by_tids, parse_keywords, search_titles,
+    set_titles_followed, update_external_ids, update_tmdb_episode_group, update_tmdb_last_updated,
+    update_tmdb_mapping, update_tmdb_movie_mapping, update_tmdb_movie_search_result,
+    update_tmdb_search_result, upsert_titles, upsert_titles_batched,
 };
+pub use watchlist::{delete_watchlist_entries, load_watchlist, upsert_watchlist_entries};
@@ -0,0 +1,121 @@
+//! Localized TMDB names and overviews of titles.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// TMDB name and overview of a title in one language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TitleLocalization {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// TMDB language tag (e.g. "ja-JP").
+    pub language: String,
+    /// Localized series or movie name.
+    pub name: String,
+    /// Localized overview (`None` when TMDB has none).
+    pub overview: Option<String>,
+}
+
+/// Inserts or replaces the localization of `loc.tid` in `loc.language`.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn upsert_title_localization(
+    db: &impl DbHandle,
+    loc: &TitleLocalization,
+    updated_at: &str,
+) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO title_localizations (tid, language, name, overview, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(tid, language) DO UPDATE SET
+             name = excluded.name,
+             overview = excluded.overview,
+             updated_at = excluded.updated_at",
+        rusqlite::params![loc.tid, loc.language, loc.name, loc.overview, updated_at],
+    )
+    .with_context(|| {
+        format!(
+            "failed to store {} localization of title {}",
+            loc.language, loc.tid
+        )
+    })?;
+    Ok(())
+}
+
+/// Loads the localizations of all titles in `language`, ordered by TID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_title_localizations(
+    db: &impl DbHandle,
+    language: &str,
+) -> Result<Vec<TitleLocalization>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT tid, language, name, overview
+             FROM title_localizations
+             WHERE language = ?1
+             ORDER BY tid",
+        )
+        .context("failed to prepare title localizations query")?;
+    let rows = stmt
+        .query_map([language], |row| {
+            Ok(TitleLocalization {
+                tid: row.get(0)?,
+                language: row.get(1)?,
+                name: row.get(2)?,
+                overview: row.get(3)?,
+            })
+        })
+        .context("failed to query title localizations")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read title localizations rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_and_load_title_localizations() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update)
+             VALUES (1, 'A', '2024-01-01 00:00:00'), (2, 'B', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+        let loc = |tid: u32, language: &str, name: &str| TitleLocalization {
+            tid,
+            language: language.to_owned(),
+            name: name.to_owned(),
+            overview: None,
+        };
+        upsert_title_localization(&conn, &loc(2, "en-US", "Old"), "t0").unwrap();
+
+        // Act
+        upsert_title_localization(&conn, &loc(2, "en-US", "Frieren"), "t1").unwrap();
+        upsert_title_localization(&conn, &loc(1, "en-US", "Spy"), "t1").unwrap();
+        upsert_title_localization(&conn, &loc(1, "ja-JP", "スパイ"), "t1").unwrap();
+        let en = load_title_localizations(&conn, "en-US").unwrap();
+
+        // Assert
+        assert_eq!(en, vec![loc(1, "en-US", "Spy"), loc(2, "en-US", "Frieren")]);
+    }
+}
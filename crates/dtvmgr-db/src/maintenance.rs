@@ -0,0 +1,207 @@
+//! Retention and compaction of the local cache.
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Rows removed (or that would be removed) by [`prune_programs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PruneReport {
+    /// Programs that ended before the cutoff.
+    pub programs: usize,
+    /// Titles left without programs (only with `orphan_titles`).
+    pub titles: usize,
+}
+
+/// Database size before and after [`vacuum`], in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VacuumReport {
+    /// Size before compaction.
+    pub before_bytes: u64,
+    /// Size after compaction.
+    pub after_bytes: u64,
+}
+
+/// Deletes programs that ended before `cutoff` (`YYYY-MM-DD HH:MM:SS`).
+///
+/// With `orphan_titles`, titles that are left without programs are deleted
+/// too, unless they are followed, on the watchlist, or mapped to TMDB.
+/// Programs are deleted before titles so the `programs.tid` foreign key
+/// holds throughout. With `dry_run`, the deletions run in a transaction
+/// that is rolled back, so the report shows exact counts without changes.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn prune_programs(
+    db: &impl DbHandle,
+    cutoff: &str,
+    orphan_titles: bool,
+    dry_run: bool,
+) -> Result<PruneReport> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+
+    let programs = tx
+        .execute("DELETE FROM programs WHERE ed_time < ?1", [cutoff])
+        .with_context(|| format!("failed to delete programs ended before {cutoff}"))?;
+
+    let titles = if orphan_titles {
+        tx.execute(
+            "DELETE FROM titles
+             WHERE NOT EXISTS (SELECT 1 FROM programs p WHERE p.tid = titles.tid)
+               AND followed = 0
+               AND tmdb_series_id IS NULL
+               AND tmdb_movie_id IS NULL
+               AND tid NOT IN (SELECT tid FROM watchlist)",
+            [],
+        )
+        .context("failed to delete orphan titles")?
+    } else {
+        0
+    };
+
+    if dry_run {
+        tx.rollback().context("failed to roll back dry run")?;
+    } else {
+        tx.commit().context("failed to commit transaction")?;
+    }
+    Ok(PruneReport { programs, titles })
+}
+
+/// Rebuilds the database file to reclaim space freed by deletions.
+///
+/// # Errors
+///
+/// Returns an error if `VACUUM` fails (e.g. inside a transaction).
+#[instrument(skip_all, err(level = "error"))]
+pub fn vacuum(db: &impl DbHandle) -> Result<VacuumReport> {
+    let conn = &*db.conn()?;
+    let before_bytes = database_size(conn)?;
+    conn.execute_batch("VACUUM")
+        .context("failed to vacuum database")?;
+    let after_bytes = database_size(conn)?;
+    Ok(VacuumReport {
+        before_bytes,
+        after_bytes,
+    })
+}
+
+/// Returns the size of the main database (`page_count * page_size`).
+fn database_size(conn: &Connection) -> Result<u64> {
+    let page_count: u64 = conn
+        .pragma_query_value(None, "page_count", |row| row.get(0))
+        .context("failed to read page_count")?;
+    let page_size: u64 = conn
+        .pragma_query_value(None, "page_size", |row| row.get(0))
+        .context("failed to read page_size")?;
+    Ok(page_count.saturating_mul(page_size))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channel_groups (ch_gid, ch_group_name, ch_group_order) VALUES (1, 'Test', 0);
+             INSERT INTO channels (ch_id, ch_gid, ch_name) VALUES (1, 1, 'ChA');
+             INSERT INTO titles (tid, title, last_update) VALUES
+                 (100, 'Old', '2024-01-01 00:00:00'),
+                 (101, 'Followed', '2024-01-01 00:00:00'),
+                 (102, 'Current', '2024-01-01 00:00:00');
+             UPDATE titles SET followed = 1 WHERE tid = 101;
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time) VALUES
+                 (1, 100, 1, '2023-01-01 00:00:00', '2023-01-01 00:30:00'),
+                 (2, 101, 1, '2023-01-02 00:00:00', '2023-01-02 00:30:00'),
+                 (3, 102, 1, '2023-01-03 00:00:00', '2023-01-03 00:30:00'),
+                 (4, 102, 1, '2024-06-01 00:00:00', '2024-06-01 00:30:00');",
+        )
+        .unwrap();
+        (conn, dir)
+    }
+
+    fn count(conn: &Connection, table: &str) -> u32 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_prune_programs_dry_run_reports_without_deleting() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+
+        // Act
+        let report = prune_programs(&conn, "2024-01-01 00:00:00", true, true).unwrap();
+
+        // Assert
+        assert_eq!(
+            report,
+            PruneReport {
+                programs: 3,
+                titles: 1
+            }
+        );
+        assert_eq!(count(&conn, "programs"), 4);
+        assert_eq!(count(&conn, "titles"), 3);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_prune_programs_keeps_followed_and_current_titles() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+
+        // Act
+        let report = prune_programs(&conn, "2024-01-01 00:00:00", true, false).unwrap();
+        let remaining: Vec<u32> = conn
+            .prepare("SELECT tid FROM titles ORDER BY tid")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+
+        // Assert: only the unfollowed title without programs is gone
+        assert_eq!(report.programs, 3);
+        assert_eq!(remaining, vec![101, 102]);
+        assert_eq!(count(&conn, "programs"), 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_vacuum_does_not_grow_database() {
+        // Arrange: enough old programs that pruning frees whole pages
+        let (conn, _dir) = setup_db();
+        conn.execute_batch(
+            "WITH RECURSIVE n(i) AS (SELECT 1000 UNION ALL SELECT i + 1 FROM n WHERE i < 3000)
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, sub_title)
+             SELECT i, 100, 1, '2023-02-01 00:00:00', '2023-02-01 00:30:00',
+                    printf('%.64c', 'x')
+             FROM n;",
+        )
+        .unwrap();
+        prune_programs(&conn, "2025-01-01 00:00:00", true, false).unwrap();
+
+        // Act
+        let report = vacuum(&conn).unwrap();
+
+        // Assert
+        assert!(report.before_bytes > 0);
+        assert!(report.after_bytes < report.before_bytes, "{report:?}");
+    }
+}
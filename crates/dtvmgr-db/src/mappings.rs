@@ -0,0 +1,355 @@
+//! Export and import of per-title TMDB mappings.
+//!
+//! The exported document is a stable, versioned format so that manual
+//! matching survives database recreation and can be shared between machines.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+use crate::season_ranges::{SeasonRange, load_season_ranges};
+use crate::titles::load_titles;
+
+/// Current version of the mappings document.
+pub const MAPPINGS_FORMAT_VERSION: u32 = 1;
+
+/// Exported TMDB mappings of all mapped titles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MappingsDocument {
+    /// Format version ([`MAPPINGS_FORMAT_VERSION`]).
+    pub version: u32,
+    /// Mappings ordered by TID.
+    pub mappings: Vec<TitleMapping>,
+}
+
+/// TMDB mapping of one Syoboi title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TitleMapping {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name, for readability only (ignored on import).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    /// TMDB series ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb_series_id: Option<u64>,
+    /// TMDB season number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb_season_number: Option<u32>,
+    /// TMDB season ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb_season_id: Option<u64>,
+    /// TMDB movie ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb_movie_id: Option<u64>,
+    /// TMDB episode group ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmdb_episode_group_id: Option<String>,
+    /// Episode count ranges mapped to TMDB seasons.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub season_ranges: Vec<MappedRange>,
+}
+
+/// Episode count range of a [`TitleMapping`] (see [`SeasonRange`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappedRange {
+    /// First episode count of the range.
+    pub first_count: u32,
+    /// Last episode count of the range (`None` = open-ended).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_count: Option<u32>,
+    /// TMDB season number.
+    pub season_number: u32,
+}
+
+impl TitleMapping {
+    /// Returns whether the mapping carries no TMDB data at all.
+    const fn is_empty(&self) -> bool {
+        self.tmdb_series_id.is_none()
+            && self.tmdb_movie_id.is_none()
+            && self.tmdb_episode_group_id.is_none()
+            && self.season_ranges.is_empty()
+    }
+
+    /// Returns whether the TMDB data (not the title name) equals `other`.
+    fn same_mapping(&self, other: &Self) -> bool {
+        self.tmdb_series_id == other.tmdb_series_id
+            && self.tmdb_season_number == other.tmdb_season_number
+            && self.tmdb_season_id == other.tmdb_season_id
+            && self.tmdb_movie_id == other.tmdb_movie_id
+            && self.tmdb_episode_group_id == other.tmdb_episode_group_id
+            && self.season_ranges == other.season_ranges
+    }
+}
+
+/// Outcome of [`import_mappings`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MappingImport {
+    /// Titles whose mapping was written.
+    pub imported: usize,
+    /// Titles that already had the same mapping.
+    pub unchanged: usize,
+    /// Titles kept because they have a different mapping (without overwrite).
+    pub conflicts: Vec<u32>,
+    /// TIDs not present in the titles cache.
+    pub unknown_tids: Vec<u32>,
+}
+
+/// Collects the current mappings of every title that has any TMDB data.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn export_mappings(db: &impl DbHandle) -> Result<MappingsDocument> {
+    let conn = &*db.conn()?;
+    let mut mappings: Vec<TitleMapping> = current_mappings(conn)?
+        .into_values()
+        .filter(|m| !m.is_empty())
+        .collect();
+    mappings.sort_by_key(|m| m.tid);
+    Ok(MappingsDocument {
+        version: MAPPINGS_FORMAT_VERSION,
+        mappings,
+    })
+}
+
+/// Applies `doc` to the titles cache in one transaction.
+///
+/// Titles without a mapping are filled in and titles with the same mapping
+/// are left as is; titles with a different mapping are only replaced when
+/// `overwrite` is set. TIDs missing from the cache are reported and skipped, so run a sync
+/// before importing into a fresh database.
+///
+/// # Errors
+///
+/// Returns an error for an unsupported format version or if a database
+/// operation fails (nothing is written in that case).
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn import_mappings(
+    db: &impl DbHandle,
+    doc: &MappingsDocument,
+    overwrite: bool,
+) -> Result<MappingImport> {
+    let conn = &*db.conn()?;
+    if doc.version != MAPPINGS_FORMAT_VERSION {
+        bail!(
+            "unsupported mappings format version {} (expected {MAPPINGS_FORMAT_VERSION})",
+            doc.version
+        );
+    }
+    let current = current_mappings(conn)?;
+    let mut report = MappingImport::default();
+
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    for mapping in &doc.mappings {
+        let Some(existing) = current.get(&mapping.tid) else {
+            report.unknown_tids.push(mapping.tid);
+            continue;
+        };
+        if existing.same_mapping(mapping) {
+            report.unchanged = report.unchanged.saturating_add(1);
+            continue;
+        }
+        if !existing.is_empty() && !overwrite {
+            report.conflicts.push(mapping.tid);
+            continue;
+        }
+        write_mapping(&tx, mapping)?;
+        report.imported = report.imported.saturating_add(1);
+    }
+    tx.commit().context("failed to commit mappings")?;
+    Ok(report)
+}
+
+/// Loads the mapping of every cached title, keyed by TID.
+fn current_mappings(conn: &Connection) -> Result<HashMap<u32, TitleMapping>> {
+    let mut ranges: HashMap<u32, Vec<MappedRange>> = HashMap::new();
+    for SeasonRange {
+        tid,
+        first_count,
+        last_count,
+        season_number,
+    } in load_season_ranges(conn)?
+    {
+        ranges.entry(tid).or_default().push(MappedRange {
+            first_count,
+            last_count,
+            season_number,
+        });
+    }
+
+    Ok(load_titles(conn)?
+        .into_iter()
+        .map(|t| {
+            let mapping = TitleMapping {
+                tid: t.tid,
+                title: t.title,
+                tmdb_series_id: t.tmdb_series_id,
+                tmdb_season_number: t.tmdb_season_number,
+                tmdb_season_id: t.tmdb_season_id,
+                tmdb_movie_id: t.tmdb_movie_id,
+                tmdb_episode_group_id: t.tmdb_episode_group_id,
+                season_ranges: ranges.remove(&t.tid).unwrap_or_default(),
+            };
+            (t.tid, mapping)
+        })
+        .collect())
+}
+
+/// Writes all TMDB columns and season ranges of one title.
+fn write_mapping(conn: &Connection, m: &TitleMapping) -> Result<()> {
+    conn.execute(
+        "UPDATE titles
+         SET tmdb_series_id = ?1, tmdb_season_number = ?2, tmdb_season_id = ?3,
+             tmdb_movie_id = ?4, tmdb_episode_group_id = ?5
+         WHERE tid = ?6",
+        rusqlite::params![
+            m.tmdb_series_id,
+            m.tmdb_season_number,
+            m.tmdb_season_id,
+            m.tmdb_movie_id,
+            m.tmdb_episode_group_id,
+            m.tid,
+        ],
+    )
+    .with_context(|| format!("failed to update TMDB mapping for title {}", m.tid))?;
+    conn.execute("DELETE FROM title_season_ranges WHERE tid = ?1", [m.tid])
+        .with_context(|| format!("failed to clear season ranges of title {}", m.tid))?;
+    for range in &m.season_ranges {
+        conn.execute(
+            "INSERT INTO title_season_ranges (tid, first_count, last_count, season_number)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                m.tid,
+                range.first_count,
+                range.last_count,
+                range.season_number
+            ],
+        )
+        .with_context(|| format!("failed to insert season range of title {}", m.tid))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+    use crate::season_ranges::replace_season_ranges;
+    use crate::titles::update_tmdb_mapping;
+
+    fn setup() -> (tempfile::TempDir, Connection) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update) VALUES
+                 (1, 'A', '2024-01-01 00:00:00'),
+                 (2, 'B', '2024-01-01 00:00:00'),
+                 (3, 'C', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+        (dir, conn)
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        // Arrange
+        let (_dir, conn) = setup();
+        update_tmdb_mapping(&conn, 1, Some(100), Some(2), Some(1002)).unwrap();
+        replace_season_ranges(
+            &conn,
+            1,
+            &[SeasonRange {
+                tid: 1,
+                first_count: 13,
+                last_count: None,
+                season_number: 2,
+            }],
+        )
+        .unwrap();
+        let doc = export_mappings(&conn).unwrap();
+        let (_dir2, fresh) = setup();
+
+        // Act
+        let report = import_mappings(&fresh, &doc, false).unwrap();
+
+        // Assert
+        assert_eq!(doc.mappings.len(), 1);
+        assert_eq!(doc.mappings[0].title, "A");
+        assert_eq!(report.imported, 1);
+        assert_eq!(export_mappings(&fresh).unwrap(), doc);
+    }
+
+    #[test]
+    fn test_import_keeps_conflicts_unless_overwrite() {
+        // Arrange
+        let (_dir, conn) = setup();
+        update_tmdb_mapping(&conn, 2, Some(200), Some(1), None).unwrap();
+        let doc: MappingsDocument = MappingsDocument {
+            version: MAPPINGS_FORMAT_VERSION,
+            mappings: vec![
+                TitleMapping {
+                    tid: 2,
+                    title: String::new(),
+                    tmdb_series_id: Some(999),
+                    tmdb_season_number: Some(1),
+                    tmdb_season_id: None,
+                    tmdb_movie_id: None,
+                    tmdb_episode_group_id: None,
+                    season_ranges: Vec::new(),
+                },
+                TitleMapping {
+                    tid: 42,
+                    title: String::new(),
+                    tmdb_series_id: None,
+                    tmdb_season_number: None,
+                    tmdb_season_id: None,
+                    tmdb_movie_id: Some(7),
+                    tmdb_episode_group_id: None,
+                    season_ranges: Vec::new(),
+                },
+            ],
+        };
+
+        // Act
+        let kept = import_mappings(&conn, &doc, false).unwrap();
+        let replaced = import_mappings(&conn, &doc, true).unwrap();
+
+        // Assert
+        assert_eq!(kept.conflicts, vec![2]);
+        assert_eq!(kept.unknown_tids, vec![42]);
+        assert_eq!(kept.imported, 0);
+        assert_eq!(replaced.imported, 1);
+        let titles = load_titles(&conn).unwrap();
+        let t2 = titles.iter().find(|t| t.tid == 2).unwrap();
+        assert_eq!(t2.tmdb_series_id, Some(999));
+    }
+
+    #[test]
+    fn test_import_rejects_unknown_version() {
+        // Arrange
+        let (_dir, conn) = setup();
+        let doc = MappingsDocument {
+            version: 99,
+            mappings: Vec::new(),
+        };
+
+        // Act
+        let result = import_mappings(&conn, &doc, false);
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -4,7 +4,43 @@ use anyhow::{Context, Result};
 use rusqlite::Connection;
 
 /// Current schema version.
-const CURRENT_VERSION: u32 = 7;
+pub const CURRENT_VERSION: u32 = 32;
+
+/// Migration steps in order; entry `n` migrates from version `n` to `n + 1`.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    migrate_v1,
+    migrate_v2,
+    migrate_v3,
+    migrate_v4,
+    migrate_v5,
+    migrate_v6,
+    migrate_v7,
+    migrate_v8,
+    migrate_v9,
+    migrate_v10,
+    migrate_v11,
+    migrate_v12,
+    migrate_v13,
+    migrate_v14,
+    migrate_v15,
+    migrate_v16,
+    migrate_v17,
+    migrate_v18,
+    migrate_v19,
+    migrate_v20,
+    migrate_v21,
+    migrate_v22,
+    migrate_v23,
+    migrate_v24,
+    migrate_v25,
+    migrate_v26,
+    migrate_v27,
+    migrate_v28,
+    migrate_v29,
+    migrate_v30,
+    migrate_v31,
+    migrate_v32,
+];
 
 /// Runs database migrations up to `CURRENT_VERSION`.
 ///
@@ -22,26 +58,11 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
         return Ok(());
     }
 
-    if version < 1 {
-        migrate_v1(conn).context("migration to v1 failed")?;
-    }
-    if version < 2 {
-        migrate_v2(conn).context("migration to v2 failed")?;
-    }
-    if version < 3 {
-        migrate_v3(conn).context("migration to v3 failed")?;
-    }
-    if version < 4 {
-        migrate_v4(conn).context("migration to v4 failed")?;
-    }
-    if version < 5 {
-        migrate_v5(conn).context("migration to v5 failed")?;
-    }
-    if version < 6 {
-        migrate_v6(conn).context("migration to v6 failed")?;
-    }
-    if version < 7 {
-        migrate_v7(conn).context("migration to v7 failed")?;
+    for (from, migrate) in (0..).zip(MIGRATIONS) {
+        if version <= from {
+            let to = from.saturating_add(1);
+            migrate(conn).with_context(|| format!("migration to v{to} failed"))?;
+        }
     }
 
     conn.pragma_update(None, "user_version", CURRENT_VERSION)
@@ -198,6 +219,564 @@ fn migrate_v7(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Migration to v8: create `titles_fts` full-text index over title names.
+///
+/// Uses an external-content FTS5 table with the `trigram` tokenizer so
+/// Japanese titles (no word separators) match on substrings. Triggers keep
+/// the index in sync with `titles`.
+fn migrate_v8(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS titles_fts USING fts5(
+            title, short_title, title_yomi, title_en, keywords,
+            tmdb_name, tmdb_original_name,
+            content='titles', content_rowid='tid', tokenize='trigram'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS titles_fts_ai AFTER INSERT ON titles BEGIN
+            INSERT INTO titles_fts(rowid, title, short_title, title_yomi, title_en, keywords,
+                                   tmdb_name, tmdb_original_name)
+            VALUES (new.tid, new.title, new.short_title, new.title_yomi, new.title_en,
+                    new.keywords, new.tmdb_name, new.tmdb_original_name);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS titles_fts_ad AFTER DELETE ON titles BEGIN
+            INSERT INTO titles_fts(titles_fts, rowid, title, short_title, title_yomi, title_en,
+                                   keywords, tmdb_name, tmdb_original_name)
+            VALUES ('delete', old.tid, old.title, old.short_title, old.title_yomi, old.title_en,
+                    old.keywords, old.tmdb_name, old.tmdb_original_name);
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS titles_fts_au AFTER UPDATE ON titles BEGIN
+            INSERT INTO titles_fts(titles_fts, rowid, title, short_title, title_yomi, title_en,
+                                   keywords, tmdb_name, tmdb_original_name)
+            VALUES ('delete', old.tid, old.title, old.short_title, old.title_yomi, old.title_en,
+                    old.keywords, old.tmdb_name, old.tmdb_original_name);
+            INSERT INTO titles_fts(rowid, title, short_title, title_yomi, title_en, keywords,
+                                   tmdb_name, tmdb_original_name)
+            VALUES (new.tid, new.title, new.short_title, new.title_yomi, new.title_en,
+                    new.keywords, new.tmdb_name, new.tmdb_original_name);
+        END;
+
+        INSERT INTO titles_fts(titles_fts) VALUES ('rebuild');",
+    )
+    .context("failed to create titles_fts index")?;
+
+    Ok(())
+}
+
+/// Migration to v9: create `watchlist` table with per-title notification preferences.
+///
+/// No FK to `titles`: entries survive category cleanup and re-sync.
+fn migrate_v9(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS watchlist (
+            tid                 INTEGER PRIMARY KEY,
+            notify_new_program  INTEGER NOT NULL DEFAULT 1,
+            notify_time_change  INTEGER NOT NULL DEFAULT 0,
+            notify_finale       INTEGER NOT NULL DEFAULT 0,
+            added_at            TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+        );",
+    )
+    .context("failed to create watchlist table")?;
+
+    Ok(())
+}
+
+/// Migration to v10: create `video_file_hashes` table for recording checksums.
+///
+/// Kept separate from `epg_video_files` because `EPGStation` sync rewrites
+/// those rows; hashes are recorded by `library verify --hash`.
+fn migrate_v10(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS video_file_hashes (
+            video_file_id  INTEGER PRIMARY KEY REFERENCES epg_video_files(id) ON DELETE CASCADE,
+            sha256         TEXT NOT NULL,
+            size           INTEGER NOT NULL,
+            hashed_at      TEXT NOT NULL
+        );",
+    )
+    .context("failed to create video_file_hashes table")?;
+
+    Ok(())
+}
+
+/// Migration to v11: create `jobs` table for the background job queue.
+fn migrate_v11(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS jobs (
+            id            INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind          TEXT NOT NULL,
+            state         TEXT NOT NULL DEFAULT 'queued',
+            payload       TEXT,
+            attempts      INTEGER NOT NULL DEFAULT 0,
+            max_attempts  INTEGER NOT NULL DEFAULT 3,
+            last_error    TEXT,
+            scheduled_at  TEXT NOT NULL,
+            created_at    TEXT NOT NULL,
+            updated_at    TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_jobs_state_scheduled_at ON jobs(state, scheduled_at);",
+    )
+    .context("failed to create jobs table")?;
+
+    Ok(())
+}
+
+/// Migration to v12: create `sync_state` table holding incremental sync cursors.
+fn migrate_v12(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sync_state (
+            scope        TEXT PRIMARY KEY,
+            last_update  TEXT NOT NULL,
+            synced_at    TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+        );",
+    )
+    .context("failed to create sync_state table")?;
+
+    Ok(())
+}
+
+/// Migration to v13: create `sync_runs` table recording each daemon sync run.
+fn migrate_v13(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sync_runs (
+            id                INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at        TEXT NOT NULL,
+            finished_at       TEXT NOT NULL,
+            status            TEXT NOT NULL,
+            titles            INTEGER NOT NULL DEFAULT 0,
+            titles_changed    INTEGER NOT NULL DEFAULT 0,
+            programs          INTEGER NOT NULL DEFAULT 0,
+            programs_changed  INTEGER NOT NULL DEFAULT 0,
+            error             TEXT
+        );",
+    )
+    .context("failed to create sync_runs table")?;
+
+    Ok(())
+}
+
+/// Migration to v14: create `channel_aliases` table mapping Syoboi channels to
+/// Mirakurun / `EPGStation` identifiers.
+fn migrate_v14(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS channel_aliases (
+            ch_id                  INTEGER PRIMARY KEY,
+            mirakurun_service_id   INTEGER,
+            epgstation_channel_id  INTEGER,
+            channel_type           TEXT,
+            name                   TEXT,
+            updated_at             TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+        );",
+    )
+    .context("failed to create channel_aliases table")?;
+
+    Ok(())
+}
+
+/// Migration to v15: add `followed` flag to `titles`.
+fn migrate_v15(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE titles ADD COLUMN followed INTEGER NOT NULL DEFAULT 0;")
+        .context("failed to add followed column to titles")?;
+
+    Ok(())
+}
+
+/// Migration to v16: add external IDs (`imdb_id`, `tvdb_id`) to `titles`.
+fn migrate_v16(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE titles ADD COLUMN imdb_id TEXT;
+         ALTER TABLE titles ADD COLUMN tvdb_id INTEGER;",
+    )
+    .context("failed to add external ID columns to titles")?;
+
+    Ok(())
+}
+
+/// Migration to v17: add channel metadata (`ch_url`, `ch_iepg_name`,
+/// `ch_comment`) and `logo_url` to `channels`.
+fn migrate_v17(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE channels ADD COLUMN ch_url TEXT;
+         ALTER TABLE channels ADD COLUMN ch_iepg_name TEXT;
+         ALTER TABLE channels ADD COLUMN ch_comment TEXT;
+         ALTER TABLE channels ADD COLUMN logo_url TEXT;",
+    )
+    .context("failed to add metadata columns to channels")?;
+
+    Ok(())
+}
+
+/// Migration to v18: create `title_images` table tracking downloaded
+/// TMDB artwork (one row per title and kind).
+fn migrate_v18(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS title_images (
+            tid            INTEGER NOT NULL REFERENCES titles(tid) ON DELETE CASCADE,
+            kind           TEXT NOT NULL,
+            file_path      TEXT NOT NULL,
+            local_path     TEXT NOT NULL,
+            sha256         TEXT NOT NULL,
+            downloaded_at  TEXT NOT NULL,
+            PRIMARY KEY (tid, kind)
+        );",
+    )
+    .context("failed to create title_images table")?;
+
+    Ok(())
+}
+
+/// Migration to v19: rebuild `sync_runs` with a nullable `finished_at` and
+/// checkpoint columns so interrupted `db sync` runs can be resumed.
+fn migrate_v19(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE sync_runs_new (
+            id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at          TEXT NOT NULL,
+            finished_at         TEXT,
+            status              TEXT NOT NULL,
+            titles              INTEGER NOT NULL DEFAULT 0,
+            titles_changed      INTEGER NOT NULL DEFAULT 0,
+            programs            INTEGER NOT NULL DEFAULT 0,
+            programs_changed    INTEGER NOT NULL DEFAULT 0,
+            error               TEXT,
+            params              TEXT,
+            programs_fetched    INTEGER NOT NULL DEFAULT 0,
+            title_chunks_done   INTEGER NOT NULL DEFAULT 0,
+            title_chunks_total  INTEGER NOT NULL DEFAULT 0,
+            done_tids           TEXT NOT NULL DEFAULT ''
+        );
+        INSERT INTO sync_runs_new
+            (id, started_at, finished_at, status, titles, titles_changed, programs,
+             programs_changed, error)
+        SELECT id, started_at, finished_at, status, titles, titles_changed, programs,
+               programs_changed, error
+        FROM sync_runs;
+        DROP TABLE sync_runs;
+        ALTER TABLE sync_runs_new RENAME TO sync_runs;",
+    )
+    .context("failed to rebuild sync_runs table")?;
+
+    Ok(())
+}
+
+/// Migration to v20: add `title_season_ranges` mapping ranges of Syoboi episode
+/// counts to TMDB season numbers.
+fn migrate_v20(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS title_season_ranges (
+            tid INTEGER NOT NULL REFERENCES titles(tid) ON DELETE CASCADE,
+            first_count INTEGER NOT NULL,
+            last_count INTEGER,
+            season_number INTEGER NOT NULL,
+            PRIMARY KEY (tid, first_count)
+        );",
+    )
+    .context("failed to create title_season_ranges table")?;
+
+    Ok(())
+}
+
+/// Migration to v21: add `program_tags` and `notes` for user annotations of
+/// programs.
+fn migrate_v21(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS program_tags (
+            pid INTEGER NOT NULL REFERENCES programs(pid) ON DELETE CASCADE,
+            tag TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (pid, tag)
+        );
+        CREATE INDEX IF NOT EXISTS idx_program_tags_tag ON program_tags(tag);
+        CREATE TABLE IF NOT EXISTS notes (
+            pid INTEGER PRIMARY KEY REFERENCES programs(pid) ON DELETE CASCADE,
+            body TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );",
+    )
+    .context("failed to create program_tags / notes tables")?;
+
+    Ok(())
+}
+
+/// Migration to v22: add `tmdb_movie_id` to titles. Movie IDs previously stored
+/// in `tmdb_series_id` for movie titles (`Cat=8`) are moved over.
+fn migrate_v22(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE titles ADD COLUMN tmdb_movie_id INTEGER;
+        CREATE INDEX IF NOT EXISTS idx_titles_tmdb_movie_id ON titles(tmdb_movie_id);
+        UPDATE titles
+        SET tmdb_movie_id = tmdb_series_id,
+            tmdb_series_id = NULL,
+            tmdb_season_number = NULL,
+            tmdb_season_id = NULL
+        WHERE cat = 8 AND tmdb_series_id IS NOT NULL;",
+    )
+    .context("failed to add tmdb_movie_id to titles")?;
+
+    Ok(())
+}
+
+/// Migration to v23: add `tmdb_episode_group_id` to titles, the TMDB episode
+/// group (alternate order) used instead of the mapped season.
+fn migrate_v23(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE titles ADD COLUMN tmdb_episode_group_id TEXT;")
+        .context("failed to add tmdb_episode_group_id to titles")?;
+
+    Ok(())
+}
+
+/// Migration to v24: add `title_localizations` for TMDB names and overviews per
+/// language.
+fn migrate_v24(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS title_localizations (
+            tid INTEGER NOT NULL REFERENCES titles(tid) ON DELETE CASCADE,
+            language TEXT NOT NULL,
+            name TEXT NOT NULL,
+            overview TEXT,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (tid, language)
+        );",
+    )
+    .context("failed to create title_localizations table")?;
+
+    Ok(())
+}
+
+/// Migration to v25: add `snapshots` and `snapshot_programs`, named copies of
+/// the program schedule compared by `db diff`.
+fn migrate_v25(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS snapshots (
+            name TEXT PRIMARY KEY,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS snapshot_programs (
+            snapshot TEXT NOT NULL REFERENCES snapshots(name) ON DELETE CASCADE,
+            pid INTEGER NOT NULL,
+            tid INTEGER NOT NULL,
+            ch_id INTEGER NOT NULL,
+            st_time TEXT NOT NULL,
+            ed_time TEXT NOT NULL,
+            count INTEGER,
+            st_sub_title TEXT,
+            PRIMARY KEY (snapshot, pid)
+        );",
+    )
+    .context("failed to create snapshot tables")?;
+
+    Ok(())
+}
+
+/// Migration to v26: add `category` to titles, the decoded `cat` label (as
+/// `TitleCategory::label` in `dtvmgr-api`), generated from `cat`.
+fn migrate_v26(conn: &Connection) -> Result<()> {
+    // `ADD COLUMN` has no `IF NOT EXISTS`; generated columns are only listed
+    // by `table_xinfo`.
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_xinfo('titles') WHERE name = 'category'",
+            [],
+            |row| row.get(0),
+        )
+        .context("failed to inspect titles columns")?;
+    if exists {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "ALTER TABLE titles ADD COLUMN category TEXT GENERATED ALWAYS AS (
+            CASE COALESCE(cat, 0)
+                WHEN 0 THEN 'other'
+                WHEN 1 THEN 'anime'
+                WHEN 2 THEN 'radio'
+                WHEN 3 THEN 'tv'
+                WHEN 4 THEN 'tokusatsu'
+                WHEN 5 THEN 'anime-rel'
+                WHEN 6 THEN 'memo'
+                WHEN 7 THEN 'ova'
+                WHEN 8 THEN 'movie'
+                WHEN 10 THEN 'anime-end'
+                ELSE 'unknown'
+            END
+        ) VIRTUAL;",
+    )
+    .context("failed to add category to titles")?;
+
+    Ok(())
+}
+
+/// Migration to v27: add `title_staff` and `title_links`, the staff / cast and
+/// links sections parsed from the Syoboi title comment.
+fn migrate_v27(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS title_staff (
+            tid INTEGER NOT NULL REFERENCES titles(tid) ON DELETE CASCADE,
+            kind TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            name TEXT NOT NULL,
+            PRIMARY KEY (tid, kind, position)
+        );
+        CREATE INDEX IF NOT EXISTS idx_title_staff_name ON title_staff(name);
+        CREATE TABLE IF NOT EXISTS title_links (
+            tid INTEGER NOT NULL REFERENCES titles(tid) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            url TEXT NOT NULL,
+            PRIMARY KEY (tid, position)
+        );",
+    )
+    .context("failed to create title_staff / title_links tables")?;
+
+    Ok(())
+}
+
+/// Migration to v28: add `program_history`, filled by a trigger with the
+/// previous and new times whenever an update changes a program's `st_time`,
+/// `ed_time`, or `revision`.
+fn migrate_v28(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS program_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            pid INTEGER NOT NULL REFERENCES programs(pid) ON DELETE CASCADE,
+            changed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+            old_st_time TEXT NOT NULL,
+            old_ed_time TEXT NOT NULL,
+            old_revision INTEGER,
+            new_st_time TEXT NOT NULL,
+            new_ed_time TEXT NOT NULL,
+            new_revision INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_program_history_pid ON program_history(pid);
+        CREATE TRIGGER IF NOT EXISTS programs_history_au AFTER UPDATE ON programs
+        WHEN old.st_time IS NOT new.st_time
+          OR old.ed_time IS NOT new.ed_time
+          OR old.revision IS NOT new.revision
+        BEGIN
+            INSERT INTO program_history (
+                pid, old_st_time, old_ed_time, old_revision,
+                new_st_time, new_ed_time, new_revision
+            ) VALUES (
+                new.pid, old.st_time, old.ed_time, old.revision,
+                new.st_time, new.ed_time, new.revision
+            );
+        END;",
+    )
+    .context("failed to create program_history table")?;
+
+    Ok(())
+}
+
+/// Migration to v29: add the title `comment`, `user_point`, and
+/// `user_point_rank` columns, stored when `[syoboi.sync] title_fields` requests
+/// them.
+fn migrate_v29(conn: &Connection) -> Result<()> {
+    // `ADD COLUMN` has no `IF NOT EXISTS`.
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('titles') WHERE name = 'user_point'",
+            [],
+            |row| row.get(0),
+        )
+        .context("failed to inspect titles columns")?;
+    if exists {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "ALTER TABLE titles ADD COLUMN comment TEXT;
+         ALTER TABLE titles ADD COLUMN user_point INTEGER;
+         ALTER TABLE titles ADD COLUMN user_point_rank INTEGER;",
+    )
+    .context("failed to add comment / user point columns to titles")?;
+
+    Ok(())
+}
+
+/// Migration to v30: add `title_aliases`, recording TIDs merged into another
+/// title by `titles merge` so sync folds their programs into the new TID.
+fn migrate_v30(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS title_aliases (
+            old_tid   INTEGER PRIMARY KEY,
+            new_tid   INTEGER NOT NULL REFERENCES titles(tid) ON DELETE CASCADE,
+            merged_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_title_aliases_new_tid ON title_aliases(new_tid);",
+    )
+    .context("failed to create title_aliases table")?;
+
+    Ok(())
+}
+
+/// Migration to v31: move the raw `titles.sub_titles` text (`*01*Subtitle` per
+/// line) into the normalized `episodes` table and drop the column.
+fn migrate_v31(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS episodes (
+            tid       INTEGER NOT NULL REFERENCES titles(tid) ON DELETE CASCADE,
+            count     INTEGER NOT NULL,
+            sub_title TEXT NOT NULL,
+            PRIMARY KEY (tid, count)
+        );",
+    )
+    .context("failed to create episodes table")?;
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('titles') WHERE name = 'sub_titles'",
+            [],
+            |row| row.get(0),
+        )
+        .context("failed to inspect titles columns")?;
+    if !exists {
+        return Ok(());
+    }
+    // Split the text into lines and keep those shaped like `*<digits>*<text>`,
+    // the same lines `parse_sub_titles` accepts.
+    conn.execute_batch(
+        "WITH RECURSIVE lines(tid, line, rest) AS (
+            SELECT tid, NULL, replace(sub_titles, char(13), '') || char(10)
+            FROM titles WHERE sub_titles IS NOT NULL
+            UNION ALL
+            SELECT tid,
+                   trim(substr(rest, 1, instr(rest, char(10)) - 1)),
+                   substr(rest, instr(rest, char(10)) + 1)
+            FROM lines WHERE rest <> ''
+        ),
+        parsed(tid, num, sub_title) AS (
+            SELECT tid,
+                   substr(line, 2, instr(substr(line, 2), '*') - 1),
+                   substr(line, instr(substr(line, 2), '*') + 2)
+            FROM lines
+            WHERE line GLOB '[*][0-9]*[*]?*'
+        )
+        INSERT OR IGNORE INTO episodes (tid, count, sub_title)
+        SELECT tid, CAST(num AS INTEGER), sub_title FROM parsed
+        WHERE num NOT GLOB '*[^0-9]*';
+        ALTER TABLE titles DROP COLUMN sub_titles;",
+    )
+    .context("failed to move title sub-titles into episodes")?;
+
+    Ok(())
+}
+
+/// Migration to v32: add `fetch_log`, recording when the read-through cache
+/// last fetched each request from the Syoboi API.
+fn migrate_v32(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS fetch_log (
+            key        TEXT PRIMARY KEY,
+            fetched_at TEXT NOT NULL
+        );",
+    )
+    .context("failed to create fetch_log table")?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -427,6 +1006,871 @@ mod tests {
         assert_eq!(stmt.column_count(), 1);
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v7_to_v8_migration() {
+        // Arrange: start from v7 with an existing title
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO titles (tid, title, last_update) VALUES (1, 'ぼっち・ざ・ろっく！', '2024-01-01 00:00:00')",
+            [],
+        )
+        .unwrap();
+        conn.pragma_update(None, "user_version", 7u32).unwrap();
+
+        // Act: run full migrations (should apply v8)
+        run_migrations(&conn).unwrap();
+
+        // Assert: existing rows are indexed by the rebuild
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+        let hits: u32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM titles_fts WHERE titles_fts MATCH '\"ざ・ろっく\"'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v8_to_v9_migration() {
+        // Arrange: start from v8
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 8u32).unwrap();
+
+        // Act: run full migrations (should apply v9)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT tid, notify_new_program, notify_time_change, notify_finale, added_at FROM watchlist LIMIT 0")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 5);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v9_to_v10_migration() {
+        // Arrange: start from v9
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 9u32).unwrap();
+
+        // Act: run full migrations (should apply v10)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT video_file_id, sha256, size, hashed_at FROM video_file_hashes LIMIT 0")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 4);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v10_to_v11_migration() {
+        // Arrange: start from v10
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 10u32).unwrap();
+
+        // Act: run full migrations (should apply v11)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT id, kind, state, payload, attempts, max_attempts, last_error, scheduled_at, created_at, updated_at FROM jobs LIMIT 0")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 10);
+    }
+
+    #[test]
+    fn test_v11_to_v12_migration() {
+        // Arrange: start from v11
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 11u32).unwrap();
+
+        // Act: run full migrations (should apply v12)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT scope, last_update, synced_at FROM sync_state")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 3);
+    }
+
+    #[test]
+    fn test_v12_to_v13_migration() {
+        // Arrange: start from v12
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 12u32).unwrap();
+
+        // Act: run full migrations (should apply v13)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT id, started_at, finished_at, status, titles, titles_changed, programs, programs_changed, error FROM sync_runs")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 9);
+    }
+
+    #[test]
+    fn test_v13_to_v14_migration() {
+        // Arrange: start from v13
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 13u32).unwrap();
+
+        // Act: run full migrations (should apply v14)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn.prepare("SELECT * FROM channel_aliases").unwrap();
+        assert_eq!(stmt.column_count(), 6);
+    }
+
+    #[test]
+    fn test_v14_to_v15_migration() {
+        // Arrange: start from v14
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 14u32).unwrap();
+
+        // Act: run full migrations (should apply v15)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn.prepare("SELECT followed FROM titles").unwrap();
+        assert_eq!(stmt.column_count(), 1);
+    }
+
+    #[test]
+    fn test_v15_to_v16_migration() {
+        // Arrange: start from v15
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 15u32).unwrap();
+
+        // Act: run full migrations (should apply v16)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn.prepare("SELECT imdb_id, tvdb_id FROM titles").unwrap();
+        assert_eq!(stmt.column_count(), 2);
+    }
+
+    #[test]
+    fn test_v16_to_v17_migration() {
+        // Arrange: start from v16
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        migrate_v16(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 16u32).unwrap();
+
+        // Act: run full migrations (should apply v17)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT ch_id, ch_gid, ch_name, ch_url, ch_iepg_name, ch_comment, logo_url FROM channels")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 7);
+    }
+
+    #[test]
+    fn test_v17_to_v18_migration() {
+        // Arrange: start from v17
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        migrate_v16(&conn).unwrap();
+        migrate_v17(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 17u32).unwrap();
+
+        // Act: run full migrations (should apply v18)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare(
+                "SELECT tid, kind, file_path, local_path, sha256, downloaded_at FROM title_images",
+            )
+            .unwrap();
+        assert_eq!(stmt.column_count(), 6);
+    }
+
+    #[test]
+    fn test_v18_to_v19_migration() {
+        // Arrange: start from v18
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        migrate_v16(&conn).unwrap();
+        migrate_v17(&conn).unwrap();
+        migrate_v18(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 18u32).unwrap();
+
+        // Act: run full migrations (should apply v19)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT id, started_at, finished_at, status, titles, titles_changed, programs, programs_changed, error, params, programs_fetched, title_chunks_done, title_chunks_total, done_tids FROM sync_runs")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 14);
+    }
+
+    #[test]
+    fn test_v19_to_v20_migration() {
+        // Arrange: start from v19
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        migrate_v16(&conn).unwrap();
+        migrate_v17(&conn).unwrap();
+        migrate_v18(&conn).unwrap();
+        migrate_v19(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 19u32).unwrap();
+
+        // Act: run full migrations (should apply v20)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT tid, first_count, last_count, season_number FROM title_season_ranges")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 4);
+    }
+
+    #[test]
+    fn test_v20_to_v21_migration() {
+        // Arrange: start from v20
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        migrate_v16(&conn).unwrap();
+        migrate_v17(&conn).unwrap();
+        migrate_v18(&conn).unwrap();
+        migrate_v19(&conn).unwrap();
+        migrate_v20(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 20u32).unwrap();
+
+        // Act: run full migrations (should apply v21)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT pid, tag, created_at FROM program_tags")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 3);
+    }
+
+    #[test]
+    fn test_v21_to_v22_migration() {
+        // Arrange: start from v21
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        migrate_v16(&conn).unwrap();
+        migrate_v17(&conn).unwrap();
+        migrate_v18(&conn).unwrap();
+        migrate_v19(&conn).unwrap();
+        migrate_v20(&conn).unwrap();
+        migrate_v21(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 21u32).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, cat, last_update, tmdb_series_id, tmdb_season_number)
+             VALUES (1, 'Movie', 8, '2023-01-01 00:00:00', 916224, 1),
+                    (2, 'Series', 1, '2023-01-01 00:00:00', 120089, 1);",
+        )
+        .unwrap();
+
+        // Act: run full migrations (should apply v22)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let rows: Vec<(Option<u64>, Option<u64>, Option<u32>)> = conn
+            .prepare(
+                "SELECT tmdb_movie_id, tmdb_series_id, tmdb_season_number
+                 FROM titles ORDER BY tid",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![(Some(916_224), None, None), (None, Some(120_089), Some(1))]
+        );
+    }
+
+    #[test]
+    fn test_v22_to_v23_migration() {
+        // Arrange: start from v22
+        let conn = Connection::open_in_memory().unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        migrate_v10(&conn).unwrap();
+        migrate_v11(&conn).unwrap();
+        migrate_v12(&conn).unwrap();
+        migrate_v13(&conn).unwrap();
+        migrate_v14(&conn).unwrap();
+        migrate_v15(&conn).unwrap();
+        migrate_v16(&conn).unwrap();
+        migrate_v17(&conn).unwrap();
+        migrate_v18(&conn).unwrap();
+        migrate_v19(&conn).unwrap();
+        migrate_v20(&conn).unwrap();
+        migrate_v21(&conn).unwrap();
+        migrate_v22(&conn).unwrap();
+        conn.pragma_update(None, "user_version", 22u32).unwrap();
+
+        // Act: run full migrations (should apply v23)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT tmdb_episode_group_id FROM titles")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 1);
+    }
+
+    #[test]
+    fn test_v23_to_v24_migration() {
+        // Arrange: start from v23
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute_batch("DROP TABLE title_localizations;")
+            .unwrap();
+        conn.pragma_update(None, "user_version", 23u32).unwrap();
+
+        // Act: run full migrations (should apply v24)
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+
+        let stmt = conn
+            .prepare("SELECT tid, language, name, overview, updated_at FROM title_localizations")
+            .unwrap();
+        assert_eq!(stmt.column_count(), 5);
+    }
+
+    #[test]
+    fn test_migration_steps_match_current_version() {
+        // Arrange & Act & Assert
+        assert_eq!(u32::try_from(MIGRATIONS.len()).unwrap(), CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_v24_to_v25_migration() {
+        // Arrange: start from v24
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute_batch("DROP TABLE snapshot_programs; DROP TABLE snapshots;")
+            .unwrap();
+        conn.pragma_update(None, "user_version", 24u32).unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+        let stmt = conn
+            .prepare(
+                "SELECT snapshot, pid, tid, ch_id, st_time, ed_time, count, st_sub_title
+                 FROM snapshot_programs",
+            )
+            .unwrap();
+        assert_eq!(stmt.column_count(), 8);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v25_to_v26_migration() {
+        // Arrange: start from v25 with titles already cached
+        let conn = Connection::open_in_memory().unwrap();
+        for migrate in MIGRATIONS.iter().take(25) {
+            migrate(&conn).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 25u32).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, cat, last_update) VALUES
+                (1, 'A', 1, ''), (2, 'B', 7, ''), (3, 'C', NULL, ''), (4, 'D', 9, '');",
+        )
+        .unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+
+        // Assert: existing rows get a label without being rewritten
+        let mut stmt = conn
+            .prepare("SELECT category FROM titles ORDER BY tid")
+            .unwrap();
+        let labels: Vec<String> = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(labels, vec!["anime", "ova", "other", "unknown"]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v26_to_v27_migration() {
+        // Arrange: start from v26
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute_batch("DROP TABLE title_staff; DROP TABLE title_links;")
+            .unwrap();
+        conn.pragma_update(None, "user_version", 26u32).unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+        let staff = conn
+            .prepare("SELECT tid, kind, position, role, name FROM title_staff")
+            .unwrap();
+        assert_eq!(staff.column_count(), 5);
+        let links = conn
+            .prepare("SELECT tid, position, label, url FROM title_links")
+            .unwrap();
+        assert_eq!(links.column_count(), 4);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v27_to_v28_migration() {
+        // Arrange: start from v27
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute_batch("DROP TRIGGER programs_history_au; DROP TABLE program_history;")
+            .unwrap();
+        conn.pragma_update(None, "user_version", 27u32).unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, CURRENT_VERSION);
+        let triggers: u32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master
+                 WHERE type = 'trigger' AND name = 'programs_history_au'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(triggers, 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v28_to_v29_migration() {
+        // Arrange: start from v28 with a title already cached
+        let conn = Connection::open_in_memory().unwrap();
+        for migrate in MIGRATIONS.iter().take(28) {
+            migrate(&conn).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 28u32).unwrap();
+        conn.execute_batch("INSERT INTO titles (tid, title, last_update) VALUES (1, 'A', '');")
+            .unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let row: (Option<String>, Option<i32>, Option<u32>) = conn
+            .query_row(
+                "SELECT comment, user_point, user_point_rank FROM titles WHERE tid = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(row, (None, None, None));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v29_to_v30_migration() {
+        // Arrange: start from v29 with a title already cached
+        let conn = Connection::open_in_memory().unwrap();
+        for migrate in MIGRATIONS.iter().take(29) {
+            migrate(&conn).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 29u32).unwrap();
+        conn.execute_batch("INSERT INTO titles (tid, title, last_update) VALUES (2, 'B', '');")
+            .unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO title_aliases (old_tid, new_tid) VALUES (1, 2)",
+            [],
+        )
+        .unwrap();
+
+        // Assert
+        let (new_tid, merged_at): (u32, String) = conn
+            .query_row(
+                "SELECT new_tid, merged_at FROM title_aliases WHERE old_tid = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(new_tid, 2);
+        assert!(!merged_at.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v30_to_v31_migration() {
+        // Arrange: start from v30 with raw sub-titles cached
+        let conn = Connection::open_in_memory().unwrap();
+        for migrate in MIGRATIONS.iter().take(30) {
+            migrate(&conn).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 30u32).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update, sub_titles)
+                 VALUES (1, 'A', '', '*01*始まり*の日
+*02*次の日
+メモ
+*x*不正'),
+                        (2, 'B', '', NULL);",
+        )
+        .unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+
+        // Assert
+        let episodes: Vec<(u32, u32, String)> = conn
+            .prepare("SELECT tid, count, sub_title FROM episodes ORDER BY tid, count")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            episodes,
+            vec![
+                (1, 1, String::from("始まり*の日")),
+                (1, 2, String::from("次の日")),
+            ]
+        );
+        let column: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('titles') WHERE name = 'sub_titles'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(column, 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_v31_to_v32_migration() {
+        // Arrange
+        let conn = Connection::open_in_memory().unwrap();
+        for migrate in MIGRATIONS.iter().take(31) {
+            migrate(&conn).unwrap();
+        }
+        conn.pragma_update(None, "user_version", 31u32).unwrap();
+
+        // Act
+        run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO fetch_log (key, fetched_at) VALUES ('title:1', '2024-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+
+        // Assert
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM fetch_log", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_migrations_noop_when_current_version() {
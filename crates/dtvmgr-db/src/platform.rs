@@ -0,0 +1,254 @@
+//! Platform-native config, data, and cache directories.
+//!
+//! Shared by the CLI path resolution and [`crate::resolve_db_path`], so the
+//! default database location is the same for every caller.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Directory name of the application under the platform base directories.
+const APP_DIR_NAME: &str = "dtvmgr";
+
+/// Operating system family whose directory conventions apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// XDG base directories (Linux and other Unix systems).
+    Xdg,
+    /// `~/Library` (macOS).
+    MacOs,
+    /// `%APPDATA%` / `%LOCALAPPDATA%` (Windows).
+    Windows,
+}
+
+impl Platform {
+    /// Returns the platform the binary was built for.
+    #[must_use]
+    pub const fn current() -> Self {
+        if cfg!(windows) {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else {
+            Self::Xdg
+        }
+    }
+}
+
+/// Platform base directories of dtvmgr.
+///
+/// Derived from environment variables only, so the layout of every
+/// platform can be checked on any CI host with [`PlatformDirs::from_env`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct PlatformDirs {
+    /// Config directory.
+    pub config: Option<PathBuf>,
+    /// Data directory (database, reports).
+    pub data: Option<PathBuf>,
+    /// Cache directory (logos, images).
+    pub cache: Option<PathBuf>,
+}
+
+impl PlatformDirs {
+    /// Detects the directories of the current platform from the process
+    /// environment.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::from_env(Platform::current(), |key| std::env::var_os(key))
+    }
+
+    /// Builds the directories of `platform` using the given environment
+    /// lookup. Empty variables count as unset.
+    ///
+    /// - XDG: `$XDG_CONFIG_HOME/dtvmgr`, `$XDG_DATA_HOME/dtvmgr`, and
+    ///   `$XDG_CACHE_HOME/dtvmgr`, falling back to `~/.config`,
+    ///   `~/.local/share`, and `~/.cache`. Relative XDG values are ignored.
+    /// - macOS: `~/Library/Application Support/dtvmgr` for config and data,
+    ///   `~/Library/Caches/dtvmgr` for the cache.
+    /// - Windows: `%APPDATA%\dtvmgr\config`, `%APPDATA%\dtvmgr\data`, and
+    ///   `%LOCALAPPDATA%\dtvmgr\cache`, falling back to the `AppData`
+    ///   folders under `%USERPROFILE%`.
+    #[must_use]
+    pub fn from_env(platform: Platform, get: impl Fn(&str) -> Option<OsString>) -> Self {
+        let var = |key: &str| get(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+        let under = |base: Option<PathBuf>, parts: &[&str]| {
+            base.map(|b| parts.iter().fold(b, |path, part| path.join(part)))
+        };
+        match platform {
+            Platform::Xdg => {
+                let home = var("HOME");
+                let xdg = |key: &str, fallback: &[&str]| {
+                    var(key)
+                        .filter(|d| d.is_absolute())
+                        .or_else(|| under(home.clone(), fallback))
+                        .map(|d| d.join(APP_DIR_NAME))
+                };
+                Self {
+                    config: xdg("XDG_CONFIG_HOME", &[".config"]),
+                    data: xdg("XDG_DATA_HOME", &[".local", "share"]),
+                    cache: xdg("XDG_CACHE_HOME", &[".cache"]),
+                }
+            }
+            Platform::MacOs => {
+                let home = var("HOME");
+                let support = under(
+                    home.clone(),
+                    &["Library", "Application Support", APP_DIR_NAME],
+                );
+                Self {
+                    config: support.clone(),
+                    data: support,
+                    cache: under(home, &["Library", "Caches", APP_DIR_NAME]),
+                }
+            }
+            Platform::Windows => {
+                let profile = var("USERPROFILE");
+                let roaming =
+                    var("APPDATA").or_else(|| under(profile.clone(), &["AppData", "Roaming"]));
+                let local = var("LOCALAPPDATA").or_else(|| under(profile, &["AppData", "Local"]));
+                Self {
+                    config: under(roaming.clone(), &[APP_DIR_NAME, "config"]),
+                    data: under(roaming, &[APP_DIR_NAME, "data"]),
+                    cache: under(local, &[APP_DIR_NAME, "cache"]),
+                }
+            }
+        }
+    }
+
+    /// Builds the layout used before platform-native directories, on every
+    /// platform: `$HOME/.config/dtvmgr` for config and
+    /// `$HOME/.local/share/dtvmgr` for data (no cache directory). Files
+    /// found there are migrated to the current layout.
+    #[must_use]
+    pub fn legacy(get: impl Fn(&str) -> Option<OsString>) -> Self {
+        let home = get("HOME").filter(|v| !v.is_empty()).map(PathBuf::from);
+        Self {
+            config: home.as_ref().map(|h| h.join(".config").join(APP_DIR_NAME)),
+            data: home.map(|h| h.join(".local").join("share").join(APP_DIR_NAME)),
+            cache: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<const N: usize>(
+        vars: [(&'static str, &'static str); N],
+    ) -> impl Fn(&str) -> Option<OsString> {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| OsString::from(v))
+        }
+    }
+
+    #[test]
+    fn test_platform_dirs_xdg_prefers_absolute_xdg_vars() {
+        // Arrange
+        let vars = env([
+            ("HOME", "/home/u"),
+            ("XDG_CONFIG_HOME", "/xdg/config"),
+            ("XDG_DATA_HOME", "relative/data"),
+            ("XDG_CACHE_HOME", ""),
+        ]);
+
+        // Act
+        let dirs = PlatformDirs::from_env(Platform::Xdg, vars);
+
+        // Assert: relative and empty values fall back to $HOME
+        assert_eq!(dirs.config, Some(PathBuf::from("/xdg/config/dtvmgr")));
+        assert_eq!(
+            dirs.data,
+            Some(PathBuf::from("/home/u/.local/share/dtvmgr"))
+        );
+        assert_eq!(dirs.cache, Some(PathBuf::from("/home/u/.cache/dtvmgr")));
+    }
+
+    #[test]
+    fn test_platform_dirs_macos_uses_library() {
+        // Arrange & Act
+        let dirs = PlatformDirs::from_env(Platform::MacOs, env([("HOME", "/Users/u")]));
+
+        // Assert
+        let support = PathBuf::from("/Users/u/Library/Application Support/dtvmgr");
+        assert_eq!(dirs.config, Some(support.clone()));
+        assert_eq!(dirs.data, Some(support));
+        assert_eq!(
+            dirs.cache,
+            Some(PathBuf::from("/Users/u/Library/Caches/dtvmgr"))
+        );
+    }
+
+    #[test]
+    fn test_platform_dirs_windows_uses_appdata() {
+        // Arrange
+        let roaming = r"C:\Users\u\AppData\Roaming";
+        let local = r"D:\Local";
+        let vars = env([("APPDATA", roaming), ("LOCALAPPDATA", local)]);
+
+        // Act
+        let dirs = PlatformDirs::from_env(Platform::Windows, vars);
+
+        // Assert
+        let app = PathBuf::from(roaming).join("dtvmgr");
+        assert_eq!(dirs.config, Some(app.join("config")));
+        assert_eq!(dirs.data, Some(app.join("data")));
+        assert_eq!(
+            dirs.cache,
+            Some(PathBuf::from(local).join("dtvmgr").join("cache"))
+        );
+    }
+
+    #[test]
+    fn test_platform_dirs_windows_falls_back_to_userprofile() {
+        // Arrange
+        let profile = r"C:\Users\u";
+        let vars = env([("USERPROFILE", profile), ("APPDATA", "")]);
+
+        // Act
+        let dirs = PlatformDirs::from_env(Platform::Windows, vars);
+
+        // Assert
+        let appdata = PathBuf::from(profile).join("AppData");
+        assert_eq!(
+            dirs.data,
+            Some(appdata.join("Roaming").join("dtvmgr").join("data"))
+        );
+        assert_eq!(
+            dirs.cache,
+            Some(appdata.join("Local").join("dtvmgr").join("cache"))
+        );
+    }
+
+    #[test]
+    fn test_platform_dirs_legacy_ignores_xdg_vars() {
+        // Arrange
+        let vars = env([("HOME", "/Users/u"), ("XDG_CONFIG_HOME", "/xdg/config")]);
+
+        // Act
+        let dirs = PlatformDirs::legacy(vars);
+
+        // Assert
+        assert_eq!(dirs.config, Some(PathBuf::from("/Users/u/.config/dtvmgr")));
+        assert_eq!(
+            dirs.data,
+            Some(PathBuf::from("/Users/u/.local/share/dtvmgr"))
+        );
+        assert_eq!(dirs.cache, None);
+    }
+
+    #[test]
+    fn test_platform_dirs_without_home_is_empty() {
+        // Arrange & Act
+        let xdg = PlatformDirs::from_env(Platform::Xdg, env([]));
+        let windows = PlatformDirs::from_env(Platform::Windows, env([]));
+        let legacy = PlatformDirs::legacy(env([]));
+
+        // Assert
+        assert_eq!(legacy, PlatformDirs::default());
+        assert_eq!(xdg, PlatformDirs::default());
+        assert_eq!(windows, PlatformDirs::default());
+    }
+}
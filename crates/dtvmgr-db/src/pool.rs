@@ -0,0 +1,402 @@
+//! Shared pool of database connections.
+//!
+//! A `rusqlite::Connection` is `Send` but not `Sync`, so long-running
+//! processes (the daemon, the HTTP server) cannot share one connection
+//! between tasks. [`DbPool`] hands out connections one at a time and keeps
+//! returned ones open for reuse; together with WAL mode and the busy
+//! timeout this lets several readers and one writer use the database at
+//! once without `database is locked` errors.
+//!
+//! CRUD functions take a [`DbHandle`]: the [`DbPool`] itself, which checks
+//! out a connection for the duration of the call, or a connection the
+//! caller already holds (a [`PooledConnection`], a plain [`Connection`], or
+//! a [`Transaction`]) when several calls must share one connection.
+
+use core::borrow::Borrow;
+use core::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use anyhow::{Result, bail};
+use rusqlite::{Connection, Transaction};
+
+use crate::connection::{DbOptions, open_db_with_options};
+
+/// Default number of connections a [`DbPool`] keeps open.
+pub const DEFAULT_POOL_SIZE: usize = 4;
+
+/// Idle connections and the number of connections handed out or idle.
+struct PoolState {
+    idle: Vec<Connection>,
+    open: usize,
+}
+
+struct PoolInner {
+    dir: Option<PathBuf>,
+    options: DbOptions,
+    max_size: usize,
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+impl PoolInner {
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Cloneable handle to a bounded pool of database connections.
+///
+/// Connections are opened lazily with [`open_db_with_options`] (so
+/// migrations run on the first open) and at most `max_size` exist at a
+/// time. Clones share the same pool.
+#[derive(Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct DbPool {
+    inner: Arc<PoolInner>,
+}
+
+impl core::fmt::Debug for DbPool {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = self.inner.lock();
+        f.debug_struct("DbPool")
+            .field("dir", &self.inner.dir)
+            .field("max_size", &self.inner.max_size)
+            .field("open", &state.open)
+            .field("idle", &state.idle.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl DbPool {
+    /// Creates a pool over the database in `dir` (see
+    /// [`open_db_with_options`]). No connection is opened until
+    /// [`get`](Self::get) is called; `max_size` is raised to at least 1.
+    #[must_use]
+    pub fn new(dir: Option<PathBuf>, options: DbOptions, max_size: usize) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                dir,
+                options,
+                max_size: max_size.max(1),
+                state: Mutex::new(PoolState {
+                    idle: Vec::new(),
+                    open: 0,
+                }),
+                available: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Checks out a connection, reusing an idle one or opening a new one
+    /// while fewer than `max_size` are open.
+    ///
+    /// When the pool is exhausted, blocks for up to the options'
+    /// `busy_timeout` until another connection is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if opening a connection fails or no connection
+    /// becomes available within the busy timeout.
+    pub fn get(&self) -> Result<PooledConnection> {
+        let started = Instant::now();
+        let mut state = self.inner.lock();
+        loop {
+            if let Some(conn) = state.idle.pop() {
+                return Ok(self.wrap(conn));
+            }
+            if state.open < self.inner.max_size {
+                state.open = state.open.saturating_add(1);
+                drop(state);
+                return match open_db_with_options(self.inner.dir.as_ref(), &self.inner.options) {
+                    Ok(conn) => Ok(self.wrap(conn)),
+                    Err(e) => {
+                        self.release_slot();
+                        Err(e)
+                    }
+                };
+            }
+            let remaining = self
+                .inner
+                .options
+                .busy_timeout
+                .saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                bail!(
+                    "timed out waiting for a database connection ({} in use)",
+                    self.inner.max_size
+                );
+            }
+            state = self
+                .inner
+                .available
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Returns the data directory the pool opens connections in.
+    #[must_use]
+    pub fn dir(&self) -> Option<&PathBuf> {
+        self.inner.dir.as_ref()
+    }
+
+    /// Returns the number of connections currently open (idle or in use).
+    #[must_use]
+    pub fn open_connections(&self) -> usize {
+        self.inner.lock().open
+    }
+
+    fn wrap(&self, conn: Connection) -> PooledConnection {
+        PooledConnection {
+            conn: Some(conn),
+            pool: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Frees the slot of a connection that failed to open.
+    fn release_slot(&self) {
+        let mut state = self.inner.lock();
+        state.open = state.open.saturating_sub(1);
+        drop(state);
+        self.inner.available.notify_one();
+    }
+}
+
+/// Database handle accepted by the CRUD functions.
+pub trait DbHandle {
+    /// Returns a connection to run the call on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a connection has to be checked out of a
+    /// [`DbPool`] and none can be (see [`DbPool::get`]).
+    fn conn(&self) -> Result<DbConn<'_>>;
+}
+
+/// Connection returned by [`DbHandle::conn`]: borrowed from the handle or
+/// checked out of a pool for the call.
+#[derive(Debug)]
+pub enum DbConn<'a> {
+    /// A connection the caller holds.
+    Borrowed(&'a Connection),
+    /// A connection checked out of a [`DbPool`], returned on drop.
+    Pooled(PooledConnection),
+}
+
+impl Deref for DbConn<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            Self::Borrowed(conn) => conn,
+            Self::Pooled(conn) => conn,
+        }
+    }
+}
+
+impl DbHandle for DbPool {
+    fn conn(&self) -> Result<DbConn<'_>> {
+        self.get().map(DbConn::Pooled)
+    }
+}
+
+impl DbHandle for PooledConnection {
+    fn conn(&self) -> Result<DbConn<'_>> {
+        Ok(DbConn::Borrowed(self))
+    }
+}
+
+impl DbHandle for Connection {
+    fn conn(&self) -> Result<DbConn<'_>> {
+        Ok(DbConn::Borrowed(self))
+    }
+}
+
+impl DbHandle for Transaction<'_> {
+    fn conn(&self) -> Result<DbConn<'_>> {
+        Ok(DbConn::Borrowed(self))
+    }
+}
+
+impl<T: DbHandle + ?Sized> DbHandle for &T {
+    fn conn(&self) -> Result<DbConn<'_>> {
+        (**self).conn()
+    }
+}
+
+/// A connection checked out of a [`DbPool`]; returned to the pool on drop.
+#[allow(clippy::module_name_repetitions)]
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<PoolInner>,
+}
+
+impl core::fmt::Debug for PooledConnection {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PooledConnection")
+            .field("conn", &self.conn)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    #[allow(clippy::expect_used)]
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("pooled connection is only taken on drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    #[allow(clippy::expect_used)]
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn
+            .as_mut()
+            .expect("pooled connection is only taken on drop")
+    }
+}
+
+impl Borrow<Connection> for PooledConnection {
+    fn borrow(&self) -> &Connection {
+        self
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            let mut state = self.pool.lock();
+            state.idle.push(conn);
+            drop(state);
+            self.pool.available.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::time::Duration;
+
+    use super::*;
+
+    fn pool(dir: &tempfile::TempDir, max_size: usize, busy_timeout: Duration) -> DbPool {
+        DbPool::new(
+            Some(dir.path().to_path_buf()),
+            DbOptions {
+                busy_timeout,
+                ..DbOptions::default()
+            },
+            max_size,
+        )
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_pool_reuses_returned_connections() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool(&dir, 2, Duration::from_secs(5));
+
+        // Act
+        {
+            let conn = pool.get().unwrap();
+            conn.execute_batch("INSERT INTO channels (ch_id, ch_name) VALUES (1, 'NHK総合');")
+                .unwrap();
+        }
+        let conn = pool.get().unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM channels", [], |row| row.get(0))
+            .unwrap();
+
+        // Assert
+        assert_eq!(count, 1);
+        assert_eq!(pool.open_connections(), 1);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_pool_serves_concurrent_readers_and_writer() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool(&dir, 3, Duration::from_secs(5));
+        drop(pool.get().unwrap());
+
+        // Act
+        // Spawn every thread before joining any, so they overlap.
+        #[allow(clippy::needless_collect)]
+        let handles: Vec<_> = (0..6_u32)
+            .map(|i| {
+                let pool = pool.clone();
+                std::thread::spawn(move || {
+                    let conn = pool.get().unwrap();
+                    if i == 0 {
+                        conn.execute(
+                            "INSERT INTO channels (ch_id, ch_name) VALUES (?1, 'ch')",
+                            [i],
+                        )
+                        .map(|_| ())
+                    } else {
+                        conn.query_row("SELECT COUNT(*) FROM channels", [], |row| {
+                            row.get::<_, i64>(0)
+                        })
+                        .map(|_| ())
+                    }
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // Assert
+        assert!(results.iter().all(Result::is_ok));
+        assert!(pool.open_connections() <= 3);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_pool_times_out_when_exhausted() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool(&dir, 1, Duration::from_millis(50));
+        let held = pool.get().unwrap();
+
+        // Act
+        let result = pool.get();
+
+        // Assert
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("timed out waiting for a database connection")
+        );
+        drop(held);
+        assert!(pool.get().is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_crud_functions_accept_pool_and_checked_out_connections() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let pool = pool(&dir, 1, Duration::from_millis(50));
+
+        // Act: the pool checks out (and returns) a connection per call
+        crate::mark_channels_refreshed(&pool, "2024-04-01T00:00:00Z").unwrap();
+        let via_pool = crate::load_channels_refreshed_at(&pool).unwrap();
+        let conn = pool.get().unwrap();
+        let via_conn = crate::load_channels_refreshed_at(&conn).unwrap();
+
+        // Assert
+        assert_eq!(via_pool.as_deref(), Some("2024-04-01T00:00:00Z"));
+        assert_eq!(via_conn, via_pool);
+        assert_eq!(pool.open_connections(), 1);
+    }
+}
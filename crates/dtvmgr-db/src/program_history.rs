@@ -0,0 +1,148 @@
+//! Previous broadcast times of programs, recorded by the
+//! `programs_history_au` trigger.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// One change of a program's start time, end time, or revision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramHistoryEntry {
+    /// Syoboi program ID.
+    pub pid: u32,
+    /// When the change was stored (UTC, RFC 3339).
+    pub changed_at: String,
+    /// Start time before the change.
+    pub old_st_time: String,
+    /// End time before the change.
+    pub old_ed_time: String,
+    /// Revision before the change.
+    pub old_revision: Option<u32>,
+    /// Start time after the change.
+    pub new_st_time: String,
+    /// End time after the change.
+    pub new_ed_time: String,
+    /// Revision after the change.
+    pub new_revision: Option<u32>,
+}
+
+/// Loads the recorded changes of `pid`, oldest first.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_program_history(db: &impl DbHandle, pid: u32) -> Result<Vec<ProgramHistoryEntry>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT pid, changed_at, old_st_time, old_ed_time, old_revision,
+                    new_st_time, new_ed_time, new_revision
+             FROM program_history
+             WHERE pid = ?1
+             ORDER BY id",
+        )
+        .context("failed to prepare program history query")?;
+    let rows = stmt
+        .query_map([pid], |row| {
+            Ok(ProgramHistoryEntry {
+                pid: row.get(0)?,
+                changed_at: row.get(1)?,
+                old_st_time: row.get(2)?,
+                old_ed_time: row.get(3)?,
+                old_revision: row.get(4)?,
+                new_st_time: row.get(5)?,
+                new_ed_time: row.get(6)?,
+                new_revision: row.get(7)?,
+            })
+        })
+        .context("failed to query program history")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read program history rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+    use crate::programs::{CachedProgram, upsert_programs};
+
+    fn program(st_time: &str, ed_time: &str, revision: u32, last_update: &str) -> CachedProgram {
+        CachedProgram {
+            pid: 100,
+            tid: 6309,
+            ch_id: 7,
+            tmdb_episode_id: None,
+            st_time: st_time.to_owned(),
+            st_offset: None,
+            ed_time: ed_time.to_owned(),
+            count: Some(1),
+            sub_title: None,
+            flag: None,
+            deleted: None,
+            warn: None,
+            revision: Some(revision),
+            last_update: Some(last_update.to_owned()),
+            st_sub_title: None,
+            duration_min: None,
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_records_time_changes_only() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'Test');
+             INSERT INTO titles (tid, title, last_update) VALUES (6309, 'Test', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+        upsert_programs(
+            &conn,
+            &[program(
+                "2024-04-06 23:00:00",
+                "2024-04-06 23:30:00",
+                0,
+                "2024-03-01 00:00:00",
+            )],
+        )
+        .unwrap();
+
+        // Act: a sub-title-only update, then a 15 minute delay
+        let mut retitled = program(
+            "2024-04-06 23:00:00",
+            "2024-04-06 23:30:00",
+            0,
+            "2024-03-02 00:00:00",
+        );
+        retitled.sub_title = Some(String::from("Sub"));
+        upsert_programs(&conn, &[retitled]).unwrap();
+        upsert_programs(
+            &conn,
+            &[program(
+                "2024-04-06 23:15:00",
+                "2024-04-06 23:45:00",
+                1,
+                "2024-03-03 00:00:00",
+            )],
+        )
+        .unwrap();
+        let history = load_program_history(&conn, 100).unwrap();
+
+        // Assert
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].old_st_time, "2024-04-06 23:00:00");
+        assert_eq!(history[0].new_st_time, "2024-04-06 23:15:00");
+        assert_eq!(history[0].old_revision, Some(0));
+        assert_eq!(history[0].new_revision, Some(1));
+        assert!(load_program_history(&conn, 999).unwrap().is_empty());
+    }
+}
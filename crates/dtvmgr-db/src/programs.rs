@@ -1,11 +1,15 @@
 //! Program cache CRUD operations.
 
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::OptionalExtension as _;
+use serde::Serialize;
 use tracing::instrument;
 
+use crate::connection::{DEFAULT_UPSERT_BATCH_SIZE, in_batches};
+use crate::pool::DbHandle;
+
 /// A cached program with optional TMDB mapping.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CachedProgram {
     /// Syoboi program ID.
     pub pid: u32,
@@ -35,85 +39,114 @@ pub struct CachedProgram {
     pub revision: Option<u32>,
     /// Last update timestamp (nullable).
     pub last_update: Option<String>,
-    /// Subtitle from `SubTitles` JOIN (nullable).
+    /// Subtitle from `SubTitles` JOIN (nullable). When loaded, a missing
+    /// value falls back to the title's `episodes` row for `count`.
     pub st_sub_title: Option<String>,
     /// Duration in minutes (`ed_time` - `st_time`).
     pub duration_min: Option<u32>,
 }
 
-/// Upserts programs into the cache. Returns the number of rows changed.
+/// Upserts programs into the cache in batches of
+/// [`DEFAULT_UPSERT_BATCH_SIZE`]. Returns the number of rows changed.
+///
+/// See [`upsert_programs_batched`].
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[allow(clippy::module_name_repetitions)]
+pub fn upsert_programs(db: &impl DbHandle, programs: &[CachedProgram]) -> Result<usize> {
+    let conn = &*db.conn()?;
+    upsert_programs_batched(conn, programs, DEFAULT_UPSERT_BATCH_SIZE)
+}
+
+/// Upserts programs into the cache, committing one transaction per
+/// `batch_size` rows (`0` writes all rows in one transaction). Returns the
+/// number of rows changed.
 ///
 /// Uses `INSERT ... ON CONFLICT(pid) DO UPDATE SET` to update existing rows.
 /// The `tmdb_episode_id` column is preserved on conflict to avoid
 /// overwriting manual TMDB mappings.
-/// Only updates when `last_update` has changed.
+/// Only updates when `last_update` has changed. The prepared statement is
+/// cached on the connection and reused across batches and calls.
 ///
 /// # Errors
 ///
-/// Returns an error if the database operation fails.
+/// Returns an error if the database operation fails. Batches committed
+/// before the failing one are kept.
 #[allow(clippy::module_name_repetitions)]
-#[instrument(skip_all, err(level = "error"))]
-pub fn upsert_programs(conn: &Connection, programs: &[CachedProgram]) -> Result<usize> {
-    let tx = conn
-        .unchecked_transaction()
-        .context("failed to begin transaction")?;
-
-    let mut stmt = tx
-        .prepare(
-            "INSERT INTO programs (
-                pid, tid, ch_id, tmdb_episode_id,
-                st_time, st_offset, ed_time, count,
-                sub_title, flag, deleted, warn,
-                revision, last_update, st_sub_title, duration_min
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
-                CAST(ROUND((julianday(?7) - julianday(?5)) * 24 * 60) AS INTEGER))
-            ON CONFLICT(pid) DO UPDATE SET
-                tid = excluded.tid,
-                ch_id = excluded.ch_id,
-                st_time = excluded.st_time,
-                st_offset = excluded.st_offset,
-                ed_time = excluded.ed_time,
-                count = excluded.count,
-                sub_title = excluded.sub_title,
-                flag = excluded.flag,
-                deleted = excluded.deleted,
-                warn = excluded.warn,
-                revision = excluded.revision,
-                last_update = excluded.last_update,
-                st_sub_title = excluded.st_sub_title,
-                duration_min = CAST(ROUND((julianday(excluded.ed_time) - julianday(excluded.st_time)) * 24 * 60) AS INTEGER)
-            WHERE programs.last_update IS NOT excluded.last_update",
-        )
-        .context("failed to prepare programs upsert")?;
+#[instrument(skip_all, fields(rows = programs.len(), batch_size), err(level = "error"))]
+pub fn upsert_programs_batched(
+    db: &impl DbHandle,
+    programs: &[CachedProgram],
+    batch_size: usize,
+) -> Result<usize> {
+    let conn = &*db.conn()?;
+    in_batches(conn, programs, batch_size, |tx, chunk| {
+        let mut stmt = tx
+            .prepare_cached(UPSERT_PROGRAM_SQL)
+            .context("failed to prepare programs upsert")?;
+        let mut changed: usize = 0;
+        for p in chunk {
+            let rows = stmt
+                .execute(rusqlite::params![
+                    p.pid,
+                    p.tid,
+                    p.ch_id,
+                    p.tmdb_episode_id,
+                    p.st_time,
+                    p.st_offset,
+                    p.ed_time,
+                    p.count,
+                    p.sub_title,
+                    p.flag,
+                    p.deleted,
+                    p.warn,
+                    p.revision,
+                    p.last_update,
+                    p.st_sub_title,
+                ])
+                .with_context(|| format!("failed to upsert program {}", p.pid))?;
+            changed = changed.saturating_add(rows);
+        }
+        Ok(changed)
+    })
+}
 
-    let mut changed: usize = 0;
-    for p in programs {
-        let rows = stmt
-            .execute(rusqlite::params![
-                p.pid,
-                p.tid,
-                p.ch_id,
-                p.tmdb_episode_id,
-                p.st_time,
-                p.st_offset,
-                p.ed_time,
-                p.count,
-                p.sub_title,
-                p.flag,
-                p.deleted,
-                p.warn,
-                p.revision,
-                p.last_update,
-                p.st_sub_title,
-            ])
-            .with_context(|| format!("failed to upsert program {}", p.pid))?;
-        changed = changed.saturating_add(rows);
-    }
+/// Upsert statement of [`upsert_programs_batched`].
+const UPSERT_PROGRAM_SQL: &str = "INSERT INTO programs (
+        pid, tid, ch_id, tmdb_episode_id,
+        st_time, st_offset, ed_time, count,
+        sub_title, flag, deleted, warn,
+        revision, last_update, st_sub_title, duration_min
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+        CAST(ROUND((julianday(?7) - julianday(?5)) * 24 * 60) AS INTEGER))
+    ON CONFLICT(pid) DO UPDATE SET
+        tid = excluded.tid,
+        ch_id = excluded.ch_id,
+        st_time = excluded.st_time,
+        st_offset = excluded.st_offset,
+        ed_time = excluded.ed_time,
+        count = excluded.count,
+        sub_title = excluded.sub_title,
+        flag = excluded.flag,
+        deleted = excluded.deleted,
+        warn = excluded.warn,
+        revision = excluded.revision,
+        last_update = excluded.last_update,
+        st_sub_title = excluded.st_sub_title,
+        duration_min = CAST(ROUND((julianday(excluded.ed_time) - julianday(excluded.st_time)) * 24 * 60) AS INTEGER)
+    WHERE programs.last_update IS NOT excluded.last_update";
 
-    drop(stmt);
-    tx.commit().context("failed to commit programs upsert")?;
-    Ok(changed)
-}
+/// Columns selected for [`map_program_row`]. `st_sub_title` falls back to
+/// the episode subtitle of the program's title and count.
+const PROGRAM_COLUMNS: &str = "pid, tid, ch_id, tmdb_episode_id,
+    st_time, st_offset, ed_time, count,
+    sub_title, flag, deleted, warn,
+    revision, last_update,
+    COALESCE(st_sub_title, (SELECT e.sub_title FROM episodes e
+                            WHERE e.tid = programs.tid AND e.count = programs.count)),
+    duration_min";
 
 /// Loads all programs from the cache, ordered by `st_time`.
 ///
@@ -122,16 +155,14 @@ pub fn upsert_programs(conn: &Connection, programs: &[CachedProgram]) -> Result<
 /// Returns an error if the database query fails.
 #[allow(clippy::module_name_repetitions)]
 #[instrument(skip_all, err(level = "error"))]
-pub fn load_programs(conn: &Connection) -> Result<Vec<CachedProgram>> {
+pub fn load_programs(db: &impl DbHandle) -> Result<Vec<CachedProgram>> {
+    let conn = &*db.conn()?;
     let mut stmt = conn
-        .prepare(
-            "SELECT pid, tid, ch_id, tmdb_episode_id,
-                    st_time, st_offset, ed_time, count,
-                    sub_title, flag, deleted, warn,
-                    revision, last_update, st_sub_title, duration_min
+        .prepare(&format!(
+            "SELECT {PROGRAM_COLUMNS}
              FROM programs
-             ORDER BY st_time",
-        )
+             ORDER BY st_time"
+        ))
         .context("failed to prepare programs query")?;
 
     let rows = stmt
@@ -142,6 +173,27 @@ pub fn load_programs(conn: &Connection) -> Result<Vec<CachedProgram>> {
         .context("failed to read programs rows")
 }
 
+/// Loads a single program by PID (`None` if it is not cached).
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_program(db: &impl DbHandle, pid: u32) -> Result<Option<CachedProgram>> {
+    let conn = &*db.conn()?;
+    conn.query_row(
+        &format!(
+            "SELECT {PROGRAM_COLUMNS}
+             FROM programs
+             WHERE pid = ?1"
+        ),
+        [pid],
+        map_program_row,
+    )
+    .optional()
+    .with_context(|| format!("failed to load program {pid}"))
+}
+
 /// Loads programs filtered by title IDs.
 ///
 /// # Errors
@@ -149,17 +201,15 @@ pub fn load_programs(conn: &Connection) -> Result<Vec<CachedProgram>> {
 /// Returns an error if the database query fails.
 #[allow(clippy::module_name_repetitions)]
 #[instrument(skip_all, err(level = "error"))]
-pub fn load_programs_by_tids(conn: &Connection, tids: &[u32]) -> Result<Vec<CachedProgram>> {
+pub fn load_programs_by_tids(db: &impl DbHandle, tids: &[u32]) -> Result<Vec<CachedProgram>> {
+    let conn = &*db.conn()?;
     if tids.is_empty() {
         return Ok(Vec::new());
     }
 
     let placeholders: Vec<String> = tids.iter().map(|_| String::from("?")).collect();
     let sql = format!(
-        "SELECT pid, tid, ch_id, tmdb_episode_id,
-                st_time, st_offset, ed_time, count,
-                sub_title, flag, deleted, warn,
-                revision, last_update, st_sub_title, duration_min
+        "SELECT {PROGRAM_COLUMNS}
          FROM programs
          WHERE tid IN ({})
          ORDER BY st_time",
@@ -184,6 +234,152 @@ pub fn load_programs_by_tids(conn: &Connection, tids: &[u32]) -> Result<Vec<Cach
         .context("failed to read programs rows")
 }
 
+/// Extra condition of a [`ProgramFilter`], such as a compiled `--filter`
+/// expression.
+///
+/// `sql` is a boolean expression over the `programs` columns whose `?`
+/// placeholders are bound to `params` in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SqlCondition {
+    /// Boolean SQL expression.
+    pub sql: String,
+    /// Values of the `?` placeholders in `sql`.
+    pub params: Vec<rusqlite::types::Value>,
+}
+
+/// Filter for [`iter_programs`], evaluated in SQL.
+///
+/// Unset fields do not filter. Times are compared against `st_time` in the
+/// stored `YYYY-MM-DD HH:MM:SS` format (both bounds inclusive).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramFilter<'a> {
+    /// Only programs of these titles.
+    pub tids: Option<&'a [u32]>,
+    /// Only programs on these channels.
+    pub ch_ids: Option<&'a [u32]>,
+    /// Only programs of titles in these `cat` codes (a missing `cat`
+    /// counts as `0`).
+    pub cats: Option<&'a [u32]>,
+    /// Only programs starting at or after this time.
+    pub since: Option<&'a str>,
+    /// Only programs starting at or before this time.
+    pub until: Option<&'a str>,
+    /// Skip programs marked deleted by Syoboi.
+    pub exclude_deleted: bool,
+    /// Additional condition that must also hold.
+    pub condition: Option<&'a SqlCondition>,
+}
+
+impl ProgramFilter<'_> {
+    /// Builds the `WHERE` clause and its parameters.
+    fn where_clause(&self) -> (String, Vec<Box<dyn rusqlite::types::ToSql + '_>>) {
+        let mut conditions: Vec<String> = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::types::ToSql + '_>> = Vec::new();
+        for (column, ids) in [("tid", self.tids), ("ch_id", self.ch_ids)] {
+            let Some(ids) = ids else { continue };
+            let placeholders: Vec<&str> = ids.iter().map(|_| "?").collect();
+            conditions.push(format!("{column} IN ({})", placeholders.join(", ")));
+            params.extend(
+                ids.iter()
+                    .map(|id| -> Box<dyn rusqlite::types::ToSql> { Box::new(*id) }),
+            );
+        }
+        if let Some(cats) = self.cats {
+            let placeholders: Vec<&str> = cats.iter().map(|_| "?").collect();
+            conditions.push(format!(
+                "tid IN (SELECT tid FROM titles WHERE COALESCE(cat, 0) IN ({}))",
+                placeholders.join(", ")
+            ));
+            params.extend(
+                cats.iter()
+                    .map(|cat| -> Box<dyn rusqlite::types::ToSql> { Box::new(*cat) }),
+            );
+        }
+        if let Some(since) = self.since {
+            conditions.push(String::from("st_time >= ?"));
+            params.push(Box::new(since));
+        }
+        if let Some(until) = self.until {
+            conditions.push(String::from("st_time <= ?"));
+            params.push(Box::new(until));
+        }
+        if self.exclude_deleted {
+            conditions.push(String::from("COALESCE(deleted, 0) != 1"));
+        }
+        if let Some(condition) = self.condition {
+            conditions.push(format!("({})", condition.sql));
+            params.extend(
+                condition
+                    .params
+                    .iter()
+                    .map(|value| -> Box<dyn rusqlite::types::ToSql> { Box::new(value) }),
+            );
+        }
+        if conditions.is_empty() {
+            return (String::new(), params);
+        }
+        (format!("WHERE {}", conditions.join(" AND ")), params)
+    }
+}
+
+/// Streams programs matching `filter` to `f` in `(st_time, pid)` order
+/// without collecting them. Returns the number of programs visited.
+///
+/// Stops at the first error returned by `f`.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails or `f` returns an error.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn iter_programs<F>(db: &impl DbHandle, filter: &ProgramFilter<'_>, mut f: F) -> Result<usize>
+where
+    F: FnMut(CachedProgram) -> Result<()>,
+{
+    let conn = &*db.conn()?;
+    let (where_clause, params) = filter.where_clause();
+    let sql = format!(
+        "SELECT {PROGRAM_COLUMNS}
+         FROM programs
+         {where_clause}
+         ORDER BY st_time, pid"
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .context("failed to prepare programs query")?;
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(AsRef::as_ref).collect();
+    let mut rows = stmt
+        .query(param_refs.as_slice())
+        .context("failed to query programs")?;
+
+    let mut visited: usize = 0;
+    while let Some(row) = rows.next().context("failed to read programs rows")? {
+        f(map_program_row(row).context("failed to read programs rows")?)?;
+        visited = visited.saturating_add(1);
+    }
+    Ok(visited)
+}
+
+/// Loads programs matching `filter` in `(st_time, pid)` order.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+pub fn load_programs_filtered(
+    db: &impl DbHandle,
+    filter: &ProgramFilter<'_>,
+) -> Result<Vec<CachedProgram>> {
+    let conn = &*db.conn()?;
+    let mut programs = Vec::new();
+    iter_programs(conn, filter, |p| {
+        programs.push(p);
+        Ok(())
+    })?;
+    Ok(programs)
+}
+
 /// Maps a database row to a `CachedProgram`.
 fn map_program_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CachedProgram> {
     Ok(CachedProgram {
@@ -212,7 +408,8 @@ fn map_program_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CachedProgram> {
 ///
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn delete_programs_by_tids_not_in(conn: &Connection, valid_tids: &[u32]) -> Result<usize> {
+pub fn delete_programs_by_tids_not_in(db: &impl DbHandle, valid_tids: &[u32]) -> Result<usize> {
+    let conn = &*db.conn()?;
     if valid_tids.is_empty() {
         let deleted = conn
             .execute("DELETE FROM programs", [])
@@ -238,11 +435,121 @@ pub fn delete_programs_by_tids_not_in(conn: &Connection, valid_tids: &[u32]) ->
     Ok(deleted)
 }
 
+/// Sets `tmdb_episode_id` for each `(pid, episode_id)` pair in a single
+/// transaction. Returns the number of rows changed.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn update_tmdb_episode_mapping(
+    db: &impl DbHandle,
+    mappings: &[(u32, Option<u64>)],
+) -> Result<usize> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    let mut changed: usize = 0;
+    {
+        let mut stmt = tx
+            .prepare(
+                "UPDATE programs SET tmdb_episode_id = ?1
+                 WHERE pid = ?2 AND tmdb_episode_id IS NOT ?1",
+            )
+            .context("failed to prepare episode mapping update")?;
+        for &(pid, episode_id) in mappings {
+            let n = stmt
+                .execute(rusqlite::params![episode_id, pid])
+                .with_context(|| format!("failed to update TMDB episode for pid {pid}"))?;
+            changed = changed.saturating_add(n);
+        }
+    }
+    tx.commit().context("failed to commit episode mappings")?;
+    Ok(changed)
+}
+
+/// Progress of a bulk recompute of derived program columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecomputeProgress {
+    /// Rows scanned so far.
+    pub scanned: usize,
+    /// Total rows to scan.
+    pub total: usize,
+    /// Rows whose derived columns changed so far.
+    pub updated: usize,
+}
+
+/// Recalculates derived program columns (`duration_min`) from the stored
+/// broadcast times.
+///
+/// Rows are processed in `pid` order, `batch_size` rows per transaction;
+/// `on_batch` is called after each committed batch. Returns the final progress.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn recompute_program_columns(
+    db: &impl DbHandle,
+    batch_size: usize,
+    mut on_batch: impl FnMut(&RecomputeProgress),
+) -> Result<RecomputeProgress> {
+    let conn = &*db.conn()?;
+    let batch_size = i64::try_from(batch_size.max(1)).unwrap_or(i64::MAX);
+    let total: usize = conn
+        .query_row("SELECT COUNT(*) FROM programs", [], |row| row.get(0))
+        .context("failed to count programs")?;
+    let mut progress = RecomputeProgress {
+        total,
+        ..RecomputeProgress::default()
+    };
+
+    let mut last_pid: i64 = -1;
+    loop {
+        let tx = conn
+            .unchecked_transaction()
+            .context("failed to begin transaction")?;
+        let (max_pid, count): (Option<i64>, usize) = tx
+            .query_row(
+                "SELECT MAX(pid), COUNT(*) FROM (
+                     SELECT pid FROM programs WHERE pid > ?1 ORDER BY pid LIMIT ?2
+                 )",
+                [last_pid, batch_size],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .context("failed to select program batch")?;
+        let Some(max_pid) = max_pid else {
+            break;
+        };
+
+        let updated = tx
+            .execute(
+                "UPDATE programs
+                 SET duration_min = CAST(ROUND((julianday(ed_time) - julianday(st_time)) * 24 * 60) AS INTEGER)
+                 WHERE pid > ?1 AND pid <= ?2
+                   AND duration_min IS NOT CAST(ROUND((julianday(ed_time) - julianday(st_time)) * 24 * 60) AS INTEGER)",
+                [last_pid, max_pid],
+            )
+            .with_context(|| format!("failed to recompute programs up to pid {max_pid}"))?;
+        tx.commit().context("failed to commit recompute batch")?;
+
+        last_pid = max_pid;
+        progress.scanned = progress.scanned.saturating_add(count);
+        progress.updated = progress.updated.saturating_add(updated);
+        on_batch(&progress);
+    }
+
+    Ok(progress)
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
     #![allow(clippy::indexing_slicing)]
 
+    use rusqlite::Connection;
+
     use super::*;
     use crate::connection::open_db;
     use crate::titles::{CachedTitle, upsert_titles};
@@ -268,6 +575,8 @@ mod tests {
             tmdb_series_id: None,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: String::from("Test Title"),
             short_title: None,
             title_yomi: None,
@@ -277,12 +586,14 @@ mod tests {
             first_year: None,
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::from("2024-01-01 00:00:00"),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         }];
         upsert_titles(&conn, &titles).unwrap();
 
@@ -338,6 +649,138 @@ mod tests {
         assert_eq!(loaded[1].pid, 2);
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_iter_programs_applies_filter_in_sql() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        conn.execute(
+            "INSERT INTO channels (ch_id, ch_gid, ch_name) VALUES (2, 1, 'OtherCh')",
+            [],
+        )
+        .unwrap();
+        let mut other_channel = make_program(3, "2024-01-02 00:00:00");
+        other_channel.ch_id = 2;
+        let mut deleted = make_program(4, "2024-01-02 01:00:00");
+        deleted.deleted = Some(1);
+        upsert_programs(
+            &conn,
+            &[
+                make_program(2, "2024-01-02 02:00:00"),
+                make_program(1, "2024-01-01 00:00:00"),
+                other_channel,
+                deleted,
+            ],
+        )
+        .unwrap();
+        let filter = ProgramFilter {
+            ch_ids: Some(&[1]),
+            since: Some("2024-01-02 00:00:00"),
+            exclude_deleted: true,
+            ..ProgramFilter::default()
+        };
+
+        // Act
+        let mut pids = Vec::new();
+        let visited = iter_programs(&conn, &filter, |p| {
+            pids.push(p.pid);
+            Ok(())
+        })
+        .unwrap();
+        let all = load_programs_filtered(&conn, &ProgramFilter::default()).unwrap();
+
+        // Assert
+        assert_eq!(visited, 1);
+        assert_eq!(pids, vec![2]);
+        let all_pids: Vec<u32> = all.iter().map(|p| p.pid).collect();
+        assert_eq!(all_pids, vec![1, 3, 4, 2]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_load_programs_filtered_by_title_category() {
+        // Arrange: the setup title has no `cat`, which counts as `0`
+        let (conn, _dir) = setup_db();
+        upsert_programs(&conn, &[make_program(1, "2024-01-01 00:00:00")]).unwrap();
+
+        // Act
+        let other = load_programs_filtered(
+            &conn,
+            &ProgramFilter {
+                cats: Some(&[0, 7]),
+                ..ProgramFilter::default()
+            },
+        )
+        .unwrap();
+        let anime = load_programs_filtered(
+            &conn,
+            &ProgramFilter {
+                cats: Some(&[1]),
+                ..ProgramFilter::default()
+            },
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(other.len(), 1);
+        assert!(anime.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_load_programs_filtered_with_sql_condition() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let mut second = make_program(2, "2024-01-01 01:00:00");
+        second.count = Some(2);
+        upsert_programs(&conn, &[make_program(1, "2024-01-01 00:00:00"), second]).unwrap();
+        let condition = SqlCondition {
+            sql: String::from("count >= ? OR pid = ?"),
+            params: vec![2_i64.into(), 9_i64.into()],
+        };
+
+        // Act
+        let loaded = load_programs_filtered(
+            &conn,
+            &ProgramFilter {
+                tids: Some(&[100]),
+                condition: Some(&condition),
+                ..ProgramFilter::default()
+            },
+        )
+        .unwrap();
+
+        // Assert: the condition is parenthesized before the AND
+        let pids: Vec<u32> = loaded.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![2]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_iter_programs_stops_on_callback_error() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_programs(
+            &conn,
+            &[
+                make_program(1, "2024-01-01 00:00:00"),
+                make_program(2, "2024-01-01 01:00:00"),
+            ],
+        )
+        .unwrap();
+
+        // Act
+        let mut seen = 0;
+        let result = iter_programs(&conn, &ProgramFilter::default(), |_| {
+            seen += 1;
+            anyhow::bail!("stop")
+        });
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(seen, 1);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_upsert_programs_updates_existing() {
@@ -359,6 +802,29 @@ mod tests {
         assert_eq!(loaded[0].sub_title.as_deref(), Some("Updated Episode"));
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_programs_batched_keeps_earlier_batches_on_error() {
+        // Arrange: the fourth program references a missing channel
+        let (conn, _dir) = setup_db();
+        let mut programs: Vec<CachedProgram> = (1..=4)
+            .map(|pid| make_program(pid, &format!("2024-01-01 0{pid}:00:00")))
+            .collect();
+        programs[3].ch_id = 99;
+
+        // Act
+        let all_at_once = upsert_programs_batched(&conn, &programs, 0);
+        let batched = upsert_programs_batched(&conn, &programs, 2);
+        let loaded = load_programs(&conn).unwrap();
+
+        // Assert: one transaction rolls back everything; batches of two
+        // keep the first batch
+        assert!(all_at_once.is_err());
+        assert!(batched.is_err());
+        let pids: Vec<u32> = loaded.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1, 2]);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_upsert_programs_skips_when_last_update_unchanged() {
@@ -396,6 +862,29 @@ mod tests {
         assert_eq!(loaded[0].tmdb_episode_id, Some(99999));
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_update_tmdb_episode_mapping() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let programs = vec![
+            make_program(1, "2024-01-01 00:00:00"),
+            make_program(2, "2024-01-01 01:00:00"),
+        ];
+        upsert_programs(&conn, &programs).unwrap();
+
+        // Act: the second call repeats one unchanged mapping and clears another
+        let first = update_tmdb_episode_mapping(&conn, &[(1, Some(501)), (2, Some(502))]).unwrap();
+        let second = update_tmdb_episode_mapping(&conn, &[(1, Some(501)), (2, None)]).unwrap();
+        let loaded = load_programs(&conn).unwrap();
+
+        // Assert
+        assert_eq!(first, 2);
+        assert_eq!(second, 1);
+        assert_eq!(loaded[0].tmdb_episode_id, Some(501));
+        assert_eq!(loaded[1].tmdb_episode_id, None);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_load_programs_by_tids() {
@@ -408,6 +897,8 @@ mod tests {
             tmdb_series_id: None,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: String::from("Title 2"),
             short_title: None,
             title_yomi: None,
@@ -417,12 +908,14 @@ mod tests {
             first_year: None,
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::from("2024-01-01 00:00:00"),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         };
         upsert_titles(&conn, &[title2]).unwrap();
 
@@ -442,6 +935,22 @@ mod tests {
         assert!(loaded.iter().all(|p| p.tid == 100));
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_load_program_by_pid() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_programs(&conn, &[make_program(1, "2024-01-01 00:00:00")]).unwrap();
+
+        // Act
+        let found = load_program(&conn, 1).unwrap();
+        let missing = load_program(&conn, 2).unwrap();
+
+        // Assert
+        assert_eq!(found.map(|p| p.pid), Some(1));
+        assert!(missing.is_none());
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_load_programs_by_tids_empty() {
@@ -504,6 +1013,8 @@ mod tests {
             tmdb_series_id: None,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: String::from("Title 2"),
             short_title: None,
             title_yomi: None,
@@ -513,12 +1024,14 @@ mod tests {
             first_year: None,
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::from("2024-01-01 00:00:00"),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         };
         upsert_titles(&conn, &[title2]).unwrap();
 
@@ -556,4 +1069,50 @@ mod tests {
         assert_eq!(deleted, 1);
         assert!(remaining.is_empty());
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_recompute_program_columns_fixes_stale_rows_in_batches() {
+        // Arrange: three programs, two with stale duration_min
+        let (conn, _dir) = setup_db();
+        let progs = vec![
+            make_program(1, "2024-01-01 00:00:00"),
+            make_program(2, "2024-01-01 01:00:00"),
+            make_program(3, "2024-01-01 02:00:00"),
+        ];
+        upsert_programs(&conn, &progs).unwrap();
+        conn.execute("UPDATE programs SET duration_min = NULL WHERE pid = 1", [])
+            .unwrap();
+        conn.execute("UPDATE programs SET duration_min = 99 WHERE pid = 3", [])
+            .unwrap();
+        let mut batches = Vec::new();
+
+        // Act
+        let result = recompute_program_columns(&conn, 2, |p| batches.push(*p)).unwrap();
+
+        // Assert
+        assert_eq!(result.scanned, 3);
+        assert_eq!(result.total, 3);
+        assert_eq!(result.updated, 2);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].scanned, 2);
+        assert_eq!(batches[0].updated, 1);
+        let loaded = load_programs(&conn).unwrap();
+        assert!(loaded.iter().all(|p| p.duration_min == Some(30)));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_recompute_program_columns_empty_table() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let mut calls = 0_u32;
+
+        // Act
+        let result = recompute_program_columns(&conn, 100, |_| calls += 1).unwrap();
+
+        // Assert
+        assert_eq!(result, RecomputeProgress::default());
+        assert_eq!(calls, 0);
+    }
 }
@@ -4,6 +4,8 @@ use anyhow::{Context, Result};
 use rusqlite::Connection;
 use tracing::instrument;
 
+use crate::pool::DbHandle;
+
 /// A cached `EPGStation` recorded item.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CachedRecordedItem {
@@ -69,10 +71,11 @@ pub struct CachedVideoFile {
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
 pub fn upsert_recorded_items(
-    conn: &Connection,
+    db: &impl DbHandle,
     items: &[CachedRecordedItem],
     video_files: &[(i64, Vec<CachedVideoFile>)],
 ) -> Result<usize> {
+    let conn = &*db.conn()?;
     let tx = conn
         .unchecked_transaction()
         .context("failed to begin transaction")?;
@@ -211,10 +214,11 @@ fn delete_stale_video_files(
 #[allow(clippy::type_complexity)]
 #[instrument(skip_all, err(level = "error"))]
 pub fn load_recorded_items_page(
-    conn: &Connection,
+    db: &impl DbHandle,
     offset: i64,
     limit: i64,
 ) -> Result<(Vec<(CachedRecordedItem, Vec<CachedVideoFile>)>, u64)> {
+    let conn = &*db.conn()?;
     // Get total count
     let total: i64 = conn
         .query_row("SELECT COUNT(*) FROM epg_recorded_items", [], |row| {
@@ -262,8 +266,9 @@ pub fn load_recorded_items_page(
 /// Returns an error if the database query fails.
 #[instrument(skip_all, err(level = "error"))]
 pub fn load_recorded_items(
-    conn: &Connection,
+    db: &impl DbHandle,
 ) -> Result<Vec<(CachedRecordedItem, Vec<CachedVideoFile>)>> {
+    let conn = &*db.conn()?;
     let (items, _total) = load_recorded_items_page(conn, 0, i64::MAX)?;
     Ok(items)
 }
@@ -274,7 +279,8 @@ pub fn load_recorded_items(
 ///
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn delete_recorded_items_not_in(conn: &Connection, ids: &[i64]) -> Result<usize> {
+pub fn delete_recorded_items_not_in(db: &impl DbHandle, ids: &[i64]) -> Result<usize> {
+    let conn = &*db.conn()?;
     if ids.is_empty() {
         let deleted = conn
             .execute("DELETE FROM epg_recorded_items", [])
@@ -317,7 +323,8 @@ pub fn delete_recorded_items_not_in(conn: &Connection, ids: &[i64]) -> Result<us
 ///
 /// Returns an error if the database query fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn newest_start_at(conn: &Connection) -> Result<Option<i64>> {
+pub fn newest_start_at(db: &impl DbHandle) -> Result<Option<i64>> {
+    let conn = &*db.conn()?;
     conn.query_row("SELECT MAX(start_at) FROM epg_recorded_items", [], |row| {
         row.get(0)
     })
@@ -331,11 +338,12 @@ pub fn newest_start_at(conn: &Connection) -> Result<Option<i64>> {
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
 pub fn update_file_exists(
-    conn: &Connection,
+    db: &impl DbHandle,
     video_file_id: i64,
     exists: bool,
     checked_at: &str,
 ) -> Result<()> {
+    let conn = &*db.conn()?;
     conn.execute(
         "UPDATE epg_video_files SET file_exists = ?1, file_checked_at = ?2 WHERE id = ?3",
         rusqlite::params![exists, checked_at, video_file_id],
@@ -350,7 +358,8 @@ pub fn update_file_exists(
 ///
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn invalidate_file_exists(conn: &Connection, recorded_id: i64) -> Result<()> {
+pub fn invalidate_file_exists(db: &impl DbHandle, recorded_id: i64) -> Result<()> {
+    let conn = &*db.conn()?;
     conn.execute(
         "UPDATE epg_video_files SET file_exists = NULL, file_checked_at = NULL WHERE recorded_id = ?1",
         rusqlite::params![recorded_id],
@@ -359,6 +368,53 @@ pub fn invalidate_file_exists(conn: &Connection, recorded_id: i64) -> Result<()>
     Ok(())
 }
 
+/// Loads stored SHA-256 hashes keyed by video file ID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_video_file_hashes(
+    db: &impl DbHandle,
+) -> Result<std::collections::HashMap<i64, String>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare("SELECT video_file_id, sha256 FROM video_file_hashes")
+        .context("failed to prepare video file hashes query")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .context("failed to query video file hashes")?;
+    rows.collect::<std::result::Result<_, _>>()
+        .context("failed to read video file hash rows")
+}
+
+/// Stores the SHA-256 hash of a video file, replacing any previous value.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn upsert_video_file_hash(
+    db: &impl DbHandle,
+    video_file_id: i64,
+    sha256: &str,
+    size: i64,
+    hashed_at: &str,
+) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO video_file_hashes (video_file_id, sha256, size, hashed_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(video_file_id) DO UPDATE SET
+             sha256 = excluded.sha256,
+             size = excluded.size,
+             hashed_at = excluded.hashed_at",
+        rusqlite::params![video_file_id, sha256, size, hashed_at],
+    )
+    .with_context(|| format!("failed to store hash for video file {video_file_id}"))?;
+    Ok(())
+}
+
 /// Batch-loads video files for a set of recorded item IDs.
 ///
 /// Returns a map from `recorded_id` to the list of video files.
@@ -651,6 +707,27 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_video_file_hash_replaces_and_cascades() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let items = vec![make_item(1, 1_000_000)];
+        let vf = vec![(1, vec![make_video_file(10, 1, "ts")])];
+        upsert_recorded_items(&conn, &items, &vf).unwrap();
+
+        // Act
+        upsert_video_file_hash(&conn, 10, "aaa", 100, "2024-01-01T00:00:00Z").unwrap();
+        upsert_video_file_hash(&conn, 10, "bbb", 100, "2024-01-02T00:00:00Z").unwrap();
+        let hashes = load_video_file_hashes(&conn).unwrap();
+        delete_recorded_items_not_in(&conn, &[]).unwrap();
+        let after_delete = load_video_file_hashes(&conn).unwrap();
+
+        // Assert
+        assert_eq!(hashes.get(&10).map(String::as_str), Some("bbb"));
+        assert!(after_delete.is_empty());
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_invalidate_file_exists() {
@@ -0,0 +1,137 @@
+//! Mapping of Syoboi episode count ranges to TMDB seasons.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Episode counts `first_count..=last_count` of a title belong to TMDB
+/// season `season_number`. The count within the season is
+/// `count - first_count + 1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SeasonRange {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// First episode count of the range.
+    pub first_count: u32,
+    /// Last episode count of the range (`None` = open-ended).
+    pub last_count: Option<u32>,
+    /// TMDB season number.
+    pub season_number: u32,
+}
+
+impl SeasonRange {
+    /// Returns whether `count` falls into this range.
+    #[must_use]
+    pub fn contains(&self, count: u32) -> bool {
+        count >= self.first_count && self.last_count.is_none_or(|last| count <= last)
+    }
+}
+
+/// Replaces all season ranges of `tid` with `ranges` in one transaction.
+/// An empty slice clears the title's ranges.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn replace_season_ranges(db: &impl DbHandle, tid: u32, ranges: &[SeasonRange]) -> Result<()> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    tx.execute("DELETE FROM title_season_ranges WHERE tid = ?1", [tid])
+        .with_context(|| format!("failed to clear season ranges of title {tid}"))?;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO title_season_ranges (tid, first_count, last_count, season_number)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .context("failed to prepare season range insert")?;
+        for range in ranges {
+            stmt.execute(rusqlite::params![
+                tid,
+                range.first_count,
+                range.last_count,
+                range.season_number,
+            ])
+            .with_context(|| format!("failed to insert season range of title {tid}"))?;
+        }
+    }
+    tx.commit().context("failed to commit season ranges")?;
+    Ok(())
+}
+
+/// Loads all season ranges ordered by TID and first count.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_season_ranges(db: &impl DbHandle) -> Result<Vec<SeasonRange>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT tid, first_count, last_count, season_number
+             FROM title_season_ranges
+             ORDER BY tid, first_count",
+        )
+        .context("failed to prepare season ranges query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SeasonRange {
+                tid: row.get(0)?,
+                first_count: row.get(1)?,
+                last_count: row.get(2)?,
+                season_number: row.get(3)?,
+            })
+        })
+        .context("failed to query season ranges")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read season range rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    const fn range(first_count: u32, last_count: Option<u32>, season_number: u32) -> SeasonRange {
+        SeasonRange {
+            tid: 6309,
+            first_count,
+            last_count,
+            season_number,
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_replace_season_ranges_overwrites_previous_ranges() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch("INSERT INTO titles (tid, title, last_update) VALUES (6309, 'Test', '2024-01-01 00:00:00');")
+            .unwrap();
+        replace_season_ranges(&conn, 6309, &[range(1, None, 1)]).unwrap();
+
+        // Act
+        replace_season_ranges(&conn, 6309, &[range(13, None, 2), range(1, Some(12), 1)]).unwrap();
+        let loaded = load_season_ranges(&conn).unwrap();
+
+        // Assert
+        assert_eq!(loaded, vec![range(1, Some(12), 1), range(13, None, 2)]);
+        assert!(loaded[0].contains(12));
+        assert!(!loaded[0].contains(13));
+        assert!(loaded[1].contains(100));
+
+        replace_season_ranges(&conn, 6309, &[]).unwrap();
+        assert!(load_season_ranges(&conn).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Import of a published seed database (channels and title catalogue).
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use rusqlite::Connection;
+use tracing::instrument;
+
+use super::migrations::{CURRENT_VERSION, run_migrations};
+
+/// Tables copied from a seed database, in foreign-key order.
+const SEED_TABLES: [&str; 3] = ["channel_groups", "channels", "titles"];
+
+/// Rows added by a seed import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SeedImport {
+    /// Schema version of the seed before migration.
+    pub seed_version: u32,
+    /// Channel groups inserted.
+    pub channel_groups: usize,
+    /// Channels inserted.
+    pub channels: usize,
+    /// Titles inserted.
+    pub titles: usize,
+    /// Newest incremental sync cursor (`sync_state.last_update`) of the
+    /// seed, where a catch-up sync can start; `None` when the seed has none.
+    pub cursor: Option<String>,
+}
+
+/// Imports channel groups, channels, and titles from the seed database at
+/// `seed_path` into `conn`. Existing rows are kept as they are.
+///
+/// The seed must be a dtvmgr database (`user_version` > 0) no newer than this
+/// build's schema; older seeds are migrated in place first, so `seed_path`
+/// should be a scratch copy.
+///
+/// # Errors
+///
+/// Returns an error if the seed cannot be opened, its schema version is not
+/// supported, or the copy fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn import_seed(conn: &Connection, seed_path: &Path) -> Result<SeedImport> {
+    let seed_version = {
+        let seed = Connection::open(seed_path)
+            .with_context(|| format!("failed to open seed database {}", seed_path.display()))?;
+        let version: u32 = seed
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .context("failed to read seed schema version")?;
+        if version == 0 {
+            bail!("seed is not a dtvmgr database (schema version 0)");
+        }
+        if version > CURRENT_VERSION {
+            bail!(
+                "seed schema version {version} is newer than supported version {CURRENT_VERSION}; upgrade dtvmgr"
+            );
+        }
+        run_migrations(&seed).context("failed to migrate seed database")?;
+        version
+    };
+
+    let path = seed_path.to_str().context("seed path is not valid UTF-8")?;
+    conn.execute("ATTACH DATABASE ?1 AS seed", [path])
+        .context("failed to attach seed database")?;
+    let copied = copy_seed_tables(conn).and_then(|copied| {
+        let cursor = conn
+            .query_row("SELECT MAX(last_update) FROM seed.sync_state", [], |row| {
+                row.get(0)
+            })
+            .context("failed to read seed sync cursor")?;
+        Ok((copied, cursor))
+    });
+    conn.execute_batch("DETACH DATABASE seed")
+        .context("failed to detach seed database")?;
+    let ([channel_groups, channels, titles], cursor) = copied?;
+
+    Ok(SeedImport {
+        seed_version,
+        channel_groups,
+        channels,
+        titles,
+        cursor,
+    })
+}
+
+/// Copies the seed tables in one transaction. Returns rows inserted per table.
+///
+/// Columns are listed explicitly: `SELECT *` would include generated
+/// columns, which cannot be inserted.
+fn copy_seed_tables(conn: &Connection) -> Result<[usize; 3]> {
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    let mut inserted = [0; SEED_TABLES.len()];
+    for (n, table) in inserted.iter_mut().zip(SEED_TABLES) {
+        let columns: Vec<String> = tx
+            .prepare(&format!(
+                "SELECT name FROM pragma_table_info('{table}', 'main')"
+            ))
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()
+            })
+            .with_context(|| format!("failed to list columns of {table}"))?;
+        let columns = columns.join(", ");
+        *n = tx
+            .execute(
+                &format!(
+                    "INSERT OR IGNORE INTO main.{table} ({columns}) SELECT {columns} FROM seed.{table}"
+                ),
+                [],
+            )
+            .with_context(|| format!("failed to copy seed table {table}"))?;
+    }
+    tx.commit().context("failed to commit seed import")?;
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_import_seed_keeps_existing_rows() {
+        // Arrange: a seed with one group, two channels, and two titles
+        let seed_dir = tempfile::tempdir().unwrap();
+        let seed = open_db(Some(&seed_dir.path().to_path_buf())).unwrap();
+        seed.execute_batch(
+            "INSERT INTO channel_groups VALUES (1, '地上波', 1);
+             INSERT INTO channels (ch_id, ch_gid, ch_name) VALUES (1, 1, 'NHK総合'), (7, 1, 'テレビ東京');
+             INSERT INTO titles (tid, title, last_update) VALUES
+                 (6309, 'SPY×FAMILY', '2022-01-01 00:00:00'),
+                 (6310, 'Seed title', '2022-01-01 00:00:00');
+             INSERT INTO sync_state (scope, last_update) VALUES
+                 ('programs:ch=1', '2024-03-01 00:00:00'),
+                 ('programs:ch=7', '2024-04-01 00:00:00');",
+        )
+        .unwrap();
+        drop(seed);
+        let local_dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&local_dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO titles (tid, title, last_update) VALUES (6310, 'Local title', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+
+        // Act
+        let result = import_seed(&conn, &seed_dir.path().join("dtvmgr.db")).unwrap();
+
+        // Assert
+        assert_eq!(result.seed_version, CURRENT_VERSION);
+        assert_eq!(result.channel_groups, 1);
+        assert_eq!(result.channels, 2);
+        assert_eq!(result.titles, 1);
+        assert_eq!(result.cursor.as_deref(), Some("2024-04-01 00:00:00"));
+        let local: String = conn
+            .query_row("SELECT title FROM titles WHERE tid = 6310", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(local, "Local title");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_import_seed_without_sync_state_has_no_cursor() {
+        // Arrange
+        let seed_dir = tempfile::tempdir().unwrap();
+        drop(open_db(Some(&seed_dir.path().to_path_buf())).unwrap());
+        let local_dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&local_dir.path().to_path_buf())).unwrap();
+
+        // Act
+        let result = import_seed(&conn, &seed_dir.path().join("dtvmgr.db")).unwrap();
+
+        // Assert
+        assert_eq!(result.cursor, None);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_import_seed_rejects_unknown_schema_versions() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let empty = dir.path().join("empty.db");
+        Connection::open(&empty).unwrap();
+        let newer = dir.path().join("newer.db");
+        Connection::open(&newer)
+            .unwrap()
+            .pragma_update(None, "user_version", CURRENT_VERSION.saturating_add(1))
+            .unwrap();
+
+        // Act
+        let empty_err = import_seed(&conn, &empty).unwrap_err();
+        let newer_err = import_seed(&conn, &newer).unwrap_err();
+
+        // Assert
+        assert!(empty_err.to_string().contains("not a dtvmgr database"));
+        assert!(newer_err.to_string().contains("newer than supported"));
+    }
+}
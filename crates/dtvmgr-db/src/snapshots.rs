@@ -0,0 +1,327 @@
+//! Named copies of the program schedule and diffs between them.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// A stored schedule snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SnapshotInfo {
+    /// Snapshot name.
+    pub name: String,
+    /// When the snapshot was taken.
+    pub created_at: String,
+    /// Number of programs copied.
+    pub program_count: u32,
+}
+
+/// How a program differs between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgramChangeKind {
+    /// Only in the newer snapshot.
+    Added,
+    /// Only in the older snapshot.
+    Removed,
+    /// In both, with a different start or end time.
+    TimeShifted,
+}
+
+/// A program added, removed, or moved between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProgramChange {
+    /// Kind of change.
+    pub kind: ProgramChangeKind,
+    /// Syoboi program ID.
+    pub pid: u32,
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name (empty if the title is no longer cached).
+    pub title: String,
+    /// Channel ID.
+    pub ch_id: u32,
+    /// Episode number.
+    pub count: Option<u32>,
+    /// Start time in the older snapshot (`None` for added programs).
+    pub old_st_time: Option<String>,
+    /// Start time in the newer snapshot (`None` for removed programs).
+    pub new_st_time: Option<String>,
+    /// End time in the older snapshot.
+    pub old_ed_time: Option<String>,
+    /// End time in the newer snapshot.
+    pub new_ed_time: Option<String>,
+}
+
+/// Copies the cached programs into a new snapshot `name`.
+///
+/// # Errors
+///
+/// Returns an error if a snapshot `name` already exists or the copy fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn create_snapshot(db: &impl DbHandle, name: &str, created_at: &str) -> Result<SnapshotInfo> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    let inserted = tx
+        .execute(
+            "INSERT INTO snapshots (name, created_at) VALUES (?1, ?2)
+             ON CONFLICT(name) DO NOTHING",
+            rusqlite::params![name, created_at],
+        )
+        .with_context(|| format!("failed to create snapshot {name}"))?;
+    if inserted == 0 {
+        bail!("snapshot {name} already exists");
+    }
+    let copied = tx
+        .execute(
+            "INSERT INTO snapshot_programs
+                 (snapshot, pid, tid, ch_id, st_time, ed_time, count, st_sub_title)
+             SELECT ?1, pid, tid, ch_id, st_time, ed_time, count, st_sub_title
+             FROM programs",
+            [name],
+        )
+        .with_context(|| format!("failed to copy programs into snapshot {name}"))?;
+    tx.commit().context("failed to commit snapshot")?;
+    Ok(SnapshotInfo {
+        name: name.to_owned(),
+        created_at: created_at.to_owned(),
+        program_count: u32::try_from(copied).unwrap_or(u32::MAX),
+    })
+}
+
+/// Loads all snapshots, oldest first.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_snapshots(db: &impl DbHandle) -> Result<Vec<SnapshotInfo>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.name, s.created_at,
+                    (SELECT COUNT(*) FROM snapshot_programs p WHERE p.snapshot = s.name)
+             FROM snapshots s
+             ORDER BY s.created_at, s.name",
+        )
+        .context("failed to prepare snapshots query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SnapshotInfo {
+                name: row.get(0)?,
+                created_at: row.get(1)?,
+                program_count: row.get(2)?,
+            })
+        })
+        .context("failed to query snapshots")?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to read snapshot row")
+}
+
+/// Deletes snapshot `name` and its programs. Returns `false` if it did not
+/// exist.
+///
+/// # Errors
+///
+/// Returns an error if the delete fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn delete_snapshot(db: &impl DbHandle, name: &str) -> Result<bool> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    tx.execute("DELETE FROM snapshot_programs WHERE snapshot = ?1", [name])
+        .with_context(|| format!("failed to delete programs of snapshot {name}"))?;
+    let deleted = tx
+        .execute("DELETE FROM snapshots WHERE name = ?1", [name])
+        .with_context(|| format!("failed to delete snapshot {name}"))?;
+    tx.commit().context("failed to commit snapshot deletion")?;
+    Ok(deleted > 0)
+}
+
+/// Compares snapshot `old` with snapshot `new`.
+///
+/// Reports programs only in `new` (added), only in `old` (removed), and in
+/// both with a different start or end time (time-shifted), ordered by start
+/// time. With `followed_only`, only programs of titles currently followed
+/// are included.
+///
+/// # Errors
+///
+/// Returns an error if either snapshot does not exist or the query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn diff_snapshots(
+    db: &impl DbHandle,
+    old: &str,
+    new: &str,
+    followed_only: bool,
+) -> Result<Vec<ProgramChange>> {
+    let conn = &*db.conn()?;
+    for name in [old, new] {
+        let exists: bool = conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM snapshots WHERE name = ?1)",
+                [name],
+                |row| row.get(0),
+            )
+            .context("failed to look up snapshot")?;
+        if !exists {
+            bail!("unknown snapshot {name}");
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "WITH changes AS (
+                 SELECT 'added' AS kind, n.pid, n.tid, n.ch_id, n.count,
+                        NULL AS old_st, n.st_time AS new_st, NULL AS old_ed, n.ed_time AS new_ed
+                 FROM snapshot_programs n
+                 WHERE n.snapshot = ?2
+                   AND NOT EXISTS (SELECT 1 FROM snapshot_programs o
+                                   WHERE o.snapshot = ?1 AND o.pid = n.pid)
+                 UNION ALL
+                 SELECT 'removed', o.pid, o.tid, o.ch_id, o.count,
+                        o.st_time, NULL, o.ed_time, NULL
+                 FROM snapshot_programs o
+                 WHERE o.snapshot = ?1
+                   AND NOT EXISTS (SELECT 1 FROM snapshot_programs n
+                                   WHERE n.snapshot = ?2 AND n.pid = o.pid)
+                 UNION ALL
+                 SELECT 'time_shifted', n.pid, n.tid, n.ch_id, n.count,
+                        o.st_time, n.st_time, o.ed_time, n.ed_time
+                 FROM snapshot_programs o
+                 JOIN snapshot_programs n ON n.snapshot = ?2 AND n.pid = o.pid
+                 WHERE o.snapshot = ?1
+                   AND (o.st_time <> n.st_time OR o.ed_time <> n.ed_time)
+             )
+             SELECT c.kind, c.pid, c.tid, COALESCE(t.title, ''), c.ch_id, c.count,
+                    c.old_st, c.new_st, c.old_ed, c.new_ed
+             FROM changes c
+             LEFT JOIN titles t ON t.tid = c.tid
+             WHERE ?3 = 0 OR t.followed = 1
+             ORDER BY COALESCE(c.new_st, c.old_st), c.pid",
+        )
+        .context("failed to prepare snapshot diff query")?;
+    let rows = stmt
+        .query_map(rusqlite::params![old, new, followed_only], |row| {
+            let kind = match row.get_ref(0)?.as_str()? {
+                "added" => ProgramChangeKind::Added,
+                "removed" => ProgramChangeKind::Removed,
+                _ => ProgramChangeKind::TimeShifted,
+            };
+            Ok(ProgramChange {
+                kind,
+                pid: row.get(1)?,
+                tid: row.get(2)?,
+                title: row.get(3)?,
+                ch_id: row.get(4)?,
+                count: row.get(5)?,
+                old_st_time: row.get(6)?,
+                new_st_time: row.get(7)?,
+                old_ed_time: row.get(8)?,
+                new_ed_time: row.get(9)?,
+            })
+        })
+        .context("failed to diff snapshots")?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to read snapshot diff row")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use rusqlite::Connection;
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn seeded_db(dir: &tempfile::TempDir) -> Connection {
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京');
+             INSERT INTO titles (tid, title, last_update, followed)
+                 VALUES (6309, 'Followed', '2024-01-01 00:00:00', 1),
+                        (7000, 'Other', '2024-01-01 00:00:00', 0);
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count) VALUES
+                 (100, 6309, 7, '2024-01-01 23:00:00', '2024-01-01 23:30:00', 1),
+                 (101, 6309, 7, '2024-01-08 23:00:00', '2024-01-08 23:30:00', 2),
+                 (200, 7000, 7, '2024-01-02 01:00:00', '2024-01-02 01:30:00', 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_create_list_and_delete_snapshots() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+
+        // Act
+        let created = create_snapshot(&conn, "w1", "2024-01-01T00:00:00Z").unwrap();
+        let duplicate = create_snapshot(&conn, "w1", "2024-01-02T00:00:00Z");
+        let listed = load_snapshots(&conn).unwrap();
+
+        // Assert
+        assert_eq!(created.program_count, 3);
+        assert!(duplicate.is_err());
+        assert_eq!(listed, vec![created]);
+        assert!(delete_snapshot(&conn, "w1").unwrap());
+        assert!(!delete_snapshot(&conn, "w1").unwrap());
+        assert!(load_snapshots(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_diff_snapshots_reports_added_removed_and_shifted() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+        create_snapshot(&conn, "w1", "2024-01-01T00:00:00Z").unwrap();
+        conn.execute_batch(
+            "DELETE FROM programs WHERE pid = 100;
+             UPDATE programs SET st_time = '2024-01-08 23:30:00', ed_time = '2024-01-09 00:00:00'
+                 WHERE pid = 101;
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count)
+                 VALUES (102, 6309, 7, '2024-01-15 23:00:00', '2024-01-15 23:30:00', 3);
+             DELETE FROM programs WHERE pid = 200;",
+        )
+        .unwrap();
+        create_snapshot(&conn, "w2", "2024-01-08T00:00:00Z").unwrap();
+
+        // Act
+        let followed = diff_snapshots(&conn, "w1", "w2", true).unwrap();
+        let all = diff_snapshots(&conn, "w1", "w2", false).unwrap();
+
+        // Assert
+        let kinds: Vec<(ProgramChangeKind, u32)> =
+            followed.iter().map(|c| (c.kind, c.pid)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ProgramChangeKind::Removed, 100),
+                (ProgramChangeKind::TimeShifted, 101),
+                (ProgramChangeKind::Added, 102),
+            ]
+        );
+        assert_eq!(
+            followed[1].old_st_time.as_deref(),
+            Some("2024-01-08 23:00:00")
+        );
+        assert_eq!(
+            followed[1].new_st_time.as_deref(),
+            Some("2024-01-08 23:30:00")
+        );
+        assert_eq!(followed[0].title, "Followed");
+        assert_eq!(all.len(), 4);
+        assert!(diff_snapshots(&conn, "w1", "missing", true).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Ad-hoc SQL against the local cache.
+
+use std::fmt;
+
+use anyhow::{Context, Result, bail};
+use rusqlite::types::ValueRef;
+use serde::{Serialize, Serializer};
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// A single column value returned by [`run_sql`].
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum SqlValue {
+    /// SQL `NULL`.
+    Null,
+    /// 64-bit integer.
+    Integer(i64),
+    /// Floating point number.
+    Real(f64),
+    /// UTF-8 text (invalid sequences replaced).
+    Text(String),
+    /// Binary data.
+    Blob(Vec<u8>),
+}
+
+impl From<ValueRef<'_>> for SqlValue {
+    fn from(value: ValueRef<'_>) -> Self {
+        match value {
+            ValueRef::Null => Self::Null,
+            ValueRef::Integer(i) => Self::Integer(i),
+            ValueRef::Real(f) => Self::Real(f),
+            ValueRef::Text(t) => Self::Text(String::from_utf8_lossy(t).into_owned()),
+            ValueRef::Blob(b) => Self::Blob(b.to_vec()),
+        }
+    }
+}
+
+/// Formats the value as `sqlite3` would list it: `NULL`, numbers, text as
+/// is, and blobs as `x'..'` hex literals.
+impl fmt::Display for SqlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("NULL"),
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Real(r) => write!(f, "{r}"),
+            Self::Text(t) => f.write_str(t),
+            Self::Blob(b) => {
+                f.write_str("x'")?;
+                for byte in b {
+                    write!(f, "{byte:02x}")?;
+                }
+                f.write_str("'")
+            }
+        }
+    }
+}
+
+/// Serializes as the matching JSON scalar; blobs become their `x'..'`
+/// literal.
+impl Serialize for SqlValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_none(),
+            Self::Integer(i) => serializer.serialize_i64(*i),
+            Self::Real(r) => serializer.serialize_f64(*r),
+            Self::Text(t) => serializer.serialize_str(t),
+            Self::Blob(_) => serializer.collect_str(self),
+        }
+    }
+}
+
+/// Result of [`run_sql`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SqlOutput {
+    /// Column names, in result order. Empty for statements without rows.
+    pub columns: Vec<String>,
+    /// Result rows, each with one value per column.
+    pub rows: Vec<Vec<SqlValue>>,
+    /// Rows changed by a statement that returns no columns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<usize>,
+}
+
+/// Runs a single SQL statement and collects its result rows.
+///
+/// Unless `allow_write` is set, statements that may modify the database
+/// are rejected before they run.
+///
+/// # Errors
+///
+/// Returns an error if the statement is invalid, would write without
+/// `allow_write`, or fails to run.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn run_sql(db: &impl DbHandle, sql: &str, allow_write: bool) -> Result<SqlOutput> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(sql)
+        .context("failed to prepare SQL statement")?;
+    if !allow_write && !stmt.readonly() {
+        bail!("statement may modify the database; re-run with --write to allow it");
+    }
+
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    if columns.is_empty() {
+        let changes = stmt
+            .execute([])
+            .context("failed to execute SQL statement")?;
+        return Ok(SqlOutput {
+            columns,
+            rows: Vec::new(),
+            changes: Some(changes),
+        });
+    }
+
+    let width = columns.len();
+    let rows = stmt
+        .query_map([], |row| {
+            (0..width)
+                .map(|i| row.get_ref(i).map(SqlValue::from))
+                .collect::<rusqlite::Result<Vec<SqlValue>>>()
+        })
+        .context("failed to execute SQL query")?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to read SQL result rows")?;
+    Ok(SqlOutput {
+        columns,
+        rows,
+        changes: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use rusqlite::Connection;
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channels (ch_id, ch_name) VALUES (7, 'テレビ東京'), (1, 'NHK総合');",
+        )
+        .unwrap();
+        (conn, dir)
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_run_sql_returns_columns_and_typed_rows() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+
+        // Act
+        let output = run_sql(
+            &conn,
+            "SELECT ch_id, ch_name, ch_gid, x'00ff' AS raw FROM channels ORDER BY ch_id",
+            false,
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(output.columns, vec!["ch_id", "ch_name", "ch_gid", "raw"]);
+        assert_eq!(
+            output.rows.first().unwrap(),
+            &vec![
+                SqlValue::Integer(1),
+                SqlValue::Text(String::from("NHK総合")),
+                SqlValue::Null,
+                SqlValue::Blob(vec![0x00, 0xff]),
+            ]
+        );
+        assert_eq!(output.rows.len(), 2);
+        assert_eq!(SqlValue::Blob(vec![0x00, 0xff]).to_string(), "x'00ff'");
+        assert_eq!(
+            serde_json::to_string(output.rows.first().unwrap()).unwrap(),
+            r#"[1,"NHK総合",null,"x'00ff'"]"#
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_run_sql_rejects_writes_unless_allowed() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let sql = "DELETE FROM channels WHERE ch_id = 7";
+
+        // Act
+        let rejected = run_sql(&conn, sql, false);
+        let allowed = run_sql(&conn, sql, true).unwrap();
+
+        // Assert
+        assert!(
+            rejected
+                .unwrap_err()
+                .to_string()
+                .contains("re-run with --write")
+        );
+        assert_eq!(allowed.changes, Some(1));
+        assert!(allowed.columns.is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+//! Aggregate statistics over the local cache.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Number of busiest hour slots reported per channel.
+const BUSIEST_SLOT_LIMIT: u32 = 3;
+
+/// Overall counts for the local cache.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DbSummary {
+    /// Total number of cached titles.
+    pub total_titles: u32,
+    /// Total number of cached programs.
+    pub total_programs: u32,
+    /// Number of distinct channels with at least one program.
+    pub unique_channels: u32,
+    /// Earliest program start time (if any).
+    pub oldest_st_time: Option<String>,
+    /// Latest program start time (if any).
+    pub newest_st_time: Option<String>,
+    /// Number of titles with a TMDB series mapping.
+    pub tmdb_matched: u32,
+}
+
+/// Broadcast usage statistics for a single channel.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelUsage {
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Channel name (`None` when the channel is not cached).
+    pub ch_name: Option<String>,
+    /// Number of programs on the channel.
+    pub program_count: u32,
+    /// Sum of program durations in minutes.
+    pub total_minutes: u32,
+    /// Minutes covered by at least one program (overlaps counted once).
+    pub airtime_minutes: u32,
+    /// Average program length in minutes.
+    pub avg_minutes: f64,
+    /// Busiest start hours as `(hour, program_count)`, busiest first.
+    pub busiest_hours: Vec<(u8, u32)>,
+}
+
+impl ChannelUsage {
+    /// Minutes double-counted because programs overlap.
+    #[must_use]
+    pub const fn overlap_minutes(&self) -> u32 {
+        self.total_minutes.saturating_sub(self.airtime_minutes)
+    }
+}
+
+/// Loads overall cache counts.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_db_summary(db: &impl DbHandle) -> Result<DbSummary> {
+    let conn = &*db.conn()?;
+    conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM titles),
+                (SELECT COUNT(*) FROM programs),
+                (SELECT COUNT(DISTINCT ch_id) FROM programs),
+                (SELECT MIN(st_time) FROM programs),
+                (SELECT MAX(st_time) FROM programs),
+                (SELECT COUNT(*) FROM titles
+                 WHERE tmdb_series_id IS NOT NULL OR tmdb_movie_id IS NOT NULL)",
+        [],
+        |row| {
+            Ok(DbSummary {
+                total_titles: row.get(0)?,
+                total_programs: row.get(1)?,
+                unique_channels: row.get(2)?,
+                oldest_st_time: row.get(3)?,
+                newest_st_time: row.get(4)?,
+                tmdb_matched: row.get(5)?,
+            })
+        },
+    )
+    .context("failed to query db summary")
+}
+
+/// Loads title counts per Syoboi category code, ordered by count (descending).
+///
+/// Each entry is `(cat, title_count, program_count)`; `cat` is `None` for
+/// titles without a category.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_category_counts(db: &impl DbHandle) -> Result<Vec<(Option<u32>, u32, u32)>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.cat, COUNT(*), COALESCE(SUM(p.cnt), 0)
+             FROM titles t
+             LEFT JOIN (SELECT tid, COUNT(*) AS cnt FROM programs GROUP BY tid) p
+                    ON p.tid = t.tid
+             GROUP BY t.cat
+             ORDER BY COUNT(*) DESC, t.cat",
+        )
+        .context("failed to prepare category counts query")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .context("failed to query category counts")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read category count rows")
+}
+
+/// Loads per-channel usage statistics, ordered by airtime (descending).
+///
+/// Deleted programs and programs without a positive duration are ignored.
+/// Airtime merges overlapping programs per channel (gaps-and-islands over
+/// a running `MAX(ed_time)` window), so simulcast or duplicated entries
+/// are not double-counted.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_channel_usage(db: &impl DbHandle) -> Result<Vec<ChannelUsage>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "WITH p AS (
+                 SELECT ch_id, julianday(st_time) AS s, julianday(ed_time) AS e
+                 FROM programs
+                 WHERE COALESCE(deleted, 0) = 0
+                   AND julianday(ed_time) > julianday(st_time)
+             ),
+             w AS (
+                 SELECT ch_id, s, e,
+                        MAX(e) OVER (
+                            PARTITION BY ch_id ORDER BY s, e
+                            ROWS BETWEEN UNBOUNDED PRECEDING AND 1 PRECEDING
+                        ) AS prev_end
+                 FROM p
+             ),
+             g AS (
+                 SELECT ch_id, s, e,
+                        SUM(CASE WHEN prev_end IS NULL OR s > prev_end THEN 1 ELSE 0 END) OVER (
+                            PARTITION BY ch_id ORDER BY s, e
+                            ROWS UNBOUNDED PRECEDING
+                        ) AS island
+                 FROM w
+             ),
+             airtime AS (
+                 SELECT ch_id, SUM(e - s) AS days
+                 FROM (SELECT ch_id, MIN(s) AS s, MAX(e) AS e FROM g GROUP BY ch_id, island)
+                 GROUP BY ch_id
+             ),
+             totals AS (
+                 SELECT ch_id, COUNT(*) AS cnt, SUM(e - s) AS days, AVG(e - s) AS avg_days
+                 FROM p
+                 GROUP BY ch_id
+             )
+             SELECT t.ch_id, c.ch_name, t.cnt,
+                    CAST(ROUND(t.days * 1440) AS INTEGER),
+                    CAST(ROUND(a.days * 1440) AS INTEGER),
+                    t.avg_days * 1440
+             FROM totals t
+             JOIN airtime a ON a.ch_id = t.ch_id
+             LEFT JOIN channels c ON c.ch_id = t.ch_id
+             ORDER BY a.days DESC, t.ch_id",
+        )
+        .context("failed to prepare channel usage query")?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ChannelUsage {
+                ch_id: row.get(0)?,
+                ch_name: row.get(1)?,
+                program_count: row.get(2)?,
+                total_minutes: row.get(3)?,
+                airtime_minutes: row.get(4)?,
+                avg_minutes: row.get(5)?,
+                busiest_hours: Vec::new(),
+            })
+        })
+        .context("failed to query channel usage")?;
+    let mut usage = rows
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read channel usage rows")?;
+
+    let mut slots = load_busiest_hours(conn)?;
+    for u in &mut usage {
+        u.busiest_hours = slots.remove(&u.ch_id).unwrap_or_default();
+    }
+    Ok(usage)
+}
+
+/// Loads the busiest start hours per channel, ranked with `ROW_NUMBER()`.
+fn load_busiest_hours(conn: &Connection) -> Result<HashMap<u32, Vec<(u8, u32)>>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT ch_id, hour, cnt
+             FROM (
+                 SELECT ch_id, hour, COUNT(*) AS cnt,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY ch_id ORDER BY COUNT(*) DESC, hour
+                        ) AS rn
+                 FROM (
+                     SELECT ch_id, CAST(strftime('%H', st_time) AS INTEGER) AS hour
+                     FROM programs
+                     WHERE COALESCE(deleted, 0) = 0
+                       AND julianday(ed_time) > julianday(st_time)
+                 )
+                 GROUP BY ch_id, hour
+             )
+             WHERE rn <= ?1
+             ORDER BY ch_id, rn",
+        )
+        .context("failed to prepare busiest hours query")?;
+
+    let rows = stmt
+        .query_map([BUSIEST_SLOT_LIMIT], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, u8>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })
+        .context("failed to query busiest hours")?;
+
+    let mut slots: HashMap<u32, Vec<(u8, u32)>> = HashMap::new();
+    for row in rows {
+        let (ch_id, hour, cnt) = row.context("failed to read busiest hours row")?;
+        slots.entry(ch_id).or_default().push((hour, cnt));
+    }
+    Ok(slots)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channel_groups (ch_gid, ch_group_name, ch_group_order) VALUES (1, 'Test', 0);
+             INSERT INTO channels (ch_id, ch_gid, ch_name) VALUES (1, 1, 'ChA'), (2, 1, 'ChB');
+             INSERT INTO titles (tid, title, last_update) VALUES (100, 'T', '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+        (conn, dir)
+    }
+
+    fn insert_program(conn: &Connection, pid: u32, ch_id: u32, st: &str, ed: &str) {
+        conn.execute(
+            "INSERT INTO programs (pid, tid, ch_id, st_time, ed_time) VALUES (?1, 100, ?2, ?3, ?4)",
+            rusqlite::params![pid, ch_id, st, ed],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn channel_usage_merges_overlapping_programs() {
+        // Arrange: two overlapping programs (00:00-00:30, 00:15-01:00) and one separate
+        let (conn, _dir) = setup_db();
+        insert_program(&conn, 1, 1, "2024-01-01 00:00:00", "2024-01-01 00:30:00");
+        insert_program(&conn, 2, 1, "2024-01-01 00:15:00", "2024-01-01 01:00:00");
+        insert_program(&conn, 3, 1, "2024-01-01 23:00:00", "2024-01-01 23:30:00");
+
+        // Act
+        let usage = load_channel_usage(&conn).unwrap();
+
+        // Assert
+        assert_eq!(usage.len(), 1);
+        let ch = &usage[0];
+        assert_eq!(ch.ch_name.as_deref(), Some("ChA"));
+        assert_eq!(ch.program_count, 3);
+        assert_eq!(ch.total_minutes, 105);
+        assert_eq!(ch.airtime_minutes, 90);
+        assert_eq!(ch.overlap_minutes(), 15);
+        assert!((ch.avg_minutes - 35.0).abs() < 0.01);
+        assert_eq!(ch.busiest_hours, vec![(0, 2), (23, 1)]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn channel_usage_orders_by_airtime_and_skips_deleted() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        insert_program(&conn, 1, 1, "2024-01-01 00:00:00", "2024-01-01 00:30:00");
+        insert_program(&conn, 2, 2, "2024-01-01 00:00:00", "2024-01-01 02:00:00");
+        insert_program(&conn, 3, 1, "2024-01-02 00:00:00", "2024-01-02 05:00:00");
+        conn.execute("UPDATE programs SET deleted = 1 WHERE pid = 3", [])
+            .unwrap();
+
+        // Act
+        let usage = load_channel_usage(&conn).unwrap();
+
+        // Assert
+        let ids: Vec<u32> = usage.iter().map(|u| u.ch_id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert_eq!(usage[1].airtime_minutes, 30);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn category_counts_group_titles_and_programs() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        conn.execute_batch(
+            "UPDATE titles SET cat = 1 WHERE tid = 100;
+             INSERT INTO titles (tid, title, cat, last_update) VALUES
+                 (101, 'U', 1, '2024-01-01 00:00:00'),
+                 (102, 'V', 8, '2024-01-01 00:00:00'),
+                 (103, 'W', NULL, '2024-01-01 00:00:00');",
+        )
+        .unwrap();
+        insert_program(&conn, 1, 1, "2024-01-01 00:00:00", "2024-01-01 00:30:00");
+        insert_program(&conn, 2, 1, "2024-01-02 00:00:00", "2024-01-02 00:30:00");
+
+        // Act
+        let counts = load_category_counts(&conn).unwrap();
+
+        // Assert
+        assert_eq!(counts, vec![(Some(1), 2, 2), (None, 1, 0), (Some(8), 1, 0)]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn db_summary_counts_rows() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        insert_program(&conn, 1, 1, "2024-01-01 00:00:00", "2024-01-01 00:30:00");
+        insert_program(&conn, 2, 2, "2024-01-03 00:00:00", "2024-01-03 00:30:00");
+
+        // Act
+        let summary = load_db_summary(&conn).unwrap();
+
+        // Assert
+        assert_eq!(summary.total_titles, 1);
+        assert_eq!(summary.total_programs, 2);
+        assert_eq!(summary.unique_channels, 2);
+        assert_eq!(
+            summary.oldest_st_time.as_deref(),
+            Some("2024-01-01 00:00:00")
+        );
+        assert_eq!(
+            summary.newest_st_time.as_deref(),
+            Some("2024-01-03 00:00:00")
+        );
+        assert_eq!(summary.tmdb_matched, 0);
+    }
+}
@@ -0,0 +1,335 @@
+//! Per-run summaries and checkpoints of `db sync` runs.
+
+use anyhow::{Context, Result};
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// One recorded sync run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SyncRunRecord {
+    /// Row ID (ignored on insert).
+    pub id: i64,
+    /// Start time (`%Y-%m-%dT%H:%M:%SZ`).
+    pub started_at: String,
+    /// Finish time (`%Y-%m-%dT%H:%M:%SZ`); `None` while running.
+    pub finished_at: Option<String>,
+    /// Outcome (`running`, `succeeded`, or `failed`).
+    pub status: String,
+    /// Titles fetched.
+    pub titles: u32,
+    /// Titles inserted or changed.
+    pub titles_changed: u32,
+    /// Programs stored.
+    pub programs: u32,
+    /// Programs inserted or changed.
+    pub programs_changed: u32,
+    /// Error message of a failed run.
+    pub error: Option<String>,
+    /// Resolved run parameters (JSON, owned by the caller) for resuming.
+    pub params: Option<String>,
+    /// Programs fetched (checkpoint).
+    pub programs_fetched: u32,
+    /// Title lookup chunks completed (checkpoint).
+    pub title_chunks_done: u32,
+    /// Title lookup chunks of the run (checkpoint).
+    pub title_chunks_total: u32,
+    /// TIDs whose title chunk completed (checkpoint).
+    pub done_tids: Vec<u32>,
+}
+
+/// Column list shared by the sync run queries.
+const SYNC_RUN_COLUMNS: &str = "id, started_at, finished_at, status, titles, titles_changed,
+     programs, programs_changed, error, params, programs_fetched, title_chunks_done,
+     title_chunks_total, done_tids";
+
+/// Maps a row selected with [`SYNC_RUN_COLUMNS`].
+fn sync_run_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SyncRunRecord> {
+    let done_tids: String = row.get(13)?;
+    Ok(SyncRunRecord {
+        id: row.get(0)?,
+        started_at: row.get(1)?,
+        finished_at: row.get(2)?,
+        status: row.get(3)?,
+        titles: row.get(4)?,
+        titles_changed: row.get(5)?,
+        programs: row.get(6)?,
+        programs_changed: row.get(7)?,
+        error: row.get(8)?,
+        params: row.get(9)?,
+        programs_fetched: row.get(10)?,
+        title_chunks_done: row.get(11)?,
+        title_chunks_total: row.get(12)?,
+        done_tids: done_tids
+            .split(',')
+            .filter_map(|tid| tid.parse().ok())
+            .collect(),
+    })
+}
+
+/// Inserts a sync run summary and returns its ID.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn insert_sync_run(db: &impl DbHandle, run: &SyncRunRecord) -> Result<i64> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO sync_runs
+             (started_at, finished_at, status, titles, titles_changed, programs,
+              programs_changed, error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            run.started_at,
+            run.finished_at,
+            run.status,
+            run.titles,
+            run.titles_changed,
+            run.programs,
+            run.programs_changed,
+            run.error,
+        ],
+    )
+    .context("failed to insert sync run")?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Inserts a `running` sync run and returns its ID.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn start_sync_run(db: &impl DbHandle, started_at: &str) -> Result<i64> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "INSERT INTO sync_runs (started_at, status) VALUES (?1, 'running')",
+        [started_at],
+    )
+    .context("failed to start sync run")?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Stores the resolved parameters of a sync run, used to resume it.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn save_sync_params(db: &impl DbHandle, id: i64, params: &str) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "UPDATE sync_runs SET params = ?2 WHERE id = ?1",
+        rusqlite::params![id, params],
+    )
+    .context("failed to save sync parameters")?;
+    Ok(())
+}
+
+/// Records the number of programs fetched by a running sync.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn save_programs_fetched(db: &impl DbHandle, id: i64, programs: u32) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "UPDATE sync_runs SET programs_fetched = ?2 WHERE id = ?1",
+        rusqlite::params![id, programs],
+    )
+    .context("failed to save programs checkpoint")?;
+    Ok(())
+}
+
+/// Records a completed title chunk: bumps `title_chunks_done`, sets
+/// `title_chunks_total`, and appends `tids` to `done_tids`.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn save_title_chunk(
+    db: &impl DbHandle,
+    id: i64,
+    tids: &[u32],
+    total_chunks: u32,
+) -> Result<()> {
+    let conn = &*db.conn()?;
+    let tids = tids
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    conn.execute(
+        "UPDATE sync_runs
+         SET title_chunks_done = title_chunks_done + 1,
+             title_chunks_total = ?3,
+             done_tids = CASE WHEN done_tids = '' THEN ?2 ELSE done_tids || ',' || ?2 END
+         WHERE id = ?1",
+        rusqlite::params![id, tids, total_chunks],
+    )
+    .context("failed to save title chunk checkpoint")?;
+    Ok(())
+}
+
+/// Stores the outcome of the run `run.id`: finish time, status, totals,
+/// and error. Checkpoint columns are kept for a later resume.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn finish_sync_run(db: &impl DbHandle, run: &SyncRunRecord) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "UPDATE sync_runs
+         SET finished_at = ?2, status = ?3, titles = ?4, titles_changed = ?5,
+             programs = ?6, programs_changed = ?7, error = ?8
+         WHERE id = ?1",
+        rusqlite::params![
+            run.id,
+            run.finished_at,
+            run.status,
+            run.titles,
+            run.titles_changed,
+            run.programs,
+            run.programs_changed,
+            run.error,
+        ],
+    )
+    .context("failed to finish sync run")?;
+    Ok(())
+}
+
+/// Loads one sync run by ID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_sync_run(db: &impl DbHandle, id: i64) -> Result<Option<SyncRunRecord>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {SYNC_RUN_COLUMNS} FROM sync_runs WHERE id = ?1"
+        ))
+        .context("failed to prepare sync run query")?;
+    let mut rows = stmt
+        .query_map([id], sync_run_from_row)
+        .context("failed to query sync run")?;
+    rows.next()
+        .transpose()
+        .context("failed to read sync run row")
+}
+
+/// Loads the most recent `limit` sync runs, newest first.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+#[allow(clippy::module_name_repetitions)]
+pub fn load_sync_runs(db: &impl DbHandle, limit: u32) -> Result<Vec<SyncRunRecord>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {SYNC_RUN_COLUMNS} FROM sync_runs ORDER BY id DESC LIMIT ?1"
+        ))
+        .context("failed to prepare sync runs query")?;
+    let rows = stmt
+        .query_map([limit], sync_run_from_row)
+        .context("failed to query sync runs")?;
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read sync run rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn run(status: &str, error: Option<&str>) -> SyncRunRecord {
+        SyncRunRecord {
+            id: 0,
+            started_at: String::from("2024-04-01T00:00:00Z"),
+            finished_at: Some(String::from("2024-04-01T00:01:00Z")),
+            status: status.to_owned(),
+            titles: 3,
+            titles_changed: 1,
+            programs: 10,
+            programs_changed: 2,
+            error: error.map(str::to_owned),
+            ..SyncRunRecord::default()
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_insert_and_load_sync_runs_newest_first() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+
+        // Act
+        let first = insert_sync_run(&conn, &run("succeeded", None)).unwrap();
+        let second = insert_sync_run(&conn, &run("failed", Some("boom"))).unwrap();
+        let runs = load_sync_runs(&conn, 1).unwrap();
+
+        // Assert
+        assert!(second > first);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, second);
+        assert_eq!(runs[0].status, "failed");
+        assert_eq!(runs[0].error.as_deref(), Some("boom"));
+        assert_eq!(runs[0].programs, 10);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_sync_run_checkpoint_and_finish() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        let id = start_sync_run(&conn, "2024-04-01T00:00:00Z").unwrap();
+
+        // Act
+        save_sync_params(&conn, id, r#"{"ch_ids":[1]}"#).unwrap();
+        save_programs_fetched(&conn, id, 120).unwrap();
+        save_title_chunk(&conn, id, &[1, 2], 3).unwrap();
+        save_title_chunk(&conn, id, &[3], 3).unwrap();
+        let running = load_sync_run(&conn, id).unwrap().unwrap();
+        finish_sync_run(
+            &conn,
+            &SyncRunRecord {
+                id,
+                finished_at: Some(String::from("2024-04-01T01:00:00Z")),
+                status: String::from("failed"),
+                error: Some(String::from("rate limited")),
+                ..SyncRunRecord::default()
+            },
+        )
+        .unwrap();
+        let finished = load_sync_run(&conn, id).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(running.status, "running");
+        assert!(running.finished_at.is_none());
+        assert_eq!(running.params.as_deref(), Some(r#"{"ch_ids":[1]}"#));
+        assert_eq!(running.programs_fetched, 120);
+        assert_eq!(running.title_chunks_done, 2);
+        assert_eq!(running.title_chunks_total, 3);
+        assert_eq!(running.done_tids, vec![1, 2, 3]);
+        assert_eq!(finished.status, "failed");
+        assert_eq!(finished.done_tids, vec![1, 2, 3]);
+        assert!(load_sync_run(&conn, id + 1).unwrap().is_none());
+    }
+}
@@ -0,0 +1,83 @@
+//! Incremental sync cursors keyed by sync scope.
+
+use anyhow::{Context, Result};
+use rusqlite::OptionalExtension as _;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// Loads the `LastUpdate` cursor recorded for `scope`.
+///
+/// Returns `None` when the scope has never completed an incremental sync.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_sync_cursor(db: &impl DbHandle, scope: &str) -> Result<Option<String>> {
+    let conn = &*db.conn()?;
+    conn.query_row(
+        "SELECT last_update FROM sync_state WHERE scope = ?1",
+        [scope],
+        |row| row.get(0),
+    )
+    .optional()
+    .with_context(|| format!("failed to load sync cursor for {scope}"))
+}
+
+/// Records `last_update` as the cursor for `scope`.
+///
+/// The cursor only moves forward: an older value than the stored one is
+/// ignored. Returns `true` when the row changed.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn save_sync_cursor(db: &impl DbHandle, scope: &str, last_update: &str) -> Result<bool> {
+    let conn = &*db.conn()?;
+    let rows = conn
+        .execute(
+            "INSERT INTO sync_state (scope, last_update) VALUES (?1, ?2)
+            ON CONFLICT(scope) DO UPDATE SET
+                last_update = excluded.last_update,
+                synced_at   = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+            WHERE excluded.last_update > sync_state.last_update",
+            [scope, last_update],
+        )
+        .with_context(|| format!("failed to save sync cursor for {scope}"))?;
+    Ok(rows > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::connection::open_db;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_save_sync_cursor_only_moves_forward() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+
+        // Act
+        let missing = load_sync_cursor(&conn, "programs:ch=7").unwrap();
+        let first = save_sync_cursor(&conn, "programs:ch=7", "2024-04-01 10:00:00").unwrap();
+        let older = save_sync_cursor(&conn, "programs:ch=7", "2024-03-31 10:00:00").unwrap();
+        let newer = save_sync_cursor(&conn, "programs:ch=7", "2024-04-02 10:00:00").unwrap();
+
+        // Assert
+        assert!(missing.is_none());
+        assert!(first);
+        assert!(!older);
+        assert!(newer);
+        assert_eq!(
+            load_sync_cursor(&conn, "programs:ch=7").unwrap().as_deref(),
+            Some("2024-04-02 10:00:00")
+        );
+        assert!(load_sync_cursor(&conn, "programs:ch=1").unwrap().is_none());
+    }
+}
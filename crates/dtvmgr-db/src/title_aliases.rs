@@ -0,0 +1,304 @@
+//! Merging of split or re-created Syoboi titles.
+//!
+//! Syoboi occasionally re-creates a title under a new TID. [`merge_titles`]
+//! moves the cached programs and TMDB mapping of the old TID onto the new
+//! one and records the old TID in `title_aliases`, so later syncs fold its
+//! programs into the new title instead of re-creating it.
+
+use anyhow::{Context, Result, bail};
+use rusqlite::OptionalExtension;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// An old TID folded into another title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TitleAlias {
+    /// TID that was merged away.
+    pub old_tid: u32,
+    /// TID its programs now belong to.
+    pub new_tid: u32,
+    /// UTC timestamp of the merge (set by the DB on insert).
+    pub merged_at: String,
+}
+
+/// Outcome of [`merge_titles`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TitleMerge {
+    /// Number of programs re-linked to the new TID.
+    pub programs: usize,
+    /// Whether the old title's TMDB mapping was copied to the new one.
+    pub mapping_copied: bool,
+    /// Number of earlier aliases re-pointed from the old TID to the new one.
+    pub aliases_repointed: usize,
+}
+
+/// Merges the cached title `old_tid` into `new_tid`.
+///
+/// In one transaction:
+///
+/// - programs of `old_tid` are re-linked to `new_tid`;
+/// - the TMDB mapping (series, season, movie, episode group, external
+///   IDs) is copied when `new_tid` has none, and the followed flag is kept;
+/// - downloaded images, localizations, season ranges, and the watchlist
+///   entry move to `new_tid` unless it already has its own;
+/// - the old title is deleted and recorded as an alias of `new_tid`.
+///   Aliases that pointed at `old_tid` are re-pointed as well.
+///
+/// # Errors
+///
+/// Returns an error if the TIDs are equal, either title is not cached, or
+/// the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn merge_titles(db: &impl DbHandle, old_tid: u32, new_tid: u32) -> Result<TitleMerge> {
+    let conn = &*db.conn()?;
+    if old_tid == new_tid {
+        bail!("cannot merge title {old_tid} into itself");
+    }
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+
+    for tid in [old_tid, new_tid] {
+        let cached = tx
+            .query_row("SELECT 1 FROM titles WHERE tid = ?1", [tid], |_| Ok(()))
+            .optional()
+            .with_context(|| format!("failed to look up title {tid}"))?;
+        if cached.is_none() {
+            bail!("title {tid} is not cached");
+        }
+    }
+
+    let programs = tx
+        .execute(
+            "UPDATE programs SET tid = ?2 WHERE tid = ?1",
+            [old_tid, new_tid],
+        )
+        .with_context(|| format!("failed to re-link programs of title {old_tid}"))?;
+
+    let mapping_copied = tx
+        .execute(
+            "UPDATE titles SET
+                 tmdb_series_id        = old.tmdb_series_id,
+                 tmdb_season_number    = old.tmdb_season_number,
+                 tmdb_season_id        = old.tmdb_season_id,
+                 tmdb_movie_id         = old.tmdb_movie_id,
+                 tmdb_episode_group_id = old.tmdb_episode_group_id,
+                 tmdb_original_name    = old.tmdb_original_name,
+                 tmdb_name             = old.tmdb_name,
+                 tmdb_alt_titles       = old.tmdb_alt_titles,
+                 tmdb_last_updated     = old.tmdb_last_updated,
+                 imdb_id               = COALESCE(titles.imdb_id, old.imdb_id),
+                 tvdb_id               = COALESCE(titles.tvdb_id, old.tvdb_id)
+             FROM (SELECT * FROM titles WHERE tid = ?1) AS old
+             WHERE titles.tid = ?2
+               AND titles.tmdb_series_id IS NULL AND titles.tmdb_movie_id IS NULL
+               AND (old.tmdb_series_id IS NOT NULL OR old.tmdb_movie_id IS NOT NULL)",
+            [old_tid, new_tid],
+        )
+        .with_context(|| format!("failed to copy TMDB mapping of title {old_tid}"))?
+        > 0;
+
+    tx.execute(
+        "UPDATE titles SET followed = 1
+         WHERE tid = ?2 AND (SELECT followed FROM titles WHERE tid = ?1) != 0",
+        [old_tid, new_tid],
+    )
+    .with_context(|| format!("failed to copy followed flag of title {old_tid}"))?;
+
+    for table in [
+        "title_images",
+        "title_localizations",
+        "title_season_ranges",
+        "watchlist",
+    ] {
+        tx.execute(
+            &format!("UPDATE OR IGNORE {table} SET tid = ?2 WHERE tid = ?1"),
+            [old_tid, new_tid],
+        )
+        .with_context(|| format!("failed to move {table} rows of title {old_tid}"))?;
+    }
+    // Leftover watchlist rows (the new title was already watched) have no
+    // foreign key to cascade from.
+    tx.execute("DELETE FROM watchlist WHERE tid = ?1", [old_tid])
+        .with_context(|| format!("failed to remove watchlist entry of title {old_tid}"))?;
+
+    let aliases_repointed = tx
+        .execute(
+            "UPDATE title_aliases SET new_tid = ?2 WHERE new_tid = ?1",
+            [old_tid, new_tid],
+        )
+        .context("failed to re-point title aliases")?;
+    tx.execute(
+        "INSERT OR REPLACE INTO title_aliases (old_tid, new_tid) VALUES (?1, ?2)",
+        [old_tid, new_tid],
+    )
+    .with_context(|| format!("failed to record alias {old_tid} -> {new_tid}"))?;
+    tx.execute("DELETE FROM titles WHERE tid = ?1", [old_tid])
+        .with_context(|| format!("failed to delete title {old_tid}"))?;
+
+    tx.commit().context("failed to commit title merge")?;
+    Ok(TitleMerge {
+        programs,
+        mapping_copied,
+        aliases_repointed,
+    })
+}
+
+/// Loads all title aliases ordered by old TID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_title_aliases(db: &impl DbHandle) -> Result<Vec<TitleAlias>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare("SELECT old_tid, new_tid, merged_at FROM title_aliases ORDER BY old_tid")
+        .context("failed to prepare title alias query")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TitleAlias {
+                old_tid: row.get(0)?,
+                new_tid: row.get(1)?,
+                merged_at: row.get(2)?,
+            })
+        })
+        .context("failed to query title aliases")?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .context("failed to read title alias rows")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::indexing_slicing)]
+
+    use rusqlite::Connection;
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channels (ch_id, ch_name) VALUES (1, 'NHK総合');
+             INSERT INTO titles (tid, title, last_update, tmdb_series_id, tmdb_season_number, followed)
+                 VALUES (100, '旧タイトル', '', 5000, 2, 1);
+             INSERT INTO titles (tid, title, last_update) VALUES (200, '新タイトル', '');
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time)
+                 VALUES (1, 100, 1, '2024-01-01 23:00:00', '2024-01-01 23:30:00'),
+                        (2, 100, 1, '2024-01-08 23:00:00', '2024-01-08 23:30:00'),
+                        (3, 200, 1, '2024-01-15 23:00:00', '2024-01-15 23:30:00');
+             INSERT INTO watchlist (tid) VALUES (100);",
+        )
+        .unwrap();
+        (conn, dir)
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_merge_titles_relinks_programs_and_keeps_mapping() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+
+        // Act
+        let merge = merge_titles(&conn, 100, 200).unwrap();
+
+        // Assert
+        assert_eq!(merge.programs, 2);
+        assert!(merge.mapping_copied);
+        let tids: Vec<u32> = conn
+            .prepare("SELECT DISTINCT tid FROM programs")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(tids, vec![200]);
+        let (series, season, followed): (Option<u64>, Option<u32>, bool) = conn
+            .query_row(
+                "SELECT tmdb_series_id, tmdb_season_number, followed FROM titles WHERE tid = 200",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((series, season, followed), (Some(5000), Some(2), true));
+        let old: i64 = conn
+            .query_row("SELECT COUNT(*) FROM titles WHERE tid = 100", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(old, 0);
+        let watched: u32 = conn
+            .query_row("SELECT tid FROM watchlist", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(watched, 200);
+        let aliases = load_title_aliases(&conn).unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!((aliases[0].old_tid, aliases[0].new_tid), (100, 200));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_merge_titles_keeps_existing_mapping_and_repoints_aliases() {
+        // Arrange: 200 is already mapped, and 50 was merged into 100 earlier
+        let (conn, _dir) = setup_db();
+        conn.execute_batch(
+            "UPDATE titles SET tmdb_series_id = 7000 WHERE tid = 200;
+             INSERT INTO title_aliases (old_tid, new_tid) VALUES (50, 100);",
+        )
+        .unwrap();
+
+        // Act
+        let merge = merge_titles(&conn, 100, 200).unwrap();
+
+        // Assert
+        assert!(!merge.mapping_copied);
+        assert_eq!(merge.aliases_repointed, 1);
+        let series: Option<u64> = conn
+            .query_row(
+                "SELECT tmdb_series_id FROM titles WHERE tid = 200",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(series, Some(7000));
+        let targets: Vec<(u32, u32)> = load_title_aliases(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|a| (a.old_tid, a.new_tid))
+            .collect();
+        assert_eq!(targets, vec![(50, 200), (100, 200)]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_merge_titles_rejects_unknown_or_same_tid() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+
+        // Act
+        let same = merge_titles(&conn, 100, 100);
+        let unknown = merge_titles(&conn, 100, 999);
+
+        // Assert
+        assert!(same.unwrap_err().to_string().contains("into itself"));
+        assert!(
+            unknown
+                .unwrap_err()
+                .to_string()
+                .contains("title 999 is not cached")
+        );
+        let programs: i64 = conn
+            .query_row("SELECT COUNT(*) FROM programs WHERE tid = 100", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(programs, 2);
+    }
+}
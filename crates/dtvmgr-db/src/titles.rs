@@ -2,10 +2,14 @@
 
 use anyhow::{Context, Result};
 use rusqlite::Connection;
+use serde::Serialize;
 use tracing::instrument;
 
+use crate::connection::{DEFAULT_UPSERT_BATCH_SIZE, in_batches};
+use crate::pool::DbHandle;
+
 /// A cached title with optional TMDB mapping.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CachedTitle {
     /// Syoboi title ID.
     pub tid: u32,
@@ -15,6 +19,11 @@ pub struct CachedTitle {
     pub tmdb_season_number: Option<u32>,
     /// Mapped TMDB season ID (cache, nullable).
     pub tmdb_season_id: Option<u64>,
+    /// Mapped TMDB movie ID for movie titles (cache, nullable).
+    pub tmdb_movie_id: Option<u64>,
+    /// Mapped TMDB episode group ID, used instead of the season when set
+    /// (cache, nullable).
+    pub tmdb_episode_group_id: Option<String>,
     /// Title name.
     pub title: String,
     /// Short title (nullable).
@@ -33,10 +42,15 @@ pub struct CachedTitle {
     pub first_month: Option<u32>,
     /// Keywords parsed from comma-separated DB value.
     pub keywords: Vec<String>,
-    /// Raw subtitle text (nullable).
-    pub sub_titles: Option<String>,
     /// Last update timestamp.
     pub last_update: String,
+    /// Raw Syoboi comment (nullable; stored when `Comment` is synced).
+    pub comment: Option<String>,
+    /// Syoboi user point (nullable; stored when `UserPoint` is synced).
+    pub user_point: Option<i32>,
+    /// Syoboi user point rank (nullable; stored when `UserPointRank` is
+    /// synced).
+    pub user_point_rank: Option<u32>,
     /// TMDB original name from search result (nullable).
     pub tmdb_original_name: Option<String>,
     /// TMDB localized name from search result (nullable).
@@ -108,82 +122,109 @@ pub fn filter_keywords(keywords: &[String], title: &str, short_title: Option<&st
         .collect()
 }
 
-/// Upserts titles into the cache. Returns the number of rows changed.
+/// Upserts titles into the cache in batches of
+/// [`DEFAULT_UPSERT_BATCH_SIZE`]. Returns the number of rows changed.
 ///
-/// Uses `INSERT ... ON CONFLICT(tid) DO UPDATE SET` to update existing rows.
-/// TMDB mapping columns (`tmdb_series_id`, `tmdb_season_number`) are preserved
-/// on conflict to avoid overwriting manual mappings.
-/// Only updates when `last_update` has changed.
+/// See [`upsert_titles_batched`].
 ///
 /// # Errors
 ///
 /// Returns an error if the database operation fails.
 #[allow(clippy::module_name_repetitions)]
-#[instrument(skip_all, err(level = "error"))]
-pub fn upsert_titles(conn: &Connection, titles: &[CachedTitle]) -> Result<usize> {
-    let tx = conn
-        .unchecked_transaction()
-        .context("failed to begin transaction")?;
-
-    let mut stmt = tx
-        .prepare(
-            "INSERT INTO titles (
-                tid, tmdb_series_id, tmdb_season_number, tmdb_season_id,
-                title, short_title, title_yomi, title_en,
-                cat, title_flag, first_year, first_month,
-                keywords, sub_titles, last_update,
-                tmdb_original_name, tmdb_name, tmdb_alt_titles,
-                tmdb_last_updated
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)
-            ON CONFLICT(tid) DO UPDATE SET
-                title = excluded.title,
-                short_title = excluded.short_title,
-                title_yomi = excluded.title_yomi,
-                title_en = excluded.title_en,
-                cat = excluded.cat,
-                title_flag = excluded.title_flag,
-                first_year = excluded.first_year,
-                first_month = excluded.first_month,
-                keywords = excluded.keywords,
-                sub_titles = excluded.sub_titles,
-                last_update = excluded.last_update
-            WHERE titles.last_update != excluded.last_update",
-        )
-        .context("failed to prepare titles upsert")?;
-
-    let mut changed: usize = 0;
-    for t in titles {
-        let rows = stmt
-            .execute(rusqlite::params![
-                t.tid,
-                t.tmdb_series_id,
-                t.tmdb_season_number,
-                t.tmdb_season_id,
-                t.title,
-                t.short_title,
-                t.title_yomi,
-                t.title_en,
-                t.cat,
-                t.title_flag,
-                t.first_year,
-                t.first_month,
-                serialize_keywords(&t.keywords),
-                t.sub_titles,
-                t.last_update,
-                t.tmdb_original_name,
-                t.tmdb_name,
-                t.tmdb_alt_titles,
-                t.tmdb_last_updated,
-            ])
-            .with_context(|| format!("failed to upsert title {}", t.tid))?;
-        changed = changed.saturating_add(rows);
-    }
+pub fn upsert_titles(db: &impl DbHandle, titles: &[CachedTitle]) -> Result<usize> {
+    let conn = &*db.conn()?;
+    upsert_titles_batched(conn, titles, DEFAULT_UPSERT_BATCH_SIZE)
+}
 
-    drop(stmt);
-    tx.commit().context("failed to commit titles upsert")?;
-    Ok(changed)
+/// Upserts titles into the cache, committing one transaction per
+/// `batch_size` rows (`0` writes all rows in one transaction). Returns the
+/// number of rows changed.
+///
+/// Uses `INSERT ... ON CONFLICT(tid) DO UPDATE SET` to update existing rows.
+/// TMDB mapping columns (`tmdb_series_id`, `tmdb_season_number`,
+/// `tmdb_movie_id`, `tmdb_episode_group_id`) are preserved on conflict to
+/// avoid overwriting manual mappings.
+/// Only updates when `last_update` has changed.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails. Batches committed
+/// before the failing one are kept.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, fields(rows = titles.len(), batch_size), err(level = "error"))]
+pub fn upsert_titles_batched(
+    db: &impl DbHandle,
+    titles: &[CachedTitle],
+    batch_size: usize,
+) -> Result<usize> {
+    let conn = &*db.conn()?;
+    in_batches(conn, titles, batch_size, |tx, chunk| {
+        let mut stmt = tx
+            .prepare_cached(UPSERT_TITLE_SQL)
+            .context("failed to prepare titles upsert")?;
+        let mut changed: usize = 0;
+        for t in chunk {
+            let rows = stmt
+                .execute(rusqlite::params![
+                    t.tid,
+                    t.tmdb_series_id,
+                    t.tmdb_season_number,
+                    t.tmdb_season_id,
+                    t.title,
+                    t.short_title,
+                    t.title_yomi,
+                    t.title_en,
+                    t.cat,
+                    t.title_flag,
+                    t.first_year,
+                    t.first_month,
+                    serialize_keywords(&t.keywords),
+                    t.last_update,
+                    t.tmdb_original_name,
+                    t.tmdb_name,
+                    t.tmdb_alt_titles,
+                    t.tmdb_last_updated,
+                    t.tmdb_movie_id,
+                    t.tmdb_episode_group_id,
+                    t.comment,
+                    t.user_point,
+                    t.user_point_rank,
+                ])
+                .with_context(|| format!("failed to upsert title {}", t.tid))?;
+            changed = changed.saturating_add(rows);
+        }
+        Ok(changed)
+    })
 }
 
+/// Upsert statement of [`upsert_titles_batched`].
+const UPSERT_TITLE_SQL: &str = "INSERT INTO titles (
+        tid, tmdb_series_id, tmdb_season_number, tmdb_season_id,
+        title, short_title, title_yomi, title_en,
+        cat, title_flag, first_year, first_month,
+        keywords, last_update,
+        tmdb_original_name, tmdb_name, tmdb_alt_titles,
+        tmdb_last_updated, tmdb_movie_id, tmdb_episode_group_id,
+        comment, user_point, user_point_rank
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)
+    ON CONFLICT(tid) DO UPDATE SET
+        title = excluded.title,
+        short_title = excluded.short_title,
+        title_yomi = excluded.title_yomi,
+        title_en = excluded.title_en,
+        cat = excluded.cat,
+        title_flag = excluded.title_flag,
+        first_year = excluded.first_year,
+        first_month = excluded.first_month,
+        keywords = excluded.keywords,
+        last_update = excluded.last_update,
+        comment = excluded.comment,
+        user_point = excluded.user_point,
+        user_point_rank = excluded.user_point_rank
+    WHERE titles.last_update != excluded.last_update
+       OR titles.user_point IS NOT excluded.user_point
+       OR titles.user_point_rank IS NOT excluded.user_point_rank";
+
 /// Loads all titles from the cache.
 ///
 /// # Errors
@@ -191,15 +232,17 @@ pub fn upsert_titles(conn: &Connection, titles: &[CachedTitle]) -> Result<usize>
 /// Returns an error if the database query fails.
 #[allow(clippy::module_name_repetitions)]
 #[instrument(skip_all, err(level = "error"))]
-pub fn load_titles(conn: &Connection) -> Result<Vec<CachedTitle>> {
+pub fn load_titles(db: &impl DbHandle) -> Result<Vec<CachedTitle>> {
+    let conn = &*db.conn()?;
     let mut stmt = conn
         .prepare(
             "SELECT tid, tmdb_series_id, tmdb_season_number, tmdb_season_id,
                     title, short_title, title_yomi, title_en,
                     cat, title_flag, first_year, first_month,
-                    keywords, sub_titles, last_update,
+                    keywords, last_update,
                     tmdb_original_name, tmdb_name, tmdb_alt_titles,
-                    tmdb_last_updated
+                    tmdb_last_updated, tmdb_movie_id, tmdb_episode_group_id,
+                    comment, user_point, user_point_rank
              FROM titles
              ORDER BY tid",
         )
@@ -212,6 +255,8 @@ pub fn load_titles(conn: &Connection) -> Result<Vec<CachedTitle>> {
                 tmdb_series_id: row.get(1)?,
                 tmdb_season_number: row.get(2)?,
                 tmdb_season_id: row.get(3)?,
+                tmdb_movie_id: row.get(18)?,
+                tmdb_episode_group_id: row.get(19)?,
                 title: row.get(4)?,
                 short_title: row.get(5)?,
                 title_yomi: row.get(6)?,
@@ -221,12 +266,14 @@ pub fn load_titles(conn: &Connection) -> Result<Vec<CachedTitle>> {
                 first_year: row.get(10)?,
                 first_month: row.get(11)?,
                 keywords: parse_keywords(row.get(12)?),
-                sub_titles: row.get(13)?,
-                last_update: row.get(14)?,
-                tmdb_original_name: row.get(15)?,
-                tmdb_name: row.get(16)?,
-                tmdb_alt_titles: row.get(17)?,
-                tmdb_last_updated: row.get(18)?,
+                last_update: row.get(13)?,
+                tmdb_original_name: row.get(14)?,
+                tmdb_name: row.get(15)?,
+                tmdb_alt_titles: row.get(16)?,
+                tmdb_last_updated: row.get(17)?,
+                comment: row.get(20)?,
+                user_point: row.get(21)?,
+                user_point_rank: row.get(22)?,
             })
         })
         .context("failed to query titles")?;
@@ -242,7 +289,8 @@ pub fn load_titles(conn: &Connection) -> Result<Vec<CachedTitle>> {
 /// Returns an error if the database query fails.
 #[allow(clippy::module_name_repetitions)]
 #[instrument(skip_all, err(level = "error"))]
-pub fn load_titles_by_tids(conn: &Connection, tids: &[u32]) -> Result<Vec<CachedTitle>> {
+pub fn load_titles_by_tids(db: &impl DbHandle, tids: &[u32]) -> Result<Vec<CachedTitle>> {
+    let conn = &*db.conn()?;
     if tids.is_empty() {
         return Ok(Vec::new());
     }
@@ -252,9 +300,10 @@ pub fn load_titles_by_tids(conn: &Connection, tids: &[u32]) -> Result<Vec<Cached
         "SELECT tid, tmdb_series_id, tmdb_season_number, tmdb_season_id,
                 title, short_title, title_yomi, title_en,
                 cat, title_flag, first_year, first_month,
-                keywords, sub_titles, last_update,
+                keywords, last_update,
                 tmdb_original_name, tmdb_name, tmdb_alt_titles,
-                tmdb_last_updated
+                tmdb_last_updated, tmdb_movie_id, tmdb_episode_group_id,
+                comment, user_point, user_point_rank
          FROM titles
          WHERE tid IN ({})
          ORDER BY tid",
@@ -278,6 +327,8 @@ pub fn load_titles_by_tids(conn: &Connection, tids: &[u32]) -> Result<Vec<Cached
                 tmdb_series_id: row.get(1)?,
                 tmdb_season_number: row.get(2)?,
                 tmdb_season_id: row.get(3)?,
+                tmdb_movie_id: row.get(18)?,
+                tmdb_episode_group_id: row.get(19)?,
                 title: row.get(4)?,
                 short_title: row.get(5)?,
                 title_yomi: row.get(6)?,
@@ -287,12 +338,14 @@ pub fn load_titles_by_tids(conn: &Connection, tids: &[u32]) -> Result<Vec<Cached
                 first_year: row.get(10)?,
                 first_month: row.get(11)?,
                 keywords: parse_keywords(row.get(12)?),
-                sub_titles: row.get(13)?,
-                last_update: row.get(14)?,
-                tmdb_original_name: row.get(15)?,
-                tmdb_name: row.get(16)?,
-                tmdb_alt_titles: row.get(17)?,
-                tmdb_last_updated: row.get(18)?,
+                last_update: row.get(13)?,
+                tmdb_original_name: row.get(14)?,
+                tmdb_name: row.get(15)?,
+                tmdb_alt_titles: row.get(16)?,
+                tmdb_last_updated: row.get(17)?,
+                comment: row.get(20)?,
+                user_point: row.get(21)?,
+                user_point_rank: row.get(22)?,
             })
         })
         .context("failed to query titles by tids")?;
@@ -301,27 +354,190 @@ pub fn load_titles_by_tids(conn: &Connection, tids: &[u32]) -> Result<Vec<Cached
         .context("failed to read titles rows")
 }
 
+/// Minimum query length (in characters) for the trigram FTS index.
+const FTS_MIN_QUERY_CHARS: usize = 3;
+
+/// Maps a row selected with the standard title column order to a `CachedTitle`.
+fn map_title_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CachedTitle> {
+    Ok(CachedTitle {
+        tid: row.get(0)?,
+        tmdb_series_id: row.get(1)?,
+        tmdb_season_number: row.get(2)?,
+        tmdb_season_id: row.get(3)?,
+        tmdb_movie_id: row.get(18)?,
+        tmdb_episode_group_id: row.get(19)?,
+        title: row.get(4)?,
+        short_title: row.get(5)?,
+        title_yomi: row.get(6)?,
+        title_en: row.get(7)?,
+        cat: row.get(8)?,
+        title_flag: row.get(9)?,
+        first_year: row.get(10)?,
+        first_month: row.get(11)?,
+        keywords: parse_keywords(row.get(12)?),
+        last_update: row.get(13)?,
+        tmdb_original_name: row.get(14)?,
+        tmdb_name: row.get(15)?,
+        tmdb_alt_titles: row.get(16)?,
+        tmdb_last_updated: row.get(17)?,
+        comment: row.get(20)?,
+        user_point: row.get(21)?,
+        user_point_rank: row.get(22)?,
+    })
+}
+
+/// Searches cached titles by name, reading, English title, keywords,
+/// and TMDB names, ordered by relevance.
+///
+/// Queries of three or more characters use the `titles_fts` trigram index
+/// (ranked by bm25). Shorter queries fall back to a `LIKE` scan ordered by TID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn search_titles(db: &impl DbHandle, query: &str, limit: u32) -> Result<Vec<CachedTitle>> {
+    let conn = &*db.conn()?;
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (sql, pattern) = if query.chars().count() >= FTS_MIN_QUERY_CHARS {
+        // Quote as a single FTS5 phrase so operators in the query are literal.
+        let phrase = format!("\"{}\"", query.replace('"', "\"\""));
+        (
+            "SELECT t.tid, t.tmdb_series_id, t.tmdb_season_number, t.tmdb_season_id,
+                    t.title, t.short_title, t.title_yomi, t.title_en,
+                    t.cat, t.title_flag, t.first_year, t.first_month,
+                    t.keywords, t.last_update,
+                    t.tmdb_original_name, t.tmdb_name, t.tmdb_alt_titles,
+                    t.tmdb_last_updated, t.tmdb_movie_id, t.tmdb_episode_group_id,
+                    t.comment, t.user_point, t.user_point_rank
+             FROM titles_fts
+             JOIN titles t ON t.tid = titles_fts.rowid
+             WHERE titles_fts MATCH ?1
+             ORDER BY titles_fts.rank, t.tid
+             LIMIT ?2",
+            phrase,
+        )
+    } else {
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        (
+            "SELECT tid, tmdb_series_id, tmdb_season_number, tmdb_season_id,
+                    title, short_title, title_yomi, title_en,
+                    cat, title_flag, first_year, first_month,
+                    keywords, last_update,
+                    tmdb_original_name, tmdb_name, tmdb_alt_titles,
+                    tmdb_last_updated, tmdb_movie_id, tmdb_episode_group_id,
+                    comment, user_point, user_point_rank
+             FROM titles
+             WHERE title LIKE ?1 ESCAPE '\\'
+                OR short_title LIKE ?1 ESCAPE '\\'
+                OR title_yomi LIKE ?1 ESCAPE '\\'
+                OR title_en LIKE ?1 ESCAPE '\\'
+                OR keywords LIKE ?1 ESCAPE '\\'
+                OR tmdb_name LIKE ?1 ESCAPE '\\'
+                OR tmdb_original_name LIKE ?1 ESCAPE '\\'
+             ORDER BY tid
+             LIMIT ?2",
+            format!("%{escaped}%"),
+        )
+    };
+
+    let mut stmt = conn
+        .prepare(sql)
+        .context("failed to prepare title search query")?;
+    let rows = stmt
+        .query_map(rusqlite::params![pattern, limit], map_title_row)
+        .with_context(|| format!("failed to search titles for {query:?}"))?;
+
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read title search rows")
+}
+
 /// Updates TMDB mapping for a title.
 ///
+/// Changing the series clears the episode group, which belongs to the
+/// previous series.
+///
 /// # Errors
 ///
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
 pub fn update_tmdb_mapping(
-    conn: &Connection,
+    db: &impl DbHandle,
     tid: u32,
     tmdb_series_id: Option<u64>,
     tmdb_season_number: Option<u32>,
     tmdb_season_id: Option<u64>,
 ) -> Result<()> {
+    let conn = &*db.conn()?;
     conn.execute(
-        "UPDATE titles SET tmdb_series_id = ?1, tmdb_season_number = ?2, tmdb_season_id = ?3 WHERE tid = ?4",
+        "UPDATE titles
+         SET tmdb_series_id = ?1, tmdb_season_number = ?2, tmdb_season_id = ?3,
+             tmdb_episode_group_id = CASE WHEN tmdb_series_id IS ?1 THEN tmdb_episode_group_id END
+         WHERE tid = ?4",
         rusqlite::params![tmdb_series_id, tmdb_season_number, tmdb_season_id, tid],
     )
     .with_context(|| format!("failed to update TMDB mapping for title {tid}"))?;
     Ok(())
 }
 
+/// Maps a movie title to a TMDB movie, clearing any series mapping.
+///
+/// `None` removes the movie mapping.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn update_tmdb_movie_mapping(
+    db: &impl DbHandle,
+    tid: u32,
+    tmdb_movie_id: Option<u64>,
+) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "UPDATE titles
+         SET tmdb_movie_id = ?1,
+             tmdb_series_id = CASE WHEN ?1 IS NULL THEN tmdb_series_id END,
+             tmdb_season_number = CASE WHEN ?1 IS NULL THEN tmdb_season_number END,
+             tmdb_season_id = CASE WHEN ?1 IS NULL THEN tmdb_season_id END,
+             tmdb_episode_group_id = CASE WHEN ?1 IS NULL THEN tmdb_episode_group_id END
+         WHERE tid = ?2",
+        rusqlite::params![tmdb_movie_id, tid],
+    )
+    .with_context(|| format!("failed to update TMDB movie mapping for title {tid}"))?;
+    Ok(())
+}
+
+/// Sets the TMDB episode group matched instead of the mapped season.
+///
+/// `None` removes the episode group mapping.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn update_tmdb_episode_group(
+    db: &impl DbHandle,
+    tid: u32,
+    group_id: Option<&str>,
+) -> Result<()> {
+    let conn = &*db.conn()?;
+    conn.execute(
+        "UPDATE titles SET tmdb_episode_group_id = ?1 WHERE tid = ?2",
+        rusqlite::params![group_id, tid],
+    )
+    .with_context(|| format!("failed to update TMDB episode group for title {tid}"))?;
+    Ok(())
+}
+
 /// Updates TMDB search result fields for a title.
 ///
 /// Sets `tmdb_series_id`, `tmdb_original_name`, `tmdb_name`,
@@ -332,7 +548,7 @@ pub fn update_tmdb_mapping(
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
 pub fn update_tmdb_search_result(
-    conn: &Connection,
+    db: &impl DbHandle,
     tid: u32,
     tmdb_series_id: u64,
     tmdb_original_name: &str,
@@ -340,23 +556,77 @@ pub fn update_tmdb_search_result(
     tmdb_alt_titles: &str,
     tmdb_last_updated: &str,
 ) -> Result<()> {
-    conn.execute(
-        "UPDATE titles
-         SET tmdb_series_id = ?1,
-             tmdb_original_name = ?2,
-             tmdb_name = ?3,
-             tmdb_alt_titles = ?4,
-             tmdb_last_updated = ?5
-         WHERE tid = ?6",
-        rusqlite::params![
-            tmdb_series_id,
+    let conn = &*db.conn()?;
+    write_tmdb_search_result(
+        conn,
+        tid,
+        "tmdb_series_id",
+        tmdb_series_id,
+        [
             tmdb_original_name,
             tmdb_name,
             tmdb_alt_titles,
             tmdb_last_updated,
-            tid
         ],
     )
+}
+
+/// Updates TMDB search result fields for a movie title.
+///
+/// Same as [`update_tmdb_search_result`] but stores the ID in
+/// `tmdb_movie_id`.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn update_tmdb_movie_search_result(
+    db: &impl DbHandle,
+    tid: u32,
+    tmdb_movie_id: u64,
+    tmdb_original_name: &str,
+    tmdb_name: &str,
+    tmdb_alt_titles: &str,
+    tmdb_last_updated: &str,
+) -> Result<()> {
+    let conn = &*db.conn()?;
+    write_tmdb_search_result(
+        conn,
+        tid,
+        "tmdb_movie_id",
+        tmdb_movie_id,
+        [
+            tmdb_original_name,
+            tmdb_name,
+            tmdb_alt_titles,
+            tmdb_last_updated,
+        ],
+    )
+}
+
+/// Stores a TMDB search result with the ID in `id_column`.
+///
+/// `fields` are the original name, name, alternative titles, and timestamp.
+fn write_tmdb_search_result(
+    conn: &Connection,
+    tid: u32,
+    id_column: &str,
+    tmdb_id: u64,
+    fields: [&str; 4],
+) -> Result<()> {
+    let [original_name, name, alt_titles, last_updated] = fields;
+    conn.execute(
+        &format!(
+            "UPDATE titles
+             SET {id_column} = ?1,
+                 tmdb_original_name = ?2,
+                 tmdb_name = ?3,
+                 tmdb_alt_titles = ?4,
+                 tmdb_last_updated = ?5
+             WHERE tid = ?6"
+        ),
+        rusqlite::params![tmdb_id, original_name, name, alt_titles, last_updated, tid],
+    )
     .with_context(|| format!("failed to update TMDB search result for title {tid}"))?;
     Ok(())
 }
@@ -370,7 +640,8 @@ pub fn update_tmdb_search_result(
 ///
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn update_tmdb_last_updated(conn: &Connection, tid: u32, timestamp: &str) -> Result<()> {
+pub fn update_tmdb_last_updated(db: &impl DbHandle, tid: u32, timestamp: &str) -> Result<()> {
+    let conn = &*db.conn()?;
     conn.execute(
         "UPDATE titles SET tmdb_last_updated = ?1 WHERE tid = ?2",
         rusqlite::params![timestamp, tid],
@@ -379,6 +650,105 @@ pub fn update_tmdb_last_updated(conn: &Connection, tid: u32, timestamp: &str) ->
     Ok(())
 }
 
+/// Sets the `followed` flag of `tids`. Returns the number of titles whose
+/// flag changed; unknown TIDs are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn set_titles_followed(db: &impl DbHandle, tids: &[u32], followed: bool) -> Result<usize> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+    let mut stmt = tx
+        .prepare("UPDATE titles SET followed = ?1 WHERE tid = ?2 AND followed != ?1")
+        .context("failed to prepare followed update")?;
+    let mut changed: usize = 0;
+    for tid in tids {
+        let rows = stmt
+            .execute(rusqlite::params![followed, tid])
+            .with_context(|| format!("failed to update followed flag of title {tid}"))?;
+        changed = changed.saturating_add(rows);
+    }
+    drop(stmt);
+    tx.commit().context("failed to commit transaction")?;
+    Ok(changed)
+}
+
+/// External IDs of a title, used to import and cross-check TMDB mappings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExternalIds {
+    /// `IMDb` ID (e.g. "tt13706018").
+    pub imdb_id: Option<String>,
+    /// `TheTVDB` series ID.
+    pub tvdb_id: Option<u64>,
+}
+
+/// Stores external IDs for a title. `None` fields keep their current value.
+///
+/// Returns `false` when the title does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn update_external_ids(db: &impl DbHandle, tid: u32, ids: &ExternalIds) -> Result<bool> {
+    let conn = &*db.conn()?;
+    let rows = conn
+        .execute(
+            "UPDATE titles
+             SET imdb_id = COALESCE(?1, imdb_id), tvdb_id = COALESCE(?2, tvdb_id)
+             WHERE tid = ?3",
+            rusqlite::params![ids.imdb_id, ids.tvdb_id, tid],
+        )
+        .with_context(|| format!("failed to update external IDs for title {tid}"))?;
+    Ok(rows > 0)
+}
+
+/// Loads the external IDs of a title, or `None` when it does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_external_ids(db: &impl DbHandle, tid: u32) -> Result<Option<ExternalIds>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare("SELECT imdb_id, tvdb_id FROM titles WHERE tid = ?1")
+        .context("failed to prepare external IDs query")?;
+    let mut rows = stmt
+        .query_map([tid], |row| {
+            Ok(ExternalIds {
+                imdb_id: row.get(0)?,
+                tvdb_id: row.get(1)?,
+            })
+        })
+        .with_context(|| format!("failed to query external IDs for title {tid}"))?;
+    rows.next()
+        .transpose()
+        .with_context(|| format!("failed to read external IDs for title {tid}"))
+}
+
+/// Loads the TIDs of followed titles in ascending order.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_followed_tids(db: &impl DbHandle) -> Result<Vec<u32>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare("SELECT tid FROM titles WHERE followed = 1 ORDER BY tid")
+        .context("failed to prepare followed titles query")?;
+    let rows = stmt
+        .query_map([], |row| row.get(0))
+        .context("failed to query followed titles")?;
+    rows.collect::<std::result::Result<Vec<u32>, _>>()
+        .context("failed to read followed title rows")
+}
+
 /// Deletes titles whose `cat` is not in the allowed set. Returns the number of rows deleted.
 ///
 /// Titles with `cat IS NULL` are also deleted.
@@ -387,7 +757,8 @@ pub fn update_tmdb_last_updated(conn: &Connection, tid: u32, timestamp: &str) ->
 ///
 /// Returns an error if the database operation fails.
 #[instrument(skip_all, err(level = "error"))]
-pub fn delete_titles_by_cat_not_in(conn: &Connection, allowed_cats: &[u32]) -> Result<usize> {
+pub fn delete_titles_by_cat_not_in(db: &impl DbHandle, allowed_cats: &[u32]) -> Result<usize> {
+    let conn = &*db.conn()?;
     if allowed_cats.is_empty() {
         let deleted = conn
             .execute("DELETE FROM titles", [])
@@ -433,6 +804,8 @@ mod tests {
             tmdb_series_id: None,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: String::from(title),
             short_title: None,
             title_yomi: None,
@@ -442,12 +815,14 @@ mod tests {
             first_year: None,
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::from(last_update),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         }
     }
 
@@ -620,6 +995,72 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_update_tmdb_movie_mapping_replaces_series_mapping() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let titles = vec![make_title(100, "すずめの戸締まり", "2024-01-01 00:00:00")];
+        upsert_titles(&conn, &titles).unwrap();
+        update_tmdb_mapping(&conn, 100, Some(99999), Some(1), Some(55555)).unwrap();
+
+        // Act
+        update_tmdb_movie_mapping(&conn, 100, Some(916_224)).unwrap();
+        let mapped = load_titles(&conn).unwrap();
+        update_tmdb_movie_search_result(
+            &conn,
+            100,
+            916_225,
+            "すずめの戸締まり",
+            "Suzume",
+            "[]",
+            "2026-02-19T10:30:00Z",
+        )
+        .unwrap();
+        let searched = load_titles(&conn).unwrap();
+        upsert_titles(
+            &conn,
+            &[make_title(100, "すずめの戸締まり", "2024-02-01 00:00:00")],
+        )
+        .unwrap();
+        let upserted = load_titles(&conn).unwrap();
+
+        // Assert
+        assert_eq!(mapped[0].tmdb_movie_id, Some(916_224));
+        assert_eq!(mapped[0].tmdb_series_id, None);
+        assert_eq!(mapped[0].tmdb_season_number, None);
+        assert_eq!(mapped[0].tmdb_season_id, None);
+        assert_eq!(searched[0].tmdb_movie_id, Some(916_225));
+        assert_eq!(searched[0].tmdb_name.as_deref(), Some("Suzume"));
+        assert_eq!(upserted[0].tmdb_movie_id, Some(916_225));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_update_tmdb_episode_group_survives_upsert_until_series_changes() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_titles(&conn, &[make_title(100, "Test", "2024-01-01 00:00:00")]).unwrap();
+        update_tmdb_mapping(&conn, 100, Some(120_089), Some(1), None).unwrap();
+
+        // Act
+        update_tmdb_episode_group(&conn, 100, Some("6310c3c1a4a9b9007e1f4f21")).unwrap();
+        upsert_titles(&conn, &[make_title(100, "Test", "2024-02-01 00:00:00")]).unwrap();
+        let upserted = load_titles(&conn).unwrap();
+        update_tmdb_mapping(&conn, 100, Some(120_089), Some(2), None).unwrap();
+        let same_series = load_titles(&conn).unwrap();
+        update_tmdb_mapping(&conn, 100, Some(99_999), Some(1), None).unwrap();
+        let other_series = load_titles(&conn).unwrap();
+
+        // Assert
+        assert_eq!(
+            upserted[0].tmdb_episode_group_id.as_deref(),
+            Some("6310c3c1a4a9b9007e1f4f21")
+        );
+        assert!(same_series[0].tmdb_episode_group_id.is_some());
+        assert!(other_series[0].tmdb_episode_group_id.is_none());
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn test_upsert_preserves_tmdb_search_result() {
@@ -1011,4 +1452,151 @@ mod tests {
         assert_eq!(deleted, 0);
         assert_eq!(remaining.len(), 2);
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_search_titles_fts_substring() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let mut spy = make_title(100, "SPY×FAMILY", "2024-01-01 00:00:00");
+        spy.title_yomi = Some(String::from("すぱいふぁみりー"));
+        let titles = vec![
+            spy,
+            make_title(101, "ぼっち・ざ・ろっく！", "2024-01-01 00:00:00"),
+        ];
+        upsert_titles(&conn, &titles).unwrap();
+
+        // Act
+        let by_title = search_titles(&conn, "FAMILY", 10).unwrap();
+        let by_yomi = search_titles(&conn, "ふぁみり", 10).unwrap();
+
+        // Assert
+        assert_eq!(by_title.len(), 1);
+        assert_eq!(by_title[0].tid, 100);
+        assert_eq!(by_yomi.len(), 1);
+        assert_eq!(by_yomi[0].tid, 100);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_search_titles_tracks_updates() {
+        // Arrange: TMDB name is set after the initial insert
+        let (conn, _dir) = setup_db();
+        upsert_titles(&conn, &[make_title(100, "Title", "2024-01-01 00:00:00")]).unwrap();
+        update_tmdb_search_result(
+            &conn,
+            100,
+            42,
+            "Original Name",
+            "Localized Name",
+            "[]",
+            "2024-01-02T00:00:00Z",
+        )
+        .unwrap();
+
+        // Act
+        let result = search_titles(&conn, "Localized", 10).unwrap();
+
+        // Assert
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tmdb_series_id, Some(42));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_search_titles_short_query_uses_like() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let titles = vec![
+            make_title(100, "推しの子", "2024-01-01 00:00:00"),
+            make_title(101, "100%_title", "2024-01-01 00:00:00"),
+        ];
+        upsert_titles(&conn, &titles).unwrap();
+
+        // Act
+        let result = search_titles(&conn, "推し", 10).unwrap();
+        let literal = search_titles(&conn, "%_", 10).unwrap();
+
+        // Assert
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tid, 100);
+        assert_eq!(literal.len(), 1);
+        assert_eq!(literal[0].tid, 101);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_search_titles_empty_and_quoted_query() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_titles(&conn, &[make_title(100, "Title", "2024-01-01 00:00:00")]).unwrap();
+
+        // Act
+        let empty = search_titles(&conn, "  ", 10).unwrap();
+        let quoted = search_titles(&conn, "\"Tit", 10).unwrap();
+
+        // Assert
+        assert!(empty.is_empty());
+        assert!(quoted.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_set_titles_followed_survives_upsert() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_titles(
+            &conn,
+            &[
+                make_title(100, "A", "2024-01-01 00:00:00"),
+                make_title(101, "B", "2024-01-01 00:00:00"),
+            ],
+        )
+        .unwrap();
+
+        // Act: follow twice (second is a no-op), unknown TID ignored, then re-sync
+        let followed = set_titles_followed(&conn, &[101, 100, 999], true).unwrap();
+        let again = set_titles_followed(&conn, &[100], true).unwrap();
+        upsert_titles(&conn, &[make_title(100, "A2", "2024-02-01 00:00:00")]).unwrap();
+        let unfollowed = set_titles_followed(&conn, &[101], false).unwrap();
+
+        // Assert
+        assert_eq!(followed, 2);
+        assert_eq!(again, 0);
+        assert_eq!(unfollowed, 1);
+        assert_eq!(load_followed_tids(&conn).unwrap(), vec![100]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_update_external_ids_merges_and_survives_upsert() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_titles(&conn, &[make_title(100, "A", "2024-01-01 00:00:00")]).unwrap();
+
+        // Act
+        let imdb = ExternalIds {
+            imdb_id: Some(String::from("tt13706018")),
+            tvdb_id: None,
+        };
+        let tvdb = ExternalIds {
+            imdb_id: None,
+            tvdb_id: Some(405_920),
+        };
+        assert!(update_external_ids(&conn, 100, &imdb).unwrap());
+        assert!(update_external_ids(&conn, 100, &tvdb).unwrap());
+        let unknown = update_external_ids(&conn, 999, &imdb).unwrap();
+        upsert_titles(&conn, &[make_title(100, "A2", "2024-02-01 00:00:00")]).unwrap();
+
+        // Assert
+        assert!(!unknown);
+        assert_eq!(
+            load_external_ids(&conn, 100).unwrap(),
+            Some(ExternalIds {
+                imdb_id: Some(String::from("tt13706018")),
+                tvdb_id: Some(405_920),
+            })
+        );
+        assert_eq!(load_external_ids(&conn, 999).unwrap(), None);
+    }
 }
@@ -0,0 +1,224 @@
+//! Watchlist CRUD operations with per-title notification preferences.
+
+use anyhow::{Context, Result};
+use tracing::instrument;
+
+use crate::pool::DbHandle;
+
+/// A watched title and the program changes that should be reported for it.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchlistEntry {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Notify when a new program appears.
+    pub notify_new_program: bool,
+    /// Notify when an existing program's start/end time changes.
+    pub notify_time_change: bool,
+    /// Notify when a program is flagged as the finale.
+    pub notify_finale: bool,
+    /// UTC timestamp when the title was added (set by the DB on insert).
+    pub added_at: String,
+}
+
+/// Adds or updates watchlist entries. Returns the number of rows changed.
+///
+/// Existing entries keep their `added_at` timestamp; only the notification
+/// preferences are updated.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn upsert_watchlist_entries(db: &impl DbHandle, entries: &[WatchlistEntry]) -> Result<usize> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+
+    let mut stmt = tx
+        .prepare(
+            "INSERT INTO watchlist (tid, notify_new_program, notify_time_change, notify_finale)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(tid) DO UPDATE SET
+                notify_new_program = excluded.notify_new_program,
+                notify_time_change = excluded.notify_time_change,
+                notify_finale      = excluded.notify_finale
+            WHERE watchlist.notify_new_program != excluded.notify_new_program
+               OR watchlist.notify_time_change != excluded.notify_time_change
+               OR watchlist.notify_finale      != excluded.notify_finale",
+        )
+        .context("failed to prepare watchlist upsert")?;
+
+    let mut changed: usize = 0;
+    for e in entries {
+        let rows = stmt
+            .execute(rusqlite::params![
+                e.tid,
+                e.notify_new_program,
+                e.notify_time_change,
+                e.notify_finale
+            ])
+            .with_context(|| format!("failed to upsert watchlist entry {}", e.tid))?;
+        changed = changed.saturating_add(rows);
+    }
+
+    drop(stmt);
+    tx.commit().context("failed to commit watchlist")?;
+    Ok(changed)
+}
+
+/// Loads all watchlist entries ordered by TID.
+///
+/// # Errors
+///
+/// Returns an error if the database query fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub fn load_watchlist(db: &impl DbHandle) -> Result<Vec<WatchlistEntry>> {
+    let conn = &*db.conn()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT tid, notify_new_program, notify_time_change, notify_finale, added_at
+             FROM watchlist
+             ORDER BY tid",
+        )
+        .context("failed to prepare watchlist query")?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(WatchlistEntry {
+                tid: row.get(0)?,
+                notify_new_program: row.get(1)?,
+                notify_time_change: row.get(2)?,
+                notify_finale: row.get(3)?,
+                added_at: row.get(4)?,
+            })
+        })
+        .context("failed to query watchlist")?;
+
+    rows.collect::<std::result::Result<Vec<_>, _>>()
+        .context("failed to read watchlist rows")
+}
+
+/// Removes titles from the watchlist. Returns the number of rows deleted.
+///
+/// # Errors
+///
+/// Returns an error if the database operation fails.
+#[instrument(skip_all, err(level = "error"))]
+pub fn delete_watchlist_entries(db: &impl DbHandle, tids: &[u32]) -> Result<usize> {
+    let conn = &*db.conn()?;
+    let tx = conn
+        .unchecked_transaction()
+        .context("failed to begin transaction")?;
+
+    let mut deleted: usize = 0;
+    for tid in tids {
+        let rows = tx
+            .execute("DELETE FROM watchlist WHERE tid = ?1", [tid])
+            .with_context(|| format!("failed to delete watchlist entry {tid}"))?;
+        deleted = deleted.saturating_add(rows);
+    }
+
+    tx.commit().context("failed to commit watchlist delete")?;
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::indexing_slicing)]
+
+    use rusqlite::Connection;
+
+    use super::*;
+    use crate::connection::open_db;
+
+    fn setup_db() -> (Connection, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_db(Some(&dir.path().to_path_buf())).unwrap();
+        (conn, dir)
+    }
+
+    fn make_entry(tid: u32, new_program: bool, time_change: bool, finale: bool) -> WatchlistEntry {
+        WatchlistEntry {
+            tid,
+            notify_new_program: new_program,
+            notify_time_change: time_change,
+            notify_finale: finale,
+            added_at: String::new(),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_and_load_watchlist() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let entries = vec![
+            make_entry(200, true, false, false),
+            make_entry(100, true, true, true),
+        ];
+
+        // Act
+        let changed = upsert_watchlist_entries(&conn, &entries).unwrap();
+        let loaded = load_watchlist(&conn).unwrap();
+
+        // Assert
+        assert_eq!(changed, 2);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].tid, 100);
+        assert!(loaded[0].notify_finale);
+        assert!(!loaded[1].notify_time_change);
+        assert!(!loaded[0].added_at.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_upsert_watchlist_updates_prefs_keeps_added_at() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        upsert_watchlist_entries(&conn, &[make_entry(100, true, false, false)]).unwrap();
+        conn.execute(
+            "UPDATE watchlist SET added_at = '2024-01-01T00:00:00Z' WHERE tid = 100",
+            [],
+        )
+        .unwrap();
+
+        // Act
+        let unchanged =
+            upsert_watchlist_entries(&conn, &[make_entry(100, true, false, false)]).unwrap();
+        let changed =
+            upsert_watchlist_entries(&conn, &[make_entry(100, false, true, false)]).unwrap();
+        let loaded = load_watchlist(&conn).unwrap();
+
+        // Assert
+        assert_eq!(unchanged, 0);
+        assert_eq!(changed, 1);
+        assert!(!loaded[0].notify_new_program);
+        assert!(loaded[0].notify_time_change);
+        assert_eq!(loaded[0].added_at, "2024-01-01T00:00:00Z");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_delete_watchlist_entries() {
+        // Arrange
+        let (conn, _dir) = setup_db();
+        let entries = vec![
+            make_entry(100, true, false, false),
+            make_entry(200, true, false, false),
+        ];
+        upsert_watchlist_entries(&conn, &entries).unwrap();
+
+        // Act
+        let deleted = delete_watchlist_entries(&conn, &[100, 999]).unwrap();
+        let loaded = load_watchlist(&conn).unwrap();
+
+        // Assert
+        assert_eq!(deleted, 1);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].tid, 200);
+    }
+}
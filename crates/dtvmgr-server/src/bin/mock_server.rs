@@ -0,0 +1,76 @@
+//! dtvmgr-mock-server - serves recorded Syoboi / TMDB fixtures over HTTP.
+//!
+//! Point the CLI at it with `DTVMGR_SYOBOI_URL=http://ADDR/db.php` and
+//! `DTVMGR_TMDB_URL=http://ADDR/3/`. See [`dtvmgr_server::mock`] for the
+//! request-to-fixture mapping.
+
+use std::io::Write as _;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use dtvmgr_server::mock::{MockOptions, serve_mock};
+use tracing_subscriber::filter::EnvFilter;
+
+/// Serves recorded Syoboi XML and TMDB JSON fixtures for integration tests
+/// and offline demos.
+#[derive(Debug, Parser)]
+#[command(name = "dtvmgr-mock-server", version)]
+struct Args {
+    /// Address to listen on (port 0 picks a free port).
+    #[arg(long, default_value = "127.0.0.1:8787")]
+    listen: SocketAddr,
+    /// Fixtures directory containing `syoboi/` and `tmdb/`.
+    #[arg(long, value_name = "DIR", default_value = "fixtures")]
+    fixtures: PathBuf,
+    /// Delay before every response, in milliseconds.
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    latency_ms: u64,
+    /// Requests answered per window before responding 429 with `Retry-After`.
+    #[arg(long, value_name = "N")]
+    rate_limit: Option<u32>,
+    /// Length of the rate limit window, in seconds.
+    #[arg(long, value_name = "SECS", default_value_t = 10)]
+    rate_window_secs: u64,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+    let args = Args::parse();
+    if !args.fixtures.is_dir() {
+        anyhow::bail!("fixtures directory not found: {}", args.fixtures.display());
+    }
+
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("failed to bind {}", args.listen))?;
+    let addr = listener
+        .local_addr()
+        .context("failed to read bound address")?;
+    // The bound address goes to stdout so scripts can pick up a port 0 choice.
+    writeln!(std::io::stdout().lock(), "http://{addr}").context("failed to write address")?;
+
+    let options = MockOptions {
+        latency: Duration::from_millis(args.latency_ms),
+        rate_limit: args.rate_limit,
+        rate_window: Duration::from_secs(args.rate_window_secs),
+        ..MockOptions::new(args.fixtures)
+    };
+    serve_mock(listener, options, async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %e, "failed to listen for Ctrl-C");
+            core::future::pending::<()>().await;
+        }
+    })
+    .await?;
+    tracing::info!("Mock server stopped");
+    Ok(())
+}
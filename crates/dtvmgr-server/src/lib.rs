@@ -0,0 +1,119 @@
+//! HTTP REST API over the dtvmgr local cache.
+//!
+//! Serves the merged Syoboi + TMDB data as JSON so that other tools
+//! (recorders, dashboards) can consume it without linking the Rust crates.
+//! The server is read-only and checks connections out of a shared
+//! [`dtvmgr_db::DbPool`], so requests run concurrently with the daemon and
+//! other CLI invocations.
+//! `GET /metrics` is answered without touching the database schema.
+
+/// Prometheus `/metrics` exposition.
+pub mod metrics;
+
+/// Request routing and JSON responses.
+pub mod routes;
+
+/// Fixture-backed Syoboi / TMDB stand-in (`mock-server` feature).
+#[cfg(feature = "mock-server")]
+pub mod mock;
+
+use core::convert::Infallible;
+use core::future::Future;
+
+use anyhow::{Context, Result};
+use dtvmgr_db::DbPool;
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{CONTENT_TYPE, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, Uri};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tracing::instrument;
+
+pub use routes::{Reply, route};
+
+/// Accepts connections on `listener` until `shutdown` completes.
+///
+/// Each connection is served on its own task; requests check a database
+/// connection out of `pool`, which callers may share with other tasks.
+/// Checking out a connection may wait for one to be returned and queries
+/// are synchronous, so requests are handled on the blocking thread pool
+/// rather than the runtime's worker threads.
+///
+/// # Errors
+///
+/// Returns an error if accepting a connection fails.
+#[instrument(skip_all, err(level = "error"))]
+pub async fn serve(
+    listener: TcpListener,
+    pool: DbPool,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    tokio::pin!(shutdown);
+    loop {
+        let (stream, peer) = tokio::select! {
+            () = &mut shutdown => break,
+            accepted = listener.accept() => accepted.context("failed to accept connection")?,
+        };
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req: Request<Incoming>| {
+                let pool = pool.clone();
+                let method = req.method().clone();
+                let uri = req.uri().clone();
+                async move {
+                    let response =
+                        tokio::task::spawn_blocking(move || handle(&method, &uri, &pool))
+                            .await
+                            .unwrap_or_else(|e| {
+                                respond(Reply::internal_error(&anyhow::Error::new(e)))
+                            });
+                    Ok::<_, Infallible>(response)
+                }
+            });
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(%peer, error = %e, "connection closed with error");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Answers one request from a pooled database connection.
+fn handle(method: &Method, uri: &Uri, pool: &DbPool) -> Response<Full<Bytes>> {
+    if method == Method::GET && uri.path() == "/metrics" {
+        let mut response = Response::new(Full::new(Bytes::from(metrics::render(pool.dir()))));
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(metrics::CONTENT_TYPE),
+        );
+        return response;
+    }
+    let reply = match pool.get() {
+        Ok(conn) => route(&conn, method, uri.path(), uri.query()),
+        Err(e) => Reply::internal_error(&e),
+    };
+    tracing::info!(
+        method = %method,
+        path = uri.path(),
+        status = reply.status.as_u16(),
+        "request"
+    );
+    respond(reply)
+}
+
+/// Builds the JSON response of `reply`.
+fn respond(reply: Reply) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(reply.body)));
+    *response.status_mut() = reply.status;
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    response
+}
@@ -0,0 +1,57 @@
+//! Prometheus `/metrics` exposition.
+//!
+//! Renders the process-wide [`dtvmgr_api::prometheus`] registry (API
+//! requests, rate-limiter waits, and the sync metrics the daemon records)
+//! plus the current database file size.
+
+use std::path::PathBuf;
+
+/// `Content-Type` of the Prometheus text format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Updates the database size gauge and renders every metric.
+///
+/// The size (including the WAL file) is read on each scrape; a missing or
+/// unresolvable database leaves the gauge out.
+#[must_use]
+pub fn render(data_dir: Option<&PathBuf>) -> String {
+    let registry = dtvmgr_api::prometheus::global();
+    if let Ok(db_path) = dtvmgr_db::resolve_db_path(data_dir)
+        && let Ok(meta) = std::fs::metadata(&db_path)
+    {
+        let mut wal = db_path.into_os_string();
+        wal.push("-wal");
+        let wal_len = std::fs::metadata(&wal).map_or(0, |m| m.len());
+        #[allow(clippy::cast_precision_loss, clippy::as_conversions)]
+        let bytes = meta.len().saturating_add(wal_len) as f64;
+        registry.set(
+            "dtvmgr_db_size_bytes",
+            "Size of the SQLite database including its WAL file.",
+            &[],
+            bytes,
+        );
+    }
+    registry.render()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_render_includes_db_size() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().to_path_buf();
+        drop(dtvmgr_db::open_db(Some(&data_dir)).unwrap());
+
+        // Act
+        let text = render(Some(&data_dir));
+
+        // Assert
+        assert!(text.contains("# TYPE dtvmgr_db_size_bytes gauge"));
+        assert!(!text.contains("dtvmgr_db_size_bytes 0\n"));
+    }
+}
@@ -0,0 +1,474 @@
+//! Fixture-backed stand-in for the Syoboi and TMDB APIs.
+//!
+//! Serves recorded responses from a fixtures directory laid out like the
+//! repository's `fixtures/` over plain HTTP (the API clients accept `http`
+//! base URLs, so no TLS setup is needed). The CLI can then be exercised
+//! end to end, or demoed, without network access by pointing it at
+//! `DTVMGR_SYOBOI_URL=http://ADDR/db.php` and `DTVMGR_TMDB_URL=http://ADDR/3/`.
+//!
+//! | Request | Fixture |
+//! |---------|---------|
+//! | `GET /db.php?Command=TitleLookup&TID=6309` | `syoboi/title_lookup_6309.xml` |
+//! | `GET /db.php?Command=ChLookup` | `syoboi/ch_lookup_all.xml` |
+//! | `GET /cal_chk.php` | `syoboi/cal_chk.xml` |
+//! | `GET /3/tv/120089` | `tmdb/tv_details_120089.json` |
+//! | `GET /3/tv/120089/season/1` | `tmdb/tv_season_120089_1.json` |
+//! | `GET /3/tv/120089/season/1/episode/1` | `tmdb/tv_episode_120089_1_1.json` |
+//! | `GET /3/tv/120089/watch/providers` | `tmdb/tv_watch_providers_120089.json` |
+//! | `GET /3/search/multi?query=Lupin` | `tmdb/search_multi_lupin.json` |
+//! | `GET /3/genre/tv/list` | `tmdb/genre_tv_list.json` |
+//!
+//! Syoboi lookups without a single numeric TID use `{command}_all.xml`,
+//! and any lookup without a fixture answers `syoboi/empty_response.xml`.
+//! Searches without a fixture answer `search_{kind}_empty.json`; other
+//! TMDB paths without one answer 404 like TMDB does.
+
+use core::convert::Infallible;
+use core::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{CONTENT_TYPE, HeaderValue, RETRY_AFTER};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tracing::instrument;
+
+/// Body of TMDB's 404 response.
+const TMDB_NOT_FOUND: &str = r#"{"success":false,"status_code":34,"status_message":"The resource you requested could not be found."}"#;
+
+/// Behavior of the mock server.
+#[derive(Debug, Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MockOptions {
+    /// Fixtures directory containing `syoboi/` and `tmdb/`.
+    pub fixtures: PathBuf,
+    /// Delay before every response.
+    pub latency: Duration,
+    /// Requests answered per `rate_window`; further requests get HTTP 429
+    /// with `Retry-After`. `None` disables rate limiting.
+    pub rate_limit: Option<u32>,
+    /// Length of the rate limit window.
+    pub rate_window: Duration,
+}
+
+impl MockOptions {
+    /// Serves `fixtures` without latency or rate limiting.
+    #[must_use]
+    pub const fn new(fixtures: PathBuf) -> Self {
+        Self {
+            fixtures,
+            latency: Duration::ZERO,
+            rate_limit: None,
+            rate_window: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Requests counted in the current rate limit window.
+#[derive(Debug)]
+struct Window {
+    /// Start of the window.
+    started: Instant,
+    /// Requests answered in the window.
+    used: u32,
+}
+
+/// Shared state of all connections.
+#[derive(Debug)]
+struct MockState {
+    /// Server behavior.
+    options: MockOptions,
+    /// Rate limit window.
+    window: Mutex<Window>,
+}
+
+/// Outcome of the rate limit check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Admission {
+    /// Whether the request may be answered.
+    allowed: bool,
+    /// Requests left in the window.
+    remaining: u32,
+    /// Time until the window resets.
+    reset: Duration,
+}
+
+impl MockState {
+    /// Counts one request against the rate limit.
+    fn admit(&self, limit: u32) -> Admission {
+        let mut window = self.window.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        if now.duration_since(window.started) >= self.options.rate_window {
+            window.started = now;
+            window.used = 0;
+        }
+        let reset = self
+            .options
+            .rate_window
+            .saturating_sub(now.duration_since(window.started));
+        let allowed = window.used < limit;
+        if allowed {
+            window.used = window.used.saturating_add(1);
+        }
+        Admission {
+            allowed,
+            remaining: limit.saturating_sub(window.used),
+            reset,
+        }
+    }
+}
+
+/// Serves fixtures on `listener` until `shutdown` completes.
+///
+/// # Errors
+///
+/// Returns an error if accepting a connection fails.
+#[allow(clippy::module_name_repetitions)]
+#[instrument(skip_all, err(level = "error"))]
+pub async fn serve_mock(
+    listener: TcpListener,
+    options: MockOptions,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let state = Arc::new(MockState {
+        options,
+        window: Mutex::new(Window {
+            started: Instant::now(),
+            used: 0,
+        }),
+    });
+    tokio::pin!(shutdown);
+    loop {
+        let (stream, peer) = tokio::select! {
+            () = &mut shutdown => break,
+            accepted = listener.accept() => accepted.context("failed to accept connection")?,
+        };
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let service = service_fn(move |req: Request<Incoming>| {
+                let state = Arc::clone(&state);
+                async move { Ok::<_, Infallible>(handle(&req, &state).await) }
+            });
+            if let Err(e) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(%peer, error = %e, "connection closed with error");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Answers one request from the fixtures.
+async fn handle(req: &Request<Incoming>, state: &MockState) -> Response<Full<Bytes>> {
+    let options = &state.options;
+    if !options.latency.is_zero() {
+        tokio::time::sleep(options.latency).await;
+    }
+    let uri = req.uri();
+    let admission = options.rate_limit.map(|limit| state.admit(limit));
+
+    let (status, content_type, body) = if admission.is_some_and(|a| !a.allowed) {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            "text/plain; charset=utf-8",
+            Bytes::from_static(b"Too Many Requests"),
+        )
+    } else {
+        let candidates = fixture_candidates(uri.path(), uri.query());
+        match candidates
+            .iter()
+            .find_map(|name| read_fixture(&options.fixtures, name))
+        {
+            Some((name, body)) => (StatusCode::OK, content_type_of(&name), body),
+            None if uri.path().starts_with("/3/") => (
+                StatusCode::NOT_FOUND,
+                "application/json; charset=utf-8",
+                Bytes::from_static(TMDB_NOT_FOUND.as_bytes()),
+            ),
+            None => (
+                StatusCode::NOT_FOUND,
+                "text/plain; charset=utf-8",
+                Bytes::from_static(b"Not Found"),
+            ),
+        }
+    };
+    tracing::info!(
+        method = %req.method(),
+        path = uri.path(),
+        query = uri.query().unwrap_or_default(),
+        status = status.as_u16(),
+        "request"
+    );
+
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let (Some(limit), Some(admission)) = (options.rate_limit, admission) {
+        // Whole seconds, rounded up so clients never retry too early.
+        let reset = admission.reset.as_secs().saturating_add(1);
+        headers.insert("x-ratelimit-limit", HeaderValue::from(limit));
+        headers.insert(
+            "x-ratelimit-remaining",
+            HeaderValue::from(admission.remaining),
+        );
+        headers.insert("x-ratelimit-reset", HeaderValue::from(reset));
+        if !admission.allowed {
+            headers.insert(RETRY_AFTER, HeaderValue::from(reset));
+        }
+    }
+    response
+}
+
+/// Reads fixture `name` under `dir`, or `None` when it does not exist.
+fn read_fixture(dir: &Path, name: &str) -> Option<(String, Bytes)> {
+    let path = dir.join(name);
+    match std::fs::read(&path) {
+        Ok(body) => Some((name.to_owned(), Bytes::from(body))),
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(path = %path.display(), error = %e, "failed to read fixture");
+            }
+            None
+        }
+    }
+}
+
+/// Content type of a fixture by extension.
+fn content_type_of(name: &str) -> &'static str {
+    if Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"))
+    {
+        "text/xml; charset=utf-8"
+    } else {
+        "application/json; charset=utf-8"
+    }
+}
+
+/// Returns the fixture files answering a request, most specific first
+/// (relative to the fixtures directory). Empty for unknown paths.
+#[must_use]
+pub fn fixture_candidates(path: &str, query: Option<&str>) -> Vec<String> {
+    let params: Vec<(String, String)> =
+        url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+    let param = |key: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+
+    match path {
+        "/db.php" => {
+            let Some(command) = param("Command").filter(|c| is_safe_segment(c)) else {
+                return Vec::new();
+            };
+            let command = to_snake_case(command);
+            let target = param("TID")
+                .filter(|tid| !tid.is_empty() && tid.bytes().all(|b| b.is_ascii_digit()))
+                .unwrap_or("all");
+            let mut candidates = vec![format!("syoboi/{command}_{target}.xml")];
+            if target != "all" {
+                candidates.push(format!("syoboi/{command}_all.xml"));
+            }
+            candidates.push(String::from("syoboi/empty_response.xml"));
+            candidates
+        }
+        "/cal_chk.php" => vec![String::from("syoboi/cal_chk.xml")],
+        _ => path
+            .strip_prefix("/3/")
+            .and_then(|rest| tmdb_candidates(rest, param("query")))
+            .unwrap_or_default(),
+    }
+}
+
+/// Fixture names for a TMDB path below `/3/`.
+fn tmdb_candidates(rest: &str, query: Option<&str>) -> Option<Vec<String>> {
+    let segments: Vec<&str> = rest.trim_end_matches('/').split('/').collect();
+    if !segments.iter().all(|s| is_safe_segment(s)) {
+        return None;
+    }
+    let is_id = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let name = match segments.as_slice() {
+        ["search", kind] => {
+            let slug: String = query
+                .unwrap_or_default()
+                .to_lowercase()
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                .collect();
+            return Some(vec![
+                format!("tmdb/search_{kind}_{slug}.json"),
+                format!("tmdb/search_{kind}_empty.json"),
+            ]);
+        }
+        [kind @ ("tv" | "movie"), id] if is_id(id) => format!("{kind}_details_{id}"),
+        ["tv", id, "season", season] if is_id(id) => format!("tv_season_{id}_{season}"),
+        ["tv", id, "season", season, "episode", episode] if is_id(id) => {
+            format!("tv_episode_{id}_{season}_{episode}")
+        }
+        [kind @ ("tv" | "movie"), id, sub @ ..] if is_id(id) => {
+            format!("{kind}_{}_{id}", sub.join("_"))
+        }
+        _ => segments.join("_"),
+    };
+    Some(vec![format!("tmdb/{name}.json")])
+}
+
+/// Whether a path segment or command is safe to use in a file name.
+fn is_safe_segment(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+/// Converts a Syoboi command (`ChGroupLookup`) to `ch_group_lookup`.
+fn to_snake_case(command: &str) -> String {
+    let mut out = String::with_capacity(command.len().saturating_add(4));
+    for (i, c) in command.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    #[test]
+    fn test_fixture_candidates_maps_syoboi_requests() {
+        // Act & Assert
+        assert_eq!(
+            fixture_candidates("/db.php", Some("Command=TitleLookup&TID=6309")),
+            vec![
+                "syoboi/title_lookup_6309.xml",
+                "syoboi/title_lookup_all.xml",
+                "syoboi/empty_response.xml",
+            ]
+        );
+        assert_eq!(
+            fixture_candidates("/db.php", Some("Command=ChGroupLookup")),
+            vec![
+                "syoboi/ch_group_lookup_all.xml",
+                "syoboi/empty_response.xml"
+            ]
+        );
+        assert_eq!(
+            fixture_candidates("/db.php", Some("Command=ProgLookup&TID=6309,7667"))[0],
+            "syoboi/prog_lookup_all.xml"
+        );
+        assert!(fixture_candidates("/db.php", Some("Command=../etc")).is_empty());
+        assert_eq!(
+            fixture_candidates("/cal_chk.php", Some("user=a&days=7")),
+            vec!["syoboi/cal_chk.xml"]
+        );
+    }
+
+    #[test]
+    fn test_fixture_candidates_maps_tmdb_paths() {
+        // Arrange
+        let cases = [
+            ("/3/tv/120089", "tmdb/tv_details_120089.json"),
+            ("/3/movie/916224", "tmdb/movie_details_916224.json"),
+            ("/3/tv/120089/season/1", "tmdb/tv_season_120089_1.json"),
+            (
+                "/3/tv/120089/season/1/episode/1",
+                "tmdb/tv_episode_120089_1_1.json",
+            ),
+            (
+                "/3/tv/120089/watch/providers",
+                "tmdb/tv_watch_providers_120089.json",
+            ),
+            (
+                "/3/movie/916224/alternative_titles",
+                "tmdb/movie_alternative_titles_916224.json",
+            ),
+            (
+                "/3/tv/episode_group/6310c3c1a4a9b9007e1f4f21",
+                "tmdb/tv_episode_group_6310c3c1a4a9b9007e1f4f21.json",
+            ),
+            ("/3/find/tt13706018", "tmdb/find_tt13706018.json"),
+            ("/3/genre/tv/list", "tmdb/genre_tv_list.json"),
+            ("/3/configuration", "tmdb/configuration.json"),
+        ];
+
+        // Act & Assert
+        for (path, expected) in cases {
+            assert_eq!(fixture_candidates(path, None), vec![expected], "{path}");
+        }
+        assert_eq!(
+            fixture_candidates("/3/search/multi", Some("query=Lupin&language=ja-JP")),
+            vec![
+                "tmdb/search_multi_lupin.json",
+                "tmdb/search_multi_empty.json"
+            ]
+        );
+        assert!(fixture_candidates("/3/tv/../secret", None).is_empty());
+        assert!(fixture_candidates("/unknown", None).is_empty());
+    }
+
+    #[test]
+    fn test_fixture_candidates_resolve_to_repository_fixtures() {
+        // Arrange
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../fixtures");
+
+        // Act
+        let (name, body) = fixture_candidates("/3/tv/120089", None)
+            .iter()
+            .find_map(|name| read_fixture(&dir, name))
+            .unwrap();
+
+        // Assert
+        assert_eq!(name, "tmdb/tv_details_120089.json");
+        assert!(!body.is_empty());
+        assert_eq!(content_type_of(&name), "application/json; charset=utf-8");
+    }
+
+    #[test]
+    fn test_admit_limits_requests_per_window() {
+        // Arrange
+        let state = MockState {
+            options: MockOptions {
+                rate_limit: Some(2),
+                rate_window: Duration::from_mins(1),
+                ..MockOptions::new(PathBuf::new())
+            },
+            window: Mutex::new(Window {
+                started: Instant::now(),
+                used: 0,
+            }),
+        };
+
+        // Act
+        let results: Vec<Admission> = (0..3).map(|_| state.admit(2)).collect();
+
+        // Assert
+        assert!(results.iter().take(2).all(|a| a.allowed));
+        assert_eq!(
+            results.iter().map(|a| a.remaining).collect::<Vec<_>>(),
+            vec![1, 0, 0]
+        );
+        assert!(results.last().is_some_and(|a| !a.allowed));
+    }
+}
@@ -0,0 +1,283 @@
+//! Request routing for the REST API.
+//!
+//! | Method | Path | Response |
+//! |--------|------|----------|
+//! | GET | `/titles` | all cached titles |
+//! | GET | `/titles/{tid}/programs` | programs of one title |
+//! | GET | `/channels` | all cached channels |
+//! | GET | `/search?q=&limit=` | titles matching a keyword |
+//! | GET | `/jobs` | background jobs, newest first |
+//!
+//! `GET /metrics` (Prometheus text) is served by [`crate::metrics`] before
+//! routing.
+
+use anyhow::Result;
+use dtvmgr_db::Connection;
+use hyper::{Method, StatusCode};
+use serde::Serialize;
+
+/// Default number of `/search` results.
+const DEFAULT_SEARCH_LIMIT: u32 = 20;
+
+/// Status and JSON body of a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    /// HTTP status.
+    pub status: StatusCode,
+    /// JSON body.
+    pub body: String,
+}
+
+/// One entry of `GET /jobs`.
+#[derive(Serialize)]
+struct JobStatus {
+    /// Job ID.
+    id: i64,
+    /// Job kind (`sync`, `prefetch`, `prune`, `notify`).
+    kind: String,
+    /// Job state (`queued`, `running`, `succeeded`, `failed`, `cancelled`).
+    state: String,
+    /// When a queued job runs next; `null` for jobs that are not queued.
+    next_run: Option<String>,
+    /// Error message of the last failed attempt.
+    last_error: Option<String>,
+}
+
+impl From<dtvmgr_db::JobRecord> for JobStatus {
+    fn from(job: dtvmgr_db::JobRecord) -> Self {
+        Self {
+            next_run: (job.state == "queued").then_some(job.scheduled_at),
+            id: job.id,
+            kind: job.kind,
+            state: job.state,
+            last_error: job.last_error,
+        }
+    }
+}
+
+/// Error body (`{"error": "..."}`).
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    /// Human-readable message.
+    error: &'a str,
+}
+
+impl Reply {
+    /// Builds a `200 OK` reply from a serializable value.
+    fn ok<T: Serialize + ?Sized>(value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self {
+                status: StatusCode::OK,
+                body,
+            },
+            Err(e) => Self::internal_error(&e.into()),
+        }
+    }
+
+    /// Builds an error reply with `message` in the body.
+    fn error(status: StatusCode, message: &str) -> Self {
+        let body = serde_json::to_string(&ErrorBody { error: message })
+            .unwrap_or_else(|_| String::from(r#"{"error":"internal error"}"#));
+        Self { status, body }
+    }
+
+    /// Builds a `500 Internal Server Error` reply, logging the cause.
+    #[must_use]
+    pub fn internal_error(err: &anyhow::Error) -> Self {
+        tracing::error!(error = %format!("{err:#}"), "request failed");
+        Self::error(StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+    }
+}
+
+/// Routes a request to its handler.
+///
+/// `query` is the raw (percent-encoded) query string without the `?`.
+#[must_use]
+pub fn route(conn: &Connection, method: &Method, path: &str, query: Option<&str>) -> Reply {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let known = matches!(
+        segments.as_slice(),
+        ["titles" | "channels" | "search" | "jobs"] | ["titles", _, "programs"]
+    );
+    if !known {
+        return Reply::error(StatusCode::NOT_FOUND, "not found");
+    }
+    if method != Method::GET {
+        return Reply::error(StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+    }
+    let result = match segments.as_slice() {
+        ["titles"] => dtvmgr_db::load_titles(conn).map(|t| Reply::ok(&t)),
+        ["titles", tid, "programs"] => title_programs(conn, tid),
+        ["channels"] => dtvmgr_db::load_channels(conn).map(|c| Reply::ok(&c)),
+        ["jobs"] => jobs(conn),
+        _ => search(conn, query.unwrap_or_default()),
+    };
+    result.unwrap_or_else(|e| Reply::internal_error(&e))
+}
+
+/// `GET /titles/{tid}/programs`.
+fn title_programs(conn: &Connection, tid: &str) -> Result<Reply> {
+    let Ok(tid) = tid.parse::<u32>() else {
+        return Ok(Reply::error(StatusCode::BAD_REQUEST, "invalid tid"));
+    };
+    if dtvmgr_db::load_titles_by_tids(conn, &[tid])?.is_empty() {
+        return Ok(Reply::error(StatusCode::NOT_FOUND, "title not found"));
+    }
+    let programs = dtvmgr_db::load_programs_by_tids(conn, &[tid])?;
+    Ok(Reply::ok(&programs))
+}
+
+/// `GET /jobs`.
+fn jobs(conn: &Connection) -> Result<Reply> {
+    let jobs: Vec<JobStatus> = dtvmgr_db::load_jobs(conn, None)?
+        .into_iter()
+        .map(JobStatus::from)
+        .collect();
+    Ok(Reply::ok(&jobs))
+}
+
+/// `GET /search?q=&limit=`.
+fn search(conn: &Connection, query: &str) -> Result<Reply> {
+    let mut keyword = None;
+    let mut limit = DEFAULT_SEARCH_LIMIT;
+    for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+        match key.as_ref() {
+            "q" => keyword = Some(value.into_owned()),
+            "limit" => match value.parse::<u32>() {
+                Ok(n) if n > 0 => limit = n,
+                _ => return Ok(Reply::error(StatusCode::BAD_REQUEST, "invalid limit")),
+            },
+            _ => {}
+        }
+    }
+    let Some(keyword) = keyword.filter(|k| !k.trim().is_empty()) else {
+        return Ok(Reply::error(
+            StatusCode::BAD_REQUEST,
+            "missing query parameter q",
+        ));
+    };
+    let titles = dtvmgr_db::search_titles(conn, &keyword, limit)?;
+    Ok(Reply::ok(&titles))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn seeded_db(dir: &tempfile::TempDir) -> Connection {
+        let conn = dtvmgr_db::open_db(Some(&dir.path().to_path_buf())).unwrap();
+        conn.execute_batch(
+            "INSERT INTO channel_groups VALUES (1, '地上波', 1);
+             INSERT INTO channels (ch_id, ch_gid, ch_name) VALUES (7, 1, 'テレビ東京');
+             INSERT INTO titles (tid, title, last_update) VALUES
+                 (6309, 'SPY×FAMILY', '2022-01-01 00:00:00'),
+                 (6310, 'Other', '2022-01-01 00:00:00');
+             INSERT INTO programs (pid, tid, ch_id, st_time, ed_time, count) VALUES
+                 (100, 6309, 7, '2022-04-09 23:00:00', '2022-04-09 23:30:00', 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn get(conn: &Connection, path: &str, query: Option<&str>) -> (StatusCode, serde_json::Value) {
+        let reply = route(conn, &Method::GET, path, query);
+        (reply.status, serde_json::from_str(&reply.body).unwrap())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_route_lists_titles_programs_and_channels() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+
+        // Act
+        let (titles_status, titles) = get(&conn, "/titles", None);
+        let (programs_status, programs) = get(&conn, "/titles/6309/programs", None);
+        let (channels_status, channels) = get(&conn, "/channels/", None);
+
+        // Assert
+        assert_eq!(titles_status, StatusCode::OK);
+        assert_eq!(titles.as_array().unwrap().len(), 2);
+        assert_eq!(programs_status, StatusCode::OK);
+        assert_eq!(programs[0]["pid"], 100);
+        assert_eq!(programs[0]["count"], 1);
+        assert_eq!(channels_status, StatusCode::OK);
+        assert_eq!(channels[0]["ch_name"], "テレビ東京");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_route_search_decodes_query() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+
+        // Act: "SPY×" percent-encoded
+        let (status, body) = get(&conn, "/search", Some("q=SPY%C3%97&limit=5"));
+        let (missing_status, missing) = get(&conn, "/search", Some("limit=5"));
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["tid"], 6309);
+        assert_eq!(missing_status, StatusCode::BAD_REQUEST);
+        assert_eq!(missing["error"], "missing query parameter q");
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_route_lists_jobs() {
+        // Arrange: a queued job and a failed one
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+        let queued = dtvmgr_db::insert_job(&conn, "sync", None, 3, "2024-01-01T00:00:00Z").unwrap();
+        let failed =
+            dtvmgr_db::insert_job(&conn, "prune", Some("30"), 1, "2024-01-01T00:00:00Z").unwrap();
+        conn.execute(
+            "UPDATE jobs SET state = 'failed', last_error = 'disk full' WHERE id = ?1",
+            [failed],
+        )
+        .unwrap();
+
+        // Act
+        let (status, body) = get(&conn, "/jobs", None);
+
+        // Assert: newest first; only queued jobs have a next run
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 2);
+        assert_eq!(body[0]["id"], failed);
+        assert_eq!(body[0]["kind"], "prune");
+        assert_eq!(body[0]["state"], "failed");
+        assert!(body[0]["next_run"].is_null());
+        assert_eq!(body[0]["last_error"], "disk full");
+        assert_eq!(body[1]["id"], queued);
+        assert_eq!(body[1]["state"], "queued");
+        assert_eq!(body[1]["next_run"], "2024-01-01T00:00:00Z");
+        assert!(body[1]["last_error"].is_null());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_route_rejects_bad_requests() {
+        // Arrange
+        let dir = tempfile::tempdir().unwrap();
+        let conn = seeded_db(&dir);
+
+        // Act
+        let bad_tid = route(&conn, &Method::GET, "/titles/abc/programs", None);
+        let unknown_tid = route(&conn, &Method::GET, "/titles/1/programs", None);
+        let unknown_path = route(&conn, &Method::GET, "/recorded", None);
+        let post = route(&conn, &Method::POST, "/titles", None);
+        let post_jobs = route(&conn, &Method::POST, "/jobs", None);
+
+        // Assert
+        assert_eq!(bad_tid.status, StatusCode::BAD_REQUEST);
+        assert_eq!(unknown_tid.status, StatusCode::NOT_FOUND);
+        assert_eq!(unknown_path.status, StatusCode::NOT_FOUND);
+        assert_eq!(post.status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(post_jobs.status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::collections::BTreeSet;
 use std::io;
+use std::sync::mpsc;
 
 use anyhow::{Context, Result};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
@@ -11,19 +12,27 @@ use crossterm::terminal::{
 use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 
-use super::state::{ChannelGroup, ChannelSelectorState, InputMode, SelectorResult};
+use super::state::{
+    ChannelGroup, ChannelSelectorState, ChannelUpdate, InputMode, RefreshStatus, SelectorResult,
+};
+use super::term;
 use super::ui;
 
 /// Runs the channel selector TUI and returns the selected channel IDs.
 ///
+/// When `updates` is given, `groups` is the cached list shown immediately and
+/// API-refreshed groups received on the channel are applied in place.
+///
 /// Returns `None` if the user cancels, or `Some(selected)` if confirmed.
 ///
 /// # Errors
 ///
 /// Returns an error if terminal setup or event handling fails.
-pub fn run_channel_selector(
+#[allow(clippy::future_not_send)]
+pub async fn run_channel_selector(
     groups: Vec<ChannelGroup>,
     initial_selected: BTreeSet<u32>,
+    updates: Option<mpsc::Receiver<ChannelUpdate>>,
 ) -> Result<Option<Vec<u32>>> {
     enable_raw_mode().context("failed to enable raw mode")?;
     let mut stdout = io::stdout();
@@ -34,8 +43,11 @@ pub fn run_channel_selector(
     let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
 
     let mut state = ChannelSelectorState::new(groups, initial_selected);
+    if updates.is_some() {
+        state.refresh = Some(RefreshStatus::Pending);
+    }
 
-    let result = run_event_loop(&mut terminal, &mut state);
+    let result = run_event_loop(&mut terminal, &mut state, updates.as_ref()).await;
 
     // Cleanup (always attempt even if event loop failed)
     disable_raw_mode().context("failed to disable raw mode")?;
@@ -54,32 +66,55 @@ pub fn run_channel_selector(
 }
 
 /// Main event loop.
-fn run_event_loop(
+///
+/// Uses non-blocking `event::poll` with async sleep so that the spawned
+/// refresh task can make progress on a `current_thread` runtime. Each tick
+/// handles every pending event, so fast typing and pastes are not throttled,
+/// and the screen is redrawn only after an event or a background update.
+#[allow(clippy::future_not_send)]
+async fn run_event_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     state: &mut ChannelSelectorState,
+    updates: Option<&mpsc::Receiver<ChannelUpdate>>,
 ) -> Result<SelectorResult> {
+    let mut dirty = true;
     loop {
-        terminal
-            .draw(|frame| ui::draw(frame, state))
-            .context("failed to draw TUI")?;
-
-        if event::poll(std::time::Duration::from_millis(100)).context("failed to poll events")?
-            && let Event::Key(key) = event::read().context("failed to read event")?
-            && key.kind == KeyEventKind::Press
-        {
-            match state.input_mode {
-                InputMode::Filter => {
-                    if let Some(result) = handle_filter_input(state, key.code) {
-                        return Ok(result);
-                    }
-                }
-                InputMode::Normal => {
-                    if let Some(result) = handle_normal_input(state, key.code, key.modifiers) {
-                        return Ok(result);
-                    }
-                }
+        if let Some(rx) = updates {
+            dirty |= state.drain_updates(rx);
+        }
+
+        if dirty {
+            terminal
+                .draw(|frame| {
+                    ui::draw(frame, state);
+                    term::degrade(frame.buffer_mut(), term::current());
+                })
+                .context("failed to draw TUI")?;
+            dirty = false;
+        }
+
+        while event::poll(std::time::Duration::ZERO).context("failed to poll events")? {
+            // Any event, including a resize, needs a redraw.
+            dirty = true;
+            let Event::Key(key) = event::read().context("failed to read event")? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let result = match state.input_mode {
+                InputMode::Filter => handle_filter_input(state, key.code),
+                InputMode::Normal => handle_normal_input(state, key.code, key.modifiers),
+            };
+            if let Some(result) = result {
+                return Ok(result);
             }
         }
+
+        // Yield to the tokio runtime so the refresh task can progress.
+        if !dirty {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
     }
 }
 
@@ -152,10 +187,12 @@ mod tests {
                     ChannelEntry {
                         ch_id: 10,
                         ch_name: String::from("Ch10"),
+                        ..ChannelEntry::default()
                     },
                     ChannelEntry {
                         ch_id: 11,
                         ch_name: String::from("Ch11"),
+                        ..ChannelEntry::default()
                     },
                 ],
             },
@@ -165,6 +202,7 @@ mod tests {
                 channels: vec![ChannelEntry {
                     ch_id: 20,
                     ch_name: String::from("Ch20"),
+                    ..ChannelEntry::default()
                 }],
             },
         ];
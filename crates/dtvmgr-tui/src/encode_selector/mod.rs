@@ -23,6 +23,7 @@ use self::state::{
     SyncMessage, WizardStep,
 };
 use self::state::{FileCheckMessage, QueueMessage, StorageMessage};
+use crate::term;
 
 /// TUI terminal handle (alternate screen + raw mode).
 pub type TuiTerminal = Terminal<CrosstermBackend<io::Stdout>>;
@@ -98,7 +99,10 @@ pub fn draw_loading_progress(
     total: usize,
 ) -> Result<()> {
     terminal
-        .draw(|frame| ui::draw_loading_progress(frame, page, checked, total))
+        .draw(|frame| {
+            ui::draw_loading_progress(frame, page, checked, total);
+            term::degrade(frame.buffer_mut(), term::current());
+        })
         .context("failed to draw loading screen")?;
     Ok(())
 }
@@ -188,7 +192,10 @@ async fn run_event_loop(
         }
 
         terminal
-            .draw(|frame| ui::draw(frame, state))
+            .draw(|frame| {
+                ui::draw(frame, state);
+                term::degrade(frame.buffer_mut(), term::current());
+            })
             .context("failed to draw TUI")?;
 
         // Non-blocking poll: check if a key event is already available.
@@ -9,10 +9,14 @@ pub mod encode_selector;
 pub mod fmt;
 /// Normalize viewer TUI.
 pub mod normalize_viewer;
+/// Program grid (EPG) TUI.
+pub mod program_grid;
 /// Progress viewer TUI.
 pub mod progress_viewer;
 /// Channel selector state types.
 pub mod state;
+/// Terminal capability detection and graceful degradation.
+pub mod term;
 /// Title/program viewer TUI.
 pub mod title_viewer;
 mod ui;
@@ -17,6 +17,7 @@ use ratatui::backend::CrosstermBackend;
 use self::state::{
     InputMode, NormalizeRow, NormalizeViewerState, RegexSource, categorize, normalize_chars,
 };
+use crate::term;
 use dtvmgr_db::titles::CachedTitle;
 
 /// Builds normalize rows from cached titles.
@@ -89,6 +90,7 @@ fn run_event_loop(
         terminal
             .draw(|frame| {
                 main_area_height = ui::draw(frame, state);
+                term::degrade(frame.buffer_mut(), term::current());
             })
             .context("failed to draw TUI")?;
 
@@ -705,6 +707,8 @@ mod tests {
                 tmdb_series_id: None,
                 tmdb_season_number: None,
                 tmdb_season_id: None,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
                 title: String::from("SPY×FAMILY Season 2"),
                 short_title: None,
                 title_yomi: None,
@@ -714,18 +718,22 @@ mod tests {
                 first_year: Some(2023),
                 first_month: None,
                 keywords: Vec::new(),
-                sub_titles: None,
                 last_update: String::new(),
                 tmdb_original_name: None,
                 tmdb_name: None,
                 tmdb_alt_titles: None,
                 tmdb_last_updated: None,
+                comment: None,
+                user_point: None,
+                user_point_rank: None,
             },
             CachedTitle {
                 tid: 2,
                 tmdb_series_id: None,
                 tmdb_season_number: None,
                 tmdb_season_id: None,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
                 title: String::from("劇場版 鬼滅の刃"),
                 short_title: None,
                 title_yomi: None,
@@ -735,12 +743,14 @@ mod tests {
                 first_year: Some(2020),
                 first_month: None,
                 keywords: Vec::new(),
-                sub_titles: None,
                 last_update: String::new(),
                 tmdb_original_name: None,
                 tmdb_name: None,
                 tmdb_alt_titles: None,
                 tmdb_last_updated: None,
+                comment: None,
+                user_point: None,
+                user_point_rank: None,
             },
         ];
 
@@ -780,6 +790,8 @@ mod tests {
             tmdb_series_id: None,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: String::from("Ｈｅｌｌｏ"),
             short_title: None,
             title_yomi: None,
@@ -789,12 +801,14 @@ mod tests {
             first_year: None,
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::new(),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         }];
 
         // Act
@@ -0,0 +1,206 @@
+//! Program grid (EPG) TUI main loop.
+
+/// Program grid state types.
+pub mod state;
+mod ui;
+
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+
+use self::state::{GridChannel, GridPosition, GridProgram, ProgramGridState};
+use crate::term;
+use dtvmgr_db::channels::CachedChannel;
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
+
+/// Result returned by the program grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramGridOutput {
+    /// Title to open in the title viewer (`None` when the user quit).
+    pub open_tid: Option<u32>,
+    /// Cursor position on exit, to resume the grid at the same place.
+    pub position: GridPosition,
+}
+
+/// Action requested by a key press.
+enum GridAction {
+    /// Keep running.
+    None,
+    /// Quit the grid.
+    Quit,
+    /// Quit and open the given title.
+    Open(u32),
+}
+
+/// Parses a stored `YYYY-MM-DD HH:MM:SS` timestamp.
+fn parse_time(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// Builds grid columns from cached data.
+///
+/// Columns follow the order of `channels`; channels without programs are
+/// skipped, and programs on unknown channels get a column named by their ID.
+/// Programs with unparsable times are ignored.
+fn build_grid_channels(
+    titles: &[CachedTitle],
+    programs: &[CachedProgram],
+    channels: &[CachedChannel],
+) -> Vec<GridChannel> {
+    let title_names: HashMap<u32, &str> =
+        titles.iter().map(|t| (t.tid, t.title.as_str())).collect();
+
+    let mut by_channel: HashMap<u32, Vec<GridProgram>> = HashMap::new();
+    for p in programs {
+        let (Some(start), Some(end)) = (parse_time(&p.st_time), parse_time(&p.ed_time)) else {
+            continue;
+        };
+        by_channel.entry(p.ch_id).or_default().push(GridProgram {
+            pid: p.pid,
+            tid: p.tid,
+            title: title_names
+                .get(&p.tid)
+                .map_or_else(|| format!("TID {}", p.tid), |t| (*t).to_owned()),
+            count: p.count,
+            sub_title: p.st_sub_title.clone().or_else(|| p.sub_title.clone()),
+            start,
+            end,
+        });
+    }
+
+    let mut columns: Vec<GridChannel> = channels
+        .iter()
+        .filter_map(|ch| {
+            by_channel.remove(&ch.ch_id).map(|programs| GridChannel {
+                ch_id: ch.ch_id,
+                name: ch.ch_name.clone(),
+                programs,
+            })
+        })
+        .collect();
+    let mut unknown: Vec<GridChannel> = by_channel
+        .into_iter()
+        .map(|(ch_id, programs)| GridChannel {
+            ch_id,
+            name: ch_id.to_string(),
+            programs,
+        })
+        .collect();
+    unknown.sort_by_key(|c| c.ch_id);
+    columns.extend(unknown);
+
+    for column in &mut columns {
+        column.programs.sort_by_key(|p| (p.start, p.pid));
+    }
+    columns
+}
+
+/// Launches the interactive program grid TUI.
+///
+/// Channels are shown as columns and 30-minute slots of the broadcast day
+/// (05:00 to 05:00) as rows. `today` is the target of the `t` key.
+///
+/// # Errors
+///
+/// Returns an error if terminal setup, event handling, or teardown fails.
+#[allow(clippy::module_name_repetitions)]
+pub fn run_program_grid(
+    titles: &[CachedTitle],
+    programs: &[CachedProgram],
+    channels: &[CachedChannel],
+    position: GridPosition,
+    today: NaiveDate,
+) -> Result<ProgramGridOutput> {
+    let columns = build_grid_channels(titles, programs, channels);
+    let mut state = ProgramGridState::new(columns, position, today);
+
+    enable_raw_mode().context("failed to enable raw mode")?;
+    let mut stdout = io::stdout();
+    crossterm::execute!(stdout, EnterAlternateScreen)
+        .context("failed to enter alternate screen")?;
+
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
+
+    let result = run_event_loop(&mut terminal, &mut state);
+
+    // Cleanup (always attempt even if event loop failed)
+    disable_raw_mode().context("failed to disable raw mode")?;
+    crossterm::execute!(io::stdout(), LeaveAlternateScreen)
+        .context("failed to leave alternate screen")?;
+
+    Ok(ProgramGridOutput {
+        open_tid: result?,
+        position: state.position,
+    })
+}
+
+/// Main event loop. Returns the TID to open, if any.
+fn run_event_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    state: &mut ProgramGridState,
+) -> Result<Option<u32>> {
+    let mut page_size: usize = 1;
+
+    loop {
+        terminal
+            .draw(|frame| {
+                page_size = usize::from(ui::draw(frame, state));
+                term::degrade(frame.buffer_mut(), term::current());
+            })
+            .context("failed to draw TUI")?;
+
+        if event::poll(Duration::from_millis(250)).context("failed to poll events")?
+            && let Event::Key(key) = event::read().context("failed to read event")?
+            && key.kind == KeyEventKind::Press
+        {
+            match handle_key(state, key.code, key.modifiers, page_size) {
+                GridAction::None => {}
+                GridAction::Quit => return Ok(None),
+                GridAction::Open(tid) => return Ok(Some(tid)),
+            }
+        }
+    }
+}
+
+/// Handles a key press.
+fn handle_key(
+    state: &mut ProgramGridState,
+    key: KeyCode,
+    modifiers: KeyModifiers,
+    page_size: usize,
+) -> GridAction {
+    match key {
+        KeyCode::Char('q') | KeyCode::Esc => return GridAction::Quit,
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+            return GridAction::Quit;
+        }
+        KeyCode::Enter => {
+            if let Some(p) = state.current_program() {
+                return GridAction::Open(p.tid);
+            }
+        }
+        KeyCode::Left | KeyCode::Char('h') => state.move_left(),
+        KeyCode::Right | KeyCode::Char('l') => state.move_right(),
+        KeyCode::Up | KeyCode::Char('k') => state.move_up(1),
+        KeyCode::Down | KeyCode::Char('j') => state.move_down(1),
+        KeyCode::PageUp => state.move_up(page_size),
+        KeyCode::PageDown => state.move_down(page_size),
+        KeyCode::Char('[' | 'p') => state.prev_day(),
+        KeyCode::Char(']' | 'n') => state.next_day(),
+        KeyCode::Char('t') => state.go_today(),
+        _ => {}
+    }
+    GridAction::None
+}
@@ -0,0 +1,398 @@
+//! Program grid TUI state management.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// Hour at which a broadcast day starts (programs before 05:00 belong to
+/// the previous day, as in printed TV guides).
+pub const DAY_START_HOUR: u32 = 5;
+
+/// Length of one grid row in minutes.
+pub const SLOT_MINUTES: u32 = 30;
+
+/// Number of rows in one broadcast day.
+#[allow(clippy::as_conversions)]
+pub const SLOTS_PER_DAY: usize = (24 * 60 / SLOT_MINUTES) as usize;
+
+/// A program placed on the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridProgram {
+    /// Syoboi program ID.
+    pub pid: u32,
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Title name.
+    pub title: String,
+    /// Episode number (if any).
+    pub count: Option<u32>,
+    /// Episode subtitle (if any).
+    pub sub_title: Option<String>,
+    /// Broadcast start time.
+    pub start: NaiveDateTime,
+    /// Broadcast end time.
+    pub end: NaiveDateTime,
+}
+
+/// A grid column: one channel and its programs sorted by start time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridChannel {
+    /// Syoboi channel ID.
+    pub ch_id: u32,
+    /// Channel name.
+    pub name: String,
+    /// Programs sorted by start time.
+    pub programs: Vec<GridProgram>,
+}
+
+/// Cursor position on the grid, kept across title viewer round trips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridPosition {
+    /// Broadcast day shown.
+    pub date: NaiveDate,
+    /// Channel column index.
+    pub channel: usize,
+    /// Time slot row index (0 = 05:00).
+    pub slot: usize,
+}
+
+impl GridPosition {
+    /// Returns the position of the first slot of `date`.
+    #[must_use]
+    pub const fn day(date: NaiveDate) -> Self {
+        Self {
+            date,
+            channel: 0,
+            slot: 0,
+        }
+    }
+
+    /// Returns the position of the slot containing `at`.
+    #[must_use]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn at(at: NaiveDateTime) -> Self {
+        let date = broadcast_date(at);
+        let minutes = (at - day_start(date)).num_minutes();
+        let slot = usize::try_from(minutes / i64::from(SLOT_MINUTES)).unwrap_or(0);
+        Self {
+            date,
+            channel: 0,
+            slot: slot.min(SLOTS_PER_DAY - 1),
+        }
+    }
+}
+
+/// Returns the broadcast day of `at` (before 05:00 counts as the previous day).
+#[must_use]
+pub fn broadcast_date(at: NaiveDateTime) -> NaiveDate {
+    if at.hour() < DAY_START_HOUR {
+        at.date().pred_opt().unwrap_or_else(|| at.date())
+    } else {
+        at.date()
+    }
+}
+
+/// Returns the first instant of the broadcast day `date`.
+#[must_use]
+pub fn day_start(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::from_hms_opt(DAY_START_HOUR, 0, 0).unwrap_or(NaiveTime::MIN))
+}
+
+/// Returns the start time of `slot` on the broadcast day `date`.
+#[must_use]
+#[allow(clippy::arithmetic_side_effects)]
+pub fn slot_start(date: NaiveDate, slot: usize) -> NaiveDateTime {
+    day_start(date) + Duration::minutes(i64::try_from(slot).unwrap_or(0) * i64::from(SLOT_MINUTES))
+}
+
+/// Formats a slot label in broadcast-day notation (e.g. slot 41 -> "25:30").
+#[must_use]
+#[allow(clippy::arithmetic_side_effects)]
+pub fn slot_label(slot: usize) -> String {
+    let minutes = DAY_START_HOUR * 60 + u32::try_from(slot).unwrap_or(0) * SLOT_MINUTES;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// State for the program grid TUI.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProgramGridState {
+    /// Grid columns.
+    pub channels: Vec<GridChannel>,
+    /// Cursor position.
+    pub position: GridPosition,
+    /// Today's broadcast day (target of the "today" key).
+    pub today: NaiveDate,
+    /// First visible channel column.
+    pub channel_offset: usize,
+    /// First visible slot row.
+    pub slot_offset: usize,
+}
+
+impl ProgramGridState {
+    /// Creates a new state with the cursor at `position` (clamped to the grid).
+    #[must_use]
+    pub fn new(channels: Vec<GridChannel>, position: GridPosition, today: NaiveDate) -> Self {
+        let mut position = position;
+        position.channel = position.channel.min(channels.len().saturating_sub(1));
+        position.slot = position.slot.min(SLOTS_PER_DAY.saturating_sub(1));
+        Self {
+            channels,
+            position,
+            today,
+            channel_offset: 0,
+            slot_offset: 0,
+        }
+    }
+
+    /// Returns the program airing in `slot` of the shown day on `channel`.
+    ///
+    /// When several programs share a slot, the latest-starting one wins.
+    #[must_use]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn program_at(&self, channel: usize, slot: usize) -> Option<&GridProgram> {
+        let programs = &self.channels.get(channel)?.programs;
+        let from = slot_start(self.position.date, slot);
+        let to = from + Duration::minutes(i64::from(SLOT_MINUTES));
+        let idx = programs.partition_point(|p| p.start < to);
+        programs
+            .get(..idx)?
+            .iter()
+            .rev()
+            .take(2)
+            .find(|p| p.end > from)
+    }
+
+    /// Returns the program under the cursor.
+    #[must_use]
+    pub fn current_program(&self) -> Option<&GridProgram> {
+        self.program_at(self.position.channel, self.position.slot)
+    }
+
+    /// Returns the channel under the cursor.
+    #[must_use]
+    pub fn current_channel(&self) -> Option<&GridChannel> {
+        self.channels.get(self.position.channel)
+    }
+
+    /// Moves the cursor one channel to the left.
+    pub const fn move_left(&mut self) {
+        self.position.channel = self.position.channel.saturating_sub(1);
+    }
+
+    /// Moves the cursor one channel to the right.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub const fn move_right(&mut self) {
+        if self.position.channel + 1 < self.channels.len() {
+            self.position.channel += 1;
+        }
+    }
+
+    /// Moves the cursor up by `n` slots.
+    pub const fn move_up(&mut self, n: usize) {
+        self.position.slot = self.position.slot.saturating_sub(n);
+    }
+
+    /// Moves the cursor down by `n` slots.
+    pub fn move_down(&mut self, n: usize) {
+        self.position.slot = self
+            .position
+            .slot
+            .saturating_add(n)
+            .min(SLOTS_PER_DAY.saturating_sub(1));
+    }
+
+    /// Shows the previous broadcast day.
+    pub const fn prev_day(&mut self) {
+        if let Some(date) = self.position.date.pred_opt() {
+            self.position.date = date;
+        }
+    }
+
+    /// Shows the next broadcast day.
+    pub const fn next_day(&mut self) {
+        if let Some(date) = self.position.date.succ_opt() {
+            self.position.date = date;
+        }
+    }
+
+    /// Shows today's broadcast day.
+    pub const fn go_today(&mut self) {
+        self.position.date = self.today;
+    }
+
+    /// Adjusts the scroll offsets so the cursor stays within a viewport of
+    /// `columns` channels by `rows` slots.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub const fn ensure_visible(&mut self, columns: usize, rows: usize) {
+        let columns = if columns == 0 { 1 } else { columns };
+        let rows = if rows == 0 { 1 } else { rows };
+        if self.position.channel < self.channel_offset {
+            self.channel_offset = self.position.channel;
+        } else if self.position.channel >= self.channel_offset + columns {
+            self.channel_offset = self.position.channel + 1 - columns;
+        }
+        if self.position.slot < self.slot_offset {
+            self.slot_offset = self.position.slot;
+        } else if self.position.slot >= self.slot_offset + rows {
+            self.slot_offset = self.position.slot + 1 - rows;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn program(pid: u32, tid: u32, start: &str, end: &str) -> GridProgram {
+        GridProgram {
+            pid,
+            tid,
+            title: format!("Title {tid}"),
+            count: Some(1),
+            sub_title: None,
+            start: dt(start),
+            end: dt(end),
+        }
+    }
+
+    fn make_state() -> ProgramGridState {
+        let channels = vec![
+            GridChannel {
+                ch_id: 1,
+                name: String::from("NHK"),
+                programs: vec![
+                    program(10, 100, "2024-01-01 23:00:00", "2024-01-01 23:30:00"),
+                    program(11, 101, "2024-01-02 01:00:00", "2024-01-02 02:00:00"),
+                ],
+            },
+            GridChannel {
+                ch_id: 3,
+                name: String::from("TBS"),
+                programs: vec![program(
+                    20,
+                    200,
+                    "2024-01-01 22:45:00",
+                    "2024-01-01 23:15:00",
+                )],
+            },
+        ];
+        ProgramGridState::new(
+            channels,
+            GridPosition::day(date("2024-01-01")),
+            date("2024-01-03"),
+        )
+    }
+
+    #[test]
+    fn test_broadcast_date_before_day_start_is_previous_day() {
+        // Arrange & Act & Assert
+        assert_eq!(
+            broadcast_date(dt("2024-01-02 04:59:00")),
+            date("2024-01-01")
+        );
+        assert_eq!(
+            broadcast_date(dt("2024-01-02 05:00:00")),
+            date("2024-01-02")
+        );
+    }
+
+    #[test]
+    fn test_grid_position_at_late_night() {
+        // Arrange & Act
+        let pos = GridPosition::at(dt("2024-01-02 01:40:00"));
+
+        // Assert
+        assert_eq!(pos.date, date("2024-01-01"));
+        assert_eq!(slot_label(pos.slot), "25:30");
+    }
+
+    #[test]
+    fn test_program_at_finds_overlapping_program() {
+        // Arrange
+        let state = make_state();
+        let slot_2300 = 36; // 05:00 + 18h
+
+        // Act & Assert
+        assert_eq!(state.program_at(0, slot_2300).map(|p| p.pid), Some(10));
+        assert_eq!(state.program_at(0, slot_2300 + 1), None);
+        assert_eq!(state.program_at(0, slot_2300 + 4).map(|p| p.pid), Some(11));
+        assert_eq!(state.program_at(0, slot_2300 + 5).map(|p| p.pid), Some(11));
+        // 22:45-23:15 spans the 22:30 and 23:00 slots.
+        assert_eq!(state.program_at(1, slot_2300 - 1).map(|p| p.pid), Some(20));
+        assert_eq!(state.program_at(1, slot_2300).map(|p| p.pid), Some(20));
+        assert_eq!(state.program_at(5, slot_2300), None);
+    }
+
+    #[test]
+    fn test_day_navigation_changes_programs() {
+        // Arrange
+        let mut state = make_state();
+        state.position.slot = 36;
+
+        // Act
+        state.next_day();
+
+        // Assert
+        assert_eq!(state.position.date, date("2024-01-02"));
+        assert_eq!(state.current_program(), None);
+
+        // Act
+        state.go_today();
+        state.prev_day();
+        state.prev_day();
+
+        // Assert
+        assert_eq!(state.position.date, date("2024-01-01"));
+        assert_eq!(state.current_program().map(|p| p.tid), Some(100));
+    }
+
+    #[test]
+    fn test_cursor_moves_are_clamped() {
+        // Arrange
+        let mut state = make_state();
+
+        // Act
+        state.move_left();
+        state.move_up(3);
+        state.move_right();
+        state.move_right();
+        state.move_down(SLOTS_PER_DAY * 2);
+
+        // Assert
+        assert_eq!(state.position.channel, 1);
+        assert_eq!(state.position.slot, SLOTS_PER_DAY - 1);
+    }
+
+    #[test]
+    fn test_ensure_visible_scrolls_to_cursor() {
+        // Arrange
+        let mut state = make_state();
+        state.position.slot = 30;
+        state.position.channel = 1;
+
+        // Act
+        state.ensure_visible(1, 10);
+
+        // Assert
+        assert_eq!(state.slot_offset, 21);
+        assert_eq!(state.channel_offset, 1);
+
+        // Act
+        state.move_up(30);
+        state.move_left();
+        state.ensure_visible(1, 10);
+
+        // Assert
+        assert_eq!(state.slot_offset, 0);
+        assert_eq!(state.channel_offset, 0);
+    }
+}
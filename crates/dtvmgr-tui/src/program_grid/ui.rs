@@ -0,0 +1,249 @@
+//! TUI rendering logic for the program grid.
+
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+
+use super::state::{GridProgram, ProgramGridState, SLOT_MINUTES, SLOTS_PER_DAY, slot_label};
+
+/// Width of the time label column.
+const TIME_COL_WIDTH: u16 = 5;
+
+/// Width of one channel column.
+const CHANNEL_COL_WIDTH: u16 = 18;
+
+/// Draws the program grid UI. Returns the number of visible slot rows for
+/// page size calculation.
+#[allow(clippy::indexing_slicing)]
+pub fn draw(frame: &mut Frame, state: &mut ProgramGridState) -> u16 {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4), // header (date + current program)
+            Constraint::Min(5),    // grid
+            Constraint::Length(3), // footer
+        ])
+        .split(frame.area());
+
+    draw_header(frame, chunks[0], state);
+    let rows = draw_grid(frame, chunks[1], state);
+    draw_footer(frame, chunks[2]);
+
+    rows
+}
+
+/// Draws the header with the shown day and the program under the cursor.
+fn draw_header(frame: &mut Frame, area: Rect, state: &ProgramGridState) {
+    let date = state.position.date.format("%Y-%m-%d (%a)").to_string();
+    let today = if state.position.date == state.today {
+        " today"
+    } else {
+        ""
+    };
+    let channel = state.current_channel().map_or("", |c| c.name.as_str());
+    let current = state.current_program().map_or_else(
+        || {
+            format!(
+                "{channel}  {}  (no program)",
+                slot_label(state.position.slot)
+            )
+        },
+        |p| format!("{channel}  {}", program_summary(p)),
+    );
+
+    let header = Paragraph::new(vec![
+        Line::from(format!(
+            "{date}{today}  |  {} channels",
+            state.channels.len()
+        )),
+        Line::from(current),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Program grid "),
+    );
+    frame.render_widget(header, area);
+}
+
+/// Formats a program as "HH:MM-HH:MM title #count subtitle".
+fn program_summary(p: &GridProgram) -> String {
+    let count = p.count.map(|c| format!(" #{c}")).unwrap_or_default();
+    let sub_title = p
+        .sub_title
+        .as_ref()
+        .map(|s| format!(" {s}"))
+        .unwrap_or_default();
+    format!(
+        "{}-{} {}{count}{sub_title}",
+        p.start.format("%H:%M"),
+        p.end.format("%H:%M"),
+        p.title
+    )
+}
+
+/// Returns the text of a grid cell.
+///
+/// The first visible row of a program shows its title; the next one shows
+/// the subtitle, and further rows stay blank.
+#[allow(clippy::arithmetic_side_effects)]
+fn cell_text(state: &ProgramGridState, channel: usize, slot: usize) -> (String, bool) {
+    let Some(program) = state.program_at(channel, slot) else {
+        return (String::new(), false);
+    };
+    let same_as = |s: usize| {
+        state
+            .program_at(channel, s)
+            .is_some_and(|p| p.pid == program.pid)
+    };
+    let first = slot == state.slot_offset || slot == 0 || !same_as(slot - 1);
+    if first {
+        let text = program.count.map_or_else(
+            || format!("{} {}", program.start.format("%H:%M"), program.title),
+            |count| {
+                format!(
+                    "{} {} #{count}",
+                    program.start.format("%H:%M"),
+                    program.title
+                )
+            },
+        );
+        return (text, true);
+    }
+    let second = slot - 1 == state.slot_offset || slot == 1 || !same_as(slot - 2);
+    if second {
+        return (
+            format!(" {}", program.sub_title.as_deref().unwrap_or("")),
+            false,
+        );
+    }
+    (String::new(), false)
+}
+
+/// Draws the channel x time slot grid. Returns the number of visible rows.
+#[allow(clippy::arithmetic_side_effects)]
+fn draw_grid(frame: &mut Frame, area: Rect, state: &mut ProgramGridState) -> u16 {
+    // Borders (2) + header row (1).
+    let rows = area.height.saturating_sub(3).max(1);
+    let columns =
+        (area.width.saturating_sub(2 + TIME_COL_WIDTH + 1) / (CHANNEL_COL_WIDTH + 1)).max(1);
+    state.ensure_visible(usize::from(columns), usize::from(rows));
+
+    let channel_end = (state.channel_offset + usize::from(columns)).min(state.channels.len());
+    let slot_end = (state.slot_offset + usize::from(rows)).min(SLOTS_PER_DAY);
+    let visible_channels = state.channel_offset..channel_end;
+
+    let header = Row::new(
+        std::iter::once(Cell::from("")).chain(
+            state
+                .channels
+                .get(visible_channels.clone())
+                .unwrap_or_default()
+                .iter()
+                .map(|c| Cell::from(c.name.clone())),
+        ),
+    )
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let cursor_style = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let body: Vec<Row> = (state.slot_offset..slot_end)
+        .map(|slot| {
+            let label = Cell::from(slot_label(slot)).style(Style::default().fg(Color::DarkGray));
+            let cells = visible_channels.clone().map(|channel| {
+                let (text, first) = cell_text(state, channel, slot);
+                let style = if channel == state.position.channel && slot == state.position.slot {
+                    cursor_style
+                } else if first {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                Cell::from(text).style(style)
+            });
+            Row::new(std::iter::once(label).chain(cells))
+        })
+        .collect();
+
+    let widths = std::iter::once(Constraint::Length(TIME_COL_WIDTH))
+        .chain(visible_channels.map(|_| Constraint::Length(CHANNEL_COL_WIDTH)));
+    let title = format!(" {SLOT_MINUTES} min slots ");
+    let table = Table::new(body, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(table, area);
+
+    rows
+}
+
+/// Draws the footer with key hints.
+fn draw_footer(frame: &mut Frame, area: Rect) {
+    let help_text = Line::from(
+        "\u{2190}\u{2192}/h/l: channel  \u{2191}\u{2193}/j/k: time  PgUp/PgDn: page  [/]: prev/next day  t: today  Enter: open title  q: quit",
+    );
+    let footer = Paragraph::new(help_text).block(Block::default().borders(Borders::ALL));
+    frame.render_widget(footer, area);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use chrono::{NaiveDate, NaiveDateTime};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::buffer::Buffer;
+
+    use super::super::state::{GridChannel, GridPosition};
+    use super::*;
+
+    /// Converts a ratatui Buffer into a single string with newlines per row.
+    fn buffer_to_string(buf: &Buffer) -> String {
+        let mut s = String::new();
+        for y in 0..buf.area.height {
+            for x in 0..buf.area.width {
+                s.push(buf[(x, y)].symbol().chars().next().unwrap_or(' '));
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    #[test]
+    fn test_draw_shows_channels_slots_and_programs() {
+        // Arrange
+        let dt = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let channels = vec![GridChannel {
+            ch_id: 1,
+            name: String::from("NHK"),
+            programs: vec![GridProgram {
+                pid: 10,
+                tid: 100,
+                title: String::from("Frieren"),
+                count: Some(3),
+                sub_title: Some(String::from("Killing Magic")),
+                start: dt("2024-01-01 05:00:00"),
+                end: dt("2024-01-01 06:00:00"),
+            }],
+        }];
+        let mut state = ProgramGridState::new(channels, GridPosition::day(date), date);
+        let mut terminal = Terminal::new(TestBackend::new(80, 16)).unwrap();
+
+        // Act
+        terminal
+            .draw(|frame| {
+                draw(frame, &mut state);
+            })
+            .unwrap();
+
+        // Assert
+        let text = buffer_to_string(terminal.backend().buffer());
+        assert!(text.contains("2024-01-01 (Mon) today"), "{text}");
+        assert!(text.contains("NHK"), "{text}");
+        assert!(text.contains("05:00 Frieren #3"), "{text}");
+        assert!(text.contains("Killing Magic"), "{text}");
+        assert!(text.contains("05:30"), "{text}");
+    }
+}
@@ -19,6 +19,7 @@ use ratatui::Terminal;
 use ratatui::backend::CrosstermBackend;
 
 use self::state::ProgressViewerState;
+use crate::term;
 use dtvmgr_jlse::progress::ProgressEvent;
 
 /// Runs the progress viewer TUI.
@@ -77,7 +78,10 @@ fn run_event_loop(
 ) -> Result<()> {
     loop {
         terminal
-            .draw(|frame| ui::draw(frame, state))
+            .draw(|frame| {
+                ui::draw(frame, state);
+                term::degrade(frame.buffer_mut(), term::current());
+            })
             .context("failed to draw TUI")?;
 
         // Drain all pending progress events
@@ -1,6 +1,7 @@
 //! Channel selector state management.
 
 use std::collections::{BTreeSet, HashMap};
+use std::sync::mpsc;
 
 /// Identifies which pane is active.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,12 +24,17 @@ pub struct ChannelGroup {
 }
 
 /// A single channel entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ChannelEntry {
     /// Channel ID.
     pub ch_id: u32,
     /// Channel name.
     pub ch_name: String,
+    /// Number of cached programs on the channel.
+    pub program_count: u32,
+    /// Newest Syoboi `LastUpdate` among the cached programs (last synced
+    /// change), if any.
+    pub last_update: Option<String>,
 }
 
 /// Input mode for the TUI.
@@ -49,6 +55,26 @@ pub enum SelectorResult {
     Cancelled,
 }
 
+/// Message from the background channel refresh task.
+#[derive(Debug, Clone)]
+pub enum ChannelUpdate {
+    /// Fresh groups and channels fetched from the API.
+    Refreshed(Vec<ChannelGroup>),
+    /// The refresh failed; the cached list stays in place.
+    Failed(String),
+}
+
+/// Progress of the background refresh shown in the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshStatus {
+    /// Waiting for the API response.
+    Pending,
+    /// API data has been applied.
+    Applied,
+    /// The refresh failed with the given message.
+    Failed(String),
+}
+
 /// State for the channel selector TUI.
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -67,6 +93,12 @@ pub struct ChannelSelectorState {
     pub input_mode: InputMode,
     /// Filter text.
     pub filter: String,
+    /// Background refresh status (`None` when the list came straight from the API).
+    pub refresh: Option<RefreshStatus>,
+    /// Channels added by the last refresh (shown with a "NEW" badge).
+    pub new_channels: BTreeSet<u32>,
+    /// Channels missing from the last refresh (shown greyed out).
+    pub removed_channels: BTreeSet<u32>,
     /// Cached filtered group indices.
     filtered_group_indices: Vec<usize>,
     /// Cached filtered channel indices per group.
@@ -86,6 +118,9 @@ impl ChannelSelectorState {
             channel_cursor: 0,
             input_mode: InputMode::Normal,
             filter: String::new(),
+            refresh: None,
+            new_channels: BTreeSet::new(),
+            removed_channels: BTreeSet::new(),
             filtered_group_indices: Vec::new(),
             filtered_channel_indices: HashMap::new(),
         };
@@ -266,6 +301,88 @@ impl ChannelSelectorState {
         }
     }
 
+    /// Drains pending background updates and applies them. Returns whether
+    /// any update was applied.
+    pub fn drain_updates(&mut self, rx: &mpsc::Receiver<ChannelUpdate>) -> bool {
+        let mut applied = false;
+        while let Ok(update) = rx.try_recv() {
+            applied = true;
+            match update {
+                ChannelUpdate::Refreshed(groups) => self.apply_refresh(groups),
+                ChannelUpdate::Failed(message) => {
+                    self.refresh = Some(RefreshStatus::Failed(message));
+                }
+            }
+        }
+        applied
+    }
+
+    /// Replaces the cached groups with API-refreshed ones.
+    ///
+    /// Channels not in the cached list are marked new; cached channels
+    /// missing from the refresh are kept in their group and marked removed.
+    /// The selection and the cursor's group are preserved.
+    pub fn apply_refresh(&mut self, mut fresh: Vec<ChannelGroup>) {
+        let old_ids: BTreeSet<u32> = self.channel_ids();
+        let cursor_gid = self
+            .current_group_index()
+            .and_then(|idx| self.groups.get(idx))
+            .map(|g| g.ch_gid);
+
+        let fresh_ids: BTreeSet<u32> = fresh
+            .iter()
+            .flat_map(|g| g.channels.iter().map(|ch| ch.ch_id))
+            .collect();
+        self.new_channels = if old_ids.is_empty() {
+            BTreeSet::new()
+        } else {
+            fresh_ids.difference(&old_ids).copied().collect()
+        };
+        self.removed_channels = old_ids.difference(&fresh_ids).copied().collect();
+
+        for old in &self.groups {
+            let removed: Vec<ChannelEntry> = old
+                .channels
+                .iter()
+                .filter(|ch| self.removed_channels.contains(&ch.ch_id))
+                .cloned()
+                .collect();
+            if removed.is_empty() {
+                continue;
+            }
+            if let Some(group) = fresh.iter_mut().find(|g| g.ch_gid == old.ch_gid) {
+                group.channels.extend(removed);
+                group.channels.sort_by_key(|ch| ch.ch_id);
+            } else {
+                fresh.push(ChannelGroup {
+                    ch_gid: old.ch_gid,
+                    name: old.name.clone(),
+                    channels: removed,
+                });
+            }
+        }
+
+        self.groups = fresh;
+        self.refresh = Some(RefreshStatus::Applied);
+        self.rebuild_filter_cache();
+        self.group_cursor = cursor_gid
+            .and_then(|gid| {
+                self.filtered_group_indices
+                    .iter()
+                    .position(|&idx| self.groups.get(idx).is_some_and(|g| g.ch_gid == gid))
+            })
+            .unwrap_or(0);
+        self.channel_cursor = 0;
+    }
+
+    /// Returns the IDs of all channels in all groups.
+    fn channel_ids(&self) -> BTreeSet<u32> {
+        self.groups
+            .iter()
+            .flat_map(|g| g.channels.iter().map(|ch| ch.ch_id))
+            .collect()
+    }
+
     /// Updates the filter and rebuilds the cache.
     pub fn set_filter(&mut self, filter: String) {
         self.filter = filter;
@@ -339,10 +456,12 @@ mod tests {
                     ChannelEntry {
                         ch_id: 3,
                         ch_name: String::from("フジテレビ"),
+                        ..ChannelEntry::default()
                     },
                     ChannelEntry {
                         ch_id: 4,
                         ch_name: String::from("日本テレビ"),
+                        ..ChannelEntry::default()
                     },
                 ],
             },
@@ -352,6 +471,7 @@ mod tests {
                 channels: vec![ChannelEntry {
                     ch_id: 10,
                     ch_name: String::from("BS11"),
+                    ..ChannelEntry::default()
                 }],
             },
         ];
@@ -605,4 +725,72 @@ mod tests {
         state.deselect_all_in_group();
         assert!(!state.selected.contains(&10));
     }
+
+    #[test]
+    fn test_apply_refresh_marks_new_and_removed() {
+        // Arrange: channel 4 disappears, 5 is added, group 2 is dropped
+        let mut state = make_test_state();
+        state.move_down(); // cursor on group 2 (BSデジタル)
+        let fresh = vec![ChannelGroup {
+            ch_gid: 1,
+            name: String::from("テレビ 関東"),
+            channels: vec![
+                ChannelEntry {
+                    ch_id: 3,
+                    ch_name: String::from("フジテレビ"),
+                    ..ChannelEntry::default()
+                },
+                ChannelEntry {
+                    ch_id: 5,
+                    ch_name: String::from("テレビ朝日"),
+                    ..ChannelEntry::default()
+                },
+            ],
+        }];
+
+        // Act
+        state.apply_refresh(fresh);
+
+        // Assert
+        assert_eq!(state.new_channels, BTreeSet::from([5]));
+        assert_eq!(state.removed_channels, BTreeSet::from([4, 10]));
+        let ids: Vec<u32> = state.groups[0].channels.iter().map(|c| c.ch_id).collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+        assert_eq!(state.groups[1].ch_gid, 2);
+        assert_eq!(state.group_cursor, 1);
+        assert_eq!(state.refresh, Some(RefreshStatus::Applied));
+        assert!(state.selected.contains(&3));
+    }
+
+    #[test]
+    fn test_drain_updates_from_empty_cache_marks_nothing_new() {
+        // Arrange
+        let mut state = ChannelSelectorState::new(Vec::new(), BTreeSet::new());
+        let (tx, rx) = mpsc::channel();
+        tx.send(ChannelUpdate::Refreshed(vec![ChannelGroup {
+            ch_gid: 1,
+            name: String::from("G"),
+            channels: vec![ChannelEntry {
+                ch_id: 1,
+                ch_name: String::from("C"),
+                ..ChannelEntry::default()
+            }],
+        }]))
+        .unwrap();
+        tx.send(ChannelUpdate::Failed(String::from("later failure")))
+            .unwrap();
+
+        // Act
+        let applied = state.drain_updates(&rx);
+
+        // Assert
+        assert!(applied);
+        assert!(!state.drain_updates(&rx));
+        assert_eq!(state.total_channels(), 1);
+        assert!(state.new_channels.is_empty());
+        assert_eq!(
+            state.refresh,
+            Some(RefreshStatus::Failed(String::from("later failure")))
+        );
+    }
 }
@@ -0,0 +1,296 @@
+//! Terminal capability detection and graceful degradation.
+//!
+//! Honors `NO_COLOR` and `TERM=dumb`, and falls back to ASCII when the
+//! locale is not UTF-8 or the terminal is a VT-series console. On Windows,
+//! where there is no locale variable, unicode is used only in terminals
+//! known to render it (Windows Terminal, `VS Code`, `ConEmu`, mintty).
+
+use std::sync::LazyLock;
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+
+/// Rendering capabilities of the attached terminal.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermCaps {
+    /// Whether ANSI colors may be emitted.
+    pub color: bool,
+    /// Whether box-drawing and other non-ASCII symbols can be displayed.
+    pub unicode: bool,
+}
+
+impl TermCaps {
+    /// Full color and unicode support.
+    pub const FULL: Self = Self {
+        color: true,
+        unicode: true,
+    };
+
+    /// Monochrome ASCII-only output.
+    pub const MINIMAL: Self = Self {
+        color: false,
+        unicode: false,
+    };
+
+    /// Detects capabilities from the process environment.
+    #[must_use]
+    pub fn detect() -> Self {
+        let get = |key: &str| std::env::var(key).ok();
+        if cfg!(windows) {
+            Self::from_windows_env(get)
+        } else {
+            Self::from_env(get)
+        }
+    }
+
+    /// Detects capabilities using the given environment lookup.
+    #[must_use]
+    pub fn from_env(get: impl Fn(&str) -> Option<String>) -> Self {
+        let term = get("TERM").unwrap_or_default();
+        let dumb = term == "dumb";
+        let no_color = get("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|key| get(key).filter(|v| !v.is_empty()));
+        let utf8_locale = locale.is_none_or(|l| {
+            let upper = l.to_uppercase();
+            upper.contains("UTF-8") || upper.contains("UTF8")
+        });
+
+        Self {
+            color: !dumb && !no_color,
+            unicode: !dumb && !term.starts_with("vt") && utf8_locale,
+        }
+    }
+
+    /// Detects capabilities of a Windows console using the given environment
+    /// lookup.
+    ///
+    /// The legacy console host draws box-drawing characters double width
+    /// under the Japanese code page, so unicode needs a terminal that
+    /// identifies itself: `WT_SESSION` (Windows Terminal), `TERM_PROGRAM`
+    /// (`VS Code`), `ConEmuANSI=ON`, or a `TERM` set by mintty / MSYS2.
+    #[must_use]
+    pub fn from_windows_env(get: impl Fn(&str) -> Option<String>) -> Self {
+        let set = |key: &str| get(key).is_some_and(|v| !v.is_empty());
+        let term = get("TERM").unwrap_or_default();
+        let dumb = term == "dumb";
+        let modern = set("WT_SESSION")
+            || set("TERM_PROGRAM")
+            || get("ConEmuANSI").is_some_and(|v| v.eq_ignore_ascii_case("on"))
+            || (!term.is_empty() && !term.starts_with("vt"));
+
+        Self {
+            color: !dumb && !set("NO_COLOR"),
+            unicode: !dumb && modern,
+        }
+    }
+
+    /// Returns `true` when nothing needs to be degraded.
+    #[must_use]
+    pub const fn is_full(self) -> bool {
+        self.color && self.unicode
+    }
+}
+
+/// Capabilities detected once per process.
+static CURRENT: LazyLock<TermCaps> = LazyLock::new(TermCaps::detect);
+
+/// Returns the capabilities detected for this process.
+#[must_use]
+pub fn current() -> TermCaps {
+    *CURRENT
+}
+
+/// Returns the ASCII replacement for a non-ASCII UI symbol, if any.
+///
+/// Text such as Japanese titles has no replacement and is left untouched.
+#[must_use]
+pub fn ascii_symbol(symbol: &str) -> Option<&'static str> {
+    let replacement = match symbol {
+        "─" | "━" | "═" | "┄" | "┈" => "-",
+        "│" | "┃" | "║" | "┆" | "┊" => "|",
+        "┌" | "┐" | "└" | "┘" | "╭" | "╮" | "╰" | "╯" | "┏" | "┓" | "┗" | "┛" | "╔" | "╗" | "╚"
+        | "╝" | "├" | "┤" | "┬" | "┴" | "┼" => "+",
+        "←" => "<",
+        "→" | "▸" | "▶" | "►" => ">",
+        "↑" | "▲" => "^",
+        "↓" | "▼" => "v",
+        "✓" | "✔" => "x",
+        "✗" | "✘" => "!",
+        "●" | "◉" => "*",
+        "○" | "◯" => "o",
+        "█" | "▉" | "▊" | "▋" | "▌" | "▍" | "▎" | "▏" | "■" => "#",
+        "░" | "▒" | "▓" | "·" | "…" => ".",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+/// Rewrites a rendered buffer to fit the given capabilities.
+///
+/// Without color, foreground/background colors are reset and cells that had
+/// a background color are shown reversed so selections stay visible.
+/// Without unicode, UI symbols are replaced by ASCII equivalents.
+pub fn degrade(buf: &mut Buffer, caps: TermCaps) {
+    if caps.is_full() {
+        return;
+    }
+    for cell in &mut buf.content {
+        if !caps.color {
+            if cell.bg != Color::Reset {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+            cell.fg = Color::Reset;
+            cell.bg = Color::Reset;
+        }
+        if !caps.unicode
+            && let Some(ascii) = ascii_symbol(cell.symbol())
+        {
+            cell.set_symbol(ascii);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::collections::HashMap;
+
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    use super::*;
+
+    fn caps_from(vars: &[(&str, &str)]) -> TermCaps {
+        let map: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect();
+        TermCaps::from_env(|key| map.get(key).cloned())
+    }
+
+    #[test]
+    fn detect_full_on_utf8_xterm() {
+        // Arrange & Act
+        let caps = caps_from(&[("TERM", "xterm-256color"), ("LANG", "ja_JP.UTF-8")]);
+
+        // Assert
+        assert_eq!(caps, TermCaps::FULL);
+    }
+
+    #[test]
+    fn detect_no_color_disables_color_only() {
+        // Arrange & Act
+        let caps = caps_from(&[("TERM", "xterm"), ("NO_COLOR", "1")]);
+
+        // Assert
+        assert!(!caps.color);
+        assert!(caps.unicode);
+    }
+
+    #[test]
+    fn detect_empty_no_color_is_ignored() {
+        // Arrange & Act
+        let caps = caps_from(&[("TERM", "xterm"), ("NO_COLOR", "")]);
+
+        // Assert
+        assert!(caps.color);
+    }
+
+    #[test]
+    fn detect_dumb_terminal_is_minimal() {
+        // Arrange & Act
+        let caps = caps_from(&[("TERM", "dumb"), ("LANG", "en_US.UTF-8")]);
+
+        // Assert
+        assert_eq!(caps, TermCaps::MINIMAL);
+    }
+
+    #[test]
+    fn detect_non_utf8_locale_and_vt_console() {
+        // Arrange & Act
+        let posix = caps_from(&[("TERM", "xterm"), ("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]);
+        let serial = caps_from(&[("TERM", "vt220"), ("LANG", "en_US.UTF-8")]);
+
+        // Assert: LC_ALL takes precedence over LANG
+        assert!(!posix.unicode);
+        assert!(posix.color);
+        assert!(!serial.unicode);
+    }
+
+    #[test]
+    fn detect_windows_terminal_is_full_and_legacy_console_ascii() {
+        // Arrange
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| (*v).to_owned())
+            }
+        };
+
+        // Act
+        let windows_terminal = TermCaps::from_windows_env(env(&[("WT_SESSION", "abc")]));
+        let mintty = TermCaps::from_windows_env(env(&[("TERM", "xterm-256color")]));
+        let conhost = TermCaps::from_windows_env(env(&[]));
+        let no_color = TermCaps::from_windows_env(env(&[("WT_SESSION", "1"), ("NO_COLOR", "1")]));
+
+        // Assert
+        assert_eq!(windows_terminal, TermCaps::FULL);
+        assert_eq!(mintty, TermCaps::FULL);
+        assert!(conhost.color);
+        assert!(!conhost.unicode);
+        assert!(!no_color.color);
+        assert!(no_color.unicode);
+    }
+
+    #[test]
+    fn ascii_symbol_maps_borders_and_arrows() {
+        // Arrange & Act & Assert
+        assert_eq!(ascii_symbol("┌"), Some("+"));
+        assert_eq!(ascii_symbol("─"), Some("-"));
+        assert_eq!(ascii_symbol("│"), Some("|"));
+        assert_eq!(ascii_symbol("▸"), Some(">"));
+        assert_eq!(ascii_symbol("↓"), Some("v"));
+        assert_eq!(ascii_symbol("█"), Some("#"));
+        assert_eq!(ascii_symbol("梟"), None);
+        assert_eq!(ascii_symbol("a"), None);
+    }
+
+    #[test]
+    fn degrade_minimal_rewrites_symbols_and_colors() {
+        // Arrange
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buf.set_string(0, 0, "┌", Style::default().fg(Color::Cyan));
+        buf.set_string(1, 0, "a", Style::default().bg(Color::Blue));
+        buf.set_string(2, 0, "─", Style::default());
+
+        // Act
+        degrade(&mut buf, TermCaps::MINIMAL);
+
+        // Assert
+        let cells = &buf.content;
+        assert_eq!(cells.first().unwrap().symbol(), "+");
+        assert_eq!(cells.first().unwrap().fg, Color::Reset);
+        assert_eq!(cells.get(1).unwrap().bg, Color::Reset);
+        assert!(cells.get(1).unwrap().modifier.contains(Modifier::REVERSED));
+        assert_eq!(cells.get(2).unwrap().symbol(), "-");
+    }
+
+    #[test]
+    fn degrade_full_is_noop() {
+        // Arrange
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+        buf.set_string(0, 0, "┌", Style::default().fg(Color::Cyan));
+        let before = buf.clone();
+
+        // Act
+        degrade(&mut buf, TermCaps::FULL);
+
+        // Assert
+        assert_eq!(buf, before);
+    }
+}
@@ -2,6 +2,8 @@
 
 /// Title viewer state types.
 pub mod state;
+/// TMDB series/season picker.
+pub mod tmdb_picker;
 mod ui;
 
 use std::collections::{HashMap, HashSet};
@@ -17,8 +19,13 @@ use ratatui::backend::CrosstermBackend;
 
 use regex::Regex;
 
-use self::state::{ActivePane, InputMode, ProgramRow, TitleRow, TitleViewerState, ViewerStats};
+use self::state::{
+    ActivePane, InputMode, ProgramColumn, ProgramRow, TitleRow, TitleViewerState, ViewerStats,
+};
+use self::tmdb_picker::TmdbPickerChannels;
 use crate::normalize_viewer::state::normalize_chars;
+use crate::term;
+use dtvmgr_db::annotations::ProgramAnnotations;
 use dtvmgr_db::channels::CachedChannel;
 use dtvmgr_db::programs::CachedProgram;
 use dtvmgr_db::titles::CachedTitle;
@@ -44,12 +51,20 @@ fn extract_base_query(title: &str, compiled_regex: Option<&Regex>) -> String {
     }
 }
 
-/// Result returned by the title viewer containing new TIDs to exclude.
+/// Result returned by the title viewer: new TIDs to exclude and follow changes.
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
 pub struct TitleViewerOutput {
     /// TIDs selected for exclusion during this session.
     pub new_excludes: Vec<u32>,
+    /// TIDs followed during this session.
+    pub followed: Vec<u32>,
+    /// TIDs unfollowed during this session.
+    pub unfollowed: Vec<u32>,
+    /// TIDs whose TMDB mapping was changed with the picker.
+    pub mapped: Vec<u32>,
+    /// Hidden program columns, when toggled during this session.
+    pub hidden_columns: Option<Vec<ProgramColumn>>,
 }
 
 /// Builds a channel name lookup from cached channels.
@@ -60,10 +75,11 @@ fn build_channel_names(channels: Vec<CachedChannel>) -> HashMap<u32, String> {
         .collect()
 }
 
-/// Groups programs by TID with channel name resolution.
+/// Groups programs by TID with channel name resolution and user annotations.
 fn group_programs_by_tid(
     programs: &[CachedProgram],
     ch_names: &HashMap<u32, String>,
+    annotations: &ProgramAnnotations,
 ) -> HashMap<u32, Vec<ProgramRow>> {
     let mut programs_by_tid: HashMap<u32, Vec<ProgramRow>> = HashMap::new();
     for p in programs {
@@ -80,6 +96,8 @@ fn group_programs_by_tid(
             flag: p.flag,
             duration_min: p.duration_min,
             sub_title: p.st_sub_title.clone().or_else(|| p.sub_title.clone()),
+            tags: annotations.tags.get(&p.pid).cloned().unwrap_or_default(),
+            note: annotations.notes.get(&p.pid).cloned(),
         });
     }
     programs_by_tid
@@ -103,7 +121,10 @@ fn compute_viewer_stats(titles: &[CachedTitle], programs: &[CachedProgram]) -> V
         .max()
         .map(String::from);
 
-    let tmdb_matched = titles.iter().filter(|t| t.tmdb_series_id.is_some()).count();
+    let tmdb_matched = titles
+        .iter()
+        .filter(|t| t.tmdb_series_id.is_some() || t.tmdb_movie_id.is_some())
+        .count();
 
     ViewerStats {
         total_titles: titles.len(),
@@ -116,10 +137,13 @@ fn compute_viewer_stats(titles: &[CachedTitle], programs: &[CachedProgram]) -> V
 }
 
 /// Builds title rows with TMDB query extraction.
+///
+/// Titles whose category is in `movie_cats` are mapped to TMDB movies.
 fn build_title_rows(
     titles: &[CachedTitle],
     programs_by_tid: &HashMap<u32, Vec<ProgramRow>>,
     compiled_regex: Option<&Regex>,
+    movie_cats: &HashSet<u32>,
 ) -> Vec<TitleRow> {
     titles
         .iter()
@@ -132,6 +156,8 @@ fn build_title_rows(
                 first_year: t.first_year,
                 tmdb_series_id: t.tmdb_series_id,
                 tmdb_season_number: t.tmdb_season_number,
+                tmdb_movie_id: t.tmdb_movie_id,
+                movie: t.cat.is_some_and(|c| movie_cats.contains(&c)),
                 program_count: programs_by_tid.get(&t.tid).map_or(0, Vec::len),
                 keywords: dtvmgr_db::filter_keywords(
                     &t.keywords,
@@ -146,23 +172,49 @@ fn build_title_rows(
 
 /// Launches the interactive title viewer TUI.
 ///
+/// When `tmdb` is given, `m` opens the TMDB picker for the current title;
+/// searches and mapping updates are delegated to the worker behind it.
+/// Titles whose category is in `movie_cats` are searched as movies.
+/// When `focus_tid` is given, the viewer opens with that title selected.
+/// `hidden_columns` are hidden in the programs pane; `1`-`8` toggle them
+/// and the result is returned in [`TitleViewerOutput::hidden_columns`].
+///
 /// # Errors
 ///
 /// Returns an error if terminal setup, event handling, or teardown fails.
-#[allow(clippy::module_name_repetitions, clippy::implicit_hasher)]
-pub fn run_title_viewer(
+#[allow(
+    clippy::module_name_repetitions,
+    clippy::implicit_hasher,
+    clippy::future_not_send,
+    clippy::too_many_arguments
+)]
+pub async fn run_title_viewer(
     titles: &[CachedTitle],
     programs: &[CachedProgram],
+    annotations: &ProgramAnnotations,
     channels: Vec<CachedChannel>,
     excluded_tids: HashSet<u32>,
+    followed_tids: HashSet<u32>,
     compiled_regex: Option<&regex::Regex>,
+    movie_cats: &HashSet<u32>,
+    tmdb: Option<TmdbPickerChannels>,
+    focus_tid: Option<u32>,
+    hidden_columns: &[ProgramColumn],
 ) -> Result<TitleViewerOutput> {
     let ch_names = build_channel_names(channels);
-    let programs_by_tid = group_programs_by_tid(programs, &ch_names);
+    let programs_by_tid = group_programs_by_tid(programs, &ch_names, annotations);
     let viewer_stats = compute_viewer_stats(titles, programs);
-    let title_rows = build_title_rows(titles, &programs_by_tid, compiled_regex);
+    let title_rows = build_title_rows(titles, &programs_by_tid, compiled_regex, movie_cats);
 
     let mut state = TitleViewerState::new(title_rows, programs_by_tid, viewer_stats, excluded_tids);
+    state.caps = term::current();
+    state.set_raw_records(titles, programs);
+    state.set_followed(followed_tids);
+    state.tmdb_available = tmdb.is_some();
+    state.set_hidden_columns(hidden_columns.iter().copied());
+    if let Some(tid) = focus_tid {
+        state.select_tid(tid);
+    }
 
     enable_raw_mode().context("failed to enable raw mode")?;
     let mut stdout = io::stdout();
@@ -172,7 +224,7 @@ pub fn run_title_viewer(
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend).context("failed to create terminal")?;
 
-    let result = run_event_loop(&mut terminal, &mut state);
+    let result = run_event_loop(&mut terminal, &mut state, tmdb.as_ref()).await;
 
     // Cleanup (always attempt even if event loop failed)
     disable_raw_mode().context("failed to disable raw mode")?;
@@ -181,28 +233,50 @@ pub fn run_title_viewer(
 
     result?;
 
+    let (followed, unfollowed) = state.follow_changes();
     Ok(TitleViewerOutput {
         new_excludes: state.new_excludes(),
+        followed,
+        unfollowed,
+        mapped: state.mapped_tids().to_vec(),
+        hidden_columns: state.hidden_columns_change(),
     })
 }
 
 /// Main event loop.
-fn run_event_loop(
+///
+/// Uses non-blocking `event::poll` with async sleep so that the TMDB picker
+/// worker can make progress on a `current_thread` runtime.
+#[allow(clippy::future_not_send)]
+async fn run_event_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     state: &mut TitleViewerState,
+    tmdb: Option<&TmdbPickerChannels>,
 ) -> Result<()> {
     let mut main_area_height: u16 = 0;
 
     loop {
+        if let Some(tmdb) = tmdb {
+            for request in state.take_picker_requests() {
+                if tmdb.requests.send(request).is_err() {
+                    state.tmdb_available = false;
+                }
+            }
+            while let Ok(message) = tmdb.messages.try_recv() {
+                state.apply_picker_message(message);
+            }
+        }
+
         terminal
             .draw(|frame| {
                 main_area_height = ui::draw(frame, state);
+                term::degrade(frame.buffer_mut(), term::current());
             })
             .context("failed to draw TUI")?;
 
         let page_size = usize::from(main_area_height.saturating_sub(4));
 
-        if event::poll(std::time::Duration::from_millis(100)).context("failed to poll events")?
+        if event::poll(std::time::Duration::ZERO).context("failed to poll events")?
             && let Event::Key(key) = event::read().context("failed to read event")?
             && key.kind == KeyEventKind::Press
         {
@@ -217,8 +291,38 @@ fn run_event_loop(
                         return Ok(());
                     }
                 }
+                InputMode::Category => handle_category_input(state, key.code),
+                InputMode::Detail => {
+                    if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                        state.input_mode = InputMode::Normal;
+                    }
+                }
+                InputMode::TmdbPicker => handle_picker_input(state, key.code),
             }
         }
+
+        // Yield to the tokio runtime so the TMDB worker can progress.
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+}
+
+/// Handles key input in the TMDB picker.
+fn handle_picker_input(state: &mut TitleViewerState, key: KeyCode) {
+    match key {
+        KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => state.picker_back(),
+        KeyCode::Char('q') => state.close_tmdb_picker(),
+        KeyCode::Enter | KeyCode::Right => state.picker_select(),
+        KeyCode::Up | KeyCode::Char('k') => {
+            if let Some(picker) = state.picker.as_mut() {
+                picker.up();
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if let Some(picker) = state.picker.as_mut() {
+                picker.down();
+            }
+        }
+        _ => {}
     }
 }
 
@@ -243,6 +347,17 @@ fn handle_filter_input(state: &mut TitleViewerState, key: KeyCode) -> bool {
     false
 }
 
+/// Handles key input in the category popup.
+fn handle_category_input(state: &mut TitleViewerState, key: KeyCode) {
+    match key {
+        KeyCode::Esc | KeyCode::Char('c' | 'q') => state.input_mode = InputMode::Normal,
+        KeyCode::Enter => state.apply_category(),
+        KeyCode::Up | KeyCode::Char('k') => state.category_up(),
+        KeyCode::Down | KeyCode::Char('j') => state.category_down(),
+        _ => {}
+    }
+}
+
 /// Handles key input in normal mode. Returns `true` to exit.
 fn handle_normal_input(
     state: &mut TitleViewerState,
@@ -263,15 +378,30 @@ fn handle_normal_input(
             state.input_mode = InputMode::Filter;
         }
         KeyCode::Char('t') => state.toggle_tmdb_filter(),
+        KeyCode::Char('c') => state.open_category_popup(),
         KeyCode::Char('p') => state.toggle_programs(),
         KeyCode::Char(' ') => state.toggle_select(),
+        KeyCode::Char('f') if state.active_pane == ActivePane::Titles => state.toggle_follow(),
+        KeyCode::Char('m') => state.open_tmdb_picker(),
         KeyCode::Char('o') => open_syoboi_url(state),
+        KeyCode::Char('s') => state.cycle_program_sort(),
+        KeyCode::Char('S') => state.reverse_program_sort(),
+        KeyCode::Char(c @ '1'..='9') if state.active_pane == ActivePane::Programs => {
+            if let Some(n) = c.to_digit(10).and_then(|n| usize::try_from(n).ok()) {
+                state.toggle_column(n);
+            }
+        }
+        KeyCode::Enter => state.open_program_detail(),
         _ => {}
     }
     false
 }
 
 /// Opens the Syoboi Calendar page for the current title or program.
+///
+/// The browser is launched detached so the TUI neither waits for it nor
+/// gets its output drawn over the screen (`xdg-open` writes to the
+/// terminal; on Windows the URL goes through `ShellExecute`).
 #[allow(clippy::indexing_slicing)]
 fn open_syoboi_url(state: &TitleViewerState) {
     let Some(title) = state.current_title() else {
@@ -296,7 +426,9 @@ fn open_syoboi_url(state: &TitleViewerState) {
             )
         }
     };
-    let _ = open::that(&url);
+    if let Err(e) = open::that_detached(&url) {
+        tracing::warn!(url, error = %e, "Failed to open browser");
+    }
 }
 
 #[cfg(test)]
@@ -322,6 +454,8 @@ mod tests {
                 first_year: Some(2023),
                 tmdb_series_id: Some(100),
                 tmdb_season_number: Some(2),
+                tmdb_movie_id: None,
+                movie: false,
                 program_count: 1,
                 keywords: Vec::new(),
                 tmdb_query: String::from("SPY x FAMILY"),
@@ -333,6 +467,8 @@ mod tests {
                 first_year: Some(2022),
                 tmdb_series_id: None,
                 tmdb_season_number: None,
+                tmdb_movie_id: None,
+                movie: false,
                 program_count: 0,
                 keywords: Vec::new(),
                 tmdb_query: String::from("Bocchi the Rock!"),
@@ -660,6 +796,27 @@ mod tests {
         assert!(!state.show_programs);
     }
 
+    #[test]
+    fn normal_input_number_toggles_column_in_programs_pane() {
+        // Arrange
+        let mut state = make_state();
+
+        // Act: ignored in the titles pane, applied in the programs pane
+        handle_normal_input(&mut state, KeyCode::Char('7'), KeyModifiers::NONE, 10);
+        let in_titles = state.hidden_columns_change();
+        state.focus_programs();
+        handle_normal_input(&mut state, KeyCode::Char('7'), KeyModifiers::NONE, 10);
+        handle_normal_input(&mut state, KeyCode::Char('s'), KeyModifiers::NONE, 10);
+
+        // Assert
+        assert_eq!(in_titles, None);
+        assert_eq!(
+            state.hidden_columns_change(),
+            Some(vec![ProgramColumn::SubTitle])
+        );
+        assert_eq!(state.program_sort, state::ProgramSort::Channel);
+    }
+
     #[test]
     fn normal_input_space_toggles_select() {
         // Arrange
@@ -685,6 +842,23 @@ mod tests {
         assert!(!exit);
     }
 
+    #[test]
+    fn normal_input_m_opens_picker_and_esc_closes_it() {
+        // Arrange
+        let mut state = make_state();
+        state.tmdb_available = true;
+
+        // Act
+        handle_normal_input(&mut state, KeyCode::Char('m'), KeyModifiers::NONE, 10);
+        let opened = state.input_mode;
+        handle_picker_input(&mut state, KeyCode::Esc);
+
+        // Assert
+        assert_eq!(opened, InputMode::TmdbPicker);
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert_eq!(state.take_picker_requests().len(), 1);
+    }
+
     // ── build_channel_names ───────────────────────────────────────
 
     #[test]
@@ -695,11 +869,13 @@ mod tests {
                 ch_id: 1,
                 ch_gid: None,
                 ch_name: String::from("NHK"),
+                ..CachedChannel::default()
             },
             CachedChannel {
                 ch_id: 2,
                 ch_gid: None,
                 ch_name: String::from("TBS"),
+                ..CachedChannel::default()
             },
         ];
 
@@ -767,7 +943,11 @@ mod tests {
         ];
 
         // Act
-        let grouped = group_programs_by_tid(&programs, &ch_names);
+        let annotations = ProgramAnnotations {
+            tags: HashMap::from([(101, vec![String::from("recorded")])]),
+            notes: HashMap::from([(101, String::from("check audio"))]),
+        };
+        let grouped = group_programs_by_tid(&programs, &ch_names, &annotations);
 
         // Assert
         assert_eq!(grouped.len(), 1);
@@ -777,6 +957,9 @@ mod tests {
         assert_eq!(rows[0].sub_title.as_deref(), Some("ep1"));
         // st_sub_title takes precedence over sub_title
         assert_eq!(rows[1].sub_title.as_deref(), Some("st_ep2"));
+        assert!(rows[0].tags.is_empty());
+        assert_eq!(rows[1].tags, vec![String::from("recorded")]);
+        assert_eq!(rows[1].note.as_deref(), Some("check audio"));
     }
 
     #[test]
@@ -803,7 +986,7 @@ mod tests {
         }];
 
         // Act
-        let grouped = group_programs_by_tid(&programs, &ch_names);
+        let grouped = group_programs_by_tid(&programs, &ch_names, &ProgramAnnotations::default());
 
         // Assert
         assert_eq!(grouped[&5][0].ch_name, "99");
@@ -820,6 +1003,8 @@ mod tests {
                 tmdb_series_id: Some(100),
                 tmdb_season_number: None,
                 tmdb_season_id: None,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
                 title: String::from("Title A"),
                 short_title: None,
                 title_yomi: None,
@@ -829,18 +1014,22 @@ mod tests {
                 first_year: None,
                 first_month: None,
                 keywords: Vec::new(),
-                sub_titles: None,
                 last_update: String::new(),
                 tmdb_original_name: None,
                 tmdb_name: None,
                 tmdb_alt_titles: None,
                 tmdb_last_updated: None,
+                comment: None,
+                user_point: None,
+                user_point_rank: None,
             },
             CachedTitle {
                 tid: 2,
                 tmdb_series_id: None,
                 tmdb_season_number: None,
                 tmdb_season_id: None,
+                tmdb_movie_id: None,
+                tmdb_episode_group_id: None,
                 title: String::from("Title B"),
                 short_title: None,
                 title_yomi: None,
@@ -850,12 +1039,14 @@ mod tests {
                 first_year: None,
                 first_month: None,
                 keywords: Vec::new(),
-                sub_titles: None,
                 last_update: String::new(),
                 tmdb_original_name: None,
                 tmdb_name: None,
                 tmdb_alt_titles: None,
                 tmdb_last_updated: None,
+                comment: None,
+                user_point: None,
+                user_point_rank: None,
             },
         ];
         let programs = vec![
@@ -933,6 +1124,8 @@ mod tests {
             tmdb_series_id: Some(42),
             tmdb_season_number: Some(1),
             tmdb_season_id: None,
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
             title: String::from("SPY×FAMILY Season 2"),
             short_title: None,
             title_yomi: None,
@@ -942,12 +1135,14 @@ mod tests {
             first_year: Some(2023),
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::new(),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         }];
         let programs_by_tid = HashMap::from([(
             1,
@@ -959,12 +1154,14 @@ mod tests {
                 flag: None,
                 duration_min: Some(30),
                 sub_title: None,
+                tags: Vec::new(),
+                note: None,
             }],
         )]);
         let re = Regex::new(r"Season\s+\d+").unwrap();
 
         // Act
-        let rows = build_title_rows(&titles, &programs_by_tid, Some(&re));
+        let rows = build_title_rows(&titles, &programs_by_tid, Some(&re), &HashSet::new());
 
         // Assert
         assert_eq!(rows.len(), 1);
@@ -983,30 +1180,36 @@ mod tests {
             tmdb_series_id: None,
             tmdb_season_number: None,
             tmdb_season_id: None,
+            tmdb_movie_id: Some(916_224),
+            tmdb_episode_group_id: None,
             title: String::from("Bocchi the Rock!"),
             short_title: None,
             title_yomi: None,
             title_en: None,
-            cat: Some(1),
+            cat: Some(8),
             title_flag: None,
             first_year: Some(2022),
             first_month: None,
             keywords: Vec::new(),
-            sub_titles: None,
             last_update: String::new(),
             tmdb_original_name: None,
             tmdb_name: None,
             tmdb_alt_titles: None,
             tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
         }];
         let programs_by_tid = HashMap::new();
 
         // Act
-        let rows = build_title_rows(&titles, &programs_by_tid, None);
+        let rows = build_title_rows(&titles, &programs_by_tid, None, &HashSet::from([8]));
 
         // Assert
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0].program_count, 0);
         assert_eq!(rows[0].tmdb_query, "Bocchi the Rock!");
+        assert!(rows[0].movie);
+        assert!(rows[0].is_mapped());
     }
 }
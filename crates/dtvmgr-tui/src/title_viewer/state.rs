@@ -1,9 +1,17 @@
 //! Title viewer TUI state management.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
+use dtvmgr_api::syoboi::TitleCategory;
+use dtvmgr_db::programs::CachedProgram;
+use dtvmgr_db::titles::CachedTitle;
 use ratatui::widgets::TableState;
 
+use super::tmdb_picker::{
+    TmdbMappingChoice, TmdbPickerMessage, TmdbPickerRequest, TmdbPickerState,
+};
+use crate::term::TermCaps;
+
 /// A title row for display.
 #[derive(Debug, Clone)]
 pub struct TitleRow {
@@ -19,6 +27,10 @@ pub struct TitleRow {
     pub tmdb_series_id: Option<u64>,
     /// TMDB season number (if mapped).
     pub tmdb_season_number: Option<u32>,
+    /// TMDB movie ID (if mapped as a movie).
+    pub tmdb_movie_id: Option<u64>,
+    /// Whether the category maps to a TMDB movie rather than a series.
+    pub movie: bool,
     /// Number of programs for this title.
     pub program_count: usize,
     /// Keywords parsed from Syoboi.
@@ -27,6 +39,14 @@ pub struct TitleRow {
     pub tmdb_query: String,
 }
 
+impl TitleRow {
+    /// Returns whether the title is mapped to a TMDB series or movie.
+    #[must_use]
+    pub const fn is_mapped(&self) -> bool {
+        self.tmdb_series_id.is_some() || self.tmdb_movie_id.is_some()
+    }
+}
+
 /// A program row for display.
 #[derive(Debug, Clone)]
 pub struct ProgramRow {
@@ -44,6 +64,10 @@ pub struct ProgramRow {
     pub duration_min: Option<u32>,
     /// Episode subtitle.
     pub sub_title: Option<String>,
+    /// User tags (`db tag`).
+    pub tags: Vec<String>,
+    /// User note (`db note`).
+    pub note: Option<String>,
 }
 
 /// Currently focused pane.
@@ -62,6 +86,12 @@ pub enum InputMode {
     Normal,
     /// Filter text input mode.
     Filter,
+    /// Category filter popup.
+    Category,
+    /// Raw field inspector popup for the current program.
+    Detail,
+    /// TMDB series/season picker for the current title.
+    TmdbPicker,
 }
 
 /// TMDB filter mode for the title list.
@@ -76,6 +106,127 @@ pub enum TmdbFilter {
     Mapped,
 }
 
+/// Sort key of the programs pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProgramSort {
+    /// Broadcast start time, then PID.
+    #[default]
+    StartTime,
+    /// Channel name, then start time.
+    Channel,
+    /// Episode number (programs without one last), then start time.
+    Count,
+}
+
+impl ProgramSort {
+    /// Returns the next key: start time -> channel -> count -> start time.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::StartTime => Self::Channel,
+            Self::Channel => Self::Count,
+            Self::Count => Self::StartTime,
+        }
+    }
+
+    /// Returns the column the key sorts by.
+    #[must_use]
+    pub const fn column(self) -> ProgramColumn {
+        match self {
+            Self::StartTime => ProgramColumn::StTime,
+            Self::Channel => ProgramColumn::Channel,
+            Self::Count => ProgramColumn::Count,
+        }
+    }
+
+    /// Orders `a` and `b` by this key.
+    fn compare(self, a: &ProgramRow, b: &ProgramRow) -> std::cmp::Ordering {
+        let by_time = || a.st_time.cmp(&b.st_time).then(a.pid.cmp(&b.pid));
+        match self {
+            Self::StartTime => by_time(),
+            Self::Channel => a.ch_name.cmp(&b.ch_name).then_with(by_time),
+            Self::Count => match (a.count, b.count) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            }
+            .then_with(by_time),
+        }
+    }
+}
+
+/// Column of the programs pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProgramColumn {
+    /// Program ID.
+    Pid,
+    /// Episode number.
+    Count,
+    /// Broadcast start time.
+    StTime,
+    /// Duration in minutes.
+    Min,
+    /// Channel name.
+    Channel,
+    /// Program flags.
+    Flag,
+    /// Episode subtitle.
+    SubTitle,
+    /// User tags and note marker.
+    Tags,
+}
+
+impl ProgramColumn {
+    /// Every column in display order; column `n` is toggled with key `n + 1`.
+    pub const ALL: [Self; 8] = [
+        Self::Pid,
+        Self::Count,
+        Self::StTime,
+        Self::Min,
+        Self::Channel,
+        Self::Flag,
+        Self::SubTitle,
+        Self::Tags,
+    ];
+
+    /// Returns the table header label.
+    #[must_use]
+    pub const fn header(self) -> &'static str {
+        match self {
+            Self::Pid => "PID",
+            Self::Count => "#",
+            Self::StTime => "StTime",
+            Self::Min => "Min",
+            Self::Channel => "Channel",
+            Self::Flag => "Flag",
+            Self::SubTitle => "SubTitle",
+            Self::Tags => "Tags",
+        }
+    }
+
+    /// Returns the config key (e.g. `"st_time"`).
+    #[must_use]
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Pid => "pid",
+            Self::Count => "count",
+            Self::StTime => "st_time",
+            Self::Min => "min",
+            Self::Channel => "channel",
+            Self::Flag => "flag",
+            Self::SubTitle => "sub_title",
+            Self::Tags => "tags",
+        }
+    }
+
+    /// Parses a config key; `None` for unknown keys.
+    #[must_use]
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key)
+    }
+}
+
 /// Summary statistics for the DB viewer header.
 #[derive(Debug, Clone)]
 pub struct ViewerStats {
@@ -114,14 +265,44 @@ pub struct TitleViewerState {
     pub filter: String,
     /// TMDB filter mode.
     pub tmdb_filter: TmdbFilter,
+    /// Category filter (`None` shows all categories).
+    pub category_filter: Option<TitleCategory>,
+    /// Cursor position in the category popup.
+    pub category_cursor: usize,
     /// Whether the programs pane is visible.
     pub show_programs: bool,
+    /// Sort key of the programs pane.
+    pub program_sort: ProgramSort,
+    /// Whether the programs pane is sorted in descending order.
+    pub program_sort_desc: bool,
+    /// Columns hidden in the programs pane.
+    hidden_columns: BTreeSet<ProgramColumn>,
+    /// Hidden columns as loaded, to detect changes made in this session.
+    initial_hidden_columns: BTreeSet<ProgramColumn>,
     /// TIDs selected for exclusion in the current session.
     pub selected_tids: HashSet<u32>,
     /// TIDs excluded from display (loaded from config).
     excluded_tids: HashSet<u32>,
+    /// TIDs currently followed (toggled in this session).
+    pub followed_tids: HashSet<u32>,
+    /// TIDs followed when the viewer opened.
+    initial_followed: HashSet<u32>,
     /// Cached filtered title indices.
     filtered_indices: Vec<usize>,
+    /// Terminal capabilities used for labels (ASCII fallbacks when unicode is unavailable).
+    pub caps: TermCaps,
+    /// Stored program records by PID, for the detail popup.
+    raw_programs: HashMap<u32, CachedProgram>,
+    /// Stored title records by TID, for the detail popup.
+    raw_titles: HashMap<u32, CachedTitle>,
+    /// Open TMDB picker (if any).
+    pub picker: Option<TmdbPickerState>,
+    /// Whether a TMDB worker is connected.
+    pub tmdb_available: bool,
+    /// Picker requests not yet sent to the worker.
+    pending_requests: Vec<TmdbPickerRequest>,
+    /// TIDs whose TMDB mapping was changed in this session.
+    mapped_tids: Vec<u32>,
 }
 
 impl TitleViewerState {
@@ -153,13 +334,34 @@ impl TitleViewerState {
             input_mode: InputMode::Normal,
             filter: String::new(),
             tmdb_filter: TmdbFilter::default(),
+            category_filter: None,
+            category_cursor: 0,
             show_programs: true,
+            program_sort: ProgramSort::default(),
+            program_sort_desc: false,
+            hidden_columns: BTreeSet::new(),
+            initial_hidden_columns: BTreeSet::new(),
             selected_tids: HashSet::new(),
             excluded_tids,
+            followed_tids: HashSet::new(),
+            initial_followed: HashSet::new(),
             filtered_indices,
+            caps: TermCaps::FULL,
+            raw_programs: HashMap::new(),
+            raw_titles: HashMap::new(),
+            picker: None,
+            tmdb_available: false,
+            pending_requests: Vec::new(),
+            mapped_tids: Vec::new(),
         }
     }
 
+    /// Keeps the stored records shown by the program detail popup.
+    pub fn set_raw_records(&mut self, titles: &[CachedTitle], programs: &[CachedProgram]) {
+        self.raw_titles = titles.iter().map(|t| (t.tid, t.clone())).collect();
+        self.raw_programs = programs.iter().map(|p| (p.pid, p.clone())).collect();
+    }
+
     /// Returns the title cursor position.
     #[must_use]
     pub fn title_cursor(&self) -> usize {
@@ -193,6 +395,65 @@ impl TitleViewerState {
             .map_or(&[], Vec::as_slice)
     }
 
+    /// Returns the program row under the program cursor (if any).
+    #[must_use]
+    pub fn current_program(&self) -> Option<&ProgramRow> {
+        self.current_programs().get(self.program_cursor())
+    }
+
+    /// Opens the detail popup when a program is focused and its record is known.
+    pub fn open_program_detail(&mut self) {
+        if self.active_pane == ActivePane::Programs && self.program_detail_fields().is_some() {
+            self.input_mode = InputMode::Detail;
+        }
+    }
+
+    /// Returns every stored field of the current program followed by the
+    /// owning title's TMDB mapping, as `(name, value)` pairs.
+    ///
+    /// `None` values are shown as `-`.
+    #[must_use]
+    pub fn program_detail_fields(&self) -> Option<Vec<(&'static str, String)>> {
+        fn opt<T: ToString>(v: Option<&T>) -> String {
+            v.map_or_else(|| String::from("-"), ToString::to_string)
+        }
+        let row = self.current_program()?;
+        let p = self.raw_programs.get(&row.pid)?;
+        let mut fields = vec![
+            ("pid", p.pid.to_string()),
+            ("tid", p.tid.to_string()),
+            ("ch_id", format!("{} ({})", p.ch_id, row.ch_name)),
+            ("st_time", p.st_time.clone()),
+            ("ed_time", p.ed_time.clone()),
+            ("st_offset", opt(p.st_offset.as_ref())),
+            ("duration_min", opt(p.duration_min.as_ref())),
+            ("count", opt(p.count.as_ref())),
+            ("sub_title", opt(p.sub_title.as_ref())),
+            ("st_sub_title", opt(p.st_sub_title.as_ref())),
+            ("flag", opt(p.flag.as_ref())),
+            ("deleted", opt(p.deleted.as_ref())),
+            ("warn", opt(p.warn.as_ref())),
+            ("revision", opt(p.revision.as_ref())),
+            ("last_update", opt(p.last_update.as_ref())),
+            ("tmdb_episode_id", opt(p.tmdb_episode_id.as_ref())),
+            ("tags", row.tags.join(", ")),
+            ("note", opt(row.note.as_ref())),
+        ];
+        if let Some(t) = self.raw_titles.get(&p.tid) {
+            fields.extend([
+                ("title", t.title.clone()),
+                ("tmdb_series_id", opt(t.tmdb_series_id.as_ref())),
+                ("tmdb_season_number", opt(t.tmdb_season_number.as_ref())),
+                ("tmdb_season_id", opt(t.tmdb_season_id.as_ref())),
+                ("tmdb_movie_id", opt(t.tmdb_movie_id.as_ref())),
+                ("tmdb_name", opt(t.tmdb_name.as_ref())),
+                ("tmdb_original_name", opt(t.tmdb_original_name.as_ref())),
+                ("tmdb_last_updated", opt(t.tmdb_last_updated.as_ref())),
+            ]);
+        }
+        Some(fields)
+    }
+
     /// Moves cursor up.
     #[allow(clippy::arithmetic_side_effects)]
     pub fn move_up(&mut self) {
@@ -290,6 +551,59 @@ impl TitleViewerState {
         self.select_first_title();
     }
 
+    /// Returns the category popup entries: "all" first, then each category
+    /// present among non-excluded titles, with title counts.
+    #[must_use]
+    pub fn category_options(&self) -> Vec<(Option<TitleCategory>, usize)> {
+        let mut counts: std::collections::BTreeMap<TitleCategory, usize> =
+            std::collections::BTreeMap::new();
+        let mut total: usize = 0;
+        for t in self
+            .titles
+            .iter()
+            .filter(|t| !self.excluded_tids.contains(&t.tid))
+        {
+            let n = counts.entry(TitleCategory::from_cat(t.cat)).or_insert(0);
+            *n = n.saturating_add(1);
+            total = total.saturating_add(1);
+        }
+        std::iter::once((None, total))
+            .chain(counts.into_iter().map(|(cat, n)| (Some(cat), n)))
+            .collect()
+    }
+
+    /// Opens the category popup with the cursor on the active filter.
+    pub fn open_category_popup(&mut self) {
+        let current = self.category_filter;
+        self.category_cursor = self
+            .category_options()
+            .iter()
+            .position(|(cat, _)| *cat == current)
+            .unwrap_or(0);
+        self.input_mode = InputMode::Category;
+    }
+
+    /// Moves the category popup cursor up.
+    pub const fn category_up(&mut self) {
+        self.category_cursor = self.category_cursor.saturating_sub(1);
+    }
+
+    /// Moves the category popup cursor down.
+    pub fn category_down(&mut self) {
+        let max = self.category_options().len().saturating_sub(1);
+        self.category_cursor = self.category_cursor.saturating_add(1).min(max);
+    }
+
+    /// Applies the category under the popup cursor and closes the popup.
+    pub fn apply_category(&mut self) {
+        if let Some((cat, _)) = self.category_options().get(self.category_cursor) {
+            self.category_filter = *cat;
+        }
+        self.input_mode = InputMode::Normal;
+        self.rebuild_filter_cache();
+        self.select_first_title();
+    }
+
     /// Toggles visibility of the programs pane.
     pub fn toggle_programs(&mut self) {
         self.show_programs = !self.show_programs;
@@ -298,6 +612,72 @@ impl TitleViewerState {
         }
     }
 
+    /// Sets the columns hidden in the programs pane (loaded from config).
+    pub fn set_hidden_columns(&mut self, hidden: impl IntoIterator<Item = ProgramColumn>) {
+        self.hidden_columns = hidden.into_iter().collect();
+        if self.visible_columns().is_empty() {
+            self.hidden_columns.clear();
+        }
+        self.initial_hidden_columns.clone_from(&self.hidden_columns);
+    }
+
+    /// Returns the visible program columns in display order.
+    #[must_use]
+    pub fn visible_columns(&self) -> Vec<ProgramColumn> {
+        ProgramColumn::ALL
+            .into_iter()
+            .filter(|c| !self.hidden_columns.contains(c))
+            .collect()
+    }
+
+    /// Toggles the program column with number `n` (1-based, as on the
+    /// keyboard). The last visible column cannot be hidden.
+    pub fn toggle_column(&mut self, n: usize) {
+        let Some(column) = n.checked_sub(1).and_then(|i| ProgramColumn::ALL.get(i)) else {
+            return;
+        };
+        if !self.hidden_columns.remove(column) && self.visible_columns().len() > 1 {
+            self.hidden_columns.insert(*column);
+        }
+    }
+
+    /// Returns the hidden columns when they changed in this session.
+    #[must_use]
+    pub fn hidden_columns_change(&self) -> Option<Vec<ProgramColumn>> {
+        (self.hidden_columns != self.initial_hidden_columns)
+            .then(|| self.hidden_columns.iter().copied().collect())
+    }
+
+    /// Switches the programs pane to the next sort key (ascending).
+    pub fn cycle_program_sort(&mut self) {
+        self.program_sort = self.program_sort.next();
+        self.program_sort_desc = false;
+        self.sort_programs();
+    }
+
+    /// Reverses the sort order of the programs pane.
+    pub fn reverse_program_sort(&mut self) {
+        self.program_sort_desc = !self.program_sort_desc;
+        self.sort_programs();
+    }
+
+    /// Re-sorts every title's programs, keeping the cursor on the same program.
+    fn sort_programs(&mut self) {
+        let pid = self.current_program().map(|p| p.pid);
+        let (sort, desc) = (self.program_sort, self.program_sort_desc);
+        for programs in self.programs_by_tid.values_mut() {
+            programs.sort_by(|a, b| {
+                let order = sort.compare(a, b);
+                if desc { order.reverse() } else { order }
+            });
+        }
+        if let Some(pos) =
+            pid.and_then(|pid| self.current_programs().iter().position(|p| p.pid == pid))
+        {
+            self.program_table_state.select(Some(pos));
+        }
+    }
+
     /// Toggles selection of the current title for exclusion.
     pub fn toggle_select(&mut self) {
         if let Some(t) = self.current_title() {
@@ -308,6 +688,153 @@ impl TitleViewerState {
         }
     }
 
+    /// Moves the title cursor to `tid`. Returns `false` when the title is
+    /// not in the filtered list (e.g. excluded).
+    pub fn select_tid(&mut self, tid: u32) -> bool {
+        let pos = self
+            .filtered_indices
+            .iter()
+            .position(|&i| self.titles.get(i).is_some_and(|t| t.tid == tid));
+        if let Some(pos) = pos {
+            self.title_table_state.select(Some(pos));
+        }
+        pos.is_some()
+    }
+
+    /// Sets the followed TIDs loaded from the database.
+    pub fn set_followed(&mut self, followed: HashSet<u32>) {
+        self.initial_followed.clone_from(&followed);
+        self.followed_tids = followed;
+    }
+
+    /// Toggles the followed flag of the current title.
+    pub fn toggle_follow(&mut self) {
+        if let Some(t) = self.current_title() {
+            let tid = t.tid;
+            if !self.followed_tids.remove(&tid) {
+                self.followed_tids.insert(tid);
+            }
+        }
+    }
+
+    /// Returns the TIDs followed and unfollowed during this session, sorted.
+    #[must_use]
+    pub fn follow_changes(&self) -> (Vec<u32>, Vec<u32>) {
+        let mut followed: Vec<u32> = self
+            .followed_tids
+            .difference(&self.initial_followed)
+            .copied()
+            .collect();
+        let mut unfollowed: Vec<u32> = self
+            .initial_followed
+            .difference(&self.followed_tids)
+            .copied()
+            .collect();
+        followed.sort_unstable();
+        unfollowed.sort_unstable();
+        (followed, unfollowed)
+    }
+
+    /// Opens the TMDB picker for the current title and queues a search.
+    pub fn open_tmdb_picker(&mut self) {
+        let Some(t) = self.current_title() else {
+            return;
+        };
+        let mut picker = TmdbPickerState::new(t.tid, t.tmdb_query.clone(), t.movie);
+        if self.tmdb_available {
+            self.pending_requests.push(TmdbPickerRequest::Search {
+                tid: picker.tid,
+                query: picker.query.clone(),
+                movie: picker.movie,
+            });
+        } else {
+            picker.loading = false;
+            picker.error = Some(String::from(
+                "TMDB is not configured (set TMDB_API_TOKEN or [tmdb] api_key)",
+            ));
+        }
+        self.picker = Some(picker);
+        self.input_mode = InputMode::TmdbPicker;
+    }
+
+    /// Confirms the picker entry under the cursor.
+    pub fn picker_select(&mut self) {
+        if let Some(request) = self.picker.as_mut().and_then(TmdbPickerState::select) {
+            self.pending_requests.push(request);
+        }
+    }
+
+    /// Goes back one picker step, closing the picker from the series list.
+    pub fn picker_back(&mut self) {
+        if !self.picker.as_mut().is_some_and(TmdbPickerState::back) {
+            self.close_tmdb_picker();
+        }
+    }
+
+    /// Closes the picker without changing the mapping.
+    pub fn close_tmdb_picker(&mut self) {
+        self.picker = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Applies a TMDB worker result to the open picker.
+    ///
+    /// A confirmed mapping updates the title row and closes the picker.
+    pub fn apply_picker_message(&mut self, message: TmdbPickerMessage) {
+        let choice = self
+            .picker
+            .as_mut()
+            .and_then(|picker| picker.apply_message(message));
+        if let Some(choice) = choice {
+            self.apply_tmdb_mapping(choice);
+            self.close_tmdb_picker();
+        }
+    }
+
+    /// Takes the picker requests queued since the last call.
+    pub fn take_picker_requests(&mut self) -> Vec<TmdbPickerRequest> {
+        std::mem::take(&mut self.pending_requests)
+    }
+
+    /// Returns the TIDs whose TMDB mapping was changed in this session.
+    #[must_use]
+    pub fn mapped_tids(&self) -> &[u32] {
+        &self.mapped_tids
+    }
+
+    /// Updates the title row, stored record, and stats with a new mapping.
+    fn apply_tmdb_mapping(&mut self, choice: TmdbMappingChoice) {
+        let (series_id, movie_id) = if choice.movie {
+            (None, Some(choice.series_id))
+        } else {
+            (Some(choice.series_id), None)
+        };
+        if let Some(row) = self.titles.iter_mut().find(|t| t.tid == choice.tid) {
+            row.tmdb_series_id = series_id;
+            row.tmdb_season_number = choice.season_number;
+            row.tmdb_movie_id = movie_id;
+        }
+        if let Some(raw) = self.raw_titles.get_mut(&choice.tid) {
+            raw.tmdb_series_id = series_id;
+            raw.tmdb_season_number = choice.season_number;
+            raw.tmdb_season_id = choice.season_id;
+            raw.tmdb_movie_id = movie_id;
+        }
+        self.stats.tmdb_matched = self.titles.iter().filter(|t| t.is_mapped()).count();
+        if !self.mapped_tids.contains(&choice.tid) {
+            self.mapped_tids.push(choice.tid);
+        }
+        let cursor = self.title_cursor();
+        self.rebuild_filter_cache();
+        if self.filtered_indices.is_empty() {
+            self.title_table_state.select(None);
+        } else {
+            self.title_table_state.select(Some(
+                cursor.min(self.filtered_indices.len().saturating_sub(1)),
+            ));
+        }
+    }
+
     /// Returns new TIDs to add to the exclude list (selected minus already excluded).
     #[must_use]
     pub fn new_excludes(&self) -> Vec<u32> {
@@ -351,11 +878,24 @@ impl TitleViewerState {
     const fn matches_tmdb_filter(&self, title: &TitleRow) -> bool {
         match self.tmdb_filter {
             TmdbFilter::All => true,
-            TmdbFilter::Unmapped => title.tmdb_series_id.is_none(),
-            TmdbFilter::Mapped => title.tmdb_series_id.is_some(),
+            TmdbFilter::Unmapped => !title.is_mapped(),
+            TmdbFilter::Mapped => title.is_mapped(),
         }
     }
 
+    /// Returns whether a title passes the category filter.
+    fn matches_category_filter(&self, title: &TitleRow) -> bool {
+        self.category_filter
+            .is_none_or(|cat| TitleCategory::from_cat(title.cat) == cat)
+    }
+
+    /// Returns whether a title passes the exclusion, TMDB, and category filters.
+    fn is_visible(&self, title: &TitleRow) -> bool {
+        !self.excluded_tids.contains(&title.tid)
+            && self.matches_tmdb_filter(title)
+            && self.matches_category_filter(title)
+    }
+
     /// Rebuilds the filtered title indices cache.
     fn rebuild_filter_cache(&mut self) {
         if self.filter.is_empty() {
@@ -363,9 +903,7 @@ impl TitleViewerState {
                 .titles
                 .iter()
                 .enumerate()
-                .filter(|(_, t)| {
-                    !self.excluded_tids.contains(&t.tid) && self.matches_tmdb_filter(t)
-                })
+                .filter(|(_, t)| self.is_visible(t))
                 .map(|(i, _)| i)
                 .collect();
         } else {
@@ -375,7 +913,7 @@ impl TitleViewerState {
                 .iter()
                 .enumerate()
                 .filter(|(_, t)| {
-                    if self.excluded_tids.contains(&t.tid) || !self.matches_tmdb_filter(t) {
+                    if !self.is_visible(t) {
                         return false;
                     }
                     // Match title name
@@ -400,10 +938,69 @@ impl TitleViewerState {
 
 #[cfg(test)]
 mod tests {
-    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
 
     use super::*;
 
+    #[test]
+    fn test_program_sort_cycles_and_reverses() {
+        // Arrange: tid 1 has episodes 1 (pid 100) and 2 (pid 101) on one channel
+        let mut state = make_state();
+        state.programs_by_tid.get_mut(&1).unwrap()[0].ch_name = String::from("NHK");
+        state.focus_programs();
+        state.move_down();
+        let pids = |state: &TitleViewerState| -> Vec<u32> {
+            state.current_programs().iter().map(|p| p.pid).collect()
+        };
+
+        // Act & Assert: channel order puts NHK (pid 100) first
+        state.cycle_program_sort();
+        assert_eq!(state.program_sort, ProgramSort::Channel);
+        assert_eq!(pids(&state), vec![100, 101]);
+
+        // Descending count puts episode 2 first, cursor stays on pid 101
+        state.cycle_program_sort();
+        state.reverse_program_sort();
+        assert_eq!(state.program_sort, ProgramSort::Count);
+        assert_eq!(pids(&state), vec![101, 100]);
+        assert_eq!(state.current_program().unwrap().pid, 101);
+
+        // Back to start time, ascending
+        state.cycle_program_sort();
+        assert_eq!(state.program_sort, ProgramSort::StartTime);
+        assert!(!state.program_sort_desc);
+        assert_eq!(pids(&state), vec![100, 101]);
+    }
+
+    #[test]
+    fn test_toggle_column_keeps_one_visible() {
+        // Arrange
+        let mut state = make_state();
+        state.set_hidden_columns(ProgramColumn::ALL.into_iter().skip(1));
+        assert_eq!(state.hidden_columns_change(), None);
+
+        // Act: hiding the last visible column is refused
+        state.toggle_column(1);
+        let refused = state.visible_columns();
+        state.toggle_column(3);
+        state.toggle_column(9);
+
+        // Assert
+        assert_eq!(refused, vec![ProgramColumn::Pid]);
+        assert_eq!(
+            state.visible_columns(),
+            vec![ProgramColumn::Pid, ProgramColumn::StTime]
+        );
+        let hidden = state.hidden_columns_change().unwrap();
+        assert_eq!(hidden.len(), 6);
+        assert!(!hidden.contains(&ProgramColumn::StTime));
+        assert_eq!(
+            ProgramColumn::from_key("sub_title"),
+            Some(ProgramColumn::SubTitle)
+        );
+        assert_eq!(ProgramColumn::from_key("nope"), None);
+    }
+
     fn make_state() -> TitleViewerState {
         let titles = vec![
             TitleRow {
@@ -413,6 +1010,8 @@ mod tests {
                 first_year: Some(2022),
                 tmdb_series_id: Some(12345),
                 tmdb_season_number: Some(1),
+                tmdb_movie_id: None,
+                movie: false,
                 program_count: 2,
                 keywords: vec![String::from("spy"), String::from("family")],
                 tmdb_query: String::from("SPYxFAMILY"),
@@ -424,6 +1023,8 @@ mod tests {
                 first_year: Some(2022),
                 tmdb_series_id: None,
                 tmdb_season_number: None,
+                tmdb_movie_id: None,
+                movie: false,
                 program_count: 1,
                 keywords: Vec::new(),
                 tmdb_query: String::from("Bocchi the Rock!"),
@@ -442,6 +1043,8 @@ mod tests {
                     flag: None,
                     duration_min: Some(30),
                     sub_title: Some(String::from("オペレーション〈梟〉")),
+                    tags: Vec::new(),
+                    note: None,
                 },
                 ProgramRow {
                     pid: 101,
@@ -451,6 +1054,8 @@ mod tests {
                     flag: Some(2),
                     duration_min: Some(30),
                     sub_title: Some(String::from("妻役を確保せよ")),
+                    tags: Vec::new(),
+                    note: None,
                 },
             ],
         );
@@ -464,6 +1069,8 @@ mod tests {
                 flag: None,
                 duration_min: Some(30),
                 sub_title: Some(String::from("転がるぼっち")),
+                tags: Vec::new(),
+                note: None,
             }],
         );
 
@@ -672,6 +1279,88 @@ mod tests {
         assert!(state.new_excludes().is_empty());
     }
 
+    #[test]
+    fn test_select_tid_moves_cursor_to_visible_title() {
+        // Arrange
+        let mut state = make_state();
+
+        // Act & Assert
+        assert!(state.select_tid(2));
+        assert_eq!(state.current_title().map(|t| t.tid), Some(2));
+        assert!(!state.select_tid(999));
+        assert_eq!(state.current_title().map(|t| t.tid), Some(2));
+    }
+
+    #[test]
+    fn test_toggle_follow_reports_changes_against_initial_set() {
+        // Arrange: tid=2 followed before opening
+        let mut state = make_state();
+        state.set_followed(HashSet::from([2]));
+
+        // Act: follow tid=1, unfollow tid=2
+        state.toggle_follow();
+        state.move_down();
+        state.toggle_follow();
+
+        // Assert
+        assert!(state.followed_tids.contains(&1));
+        assert_eq!(state.follow_changes(), (vec![1], vec![2]));
+
+        // Act: re-follow tid=2 restores the original state
+        state.toggle_follow();
+        assert_eq!(state.follow_changes(), (vec![1], vec![]));
+    }
+
+    #[test]
+    fn test_tmdb_picker_applies_mapping_to_current_title() {
+        // Arrange: tid=2 is unmapped
+        let mut state = make_state();
+        state.tmdb_available = true;
+        state.move_down();
+
+        // Act
+        state.open_tmdb_picker();
+        let requests = state.take_picker_requests();
+        state.apply_picker_message(TmdbPickerMessage::Applied(TmdbMappingChoice {
+            tid: 2,
+            movie: false,
+            series_id: 119_100,
+            season_number: Some(1),
+            season_id: Some(180_000),
+        }));
+
+        // Assert
+        assert_eq!(
+            requests,
+            vec![TmdbPickerRequest::Search {
+                tid: 2,
+                query: String::from("Bocchi the Rock!"),
+                movie: false,
+            }]
+        );
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert!(state.picker.is_none());
+        assert_eq!(state.titles[1].tmdb_series_id, Some(119_100));
+        assert_eq!(state.stats.tmdb_matched, 2);
+        assert_eq!(state.mapped_tids(), &[2]);
+    }
+
+    #[test]
+    fn test_tmdb_picker_without_worker_shows_error() {
+        // Arrange
+        let mut state = make_state();
+
+        // Act
+        state.open_tmdb_picker();
+
+        // Assert
+        assert_eq!(state.input_mode, InputMode::TmdbPicker);
+        assert!(state.take_picker_requests().is_empty());
+        assert!(state.picker.as_ref().unwrap().error.is_some());
+        state.picker_back();
+        assert_eq!(state.input_mode, InputMode::Normal);
+    }
+
     #[test]
     fn test_page_up_page_down_programs() {
         // Arrange
@@ -763,4 +1452,139 @@ mod tests {
         state.toggle_select();
         assert!(!state.selected_tids.contains(&2));
     }
+
+    #[test]
+    fn test_category_options_counts_titles() {
+        // Arrange
+        let mut state = make_state();
+        state.titles[1].cat = Some(8);
+
+        // Act
+        let options = state.category_options();
+
+        // Assert
+        assert_eq!(
+            options,
+            vec![
+                (None, 2),
+                (Some(TitleCategory::Anime), 1),
+                (Some(TitleCategory::Movie), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_category_filters_titles() {
+        // Arrange
+        let mut state = make_state();
+        state.titles[1].cat = Some(8);
+        state.open_category_popup();
+        assert_eq!(state.input_mode, InputMode::Category);
+
+        // Act: select "movie" (all, anime, movie)
+        state.category_down();
+        state.category_down();
+        state.category_down(); // clamped at last entry
+        state.apply_category();
+
+        // Assert
+        assert_eq!(state.input_mode, InputMode::Normal);
+        assert_eq!(state.category_filter, Some(TitleCategory::Movie));
+        assert_eq!(state.filtered_titles().len(), 1);
+        assert_eq!(state.current_title().unwrap().tid, 2);
+
+        // Act: reopening puts the cursor on the active filter; "all" clears it
+        state.open_category_popup();
+        assert_eq!(state.category_cursor, 2);
+        state.category_up();
+        state.category_up();
+        state.apply_category();
+
+        // Assert
+        assert_eq!(state.category_filter, None);
+        assert_eq!(state.filtered_titles().len(), 2);
+    }
+
+    #[test]
+    fn test_program_detail_shows_raw_fields_and_title_mapping() {
+        // Arrange
+        let mut state = make_state();
+        let program = CachedProgram {
+            pid: 100,
+            tid: 1,
+            ch_id: 19,
+            tmdb_episode_id: Some(501),
+            st_time: String::from("2022-04-09 23:00:00"),
+            st_offset: Some(-60),
+            ed_time: String::from("2022-04-09 23:30:00"),
+            count: Some(1),
+            sub_title: None,
+            flag: None,
+            deleted: Some(0),
+            warn: Some(1),
+            revision: Some(3),
+            last_update: Some(String::from("2022-04-01 12:00:00")),
+            st_sub_title: None,
+            duration_min: Some(30),
+        };
+        let title = CachedTitle {
+            tid: 1,
+            tmdb_series_id: Some(12345),
+            tmdb_season_number: Some(1),
+            tmdb_season_id: Some(777),
+            tmdb_movie_id: None,
+            tmdb_episode_group_id: None,
+            title: String::from("SPY×FAMILY"),
+            short_title: None,
+            title_yomi: None,
+            title_en: None,
+            cat: Some(1),
+            title_flag: None,
+            first_year: Some(2022),
+            first_month: Some(4),
+            keywords: Vec::new(),
+            last_update: String::from("2022-04-01 12:00:00"),
+            tmdb_original_name: None,
+            tmdb_name: None,
+            tmdb_alt_titles: None,
+            tmdb_last_updated: None,
+            comment: None,
+            user_point: None,
+            user_point_rank: None,
+        };
+        state.set_raw_records(&[title], &[program]);
+
+        // Act: Enter is ignored on the titles pane
+        state.open_program_detail();
+        let mode_on_titles = state.input_mode;
+        state.focus_programs();
+        state.open_program_detail();
+        let fields: HashMap<_, _> = state.program_detail_fields().unwrap().into_iter().collect();
+
+        // Assert
+        assert_eq!(mode_on_titles, InputMode::Normal);
+        assert_eq!(state.input_mode, InputMode::Detail);
+        assert_eq!(fields["ch_id"], "19 (テレビ東京)");
+        assert_eq!(fields["st_offset"], "-60");
+        assert_eq!(fields["revision"], "3");
+        assert_eq!(fields["warn"], "1");
+        assert_eq!(fields["sub_title"], "-");
+        assert_eq!(fields["tmdb_episode_id"], "501");
+        assert_eq!(fields["tmdb_season_id"], "777");
+    }
+
+    #[test]
+    fn test_program_detail_requires_stored_record() {
+        // Arrange: rows without raw records (pid 101)
+        let mut state = make_state();
+        state.focus_programs();
+        state.move_down();
+
+        // Act
+        state.open_program_detail();
+
+        // Assert
+        assert!(state.program_detail_fields().is_none());
+        assert_eq!(state.input_mode, InputMode::Normal);
+    }
 }
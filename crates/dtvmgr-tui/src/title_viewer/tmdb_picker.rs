@@ -0,0 +1,448 @@
+//! TMDB series/season picker used to map titles from the title viewer.
+//!
+//! Movie titles are searched as movies and mapped without a season step.
+//!
+//! The picker never calls TMDB itself: it queues [`TmdbPickerRequest`]s that
+//! the caller's background worker answers with [`TmdbPickerMessage`]s, so the
+//! event loop stays responsive on a `current_thread` runtime.
+
+use std::sync::mpsc;
+
+/// A TMDB series (or movie) offered by the picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmdbSeriesCandidate {
+    /// TMDB series or movie ID.
+    pub id: u64,
+    /// Localized name.
+    pub name: String,
+    /// Original name.
+    pub original_name: String,
+    /// First air date or release date (`YYYY-MM-DD`).
+    pub first_air_date: Option<String>,
+}
+
+/// A TMDB season offered by the picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmdbSeasonCandidate {
+    /// TMDB season ID.
+    pub id: u64,
+    /// Season number (0 = specials).
+    pub season_number: u32,
+    /// Season name.
+    pub name: String,
+    /// Number of episodes.
+    pub episode_count: u32,
+    /// Air date of the season.
+    pub air_date: Option<String>,
+}
+
+/// A mapping chosen in the picker, as passed to `update_tmdb_mapping` (or
+/// `update_tmdb_movie_mapping` for movies).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TmdbMappingChoice {
+    /// Syoboi title ID.
+    pub tid: u32,
+    /// Whether `series_id` is a TMDB movie ID.
+    pub movie: bool,
+    /// TMDB series ID (movie ID when `movie` is set).
+    pub series_id: u64,
+    /// TMDB season number (`None` when the series has no seasons).
+    pub season_number: Option<u32>,
+    /// TMDB season ID.
+    pub season_id: Option<u64>,
+}
+
+/// Work requested from the background TMDB worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum TmdbPickerRequest {
+    /// Search TV series (or movies) for a title.
+    Search {
+        /// Syoboi title ID the search is for.
+        tid: u32,
+        /// Search query.
+        query: String,
+        /// Search movies instead of TV series.
+        movie: bool,
+    },
+    /// List the seasons of a series.
+    Seasons {
+        /// Syoboi title ID the listing is for.
+        tid: u32,
+        /// TMDB series ID.
+        series_id: u64,
+    },
+    /// Store the chosen mapping.
+    Apply(TmdbMappingChoice),
+}
+
+/// Result sent back by the background TMDB worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum TmdbPickerMessage {
+    /// Search results for a title.
+    Series {
+        /// Syoboi title ID.
+        tid: u32,
+        /// Matching series.
+        candidates: Vec<TmdbSeriesCandidate>,
+    },
+    /// Seasons of a series.
+    Seasons {
+        /// Syoboi title ID.
+        tid: u32,
+        /// Seasons in TMDB order.
+        seasons: Vec<TmdbSeasonCandidate>,
+    },
+    /// The mapping was stored.
+    Applied(TmdbMappingChoice),
+    /// A request failed.
+    Failed {
+        /// Syoboi title ID.
+        tid: u32,
+        /// Error description.
+        error: String,
+    },
+}
+
+/// Channels connecting the picker to its background worker.
+#[derive(Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TmdbPickerChannels {
+    /// Requests for the worker.
+    pub requests: tokio::sync::mpsc::UnboundedSender<TmdbPickerRequest>,
+    /// Results from the worker.
+    pub messages: mpsc::Receiver<TmdbPickerMessage>,
+}
+
+/// Current list shown by the picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerStep {
+    /// Series search results.
+    Series,
+    /// Seasons of the chosen series.
+    Seasons,
+}
+
+/// State of the open picker.
+#[derive(Debug, Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct TmdbPickerState {
+    /// Syoboi title ID being mapped.
+    pub tid: u32,
+    /// Search query.
+    pub query: String,
+    /// Whether the title is mapped to a movie (no season step).
+    pub movie: bool,
+    /// Current list.
+    pub step: PickerStep,
+    /// Whether a request is in flight.
+    pub loading: bool,
+    /// Series search results.
+    pub series: Vec<TmdbSeriesCandidate>,
+    /// Seasons of the chosen series.
+    pub seasons: Vec<TmdbSeasonCandidate>,
+    /// Chosen series ID (set when moving to the season list).
+    pub series_id: Option<u64>,
+    /// Cursor in the current list.
+    pub cursor: usize,
+    /// Last error, shown instead of the list.
+    pub error: Option<String>,
+}
+
+impl TmdbPickerState {
+    /// Creates a picker that is waiting for search results.
+    #[must_use]
+    pub const fn new(tid: u32, query: String, movie: bool) -> Self {
+        Self {
+            tid,
+            query,
+            movie,
+            step: PickerStep::Series,
+            loading: true,
+            series: Vec::new(),
+            seasons: Vec::new(),
+            series_id: None,
+            cursor: 0,
+            error: None,
+        }
+    }
+
+    /// Returns the number of entries in the current list.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        match self.step {
+            PickerStep::Series => self.series.len(),
+            PickerStep::Seasons => self.seasons.len(),
+        }
+    }
+
+    /// Returns whether the current list is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves the cursor up.
+    pub const fn up(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Moves the cursor down.
+    pub fn down(&mut self) {
+        self.cursor = self
+            .cursor
+            .saturating_add(1)
+            .min(self.len().saturating_sub(1));
+    }
+
+    /// Confirms the entry under the cursor and returns the request to send.
+    ///
+    /// On the series list this asks for the seasons; on the season list (or
+    /// a series without seasons) it applies the mapping. Movies are applied
+    /// directly from the search results.
+    pub fn select(&mut self) -> Option<TmdbPickerRequest> {
+        if self.loading {
+            return None;
+        }
+        let request = match self.step {
+            PickerStep::Series if self.movie => TmdbPickerRequest::Apply(TmdbMappingChoice {
+                tid: self.tid,
+                movie: true,
+                series_id: self.series.get(self.cursor)?.id,
+                season_number: None,
+                season_id: None,
+            }),
+            PickerStep::Series => {
+                let series_id = self.series.get(self.cursor)?.id;
+                self.series_id = Some(series_id);
+                self.step = PickerStep::Seasons;
+                self.seasons.clear();
+                self.cursor = 0;
+                TmdbPickerRequest::Seasons {
+                    tid: self.tid,
+                    series_id,
+                }
+            }
+            PickerStep::Seasons => {
+                let series_id = self.series_id?;
+                let season = self.seasons.get(self.cursor);
+                if season.is_none() && !self.seasons.is_empty() {
+                    return None;
+                }
+                TmdbPickerRequest::Apply(TmdbMappingChoice {
+                    tid: self.tid,
+                    movie: false,
+                    series_id,
+                    season_number: season.map(|s| s.season_number),
+                    season_id: season.map(|s| s.id),
+                })
+            }
+        };
+        self.loading = true;
+        self.error = None;
+        Some(request)
+    }
+
+    /// Goes back from the season list to the series list.
+    ///
+    /// Returns `false` when already on the series list (the caller closes
+    /// the picker).
+    pub fn back(&mut self) -> bool {
+        match self.step {
+            PickerStep::Series => false,
+            PickerStep::Seasons => {
+                let previous = self.series_id.take();
+                self.step = PickerStep::Series;
+                self.loading = false;
+                self.error = None;
+                self.cursor = self
+                    .series
+                    .iter()
+                    .position(|s| Some(s.id) == previous)
+                    .unwrap_or(0);
+                true
+            }
+        }
+    }
+
+    /// Applies a worker result. Results for other titles are ignored.
+    ///
+    /// Returns the stored mapping when the worker confirms it.
+    pub fn apply_message(&mut self, message: TmdbPickerMessage) -> Option<TmdbMappingChoice> {
+        match message {
+            TmdbPickerMessage::Series { tid, candidates } if tid == self.tid => {
+                self.series = candidates;
+                self.cursor = 0;
+                self.loading = false;
+            }
+            TmdbPickerMessage::Seasons { tid, seasons }
+                if tid == self.tid && self.step == PickerStep::Seasons =>
+            {
+                self.seasons = seasons;
+                self.cursor = 0;
+                self.loading = false;
+            }
+            TmdbPickerMessage::Applied(choice) if choice.tid == self.tid => {
+                self.loading = false;
+                return Some(choice);
+            }
+            TmdbPickerMessage::Failed { tid, error } if tid == self.tid => {
+                self.error = Some(error);
+                self.loading = false;
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+
+    use super::*;
+
+    fn series(id: u64, name: &str) -> TmdbSeriesCandidate {
+        TmdbSeriesCandidate {
+            id,
+            name: name.to_owned(),
+            original_name: name.to_owned(),
+            first_air_date: Some(String::from("2022-04-09")),
+        }
+    }
+
+    fn season(id: u64, season_number: u32) -> TmdbSeasonCandidate {
+        TmdbSeasonCandidate {
+            id,
+            season_number,
+            name: format!("Season {season_number}"),
+            episode_count: 12,
+            air_date: None,
+        }
+    }
+
+    #[test]
+    fn test_picker_walks_series_then_season_to_apply() {
+        // Arrange
+        let mut picker = TmdbPickerState::new(6309, String::from("SPY×FAMILY"), false);
+        assert!(picker.select().is_none(), "no selection while loading");
+        picker.apply_message(TmdbPickerMessage::Series {
+            tid: 6309,
+            candidates: vec![series(1, "Other"), series(120_089, "SPY×FAMILY")],
+        });
+
+        // Act: pick the second series, then season 2
+        picker.down();
+        let seasons_request = picker.select().unwrap();
+        picker.apply_message(TmdbPickerMessage::Seasons {
+            tid: 6309,
+            seasons: vec![season(10, 1), season(20, 2)],
+        });
+        picker.down();
+        let apply_request = picker.select().unwrap();
+
+        // Assert
+        assert_eq!(
+            seasons_request,
+            TmdbPickerRequest::Seasons {
+                tid: 6309,
+                series_id: 120_089
+            }
+        );
+        let choice = TmdbMappingChoice {
+            tid: 6309,
+            movie: false,
+            series_id: 120_089,
+            season_number: Some(2),
+            season_id: Some(20),
+        };
+        assert_eq!(apply_request, TmdbPickerRequest::Apply(choice));
+        assert_eq!(
+            picker.apply_message(TmdbPickerMessage::Applied(choice)),
+            Some(choice)
+        );
+    }
+
+    #[test]
+    fn test_picker_back_restores_series_cursor_and_ignores_other_titles() {
+        // Arrange
+        let mut picker = TmdbPickerState::new(6309, String::from("q"), false);
+        picker.apply_message(TmdbPickerMessage::Series {
+            tid: 6309,
+            candidates: vec![series(1, "A"), series(2, "B")],
+        });
+        picker.down();
+        picker.select();
+
+        // Act
+        picker.apply_message(TmdbPickerMessage::Failed {
+            tid: 9999,
+            error: String::from("other title"),
+        });
+        let went_back = picker.back();
+
+        // Assert
+        assert!(went_back);
+        assert_eq!(picker.step, PickerStep::Series);
+        assert_eq!(picker.cursor, 1);
+        assert!(picker.error.is_none());
+        assert!(!picker.back());
+    }
+
+    #[test]
+    fn test_picker_applies_series_without_seasons() {
+        // Arrange
+        let mut picker = TmdbPickerState::new(1, String::from("q"), false);
+        picker.apply_message(TmdbPickerMessage::Series {
+            tid: 1,
+            candidates: vec![series(5, "A")],
+        });
+        picker.select();
+        picker.apply_message(TmdbPickerMessage::Seasons {
+            tid: 1,
+            seasons: Vec::new(),
+        });
+
+        // Act
+        let request = picker.select();
+
+        // Assert
+        assert_eq!(
+            request,
+            Some(TmdbPickerRequest::Apply(TmdbMappingChoice {
+                tid: 1,
+                movie: false,
+                series_id: 5,
+                season_number: None,
+                season_id: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_picker_applies_movie_without_season_step() {
+        // Arrange
+        let mut picker = TmdbPickerState::new(6500, String::from("すずめの戸締まり"), true);
+        picker.apply_message(TmdbPickerMessage::Series {
+            tid: 6500,
+            candidates: vec![series(916_224, "すずめの戸締まり")],
+        });
+
+        // Act
+        let request = picker.select();
+
+        // Assert
+        assert_eq!(
+            request,
+            Some(TmdbPickerRequest::Apply(TmdbMappingChoice {
+                tid: 6500,
+                movie: true,
+                series_id: 916_224,
+                season_number: None,
+                season_id: None,
+            }))
+        );
+        assert_eq!(picker.step, PickerStep::Series);
+    }
+}
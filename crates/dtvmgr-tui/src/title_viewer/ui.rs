@@ -1,12 +1,16 @@
 //! TUI rendering logic for the title viewer.
 
+use dtvmgr_api::syoboi::{ProgramFlags, TitleCategory};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph, Row, Table};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table};
 
-use super::state::{ActivePane, InputMode, TitleViewerState, TmdbFilter};
+use super::state::{
+    ActivePane, InputMode, ProgramColumn, ProgramRow, TitleRow, TitleViewerState, TmdbFilter,
+};
+use super::tmdb_picker::{PickerStep, TmdbPickerState};
 use crate::fmt::with_commas;
 
 /// Formats a number with thousands separators (e.g. 169940 -> "169,940").
@@ -43,6 +47,17 @@ pub fn draw(frame: &mut Frame, state: &mut TitleViewerState) -> u16 {
 
     draw_footer(frame, chunks[2], state);
 
+    match state.input_mode {
+        InputMode::Category => draw_category_popup(frame, main_area, state),
+        InputMode::Detail => draw_detail_popup(frame, main_area, state),
+        InputMode::TmdbPicker => {
+            if let Some(picker) = &state.picker {
+                draw_picker_popup(frame, main_area, picker);
+            }
+        }
+        InputMode::Normal | InputMode::Filter => {}
+    }
+
     main_area.height
 }
 
@@ -100,8 +115,11 @@ fn draw_header(frame: &mut Frame, area: Rect, state: &TitleViewerState) {
         TmdbFilter::Unmapped => " [unmapped]",
         TmdbFilter::Mapped => " [mapped]",
     };
+    let cat_tag = state
+        .category_filter
+        .map_or_else(String::new, |cat| format!(" [{cat}]"));
     let tmdb_label = format!(
-        " DB Viewer  TMDB {matched:0>width$}/{total_t:0>width$} ({pct:06.2}%), miss: {miss}{filter_tag} ",
+        " DB Viewer  TMDB {matched:0>width$}/{total_t:0>width$} ({pct:06.2}%), miss: {miss}{filter_tag}{cat_tag} ",
     );
 
     let count = Paragraph::new(vec![Line::from(line1), Line::from(line2)])
@@ -109,6 +127,15 @@ fn draw_header(frame: &mut Frame, area: Rect, state: &TitleViewerState) {
     frame.render_widget(count, header_chunks[1]);
 }
 
+/// Formats the TMDB ID of a title row (movie IDs are prefixed with `m`).
+fn tmdb_id_label(row: &TitleRow) -> String {
+    match (row.tmdb_movie_id, row.tmdb_series_id) {
+        (Some(id), _) => format!("m{id}"),
+        (None, Some(id)) => id.to_string(),
+        (None, None) => String::from("--"),
+    }
+}
+
 /// Draws the title list pane (left).
 fn draw_title_list(frame: &mut Frame, area: Rect, state: &mut TitleViewerState) {
     let border_style = if state.active_pane == ActivePane::Titles {
@@ -119,6 +146,7 @@ fn draw_title_list(frame: &mut Frame, area: Rect, state: &mut TitleViewerState)
 
     let header = Row::new(vec![
         " ",
+        "F",
         "TID",
         "Cat",
         "Title",
@@ -142,7 +170,7 @@ fn draw_title_list(frame: &mut Frame, area: Rect, state: &mut TitleViewerState)
         .filter_map(|&idx| {
             let t = state.titles.get(idx)?;
 
-            let style = if t.tmdb_series_id.is_some() {
+            let style = if t.is_mapped() {
                 Style::default().fg(Color::Green)
             } else {
                 Style::default()
@@ -154,10 +182,17 @@ fn draw_title_list(frame: &mut Frame, area: Rect, state: &mut TitleViewerState)
                 "[ ]"
             };
 
-            let cat_str = t.cat.map_or_else(|| String::from("--"), |c| c.to_string());
-            let tmdb_str = t
-                .tmdb_series_id
-                .map_or_else(|| String::from("--"), |id| id.to_string());
+            let follow = match (state.followed_tids.contains(&t.tid), state.caps.unicode) {
+                (true, true) => "\u{2605}",
+                (true, false) => "*",
+                (false, _) => "",
+            };
+
+            let cat_str = t.cat.map_or_else(
+                || String::from("--"),
+                |c| TitleCategory::from_cat(Some(c)).to_string(),
+            );
+            let tmdb_str = tmdb_id_label(t);
             let season_str = t
                 .tmdb_season_number
                 .map_or_else(|| String::from("--"), |n| n.to_string());
@@ -165,6 +200,7 @@ fn draw_title_list(frame: &mut Frame, area: Rect, state: &mut TitleViewerState)
             Some(
                 Row::new(vec![
                     String::from(check),
+                    String::from(follow),
                     t.tid.to_string(),
                     cat_str,
                     t.title.clone(),
@@ -183,8 +219,9 @@ fn draw_title_list(frame: &mut Frame, area: Rect, state: &mut TitleViewerState)
 
     let widths = [
         Constraint::Length(3),
+        Constraint::Length(1),
         Constraint::Length(7),
-        Constraint::Length(4),
+        Constraint::Length(10),
         Constraint::Min(20),
         Constraint::Length(6),
         Constraint::Length(10),
@@ -224,9 +261,21 @@ fn draw_program_detail(frame: &mut Frame, area: Rect, state: &mut TitleViewerSta
         |t| format!(" {} (TID:{}) ", t.title, t.tid),
     );
 
-    let header = Row::new(vec![
-        "PID", "#", "StTime", "Min", "Channel", "Flag", "SubTitle",
-    ])
+    let columns = state.visible_columns();
+    let sorted = state.program_sort.column();
+    let arrow = match (state.program_sort_desc, state.caps.unicode) {
+        (false, true) => "\u{25b2}",
+        (true, true) => "\u{25bc}",
+        (false, false) => "^",
+        (true, false) => "v",
+    };
+    let header = Row::new(columns.iter().map(|&c| {
+        if c == sorted {
+            format!("{}{arrow}", c.header())
+        } else {
+            String::from(c.header())
+        }
+    }))
     .style(
         Style::default()
             .fg(Color::Yellow)
@@ -238,28 +287,15 @@ fn draw_program_detail(frame: &mut Frame, area: Rect, state: &mut TitleViewerSta
     let rows: Vec<Row> = programs
         .iter()
         .map(|p| {
-            Row::new(vec![
-                p.pid.to_string(),
-                p.count.map_or_else(|| String::from("-"), |c| c.to_string()),
-                p.st_time.clone(),
-                p.duration_min
-                    .map_or_else(|| String::from("-"), |m| m.to_string()),
-                p.ch_name.clone(),
-                flag_label(p.flag),
-                p.sub_title.clone().unwrap_or_default(),
-            ])
+            Row::new(
+                columns
+                    .iter()
+                    .map(|&c| program_cell(p, c, state.caps.unicode)),
+            )
         })
         .collect();
 
-    let widths = [
-        Constraint::Length(10), // PID
-        Constraint::Length(5),  // #
-        Constraint::Length(20), // StTime
-        Constraint::Length(5),  // Min
-        Constraint::Length(15), // Channel
-        Constraint::Length(8),  // Flag
-        Constraint::Min(20),    // SubTitle
-    ];
+    let widths: Vec<Constraint> = columns.iter().map(|&c| column_width(c)).collect();
 
     let table = Table::new(rows, widths)
         .header(header)
@@ -278,36 +314,243 @@ fn draw_program_detail(frame: &mut Frame, area: Rect, state: &mut TitleViewerSta
     frame.render_stateful_widget(table, area, &mut state.program_table_state);
 }
 
-/// Builds a human-readable flag label from a bitmask.
-fn flag_label(flag: Option<u32>) -> String {
-    let Some(f) = flag else {
-        return String::new();
+/// Returns the text of column `column` for program `p`.
+fn program_cell(p: &ProgramRow, column: ProgramColumn, unicode: bool) -> String {
+    match column {
+        ProgramColumn::Pid => p.pid.to_string(),
+        ProgramColumn::Count => p.count.map_or_else(|| String::from("-"), |c| c.to_string()),
+        ProgramColumn::StTime => p.st_time.clone(),
+        ProgramColumn::Min => p
+            .duration_min
+            .map_or_else(|| String::from("-"), |m| m.to_string()),
+        ProgramColumn::Channel => p.ch_name.clone(),
+        ProgramColumn::Flag => flag_label(ProgramFlags::from_flag(p.flag), unicode),
+        ProgramColumn::SubTitle => p.sub_title.clone().unwrap_or_default(),
+        ProgramColumn::Tags => tags_label(p, unicode),
+    }
+}
+
+/// Returns the width constraint of a program column.
+const fn column_width(column: ProgramColumn) -> Constraint {
+    match column {
+        ProgramColumn::Pid => Constraint::Length(10),
+        ProgramColumn::Count | ProgramColumn::Min => Constraint::Length(5),
+        ProgramColumn::StTime => Constraint::Length(20),
+        ProgramColumn::Channel => Constraint::Length(15),
+        ProgramColumn::Flag => Constraint::Length(8),
+        ProgramColumn::SubTitle => Constraint::Min(20),
+        ProgramColumn::Tags => Constraint::Max(16),
+    }
+}
+
+/// Draws the category filter popup centered over `area`.
+fn draw_category_popup(frame: &mut Frame, area: Rect, state: &TitleViewerState) {
+    let options = state.category_options();
+    let items: Vec<ListItem> = options
+        .iter()
+        .map(|(cat, count)| {
+            let label = cat.map_or_else(|| String::from("all"), |c| c.to_string());
+            let marker = if *cat == state.category_filter {
+                "*"
+            } else {
+                " "
+            };
+            ListItem::new(format!("{marker} {label:<14} {:>7}", fmt_num(*count)))
+        })
+        .collect();
+
+    let height = u16::try_from(items.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2)
+        .min(area.height);
+    let width = 30_u16.min(area.width);
+    let popup = Rect {
+        x: area.x.saturating_add(area.width.saturating_sub(width) / 2),
+        y: area
+            .y
+            .saturating_add(area.height.saturating_sub(height) / 2),
+        width,
+        height,
+    };
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Category "))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut list_state = ListState::default().with_selected(Some(state.category_cursor));
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut list_state);
+}
+
+/// Draws the raw field inspector for the current program centered over `area`.
+fn draw_detail_popup(frame: &mut Frame, area: Rect, state: &TitleViewerState) {
+    let Some(fields) = state.program_detail_fields() else {
+        return;
     };
-    let mut labels = Vec::new();
-    if f & 1 != 0 {
-        labels.push("[注]");
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(name, value)| {
+            Line::from(vec![
+                Span::styled(format!("{name:<20}"), Style::default().fg(Color::DarkGray)),
+                Span::raw(value),
+            ])
+        })
+        .collect();
+
+    let height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2)
+        .min(area.height);
+    let width = 72_u16.min(area.width);
+    let popup = Rect {
+        x: area.x.saturating_add(area.width.saturating_sub(width) / 2),
+        y: area
+            .y
+            .saturating_add(area.height.saturating_sub(height) / 2),
+        width,
+        height,
+    };
+
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Program "));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Builds the picker list entries (or a single status line).
+fn picker_items(picker: &TmdbPickerState) -> Vec<ListItem<'_>> {
+    let status =
+        |text: String, color: Color| vec![ListItem::new(text).style(Style::default().fg(color))];
+    if let Some(error) = &picker.error {
+        return status(error.clone(), Color::Red);
     }
-    if f & 2 != 0 {
-        labels.push("[新]");
+    if picker.loading {
+        return status(String::from("Loading..."), Color::DarkGray);
     }
-    if f & 4 != 0 {
-        labels.push("[終]");
+    if picker.is_empty() {
+        let text = match picker.step {
+            PickerStep::Series if picker.movie => "No matching movies",
+            PickerStep::Series => "No matching series",
+            PickerStep::Seasons => "No seasons (Enter: map the series only)",
+        };
+        return status(String::from(text), Color::DarkGray);
     }
-    if f & 8 != 0 {
-        labels.push("[再]");
+    match picker.step {
+        PickerStep::Series => picker
+            .series
+            .iter()
+            .map(|s| {
+                let year = s.first_air_date.as_deref().and_then(|d| d.get(..4));
+                ListItem::new(format!(
+                    "{:>7}  {} ({})  {}",
+                    s.id,
+                    s.name,
+                    year.unwrap_or("-"),
+                    s.original_name
+                ))
+            })
+            .collect(),
+        PickerStep::Seasons => picker
+            .seasons
+            .iter()
+            .map(|s| {
+                ListItem::new(format!(
+                    "S{:02}  {}  {} eps  {}",
+                    s.season_number,
+                    s.name,
+                    s.episode_count,
+                    s.air_date.as_deref().unwrap_or("-")
+                ))
+            })
+            .collect(),
+    }
+}
+
+/// Draws the TMDB series/season picker centered over `area`.
+fn draw_picker_popup(frame: &mut Frame, area: Rect, picker: &TmdbPickerState) {
+    let items = picker_items(picker);
+    let selectable = picker.error.is_none() && !picker.loading && !picker.is_empty();
+
+    let height = u16::try_from(items.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2)
+        .min(area.height);
+    let width = 72_u16.min(area.width);
+    let popup = Rect {
+        x: area.x.saturating_add(area.width.saturating_sub(width) / 2),
+        y: area
+            .y
+            .saturating_add(area.height.saturating_sub(height) / 2),
+        width,
+        height,
+    };
+
+    let title = match picker.step {
+        PickerStep::Series if picker.movie => format!(" TMDB movie search: {} ", picker.query),
+        PickerStep::Series => format!(" TMDB search: {} ", picker.query),
+        PickerStep::Seasons => format!(" TMDB seasons: {} ", picker.series_id.unwrap_or(0)),
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut list_state = ListState::default().with_selected(selectable.then_some(picker.cursor));
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut list_state);
+}
+
+/// Flags with their unicode and ASCII labels.
+const FLAG_LABELS: [(ProgramFlags, &str, &str); 4] = [
+    (ProgramFlags::CAUTION, "[注]", "[!]"),
+    (ProgramFlags::NEW, "[新]", "[N]"),
+    (ProgramFlags::FINAL, "[終]", "[F]"),
+    (ProgramFlags::RERUN, "[再]", "[R]"),
+];
+
+/// Builds a human-readable flag label.
+///
+/// Uses ASCII labels when `unicode` is `false`.
+fn flag_label(flags: ProgramFlags, unicode: bool) -> String {
+    FLAG_LABELS
+        .iter()
+        .filter(|(flag, _, _)| flags.contains(*flag))
+        .map(|&(_, label, ascii)| if unicode { label } else { ascii })
+        .collect()
+}
+
+/// Builds the tags cell of a program row, prefixed with a note marker
+/// when the program has a note.
+fn tags_label(row: &ProgramRow, unicode: bool) -> String {
+    let tags = row.tags.join(",");
+    match (&row.note, unicode) {
+        (None, _) => tags,
+        (Some(_), true) => format!("✎{tags}"),
+        (Some(_), false) => format!("*{tags}"),
     }
-    labels.concat()
 }
 
 /// Draws the footer with key hints.
 fn draw_footer(frame: &mut Frame, area: Rect, state: &TitleViewerState) {
     let help_text = match (&state.input_mode, &state.active_pane) {
         (InputMode::Filter, _) => Line::from("Type to filter | Esc: cancel | Enter: apply"),
+        (InputMode::Category, _) => {
+            Line::from("\u{2191}\u{2193}/j/k: move  Enter: apply  Esc: close")
+        }
+        (InputMode::Detail, _) => Line::from("Esc/Enter: close"),
+        (InputMode::TmdbPicker, _) => {
+            Line::from("\u{2191}\u{2193}/j/k: move  Enter: select  Esc: back  q: close")
+        }
         (InputMode::Normal, ActivePane::Titles) => Line::from(vec![Span::raw(
-            "\u{2190}\u{2192}: pane  \u{2191}\u{2193}/j/k: move  PgUp/PgDn: page  /: filter  t: tmdb  p: programs  Space: select  o: open  q: quit",
+            "\u{2190}\u{2192}: pane  \u{2191}\u{2193}/j/k: move  PgUp/PgDn: page  /: filter  t: tmdb  c: category  p: programs  Space: select  f: follow  m: map  o: open  q: quit",
         )]),
         (InputMode::Normal, ActivePane::Programs) => Line::from(vec![Span::raw(
-            "\u{2190}\u{2192}: pane  \u{2191}\u{2193}/j/k: move  PgUp/PgDn: page  t: tmdb  p: programs  o: open  q: quit",
+            "\u{2190}\u{2192}: pane  \u{2191}\u{2193}/j/k: move  PgUp/PgDn: page  Enter: detail  s/S: sort/reverse  1-8: columns  t: tmdb  c: category  p: programs  o: open  q: quit",
         )]),
     };
 
@@ -325,7 +568,7 @@ mod tests {
     use ratatui::backend::TestBackend;
     use ratatui::buffer::Buffer;
 
-    use super::super::state::{ProgramRow, TitleRow, TitleViewerState, ViewerStats};
+    use super::super::state::{TitleRow, TitleViewerState, ViewerStats};
     use super::*;
 
     /// Converts a ratatui Buffer into a single string with newlines per row.
@@ -360,6 +603,8 @@ mod tests {
                 first_year: Some(2022),
                 tmdb_series_id: Some(12345),
                 tmdb_season_number: Some(1),
+                tmdb_movie_id: None,
+                movie: false,
                 program_count: 2,
                 keywords: vec![String::from("spy")],
                 tmdb_query: String::from("SPYxFAMILY"),
@@ -371,6 +616,8 @@ mod tests {
                 first_year: Some(2022),
                 tmdb_series_id: None,
                 tmdb_season_number: None,
+                tmdb_movie_id: None,
+                movie: false,
                 program_count: 1,
                 keywords: Vec::new(),
                 tmdb_query: String::from("Bocchi the Rock!"),
@@ -388,6 +635,8 @@ mod tests {
                 flag: Some(2),
                 duration_min: Some(30),
                 sub_title: Some(String::from("Ep1")),
+                tags: Vec::new(),
+                note: None,
             }],
         );
 
@@ -398,28 +647,64 @@ mod tests {
 
     #[test]
     fn flag_label_none_returns_empty() {
-        assert_eq!(flag_label(None), "");
+        assert_eq!(flag_label(ProgramFlags::from_flag(None), true), "");
     }
 
     #[test]
     fn flag_label_zero_returns_empty() {
-        assert_eq!(flag_label(Some(0)), "");
+        assert_eq!(flag_label(ProgramFlags::from_flag(Some(0)), true), "");
     }
 
     #[test]
     fn flag_label_single_bits() {
-        assert_eq!(flag_label(Some(1)), "[注]");
-        assert_eq!(flag_label(Some(2)), "[新]");
-        assert_eq!(flag_label(Some(4)), "[終]");
-        assert_eq!(flag_label(Some(8)), "[再]");
+        assert_eq!(flag_label(ProgramFlags::from_flag(Some(1)), true), "[注]");
+        assert_eq!(flag_label(ProgramFlags::from_flag(Some(2)), true), "[新]");
+        assert_eq!(flag_label(ProgramFlags::from_flag(Some(4)), true), "[終]");
+        assert_eq!(flag_label(ProgramFlags::from_flag(Some(8)), true), "[再]");
     }
 
     #[test]
     fn flag_label_combined_bits() {
         // 1 + 2 = 3 -> "[注][新]"
-        assert_eq!(flag_label(Some(3)), "[注][新]");
+        assert_eq!(
+            flag_label(ProgramFlags::from_flag(Some(3)), true),
+            "[注][新]"
+        );
         // 1 + 4 + 8 = 13 -> "[注][終][再]"
-        assert_eq!(flag_label(Some(13)), "[注][終][再]");
+        assert_eq!(
+            flag_label(ProgramFlags::from_flag(Some(13)), true),
+            "[注][終][再]"
+        );
+    }
+
+    #[test]
+    fn flag_label_ascii_fallback() {
+        assert_eq!(
+            flag_label(ProgramFlags::from_flag(Some(15)), false),
+            "[!][N][F][R]"
+        );
+    }
+
+    #[test]
+    fn tags_label_marks_notes() {
+        // Arrange
+        let mut row = ProgramRow {
+            pid: 100,
+            count: Some(1),
+            st_time: String::from("2022-04-09 23:00"),
+            ch_name: String::from("TX"),
+            flag: None,
+            duration_min: Some(30),
+            sub_title: None,
+            tags: vec![String::from("recorded"), String::from("skip")],
+            note: None,
+        };
+
+        // Act & Assert
+        assert_eq!(tags_label(&row, true), "recorded,skip");
+        row.note = Some(String::from("retry"));
+        assert_eq!(tags_label(&row, true), "✎recorded,skip");
+        assert_eq!(tags_label(&row, false), "*recorded,skip");
     }
 
     #[test]
@@ -513,6 +798,31 @@ mod tests {
         assert!(content.contains("SubTitle"));
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn draw_programs_pane_hides_columns_and_marks_sort() {
+        // Arrange
+        let backend = TestBackend::new(180, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut state = make_state_with_titles();
+        state.show_programs = true;
+        state.set_hidden_columns([ProgramColumn::SubTitle, ProgramColumn::Pid]);
+        state.cycle_program_sort();
+
+        // Act
+        terminal
+            .draw(|frame| {
+                draw(frame, &mut state);
+            })
+            .unwrap();
+
+        // Assert
+        let content = buffer_to_string(terminal.backend().buffer());
+        assert!(!content.contains("SubTitle"));
+        assert!(!content.contains("PID"));
+        assert!(content.contains("Channel\u{25b2}"));
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn draw_without_programs_pane_no_split() {
@@ -557,4 +867,29 @@ mod tests {
         let content = buffer_to_string(buf);
         assert!(content.contains("Type to filter"));
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn draw_category_popup_lists_categories() {
+        // Arrange
+        let backend = TestBackend::new(140, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut state = make_state_with_titles();
+        state.open_category_popup();
+
+        // Act
+        terminal
+            .draw(|frame| {
+                draw(frame, &mut state);
+            })
+            .unwrap();
+
+        // Assert
+        let buf = terminal.backend().buffer();
+        let content = buffer_to_string(buf);
+        assert!(content.contains("Category"));
+        assert!(content.contains("* all"));
+        assert!(content.contains("anime"));
+        assert!(content.contains("Enter: apply"));
+    }
 }
@@ -6,7 +6,7 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
 
-use super::state::{ActivePane, ChannelSelectorState, InputMode};
+use super::state::{ActivePane, ChannelEntry, ChannelSelectorState, InputMode, RefreshStatus};
 
 /// Draws the channel selector UI.
 #[allow(clippy::indexing_slicing)]
@@ -50,8 +50,18 @@ fn draw_header(frame: &mut Frame, area: Rect, state: &ChannelSelectorState) {
         .block(Block::default().borders(Borders::ALL).title(" Filter: / "));
     frame.render_widget(filter, header_chunks[0]);
 
+    let refresh_text = match &state.refresh {
+        None => String::new(),
+        Some(RefreshStatus::Pending) => String::from("  (refreshing...)"),
+        Some(RefreshStatus::Applied) => format!(
+            "  (+{} / -{})",
+            state.new_channels.len(),
+            state.removed_channels.len()
+        ),
+        Some(RefreshStatus::Failed(_)) => String::from("  (refresh failed)"),
+    };
     let count_text = format!(
-        "Selected: {} / {}",
+        "Selected: {} / {}{refresh_text}",
         state.selected_count(),
         state.total_channels()
     );
@@ -157,20 +167,41 @@ fn draw_channels_pane(frame: &mut Frame, area: Rect, state: &ChannelSelectorStat
                 "[ ]"
             };
 
+            let removed = state.removed_channels.contains(&ch.ch_id);
             let style = if i == state.channel_cursor && is_active {
                 Style::default()
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD)
+            } else if removed {
+                Style::default().fg(Color::DarkGray)
             } else if state.selected.contains(&ch.ch_id) {
                 Style::default().fg(Color::Green)
             } else {
                 Style::default()
             };
 
-            Some(ListItem::new(Line::from(vec![Span::styled(
+            let mut spans = vec![Span::styled(
                 format!(" {} {:>3}  {}", checkbox, ch.ch_id, ch.ch_name),
                 style,
-            )])))
+            )];
+            if state.new_channels.contains(&ch.ch_id) {
+                spans.push(Span::styled(
+                    " NEW",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            } else if removed {
+                spans.push(Span::styled(
+                    " (removed)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if let Some(info) = program_info(ch) {
+                spans.push(Span::styled(info, Style::default().fg(Color::DarkGray)));
+            }
+
+            Some(ListItem::new(Line::from(spans)))
         })
         .collect();
 
@@ -194,6 +225,20 @@ fn draw_channels_pane(frame: &mut Frame, area: Rect, state: &ChannelSelectorStat
     frame.render_widget(list, area);
 }
 
+/// Formats the cached program count and last synced date of a channel,
+/// or `None` when nothing is cached for it.
+fn program_info(ch: &ChannelEntry) -> Option<String> {
+    if ch.program_count == 0 {
+        return None;
+    }
+    let date = ch
+        .last_update
+        .as_deref()
+        .and_then(|ts| ts.get(..10))
+        .map_or_else(String::new, |d| format!(", updated {d}"));
+    Some(format!("  ({} programs{date})", ch.program_count))
+}
+
 /// Draws the footer with key hints.
 fn draw_footer(frame: &mut Frame, area: Rect, state: &ChannelSelectorState) {
     let help_text = if state.input_mode == InputMode::Filter {
@@ -208,7 +253,7 @@ fn draw_footer(frame: &mut Frame, area: Rect, state: &ChannelSelectorState) {
 
 #[cfg(test)]
 mod tests {
-    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
 
     use std::collections::BTreeSet;
 
@@ -216,7 +261,7 @@ mod tests {
     use ratatui::backend::TestBackend;
 
     use super::*;
-    use crate::state::{ChannelEntry, ChannelGroup, ChannelSelectorState};
+    use crate::state::{ChannelGroup, ChannelSelectorState};
 
     /// Converts terminal buffer to a single string for assertion.
     fn buffer_to_string(terminal: &Terminal<TestBackend>) -> String {
@@ -240,10 +285,12 @@ mod tests {
                     ChannelEntry {
                         ch_id: 1,
                         ch_name: String::from("NHK"),
+                        ..ChannelEntry::default()
                     },
                     ChannelEntry {
                         ch_id: 2,
                         ch_name: String::from("TBS"),
+                        ..ChannelEntry::default()
                     },
                 ],
             },
@@ -253,6 +300,7 @@ mod tests {
                 channels: vec![ChannelEntry {
                     ch_id: 10,
                     ch_name: String::from("BS11"),
+                    ..ChannelEntry::default()
                 }],
             },
         ]
@@ -464,4 +512,50 @@ mod tests {
         assert!(output.contains("NHK"));
         assert!(output.contains("Filter"));
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn draw_refresh_badges() {
+        // Arrange: channel 2 removed, channel 3 added
+        let mut state = ChannelSelectorState::new(make_groups(), BTreeSet::new());
+        let mut fresh = make_groups();
+        fresh[0].channels[1] = ChannelEntry {
+            ch_id: 3,
+            ch_name: String::from("TVA"),
+            ..ChannelEntry::default()
+        };
+        state.apply_refresh(fresh);
+        let backend = TestBackend::new(100, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        // Act
+        terminal.draw(|f| draw(f, &state)).unwrap();
+
+        // Assert
+        let output = buffer_to_string(&terminal);
+        assert!(output.contains("TVA NEW"));
+        assert!(output.contains("TBS (removed)"));
+        assert!(output.contains("(+1 / -1)"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn draw_channel_program_info() {
+        // Arrange: NHK has cached programs, TBS has none
+        let mut groups = make_groups();
+        groups[0].channels[0].program_count = 42;
+        groups[0].channels[0].last_update = Some(String::from("2025-01-05 12:34:56"));
+        let mut state = ChannelSelectorState::new(groups, BTreeSet::new());
+        state.switch_pane();
+        let backend = TestBackend::new(100, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+
+        // Act
+        terminal.draw(|f| draw(f, &state)).unwrap();
+
+        // Assert
+        let output = buffer_to_string(&terminal);
+        assert!(output.contains("NHK  (42 programs, updated 2025-01-05)"));
+        assert!(!output.contains("TBS  ("));
+    }
 }